@@ -0,0 +1,21 @@
+-- Migration: Audit Hash Chain
+-- Created: 2026-10-18
+-- Description: Tamper-evident audit log (per-tenant hash chain with signed checkpoints)
+
+-- ============================================================================
+-- CHAIN COLUMNS
+-- ============================================================================
+
+ALTER TABLE audit_events
+    ADD COLUMN chain_sequence BIGINT,
+    ADD COLUMN previous_hash VARCHAR(64),
+    ADD COLUMN hash VARCHAR(64);
+
+-- One event per chain position per tenant
+CREATE UNIQUE INDEX idx_audit_events_tenant_chain
+    ON audit_events(tenant_id, chain_sequence)
+    WHERE chain_sequence IS NOT NULL;
+
+COMMENT ON COLUMN audit_events.chain_sequence IS 'Position in the tenant hash chain (1-based)';
+COMMENT ON COLUMN audit_events.previous_hash IS 'SHA-256 hash of the preceding event in the tenant chain';
+COMMENT ON COLUMN audit_events.hash IS 'SHA-256 over this event content and previous_hash';
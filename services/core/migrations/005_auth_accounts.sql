@@ -0,0 +1,46 @@
+-- Migration: Auth Accounts
+-- Created: 2026-10-18
+-- Description: Persistent users and API keys for AuthManager
+
+-- ============================================================================
+-- USERS TABLE
+-- ============================================================================
+
+CREATE TABLE users (
+    id UUID PRIMARY KEY,
+    username VARCHAR(255) NOT NULL,
+    email VARCHAR(255) NOT NULL,
+    password_hash TEXT NOT NULL,
+    role VARCHAR(50) NOT NULL,
+    tenant_id VARCHAR(255) NOT NULL,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT users_username_unique UNIQUE (username)
+);
+
+CREATE INDEX idx_users_tenant ON users(tenant_id);
+
+-- ============================================================================
+-- API KEYS TABLE
+-- ============================================================================
+
+CREATE TABLE api_keys (
+    id UUID PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    tenant_id VARCHAR(255) NOT NULL,
+    role VARCHAR(50) NOT NULL,
+    key_hash VARCHAR(128) NOT NULL,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMP WITH TIME ZONE,
+    last_used TIMESTAMP WITH TIME ZONE,
+
+    CONSTRAINT api_keys_key_hash_unique UNIQUE (key_hash)
+);
+
+CREATE INDEX idx_api_keys_tenant ON api_keys(tenant_id);
+
+COMMENT ON COLUMN users.password_hash IS 'Argon2 PHC string';
+COMMENT ON COLUMN api_keys.key_hash IS 'SHA-256 of the plaintext key (hex); the key itself is never stored';
+COMMENT ON COLUMN api_keys.last_used IS 'Updated asynchronously, at most once per minute per key';
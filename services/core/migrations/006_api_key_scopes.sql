@@ -0,0 +1,9 @@
+-- Migration: API Key Scopes
+-- Created: 2026-10-18
+-- Description: Fine-grained restrictions on API keys (event namespaces,
+-- entity prefixes, operations, client networks, per-key rate limit)
+
+ALTER TABLE api_keys
+    ADD COLUMN scopes JSONB NOT NULL DEFAULT '{}'::jsonb;
+
+COMMENT ON COLUMN api_keys.scopes IS 'ApiKeyScopes as JSON; empty lists mean unrestricted';
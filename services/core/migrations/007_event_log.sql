@@ -0,0 +1,100 @@
+-- Migration: Event Log
+-- Created: 2026-10-18
+-- Description: Globally ordered event log for the Postgres storage engine.
+-- Events are range-partitioned by month, indexed like the in-memory
+-- EventIndex plus GIN indexes for payload filters, and announced on the
+-- `allsource_events` channel so several API nodes can share one database.
+
+-- Global position, assigned in commit order (writers hold an advisory
+-- lock from nextval() until commit)
+CREATE SEQUENCE IF NOT EXISTS event_log_position_seq;
+
+-- Per-entity stream heads; the row lock serializes appends to a stream,
+-- keeping versions gapless
+CREATE TABLE IF NOT EXISTS event_log_streams (
+    entity_id VARCHAR(255) PRIMARY KEY,
+    tenant_id VARCHAR(255) NOT NULL,
+    partition_id INTEGER NOT NULL,
+    version BIGINT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+
+    CHECK (version > 0),
+    CHECK (partition_id >= 0 AND partition_id < 32)
+);
+
+CREATE INDEX IF NOT EXISTS idx_event_log_streams_partition ON event_log_streams(partition_id);
+CREATE INDEX IF NOT EXISTS idx_event_log_streams_tenant ON event_log_streams(tenant_id);
+
+-- Events, one partition per calendar month (UTC)
+CREATE TABLE IF NOT EXISTS event_log (
+    position BIGINT NOT NULL,
+    id UUID NOT NULL,
+    tenant_id VARCHAR(255) NOT NULL,
+    event_type VARCHAR(255) NOT NULL,
+    entity_id VARCHAR(255) NOT NULL,
+    version BIGINT NOT NULL,
+    payload JSONB NOT NULL,
+    metadata JSONB,
+    timestamp TIMESTAMP WITH TIME ZONE NOT NULL,
+
+    -- The partition key must be part of every unique constraint
+    PRIMARY KEY (position, timestamp),
+    UNIQUE (id, timestamp),
+
+    CHECK (version > 0)
+) PARTITION BY RANGE (timestamp);
+
+-- Same lookups as EventIndex: by entity, by type, by ID and by time
+CREATE INDEX IF NOT EXISTS idx_event_log_entity ON event_log(entity_id, version);
+CREATE INDEX IF NOT EXISTS idx_event_log_type ON event_log(event_type, position);
+CREATE INDEX IF NOT EXISTS idx_event_log_id ON event_log(id);
+CREATE INDEX IF NOT EXISTS idx_event_log_timestamp ON event_log(timestamp);
+CREATE INDEX IF NOT EXISTS idx_event_log_tenant ON event_log(tenant_id, timestamp);
+
+-- Payload and metadata containment filters (`payload @> '{...}'`)
+CREATE INDEX IF NOT EXISTS idx_event_log_payload ON event_log USING GIN (payload jsonb_path_ops);
+CREATE INDEX IF NOT EXISTS idx_event_log_metadata ON event_log USING GIN (metadata jsonb_path_ops);
+
+-- Create the monthly partition holding `p_timestamp` if it does not exist.
+-- Returns the partition's table name.
+CREATE OR REPLACE FUNCTION event_log_ensure_partition(p_timestamp TIMESTAMPTZ)
+RETURNS TEXT AS $$
+DECLARE
+    v_start TIMESTAMPTZ := date_trunc('month', p_timestamp AT TIME ZONE 'UTC') AT TIME ZONE 'UTC';
+    v_end TIMESTAMPTZ := v_start + INTERVAL '1 month';
+    v_name TEXT := 'event_log_' || to_char(v_start AT TIME ZONE 'UTC', 'YYYY_MM');
+BEGIN
+    IF to_regclass(v_name) IS NULL THEN
+        EXECUTE format(
+            'CREATE TABLE IF NOT EXISTS %I PARTITION OF event_log FOR VALUES FROM (%L) TO (%L)',
+            v_name, v_start, v_end
+        );
+    END IF;
+    RETURN v_name;
+EXCEPTION
+    -- Another node created it concurrently
+    WHEN duplicate_table OR unique_violation THEN
+        RETURN v_name;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Announce each committed event's position to listening nodes
+CREATE OR REPLACE FUNCTION event_log_notify()
+RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM pg_notify('allsource_events', NEW.position::text);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS event_log_notify ON event_log;
+CREATE TRIGGER event_log_notify
+AFTER INSERT ON event_log
+FOR EACH ROW
+EXECUTE FUNCTION event_log_notify();
+
+COMMENT ON TABLE event_log IS 'Globally ordered events, range-partitioned by month';
+COMMENT ON COLUMN event_log.position IS 'Global position from event_log_position_seq, increasing in commit order';
+COMMENT ON COLUMN event_log.version IS 'Gapless version within the entity stream';
+COMMENT ON TABLE event_log_streams IS 'Stream heads for event_log; row locks serialize appends per entity';
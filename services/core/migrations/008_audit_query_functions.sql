@@ -0,0 +1,90 @@
+-- Migration: Audit Query Functions
+-- Created: 2026-10-18
+-- Description: Recreate get_audit_events/get_security_events with the
+-- `timestamp` output column quoted; TIMESTAMP is a column-name keyword
+-- and cannot be used bare as a RETURNS TABLE column
+
+-- Function: Get audit events for a tenant with filters
+CREATE OR REPLACE FUNCTION get_audit_events(
+    p_tenant_id VARCHAR,
+    p_start_time TIMESTAMP WITH TIME ZONE DEFAULT NULL,
+    p_end_time TIMESTAMP WITH TIME ZONE DEFAULT NULL,
+    p_action VARCHAR DEFAULT NULL,
+    p_actor_id VARCHAR DEFAULT NULL,
+    p_limit INT DEFAULT 100,
+    p_offset INT DEFAULT 0
+)
+RETURNS TABLE (
+    id UUID,
+    tenant_id VARCHAR,
+    "timestamp" TIMESTAMP WITH TIME ZONE,
+    action VARCHAR,
+    actor_type VARCHAR,
+    actor_id VARCHAR,
+    actor_name VARCHAR,
+    resource_type VARCHAR,
+    resource_id VARCHAR,
+    outcome VARCHAR,
+    ip_address INET,
+    error_message TEXT
+) AS $$
+BEGIN
+    RETURN QUERY
+    SELECT
+        ae.id,
+        ae.tenant_id,
+        ae.timestamp,
+        ae.action,
+        ae.actor_type,
+        ae.actor_id,
+        ae.actor_name,
+        ae.resource_type,
+        ae.resource_id,
+        ae.outcome,
+        ae.ip_address,
+        ae.error_message
+    FROM audit_events ae
+    WHERE ae.tenant_id = p_tenant_id
+      AND (p_start_time IS NULL OR ae.timestamp >= p_start_time)
+      AND (p_end_time IS NULL OR ae.timestamp <= p_end_time)
+      AND (p_action IS NULL OR ae.action = p_action)
+      AND (p_actor_id IS NULL OR ae.actor_id = p_actor_id)
+    ORDER BY ae.timestamp DESC
+    LIMIT p_limit
+    OFFSET p_offset;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Function: Get security events for a tenant
+CREATE OR REPLACE FUNCTION get_security_events(
+    p_tenant_id VARCHAR,
+    p_hours INT DEFAULT 24,
+    p_limit INT DEFAULT 100
+)
+RETURNS TABLE (
+    id UUID,
+    "timestamp" TIMESTAMP WITH TIME ZONE,
+    action VARCHAR,
+    actor_id VARCHAR,
+    actor_name VARCHAR,
+    ip_address INET,
+    error_message TEXT
+) AS $$
+BEGIN
+    RETURN QUERY
+    SELECT
+        ae.id,
+        ae.timestamp,
+        ae.action,
+        ae.actor_id,
+        ae.actor_name,
+        ae.ip_address,
+        ae.error_message
+    FROM audit_events ae
+    WHERE ae.tenant_id = p_tenant_id
+      AND ae.timestamp > NOW() - (p_hours || ' hours')::INTERVAL
+      AND ae.action IN ('login_failed', 'permission_denied', 'rate_limit_exceeded', 'ip_blocked', 'suspicious_activity')
+    ORDER BY ae.timestamp DESC
+    LIMIT p_limit;
+END;
+$$ LANGUAGE plpgsql;
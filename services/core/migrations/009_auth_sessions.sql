@@ -0,0 +1,40 @@
+-- Migration: Auth Sessions
+-- Created: 2026-10-19
+-- Description: Refresh tokens and revocations of AuthManager, so logouts
+-- survive restarts and are seen by every node sharing the database
+
+-- ============================================================================
+-- REFRESH TOKENS TABLE
+-- ============================================================================
+
+CREATE TABLE refresh_tokens (
+    token_hash VARCHAR(128) PRIMARY KEY,
+    session_id VARCHAR(255) NOT NULL,
+    user_id UUID NOT NULL,
+    issued_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    used_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX idx_refresh_tokens_session ON refresh_tokens(session_id);
+CREATE INDEX idx_refresh_tokens_user ON refresh_tokens(user_id);
+CREATE INDEX idx_refresh_tokens_expires ON refresh_tokens(expires_at);
+
+-- ============================================================================
+-- REVOCATIONS TABLE
+-- ============================================================================
+
+CREATE TABLE revocations (
+    kind VARCHAR(16) NOT NULL,
+    id VARCHAR(255) NOT NULL,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+
+    PRIMARY KEY (kind, id),
+    CONSTRAINT revocations_kind_check CHECK (kind IN ('session', 'token'))
+);
+
+CREATE INDEX idx_revocations_expires ON revocations(expires_at);
+
+COMMENT ON COLUMN refresh_tokens.token_hash IS 'SHA-256 of the plaintext token (hex); the token itself is never stored';
+COMMENT ON COLUMN refresh_tokens.used_at IS 'Set when the token is exchanged; presenting it again revokes the session';
+COMMENT ON COLUMN revocations.id IS 'Session ID (kind = session) or access token jti (kind = token)';
@@ -223,6 +223,7 @@ impl AnalyticsEngine {
             since: Some(request.since),
             until: Some(until),
             limit: None,
+            payload_contains: None,
         })?;
 
         if events.is_empty() {
@@ -323,6 +324,7 @@ impl AnalyticsEngine {
             since: request.since,
             until: request.until,
             limit: None,
+            payload_contains: None,
         })?;
 
         if events.is_empty() {
@@ -408,6 +410,7 @@ impl AnalyticsEngine {
             since: request.since,
             until: request.until,
             limit: None,
+            payload_contains: None,
         })?;
 
         let events_b = store.query(crate::application::dto::QueryEventsRequest {
@@ -418,6 +421,7 @@ impl AnalyticsEngine {
             since: request.since,
             until: request.until,
             limit: None,
+            payload_contains: None,
         })?;
 
         // Group events by entity
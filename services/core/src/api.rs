@@ -4,10 +4,15 @@ use crate::analytics::{
 };
 use crate::compaction::CompactionResult;
 use crate::domain::entities::Event;
-use crate::error::Result;
+use crate::error::{AllSourceError, Result};
+use crate::infrastructure::cluster::replication::REPLICATION_TOKEN_HEADER;
 use crate::application::dto::{IngestEventRequest, IngestEventResponse, QueryEventsRequest, QueryEventsResponse, EventDto};
+use crate::auth::{ApiKeyScopes, Permission};
+use crate::middleware::{Admin, Authenticated};
 use crate::pipeline::{PipelineConfig, PipelineStats};
+use crate::rate_limit::QueryCost;
 use crate::replay::{ReplayProgress, StartReplayRequest, StartReplayResponse};
+use crate::security::SensitiveFieldPolicy;
 use crate::schema::{
     CompatibilityMode, RegisterSchemaRequest, RegisterSchemaResponse, ValidateEventRequest,
     ValidateEventResponse,
@@ -18,7 +23,8 @@ use crate::snapshot::{
 };
 use crate::store::EventStore;
 use axum::{
-    extract::{Path, Query, State, WebSocketUpgrade},
+    extract::{Extension, Path, Query, State, WebSocketUpgrade},
+    http::{HeaderMap, HeaderValue},
     response::{IntoResponse, Response},
     routing::{get, post, put},
     Json, Router,
@@ -116,8 +122,17 @@ pub async fn prometheus_metrics(State(store): State<SharedStore>) -> impl IntoRe
 
 pub async fn ingest_event(
     State(store): State<SharedStore>,
+    auth: Option<Authenticated>,
     Json(req): Json<IngestEventRequest>,
-) -> Result<Json<IngestEventResponse>> {
+) -> Result<(HeaderMap, Json<IngestEventResponse>)> {
+    if let Some(scopes) = api_key_scopes(&auth) {
+        if !scopes.allows_event_type(&req.event_type) || !scopes.allows_entity_id(&req.entity_id) {
+            return Err(AllSourceError::PermissionDenied(
+                "API key is not scoped for this event type or entity".to_string(),
+            ));
+        }
+    }
+
     // Create event using from_strings with default tenant
     let event = Event::from_strings(
         req.event_type,
@@ -130,27 +145,91 @@ pub async fn ingest_event(
     let event_id = event.id;
     let timestamp = event.timestamp;
 
-    store.ingest(event)?;
+    let token = store.ingest_tracked(event)?;
+
+    // In a cluster, hand back the write's position for read-your-writes
+    let mut headers = HeaderMap::new();
+    if let (Some(log), Some(token)) = (store.replication_log(), token) {
+        log.wait_for_acks(token).await?;
+        if let Ok(value) = HeaderValue::from_str(&token.to_string()) {
+            headers.insert(REPLICATION_TOKEN_HEADER, value);
+        }
+    }
 
     tracing::info!("Event ingested: {}", event_id);
 
-    Ok(Json(IngestEventResponse {
-        event_id,
-        timestamp,
-    }))
+    Ok((
+        headers,
+        Json(IngestEventResponse {
+            event_id,
+            timestamp,
+        }),
+    ))
+}
+
+/// Whether the caller may see decrypted sensitive payload fields
+fn can_read_sensitive(auth: &Option<Authenticated>) -> bool {
+    auth.as_ref()
+        .map(|Authenticated(ctx)| ctx.require_permission(Permission::ReadSensitive).is_ok())
+        .unwrap_or(false)
+}
+
+/// Scopes of the API key that authenticated the request, if any
+fn api_key_scopes(auth: &Option<Authenticated>) -> Option<&ApiKeyScopes> {
+    auth.as_ref()
+        .and_then(|Authenticated(ctx)| ctx.claims.scopes.as_ref())
+}
+
+/// Reject entity reads outside the API key's entity-id prefixes
+///
+/// Entity state and snapshots fold in every event type of the entity, so
+/// keys scoped to event-type namespaces cannot read them at all.
+fn require_entity_scope(auth: &Option<Authenticated>, entity_id: &str) -> Result<()> {
+    match api_key_scopes(auth) {
+        Some(scopes) if !scopes.event_type_namespaces.is_empty() => Err(AllSourceError::PermissionDenied(
+            "API key is scoped to event types; entity state spans all event types".to_string(),
+        )),
+        Some(scopes) if !scopes.allows_entity_id(entity_id) => Err(AllSourceError::PermissionDenied(
+            "API key is not scoped for this entity".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Reject keys that only see some events from endpoints working over the
+/// whole store (analytics, replay)
+fn require_unscoped_events(auth: &Option<Authenticated>, operation: &str) -> Result<()> {
+    match api_key_scopes(auth) {
+        Some(scopes) if scopes.restricts_events() => Err(AllSourceError::PermissionDenied(format!(
+            "API key is scoped to a subset of events and cannot run {}",
+            operation
+        ))),
+        _ => Ok(()),
+    }
 }
 
 pub async fn query_events(
     State(store): State<SharedStore>,
+    auth: Option<Authenticated>,
     Query(req): Query<QueryEventsRequest>,
-) -> Result<Json<QueryEventsResponse>> {
-    let domain_events = store.query(req)?;
-    let events: Vec<EventDto> = domain_events.iter().map(EventDto::from).collect();
+) -> Result<(Extension<QueryCost>, Json<QueryEventsResponse>)> {
+    let (domain_events, scanned) = store.query_scanned(req, api_key_scopes(&auth))?;
+    let mut events: Vec<EventDto> = domain_events.iter().map(EventDto::from).collect();
     let count = events.len();
 
+    let authorized = can_read_sensitive(&auth);
+    let payload_encryption = store.payload_encryption();
+    for event in &mut events {
+        payload_encryption.reveal(&mut event.payload, authorized)?;
+    }
+
     tracing::debug!("Query returned {} events", count);
 
-    Ok(Json(QueryEventsResponse { events, count }))
+    let cost = QueryCost {
+        rows_scanned: scanned,
+        rows_returned: count,
+    };
+    Ok((Extension(cost), Json(QueryEventsResponse { events, count })))
 }
 
 #[derive(Deserialize)]
@@ -160,10 +239,13 @@ pub struct EntityStateParams {
 
 pub async fn get_entity_state(
     State(store): State<SharedStore>,
+    auth: Option<Authenticated>,
     Path(entity_id): Path<String>,
     Query(params): Query<EntityStateParams>,
 ) -> Result<Json<serde_json::Value>> {
-    let state = store.reconstruct_state(&entity_id, params.as_of)?;
+    require_entity_scope(&auth, &entity_id)?;
+    let mut state = store.reconstruct_state(&entity_id, params.as_of)?;
+    store.payload_encryption().reveal(&mut state, can_read_sensitive(&auth))?;
 
     tracing::info!("State reconstructed for entity: {}", entity_id);
 
@@ -172,9 +254,12 @@ pub async fn get_entity_state(
 
 pub async fn get_entity_snapshot(
     State(store): State<SharedStore>,
+    auth: Option<Authenticated>,
     Path(entity_id): Path<String>,
 ) -> Result<Json<serde_json::Value>> {
-    let snapshot = store.get_snapshot(&entity_id)?;
+    require_entity_scope(&auth, &entity_id)?;
+    let mut snapshot = store.get_snapshot(&entity_id)?;
+    store.payload_encryption().reveal(&mut snapshot, can_read_sensitive(&auth))?;
 
     tracing::debug!("Snapshot retrieved for entity: {}", entity_id);
 
@@ -190,19 +275,27 @@ pub async fn get_stats(State(store): State<SharedStore>) -> impl IntoResponse {
 pub async fn events_websocket(
     ws: WebSocketUpgrade,
     State(store): State<SharedStore>,
+    auth: Option<Authenticated>,
 ) -> Response {
     let websocket_manager = store.websocket_manager();
+    let scopes = api_key_scopes(&auth).cloned();
+    let payload_encryption = store.payload_encryption();
+    let read_sensitive = can_read_sensitive(&auth);
 
     ws.on_upgrade(move |socket| async move {
-        websocket_manager.handle_socket(socket).await;
+        websocket_manager
+            .handle_socket(socket, scopes, payload_encryption, read_sensitive)
+            .await;
     })
 }
 
 // v0.2: Event frequency analytics endpoint
 pub async fn analytics_frequency(
     State(store): State<SharedStore>,
+    auth: Option<Authenticated>,
     Query(req): Query<EventFrequencyRequest>,
-) -> Result<Json<EventFrequencyResponse>> {
+) -> Result<(Extension<QueryCost>, Json<EventFrequencyResponse>)> {
+    require_unscoped_events(&auth, "frequency analytics")?;
     let response = AnalyticsEngine::event_frequency(&store, req)?;
 
     tracing::debug!(
@@ -210,14 +303,20 @@ pub async fn analytics_frequency(
         response.buckets.len()
     );
 
-    Ok(Json(response))
+    let cost = QueryCost {
+        rows_scanned: response.total_events,
+        rows_returned: response.buckets.len(),
+    };
+    Ok((Extension(cost), Json(response)))
 }
 
 // v0.2: Statistical summary endpoint
 pub async fn analytics_summary(
     State(store): State<SharedStore>,
+    auth: Option<Authenticated>,
     Query(req): Query<StatsSummaryRequest>,
-) -> Result<Json<StatsSummaryResponse>> {
+) -> Result<(Extension<QueryCost>, Json<StatsSummaryResponse>)> {
+    require_unscoped_events(&auth, "summary analytics")?;
     let response = AnalyticsEngine::stats_summary(&store, req)?;
 
     tracing::debug!(
@@ -226,14 +325,20 @@ pub async fn analytics_summary(
         response.unique_entities
     );
 
-    Ok(Json(response))
+    let cost = QueryCost {
+        rows_scanned: response.total_events,
+        rows_returned: response.top_event_types.len() + response.top_entities.len(),
+    };
+    Ok((Extension(cost), Json(response)))
 }
 
 // v0.2: Event correlation analysis endpoint
 pub async fn analytics_correlation(
     State(store): State<SharedStore>,
+    auth: Option<Authenticated>,
     Query(req): Query<CorrelationRequest>,
-) -> Result<Json<CorrelationResponse>> {
+) -> Result<(Extension<QueryCost>, Json<CorrelationResponse>)> {
+    require_unscoped_events(&auth, "correlation analytics")?;
     let response = AnalyticsEngine::analyze_correlation(&store, req)?;
 
     tracing::debug!(
@@ -243,14 +348,20 @@ pub async fn analytics_correlation(
         response.correlation_percentage
     );
 
-    Ok(Json(response))
+    let cost = QueryCost {
+        rows_scanned: response.total_a + response.total_b,
+        rows_returned: response.examples.len(),
+    };
+    Ok((Extension(cost), Json(response)))
 }
 
 // v0.2: Create a snapshot for an entity
 pub async fn create_snapshot(
     State(store): State<SharedStore>,
+    auth: Option<Authenticated>,
     Json(req): Json<CreateSnapshotRequest>,
 ) -> Result<Json<CreateSnapshotResponse>> {
+    require_entity_scope(&auth, &req.entity_id)?;
     store.create_snapshot(&req.entity_id)?;
 
     let snapshot_manager = store.snapshot_manager();
@@ -272,21 +383,25 @@ pub async fn create_snapshot(
 // v0.2: List snapshots
 pub async fn list_snapshots(
     State(store): State<SharedStore>,
+    auth: Option<Authenticated>,
     Query(req): Query<ListSnapshotsRequest>,
 ) -> Result<Json<ListSnapshotsResponse>> {
     let snapshot_manager = store.snapshot_manager();
 
     let snapshots: Vec<SnapshotInfo> = if let Some(entity_id) = req.entity_id {
+        require_entity_scope(&auth, &entity_id)?;
         snapshot_manager
             .get_all_snapshots(&entity_id)
             .into_iter()
             .map(SnapshotInfo::from)
             .collect()
     } else {
-        // List all entities with snapshots
+        // List all entities with snapshots the caller may read (none for
+        // keys scoped to event types)
         let entities = snapshot_manager.list_entities();
         entities
             .iter()
+            .filter(|entity_id| require_entity_scope(&auth, entity_id).is_ok())
             .flat_map(|entity_id| {
                 snapshot_manager
                     .get_all_snapshots(entity_id)
@@ -306,14 +421,19 @@ pub async fn list_snapshots(
 // v0.2: Get latest snapshot for an entity
 pub async fn get_latest_snapshot(
     State(store): State<SharedStore>,
+    auth: Option<Authenticated>,
     Path(entity_id): Path<String>,
 ) -> Result<Json<serde_json::Value>> {
+    require_entity_scope(&auth, &entity_id)?;
     let snapshot_manager = store.snapshot_manager();
 
     let snapshot = snapshot_manager
         .get_latest_snapshot(&entity_id)
         .ok_or_else(|| crate::error::AllSourceError::EntityNotFound(entity_id.clone()))?;
 
+    let mut state = snapshot.state.clone();
+    store.payload_encryption().reveal(&mut state, can_read_sensitive(&auth))?;
+
     tracing::debug!("Retrieved latest snapshot for entity: {}", entity_id);
 
     Ok(Json(serde_json::json!({
@@ -324,21 +444,15 @@ pub async fn get_latest_snapshot(
         "event_count": snapshot.event_count,
         "size_bytes": snapshot.metadata.size_bytes,
         "snapshot_type": snapshot.metadata.snapshot_type,
-        "state": snapshot.state
+        "state": state
     })))
 }
 
 // v0.2: Trigger manual compaction
 pub async fn trigger_compaction(State(store): State<SharedStore>) -> Result<Json<CompactionResult>> {
-    let compaction_manager = store
-        .compaction_manager()
-        .ok_or_else(|| crate::error::AllSourceError::InternalError(
-            "Compaction not enabled (no Parquet storage)".to_string()
-        ))?;
-
     tracing::info!("📦 Manual compaction triggered via API");
 
-    let result = compaction_manager.compact_now()?;
+    let result = store.compact()?;
 
     Ok(Json(result))
 }
@@ -486,8 +600,10 @@ pub async fn set_compatibility_mode(
 // v0.5: Start a replay operation
 pub async fn start_replay(
     State(store): State<SharedStore>,
+    auth: Option<Authenticated>,
     Json(req): Json<StartReplayRequest>,
 ) -> Result<Json<StartReplayResponse>> {
+    require_unscoped_events(&auth, "replays")?;
     let replay_manager = store.replay_manager();
 
     let response = replay_manager.start_replay(store, req)?;
@@ -678,3 +794,69 @@ pub async fn reset_pipeline(
         "reset": true
     })))
 }
+
+// List sensitive field encryption policies (admin only)
+pub async fn list_encryption_policies(
+    State(store): State<SharedStore>,
+    Admin(_): Admin,
+) -> Json<serde_json::Value> {
+    let payload_encryption = store.payload_encryption();
+
+    let policies = payload_encryption.list_policies();
+
+    Json(serde_json::json!({
+        "enabled": payload_encryption.is_enabled(),
+        "policies": policies,
+        "total": policies.len()
+    }))
+}
+
+// Set the sensitive field policy for an event type (admin only)
+pub async fn set_encryption_policy(
+    State(store): State<SharedStore>,
+    Admin(_): Admin,
+    Json(policy): Json<SensitiveFieldPolicy>,
+) -> Result<Json<SensitiveFieldPolicy>> {
+    store.payload_encryption().set_policy(policy.clone())?;
+
+    tracing::info!(
+        "🔐 Encryption policy set for '{}': {:?}",
+        policy.event_type,
+        policy.fields
+    );
+
+    Ok(Json(policy))
+}
+
+// Remove the sensitive field policy for an event type (admin only)
+pub async fn remove_encryption_policy(
+    State(store): State<SharedStore>,
+    Admin(_): Admin,
+    Path(event_type): Path<String>,
+) -> Result<Json<serde_json::Value>> {
+    let removed = store.payload_encryption().remove_policy(&event_type)?;
+
+    Ok(Json(serde_json::json!({
+        "event_type": event_type,
+        "removed": removed
+    })))
+}
+
+// Rotate the payload data encryption key (admin only)
+pub async fn rotate_encryption_keys(
+    State(store): State<SharedStore>,
+    Admin(_): Admin,
+) -> Result<Json<serde_json::Value>> {
+    let payload_encryption = store.payload_encryption();
+
+    payload_encryption.rotate_keys()?;
+
+    let stats = payload_encryption.field_encryption().get_stats();
+
+    tracing::info!("🔑 Rotated payload encryption key (v{})", stats.active_key_version);
+
+    Ok(Json(serde_json::json!({
+        "rotated": true,
+        "stats": stats
+    })))
+}
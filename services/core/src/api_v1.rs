@@ -1,8 +1,23 @@
 /// v1.0 API router with authentication and multi-tenancy
+use crate::application::services::AuditLogger;
+use crate::audit_api::*;
 use crate::auth::AuthManager;
+use crate::backup::BackupJobManager;
+use crate::backup_api::*;
 use crate::auth_api::*;
-use crate::middleware::{auth_middleware, rate_limit_middleware, AuthState, RateLimitState};
-use crate::rate_limit::RateLimiter;
+use crate::domain::repositories::AuditEventRepository;
+use crate::infrastructure::cluster::{RaftNode, ReplicationManager, RequestForwarder};
+use crate::infrastructure::repositories::HashChainedAuditRepository;
+use crate::infrastructure::security::IpFilter;
+use crate::middleware::{
+    audit_middleware, auth_middleware, client_ip_middleware, forwarding_middleware, ip_filter_middleware,
+    policy_middleware, rate_limit_middleware, read_your_writes_middleware, request_id_middleware,
+    AuditState, AuthState, ForwardingState, IpFilterState, PolicyState, RateLimitState,
+    ReplicationState, TrustedProxies,
+};
+use crate::policy_api::*;
+use crate::rate_limit::{CostModel, RateLimiter};
+use crate::security::{AdaptiveRateLimiter, PolicyEngine};
 use crate::store::EventStore;
 use crate::tenant::TenantManager;
 use crate::tenant_api::*;
@@ -11,6 +26,7 @@ use axum::{
     routing::{delete, get, post, put},
     Router,
 };
+use std::net::SocketAddr;
 use std::sync::Arc;
 use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::TraceLayer;
@@ -21,6 +37,10 @@ pub struct AppState {
     pub store: Arc<EventStore>,
     pub auth_manager: Arc<AuthManager>,
     pub tenant_manager: Arc<TenantManager>,
+    pub audit_logger: Arc<AuditLogger<dyn AuditEventRepository>>,
+    pub audit_chain: Arc<HashChainedAuditRepository>,
+    pub policy_engine: Arc<PolicyEngine>,
+    pub backup_jobs: Arc<BackupJobManager>,
 }
 
 // Enable extracting Arc<EventStore> from AppState
@@ -31,34 +51,140 @@ impl axum::extract::FromRef<AppState> for Arc<EventStore> {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn serve_v1(
     store: Arc<EventStore>,
     auth_manager: Arc<AuthManager>,
     tenant_manager: Arc<TenantManager>,
     rate_limiter: Arc<RateLimiter>,
+    adaptive_limiter: Option<Arc<AdaptiveRateLimiter>>,
+    cost_model: CostModel,
+    ip_filter: Arc<IpFilter>,
+    trusted_proxies: TrustedProxies,
+    audit_logger: Arc<AuditLogger<dyn AuditEventRepository>>,
+    audit_chain: Arc<HashChainedAuditRepository>,
+    policy_engine: Arc<PolicyEngine>,
+    backup_jobs: Arc<BackupJobManager>,
+    replication: Option<Arc<ReplicationManager>>,
+    consensus: Option<Arc<RaftNode>>,
+    forwarder: Option<Arc<RequestForwarder>>,
     addr: &str,
 ) -> anyhow::Result<()> {
+    let app = router_v1(
+        store,
+        auth_manager,
+        tenant_manager,
+        rate_limiter,
+        adaptive_limiter,
+        cost_model,
+        ip_filter,
+        trusted_proxies,
+        audit_logger,
+        audit_chain,
+        policy_engine,
+        backup_jobs,
+        replication,
+        consensus,
+        forwarder,
+    );
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Build the v1.0 router with its full middleware stack
+///
+/// Requests pass through, outermost first: tracing, CORS, request id,
+/// client address resolution, IP filtering, authentication, rate limiting,
+/// audit logging, access policies, leader forwarding and read-your-writes.
+#[allow(clippy::too_many_arguments)]
+pub fn router_v1(
+    store: Arc<EventStore>,
+    auth_manager: Arc<AuthManager>,
+    tenant_manager: Arc<TenantManager>,
+    rate_limiter: Arc<RateLimiter>,
+    adaptive_limiter: Option<Arc<AdaptiveRateLimiter>>,
+    cost_model: CostModel,
+    ip_filter: Arc<IpFilter>,
+    trusted_proxies: TrustedProxies,
+    audit_logger: Arc<AuditLogger<dyn AuditEventRepository>>,
+    audit_chain: Arc<HashChainedAuditRepository>,
+    policy_engine: Arc<PolicyEngine>,
+    backup_jobs: Arc<BackupJobManager>,
+    replication: Option<Arc<ReplicationManager>>,
+    consensus: Option<Arc<RaftNode>>,
+    forwarder: Option<Arc<RequestForwarder>>,
+) -> Router {
+    let replication_state = ReplicationState {
+        log: store.replication_log().cloned(),
+    };
+
+    let forwarding_state = ForwardingState { forwarder };
+
     let app_state = AppState {
         store,
         auth_manager: auth_manager.clone(),
         tenant_manager,
+        audit_logger: audit_logger.clone(),
+        audit_chain,
+        policy_engine: policy_engine.clone(),
+        backup_jobs,
     };
 
     let auth_state = AuthState {
         auth_manager: auth_manager.clone(),
+        cluster_secret: forwarding_state.forwarder.as_ref().and_then(|f| f.config().cluster_secret.clone()),
     };
 
     let rate_limit_state = RateLimitState {
         rate_limiter,
+        adaptive_limiter,
+        cost_model: Arc::new(cost_model),
     };
 
-    let app = Router::new()
+    let ip_filter_state = IpFilterState { ip_filter };
+
+    let policy_state = PolicyState { policy_engine };
+
+    let audit_state = AuditState {
+        audit_logger,
+        auth_manager: auth_manager.clone(),
+    };
+
+    // Served outside the auth and rate limit layers
+    let public = Router::new()
+        .route("/.well-known/jwks.json", get(jwks_handler))
+        .route("/api/v1/auth/refresh", post(refresh_handler))
+        .route("/api/v1/auth/oidc/login", get(oidc_login_handler))
+        .route("/api/v1/auth/oidc/callback", get(oidc_callback_handler))
+        .with_state(app_state.clone());
+
+    // Node-to-node replication, authenticated by the cluster secret
+    let public = match replication {
+        Some(manager) => public.merge(manager.router()),
+        None => public,
+    };
+
+    // Cluster metadata consensus and membership, same authentication
+    let public = match consensus {
+        Some(raft) => public.merge(raft.router()),
+        None => public,
+    };
+
+    Router::new()
         // Public routes (no auth)
         .route("/health", get(crate::api::health))
         .route("/metrics", get(crate::api::prometheus_metrics))
         // Auth routes
         .route("/api/v1/auth/register", post(register_handler))
         .route("/api/v1/auth/login", post(login_handler))
+        .route("/api/v1/auth/logout", post(logout_handler))
         .route("/api/v1/auth/me", get(me_handler))
         .route("/api/v1/auth/api-keys", post(create_api_key_handler))
         .route("/api/v1/auth/api-keys", get(list_api_keys_handler))
@@ -113,19 +239,43 @@ pub async fn serve_v1(
         .route("/api/v1/pipelines/:pipeline_id", delete(crate::api::remove_pipeline))
         .route("/api/v1/pipelines/:pipeline_id/stats", get(crate::api::get_pipeline_stats))
         .route("/api/v1/pipelines/:pipeline_id/reset", put(crate::api::reset_pipeline))
+        // Payload encryption
+        .route("/api/v1/encryption/policies", get(crate::api::list_encryption_policies))
+        .route("/api/v1/encryption/policies", put(crate::api::set_encryption_policy))
+        .route("/api/v1/encryption/policies/:event_type", delete(crate::api::remove_encryption_policy))
+        .route("/api/v1/encryption/rotate", post(crate::api::rotate_encryption_keys))
+        // Audit log (admin only)
+        .route("/api/v1/audit", get(query_audit_events_handler))
+        .route("/api/v1/audit/verify", get(verify_audit_chain_handler))
+        // Access policies (admin only)
+        .route("/api/v1/policies", get(list_policies_handler))
+        .route("/api/v1/policies", post(put_policy_handler))
+        .route("/api/v1/policies/evaluate", post(evaluate_policy_handler))
+        .route("/api/v1/policies/:id", get(get_policy_handler))
+        .route("/api/v1/policies/:id", delete(delete_policy_handler))
+        // Online backup and restore (admin only)
+        .route("/api/v1/admin/backups", post(create_backup_handler))
+        .route("/api/v1/admin/backups", get(list_backups_handler))
+        .route("/api/v1/admin/backups/jobs", get(list_backup_jobs_handler))
+        .route("/api/v1/admin/backups/jobs/:job_id", get(get_backup_job_handler))
+        .route("/api/v1/admin/backups/:id/restore", post(restore_backup_handler))
         .with_state(app_state)
-        .layer(middleware::from_fn_with_state(auth_state, auth_middleware))
+        .layer(middleware::from_fn_with_state(replication_state, read_your_writes_middleware))
+        .layer(middleware::from_fn_with_state(forwarding_state, forwarding_middleware))
+        .layer(middleware::from_fn_with_state(policy_state, policy_middleware))
+        .layer(middleware::from_fn_with_state(audit_state, audit_middleware))
+        // Rate limits are per tenant, so they run inside authentication
         .layer(middleware::from_fn_with_state(rate_limit_state, rate_limit_middleware))
+        .layer(middleware::from_fn_with_state(auth_state, auth_middleware))
+        .merge(public)
+        .layer(middleware::from_fn_with_state(ip_filter_state, ip_filter_middleware))
+        .layer(middleware::from_fn_with_state(trusted_proxies, client_ip_middleware))
+        .layer(middleware::from_fn(request_id_middleware))
         .layer(
             CorsLayer::new()
                 .allow_origin(Any)
                 .allow_methods(Any)
                 .allow_headers(Any),
         )
-        .layer(TraceLayer::new_for_http());
-
-    let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
-
-    Ok(())
+        .layer(TraceLayer::new_for_http())
 }
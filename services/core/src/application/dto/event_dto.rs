@@ -52,6 +52,9 @@ pub struct QueryEventsRequest {
 
     /// Limit number of results
     pub limit: Option<usize>,
+
+    /// Only events whose payload contains this JSON, e.g. `{"status":"paid"}`
+    pub payload_contains: Option<String>,
 }
 
 /// DTO for query response
@@ -62,7 +65,7 @@ pub struct QueryEventsResponse {
 }
 
 /// DTO for a single event in responses
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct EventDto {
     pub id: Uuid,
     pub event_type: String,
@@ -156,7 +156,7 @@ impl AuditLogBuilder {
         event
     }
 
-    pub async fn record<R: AuditEventRepository>(self, repo: &R) -> Result<(), AllSourceError> {
+    pub async fn record<R: AuditEventRepository + ?Sized>(self, repo: &R) -> Result<(), AllSourceError> {
         let event = self.build();
         repo.append(event).await
     }
@@ -185,23 +185,28 @@ impl AuditLogBuilder {
 /// .record_async()
 /// .await;
 /// ```
-pub struct AuditLogger<R: AuditEventRepository> {
+pub struct AuditLogger<R: AuditEventRepository + ?Sized> {
     repository: Arc<R>,
 }
 
-impl<R: AuditEventRepository> AuditLogger<R> {
+impl<R: AuditEventRepository + ?Sized> AuditLogger<R> {
     /// Create a new AuditLogger with the given repository
     pub fn new(repository: Arc<R>) -> Self {
         Self { repository }
     }
 
+    /// Get the underlying repository (used by the audit query API)
+    pub fn repository(&self) -> Arc<R> {
+        Arc::clone(&self.repository)
+    }
+
     /// Start building an audit log entry
     pub fn log(
         &self,
         tenant_id: TenantId,
         action: AuditAction,
         actor: Actor,
-    ) -> AuditLogEntry<R> {
+    ) -> AuditLogEntry<'_, R> {
         AuditLogEntry {
             logger: self,
             builder: AuditLogBuilder::new(tenant_id, action, actor),
@@ -269,12 +274,12 @@ impl<R: AuditEventRepository> AuditLogger<R> {
 }
 
 /// Builder for a single audit log entry
-pub struct AuditLogEntry<'a, R: AuditEventRepository> {
+pub struct AuditLogEntry<'a, R: AuditEventRepository + ?Sized> {
     logger: &'a AuditLogger<R>,
     builder: AuditLogBuilder,
 }
 
-impl<'a, R: AuditEventRepository> AuditLogEntry<'a, R> {
+impl<'a, R: AuditEventRepository + ?Sized> AuditLogEntry<'a, R> {
     pub fn with_outcome(mut self, outcome: AuditOutcome) -> Self {
         self.builder = self.builder.with_outcome(outcome);
         self
@@ -218,6 +218,7 @@ mod tests {
             since: None,
             until: None,
             limit: None,
+            payload_contains: None,
         };
 
         let response = use_case.execute(request).await;
@@ -241,6 +242,7 @@ mod tests {
             since: None,
             until: None,
             limit: None,
+            payload_contains: None,
         };
 
         let response = use_case.execute(request).await;
@@ -264,6 +266,7 @@ mod tests {
             since: None,
             until: None,
             limit: Some(1),
+            payload_contains: None,
         };
 
         let response = use_case.execute(request).await;
@@ -287,6 +290,7 @@ mod tests {
             since: None,
             until: None,
             limit: None,
+            payload_contains: None,
         };
 
         let response = use_case.execute(request).await;
@@ -0,0 +1,196 @@
+use crate::domain::entities::{AuditAction, AuditCategory, AuditEvent};
+use crate::domain::repositories::AuditEventQuery;
+use crate::domain::value_objects::TenantId;
+use crate::infrastructure::repositories::ChainVerificationReport;
+use crate::middleware::Admin;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// AppState is defined in api_v1.rs
+use crate::api_v1::AppState;
+
+/// Default page size for audit queries
+const DEFAULT_AUDIT_LIMIT: usize = 100;
+
+/// Maximum page size for audit queries
+const MAX_AUDIT_LIMIT: usize = 1000;
+
+// ============================================================================
+// Request/Response Types
+// ============================================================================
+
+#[derive(Debug, Default, Deserialize)]
+pub struct AuditQueryParams {
+    /// Tenant to query (defaults to the caller's tenant)
+    pub tenant_id: Option<String>,
+    pub action: Option<AuditAction>,
+    pub category: Option<AuditCategory>,
+    /// Actor identifier, e.g. "user:<id>" or "api_key:<id>"
+    pub actor: Option<String>,
+    pub resource_type: Option<String>,
+    pub resource_id: Option<String>,
+    pub security_only: Option<bool>,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyChainParams {
+    /// Tenant whose chain to verify (defaults to the caller's tenant)
+    pub tenant_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditQueryResponse {
+    pub events: Vec<AuditEvent>,
+    pub total: usize,
+    pub limit: usize,
+    pub offset: usize,
+}
+
+impl AuditQueryParams {
+    /// Convert request parameters into a repository query
+    pub fn into_query(self, default_tenant: &str) -> Result<AuditEventQuery, (StatusCode, String)> {
+        let tenant = self.tenant_id.unwrap_or_else(|| default_tenant.to_string());
+        let tenant_id =
+            TenantId::new(tenant).map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+
+        if let (Some(start), Some(end)) = (self.start_time, self.end_time) {
+            if start > end {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    "start_time must not be after end_time".to_string(),
+                ));
+            }
+        }
+
+        let mut query = AuditEventQuery::new(tenant_id);
+        query.start_time = self.start_time;
+        query.end_time = self.end_time;
+        query.action = self.action;
+        query.category = self.category;
+        query.actor_identifier = self.actor;
+        query.resource_type = self.resource_type;
+        query.resource_id = self.resource_id;
+        query.security_events_only = self.security_only.unwrap_or(false);
+        query.limit = Some(self.limit.unwrap_or(DEFAULT_AUDIT_LIMIT).min(MAX_AUDIT_LIMIT));
+        query.offset = Some(self.offset.unwrap_or(0));
+
+        Ok(query)
+    }
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// Query the audit log (admin only)
+/// GET /api/v1/audit
+pub async fn query_audit_events_handler(
+    State(state): State<AppState>,
+    Admin(auth_ctx): Admin,
+    Query(params): Query<AuditQueryParams>,
+) -> Result<Json<AuditQueryResponse>, (StatusCode, String)> {
+    let query = params.into_query(auth_ctx.tenant_id())?;
+    let limit = query.limit.unwrap_or(DEFAULT_AUDIT_LIMIT);
+    let offset = query.offset.unwrap_or(0);
+
+    let repository = state.audit_logger.repository();
+    let total = repository
+        .count(query.clone())
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let events = repository
+        .query(query)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(AuditQueryResponse {
+        events,
+        total,
+        limit,
+        offset,
+    }))
+}
+
+/// Verify the tenant's tamper-evident audit chain (admin only)
+/// GET /api/v1/audit/verify
+pub async fn verify_audit_chain_handler(
+    State(state): State<AppState>,
+    Admin(auth_ctx): Admin,
+    Query(params): Query<VerifyChainParams>,
+) -> Result<Json<ChainVerificationReport>, (StatusCode, String)> {
+    let tenant = params
+        .tenant_id
+        .unwrap_or_else(|| auth_ctx.tenant_id().to_string());
+    let tenant_id = TenantId::new(tenant).map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+
+    let report = state
+        .audit_chain
+        .verify_chain(&tenant_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if !report.valid {
+        tracing::warn!(
+            "🚨 Audit chain for tenant '{}' is broken: {:?}",
+            report.tenant_id,
+            report.broken_link
+        );
+    }
+
+    Ok(Json(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_params_default_to_caller_tenant_and_page_size() {
+        let query = AuditQueryParams::default().into_query("acme").unwrap();
+        assert_eq!(query.tenant_id.as_str(), "acme");
+        assert_eq!(query.limit, Some(DEFAULT_AUDIT_LIMIT));
+        assert_eq!(query.offset, Some(0));
+        assert!(!query.security_events_only);
+    }
+
+    #[test]
+    fn test_params_clamp_limit_and_map_filters() {
+        let params = AuditQueryParams {
+            tenant_id: Some("other".to_string()),
+            action: Some(AuditAction::TenantCreated),
+            actor: Some("user:1".to_string()),
+            security_only: Some(true),
+            limit: Some(50_000),
+            offset: Some(10),
+            ..Default::default()
+        };
+        let query = params.into_query("acme").unwrap();
+        assert_eq!(query.tenant_id.as_str(), "other");
+        assert_eq!(query.action, Some(AuditAction::TenantCreated));
+        assert_eq!(query.actor_identifier.as_deref(), Some("user:1"));
+        assert!(query.security_events_only);
+        assert_eq!(query.limit, Some(MAX_AUDIT_LIMIT));
+        assert_eq!(query.offset, Some(10));
+    }
+
+    #[test]
+    fn test_params_reject_inverted_time_range() {
+        let now = Utc::now();
+        let params = AuditQueryParams {
+            start_time: Some(now),
+            end_time: Some(now - chrono::Duration::hours(1)),
+            ..Default::default()
+        };
+        let err = params.into_query("acme").unwrap_err();
+        assert_eq!(err.0, StatusCode::BAD_REQUEST);
+    }
+}
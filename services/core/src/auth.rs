@@ -1,15 +1,53 @@
+use crate::domain::entities::Event;
+use crate::domain::repositories::{ApiKeyRepository, SessionRepository, UserRepository};
+use crate::domain::value_objects::{EntityId, EventType};
 use crate::error::{AllSourceError, Result};
+use crate::infrastructure::persistence::object_store::block_on;
+use crate::infrastructure::security::IpNetwork;
+use crate::rate_limit::{RateLimitConfig, RateLimitResult, RateLimiter};
 use argon2::{
     password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
     Argon2,
 };
 use chrono::{Duration, Utc};
 use dashmap::DashMap;
-use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use crate::security::jwt_keys::{JwtKeyManager, JWT_ISSUER};
+use crate::security::oidc::{ExternalIdentity, OidcProvider};
 use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
 use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot};
 use uuid::Uuid;
 
+/// Minimum interval between persisted `last_used` updates of an API key
+const LAST_USED_PERSIST_INTERVAL_SECS: i64 = 60;
+
+/// Interval at which revocations made by other nodes are loaded from the
+/// session repository
+const SESSION_SYNC_INTERVAL_SECS: u64 = 10;
+
+/// Delimiter between an entity ID's prefix and the rest (`user-123` -> `user`)
+pub const ENTITY_PREFIX_DELIMITER: char = '-';
+
+/// Session token lifetimes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionConfig {
+    /// Access token (JWT) lifetime in seconds
+    pub access_token_ttl_secs: i64,
+
+    /// Refresh token lifetime in seconds; each refresh issues a new one
+    pub refresh_token_ttl_secs: i64,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            access_token_ttl_secs: 15 * 60,
+            refresh_token_ttl_secs: 30 * 24 * 3600,
+        }
+    }
+}
+
 /// User role for RBAC
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -51,6 +89,7 @@ pub enum Permission {
     ManageSchemas,
     ManagePipelines,
     ManageTenants,
+    ReadSensitive,
 }
 
 /// JWT Claims
@@ -68,6 +107,15 @@ pub struct Claims {
     pub iat: i64,
     /// Issuer
     pub iss: String,
+    /// Token ID (for revocation)
+    #[serde(default)]
+    pub jti: String,
+    /// Session (refresh token family) the token was issued for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
+    /// Restrictions of the API key the claims were issued for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scopes: Option<ApiKeyScopes>,
 }
 
 impl Claims {
@@ -80,10 +128,30 @@ impl Claims {
             role,
             iat: now.timestamp(),
             exp: (now + expires_in).timestamp(),
-            iss: "allsource".to_string(),
+            iss: JWT_ISSUER.to_string(),
+            jti: Uuid::new_v4().to_string(),
+            sid: None,
+            scopes: None,
         }
     }
 
+    /// Bind the claims to a session
+    pub fn with_session(mut self, session_id: String) -> Self {
+        self.sid = Some(session_id);
+        self
+    }
+
+    /// Restrict the claims to API key scopes (unrestricted scopes are dropped)
+    pub fn with_scopes(mut self, scopes: ApiKeyScopes) -> Self {
+        self.scopes = (!scopes.is_unrestricted()).then_some(scopes);
+        self
+    }
+
+    /// Check if the claims may see an event (always true without scopes)
+    pub fn allows_event(&self, event: &Event) -> bool {
+        self.scopes.as_ref().is_none_or(|scopes| scopes.allows_event(event))
+    }
+
     /// Check if claims are expired
     pub fn is_expired(&self) -> bool {
         Utc::now().timestamp() > self.exp
@@ -138,6 +206,98 @@ impl User {
     }
 }
 
+/// Operation classes an API key can be scoped to
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiOperation {
+    Read,   // Queries, state and snapshots
+    Write,  // Ingestion and other mutations
+    Stream, // WebSocket subscriptions
+    Admin,  // Key, user, tenant, audit and encryption management
+}
+
+impl ApiOperation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiOperation::Read => "read",
+            ApiOperation::Write => "write",
+            ApiOperation::Stream => "stream",
+            ApiOperation::Admin => "admin",
+        }
+    }
+}
+
+/// Restrictions on what an API key may do, on top of its role
+///
+/// Each empty list leaves its dimension unrestricted, so the default
+/// scopes grant everything the role allows.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ApiKeyScopes {
+    /// Allowed event-type namespaces (`order` matches `order.placed`)
+    pub event_type_namespaces: Vec<String>,
+    /// Allowed entity-id prefixes (`user` matches `user-123`)
+    pub entity_id_prefixes: Vec<String>,
+    /// Allowed operations
+    pub operations: Vec<ApiOperation>,
+    /// Client networks the key may be used from
+    pub allowed_cidrs: Vec<IpNetwork>,
+    /// Requests per minute for this key, on top of the tenant limit
+    pub rate_limit_per_minute: Option<u32>,
+}
+
+impl ApiKeyScopes {
+    /// Whether the scopes restrict nothing
+    pub fn is_unrestricted(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Whether the scopes restrict which events are visible
+    pub fn restricts_events(&self) -> bool {
+        !self.event_type_namespaces.is_empty() || !self.entity_id_prefixes.is_empty()
+    }
+
+    /// Check if an operation is allowed
+    pub fn allows_operation(&self, operation: ApiOperation) -> bool {
+        self.operations.is_empty() || self.operations.contains(&operation)
+    }
+
+    /// Check if a client address is allowed
+    pub fn allows_ip(&self, ip: &IpAddr) -> bool {
+        self.allowed_cidrs.is_empty() || self.allowed_cidrs.iter().any(|net| net.contains(ip))
+    }
+
+    /// Check if an event type is allowed (by namespace)
+    pub fn allows_event_type(&self, event_type: &str) -> bool {
+        self.event_type_namespaces.is_empty()
+            || EventType::new(event_type.to_string())
+                .is_ok_and(|event_type| self.allows_namespace(event_type.namespace()))
+    }
+
+    /// Check if an entity ID is allowed (by prefix)
+    pub fn allows_entity_id(&self, entity_id: &str) -> bool {
+        self.entity_id_prefixes.is_empty()
+            || EntityId::new(entity_id.to_string()).is_ok_and(|entity_id| {
+                self.allows_prefix(entity_id.prefix(ENTITY_PREFIX_DELIMITER))
+            })
+    }
+
+    /// Check if an event is visible to the key
+    pub fn allows_event(&self, event: &Event) -> bool {
+        (self.event_type_namespaces.is_empty() || self.allows_namespace(event.event_type().namespace()))
+            && (self.entity_id_prefixes.is_empty()
+                || self.allows_prefix(event.entity_id().prefix(ENTITY_PREFIX_DELIMITER)))
+    }
+
+    fn allows_namespace(&self, namespace: Option<&str>) -> bool {
+        namespace.is_some_and(|ns| self.event_type_namespaces.iter().any(|allowed| allowed == ns))
+    }
+
+    fn allows_prefix(&self, prefix: Option<&str>) -> bool {
+        prefix.is_some_and(|p| self.entity_id_prefixes.iter().any(|allowed| allowed == p))
+    }
+}
+
 /// API Key for programmatic access
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApiKey {
@@ -151,6 +311,8 @@ pub struct ApiKey {
     pub expires_at: Option<chrono::DateTime<Utc>>,
     pub active: bool,
     pub last_used: Option<chrono::DateTime<Utc>>,
+    #[serde(default)]
+    pub scopes: ApiKeyScopes,
 }
 
 impl ApiKey {
@@ -174,6 +336,7 @@ impl ApiKey {
             expires_at,
             active: true,
             last_used: None,
+            scopes: ApiKeyScopes::default(),
         };
 
         (api_key, key)
@@ -204,41 +367,251 @@ impl ApiKey {
     }
 }
 
+/// Access and refresh token issued for a session
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenPair {
+    pub access_token: String,
+    pub refresh_token: String,
+    /// Access token lifetime in seconds
+    pub expires_in: i64,
+    pub session_id: String,
+}
+
+/// A refresh token; the plaintext is only returned when it is issued
+///
+/// Every refresh rotates the token: the presented one is marked used and a
+/// new one is issued in the same session. Presenting a used token again
+/// means it was stolen, so the whole session is revoked. Only the hash of
+/// the token is stored.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RefreshToken {
+    pub session_id: String,
+    pub user_id: Uuid,
+    pub token_hash: String,
+    pub issued_at: chrono::DateTime<Utc>,
+    pub expires_at: chrono::DateTime<Utc>,
+    /// Set once the token has been exchanged
+    pub used_at: Option<chrono::DateTime<Utc>>,
+}
+
+/// What a revocation applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RevocationKind {
+    /// Every access token of a session (`sid` claim)
+    Session,
+    /// A single access token (`jti` claim)
+    Token,
+}
+
+/// A revoked session or access token
+///
+/// Kept until `expires_at`, when the last access token it applies to has
+/// expired anyway.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Revocation {
+    pub kind: RevocationKind,
+    /// Session ID or token `jti`
+    pub id: String,
+    pub expires_at: chrono::DateTime<Utc>,
+}
+
+/// A write-behind update to the API key repository
+enum AuthWrite {
+    TouchApiKey(Uuid, chrono::DateTime<Utc>),
+    Flush(oneshot::Sender<()>),
+}
+
+/// Repositories the accounts and sessions are persisted to
+struct AuthRepositories {
+    users: Arc<dyn UserRepository>,
+    api_keys: Arc<dyn ApiKeyRepository>,
+    sessions: Arc<dyn SessionRepository>,
+}
+
 /// Authentication manager
+///
+/// Users and API keys are served from in-memory maps. When created with
+/// `with_repositories`, the maps are loaded from the repositories on startup.
+/// Creating or deleting users and creating or revoking API keys writes to
+/// the repositories before the change takes effect, and fails if that write
+/// does; only API key last-used times are written behind by a background task.
+/// Tokens are signed and verified by a `JwtKeyManager`. Refresh tokens are
+/// read from and written to the session repository, which marks them used
+/// atomically, so each is exchanged once even across nodes. Revocations are
+/// written to it and cached in memory; `spawn_session_sync` loads those made
+/// by other nodes every `SESSION_SYNC_INTERVAL_SECS`. Without repositories,
+/// refresh tokens and revocations are kept in memory only.
 pub struct AuthManager {
-    /// JWT encoding key
-    encoding_key: EncodingKey,
-    /// JWT decoding key
-    decoding_key: DecodingKey,
-    /// JWT validation rules
-    validation: Validation,
-    /// Users storage (in production, use database)
+    /// JWT signing and verification keys
+    jwt_keys: Arc<JwtKeyManager>,
+    /// Access and refresh token lifetimes
+    session_config: SessionConfig,
+    /// Refresh tokens by token hash (only used without repositories)
+    refresh_tokens: Arc<DashMap<String, RefreshToken>>,
+    /// Revoked sessions -> when their last access token expires (cache)
+    revoked_sessions: Arc<DashMap<String, chrono::DateTime<Utc>>>,
+    /// Revoked access tokens by `jti` -> token expiry (cache)
+    revoked_tokens: Arc<DashMap<String, chrono::DateTime<Utc>>>,
+    /// External identity provider (None = local accounts only)
+    oidc: Option<Arc<OidcProvider>>,
+    /// Users cache
     users: Arc<DashMap<Uuid, User>>,
-    /// API keys storage (in production, use database)
+    /// API keys cache
     api_keys: Arc<DashMap<Uuid, ApiKey>>,
     /// Username to user ID mapping
     username_index: Arc<DashMap<String, Uuid>>,
+    /// API key hash to key ID mapping
+    key_hash_index: Arc<DashMap<String, Uuid>>,
+    /// Per-key limits of API keys scoped with `rate_limit_per_minute`
+    key_rate_limiter: Arc<RateLimiter>,
+    /// Account and session repositories (None = in-memory only)
+    repositories: Option<AuthRepositories>,
+    /// Write-behind queue for API key last-used times
+    persistence: Option<mpsc::UnboundedSender<AuthWrite>>,
 }
 
 impl AuthManager {
-    /// Create new authentication manager
+    /// Create new authentication manager signing HS256 tokens with a shared secret
     pub fn new(jwt_secret: &str) -> Self {
-        let encoding_key = EncodingKey::from_secret(jwt_secret.as_bytes());
-        let decoding_key = DecodingKey::from_secret(jwt_secret.as_bytes());
-
-        let mut validation = Validation::new(Algorithm::HS256);
-        validation.set_issuer(&["allsource"]);
+        Self::with_jwt_keys(Arc::new(JwtKeyManager::from_secret(jwt_secret)))
+    }
 
+    /// Create new authentication manager using the given signing keys
+    pub fn with_jwt_keys(jwt_keys: Arc<JwtKeyManager>) -> Self {
         Self {
-            encoding_key,
-            decoding_key,
-            validation,
+            jwt_keys,
+            session_config: SessionConfig::default(),
+            refresh_tokens: Arc::new(DashMap::new()),
+            revoked_sessions: Arc::new(DashMap::new()),
+            revoked_tokens: Arc::new(DashMap::new()),
+            oidc: None,
             users: Arc::new(DashMap::new()),
             api_keys: Arc::new(DashMap::new()),
             username_index: Arc::new(DashMap::new()),
+            key_hash_index: Arc::new(DashMap::new()),
+            key_rate_limiter: Arc::new(RateLimiter::new(RateLimitConfig::unlimited())),
+            repositories: None,
+            persistence: None,
+        }
+    }
+
+    /// Create an authentication manager backed by persistent repositories
+    ///
+    /// Loads all users, API keys and active revocations, then persists
+    /// subsequent changes. Must be called within a Tokio runtime.
+    pub async fn with_repositories(
+        jwt_keys: Arc<JwtKeyManager>,
+        user_repository: Arc<dyn UserRepository>,
+        api_key_repository: Arc<dyn ApiKeyRepository>,
+        session_repository: Arc<dyn SessionRepository>,
+    ) -> Result<Self> {
+        let mut manager = Self::with_jwt_keys(jwt_keys);
+
+        for user in user_repository.find_all().await? {
+            manager.username_index.insert(user.username.clone(), user.id);
+            manager.users.insert(user.id, user);
+        }
+        for api_key in api_key_repository.find_all().await? {
+            manager.key_hash_index.insert(api_key.key_hash.clone(), api_key.id);
+            manager.api_keys.insert(api_key.id, api_key);
+        }
+        for revocation in session_repository.find_revocations(Utc::now()).await? {
+            manager.cache_revocation(revocation);
+        }
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(run_auth_writer(api_key_repository.clone(), rx));
+        manager.persistence = Some(tx);
+        manager.repositories = Some(AuthRepositories {
+            users: user_repository,
+            api_keys: api_key_repository,
+            sessions: session_repository,
+        });
+
+        Ok(manager)
+    }
+
+    /// Wait until all last-used times queued so far have been persisted
+    pub async fn flush(&self) {
+        let Some(tx) = &self.persistence else {
+            return;
+        };
+        let (done_tx, done_rx) = oneshot::channel();
+        if tx.send(AuthWrite::Flush(done_tx)).is_ok() {
+            let _ = done_rx.await;
+        }
+    }
+
+    fn persist(&self, write: AuthWrite) {
+        if let Some(tx) = &self.persistence {
+            if tx.send(write).is_err() {
+                tracing::error!("❌ Auth persistence task has stopped; change not persisted");
+            }
+        }
+    }
+
+    fn save_user(&self, user: &User) -> Result<()> {
+        match &self.repositories {
+            Some(repositories) => block_on(repositories.users.save(user)),
+            None => Ok(()),
+        }
+    }
+
+    fn save_api_key(&self, api_key: &ApiKey) -> Result<()> {
+        match &self.repositories {
+            Some(repositories) => block_on(repositories.api_keys.save(api_key)),
+            None => Ok(()),
         }
     }
 
+    fn save_refresh_token(&self, token: RefreshToken) -> Result<()> {
+        match &self.repositories {
+            Some(repositories) => block_on(repositories.sessions.save_refresh_token(&token)),
+            None => {
+                self.refresh_tokens.insert(token.token_hash.clone(), token);
+                Ok(())
+            }
+        }
+    }
+
+    fn find_refresh_token(&self, token_hash: &str) -> Result<Option<RefreshToken>> {
+        match &self.repositories {
+            Some(repositories) => block_on(repositories.sessions.find_refresh_token(token_hash)),
+            None => Ok(self.refresh_tokens.get(token_hash).map(|token| token.clone())),
+        }
+    }
+
+    /// Mark a refresh token used; `false` if it already was
+    fn mark_refresh_token_used(&self, token_hash: &str, used_at: chrono::DateTime<Utc>) -> Result<bool> {
+        match &self.repositories {
+            Some(repositories) => block_on(repositories.sessions.mark_refresh_token_used(token_hash, used_at)),
+            None => Ok(match self.refresh_tokens.get_mut(token_hash) {
+                Some(mut token) if token.used_at.is_none() => {
+                    token.used_at = Some(used_at);
+                    true
+                }
+                _ => false,
+            }),
+        }
+    }
+
+    fn save_revocation(&self, revocation: Revocation) -> Result<()> {
+        if let Some(repositories) = &self.repositories {
+            block_on(repositories.sessions.save_revocation(&revocation))?;
+        }
+        self.cache_revocation(revocation);
+        Ok(())
+    }
+
+    fn cache_revocation(&self, revocation: Revocation) {
+        let revoked = match revocation.kind {
+            RevocationKind::Session => &self.revoked_sessions,
+            RevocationKind::Token => &self.revoked_tokens,
+        };
+        revoked.insert(revocation.id, revocation.expires_at);
+    }
+
     /// Register a new user
     pub fn register_user(
         &self,
@@ -256,6 +629,7 @@ impl AuthManager {
         }
 
         let user = User::new(username.clone(), email, password, role, tenant_id)?;
+        self.save_user(&user)?;
 
         self.users.insert(user.id, user.clone());
         self.username_index.insert(username, user.id);
@@ -263,16 +637,31 @@ impl AuthManager {
         Ok(user)
     }
 
-    /// Authenticate user with username and password
-    pub fn authenticate(&self, username: &str, password: &str) -> Result<String> {
-        let user_id = self
+    /// Use custom access and refresh token lifetimes
+    pub fn with_session_config(mut self, session_config: SessionConfig) -> Self {
+        self.session_config = session_config;
+        self
+    }
+
+    /// Accept logins and ID tokens from an OpenID Connect provider
+    pub fn with_oidc(mut self, provider: Arc<OidcProvider>) -> Self {
+        self.oidc = Some(provider);
+        self
+    }
+
+    /// The configured OpenID Connect provider
+    pub fn oidc(&self) -> Option<&Arc<OidcProvider>> {
+        self.oidc.as_ref()
+    }
+
+    fn verify_credentials(&self, username: &str, password: &str) -> Result<User> {
+        let user_id = *self
             .username_index
             .get(username)
             .ok_or_else(|| AllSourceError::ValidationError("Invalid credentials".to_string()))?;
 
         let user = self
-            .users
-            .get(&user_id)
+            .get_user(&user_id)
             .ok_or_else(|| AllSourceError::ValidationError("User not found".to_string()))?;
 
         if !user.active {
@@ -287,30 +676,257 @@ impl AuthManager {
             ));
         }
 
-        // Generate JWT token
-        let claims = Claims::new(
+        Ok(user)
+    }
+
+    fn access_claims(&self, user: &User) -> Claims {
+        Claims::new(
             user.id.to_string(),
             user.tenant_id.clone(),
             user.role.clone(),
-            Duration::hours(24), // Token expires in 24 hours
-        );
+            Duration::seconds(self.session_config.access_token_ttl_secs),
+        )
+    }
 
-        let token = encode(&Header::default(), &claims, &self.encoding_key)
-            .map_err(|e| AllSourceError::ValidationError(format!("Failed to create token: {}", e)))?;
+    /// Authenticate user with username and password
+    ///
+    /// Returns a standalone access token; use `login` for a refreshable session.
+    pub fn authenticate(&self, username: &str, password: &str) -> Result<String> {
+        let user = self.verify_credentials(username, password)?;
+        self.jwt_keys.sign(&self.access_claims(&user))
+    }
+
+    /// Authenticate user and start a session with an access and refresh token
+    pub fn login(&self, username: &str, password: &str) -> Result<TokenPair> {
+        let user = self.verify_credentials(username, password)?;
+        self.prune_sessions();
+        self.issue_tokens(&user, Uuid::new_v4().to_string())
+    }
+
+    fn issue_tokens(&self, user: &User, session_id: String) -> Result<TokenPair> {
+        let access_token = self
+            .jwt_keys
+            .sign(&self.access_claims(user).with_session(session_id.clone()))?;
 
-        Ok(token)
+        let refresh_token = generate_refresh_token();
+        let now = Utc::now();
+        self.save_refresh_token(RefreshToken {
+            session_id: session_id.clone(),
+            user_id: user.id,
+            token_hash: hash_api_key(&refresh_token),
+            issued_at: now,
+            expires_at: now + Duration::seconds(self.session_config.refresh_token_ttl_secs),
+            used_at: None,
+        })?;
+
+        Ok(TokenPair {
+            access_token,
+            refresh_token,
+            expires_in: self.session_config.access_token_ttl_secs,
+            session_id,
+        })
+    }
+
+    /// Exchange a refresh token for a new access and refresh token
+    ///
+    /// Reusing an already exchanged refresh token revokes its whole session.
+    pub fn refresh(&self, refresh_token: &str) -> Result<TokenPair> {
+        let invalid = || AllSourceError::ValidationError("Invalid refresh token".to_string());
+        let now = Utc::now();
+
+        let token = self.find_refresh_token(&hash_api_key(refresh_token))?.ok_or_else(invalid)?;
+        if token.used_at.is_none() && now > token.expires_at {
+            return Err(AllSourceError::ValidationError("Refresh token expired".to_string()));
+        }
+        // Losing the race to mark the token (to another request, possibly on
+        // another node) counts as reuse as well
+        if token.used_at.is_some() || !self.mark_refresh_token_used(&token.token_hash, now)? {
+            self.revoke_session(&token.session_id)?;
+            tracing::warn!("🚨 Refresh token reuse detected; session {} revoked", token.session_id);
+            return Err(AllSourceError::ValidationError(
+                "Refresh token reuse detected; session revoked".to_string(),
+            ));
+        }
+
+        let user = match self.get_user(&token.user_id) {
+            Some(user) if user.active => user,
+            _ => {
+                self.revoke_session(&token.session_id)?;
+                return Err(AllSourceError::ValidationError(
+                    "User account is inactive".to_string(),
+                ));
+            }
+        };
+
+        self.issue_tokens(&user, token.session_id)
+    }
+
+    /// End a session: revoke the presented access token and, if it belongs to
+    /// one, its session (including every refresh token issued for it)
+    pub fn logout(&self, claims: &Claims) -> Result<()> {
+        if !claims.jti.is_empty() {
+            self.save_revocation(Revocation {
+                kind: RevocationKind::Token,
+                id: claims.jti.clone(),
+                expires_at: chrono::DateTime::from_timestamp(claims.exp, 0).unwrap_or_else(Utc::now),
+            })?;
+        }
+        if let Some(session_id) = &claims.sid {
+            self.revoke_session(session_id)?;
+        }
+        Ok(())
+    }
+
+    /// Revoke a session: its refresh tokens stop working immediately and its
+    /// access tokens are rejected until they expire
+    pub fn revoke_session(&self, session_id: &str) -> Result<()> {
+        // Revoke before deleting the refresh tokens: access tokens issued by a
+        // refresh in between carry the revoked session ID
+        self.save_revocation(Revocation {
+            kind: RevocationKind::Session,
+            id: session_id.to_string(),
+            expires_at: Utc::now() + Duration::seconds(self.session_config.access_token_ttl_secs),
+        })?;
+        match &self.repositories {
+            Some(repositories) => block_on(repositories.sessions.delete_session_refresh_tokens(session_id)),
+            None => {
+                self.refresh_tokens.retain(|_, token| token.session_id != session_id);
+                Ok(())
+            }
+        }
+    }
+
+    /// Revoke every session of a user
+    pub fn revoke_user_sessions(&self, user_id: &Uuid) -> Result<()> {
+        let session_ids: std::collections::HashSet<String> = match &self.repositories {
+            Some(repositories) => block_on(repositories.sessions.find_refresh_tokens_by_user(user_id))?
+                .into_iter()
+                .map(|token| token.session_id)
+                .collect(),
+            None => self
+                .refresh_tokens
+                .iter()
+                .filter(|entry| &entry.value().user_id == user_id)
+                .map(|entry| entry.value().session_id.clone())
+                .collect(),
+        };
+        for session_id in session_ids {
+            self.revoke_session(&session_id)?;
+        }
+        Ok(())
+    }
+
+    /// Session and user a refresh token was issued to, if it is known
+    pub fn refresh_token_session(&self, refresh_token: &str) -> Result<Option<(String, Uuid)>> {
+        Ok(self
+            .find_refresh_token(&hash_api_key(refresh_token))?
+            .map(|token| (token.session_id, token.user_id)))
+    }
+
+    /// Drop expired refresh tokens and revocations that no longer matter
+    /// from memory; `sync_sessions` prunes the session repository
+    pub fn prune_sessions(&self) {
+        let now = Utc::now();
+        self.refresh_tokens.retain(|_, token| token.expires_at > now);
+        self.revoked_sessions.retain(|_, until| *until > now);
+        self.revoked_tokens.retain(|_, expires_at| *expires_at > now);
+    }
+
+    /// Prune the session repository and load the revocations made by other
+    /// nodes into the cache
+    pub async fn sync_sessions(&self) -> Result<()> {
+        let Some(repositories) = &self.repositories else {
+            return Ok(());
+        };
+        let now = Utc::now();
+        repositories.sessions.prune(now).await?;
+        for revocation in repositories.sessions.find_revocations(now).await? {
+            self.cache_revocation(revocation);
+        }
+        self.prune_sessions();
+        Ok(())
+    }
+
+    /// Spawn the background task that runs `sync_sessions` every
+    /// `SESSION_SYNC_INTERVAL_SECS`
+    pub fn spawn_session_sync(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let manager = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(std::time::Duration::from_secs(SESSION_SYNC_INTERVAL_SECS));
+            loop {
+                interval.tick().await;
+                if let Err(e) = manager.sync_sessions().await {
+                    tracing::error!("❌ Session sync failed: {}", e);
+                }
+            }
+        })
     }
 
     /// Validate JWT token
+    ///
+    /// Rejects tokens that were revoked by logout or whose session was revoked.
     pub fn validate_token(&self, token: &str) -> Result<Claims> {
-        let token_data = decode::<Claims>(token, &self.decoding_key, &self.validation)
-            .map_err(|e| AllSourceError::ValidationError(format!("Invalid token: {}", e)))?;
+        let claims: Claims = self.jwt_keys.verify(token)?;
 
-        if token_data.claims.is_expired() {
+        if claims.is_expired() {
             return Err(AllSourceError::ValidationError("Token expired".to_string()));
         }
 
-        Ok(token_data.claims)
+        self.check_not_revoked(&claims)?;
+        Ok(claims)
+    }
+
+    /// Validate a bearer token: one of our JWTs, or an ID token issued by the
+    /// configured OpenID Connect provider
+    pub async fn validate_bearer(&self, token: &str) -> Result<Claims> {
+        match &self.oidc {
+            Some(oidc) if oidc.is_issuer_of(token) => {
+                let claims = oidc.authenticate_bearer(token).await?.claims;
+                self.check_not_revoked(&claims)?;
+                Ok(claims)
+            }
+            _ => self.validate_token(token),
+        }
+    }
+
+    fn check_not_revoked(&self, claims: &Claims) -> Result<()> {
+        let session_revoked = claims
+            .sid
+            .as_ref()
+            .is_some_and(|sid| self.revoked_sessions.contains_key(sid));
+        let token_revoked = !claims.jti.is_empty() && self.revoked_tokens.contains_key(&claims.jti);
+        if session_revoked || token_revoked {
+            return Err(AllSourceError::ValidationError("Token revoked".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Finish an OpenID Connect login and issue an access token for the identity
+    pub async fn complete_oidc_login(&self, code: &str, state: &str) -> Result<(String, ExternalIdentity)> {
+        let oidc = self
+            .oidc
+            .as_ref()
+            .ok_or_else(|| AllSourceError::ValidationError("OIDC login is not configured".to_string()))?;
+        let (identity, _) = oidc.complete_login(code, state).await?;
+
+        let claims = Claims::new(
+            identity.claims.sub.clone(),
+            identity.claims.tenant_id.clone(),
+            identity.claims.role.clone(),
+            Duration::seconds(self.session_config.access_token_ttl_secs),
+        );
+        Ok((self.jwt_keys.sign(&claims)?, identity))
+    }
+
+    /// Access token lifetime in seconds
+    pub fn access_token_ttl_secs(&self) -> i64 {
+        self.session_config.access_token_ttl_secs
+    }
+
+    /// JWT signing keys (for publishing the JWKS)
+    pub fn jwt_keys(&self) -> &Arc<JwtKeyManager> {
+        &self.jwt_keys
     }
 
     /// Create API key
@@ -320,36 +936,72 @@ impl AuthManager {
         tenant_id: String,
         role: Role,
         expires_at: Option<chrono::DateTime<Utc>>,
-    ) -> (ApiKey, String) {
-        let (api_key, key) = ApiKey::new(name, tenant_id, role, expires_at);
+    ) -> Result<(ApiKey, String)> {
+        self.create_scoped_api_key(name, tenant_id, role, expires_at, ApiKeyScopes::default())
+    }
+
+    /// Create API key restricted to the given scopes
+    pub fn create_scoped_api_key(
+        &self,
+        name: String,
+        tenant_id: String,
+        role: Role,
+        expires_at: Option<chrono::DateTime<Utc>>,
+        scopes: ApiKeyScopes,
+    ) -> Result<(ApiKey, String)> {
+        let (mut api_key, key) = ApiKey::new(name, tenant_id, role, expires_at);
+        api_key.scopes = scopes;
+        self.save_api_key(&api_key)?;
         self.api_keys.insert(api_key.id, api_key.clone());
-        (api_key, key)
+        self.key_hash_index.insert(api_key.key_hash.clone(), api_key.id);
+        Ok((api_key, key))
     }
 
     /// Validate API key
     pub fn validate_api_key(&self, key: &str) -> Result<Claims> {
-        for entry in self.api_keys.iter() {
-            let api_key = entry.value();
-            if api_key.verify(key) {
-                // Update last used timestamp
-                if let Some(mut key_mut) = self.api_keys.get_mut(&api_key.id) {
-                    key_mut.last_used = Some(Utc::now());
-                }
+        let invalid = || AllSourceError::ValidationError("Invalid API key".to_string());
 
-                let claims = Claims::new(
-                    api_key.id.to_string(),
-                    api_key.tenant_id.clone(),
-                    api_key.role.clone(),
-                    Duration::hours(24),
-                );
+        let key_id = *self.key_hash_index.get(&hash_api_key(key)).ok_or_else(invalid)?;
+        let mut api_key = self.api_keys.get_mut(&key_id).ok_or_else(invalid)?;
+        if !api_key.verify(key) {
+            return Err(invalid());
+        }
 
-                return Ok(claims);
-            }
+        // Update last used timestamp; persist it at most once per interval
+        let now = Utc::now();
+        let persist_last_used = api_key
+            .last_used
+            .is_none_or(|last| now - last >= Duration::seconds(LAST_USED_PERSIST_INTERVAL_SECS));
+        api_key.last_used = Some(now);
+
+        let claims = Claims::new(
+            api_key.id.to_string(),
+            api_key.tenant_id.clone(),
+            api_key.role.clone(),
+            Duration::hours(24),
+        )
+        .with_scopes(api_key.scopes.clone());
+        drop(api_key);
+
+        if persist_last_used {
+            self.persist(AuthWrite::TouchApiKey(key_id, now));
         }
 
-        Err(AllSourceError::ValidationError(
-            "Invalid API key".to_string(),
-        ))
+        Ok(claims)
+    }
+
+    /// Check a scoped API key's own request budget
+    pub fn check_api_key_rate_limit(&self, key_id: &str, requests_per_minute: u32) -> RateLimitResult {
+        if !self.key_rate_limiter.has_config(key_id) {
+            self.key_rate_limiter.set_config(
+                key_id,
+                RateLimitConfig {
+                    requests_per_minute,
+                    burst_size: requests_per_minute,
+                },
+            );
+        }
+        self.key_rate_limiter.check_rate_limit(key_id)
     }
 
     /// Get user by ID
@@ -357,6 +1009,12 @@ impl AuthManager {
         self.users.get(user_id).map(|u| u.clone())
     }
 
+    /// Get user by username
+    pub fn get_user_by_username(&self, username: &str) -> Option<User> {
+        let user_id = *self.username_index.get(username)?;
+        self.get_user(&user_id)
+    }
+
     /// List all users (admin only)
     pub fn list_users(&self) -> Vec<User> {
         self.users.iter().map(|entry| entry.value().clone()).collect()
@@ -364,26 +1022,39 @@ impl AuthManager {
 
     /// Delete user
     pub fn delete_user(&self, user_id: &Uuid) -> Result<()> {
+        if !self.users.contains_key(user_id) {
+            return Err(AllSourceError::ValidationError(
+                "User not found".to_string(),
+            ));
+        }
+        if let Some(repositories) = &self.repositories {
+            block_on(repositories.users.delete(user_id))?;
+        }
+
         if let Some((_, user)) = self.users.remove(user_id) {
             self.username_index.remove(&user.username);
-            Ok(())
-        } else {
-            Err(AllSourceError::ValidationError(
-                "User not found".to_string(),
-            ))
         }
+        self.revoke_user_sessions(user_id)
     }
 
     /// Revoke API key
     pub fn revoke_api_key(&self, key_id: &Uuid) -> Result<()> {
+        let mut revoked = self.get_api_key(key_id).ok_or_else(|| {
+            AllSourceError::ValidationError("API key not found".to_string())
+        })?;
+        revoked.active = false;
+        self.save_api_key(&revoked)?;
+
         if let Some(mut api_key) = self.api_keys.get_mut(key_id) {
             api_key.active = false;
-            Ok(())
-        } else {
-            Err(AllSourceError::ValidationError(
-                "API key not found".to_string(),
-            ))
         }
+        self.key_rate_limiter.remove_config(&key_id.to_string());
+        Ok(())
+    }
+
+    /// Get API key by ID
+    pub fn get_api_key(&self, key_id: &Uuid) -> Option<ApiKey> {
+        self.api_keys.get(key_id).map(|k| k.clone())
     }
 
     /// List API keys for a tenant
@@ -400,9 +1071,37 @@ impl Default for AuthManager {
     fn default() -> Self {
         // Generate a random secret for development
         // In production, this should come from configuration
-        use base64::{Engine as _, engine::general_purpose};
-        let secret = general_purpose::STANDARD.encode(rand::random::<[u8; 32]>());
-        Self::new(&secret)
+        Self::new(&generate_jwt_secret())
+    }
+}
+
+/// Generate a random JWT signing secret
+///
+/// Tokens signed with it become invalid when the process restarts.
+pub fn generate_jwt_secret() -> String {
+    use base64::{Engine as _, engine::general_purpose};
+    general_purpose::STANDARD.encode(rand::random::<[u8; 32]>())
+}
+
+/// Apply queued last-used times to the API key repository, in order
+async fn run_auth_writer(
+    api_key_repository: Arc<dyn ApiKeyRepository>,
+    mut rx: mpsc::UnboundedReceiver<AuthWrite>,
+) {
+    while let Some(write) = rx.recv().await {
+        let result = match write {
+            AuthWrite::TouchApiKey(id, last_used) => {
+                api_key_repository.update_last_used(&id, last_used).await.map(|_| ())
+            }
+            AuthWrite::Flush(done) => {
+                let _ = done.send(());
+                continue;
+            }
+        };
+
+        if let Err(e) = result {
+            tracing::error!("❌ Failed to persist API key last-used time: {}", e);
+        }
     }
 }
 
@@ -434,14 +1133,21 @@ fn generate_api_key() -> String {
     format!("ask_{}", general_purpose::URL_SAFE_NO_PAD.encode(random_bytes))
 }
 
-/// Hash API key for storage
+/// Generate refresh token
+fn generate_refresh_token() -> String {
+    use base64::{Engine as _, engine::general_purpose};
+    let random_bytes: [u8; 32] = rand::random();
+    format!("rt_{}", general_purpose::URL_SAFE_NO_PAD.encode(random_bytes))
+}
+
+/// Hash API key (or refresh token) for storage
+///
+/// SHA-256 keeps the hash stable across builds, so persisted keys remain
+/// valid after upgrades.
 fn hash_api_key(key: &str) -> String {
-    use std::collections::hash_map::DefaultHasher;
-    use std::hash::{Hash, Hasher};
+    use sha2::{Digest, Sha256};
 
-    let mut hasher = DefaultHasher::new();
-    key.hash(&mut hasher);
-    format!("{:x}", hasher.finish())
+    format!("{:x}", Sha256::digest(key.as_bytes()))
 }
 
 #[cfg(test)]
@@ -505,7 +1211,8 @@ mod tests {
             "tenant1".to_string(),
             Role::ServiceAccount,
             None,
-        );
+        )
+        .unwrap();
 
         // Validate key
         let claims = auth.validate_api_key(&key).unwrap();
@@ -519,6 +1226,356 @@ mod tests {
         assert!(auth.validate_api_key(&key).is_err());
     }
 
+    #[tokio::test]
+    async fn test_accounts_persist_across_managers() {
+        use crate::infrastructure::repositories::InMemoryAuthRepository;
+
+        let repo = Arc::new(InMemoryAuthRepository::new());
+        let jwt_keys = Arc::new(JwtKeyManager::from_secret("test_secret"));
+        let auth = AuthManager::with_repositories(jwt_keys.clone(), repo.clone(), repo.clone(), repo.clone())
+            .await
+            .unwrap();
+
+        let user = auth
+            .register_user(
+                "alice".to_string(),
+                "alice@example.com".to_string(),
+                "password123",
+                Role::Admin,
+                "tenant1".to_string(),
+            )
+            .unwrap();
+        let (revoked, _) = auth.create_api_key("old".to_string(), "tenant1".to_string(), Role::ReadOnly, None).unwrap();
+        let (service, key) =
+            auth.create_api_key("svc".to_string(), "tenant1".to_string(), Role::ServiceAccount, None).unwrap();
+        auth.revoke_api_key(&revoked.id).unwrap();
+        auth.validate_api_key(&key).unwrap();
+        auth.flush().await;
+
+        // A restarted manager sees the same accounts
+        let restarted = AuthManager::with_repositories(jwt_keys, repo.clone(), repo.clone(), repo.clone())
+            .await
+            .unwrap();
+        assert!(restarted.authenticate("alice", "password123").is_ok());
+        assert_eq!(restarted.validate_api_key(&key).unwrap().sub, service.id.to_string());
+        assert!(!restarted.get_api_key(&revoked.id).unwrap().active);
+        assert!(restarted.get_api_key(&service.id).unwrap().last_used.is_some());
+
+        restarted.delete_user(&user.id).unwrap();
+        restarted.flush().await;
+        assert!(UserRepository::find_by_id(repo.as_ref(), &user.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_sessions_are_shared_through_the_repository() {
+        use crate::infrastructure::repositories::InMemoryAuthRepository;
+
+        let repo = Arc::new(InMemoryAuthRepository::new());
+        let jwt_keys = Arc::new(JwtKeyManager::from_secret("test_secret"));
+        let first = AuthManager::with_repositories(jwt_keys.clone(), repo.clone(), repo.clone(), repo.clone())
+            .await
+            .unwrap();
+        first
+            .register_user(
+                "alice".to_string(),
+                "alice@example.com".to_string(),
+                "password123",
+                Role::Developer,
+                "tenant1".to_string(),
+            )
+            .unwrap();
+        let second = AuthManager::with_repositories(jwt_keys.clone(), repo.clone(), repo.clone(), repo.clone())
+            .await
+            .unwrap();
+
+        // A refresh token exchanged on one node is spent on the other, and
+        // reusing it revokes the session on both
+        let session = first.login("alice", "password123").unwrap();
+        let rotated = second.refresh(&session.refresh_token).unwrap();
+        assert!(first.refresh(&session.refresh_token).is_err());
+        assert!(second.refresh(&rotated.refresh_token).is_err());
+        second.sync_sessions().await.unwrap();
+        assert!(second.validate_token(&rotated.access_token).is_err());
+
+        // A logout reaches other nodes on their next sync, and survives a restart
+        let session = first.login("alice", "password123").unwrap();
+        let claims = second.validate_token(&session.access_token).unwrap();
+        second.logout(&claims).unwrap();
+        first.sync_sessions().await.unwrap();
+        assert!(first.validate_token(&session.access_token).is_err());
+
+        let restarted = AuthManager::with_repositories(jwt_keys, repo.clone(), repo.clone(), repo.clone())
+            .await
+            .unwrap();
+        assert!(restarted.validate_token(&session.access_token).is_err());
+        assert!(restarted.refresh(&session.refresh_token).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_account_changes_are_written_through() {
+        use crate::domain::repositories::ApiKeyRepository;
+        use crate::infrastructure::repositories::InMemoryAuthRepository;
+
+        let repo = Arc::new(InMemoryAuthRepository::new());
+        let jwt_keys = Arc::new(JwtKeyManager::from_secret("test_secret"));
+        let first = AuthManager::with_repositories(jwt_keys.clone(), repo.clone(), repo.clone(), repo.clone())
+            .await
+            .unwrap();
+        let second = AuthManager::with_repositories(jwt_keys, repo.clone(), repo.clone(), repo.clone())
+            .await
+            .unwrap();
+
+        // Changes are in the repository as soon as the call returns
+        let user = first
+            .register_user(
+                "alice".to_string(),
+                "alice@example.com".to_string(),
+                "password123",
+                Role::Admin,
+                "tenant1".to_string(),
+            )
+            .unwrap();
+        assert!(UserRepository::find_by_id(repo.as_ref(), &user.id).await.unwrap().is_some());
+
+        let (api_key, key) = first
+            .create_api_key("svc".to_string(), "tenant1".to_string(), Role::ServiceAccount, None)
+            .unwrap();
+        first.revoke_api_key(&api_key.id).unwrap();
+        assert!(!ApiKeyRepository::find_by_id(repo.as_ref(), &api_key.id).await.unwrap().unwrap().active);
+        assert!(first.validate_api_key(&key).is_err());
+
+        // A change the repository refuses fails and is not applied
+        let taken = second.register_user(
+            "alice".to_string(),
+            "other@example.com".to_string(),
+            "password123",
+            Role::Developer,
+            "tenant1".to_string(),
+        );
+        assert!(taken.is_err());
+        assert!(second.get_user_by_username("alice").is_none());
+
+        first.delete_user(&user.id).unwrap();
+        assert!(UserRepository::find_by_id(repo.as_ref(), &user.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_tokens_survive_key_rotation() {
+        use crate::security::jwt_keys::{JwtAlgorithm, JwtKeyConfig};
+
+        let jwt_keys = Arc::new(
+            JwtKeyManager::generate(JwtKeyConfig {
+                algorithm: JwtAlgorithm::ES256,
+                ..Default::default()
+            })
+            .unwrap(),
+        );
+        let auth = AuthManager::with_jwt_keys(jwt_keys.clone());
+        auth.register_user(
+            "alice".to_string(),
+            "alice@example.com".to_string(),
+            "password123",
+            Role::Developer,
+            "tenant1".to_string(),
+        )
+        .unwrap();
+
+        let before = auth.authenticate("alice", "password123").unwrap();
+        jwt_keys.rotate().await.unwrap();
+        let after = auth.authenticate("alice", "password123").unwrap();
+
+        assert_eq!(auth.validate_token(&before).unwrap().tenant_id, "tenant1");
+        assert_eq!(auth.validate_token(&after).unwrap().tenant_id, "tenant1");
+        assert!(AuthManager::new("test_secret").validate_token(&after).is_err());
+    }
+
+    #[test]
+    fn test_scoped_api_keys() {
+        let auth = AuthManager::new("test_secret");
+        let scopes = ApiKeyScopes {
+            event_type_namespaces: vec!["order".to_string()],
+            entity_id_prefixes: vec!["order".to_string()],
+            operations: vec![ApiOperation::Read],
+            allowed_cidrs: vec!["10.0.0.0/8".parse().unwrap()],
+            rate_limit_per_minute: Some(2),
+        };
+        let (scoped, scoped_key) = auth.create_scoped_api_key(
+            "orders-reader".to_string(),
+            "tenant1".to_string(),
+            Role::ServiceAccount,
+            None,
+            scopes.clone(),
+        )
+        .unwrap();
+        let (_, plain_key) = auth.create_api_key(
+            "plain".to_string(),
+            "tenant1".to_string(),
+            Role::ServiceAccount,
+            None,
+        )
+        .unwrap();
+
+        let claims = auth.validate_api_key(&scoped_key).unwrap();
+        assert_eq!(claims.scopes.as_ref(), Some(&scopes));
+        assert!(auth.validate_api_key(&plain_key).unwrap().scopes.is_none());
+
+        assert!(scopes.allows_event_type("order.placed"));
+        assert!(!scopes.allows_event_type("user.created"));
+        assert!(!scopes.allows_event_type("order"));
+        assert!(scopes.allows_entity_id("order-42"));
+        assert!(!scopes.allows_entity_id("user-42"));
+        assert!(scopes.allows_operation(ApiOperation::Read));
+        assert!(!scopes.allows_operation(ApiOperation::Write));
+        assert!(scopes.allows_ip(&"10.1.2.3".parse().unwrap()));
+        assert!(!scopes.allows_ip(&"192.168.1.1".parse().unwrap()));
+
+        let event = Event::from_strings(
+            "order.placed".to_string(),
+            "user-42".to_string(),
+            "tenant1".to_string(),
+            serde_json::json!({}),
+            None,
+        )
+        .unwrap();
+        assert!(!claims.allows_event(&event));
+
+        let key_id = scoped.id.to_string();
+        assert!(auth.check_api_key_rate_limit(&key_id, 2).allowed);
+        assert!(auth.check_api_key_rate_limit(&key_id, 2).allowed);
+        assert!(!auth.check_api_key_rate_limit(&key_id, 2).allowed);
+    }
+
+    fn manager_with_alice() -> AuthManager {
+        let auth = AuthManager::new("test_secret");
+        auth.register_user(
+            "alice".to_string(),
+            "alice@example.com".to_string(),
+            "password123",
+            Role::Developer,
+            "tenant1".to_string(),
+        )
+        .unwrap();
+        auth
+    }
+
+    #[test]
+    fn test_refresh_rotates_tokens() {
+        let auth = manager_with_alice();
+        let first = auth.login("alice", "password123").unwrap();
+        let claims = auth.validate_token(&first.access_token).unwrap();
+        assert_eq!(claims.sid.as_deref(), Some(first.session_id.as_str()));
+        assert_eq!(first.expires_in, SessionConfig::default().access_token_ttl_secs);
+
+        let second = auth.refresh(&first.refresh_token).unwrap();
+        assert_eq!(second.session_id, first.session_id);
+        assert_ne!(second.refresh_token, first.refresh_token);
+        assert!(auth.validate_token(&second.access_token).is_ok());
+
+        // The rotated refresh token keeps working
+        assert!(auth.refresh(&second.refresh_token).is_ok());
+        assert!(auth.refresh("rt_unknown").is_err());
+    }
+
+    #[test]
+    fn test_refresh_token_reuse_revokes_session() {
+        let auth = manager_with_alice();
+        let other_session = auth.login("alice", "password123").unwrap();
+        let first = auth.login("alice", "password123").unwrap();
+        let second = auth.refresh(&first.refresh_token).unwrap();
+
+        // Replaying the exchanged token revokes the whole family
+        let err = auth.refresh(&first.refresh_token).unwrap_err();
+        assert!(err.to_string().contains("reuse"));
+        assert!(auth.refresh(&second.refresh_token).is_err());
+        assert!(auth.validate_token(&second.access_token).is_err());
+        assert!(auth.validate_token(&first.access_token).is_err());
+
+        // Other sessions are unaffected
+        assert!(auth.validate_token(&other_session.access_token).is_ok());
+        assert!(auth.refresh(&other_session.refresh_token).is_ok());
+    }
+
+    #[test]
+    fn test_logout_revokes_token_and_session() {
+        let auth = manager_with_alice();
+        let session = auth.login("alice", "password123").unwrap();
+        let standalone = auth.authenticate("alice", "password123").unwrap();
+
+        let claims = auth.validate_token(&session.access_token).unwrap();
+        auth.logout(&claims).unwrap();
+        assert!(auth.validate_token(&session.access_token).is_err());
+        assert!(auth.refresh(&session.refresh_token).is_err());
+
+        // Tokens without a session are revoked individually
+        let standalone_claims = auth.validate_token(&standalone).unwrap();
+        assert!(standalone_claims.sid.is_none());
+        auth.logout(&standalone_claims).unwrap();
+        assert!(auth.validate_token(&standalone).is_err());
+    }
+
+    #[test]
+    fn test_deleted_user_cannot_refresh() {
+        let auth = manager_with_alice();
+        let session = auth.login("alice", "password123").unwrap();
+        let user = auth.get_user_by_username("alice").unwrap();
+
+        auth.delete_user(&user.id).unwrap();
+        assert!(auth.refresh(&session.refresh_token).is_err());
+        assert!(auth.validate_token(&session.access_token).is_err());
+    }
+
+    #[test]
+    fn test_expired_refresh_token_rejected() {
+        let auth = manager_with_alice().with_session_config(SessionConfig {
+            access_token_ttl_secs: 60,
+            refresh_token_ttl_secs: -1,
+        });
+        let session = auth.login("alice", "password123").unwrap();
+        assert!(auth.refresh(&session.refresh_token).is_err());
+
+        auth.prune_sessions();
+        assert!(auth.refresh_token_session(&session.refresh_token).unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_oidc_identities_get_standard_claims() {
+        use crate::security::oidc::tests::MockIdp;
+
+        let idp = MockIdp::start().await;
+        let auth = AuthManager::new("test_secret")
+            .with_oidc(Arc::new(OidcProvider::new(idp.config()).unwrap()));
+        let oidc = auth.oidc().unwrap().clone();
+
+        // Code flow: the provider identity is exchanged for one of our tokens
+        let request = oidc.begin_login().await.unwrap();
+        let code = idp.authorize(&request.authorization_url, idp.id_claims("alice", &["engineering"]));
+        let (token, identity) = auth.complete_oidc_login(&code, &request.state).await.unwrap();
+        assert_eq!(identity.username, "alice");
+        let claims = auth.validate_bearer(&token).await.unwrap();
+        assert_eq!(claims.iss, JWT_ISSUER);
+        assert!(claims.has_permission(Permission::Write));
+        assert!(!claims.has_permission(Permission::Admin));
+
+        // Provider ID tokens are accepted as bearer tokens too
+        let id_token = idp.sign(&idp.id_claims("root", &["allsource-admins"]));
+        let claims = auth.validate_bearer(&id_token).await.unwrap();
+        assert_eq!(claims.tenant_id, "acme");
+        assert!(claims.has_permission(Permission::Admin));
+        assert!(auth.validate_token(&id_token).is_err());
+
+        // Local tokens keep working alongside
+        auth.register_user(
+            "bob".to_string(),
+            "bob@example.com".to_string(),
+            "password123",
+            Role::ReadOnly,
+            "tenant1".to_string(),
+        )
+        .unwrap();
+        let local = auth.authenticate("bob", "password123").unwrap();
+        assert_eq!(auth.validate_bearer(&local).await.unwrap().role, Role::ReadOnly);
+    }
+
     #[test]
     fn test_claims_expiration() {
         let claims = Claims::new(
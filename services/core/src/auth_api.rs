@@ -1,8 +1,12 @@
-use crate::auth::{AuthManager, Permission, Role, User};
-use crate::middleware::{Admin, Authenticated};
+use crate::auth::{ApiKeyScopes, AuthManager, Permission, Role, User};
+use crate::domain::entities::{Actor, AuditAction, AuditOutcome};
+use crate::domain::value_objects::TenantId;
+use crate::error::AllSourceError;
+use crate::middleware::{resolve_actor, Admin, AuditRequestContext, Authenticated};
 use axum::{
-    extract::State,
-    http::StatusCode,
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Redirect},
     Json,
 };
 use serde::{Deserialize, Serialize};
@@ -43,9 +47,25 @@ pub struct LoginRequest {
 #[derive(Debug, Serialize)]
 pub struct LoginResponse {
     pub token: String,
+    pub refresh_token: String,
+    /// Access token lifetime in seconds
+    pub expires_in: i64,
     pub user: UserInfo,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RefreshResponse {
+    pub token: String,
+    pub refresh_token: String,
+    /// Access token lifetime in seconds
+    pub expires_in: i64,
+}
+
 #[derive(Debug, Serialize)]
 pub struct UserInfo {
     pub id: Uuid,
@@ -67,11 +87,37 @@ impl From<User> for UserInfo {
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct OidcCallbackQuery {
+    pub code: String,
+    pub state: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OidcLoginResponse {
+    pub token: String,
+    /// Access token lifetime in seconds
+    pub expires_in: i64,
+    pub user: ExternalUserInfo,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExternalUserInfo {
+    /// Subject at the identity provider
+    pub subject: String,
+    pub username: String,
+    pub email: Option<String>,
+    pub role: Role,
+    pub tenant_id: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreateApiKeyRequest {
     pub name: String,
     pub role: Option<Role>,
     pub expires_in_days: Option<i64>,
+    /// Restrictions on the key (None = only those of the role)
+    pub scopes: Option<ApiKeyScopes>,
 }
 
 #[derive(Debug, Serialize)]
@@ -92,12 +138,24 @@ pub struct ApiKeyInfo {
     pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
     pub active: bool,
     pub last_used: Option<chrono::DateTime<chrono::Utc>>,
+    pub scopes: ApiKeyScopes,
 }
 
 // ============================================================================
 // Handlers
 // ============================================================================
 
+/// Report a failed account change with `status`, or as a server error when
+/// the change could not be persisted
+fn account_error(status: StatusCode) -> impl FnOnce(AllSourceError) -> (StatusCode, String) {
+    move |e| match e {
+        AllSourceError::StorageError(_) | AllSourceError::InternalError(_) => {
+            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+        }
+        _ => (status, e.to_string()),
+    }
+}
+
 /// Register a new user
 /// POST /api/v1/auth/register
 pub async fn register_handler(
@@ -119,7 +177,7 @@ pub async fn register_handler(
     let user = state
         .auth_manager
         .register_user(req.username, req.email, &req.password, role.clone(), tenant_id.clone())
-        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+        .map_err(account_error(StatusCode::BAD_REQUEST))?;
 
     Ok((
         StatusCode::CREATED,
@@ -135,19 +193,41 @@ pub async fn register_handler(
 
 /// Login with username and password
 /// POST /api/v1/auth/login
+///
+/// Records `Login` / `LoginFailed` audit events; the audit middleware skips
+/// this route because a failed login has no authenticated actor.
 pub async fn login_handler(
     State(state): State<AppState>,
+    request_context: AuditRequestContext,
     Json(req): Json<LoginRequest>,
 ) -> Result<Json<LoginResponse>, (StatusCode, String)> {
-    let token = state
-        .auth_manager
-        .authenticate(&req.username, &req.password)
-        .map_err(|e| (StatusCode::UNAUTHORIZED, e.to_string()))?;
+    let tokens = match state.auth_manager.login(&req.username, &req.password) {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            if let Some(user) = state.auth_manager.get_user_by_username(&req.username) {
+                if let Ok(tenant_id) = TenantId::new(user.tenant_id.clone()) {
+                    state
+                        .audit_logger
+                        .log(
+                            tenant_id,
+                            AuditAction::LoginFailed,
+                            Actor::user(user.id.to_string(), user.username),
+                        )
+                        .with_outcome(AuditOutcome::Failure)
+                        .with_context(request_context.0)
+                        .with_error(e.to_string())
+                        .record_silently()
+                        .await;
+                }
+            }
+            return Err((StatusCode::UNAUTHORIZED, e.to_string()));
+        }
+    };
 
     // Get user info
     let user_id = state
         .auth_manager
-        .validate_token(&token)
+        .validate_token(&tokens.access_token)
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
         .sub
         .parse::<Uuid>()
@@ -158,12 +238,167 @@ pub async fn login_handler(
         .get_user(&user_id)
         .ok_or_else(|| (StatusCode::NOT_FOUND, "User not found".to_string()))?;
 
+    if let Ok(tenant_id) = TenantId::new(user.tenant_id.clone()) {
+        state
+            .audit_logger
+            .log(
+                tenant_id,
+                AuditAction::Login,
+                Actor::user(user.id.to_string(), user.username.clone()),
+            )
+            .with_resource("session".to_string(), tokens.session_id.clone())
+            .with_context(request_context.0)
+            .record_silently()
+            .await;
+    }
+
     Ok(Json(LoginResponse {
-        token,
+        token: tokens.access_token,
+        refresh_token: tokens.refresh_token,
+        expires_in: tokens.expires_in,
         user: user.into(),
     }))
 }
 
+/// Start an OpenID Connect login (no auth)
+/// GET /api/v1/auth/oidc/login
+///
+/// Redirects the browser to the identity provider.
+pub async fn oidc_login_handler(State(state): State<AppState>) -> Result<Redirect, (StatusCode, String)> {
+    let oidc = state
+        .auth_manager
+        .oidc()
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "OIDC login is not configured".to_string()))?;
+
+    let request = oidc
+        .begin_login()
+        .await
+        .map_err(|e| (StatusCode::BAD_GATEWAY, e.to_string()))?;
+
+    Ok(Redirect::to(&request.authorization_url))
+}
+
+/// Complete an OpenID Connect login (no auth)
+/// GET /api/v1/auth/oidc/callback?code=..&state=..
+///
+/// Exchanges the authorization code, maps the ID token to a role and tenant
+/// and returns an AllSource access token. Records a `Login` audit event.
+pub async fn oidc_callback_handler(
+    State(state): State<AppState>,
+    request_context: AuditRequestContext,
+    Query(query): Query<OidcCallbackQuery>,
+) -> Result<Json<OidcLoginResponse>, (StatusCode, String)> {
+    let (token, identity) = state
+        .auth_manager
+        .complete_oidc_login(&query.code, &query.state)
+        .await
+        .map_err(|e| (StatusCode::UNAUTHORIZED, e.to_string()))?;
+
+    if let Ok(tenant_id) = TenantId::new(identity.claims.tenant_id.clone()) {
+        state
+            .audit_logger
+            .log(
+                tenant_id,
+                AuditAction::Login,
+                Actor::user(identity.claims.sub.clone(), identity.username.clone()),
+            )
+            .with_context(request_context.0)
+            .with_metadata(serde_json::json!({ "provider": identity.claims.iss }))
+            .record_silently()
+            .await;
+    }
+
+    Ok(Json(OidcLoginResponse {
+        token,
+        expires_in: state.auth_manager.access_token_ttl_secs(),
+        user: ExternalUserInfo {
+            subject: identity.claims.sub,
+            username: identity.username,
+            email: identity.email,
+            role: identity.claims.role,
+            tenant_id: identity.claims.tenant_id,
+        },
+    }))
+}
+
+/// Exchange a refresh token for a new access and refresh token
+/// POST /api/v1/auth/refresh
+///
+/// The refresh token is the credential, so this route is served without
+/// auth. Records `TokenRefreshed` audit events, including failed attempts
+/// such as refresh token reuse (which revokes the session).
+pub async fn refresh_handler(
+    State(state): State<AppState>,
+    request_context: AuditRequestContext,
+    Json(req): Json<RefreshRequest>,
+) -> Result<Json<RefreshResponse>, (StatusCode, String)> {
+    let session = state
+        .auth_manager
+        .refresh_token_session(&req.refresh_token)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let result = state.auth_manager.refresh(&req.refresh_token);
+
+    if let Some((session_id, user)) =
+        session.and_then(|(session_id, user_id)| Some((session_id, state.auth_manager.get_user(&user_id)?)))
+    {
+        if let Ok(tenant_id) = TenantId::new(user.tenant_id.clone()) {
+            let mut entry = state
+                .audit_logger
+                .log(
+                    tenant_id,
+                    AuditAction::TokenRefreshed,
+                    Actor::user(user.id.to_string(), user.username),
+                )
+                .with_resource("session".to_string(), session_id)
+                .with_context(request_context.0);
+            if let Err(e) = &result {
+                entry = entry.with_outcome(AuditOutcome::Failure).with_error(e.to_string());
+            }
+            entry.record_silently().await;
+        }
+    }
+
+    let tokens = result.map_err(|e| (StatusCode::UNAUTHORIZED, e.to_string()))?;
+    Ok(Json(RefreshResponse {
+        token: tokens.access_token,
+        refresh_token: tokens.refresh_token,
+        expires_in: tokens.expires_in,
+    }))
+}
+
+/// Log out: revoke the current access token and its session
+/// POST /api/v1/auth/logout
+///
+/// Records a `Logout` audit event.
+pub async fn logout_handler(
+    State(state): State<AppState>,
+    Authenticated(auth_ctx): Authenticated,
+    request_context: AuditRequestContext,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let result = state.auth_manager.logout(&auth_ctx.claims);
+
+    if let Ok(tenant_id) = TenantId::new(auth_ctx.claims.tenant_id.clone()) {
+        let mut entry = state
+            .audit_logger
+            .log(
+                tenant_id,
+                AuditAction::Logout,
+                resolve_actor(&state.auth_manager, &auth_ctx),
+            )
+            .with_context(request_context.0);
+        if let Some(session_id) = &auth_ctx.claims.sid {
+            entry = entry.with_resource("session".to_string(), session_id.clone());
+        }
+        if let Err(e) = &result {
+            entry = entry.with_outcome(AuditOutcome::Failure).with_error(e.to_string());
+        }
+        entry.record_silently().await;
+    }
+
+    result.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
 /// Get current user info
 /// GET /api/v1/auth/me
 pub async fn me_handler(
@@ -202,9 +437,26 @@ pub async fn create_api_key_handler(
         chrono::Utc::now() + chrono::Duration::days(days)
     });
 
-    let (api_key, key) = state
-        .auth_manager
-        .create_api_key(req.name.clone(), auth_ctx.tenant_id().to_string(), role, expires_at);
+    // A scoped key can only mint keys with its own scopes
+    let scopes = match (&auth_ctx.claims.scopes, req.scopes) {
+        (Some(own), Some(requested)) if &requested != own => {
+            return Err((
+                StatusCode::FORBIDDEN,
+                "Scoped API keys cannot create keys with different scopes".to_string(),
+            ));
+        }
+        (Some(own), _) => own.clone(),
+        (None, requested) => requested.unwrap_or_default(),
+    };
+
+    let (api_key, key) = state.auth_manager.create_scoped_api_key(
+        req.name.clone(),
+        auth_ctx.tenant_id().to_string(),
+        role,
+        expires_at,
+        scopes,
+    )
+    .map_err(account_error(StatusCode::BAD_REQUEST))?;
 
     Ok((
         StatusCode::CREATED,
@@ -236,6 +488,7 @@ pub async fn list_api_keys_handler(
             expires_at: k.expires_at,
             active: k.active,
             last_used: k.last_used,
+            scopes: k.scopes,
         })
         .collect();
 
@@ -256,7 +509,7 @@ pub async fn revoke_api_key_handler(
     state
         .auth_manager
         .revoke_api_key(&key_id)
-        .map_err(|e| (StatusCode::NOT_FOUND, e.to_string()))?;
+        .map_err(account_error(StatusCode::NOT_FOUND))?;
 
     Ok(StatusCode::NO_CONTENT)
 }
@@ -281,7 +534,16 @@ pub async fn delete_user_handler(
     state
         .auth_manager
         .delete_user(&user_id)
-        .map_err(|e| (StatusCode::NOT_FOUND, e.to_string()))?;
+        .map_err(account_error(StatusCode::NOT_FOUND))?;
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+/// Public keys for verifying issued tokens (no auth)
+/// GET /.well-known/jwks.json
+pub async fn jwks_handler(State(state): State<AppState>) -> impl IntoResponse {
+    (
+        [(header::CACHE_CONTROL, "public, max-age=300")],
+        Json(state.auth_manager.jwt_keys().jwks()),
+    )
+}
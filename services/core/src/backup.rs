@@ -2,20 +2,47 @@
 ///
 /// Features:
 /// - Full backups of all event data
-/// - Incremental backups from checkpoint
+/// - Incremental backups of the events committed since a previous backup
+/// - Point-in-time recovery from a full backup and its incrementals
 /// - Compressed backup files (gzip)
+/// - Envelope encryption through the KMS: each backup gets its own data key,
+///   wrapped by a KMS key and recorded in the metadata
 /// - Metadata tracking
-/// - Verification and integrity checks
-/// - Support for filesystem and S3-compatible storage
+/// - Verification and integrity checks, including whole backup chains
+/// - Online backup and restore jobs with progress reporting
+/// - Copies kept in an object store (local directory or S3-compatible),
+///   fetched back on demand when the local copy is gone
+/// - Retention policies that never break a chain a kept backup needs
+///
+/// Backups cover ranges of event sequences: positions in the event log,
+/// starting at 1 (see `EventStore::current_sequence`). A full backup holds
+/// sequences `1..=to_sequence`; an incremental holds
+/// `from_sequence + 1..=to_sequence`, where `from_sequence` is the
+/// `to_sequence` of the backup it extends. Sequences of events removed by
+/// retention leave gaps, so a backup may hold fewer events than its range.
 
 use crate::error::{AllSourceError, Result};
 use crate::domain::entities::Event;
+use crate::domain::value_objects::TenantId;
+use crate::infrastructure::persistence::object_store::{
+    self, ObjectStore, ObjectStoreConfig, RetentionPolicy,
+};
+use crate::security::kms::{KeyAlgorithm, KmsConfig, KmsManager};
+use aes_gcm::aead::{Aead, OsRng, Payload};
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
+use base64::{engine::general_purpose, Engine as _};
+use crate::store::EventStore;
 use chrono::{DateTime, Utc};
 use flate2::{write::GzEncoder, read::GzDecoder, Compression};
+use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::fs::{self, File};
-use std::io::{Read, Write};
+use std::io::{BufReader, BufWriter, Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use uuid::Uuid;
 
 /// Backup metadata
@@ -27,9 +54,40 @@ pub struct BackupMetadata {
     pub event_count: u64,
     pub size_bytes: u64,
     pub checksum: String,
+    /// Sequence this backup continues from (exclusive); `None` for full backups
     pub from_sequence: Option<u64>,
+    /// Sequence of the last event in the backup
     pub to_sequence: u64,
     pub compressed: bool,
+    /// How the backup file is encrypted; `None` for plain gzip
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<BackupEncryptionInfo>,
+}
+
+/// Envelope encryption of a backup file
+///
+/// The compressed backup is encrypted with a random data key in chunks of
+/// `ENCRYPTION_CHUNK_SIZE`; the data key is stored wrapped by the KMS key.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupEncryptionInfo {
+    /// KMS key that wrapped the data key
+    pub key_id: String,
+    /// Version of the KMS key when the backup was taken
+    pub key_version: u32,
+    pub algorithm: KeyAlgorithm,
+    /// Data key as wrapped by the KMS, base64
+    pub encrypted_data_key: String,
+    /// Random prefix of the chunk nonces, base64
+    pub nonce_prefix: String,
+}
+
+/// Backup encryption settings (`[backup.encryption]` table)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupEncryptionConfig {
+    /// KMS key wrapping the data keys of new backups
+    pub key_id: String,
+    #[serde(default)]
+    pub kms: KmsConfig,
 }
 
 /// Type of backup
@@ -45,6 +103,11 @@ pub struct BackupConfig {
     pub backup_dir: PathBuf,
     pub compression_level: Compression,
     pub verify_after_backup: bool,
+    /// Where every backup is copied once written; `backup_dir` then acts as
+    /// a local cache
+    pub object_store: Option<ObjectStoreConfig>,
+    /// Applied after each backup
+    pub retention: RetentionPolicy,
 }
 
 impl Default for BackupConfig {
@@ -53,13 +116,26 @@ impl Default for BackupConfig {
             backup_dir: PathBuf::from("./backups"),
             compression_level: Compression::default(),
             verify_after_backup: true,
+            object_store: None,
+            retention: RetentionPolicy::default(),
         }
     }
 }
 
+/// Key prefix of backups in the object store
+const OBJECT_PREFIX: &str = "backups/";
+
 /// Backup manager
 pub struct BackupManager {
     config: BackupConfig,
+    object_store: Option<Arc<dyn ObjectStore>>,
+    encryption: Option<BackupEncryption>,
+}
+
+/// KMS key that new backups are encrypted under
+struct BackupEncryption {
+    kms: Arc<KmsManager>,
+    key_id: String,
 }
 
 impl BackupManager {
@@ -68,118 +144,220 @@ impl BackupManager {
         fs::create_dir_all(&config.backup_dir)
             .map_err(|e| AllSourceError::StorageError(format!("Failed to create backup dir: {}", e)))?;
 
-        Ok(Self { config })
+        let object_store = config.object_store.as_ref().map(|c| c.open()).transpose()?;
+
+        Ok(Self {
+            config,
+            object_store,
+            encryption: None,
+        })
+    }
+
+    /// Encrypt new backups under the KMS key `key_id`
+    ///
+    /// The KMS also unwraps the data keys of encrypted backups on restore,
+    /// whichever key (or key version) they were taken with.
+    pub fn with_encryption(mut self, kms: Arc<KmsManager>, key_id: impl Into<String>) -> Self {
+        self.encryption = Some(BackupEncryption {
+            kms,
+            key_id: key_id.into(),
+        });
+        self
     }
 
     /// Create a full backup from events
+    ///
+    /// `events` is the whole event log, so the backup covers sequences
+    /// `1..=events.len()`.
     pub fn create_backup(&self, events: &[Event]) -> Result<BackupMetadata> {
-        let backup_id = format!("full_{}", Uuid::new_v4());
-        let timestamp = Utc::now();
-
-        tracing::info!("Creating backup: {}", backup_id);
-
-        let event_count = events.len() as u64;
-
-        if event_count == 0 {
-            return Err(AllSourceError::ValidationError(
-                "No events to backup".to_string(),
-            ));
-        }
-
-        // Serialize events to JSON
-        let json_data = serde_json::to_string(&events)?;
+        self.create_backup_with_progress(None, events, None, &AtomicU64::new(0))
+    }
 
-        // Compress backup
-        let backup_path = self.get_backup_path(&backup_id);
-        let mut encoder = GzEncoder::new(
-            File::create(&backup_path)
-                .map_err(|e| AllSourceError::StorageError(format!("Failed to create backup file: {}", e)))?,
-            self.config.compression_level,
-        );
+    /// Create an incremental backup on top of `from_backup_id`
+    ///
+    /// `events` are the events committed after the parent backup's
+    /// `to_sequence`, e.g. `store.events_since(parent.to_sequence)`.
+    pub fn create_incremental_backup(
+        &self,
+        from_backup_id: &str,
+        events: &[Event],
+    ) -> Result<BackupMetadata> {
+        self.create_backup_with_progress(Some(from_backup_id), events, None, &AtomicU64::new(0))
+    }
 
-        encoder
-            .write_all(json_data.as_bytes())
-            .map_err(|e| AllSourceError::StorageError(format!("Failed to write backup: {}", e)))?;
+    /// Create a full backup, or an incremental one when `from_backup_id` is
+    /// given, counting the events written in `progress`.
+    ///
+    /// `to_sequence` is the last sequence covered; by default the events
+    /// are taken to follow the parent without gaps.
+    pub fn create_backup_with_progress(
+        &self,
+        from_backup_id: Option<&str>,
+        events: &[Event],
+        to_sequence: Option<u64>,
+        progress: &AtomicU64,
+    ) -> Result<BackupMetadata> {
+        let Some(from_backup_id) = from_backup_id else {
+            if events.is_empty() {
+                return Err(AllSourceError::ValidationError(
+                    "No events to backup".to_string(),
+                ));
+            }
 
-        encoder
-            .finish()
-            .map_err(|e| AllSourceError::StorageError(format!("Failed to finish compression: {}", e)))?;
+            let backup_id = format!("full_{}", Uuid::new_v4());
+            return self.write_backup(backup_id, BackupType::Full, None, to_sequence, events, progress);
+        };
 
-        let size_bytes = fs::metadata(&backup_path)
-            .map_err(|e| AllSourceError::StorageError(e.to_string()))?
-            .len();
+        // Never extend a chain that could not be restored
+        let chain = self.validate_chain(from_backup_id)?;
+        let parent = chain.last().expect("chain includes the requested backup");
 
-        let checksum = self.calculate_checksum(&backup_path)?;
+        if events.is_empty() {
+            return Err(AllSourceError::ValidationError(format!(
+                "No events since backup {}",
+                from_backup_id
+            )));
+        }
 
-        let metadata = BackupMetadata {
-            backup_id: backup_id.clone(),
-            created_at: timestamp,
-            backup_type: BackupType::Full,
-            event_count,
-            size_bytes,
-            checksum,
-            from_sequence: None,
-            to_sequence: event_count,
-            compressed: true,
-        };
+        let backup_id = format!("incr_{}", Uuid::new_v4());
+        self.write_backup(
+            backup_id,
+            BackupType::Incremental {
+                from_backup_id: from_backup_id.to_string(),
+            },
+            Some(parent.to_sequence),
+            to_sequence,
+            events,
+            progress,
+        )
+    }
 
-        // Save metadata
-        self.save_metadata(&metadata)?;
+    /// Restore from backup
+    ///
+    /// Incremental backups are restored together with the full backup and
+    /// incrementals they build on, so the result is always the event log
+    /// from sequence 1.
+    pub fn restore_from_backup(&self, backup_id: &str) -> Result<Vec<Event>> {
+        tracing::info!("Restoring from backup: {}", backup_id);
 
-        // Verify if configured
-        if self.config.verify_after_backup {
-            self.verify_backup(&metadata)?;
+        let chain = self.validate_chain(backup_id)?;
+        let mut events = Vec::new();
+        for metadata in &chain {
+            events.extend(self.read_backup(metadata)?);
         }
 
         tracing::info!(
-            "Backup complete: {} events, {} bytes compressed",
-            event_count,
-            size_bytes
+            "Restored {} events from {} backup(s)",
+            events.len(),
+            chain.len()
         );
 
-        Ok(metadata)
+        Ok(events)
     }
 
-    /// Restore from backup
-    pub fn restore_from_backup(&self, backup_id: &str) -> Result<Vec<Event>> {
-        tracing::info!("Restoring from backup: {}", backup_id);
+    /// Restore the events recorded at or before `until`
+    ///
+    /// Replays the chain ending at the newest backup and drops events with
+    /// later timestamps.
+    pub fn restore_to_point_in_time(&self, until: DateTime<Utc>) -> Result<Vec<Event>> {
+        let latest = self
+            .list_backups()?
+            .into_iter()
+            .next()
+            .ok_or_else(|| AllSourceError::ValidationError("No backups found".to_string()))?;
+
+        if latest.created_at < until {
+            tracing::warn!(
+                "Newest backup {} was taken at {}, before the requested point in time {}",
+                latest.backup_id,
+                latest.created_at,
+                until
+            );
+        }
 
-        let metadata = self.load_metadata(backup_id)?;
+        tracing::info!("Restoring to point in time {} from backup {}", until, latest.backup_id);
 
-        // Verify backup integrity
-        self.verify_backup(&metadata)?;
+        let mut events = self.restore_from_backup(&latest.backup_id)?;
+        events.retain(|event| event.timestamp <= until);
 
-        let backup_path = self.get_backup_path(backup_id);
+        Ok(events)
+    }
 
-        // Decompress backup
-        let file = File::open(&backup_path)
-            .map_err(|e| AllSourceError::StorageError(format!("Failed to open backup: {}", e)))?;
+    /// Check that a backup and everything it builds on can be restored
+    ///
+    /// Every backup in the chain must exist and match its checksum, the
+    /// chain must start at a full backup, and each incremental must pick up
+    /// exactly where its parent stopped. Returns the chain, full backup
+    /// first.
+    pub fn validate_chain(&self, backup_id: &str) -> Result<Vec<BackupMetadata>> {
+        let mut chain = Vec::new();
+        let mut seen = HashSet::new();
+        let mut next = Some(backup_id.to_string());
+
+        while let Some(id) = next.take() {
+            if !seen.insert(id.clone()) {
+                return Err(AllSourceError::ValidationError(format!(
+                    "Backup chain of {} contains a cycle at {}",
+                    backup_id, id
+                )));
+            }
 
-        let mut decoder = GzDecoder::new(file);
-        let mut json_data = String::new();
-        decoder
-            .read_to_string(&mut json_data)
-            .map_err(|e| AllSourceError::StorageError(format!("Failed to decompress backup: {}", e)))?;
+            let metadata = self.load_metadata(&id).map_err(|e| {
+                AllSourceError::ValidationError(format!(
+                    "Backup chain of {} is broken at {}: {}",
+                    backup_id, id, e
+                ))
+            })?;
+            self.verify_backup(&metadata)?;
 
-        // Deserialize events
-        let events: Vec<Event> = serde_json::from_str(&json_data)?;
+            let first_sequence = match (&metadata.backup_type, metadata.from_sequence) {
+                (BackupType::Full, None) => 0,
+                (BackupType::Incremental { from_backup_id }, Some(from_sequence)) => {
+                    next = Some(from_backup_id.clone());
+                    from_sequence
+                }
+                _ => {
+                    return Err(AllSourceError::ValidationError(format!(
+                        "Backup {} has a start sequence that does not match its type",
+                        id
+                    )))
+                }
+            };
+
+            if first_sequence + metadata.event_count > metadata.to_sequence {
+                return Err(AllSourceError::ValidationError(format!(
+                    "Backup {} holds {} events but only covers sequences {}..={}",
+                    id,
+                    metadata.event_count,
+                    first_sequence + 1,
+                    metadata.to_sequence
+                )));
+            }
 
-        if events.len() != metadata.event_count as usize {
-            return Err(AllSourceError::ValidationError(format!(
-                "Event count mismatch: expected {}, got {}",
-                metadata.event_count,
-                events.len()
-            )));
+            chain.push(metadata);
         }
 
-        tracing::info!("Restored {} events from backup", events.len());
+        chain.reverse();
+
+        for pair in chain.windows(2) {
+            if pair[1].from_sequence != Some(pair[0].to_sequence) {
+                return Err(AllSourceError::ValidationError(format!(
+                    "Backup {} starts after sequence {:?} but its parent {} ends at {}",
+                    pair[1].backup_id,
+                    pair[1].from_sequence,
+                    pair[0].backup_id,
+                    pair[0].to_sequence
+                )));
+            }
+        }
 
-        Ok(events)
+        Ok(chain)
     }
 
     /// Verify backup integrity
     pub fn verify_backup(&self, metadata: &BackupMetadata) -> Result<()> {
         let backup_path = self.get_backup_path(&metadata.backup_id);
+        self.fetch_if_missing(&backup_path)?;
 
         if !backup_path.exists() {
             return Err(AllSourceError::ValidationError(
@@ -221,6 +399,26 @@ impl BackupManager {
             }
         }
 
+        // Backups whose local copies are gone
+        if let Some(ref store) = self.object_store {
+            let local: HashSet<String> = backups.iter().map(|b| b.backup_id.clone()).collect();
+            for object in object_store::block_on(store.list(OBJECT_PREFIX))? {
+                let Some(backup_id) = object
+                    .key
+                    .strip_prefix(OBJECT_PREFIX)
+                    .and_then(|name| name.strip_suffix("_metadata.json"))
+                else {
+                    continue;
+                };
+
+                if !local.contains(backup_id) {
+                    if let Ok(metadata) = self.load_metadata(backup_id) {
+                        backups.push(metadata);
+                    }
+                }
+            }
+        }
+
         // Sort by creation time, newest first
         backups.sort_by(|a, b| b.created_at.cmp(&a.created_at));
 
@@ -244,21 +442,59 @@ impl BackupManager {
                 .map_err(|e| AllSourceError::StorageError(e.to_string()))?;
         }
 
+        if let Some(ref store) = self.object_store {
+            for path in [&backup_path, &metadata_path] {
+                object_store::block_on(store.delete(&Self::object_key(path)))?;
+            }
+        }
+
         Ok(())
     }
 
     /// Cleanup old backups (keep last N)
+    ///
+    /// Backups that a kept incremental builds on are kept as well.
     pub fn cleanup_old_backups(&self, keep_count: usize) -> Result<usize> {
-        let mut backups = self.list_backups()?;
+        self.apply_retention(&RetentionPolicy {
+            max_age_secs: None,
+            keep_last: Some(keep_count),
+        })
+    }
 
-        if backups.len() <= keep_count {
+    /// Delete the backups `policy` expires
+    ///
+    /// Backups that a kept incremental builds on are kept as well.
+    pub fn apply_retention(&self, policy: &RetentionPolicy) -> Result<usize> {
+        if !policy.is_enabled() {
             return Ok(0);
         }
 
-        // Sort by date, oldest last
-        backups.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        let now = Utc::now();
+        let mut backups = Vec::new();
+        let mut to_delete = Vec::new();
+        for (rank, backup) in self.list_backups()?.into_iter().enumerate() {
+            if policy.expires(rank, backup.created_at, now) {
+                to_delete.push(backup);
+            } else {
+                backups.push(backup);
+            }
+        }
+
+        if to_delete.is_empty() {
+            return Ok(0);
+        }
+
+        let mut required = HashSet::new();
+        let mut pending: Vec<&BackupMetadata> = backups.iter().collect();
+        while let Some(backup) = pending.pop() {
+            if let BackupType::Incremental { from_backup_id } = &backup.backup_type {
+                if required.insert(from_backup_id.clone()) {
+                    pending.extend(to_delete.iter().filter(|b| &b.backup_id == from_backup_id));
+                }
+            }
+        }
+        to_delete.retain(|backup| !required.contains(&backup.backup_id));
 
-        let to_delete = backups.split_off(keep_count);
         let delete_count = to_delete.len();
 
         for backup in to_delete {
@@ -272,6 +508,153 @@ impl BackupManager {
 
     // Private helper methods
 
+    fn write_backup(
+        &self,
+        backup_id: String,
+        backup_type: BackupType,
+        from_sequence: Option<u64>,
+        to_sequence: Option<u64>,
+        events: &[Event],
+        progress: &AtomicU64,
+    ) -> Result<BackupMetadata> {
+        let timestamp = Utc::now();
+
+        tracing::info!("Creating backup: {}", backup_id);
+
+        let event_count = events.len() as u64;
+        let first_sequence = from_sequence.unwrap_or(0);
+        let to_sequence = to_sequence.unwrap_or(first_sequence + event_count);
+        if first_sequence + event_count > to_sequence {
+            return Err(AllSourceError::ValidationError(format!(
+                "{} events do not fit in sequences {}..={}",
+                event_count,
+                first_sequence + 1,
+                to_sequence
+            )));
+        }
+
+        let (encryption, sink) = {
+            let file = BufWriter::new(
+                File::create(self.get_backup_path(&backup_id))
+                    .map_err(|e| AllSourceError::StorageError(format!("Failed to create backup file: {}", e)))?,
+            );
+            match self.encryption {
+                Some(ref encryption) => {
+                    let (info, data_key) = self.new_data_key(encryption)?;
+                    let writer = EncryptingWriter::new(file, &data_key, &decode_nonce_prefix(&info)?)?;
+                    (Some(info), BackupSink::Encrypted(Box::new(writer)))
+                }
+                None => (None, BackupSink::Plain(file)),
+            }
+        };
+
+        // Stream events into the compressed (and maybe encrypted) JSON array
+        let backup_path = self.get_backup_path(&backup_id);
+        let mut encoder = GzEncoder::new(sink, self.config.compression_level);
+
+        let write_error =
+            |e: std::io::Error| AllSourceError::StorageError(format!("Failed to write backup: {}", e));
+        encoder.write_all(b"[").map_err(write_error)?;
+        for (i, event) in events.iter().enumerate() {
+            if i > 0 {
+                encoder.write_all(b",").map_err(write_error)?;
+            }
+            serde_json::to_writer(&mut encoder, event)?;
+            progress.fetch_add(1, Ordering::Relaxed);
+        }
+        encoder.write_all(b"]").map_err(write_error)?;
+
+        encoder
+            .finish()
+            .and_then(BackupSink::finish)
+            .and_then(|mut writer| writer.flush())
+            .map_err(|e| AllSourceError::StorageError(format!("Failed to finish compression: {}", e)))?;
+
+        let size_bytes = fs::metadata(&backup_path)
+            .map_err(|e| AllSourceError::StorageError(e.to_string()))?
+            .len();
+
+        let checksum = self.calculate_checksum(&backup_path)?;
+
+        let metadata = BackupMetadata {
+            backup_id: backup_id.clone(),
+            created_at: timestamp,
+            backup_type,
+            event_count,
+            size_bytes,
+            checksum,
+            from_sequence,
+            to_sequence,
+            compressed: true,
+            encryption,
+        };
+
+        // Save metadata
+        self.save_metadata(&metadata)?;
+
+        // Verify if configured
+        if self.config.verify_after_backup {
+            self.verify_backup(&metadata)?;
+        }
+
+        // Metadata goes last, so listings never see a backup without data
+        if let Some(ref store) = self.object_store {
+            let metadata_path = self.get_metadata_path(&backup_id);
+            object_store::block_on(store.put_file(&Self::object_key(&backup_path), &backup_path))?;
+            object_store::block_on(store.put_file(&Self::object_key(&metadata_path), &metadata_path))?;
+            tracing::info!("Backup {} copied to {} storage", backup_id, store.name());
+        }
+
+        tracing::info!(
+            "Backup complete: {} events, {} bytes compressed",
+            event_count,
+            size_bytes
+        );
+
+        if let Err(e) = self.apply_retention(&self.config.retention) {
+            tracing::warn!("Failed to apply backup retention: {}", e);
+        }
+
+        Ok(metadata)
+    }
+
+    fn read_backup(&self, metadata: &BackupMetadata) -> Result<Vec<Event>> {
+        let backup_path = self.get_backup_path(&metadata.backup_id);
+        self.fetch_if_missing(&backup_path)?;
+
+        // Decrypt and decompress backup
+        let file = BufReader::new(
+            File::open(&backup_path)
+                .map_err(|e| AllSourceError::StorageError(format!("Failed to open backup: {}", e)))?,
+        );
+        let reader: Box<dyn Read> = match metadata.encryption {
+            Some(ref info) => {
+                let data_key = self.open_data_key(&metadata.backup_id, info)?;
+                Box::new(DecryptingReader::new(file, &data_key, &decode_nonce_prefix(info)?)?)
+            }
+            None => Box::new(file),
+        };
+
+        let mut decoder = GzDecoder::new(reader);
+        let mut json_data = String::new();
+        decoder
+            .read_to_string(&mut json_data)
+            .map_err(|e| AllSourceError::StorageError(format!("Failed to decompress backup: {}", e)))?;
+
+        // Deserialize events
+        let events: Vec<Event> = serde_json::from_str(&json_data)?;
+
+        if events.len() != metadata.event_count as usize {
+            return Err(AllSourceError::ValidationError(format!(
+                "Event count mismatch: expected {}, got {}",
+                metadata.event_count,
+                events.len()
+            )));
+        }
+
+        Ok(events)
+    }
+
     fn get_backup_path(&self, backup_id: &str) -> PathBuf {
         self.config
             .backup_dir
@@ -296,6 +679,7 @@ impl BackupManager {
 
     fn load_metadata(&self, backup_id: &str) -> Result<BackupMetadata> {
         let path = self.get_metadata_path(backup_id);
+        self.fetch_if_missing(&path)?;
 
         if !path.exists() {
             return Err(AllSourceError::ValidationError(
@@ -309,6 +693,84 @@ impl BackupManager {
         Ok(serde_json::from_str(&json)?)
     }
 
+    /// Generate a data key for a new backup, wrapped by the KMS key
+    fn new_data_key(&self, encryption: &BackupEncryption) -> Result<(BackupEncryptionInfo, Vec<u8>)> {
+        let client = encryption.kms.client();
+        let (key, data_key, encrypted_data_key) = object_store::block_on(async {
+            let key = client.get_key(&encryption.key_id).await?;
+            let (data_key, encrypted_data_key) = client.generate_data_key(&encryption.key_id).await?;
+            Ok::<_, AllSourceError>((key, data_key, encrypted_data_key))
+        })?;
+
+        let mut nonce_prefix = [0u8; 8];
+        OsRng.fill_bytes(&mut nonce_prefix);
+
+        let info = BackupEncryptionInfo {
+            key_id: encryption.key_id.clone(),
+            key_version: key.version,
+            algorithm: KeyAlgorithm::Aes256Gcm,
+            encrypted_data_key: general_purpose::STANDARD.encode(encrypted_data_key),
+            nonce_prefix: general_purpose::STANDARD.encode(nonce_prefix),
+        };
+
+        Ok((info, data_key))
+    }
+
+    /// Unwrap the data key of an encrypted backup
+    fn open_data_key(&self, backup_id: &str, info: &BackupEncryptionInfo) -> Result<Vec<u8>> {
+        let Some(ref encryption) = self.encryption else {
+            return Err(AllSourceError::ValidationError(format!(
+                "Backup {} is encrypted with KMS key {}; configure backup encryption to restore it",
+                backup_id, info.key_id
+            )));
+        };
+
+        if info.algorithm != KeyAlgorithm::Aes256Gcm {
+            return Err(AllSourceError::ValidationError(format!(
+                "Backup {} uses unsupported algorithm {:?}",
+                backup_id, info.algorithm
+            )));
+        }
+
+        let encrypted_data_key = general_purpose::STANDARD
+            .decode(&info.encrypted_data_key)
+            .map_err(|e| AllSourceError::ValidationError(format!("Invalid data key in backup {}: {}", backup_id, e)))?;
+
+        object_store::block_on(encryption.kms.client().decrypt(&info.key_id, &encrypted_data_key)).map_err(|e| {
+            AllSourceError::ValidationError(format!(
+                "Cannot unwrap the data key of backup {} (KMS key {} version {}): {}",
+                backup_id, info.key_id, info.key_version, e
+            ))
+        })
+    }
+
+    /// Object store key of a file in the backup directory
+    fn object_key(path: &Path) -> String {
+        format!(
+            "{}{}",
+            OBJECT_PREFIX,
+            path.file_name().and_then(|name| name.to_str()).unwrap_or_default()
+        )
+    }
+
+    /// Download a backup file from the object store if there is no local copy
+    fn fetch_if_missing(&self, path: &Path) -> Result<()> {
+        let Some(ref store) = self.object_store else {
+            return Ok(());
+        };
+        if path.exists() {
+            return Ok(());
+        }
+
+        let key = Self::object_key(path);
+        if object_store::block_on(store.head(&key))?.is_some() {
+            tracing::info!("Fetching {} from {} storage", key, store.name());
+            object_store::block_on(store.get_to_file(&key, path))?;
+        }
+
+        Ok(())
+    }
+
     fn calculate_checksum(&self, path: &Path) -> Result<String> {
         use sha2::{Sha256, Digest};
 
@@ -334,11 +796,605 @@ impl BackupManager {
     }
 }
 
+// ============================================================================
+// Backup file encryption
+// ============================================================================
+
+/// Plaintext bytes per encrypted chunk
+///
+/// Each chunk is sealed with AES-256-GCM under the nonce
+/// `nonce_prefix || chunk index` and with the associated data `[1]` for the
+/// last chunk and `[0]` otherwise, so reordered, dropped or truncated
+/// chunks fail to decrypt. Every chunk but the last is exactly
+/// `ENCRYPTION_CHUNK_SIZE + 16` bytes on disk.
+const ENCRYPTION_CHUNK_SIZE: usize = 64 * 1024;
+
+/// AES-GCM authentication tag length
+const TAG_SIZE: usize = 16;
+
+fn decode_nonce_prefix(info: &BackupEncryptionInfo) -> Result<[u8; 8]> {
+    general_purpose::STANDARD
+        .decode(&info.nonce_prefix)
+        .ok()
+        .and_then(|prefix| prefix.try_into().ok())
+        .ok_or_else(|| AllSourceError::ValidationError("Invalid backup nonce prefix".to_string()))
+}
+
+fn chunk_nonce(prefix: &[u8; 8], index: u32) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    nonce[..8].copy_from_slice(prefix);
+    nonce[8..].copy_from_slice(&index.to_be_bytes());
+    nonce
+}
+
+fn data_cipher(data_key: &[u8]) -> Result<Aes256Gcm> {
+    Aes256Gcm::new_from_slice(data_key)
+        .map_err(|e| AllSourceError::ValidationError(format!("Invalid backup data key: {}", e)))
+}
+
+/// Where the compressed backup is written
+enum BackupSink {
+    Plain(BufWriter<File>),
+    Encrypted(Box<EncryptingWriter<BufWriter<File>>>),
+}
+
+impl BackupSink {
+    fn finish(self) -> std::io::Result<BufWriter<File>> {
+        match self {
+            BackupSink::Plain(writer) => Ok(writer),
+            BackupSink::Encrypted(writer) => writer.finish(),
+        }
+    }
+}
+
+impl Write for BackupSink {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            BackupSink::Plain(writer) => writer.write(buf),
+            BackupSink::Encrypted(writer) => writer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            BackupSink::Plain(writer) => writer.flush(),
+            BackupSink::Encrypted(writer) => writer.flush(),
+        }
+    }
+}
+
+/// Encrypts everything written to it in sealed chunks
+struct EncryptingWriter<W: Write> {
+    inner: W,
+    cipher: Aes256Gcm,
+    nonce_prefix: [u8; 8],
+    index: u32,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> EncryptingWriter<W> {
+    fn new(inner: W, data_key: &[u8], nonce_prefix: &[u8; 8]) -> Result<Self> {
+        Ok(Self {
+            inner,
+            cipher: data_cipher(data_key)?,
+            nonce_prefix: *nonce_prefix,
+            index: 0,
+            buffer: Vec::with_capacity(ENCRYPTION_CHUNK_SIZE),
+        })
+    }
+
+    fn seal(&mut self, last: bool) -> std::io::Result<()> {
+        let nonce = chunk_nonce(&self.nonce_prefix, self.index);
+        let sealed = self
+            .cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &self.buffer,
+                    aad: &[last as u8],
+                },
+            )
+            .map_err(|e| std::io::Error::other(format!("Backup encryption failed: {}", e)))?;
+
+        self.inner.write_all(&sealed)?;
+        self.buffer.clear();
+        self.index = self
+            .index
+            .checked_add(1)
+            .ok_or_else(|| std::io::Error::other("Backup too large to encrypt"))?;
+        Ok(())
+    }
+
+    /// Seal the last chunk and return the inner writer
+    fn finish(mut self) -> std::io::Result<W> {
+        self.seal(true)?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for EncryptingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        // A full chunk is only sealed once more data follows, since the
+        // last chunk has to be marked as such
+        if self.buffer.len() == ENCRYPTION_CHUNK_SIZE && !buf.is_empty() {
+            self.seal(false)?;
+        }
+
+        let count = buf.len().min(ENCRYPTION_CHUNK_SIZE - self.buffer.len());
+        self.buffer.extend_from_slice(&buf[..count]);
+        Ok(count)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Decrypts a stream written by `EncryptingWriter`
+struct DecryptingReader<R: Read> {
+    inner: R,
+    cipher: Aes256Gcm,
+    nonce_prefix: [u8; 8],
+    index: u32,
+    /// Next sealed chunk, read ahead to tell whether it is the last one
+    sealed: Vec<u8>,
+    plaintext: Vec<u8>,
+    position: usize,
+    done: bool,
+}
+
+impl<R: Read> DecryptingReader<R> {
+    fn new(mut inner: R, data_key: &[u8], nonce_prefix: &[u8; 8]) -> Result<Self> {
+        let sealed = Self::read_sealed(&mut inner)
+            .map_err(|e| AllSourceError::StorageError(format!("Failed to read backup: {}", e)))?;
+
+        Ok(Self {
+            inner,
+            cipher: data_cipher(data_key)?,
+            nonce_prefix: *nonce_prefix,
+            index: 0,
+            sealed,
+            plaintext: Vec::new(),
+            position: 0,
+            done: false,
+        })
+    }
+
+    fn read_sealed(inner: &mut R) -> std::io::Result<Vec<u8>> {
+        let mut sealed = Vec::with_capacity(ENCRYPTION_CHUNK_SIZE + TAG_SIZE);
+        inner
+            .take((ENCRYPTION_CHUNK_SIZE + TAG_SIZE) as u64)
+            .read_to_end(&mut sealed)?;
+        Ok(sealed)
+    }
+
+    fn open_next(&mut self) -> std::io::Result<()> {
+        let invalid = |message: &str| std::io::Error::new(std::io::ErrorKind::InvalidData, message.to_string());
+
+        if self.sealed.is_empty() {
+            return Err(invalid("Encrypted backup is truncated"));
+        }
+
+        let next = Self::read_sealed(&mut self.inner)?;
+        let last = next.is_empty();
+        let nonce = chunk_nonce(&self.nonce_prefix, self.index);
+
+        self.plaintext = self
+            .cipher
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &self.sealed,
+                    aad: &[last as u8],
+                },
+            )
+            .map_err(|_| invalid("Backup decryption failed: wrong key or corrupted data"))?;
+        self.position = 0;
+        self.sealed = next;
+        self.done = last;
+        self.index = self.index.checked_add(1).ok_or_else(|| invalid("Encrypted backup has too many chunks"))?;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for DecryptingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.position == self.plaintext.len() {
+            if self.done {
+                return Ok(0);
+            }
+            self.open_next()?;
+        }
+
+        let count = buf.len().min(self.plaintext.len() - self.position);
+        buf[..count].copy_from_slice(&self.plaintext[self.position..self.position + count]);
+        self.position += count;
+        Ok(count)
+    }
+}
+
+/// Events copied out of the store per read lock while taking a backup, so
+/// ingestion is only held up for one chunk at a time
+const SNAPSHOT_CHUNK_SIZE: u64 = 10_000;
+
+/// Kind of background backup job
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupOperation {
+    Backup,
+    Restore,
+}
+
+/// Status of a background backup job
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupJobStatus {
+    Running,
+    Completed,
+    Failed,
+}
+
+/// Options for taking a backup of a running store
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BackupJobRequest {
+    /// Extend this backup instead of taking a full one
+    pub from_backup_id: Option<String>,
+
+    /// Extend the newest backup (ignored when `from_backup_id` is set)
+    #[serde(default)]
+    pub incremental: bool,
+}
+
+/// Where restored events go
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "mode", rename_all = "lowercase")]
+pub enum RestoreTarget {
+    /// Replace every event in the store (native engine only)
+    Replace,
+
+    /// Copy the events into a tenant holding no events, with new event IDs
+    Tenant {
+        tenant_id: String,
+        /// Only restore this tenant's events
+        source_tenant_id: Option<String>,
+    },
+}
+
+/// Options for restoring a backup into a running store
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestoreJobRequest {
+    #[serde(flatten)]
+    pub target: RestoreTarget,
+
+    /// Only restore events recorded at or before this time
+    pub until: Option<DateTime<Utc>>,
+}
+
+/// Progress of a background backup or restore
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupJobProgress {
+    pub job_id: Uuid,
+    pub operation: BackupOperation,
+    pub status: BackupJobStatus,
+    /// Backup being restored, or the backup created once it is complete
+    pub backup_id: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub total_events: u64,
+    pub processed_events: u64,
+    pub progress_percentage: f64,
+    pub error_message: Option<String>,
+}
+
+/// Internal state of a backup job
+struct BackupJob {
+    id: Uuid,
+    operation: BackupOperation,
+    started_at: DateTime<Utc>,
+    status: RwLock<BackupJobStatus>,
+    backup_id: RwLock<Option<String>>,
+    completed_at: RwLock<Option<DateTime<Utc>>>,
+    total_events: AtomicU64,
+    processed_events: AtomicU64,
+    error_message: RwLock<Option<String>>,
+}
+
+impl BackupJob {
+    fn progress(&self) -> BackupJobProgress {
+        let total_events = self.total_events.load(Ordering::Relaxed);
+        let processed_events = self.processed_events.load(Ordering::Relaxed);
+        let progress_percentage = if total_events > 0 {
+            (processed_events as f64 / total_events as f64) * 100.0
+        } else {
+            0.0
+        };
+
+        BackupJobProgress {
+            job_id: self.id,
+            operation: self.operation,
+            status: *self.status.read(),
+            backup_id: self.backup_id.read().clone(),
+            started_at: self.started_at,
+            completed_at: *self.completed_at.read(),
+            total_events,
+            processed_events,
+            progress_percentage,
+            error_message: self.error_message.read().clone(),
+        }
+    }
+
+    fn finish(&self, result: Result<()>) {
+        *self.completed_at.write() = Some(Utc::now());
+
+        match result {
+            Ok(()) => {
+                *self.status.write() = BackupJobStatus::Completed;
+                tracing::info!("✅ {:?} job {} completed", self.operation, self.id);
+            }
+            Err(e) => {
+                *self.status.write() = BackupJobStatus::Failed;
+                *self.error_message.write() = Some(e.to_string());
+                tracing::error!("❌ {:?} job {} failed: {}", self.operation, self.id, e);
+            }
+        }
+    }
+}
+
+/// Runs backups and restores against a live store in the background
+///
+/// Backups freeze the store's current sequence and copy events up to it in
+/// chunks, so ingestion carries on while they run; later events go into the
+/// next backup. One job runs at a time, so a restore never replaces events
+/// underneath a backup.
+pub struct BackupJobManager {
+    manager: Arc<BackupManager>,
+    jobs: RwLock<Vec<Arc<BackupJob>>>,
+}
+
+impl BackupJobManager {
+    pub fn new(manager: Arc<BackupManager>) -> Self {
+        Self {
+            manager,
+            jobs: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Get the backup manager jobs write to and restore from
+    pub fn manager(&self) -> Arc<BackupManager> {
+        Arc::clone(&self.manager)
+    }
+
+    /// Start backing up `store`
+    pub fn start_backup(
+        &self,
+        store: Arc<EventStore>,
+        request: BackupJobRequest,
+    ) -> Result<BackupJobProgress> {
+        let parent = match request.from_backup_id {
+            Some(id) => Some(id),
+            None if request.incremental => Some(
+                self.manager
+                    .list_backups()?
+                    .into_iter()
+                    .next()
+                    .ok_or_else(|| {
+                        AllSourceError::ValidationError("No backup to extend".to_string())
+                    })?
+                    .backup_id,
+            ),
+            None => None,
+        };
+
+        let after = match parent {
+            Some(ref id) => {
+                let chain = self.manager.validate_chain(id)?;
+                chain.last().expect("chain includes the requested backup").to_sequence
+            }
+            None => 0,
+        };
+
+        // Everything up to here goes into this backup
+        let through = store.current_sequence();
+        if through < after {
+            return Err(AllSourceError::ValidationError(format!(
+                "The store holds {} events but backup {} already covers {}",
+                through,
+                parent.unwrap_or_default(),
+                after
+            )));
+        }
+        if through == after {
+            return Err(AllSourceError::ValidationError(
+                "No events to backup".to_string(),
+            ));
+        }
+
+        let job = self.begin(BackupOperation::Backup, through - after)?;
+        tracing::info!(
+            "💾 Backup job {} started for sequences {}..={}",
+            job.id,
+            after + 1,
+            through
+        );
+
+        let manager = Arc::clone(&self.manager);
+        let task_job = Arc::clone(&job);
+        tokio::task::spawn_blocking(move || {
+            let mut events = Vec::with_capacity((through - after) as usize);
+            let mut position = after;
+            while position < through {
+                let end = (position + SNAPSHOT_CHUNK_SIZE).min(through);
+                events.extend(store.events_between(position, end));
+                position = end;
+            }
+
+            let result = manager
+                .create_backup_with_progress(
+                    parent.as_deref(),
+                    &events,
+                    Some(through),
+                    &task_job.processed_events,
+                )
+                .map(|metadata| {
+                    *task_job.backup_id.write() = Some(metadata.backup_id);
+                });
+            task_job.finish(result);
+        });
+
+        Ok(job.progress())
+    }
+
+    /// Start restoring `backup_id`, with the full backup and incrementals
+    /// it builds on, into `store`
+    pub fn start_restore(
+        &self,
+        store: Arc<EventStore>,
+        backup_id: &str,
+        request: RestoreJobRequest,
+    ) -> Result<BackupJobProgress> {
+        let chain = self.manager.validate_chain(backup_id)?;
+
+        let tenant = match request.target {
+            RestoreTarget::Replace => None,
+            RestoreTarget::Tenant {
+                ref tenant_id,
+                ref source_tenant_id,
+            } => {
+                if store.holds_tenant_events(tenant_id) {
+                    return Err(AllSourceError::ValidationError(format!(
+                        "Tenant {} already holds events; restore into a new tenant",
+                        tenant_id
+                    )));
+                }
+                Some((TenantId::new(tenant_id.clone())?, source_tenant_id.clone()))
+            }
+        };
+
+        let job = self.begin(
+            BackupOperation::Restore,
+            chain.iter().map(|backup| backup.event_count).sum(),
+        )?;
+        *job.backup_id.write() = Some(backup_id.to_string());
+        tracing::info!("♻️  Restore job {} started from backup {}", job.id, backup_id);
+
+        let manager = Arc::clone(&self.manager);
+        let backup_id = backup_id.to_string();
+        let task_job = Arc::clone(&job);
+        tokio::task::spawn_blocking(move || {
+            let result = (|| {
+                let mut events = manager.restore_from_backup(&backup_id)?;
+                if let Some(until) = request.until {
+                    events.retain(|event| event.timestamp <= until);
+                }
+                if let Some((_, Some(ref source))) = tenant {
+                    events.retain(|event| event.tenant_id_str() == source);
+                }
+                task_job.total_events.store(events.len() as u64, Ordering::Relaxed);
+
+                match tenant {
+                    None => store.replace_events(events, &task_job.processed_events),
+                    Some((tenant_id, _)) => {
+                        for mut event in events {
+                            event.id = Uuid::new_v4();
+                            event.tenant_id = tenant_id.clone();
+                            store.ingest_restored(event)?;
+                            task_job.processed_events.fetch_add(1, Ordering::Relaxed);
+                        }
+                        Ok(())
+                    }
+                }
+            })();
+            task_job.finish(result);
+        });
+
+        Ok(job.progress())
+    }
+
+    /// Get the progress of a job
+    pub fn get_job(&self, job_id: Uuid) -> Result<BackupJobProgress> {
+        self.jobs
+            .read()
+            .iter()
+            .find(|job| job.id == job_id)
+            .map(|job| job.progress())
+            .ok_or_else(|| AllSourceError::ValidationError(format!("Backup job not found: {}", job_id)))
+    }
+
+    /// List all jobs, newest first
+    pub fn list_jobs(&self) -> Vec<BackupJobProgress> {
+        self.jobs.read().iter().rev().map(|job| job.progress()).collect()
+    }
+
+    fn begin(&self, operation: BackupOperation, total_events: u64) -> Result<Arc<BackupJob>> {
+        let mut jobs = self.jobs.write();
+        if let Some(running) = jobs
+            .iter()
+            .find(|job| *job.status.read() == BackupJobStatus::Running)
+        {
+            return Err(AllSourceError::ConcurrencyError(format!(
+                "{:?} job {} is still running",
+                running.operation, running.id
+            )));
+        }
+
+        let job = Arc::new(BackupJob {
+            id: Uuid::new_v4(),
+            operation,
+            started_at: Utc::now(),
+            status: RwLock::new(BackupJobStatus::Running),
+            backup_id: RwLock::new(None),
+            completed_at: RwLock::new(None),
+            total_events: AtomicU64::new(total_events),
+            processed_events: AtomicU64::new(0),
+            error_message: RwLock::new(None),
+        });
+        jobs.push(Arc::clone(&job));
+
+        Ok(job)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::application::dto::QueryEventsRequest;
+    use crate::security::kms::KeyPurpose;
+    use chrono::Duration;
+    use serde_json::json;
     use tempfile::TempDir;
 
+    fn manager() -> (TempDir, BackupManager) {
+        let dir = TempDir::new().unwrap();
+        let manager = BackupManager::new(BackupConfig {
+            backup_dir: dir.path().to_path_buf(),
+            ..Default::default()
+        })
+        .unwrap();
+        (dir, manager)
+    }
+
+    /// Events one minute apart, starting at `start`
+    fn events(start: DateTime<Utc>, steps: std::ops::Range<i64>) -> Vec<Event> {
+        steps
+            .map(|step| {
+                Event::reconstruct_from_strings(
+                    Uuid::new_v4(),
+                    "order.updated".to_string(),
+                    "order-1".to_string(),
+                    "default".to_string(),
+                    json!({ "step": step }),
+                    start + Duration::minutes(step),
+                    None,
+                    step + 1,
+                )
+            })
+            .collect()
+    }
+
+    fn steps(events: &[Event]) -> Vec<i64> {
+        events.iter().map(|e| e.payload["step"].as_i64().unwrap()).collect()
+    }
+
     #[test]
     fn test_backup_config_default() {
         let config = BackupConfig::default();
@@ -352,4 +1408,658 @@ mod tests {
         let deserialized: BackupType = serde_json::from_str(&json).unwrap();
         assert_eq!(full, deserialized);
     }
+
+    #[test]
+    fn test_incremental_backups_cover_new_sequences() {
+        let (_dir, manager) = manager();
+        let log = events(Utc::now(), 0..10);
+
+        let full = manager.create_backup(&log[..4]).unwrap();
+        assert_eq!(full.from_sequence, None);
+        assert_eq!(full.to_sequence, 4);
+
+        let first = manager.create_incremental_backup(&full.backup_id, &log[4..7]).unwrap();
+        assert_eq!(first.from_sequence, Some(4));
+        assert_eq!(first.to_sequence, 7);
+        assert_eq!(first.event_count, 3);
+
+        let second = manager.create_incremental_backup(&first.backup_id, &log[7..]).unwrap();
+        assert_eq!(second.from_sequence, Some(7));
+        assert_eq!(second.to_sequence, 10);
+
+        let chain = manager.validate_chain(&second.backup_id).unwrap();
+        let ids: Vec<&str> = chain.iter().map(|b| b.backup_id.as_str()).collect();
+        assert_eq!(ids, vec![&full.backup_id, &first.backup_id, &second.backup_id]);
+
+        assert_eq!(steps(&manager.restore_from_backup(&first.backup_id).unwrap()), (0..7).collect::<Vec<_>>());
+        assert_eq!(steps(&manager.restore_from_backup(&second.backup_id).unwrap()), (0..10).collect::<Vec<_>>());
+
+        assert!(manager.create_incremental_backup(&second.backup_id, &[]).is_err());
+    }
+
+    #[test]
+    fn test_incremental_backup_from_store_sequence() {
+        use crate::store::EventStore;
+
+        let (_dir, manager) = manager();
+        let store = EventStore::new();
+        for event in events(Utc::now(), 0..3) {
+            store.ingest(event).unwrap();
+        }
+
+        let full = manager.create_backup(&store.events_since(0)).unwrap();
+        assert_eq!(full.to_sequence, store.current_sequence());
+
+        for event in events(Utc::now(), 3..5) {
+            store.ingest(event).unwrap();
+        }
+        let incremental = manager
+            .create_incremental_backup(&full.backup_id, &store.events_since(full.to_sequence))
+            .unwrap();
+        assert_eq!(incremental.event_count, 2);
+        assert_eq!(incremental.to_sequence, store.current_sequence());
+
+        let restored = manager.restore_from_backup(&incremental.backup_id).unwrap();
+        assert_eq!(steps(&restored), vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_restore_to_point_in_time() {
+        let (_dir, manager) = manager();
+        let start = Utc::now() - Duration::hours(1);
+        let log = events(start, 0..6);
+
+        let full = manager.create_backup(&log[..3]).unwrap();
+        manager.create_incremental_backup(&full.backup_id, &log[3..]).unwrap();
+
+        let restored = manager.restore_to_point_in_time(start + Duration::minutes(4)).unwrap();
+        assert_eq!(steps(&restored), vec![0, 1, 2, 3, 4]);
+
+        let restored = manager.restore_to_point_in_time(start - Duration::minutes(1)).unwrap();
+        assert!(restored.is_empty());
+    }
+
+    #[test]
+    fn test_broken_chains_are_rejected() {
+        let (dir, manager) = manager();
+        let log = events(Utc::now(), 0..6);
+
+        let full = manager.create_backup(&log[..2]).unwrap();
+        let first = manager.create_incremental_backup(&full.backup_id, &log[2..4]).unwrap();
+        let second = manager.create_incremental_backup(&first.backup_id, &log[4..]).unwrap();
+
+        // A tampered backup file fails its checksum
+        let path = dir.path().join(format!("{}.backup.gz", first.backup_id));
+        let mut data = fs::read(&path).unwrap();
+        data.push(0);
+        fs::write(&path, data).unwrap();
+        assert!(manager.validate_chain(&second.backup_id).is_err());
+        assert!(manager.restore_from_backup(&second.backup_id).is_err());
+        assert!(manager.create_incremental_backup(&second.backup_id, &log[..1]).is_err());
+
+        // A missing parent breaks everything built on it
+        manager.delete_backup(&first.backup_id).unwrap();
+        assert!(matches!(
+            manager.validate_chain(&second.backup_id),
+            Err(AllSourceError::ValidationError(_))
+        ));
+        assert!(manager.validate_chain(&full.backup_id).is_ok());
+    }
+
+    #[test]
+    fn test_chain_with_a_sequence_gap_is_rejected() {
+        let (_dir, manager) = manager();
+        let log = events(Utc::now(), 0..4);
+
+        let full = manager.create_backup(&log[..2]).unwrap();
+        let incremental = manager.create_incremental_backup(&full.backup_id, &log[2..]).unwrap();
+
+        let mut skipped = incremental.clone();
+        skipped.from_sequence = Some(3);
+        skipped.to_sequence = 5;
+        manager.save_metadata(&skipped).unwrap();
+
+        assert!(manager.validate_chain(&incremental.backup_id).is_err());
+    }
+
+    #[test]
+    fn test_cleanup_keeps_backups_that_kept_incrementals_need() {
+        let (_dir, manager) = manager();
+        let log = events(Utc::now(), 0..8);
+
+        let old = manager.create_backup(&log[..1]).unwrap();
+        let full = manager.create_backup(&log[..2]).unwrap();
+        let first = manager.create_incremental_backup(&full.backup_id, &log[2..4]).unwrap();
+        let second = manager.create_incremental_backup(&first.backup_id, &log[4..]).unwrap();
+
+        assert_eq!(manager.cleanup_old_backups(1).unwrap(), 1);
+
+        let remaining: HashSet<String> = manager
+            .list_backups()
+            .unwrap()
+            .into_iter()
+            .map(|b| b.backup_id)
+            .collect();
+        assert!(!remaining.contains(&old.backup_id));
+        assert_eq!(remaining.len(), 3);
+        assert_eq!(manager.restore_from_backup(&second.backup_id).unwrap().len(), 8);
+    }
+
+    #[test]
+    fn test_retention_runs_after_each_backup() {
+        let dir = TempDir::new().unwrap();
+        let manager = BackupManager::new(BackupConfig {
+            backup_dir: dir.path().to_path_buf(),
+            retention: RetentionPolicy {
+                max_age_secs: None,
+                keep_last: Some(2),
+            },
+            ..Default::default()
+        })
+        .unwrap();
+        let log = events(Utc::now(), 0..6);
+
+        let old = manager.create_backup(&log[..1]).unwrap();
+        let full = manager.create_backup(&log[..2]).unwrap();
+        let first = manager.create_incremental_backup(&full.backup_id, &log[2..4]).unwrap();
+        let ids = |manager: &BackupManager| -> Vec<String> {
+            manager.list_backups().unwrap().into_iter().map(|b| b.backup_id).collect()
+        };
+        assert_eq!(ids(&manager), vec![first.backup_id.clone(), full.backup_id.clone()]);
+        assert!(manager.load_metadata(&old.backup_id).is_err());
+
+        // The full backup falls out of the newest two but the chain needs it
+        let second = manager.create_incremental_backup(&first.backup_id, &log[4..]).unwrap();
+        assert_eq!(ids(&manager).len(), 3);
+        assert_eq!(steps(&manager.restore_from_backup(&second.backup_id).unwrap()), (0..6).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_backups_are_kept_in_the_object_store() {
+        let dir = TempDir::new().unwrap();
+        let config = BackupConfig {
+            backup_dir: dir.path().join("backups"),
+            object_store: Some(ObjectStoreConfig::Local {
+                path: dir.path().join("objects"),
+            }),
+            ..Default::default()
+        };
+        let manager = BackupManager::new(config.clone()).unwrap();
+        let log = events(Utc::now(), 0..8);
+
+        let full = manager.create_backup(&log[..5]).unwrap();
+        let incremental = manager.create_incremental_backup(&full.backup_id, &log[5..]).unwrap();
+        for file in [
+            format!("{}.backup.gz", full.backup_id),
+            format!("{}_metadata.json", incremental.backup_id),
+        ] {
+            assert!(dir.path().join("objects/backups").join(file).exists());
+        }
+
+        // A node that lost its backup directory restores from the object store
+        fs::remove_dir_all(dir.path().join("backups")).unwrap();
+        let manager = BackupManager::new(config).unwrap();
+        assert_eq!(manager.list_backups().unwrap().len(), 2);
+        assert_eq!(
+            steps(&manager.restore_from_backup(&incremental.backup_id).unwrap()),
+            (0..8).collect::<Vec<_>>()
+        );
+
+        manager.delete_backup(&incremental.backup_id).unwrap();
+        fs::remove_dir_all(dir.path().join("backups")).unwrap();
+        fs::create_dir_all(dir.path().join("backups")).unwrap();
+        let remaining: Vec<String> = manager.list_backups().unwrap().into_iter().map(|b| b.backup_id).collect();
+        assert_eq!(remaining, vec![full.backup_id]);
+    }
+
+    async fn encryption_key(kms: &KmsManager) -> String {
+        kms.client()
+            .create_key("backups".to_string(), KeyPurpose::DataEncryption, KeyAlgorithm::Aes256Gcm)
+            .await
+            .unwrap()
+            .key_id
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_encrypted_backups_survive_key_rotation() {
+        let dir = TempDir::new().unwrap();
+        let kms = Arc::new(KmsManager::new(KmsConfig::default()).unwrap());
+        let key_id = encryption_key(&kms).await;
+        let manager = BackupManager::new(BackupConfig {
+            backup_dir: dir.path().to_path_buf(),
+            ..Default::default()
+        })
+        .unwrap()
+        .with_encryption(Arc::clone(&kms), key_id.clone());
+        let log = events(Utc::now(), 0..10);
+
+        let full = manager.create_backup(&log[..6]).unwrap();
+        let info = full.encryption.clone().unwrap();
+        assert_eq!((info.key_id.as_str(), info.key_version), (key_id.as_str(), 1));
+        assert_eq!(info.algorithm, KeyAlgorithm::Aes256Gcm);
+
+        // Not even gzip without the data key
+        let raw = fs::read(manager.get_backup_path(&full.backup_id)).unwrap();
+        assert_ne!(&raw[..2], &[0x1f, 0x8b]);
+        let mut plain = String::new();
+        assert!(GzDecoder::new(&raw[..]).read_to_string(&mut plain).is_err());
+
+        kms.client().rotate_key(&key_id).await.unwrap();
+        let incremental = manager.create_incremental_backup(&full.backup_id, &log[6..]).unwrap();
+        assert_eq!(incremental.encryption.as_ref().unwrap().key_version, 2);
+
+        assert_eq!(
+            steps(&manager.restore_from_backup(&incremental.backup_id).unwrap()),
+            (0..10).collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_encrypted_backups_need_their_kms_key() {
+        let dir = TempDir::new().unwrap();
+        let config = BackupConfig {
+            backup_dir: dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let kms = Arc::new(KmsManager::new(KmsConfig::default()).unwrap());
+        let key_id = encryption_key(&kms).await;
+        let backup = BackupManager::new(config.clone())
+            .unwrap()
+            .with_encryption(kms, key_id)
+            .create_backup(&events(Utc::now(), 0..3))
+            .unwrap();
+
+        // Checksums are over the encrypted file, so verification needs no key
+        let plain = BackupManager::new(config.clone()).unwrap();
+        plain.validate_chain(&backup.backup_id).unwrap();
+        let error = plain.restore_from_backup(&backup.backup_id).unwrap_err();
+        assert!(error.to_string().contains("is encrypted with KMS key"), "{}", error);
+
+        let other_kms = Arc::new(KmsManager::new(KmsConfig::default()).unwrap());
+        let other_key = encryption_key(&other_kms).await;
+        let error = BackupManager::new(config)
+            .unwrap()
+            .with_encryption(other_kms, other_key)
+            .restore_from_backup(&backup.backup_id)
+            .unwrap_err();
+        assert!(error.to_string().contains("Cannot unwrap the data key"), "{}", error);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_encrypted_backups_restore_after_kms_restart() {
+        let dir = TempDir::new().unwrap();
+        let config = BackupConfig {
+            backup_dir: dir.path().join("backups"),
+            ..Default::default()
+        };
+        let kms_config = KmsConfig::default().with_default_key_file(dir.path().join("kms").join("keys.json"));
+
+        let kms = Arc::new(KmsManager::new(kms_config.clone()).unwrap());
+        let key = kms
+            .ensure_key("backups", KeyPurpose::DataEncryption, KeyAlgorithm::Aes256Gcm)
+            .await
+            .unwrap();
+        let backup = BackupManager::new(config.clone())
+            .unwrap()
+            .with_encryption(kms, key.key_id)
+            .create_backup(&events(Utc::now(), 0..4))
+            .unwrap();
+
+        // A restarted process reloads the master key from the key file
+        let kms = Arc::new(KmsManager::new(kms_config).unwrap());
+        let key = kms
+            .ensure_key("backups", KeyPurpose::DataEncryption, KeyAlgorithm::Aes256Gcm)
+            .await
+            .unwrap();
+        let restored = BackupManager::new(config)
+            .unwrap()
+            .with_encryption(kms, key.key_id)
+            .restore_from_backup(&backup.backup_id)
+            .unwrap();
+        assert_eq!(steps(&restored), (0..4).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_backup_encryption_stream() {
+        let key = [7u8; 32];
+        let prefix = [1u8; 8];
+        let seal = |data: &[u8]| {
+            let mut writer = EncryptingWriter::new(Vec::new(), &key, &prefix).unwrap();
+            writer.write_all(data).unwrap();
+            writer.finish().unwrap()
+        };
+        let open = |sealed: &[u8]| -> std::io::Result<Vec<u8>> {
+            let mut plaintext = Vec::new();
+            DecryptingReader::new(sealed, &key, &prefix)
+                .unwrap()
+                .read_to_end(&mut plaintext)?;
+            Ok(plaintext)
+        };
+
+        for size in [0, 10, ENCRYPTION_CHUNK_SIZE, 2 * ENCRYPTION_CHUNK_SIZE + 5] {
+            let data: Vec<u8> = (0..size).map(|i| (i % 251) as u8).collect();
+            let sealed = seal(&data);
+            assert_eq!(sealed.len(), size + TAG_SIZE * (size.div_ceil(ENCRYPTION_CHUNK_SIZE).max(1)));
+            assert_eq!(open(&sealed).unwrap(), data, "size {}", size);
+        }
+
+        let data = vec![42u8; 2 * ENCRYPTION_CHUNK_SIZE + 5];
+        let sealed = seal(&data);
+
+        // Dropping trailing chunks is detected
+        assert!(open(&sealed[..ENCRYPTION_CHUNK_SIZE + TAG_SIZE]).is_err());
+        assert!(open(&sealed[..sealed.len() - 1]).is_err());
+        assert!(open(&[]).is_err());
+
+        // So is tampering and reordering
+        let mut tampered = sealed.clone();
+        tampered[100] ^= 1;
+        assert!(open(&tampered).is_err());
+        let chunk = ENCRYPTION_CHUNK_SIZE + TAG_SIZE;
+        let mut reordered = sealed[chunk..2 * chunk].to_vec();
+        reordered.extend_from_slice(&sealed[..chunk]);
+        reordered.extend_from_slice(&sealed[2 * chunk..]);
+        assert!(open(&reordered).is_err());
+    }
+
+    async fn wait_for(jobs: &BackupJobManager, job: &BackupJobProgress) -> BackupJobProgress {
+        for _ in 0..500 {
+            let progress = jobs.get_job(job.job_id).unwrap();
+            if progress.status != BackupJobStatus::Running {
+                return progress;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        panic!("backup job {} did not finish", job.job_id);
+    }
+
+    fn job_manager(dir: &TempDir) -> BackupJobManager {
+        BackupJobManager::new(Arc::new(
+            BackupManager::new(BackupConfig {
+                backup_dir: dir.path().join("backups"),
+                ..Default::default()
+            })
+            .unwrap(),
+        ))
+    }
+
+    fn entity_steps(store: &EventStore, entity_id: &str) -> Vec<i64> {
+        steps(
+            &store
+                .query(QueryEventsRequest {
+                    entity_id: Some(entity_id.to_string()),
+                    event_type: None,
+                    tenant_id: None,
+                    as_of: None,
+                    since: None,
+                    until: None,
+                    limit: None,
+                    payload_contains: None,
+                })
+                .unwrap(),
+        )
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_backup_jobs_freeze_the_sequence() {
+        let dir = TempDir::new().unwrap();
+        let jobs = job_manager(&dir);
+        let store = Arc::new(EventStore::new());
+        for event in events(Utc::now(), 0..5) {
+            store.ingest(event).unwrap();
+        }
+
+        let job = jobs.start_backup(store.clone(), BackupJobRequest::default()).unwrap();
+        assert_eq!(job.operation, BackupOperation::Backup);
+        assert_eq!(job.total_events, 5);
+
+        // Ingestion carries on; later events wait for the next backup
+        for event in events(Utc::now(), 5..8) {
+            store.ingest(event).unwrap();
+        }
+
+        let done = wait_for(&jobs, &job).await;
+        assert_eq!(done.status, BackupJobStatus::Completed);
+        assert_eq!(done.processed_events, 5);
+        assert_eq!(done.progress_percentage, 100.0);
+        let full = jobs.manager().validate_chain(done.backup_id.as_deref().unwrap()).unwrap();
+        assert_eq!(full[0].to_sequence, 5);
+
+        let job = jobs
+            .start_backup(
+                store.clone(),
+                BackupJobRequest {
+                    incremental: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        let done = wait_for(&jobs, &job).await;
+        let chain = jobs.manager().validate_chain(done.backup_id.as_deref().unwrap()).unwrap();
+        assert_eq!(chain.len(), 2);
+        assert_eq!(chain[1].from_sequence, Some(5));
+        assert_eq!(chain[1].to_sequence, 8);
+
+        // Nothing new to back up
+        assert!(matches!(
+            jobs.start_backup(store.clone(), BackupJobRequest { incremental: true, ..Default::default() }),
+            Err(AllSourceError::ValidationError(_))
+        ));
+        assert_eq!(jobs.list_jobs().len(), 2);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_incremental_backup_after_retention_and_restart() {
+        use crate::retention::{RetentionConfig, RetentionRule};
+        use crate::store::EventStoreConfig;
+        use crate::wal::WALConfig;
+
+        let dir = TempDir::new().unwrap();
+        let jobs = job_manager(&dir);
+        let config = EventStoreConfig {
+            retention: RetentionConfig {
+                rules: vec![RetentionRule {
+                    max_events_per_entity: Some(3),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+            ..EventStoreConfig::with_wal(dir.path().join("wal"), WALConfig::default())
+        };
+
+        let store = Arc::new(EventStore::with_config(config.clone()));
+        for event in events(Utc::now(), 0..5) {
+            store.ingest(event).unwrap();
+        }
+        let full = wait_for(&jobs, &jobs.start_backup(store.clone(), BackupJobRequest::default()).unwrap()).await;
+        assert_eq!(full.status, BackupJobStatus::Completed);
+
+        // The two oldest events expire, then the node restarts
+        assert_eq!(store.apply_retention().unwrap().events_expired, 2);
+        drop(store);
+        let store = Arc::new(EventStore::with_config(config));
+        assert_eq!(entity_steps(&store, "order-1"), vec![2, 3, 4]);
+        assert_eq!(store.current_sequence(), 5);
+        assert_eq!(store.events_since(2).len(), 3);
+
+        for event in events(Utc::now(), 5..7) {
+            store.ingest(event).unwrap();
+        }
+        let job = jobs
+            .start_backup(
+                store.clone(),
+                BackupJobRequest {
+                    incremental: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        let done = wait_for(&jobs, &job).await;
+        assert_eq!(done.status, BackupJobStatus::Completed);
+        assert_eq!(done.processed_events, 2);
+
+        let backup_id = done.backup_id.unwrap();
+        let chain = jobs.manager().validate_chain(&backup_id).unwrap();
+        assert_eq!((chain[1].from_sequence, chain[1].to_sequence), (Some(5), 7));
+        let restored = jobs.manager().restore_from_backup(&backup_id).unwrap();
+        assert_eq!(steps(&restored), (0..7).collect::<Vec<_>>());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_restore_job_replaces_the_store() {
+        use crate::store::EventStoreConfig;
+        use crate::wal::WALConfig;
+
+        let dir = TempDir::new().unwrap();
+        let wal_dir = dir.path().join("wal");
+        let jobs = job_manager(&dir);
+        let store = Arc::new(EventStore::with_config(EventStoreConfig::with_wal(&wal_dir, WALConfig::default())));
+        for event in events(Utc::now(), 0..4) {
+            store.ingest(event).unwrap();
+        }
+
+        let backup = wait_for(&jobs, &jobs.start_backup(store.clone(), BackupJobRequest::default()).unwrap()).await;
+        for event in events(Utc::now(), 4..6) {
+            store.ingest(event).unwrap();
+        }
+        assert_eq!(entity_steps(&store, "order-1"), vec![0, 1, 2, 3, 4, 5]);
+
+        let job = jobs
+            .start_restore(
+                store.clone(),
+                backup.backup_id.as_deref().unwrap(),
+                RestoreJobRequest {
+                    target: RestoreTarget::Replace,
+                    until: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(job.operation, BackupOperation::Restore);
+        let done = wait_for(&jobs, &job).await;
+        assert_eq!(done.status, BackupJobStatus::Completed);
+        assert_eq!(done.processed_events, 4);
+        assert_eq!(entity_steps(&store, "order-1"), vec![0, 1, 2, 3]);
+        assert_eq!(store.current_sequence(), 4);
+        let state = store.reconstruct_state("order-1", None).unwrap();
+        assert_eq!(state["current_state"]["step"], 3);
+
+        // The WAL holds only the restored events, and the staging is gone
+        drop(store);
+        assert!(!wal_dir.join("restore-staging-wal").exists());
+        let reopened = EventStore::with_config(EventStoreConfig::with_wal(&wal_dir, WALConfig::default()));
+        assert_eq!(entity_steps(&reopened, "order-1"), vec![0, 1, 2, 3]);
+        assert_eq!(reopened.current_sequence(), 4);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_restore_job_into_a_new_tenant() {
+        let dir = TempDir::new().unwrap();
+        let jobs = job_manager(&dir);
+        let store = Arc::new(EventStore::new());
+        let start = Utc::now() - Duration::hours(1);
+        for event in events(start, 0..4) {
+            store.ingest(event).unwrap();
+        }
+
+        let backup = wait_for(&jobs, &jobs.start_backup(store.clone(), BackupJobRequest::default()).unwrap()).await;
+        let backup_id = backup.backup_id.unwrap();
+        let request = RestoreJobRequest {
+            target: RestoreTarget::Tenant {
+                tenant_id: "restored".to_string(),
+                source_tenant_id: Some("default".to_string()),
+            },
+            until: Some(start + Duration::minutes(2)),
+        };
+
+        let done = wait_for(&jobs, &jobs.start_restore(store.clone(), &backup_id, request.clone()).unwrap()).await;
+        assert_eq!(done.status, BackupJobStatus::Completed);
+        assert_eq!(done.total_events, 3);
+
+        let (restored, originals): (Vec<Event>, Vec<Event>) = store
+            .events_since(0)
+            .into_iter()
+            .partition(|event| event.tenant_id_str() == "restored");
+        assert_eq!(steps(&restored), vec![0, 1, 2]);
+        assert_eq!(originals.len(), 4);
+        assert!(restored
+            .iter()
+            .all(|event| originals.iter().all(|original| original.id != event.id)));
+
+        // The tenant is no longer empty
+        assert!(matches!(
+            jobs.start_restore(store.clone(), &backup_id, request),
+            Err(AllSourceError::ValidationError(_))
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_engine_backed_stores_cannot_be_replaced() {
+        use crate::infrastructure::persistence::StorageEngineConfig;
+        use crate::store::EventStoreConfig;
+
+        let dir = TempDir::new().unwrap();
+        let jobs = job_manager(&dir);
+        let store = Arc::new(EventStore::with_config(EventStoreConfig {
+            storage_engine: StorageEngineConfig::Memory,
+            ..Default::default()
+        }));
+        for event in events(Utc::now(), 0..2) {
+            store.ingest(event).unwrap();
+        }
+
+        let backup = wait_for(&jobs, &jobs.start_backup(store.clone(), BackupJobRequest::default()).unwrap()).await;
+        let job = jobs
+            .start_restore(
+                store.clone(),
+                backup.backup_id.as_deref().unwrap(),
+                RestoreJobRequest {
+                    target: RestoreTarget::Replace,
+                    until: None,
+                },
+            )
+            .unwrap();
+        let done = wait_for(&jobs, &job).await;
+        assert_eq!(done.status, BackupJobStatus::Failed);
+        assert!(done.error_message.unwrap().contains("native storage engine"));
+        assert_eq!(entity_steps(&store, "order-1"), vec![0, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_one_job_runs_at_a_time() {
+        let dir = TempDir::new().unwrap();
+        let jobs = job_manager(&dir);
+        let store = Arc::new(EventStore::new());
+        store.ingest(events(Utc::now(), 0..1).remove(0)).unwrap();
+
+        let running = jobs.begin(BackupOperation::Restore, 1).unwrap();
+        assert!(matches!(
+            jobs.start_backup(store.clone(), BackupJobRequest::default()),
+            Err(AllSourceError::ConcurrencyError(_))
+        ));
+
+        running.finish(Ok(()));
+        assert!(jobs.start_backup(store, BackupJobRequest::default()).is_ok());
+    }
+
+    #[test]
+    fn test_restore_request_format() {
+        let request: RestoreJobRequest = serde_json::from_value(json!({"mode": "replace"})).unwrap();
+        assert_eq!(request.target, RestoreTarget::Replace);
+        assert!(request.until.is_none());
+
+        let request: RestoreJobRequest = serde_json::from_value(json!({
+            "mode": "tenant",
+            "tenant_id": "acme-restored",
+            "until": "2026-01-01T00:00:00Z"
+        }))
+        .unwrap();
+        assert_eq!(
+            request.target,
+            RestoreTarget::Tenant {
+                tenant_id: "acme-restored".to_string(),
+                source_tenant_id: None
+            }
+        );
+        assert!(request.until.is_some());
+    }
 }
@@ -0,0 +1,124 @@
+use crate::backup::{BackupJobProgress, BackupJobRequest, BackupMetadata, RestoreJobRequest, RestoreTarget};
+use crate::error::Result;
+use crate::middleware::Admin;
+use crate::tenant::TenantQuotas;
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Serialize;
+use uuid::Uuid;
+
+// AppState is defined in api_v1.rs
+use crate::api_v1::AppState;
+
+// ============================================================================
+// Request/Response Types
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+pub struct BackupListResponse {
+    pub backups: Vec<BackupMetadata>,
+    pub total: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BackupJobListResponse {
+    pub jobs: Vec<BackupJobProgress>,
+    pub total: usize,
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// Back up the running store in the background (admin only)
+/// POST /api/v1/admin/backups
+pub async fn create_backup_handler(
+    State(state): State<AppState>,
+    Admin(_): Admin,
+    body: Bytes,
+) -> Result<(StatusCode, Json<BackupJobProgress>)> {
+    // An empty body takes a full backup
+    let request: BackupJobRequest = if body.is_empty() {
+        BackupJobRequest::default()
+    } else {
+        serde_json::from_slice(&body)?
+    };
+    let job = state.backup_jobs.start_backup(state.store.clone(), request)?;
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+/// List backups, newest first (admin only)
+/// GET /api/v1/admin/backups
+pub async fn list_backups_handler(
+    State(state): State<AppState>,
+    Admin(_): Admin,
+) -> Result<Json<BackupListResponse>> {
+    let backups = state.backup_jobs.manager().list_backups()?;
+    let total = backups.len();
+    Ok(Json(BackupListResponse { backups, total }))
+}
+
+/// Restore a backup into the running store in the background (admin only)
+/// POST /api/v1/admin/backups/:id/restore
+pub async fn restore_backup_handler(
+    State(state): State<AppState>,
+    Admin(_): Admin,
+    Path(backup_id): Path<String>,
+    Json(request): Json<RestoreJobRequest>,
+) -> Result<(StatusCode, Json<BackupJobProgress>)> {
+    // Create the target tenant before the job starts writing to it
+    let created = match request.target {
+        RestoreTarget::Tenant { ref tenant_id, .. }
+            if state.tenant_manager.get_tenant(tenant_id).is_err() =>
+        {
+            state
+                .tenant_manager
+                .create_tenant(tenant_id.clone(), tenant_id.clone(), TenantQuotas::default())?;
+            Some(tenant_id.clone())
+        }
+        _ => None,
+    };
+
+    let job = match state
+        .backup_jobs
+        .start_restore(state.store.clone(), &backup_id, request)
+    {
+        Ok(job) => job,
+        Err(e) => {
+            // Nothing will be restored into the tenant created for the job
+            if let Some(tenant_id) = created {
+                if let Err(e) = state.tenant_manager.delete_tenant(&tenant_id) {
+                    tracing::warn!("Failed to remove tenant {} after a rejected restore: {}", tenant_id, e);
+                }
+            }
+            return Err(e);
+        }
+    };
+
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+/// List backup and restore jobs, newest first (admin only)
+/// GET /api/v1/admin/backups/jobs
+pub async fn list_backup_jobs_handler(
+    State(state): State<AppState>,
+    Admin(_): Admin,
+) -> Json<BackupJobListResponse> {
+    let jobs = state.backup_jobs.list_jobs();
+    let total = jobs.len();
+    Json(BackupJobListResponse { jobs, total })
+}
+
+/// Progress of a backup or restore job (admin only)
+/// GET /api/v1/admin/backups/jobs/:job_id
+pub async fn get_backup_job_handler(
+    State(state): State<AppState>,
+    Admin(_): Admin,
+    Path(job_id): Path<Uuid>,
+) -> Result<Json<BackupJobProgress>> {
+    Ok(Json(state.backup_jobs.get_job(job_id)?))
+}
@@ -321,7 +321,7 @@ fn main() -> Result<()> {
             let manager = BackupManager::new(config)?;
             // Note: In real usage, you'd pass actual events from the store
             println!("⚠️  Backup creation requires event store access");
-            println!("    Use the API endpoint: POST /api/v1/backups");
+            println!("    Use the API endpoint: POST /api/v1/admin/backups");
         }
 
         Command::BackupList => {
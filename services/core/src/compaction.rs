@@ -1,13 +1,19 @@
 use crate::error::{AllSourceError, Result};
 use crate::domain::entities::Event;
-use crate::storage::ParquetStorage;
+use crate::infrastructure::persistence::object_store::{
+    self, ObjectStore, ObjectStoreConfig, RetentionPolicy,
+};
+use crate::retention::RetentionPolicies;
+use crate::storage::{segment_sort_key, ParquetStorage, SegmentLayout, COLD_SEGMENT_PREFIX};
 use chrono::{DateTime, Utc};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
+use uuid::Uuid;
 
 /// Manages Parquet file compaction for optimal storage and query performance
 pub struct CompactionManager {
@@ -22,6 +28,12 @@ pub struct CompactionManager {
 
     /// Last compaction time
     last_compaction: Arc<RwLock<Option<DateTime<Utc>>>>,
+
+    /// Object store that cold segments are moved to
+    cold_storage: Option<Arc<dyn ObjectStore>>,
+
+    /// Rules for dropping expired events while compacting
+    retention: Arc<RetentionPolicies>,
 }
 
 #[derive(Debug, Clone)]
@@ -46,6 +58,15 @@ pub struct CompactionConfig {
 
     /// Compaction strategy
     pub strategy: CompactionStrategy,
+
+    /// Event types compacted by entity key (`CompactionStrategy::KeyBased`)
+    pub key_compaction: KeyCompactionConfig,
+
+    /// Layout of the compacted files
+    pub layout: SegmentLayout,
+
+    /// Move old Parquet files to an object store
+    pub cold_storage: Option<ColdStorageConfig>,
 }
 
 impl Default for CompactionConfig {
@@ -58,10 +79,35 @@ impl Default for CompactionConfig {
             compaction_interval_seconds: 3600,         // 1 hour
             auto_compact: true,
             strategy: CompactionStrategy::SizeBased,
+            key_compaction: KeyCompactionConfig::default(),
+            layout: SegmentLayout::default(),
+            cold_storage: None,
         }
     }
 }
 
+/// Cold storage tiering
+///
+/// Parquet files older than `tier_after_secs` are uploaded to the object
+/// store and removed locally; `ParquetStorage` loads them back from there.
+/// Event retention rules apply to tiered segments as to local files.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColdStorageConfig {
+    pub object_store: ObjectStoreConfig,
+
+    #[serde(default = "default_tier_after_secs")]
+    pub tier_after_secs: u64,
+
+    /// Expiry of whole tiered segments; the default keeps them forever.
+    /// Segments holding events under a legal hold never expire.
+    #[serde(default)]
+    pub retention: RetentionPolicy,
+}
+
+fn default_tier_after_secs() -> u64 {
+    7 * 24 * 3600 // 7 days
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum CompactionStrategy {
@@ -71,6 +117,123 @@ pub enum CompactionStrategy {
     TimeBased,
     /// Compact all files into one
     FullCompaction,
+    /// Compact all files, keeping only the latest events of each entity
+    /// for the types in `CompactionConfig::key_compaction`
+    KeyBased,
+}
+
+/// Key-based (log) compaction
+///
+/// For each of `event_types`, only the newest `keep_latest` events of each
+/// entity are kept, like a compacted Kafka topic. An event of one of
+/// `tombstone_event_types` deletes its entity: once it is older than
+/// `tombstone_grace_secs`, the entity's events up to and including the
+/// tombstone are removed, whatever their type. Events under a legal hold
+/// (see `RetentionRule::legal_hold`) are always kept. Segments tiered to
+/// cold storage are compacted along with the local files.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyCompactionConfig {
+    #[serde(default)]
+    pub event_types: Vec<String>,
+
+    /// Events kept per entity and type; at least 1
+    #[serde(default = "default_keep_latest")]
+    pub keep_latest: usize,
+
+    #[serde(default)]
+    pub tombstone_event_types: Vec<String>,
+
+    #[serde(default = "default_tombstone_grace_secs")]
+    pub tombstone_grace_secs: u64,
+}
+
+fn default_keep_latest() -> usize {
+    1
+}
+
+fn default_tombstone_grace_secs() -> u64 {
+    24 * 3600 // 1 day
+}
+
+impl Default for KeyCompactionConfig {
+    fn default() -> Self {
+        Self {
+            event_types: Vec::new(),
+            keep_latest: default_keep_latest(),
+            tombstone_event_types: Vec::new(),
+            tombstone_grace_secs: default_tombstone_grace_secs(),
+        }
+    }
+}
+
+/// Events removed by key-based compaction
+#[derive(Debug, Clone, Default)]
+pub struct KeyCompactionVerdict {
+    /// Whether the event at each position is removed
+    pub removed: Vec<bool>,
+    pub events_superseded: usize,
+    pub events_tombstoned: usize,
+}
+
+impl KeyCompactionConfig {
+    /// Decide which of `events` key-based compaction removes as of `now`.
+    /// Ties in timestamp go to the later position.
+    pub fn evaluate(
+        &self,
+        events: &[Event],
+        now: DateTime<Utc>,
+        retention: &RetentionPolicies,
+    ) -> KeyCompactionVerdict {
+        let mut verdict = KeyCompactionVerdict {
+            removed: vec![false; events.len()],
+            ..Default::default()
+        };
+
+        // Entities deleted by a tombstone past its grace period
+        let grace_cutoff = now - chrono::Duration::seconds(self.tombstone_grace_secs as i64);
+        let mut deleted: HashMap<(&str, &str), DateTime<Utc>> = HashMap::new();
+        for event in events.iter().filter(|event| {
+            self.tombstone_event_types.iter().any(|t| t == event.event_type_str())
+                && event.timestamp <= grace_cutoff
+        }) {
+            let deleted_at = deleted
+                .entry((event.tenant_id_str(), event.entity_id_str()))
+                .or_insert(event.timestamp);
+            *deleted_at = (*deleted_at).max(event.timestamp);
+        }
+
+        let mut newest_first: Vec<usize> = (0..events.len()).collect();
+        newest_first.sort_by(|&a, &b| (events[b].timestamp, b).cmp(&(events[a].timestamp, a)));
+
+        let mut ranks: HashMap<(&str, &str, &str), usize> = HashMap::new();
+        for position in newest_first {
+            let event = &events[position];
+            let key = (event.tenant_id_str(), event.entity_id_str());
+
+            let tombstoned = deleted
+                .get(&key)
+                .is_some_and(|&deleted_at| event.timestamp <= deleted_at);
+            let superseded = !tombstoned
+                && self.event_types.iter().any(|t| t == event.event_type_str())
+                && {
+                    let rank = ranks.entry((key.0, key.1, event.event_type_str())).or_insert(0);
+                    *rank += 1;
+                    *rank > self.keep_latest.max(1)
+                };
+
+            if !(tombstoned || superseded) || retention.is_held(event) {
+                continue;
+            }
+            verdict.removed[position] = true;
+            if tombstoned {
+                verdict.events_tombstoned += 1;
+            } else {
+                verdict.events_superseded += 1;
+            }
+        }
+
+        verdict
+    }
 }
 
 #[derive(Debug, Clone, Default, Serialize)]
@@ -82,6 +245,14 @@ pub struct CompactionStats {
     pub total_events_compacted: u64,
     pub last_compaction_duration_ms: u64,
     pub space_saved_bytes: u64,
+    pub total_segments_tiered: u64,
+    pub total_bytes_tiered: u64,
+    pub total_segments_expired: u64,
+    pub total_events_expired: u64,
+    /// Expired events kept by a legal hold, as of the last compaction
+    pub events_on_legal_hold: u64,
+    pub total_events_superseded: u64,
+    pub total_events_tombstoned: u64,
 }
 
 /// Information about a Parquet file candidate for compaction
@@ -92,6 +263,24 @@ struct FileInfo {
     created: DateTime<Utc>,
 }
 
+/// Where a loaded Parquet file is stored
+enum Segment {
+    Local(FileInfo),
+    /// Key of a segment in cold storage
+    Cold(String),
+}
+
+/// Events read from Parquet files, in file order
+#[derive(Default)]
+struct LoadedFiles {
+    /// Each file with the number of events it holds
+    files: Vec<(Segment, usize)>,
+    events: Vec<Event>,
+}
+
+/// Subdirectory cold segments are downloaded to while being rewritten
+const COLD_WORK_DIR: &str = "cold-compaction";
+
 impl CompactionManager {
     /// Create a new compaction manager
     pub fn new(storage_dir: impl Into<PathBuf>, config: CompactionConfig) -> Self {
@@ -107,9 +296,23 @@ impl CompactionManager {
             config,
             stats: Arc::new(RwLock::new(CompactionStats::default())),
             last_compaction: Arc::new(RwLock::new(None)),
+            cold_storage: None,
+            retention: Arc::new(RetentionPolicies::default()),
         }
     }
 
+    /// Move cold segments to `store` (see `CompactionConfig::cold_storage`)
+    pub fn with_cold_storage(mut self, store: Arc<dyn ObjectStore>) -> Self {
+        self.cold_storage = Some(store);
+        self
+    }
+
+    /// Drop events expired by `retention` from Parquet files when compacting
+    pub fn with_retention(mut self, retention: Arc<RetentionPolicies>) -> Self {
+        self.retention = retention;
+        self
+    }
+
     /// List all Parquet files in the storage directory
     fn list_parquet_files(&self) -> Result<Vec<FileInfo>> {
         let entries = fs::read_dir(&self.storage_dir).map_err(|e| {
@@ -164,7 +367,7 @@ impl CompactionManager {
         match self.config.strategy {
             CompactionStrategy::SizeBased => self.select_small_files(files),
             CompactionStrategy::TimeBased => self.select_old_files(files),
-            CompactionStrategy::FullCompaction => files.to_vec(),
+            CompactionStrategy::FullCompaction | CompactionStrategy::KeyBased => files.to_vec(),
         }
     }
 
@@ -219,39 +422,59 @@ impl CompactionManager {
     }
 
     /// Perform compaction of Parquet files
+    ///
+    /// Key-based compaction removes events that the store still holds in
+    /// memory; use `EventStore::compact` to remove them there too.
     pub fn compact(&self) -> Result<CompactionResult> {
+        self.compact_removing().map(|(result, _)| result)
+    }
+
+    /// Perform compaction, also returning the ids of the events key-based
+    /// compaction removed
+    pub(crate) fn compact_removing(&self) -> Result<(CompactionResult, HashSet<Uuid>)> {
         let start_time = std::time::Instant::now();
         tracing::info!("🔄 Starting Parquet compaction...");
 
+        let events_expired = self.apply_retention()?;
+
         // List all Parquet files
         let files = self.list_parquet_files()?;
 
-        if files.is_empty() {
+        // Key-based compaction also covers tiered segments, so superseded
+        // and deleted entities' events leave cold storage too
+        let key_based = self.config.strategy == CompactionStrategy::KeyBased;
+        let cold = if key_based {
+            self.load_cold_files()?
+        } else {
+            LoadedFiles::default()
+        };
+
+        if files.is_empty() && cold.files.is_empty() {
             tracing::debug!("No Parquet files to compact");
-            return Ok(CompactionResult {
-                files_compacted: 0,
-                bytes_before: 0,
-                bytes_after: 0,
-                events_compacted: 0,
-                duration_ms: 0,
-            });
+            return Ok((
+                CompactionResult {
+                    events_expired,
+                    ..Default::default()
+                },
+                HashSet::new(),
+            ));
         }
 
         // Select files for compaction
         let files_to_compact = self.select_files_for_compaction(&files);
 
-        if files_to_compact.is_empty() {
+        if files_to_compact.is_empty() && cold.files.is_empty() {
             tracing::debug!(
                 "No files meet compaction criteria (strategy: {:?})",
                 self.config.strategy
             );
-            return Ok(CompactionResult {
-                files_compacted: 0,
-                bytes_before: 0,
-                bytes_after: 0,
-                events_compacted: 0,
-                duration_ms: 0,
-            });
+            return Ok((
+                CompactionResult {
+                    events_expired,
+                    ..Default::default()
+                },
+                HashSet::new(),
+            ));
         }
 
         let bytes_before: u64 = files_to_compact.iter().map(|f| f.size).sum();
@@ -262,37 +485,88 @@ impl CompactionManager {
             bytes_before as f64 / (1024.0 * 1024.0)
         );
 
-        // Read events from all files to be compacted
+        // Read events from all files to be compacted; unreadable files are
+        // left in place
         let mut all_events = Vec::new();
-        for file_info in &files_to_compact {
-            match self.read_parquet_file(&file_info.path) {
-                Ok(mut events) => {
-                    all_events.append(&mut events);
-                }
-                Err(e) => {
-                    tracing::error!(
-                        "Failed to read Parquet file {:?}: {}",
-                        file_info.path,
-                        e
-                    );
-                    // Continue with other files
-                }
+        let mut files_to_compact = files_to_compact;
+        files_to_compact.retain(|file_info| match self.read_parquet_file(&file_info.path) {
+            Ok(mut events) => {
+                all_events.append(&mut events);
+                true
             }
-        }
+            Err(e) => {
+                tracing::error!(
+                    "Failed to read Parquet file {:?}: {}",
+                    file_info.path,
+                    e
+                );
+                false
+            }
+        });
 
-        if all_events.is_empty() {
+        if all_events.is_empty() && cold.events.is_empty() {
             tracing::warn!("No events read from files to compact");
-            return Ok(CompactionResult {
-                files_compacted: 0,
-                bytes_before,
-                bytes_after: 0,
-                events_compacted: 0,
-                duration_ms: start_time.elapsed().as_millis() as u64,
+            return Ok((
+                CompactionResult {
+                    bytes_before,
+                    events_expired,
+                    duration_ms: start_time.elapsed().as_millis() as u64,
+                    ..Default::default()
+                },
+                HashSet::new(),
+            ));
+        }
+
+        // Drop superseded and deleted entities' events
+        let mut removed = HashSet::new();
+        let mut verdict = KeyCompactionVerdict::default();
+        if key_based {
+            let local_events = all_events.len();
+            all_events.extend(cold.events.iter().cloned());
+            verdict = self
+                .config
+                .key_compaction
+                .evaluate(&all_events, Utc::now(), &self.retention);
+            all_events.truncate(local_events);
+
+            let cold_removed = verdict.removed.split_off(local_events);
+            self.rewrite_files(&cold, &cold_removed)?;
+            removed.extend(
+                cold.events
+                    .iter()
+                    .zip(&cold_removed)
+                    .filter(|(_, &removed)| removed)
+                    .map(|(event, _)| event.id),
+            );
+
+            if !verdict.removed.contains(&true)
+                && removed.is_empty()
+                && files_to_compact.len() < self.config.min_files_to_compact
+            {
+                tracing::debug!("No events superseded or deleted; nothing to compact");
+                return Ok((
+                    CompactionResult {
+                        events_expired,
+                        duration_ms: start_time.elapsed().as_millis() as u64,
+                        ..Default::default()
+                    },
+                    removed,
+                ));
+            }
+
+            let mut dropped = verdict.removed.iter();
+            all_events.retain(|event| {
+                let drop = dropped.next().copied().unwrap_or(false);
+                if drop {
+                    removed.insert(event.id);
+                }
+                !drop
             });
         }
 
-        // Sort events by timestamp for better compression and query performance
-        all_events.sort_by_key(|e| e.timestamp);
+        // Cluster each entity's events so files and row groups cover narrow
+        // key ranges, for better compression and so Parquet readers can skip them
+        all_events.sort_by(|a, b| segment_sort_key(a).cmp(&segment_sort_key(b)));
 
         tracing::debug!("Read {} events for compaction", all_events.len());
 
@@ -329,6 +603,8 @@ impl CompactionManager {
         stats.total_events_compacted += all_events.len() as u64;
         stats.last_compaction_duration_ms = duration_ms;
         stats.space_saved_bytes += bytes_before.saturating_sub(bytes_after);
+        stats.total_events_superseded += verdict.events_superseded as u64;
+        stats.total_events_tombstoned += verdict.events_tombstoned as u64;
         drop(stats);
 
         // Update last compaction time
@@ -351,25 +627,25 @@ impl CompactionManager {
             duration_ms
         );
 
-        Ok(CompactionResult {
-            files_compacted: files_to_compact.len(),
-            bytes_before,
-            bytes_after,
-            events_compacted: all_events.len(),
-            duration_ms,
-        })
+        Ok((
+            CompactionResult {
+                files_compacted: files_to_compact.len(),
+                bytes_before,
+                bytes_after,
+                events_compacted: all_events.len(),
+                events_expired,
+                events_superseded: verdict.events_superseded,
+                events_tombstoned: verdict.events_tombstoned,
+                space_reclaimed_bytes: bytes_before.saturating_sub(bytes_after),
+                duration_ms,
+            },
+            removed,
+        ))
     }
 
     /// Read events from a Parquet file
     fn read_parquet_file(&self, path: &Path) -> Result<Vec<Event>> {
-        // Use ParquetStorage to read the file
-        let storage = ParquetStorage::new(&self.storage_dir)?;
-
-        // For now, we'll read all events and filter by file
-        // In a production system, you'd want to read specific files
-        let all_events = storage.load_all_events()?;
-
-        Ok(all_events)
+        ParquetStorage::new(&self.storage_dir)?.load_events_from_file(path)
     }
 
     /// Write compacted events to new Parquet file(s)
@@ -419,7 +695,7 @@ impl CompactionManager {
 
     /// Write a batch of events to a new Parquet file
     fn write_batch(&self, events: &[Event]) -> Result<PathBuf> {
-        let mut storage = ParquetStorage::new(&self.storage_dir)?;
+        let storage = ParquetStorage::new(&self.storage_dir)?;
 
         // Generate filename with timestamp
         let filename = format!(
@@ -428,13 +704,7 @@ impl CompactionManager {
         );
         let file_path = self.storage_dir.join(filename);
 
-        // Write events
-        for event in events {
-            storage.append_event(event.clone())?;
-        }
-
-        // Flush to disk
-        storage.flush()?;
+        storage.write_segment(&file_path, events, &self.config.layout)?;
 
         tracing::debug!(
             "Wrote compacted file: {:?} ({} events)",
@@ -445,6 +715,220 @@ impl CompactionManager {
         Ok(file_path)
     }
 
+    /// Remove events expired by the retention rules from the Parquet files,
+    /// local and cold, rewriting only the files that hold any. Returns the
+    /// number of events removed.
+    pub fn apply_retention(&self) -> Result<usize> {
+        if !self.retention.is_enabled() {
+            return Ok(0);
+        }
+
+        let loaded = self.load_files()?;
+        let verdict = self.retention.evaluate(&loaded.events, Utc::now());
+        self.rewrite_files(&loaded, &verdict.expired)?;
+
+        let mut stats = self.stats.write();
+        stats.total_events_expired += verdict.events_expired as u64;
+        stats.events_on_legal_hold = verdict.events_held as u64;
+        drop(stats);
+
+        if verdict.events_expired > 0 {
+            tracing::info!(
+                "🧹 Removed {} expired events from Parquet files ({} on legal hold)",
+                verdict.events_expired,
+                verdict.events_held
+            );
+        }
+
+        Ok(verdict.events_expired)
+    }
+
+    /// Remove the events with the given ids from the Parquet files, local
+    /// and cold. Returns the number of events removed.
+    pub(crate) fn remove_events(&self, ids: &HashSet<Uuid>) -> Result<usize> {
+        let loaded = self.load_files()?;
+        let removed: Vec<bool> = loaded.events.iter().map(|event| ids.contains(&event.id)).collect();
+        self.rewrite_files(&loaded, &removed)?;
+        Ok(removed.iter().filter(|&&removed| removed).count())
+    }
+
+    /// The local Parquet files, then the cold segments, and their events
+    fn load_files(&self) -> Result<LoadedFiles> {
+        let storage = ParquetStorage::new(&self.storage_dir)?;
+        let mut loaded = LoadedFiles::default();
+        for file in self.list_parquet_files()? {
+            let file_events = storage.load_events_from_file(&file.path)?;
+            loaded.files.push((Segment::Local(file), file_events.len()));
+            loaded.events.extend(file_events);
+        }
+
+        let cold = self.load_cold_files()?;
+        loaded.files.extend(cold.files);
+        loaded.events.extend(cold.events);
+        Ok(loaded)
+    }
+
+    /// The cold segments and their events
+    fn load_cold_files(&self) -> Result<LoadedFiles> {
+        let mut loaded = LoadedFiles::default();
+        if let Some(ref store) = self.cold_storage {
+            for object in object_store::block_on(store.list(COLD_SEGMENT_PREFIX))? {
+                let file_events = self.load_cold_segment(store.as_ref(), &object.key)?;
+                loaded.files.push((Segment::Cold(object.key), file_events.len()));
+                loaded.events.extend(file_events);
+            }
+        }
+        Ok(loaded)
+    }
+
+    /// Download a cold segment and read its events
+    fn load_cold_segment(&self, store: &dyn ObjectStore, key: &str) -> Result<Vec<Event>> {
+        let path = self.cold_work_path(key);
+        object_store::block_on(store.get_to_file(key, &path))?;
+        let events = ParquetStorage::new(&self.storage_dir)?.load_events_from_file(&path);
+        let _ = fs::remove_file(&path);
+        events
+    }
+
+    fn cold_work_path(&self, key: &str) -> PathBuf {
+        let name = key.strip_prefix(COLD_SEGMENT_PREFIX).unwrap_or(key);
+        self.storage_dir.join(COLD_WORK_DIR).join(name.replace('/', "_"))
+    }
+
+    /// Rewrite loaded files without the events at the `removed` positions,
+    /// deleting files left empty
+    fn rewrite_files(&self, loaded: &LoadedFiles, removed: &[bool]) -> Result<()> {
+        let storage = ParquetStorage::new(&self.storage_dir)?;
+        let events = &loaded.events;
+        let mut start = 0;
+        for (segment, length) in &loaded.files {
+            let range = start..start + length;
+            start += length;
+            if !removed[range.clone()].contains(&true) {
+                continue;
+            }
+
+            let mut kept: Vec<Event> = range
+                .filter(|&position| !removed[position])
+                .map(|position| events[position].clone())
+                .collect();
+            // Rewritten files are laid out like compacted ones
+            kept.sort_by(|a, b| segment_sort_key(a).cmp(&segment_sort_key(b)));
+
+            match segment {
+                Segment::Local(file) if kept.is_empty() => {
+                    fs::remove_file(&file.path).map_err(|e| {
+                        AllSourceError::StorageError(format!(
+                            "Failed to remove emptied file {:?}: {}",
+                            file.path, e
+                        ))
+                    })?;
+                }
+                Segment::Local(file) => {
+                    storage.write_segment(&file.path, &kept, &self.config.layout)?;
+                }
+                Segment::Cold(key) => {
+                    let Some(ref store) = self.cold_storage else {
+                        continue;
+                    };
+                    if kept.is_empty() {
+                        object_store::block_on(store.delete(key))?;
+                        continue;
+                    }
+                    let path = self.cold_work_path(key);
+                    storage.write_segment(&path, &kept, &self.config.layout)?;
+                    let uploaded = object_store::block_on(store.put_file(key, &path));
+                    let _ = fs::remove_file(&path);
+                    uploaded?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a cold segment holds events under a legal hold
+    fn holds_held_events(&self, store: &dyn ObjectStore, key: &str) -> Result<bool> {
+        if !self.retention.is_enabled() {
+            return Ok(false);
+        }
+        Ok(self
+            .load_cold_segment(store, key)?
+            .iter()
+            .any(|event| self.retention.is_held(event)))
+    }
+
+    /// Upload Parquet files older than `tier_after_secs` to cold storage
+    /// and remove them locally, then expire tiered segments per the cold
+    /// retention policy, except those holding events under a legal hold
+    pub fn tier_cold_segments(&self) -> Result<TieringResult> {
+        let (Some(store), Some(cold)) = (&self.cold_storage, &self.config.cold_storage) else {
+            return Ok(TieringResult::default());
+        };
+
+        let threshold = Utc::now() - chrono::Duration::seconds(cold.tier_after_secs as i64);
+        let mut result = TieringResult::default();
+
+        for file in self.list_parquet_files()? {
+            if file.created > threshold {
+                continue;
+            }
+            let Some(name) = file.path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+
+            let key = format!("{}{}", COLD_SEGMENT_PREFIX, name);
+            let uploaded = object_store::block_on(store.put_file(&key, &file.path))?;
+
+            // Only drop the local copy once the object store holds all of it
+            let stored = object_store::block_on(store.head(&key))?;
+            if stored.map(|meta| meta.size) != Some(file.size) {
+                return Err(AllSourceError::StorageError(format!(
+                    "Cold storage copy of {} is incomplete",
+                    name
+                )));
+            }
+
+            fs::remove_file(&file.path).map_err(|e| {
+                AllSourceError::StorageError(format!("Failed to remove tiered file {}: {}", name, e))
+            })?;
+
+            tracing::debug!("Tiered {} to {} storage ({} bytes)", name, store.name(), uploaded.size);
+            result.segments_tiered += 1;
+            result.bytes_tiered += file.size;
+        }
+
+        let expired = object_store::block_on(object_store::expired_objects(
+            store.as_ref(),
+            COLD_SEGMENT_PREFIX,
+            &cold.retention,
+        ))?;
+        for object in expired {
+            if self.holds_held_events(store.as_ref(), &object.key)? {
+                result.segments_held += 1;
+                continue;
+            }
+            object_store::block_on(store.delete(&object.key))?;
+            result.segments_expired += 1;
+        }
+
+        let mut stats = self.stats.write();
+        stats.total_segments_tiered += result.segments_tiered as u64;
+        stats.total_bytes_tiered += result.bytes_tiered;
+        stats.total_segments_expired += result.segments_expired as u64;
+        drop(stats);
+
+        if result.segments_tiered > 0 {
+            tracing::info!(
+                "🧊 Moved {} Parquet file(s) ({:.2} MB) to {} storage",
+                result.segments_tiered,
+                result.bytes_tiered as f64 / (1024.0 * 1024.0),
+                store.name()
+            );
+        }
+
+        Ok(result)
+    }
+
     /// Get compaction statistics
     pub fn stats(&self) -> CompactionStats {
         (*self.stats.read()).clone()
@@ -463,15 +947,31 @@ impl CompactionManager {
 }
 
 /// Result of a compaction operation
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct CompactionResult {
     pub files_compacted: usize,
     pub bytes_before: u64,
     pub bytes_after: u64,
     pub events_compacted: usize,
+    pub events_expired: usize,
+    /// Events replaced by newer ones under key-based compaction
+    pub events_superseded: usize,
+    /// Events of entities deleted by a tombstone
+    pub events_tombstoned: usize,
+    pub space_reclaimed_bytes: u64,
     pub duration_ms: u64,
 }
 
+/// Result of moving cold segments to the object store
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TieringResult {
+    pub segments_tiered: usize,
+    pub bytes_tiered: u64,
+    pub segments_expired: usize,
+    /// Expired segments kept because they hold events under a legal hold
+    pub segments_held: usize,
+}
+
 /// Background compaction task
 pub struct CompactionTask {
     manager: Arc<CompactionManager>,
@@ -511,6 +1011,10 @@ impl CompactionTask {
                         tracing::error!("Auto-compaction failed: {}", e);
                     }
                 }
+
+                if let Err(e) = self.manager.tier_cold_segments() {
+                    tracing::error!("Cold storage tiering failed: {}", e);
+                }
             }
         }
     }
@@ -519,8 +1023,22 @@ impl CompactionTask {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde_json::json;
     use tempfile::TempDir;
 
+    fn event(i: i64) -> Event {
+        Event::reconstruct_from_strings(
+            uuid::Uuid::new_v4(),
+            "test.event".to_string(),
+            format!("entity-{}", i),
+            "default".to_string(),
+            json!({ "i": i }),
+            Utc::now(),
+            None,
+            1,
+        )
+    }
+
     #[test]
     fn test_compaction_manager_creation() {
         let temp_dir = TempDir::new().unwrap();
@@ -576,4 +1094,323 @@ mod tests {
         let selected = manager.select_files_for_compaction(&files);
         assert_eq!(selected.len(), 2); // Only the 2 small files
     }
+
+    fn keyed(entity_id: &str, event_type: &str, age_secs: i64) -> Event {
+        Event::reconstruct_from_strings(
+            uuid::Uuid::new_v4(),
+            event_type.to_string(),
+            entity_id.to_string(),
+            "default".to_string(),
+            json!({}),
+            Utc::now() - chrono::Duration::seconds(age_secs),
+            None,
+            1,
+        )
+    }
+
+    #[test]
+    fn test_key_based_keeps_latest_per_entity() {
+        let config = KeyCompactionConfig {
+            event_types: vec!["user.updated".to_string(), "audit.updated".to_string()],
+            keep_latest: 2,
+            tombstone_event_types: vec!["user.deleted".to_string()],
+            tombstone_grace_secs: 100,
+        };
+        let retention = RetentionPolicies::new(vec![crate::retention::RetentionRule {
+            namespace: Some("audit".to_string()),
+            legal_hold: true,
+            ..Default::default()
+        }])
+        .unwrap();
+
+        let events = vec![
+            keyed("user-1", "user.updated", 50),
+            keyed("user-1", "user.created", 45),
+            keyed("user-1", "user.updated", 40),
+            keyed("user-1", "user.updated", 30),
+            keyed("user-2", "user.updated", 20),
+            keyed("audit-1", "audit.updated", 30),
+            keyed("audit-1", "audit.updated", 20),
+            keyed("audit-1", "audit.updated", 10),
+        ];
+        let verdict = config.evaluate(&events, Utc::now(), &retention);
+        let removed: Vec<usize> = (0..events.len()).filter(|&i| verdict.removed[i]).collect();
+        // Only the oldest update of user-1 goes; the held audit events stay
+        assert_eq!(removed, vec![0]);
+        assert_eq!((verdict.events_superseded, verdict.events_tombstoned), (1, 0));
+
+        // A tombstone removes the whole entity once past its grace period
+        let mut events = events;
+        events.push(keyed("user-1", "user.deleted", 25));
+        events.push(keyed("user-1", "user.updated", 5));
+        events.push(keyed("user-2", "user.deleted", 15));
+        let verdict = config.evaluate(&events, Utc::now(), &retention);
+        let removed: Vec<usize> = (0..events.len()).filter(|&i| verdict.removed[i]).collect();
+        assert_eq!(removed, vec![0, 2]);
+        assert_eq!(verdict.events_tombstoned, 0);
+
+        let later = Utc::now() + chrono::Duration::seconds(200);
+        let verdict = config.evaluate(&events, later, &retention);
+        let removed: Vec<usize> = (0..events.len()).filter(|&i| verdict.removed[i]).collect();
+        assert_eq!(removed, vec![0, 1, 2, 3, 4, 8, 10]);
+        assert_eq!((verdict.events_superseded, verdict.events_tombstoned), (0, 7));
+    }
+
+    #[test]
+    fn test_key_based_compaction_through_store() {
+        use crate::application::dto::QueryEventsRequest;
+        use crate::store::{EventStore, EventStoreConfig};
+
+        let temp_dir = TempDir::new().unwrap();
+        let store = EventStore::with_config(EventStoreConfig {
+            storage_dir: Some(temp_dir.path().to_path_buf()),
+            compaction_config: CompactionConfig {
+                strategy: CompactionStrategy::KeyBased,
+                key_compaction: KeyCompactionConfig {
+                    event_types: vec!["user.updated".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+
+        let events: Vec<Event> = (0..20)
+            .map(|i| keyed(&format!("user-{}", i % 2), "user.updated", 100 - i))
+            .collect();
+        for event in events.clone() {
+            store.ingest(event).unwrap();
+        }
+
+        let result = store.compact().unwrap();
+        assert_eq!((result.events_superseded, result.events_compacted), (18, 2));
+        assert!(result.space_reclaimed_bytes > 0);
+        assert_eq!(result.space_reclaimed_bytes, result.bytes_before - result.bytes_after);
+
+        assert_eq!(store.stats().total_events, 2);
+        let latest = store
+            .query(QueryEventsRequest {
+                entity_id: Some("user-1".to_string()),
+                event_type: None,
+                tenant_id: None,
+                as_of: None,
+                since: None,
+                until: None,
+                limit: None,
+                payload_contains: None,
+            })
+            .unwrap();
+        assert_eq!(latest.iter().map(|event| event.id).collect::<Vec<_>>(), vec![events[19].id]);
+
+        let manager = store.compaction_manager().unwrap();
+        assert_eq!(manager.stats().total_events_superseded, 18);
+        let parquet = ParquetStorage::new(temp_dir.path()).unwrap();
+        assert_eq!(parquet.load_all_events().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_compaction_writes_sorted_segments() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut storage = ParquetStorage::new(temp_dir.path()).unwrap();
+        for file in 0..3 {
+            for i in 0..10 {
+                storage.append_event(event((i * 7 + file) % 5)).unwrap();
+            }
+            storage.flush().unwrap();
+        }
+        assert!(storage.manifest().files.values().all(|stats| !stats.sorted));
+
+        let config = CompactionConfig {
+            strategy: CompactionStrategy::FullCompaction,
+            ..Default::default()
+        };
+        let manager = CompactionManager::new(temp_dir.path(), config);
+        let result = manager.compact().unwrap();
+        assert_eq!((result.files_compacted, result.events_compacted), (3, 30));
+
+        let manifest = storage.manifest();
+        assert_eq!(manifest.files.len(), 1);
+        let (file_name, stats) = manifest.files.iter().next().unwrap();
+        assert!(stats.sorted);
+        assert_eq!(stats.min_entity_id, "entity-0");
+        assert_eq!(stats.max_entity_id, "entity-4");
+
+        let events = storage
+            .load_events_from_file(&temp_dir.path().join(file_name))
+            .unwrap();
+        assert!(events
+            .windows(2)
+            .all(|pair| segment_sort_key(&pair[0]) <= segment_sort_key(&pair[1])));
+    }
+
+    #[test]
+    fn test_cold_segments_move_to_object_store() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().join("data");
+        let cold = ColdStorageConfig {
+            object_store: ObjectStoreConfig::Local {
+                path: temp_dir.path().join("objects"),
+            },
+            tier_after_secs: 0,
+            retention: RetentionPolicy::default(),
+        };
+        let store = cold.object_store.open().unwrap();
+
+        let mut storage = ParquetStorage::new(&data_dir)
+            .unwrap()
+            .with_cold_storage(Arc::clone(&store));
+        for i in 0..10 {
+            storage.append_event(event(i)).unwrap();
+        }
+        storage.flush().unwrap();
+
+        let config = CompactionConfig {
+            cold_storage: Some(cold),
+            ..Default::default()
+        };
+        let manager = CompactionManager::new(&data_dir, config).with_cold_storage(Arc::clone(&store));
+
+        let result = manager.tier_cold_segments().unwrap();
+        assert_eq!(result.segments_tiered, 1);
+        assert_eq!(storage.stats().unwrap().total_files, 0);
+        assert_eq!(manager.stats().total_segments_tiered, 1);
+
+        // Tiered and local files are read together
+        for i in 10..15 {
+            storage.append_event(event(i)).unwrap();
+        }
+        storage.flush().unwrap();
+        assert_eq!(storage.load_all_events().unwrap().len(), 15);
+        assert_eq!(fs::read_dir(data_dir.join("cold-cache")).unwrap().count(), 0);
+
+        storage.clear().unwrap();
+        assert!(storage.load_all_events().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_retention_and_legal_holds_apply_to_cold_segments() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().join("data");
+        let cold = ColdStorageConfig {
+            object_store: ObjectStoreConfig::Local {
+                path: temp_dir.path().join("objects"),
+            },
+            tier_after_secs: 0,
+            retention: RetentionPolicy {
+                max_age_secs: None,
+                keep_last: Some(0),
+            },
+        };
+        let store = cold.object_store.open().unwrap();
+        let retention = RetentionPolicies::new(vec![
+            crate::retention::RetentionRule {
+                max_age_secs: Some(60),
+                ..Default::default()
+            },
+            crate::retention::RetentionRule {
+                namespace: Some("audit".to_string()),
+                legal_hold: true,
+                ..Default::default()
+            },
+        ])
+        .unwrap();
+
+        // One segment with held events, one without
+        let mut storage = ParquetStorage::new(&data_dir)
+            .unwrap()
+            .with_cold_storage(Arc::clone(&store));
+        for i in 0..3 {
+            storage.append_event(keyed(&format!("user-{}", i), "user.created", 3600)).unwrap();
+        }
+        for i in 0..2 {
+            storage.append_event(keyed(&format!("audit-{}", i), "audit.logged", 3600)).unwrap();
+        }
+        storage.flush().unwrap();
+        for i in 3..6 {
+            storage.append_event(keyed(&format!("user-{}", i), "user.created", 3600)).unwrap();
+        }
+        storage.flush().unwrap();
+
+        let config = CompactionConfig {
+            cold_storage: Some(cold),
+            ..Default::default()
+        };
+        let manager = CompactionManager::new(&data_dir, config)
+            .with_cold_storage(Arc::clone(&store))
+            .with_retention(Arc::new(retention));
+
+        // Segment expiry spares the segment holding held events
+        let result = manager.tier_cold_segments().unwrap();
+        assert_eq!(result.segments_tiered, 2);
+        assert_eq!(result.segments_expired, 1);
+        assert_eq!(result.segments_held, 1);
+
+        // Expired events are removed from the cold segment that remains
+        assert_eq!(manager.apply_retention().unwrap(), 3);
+        let events = storage.load_all_events().unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|e| e.event_type_str() == "audit.logged"));
+
+        // Downloads are cleaned up after rewriting
+        let downloads = fs::read_dir(data_dir.join(COLD_WORK_DIR)).unwrap();
+        assert!(!downloads
+            .map(|entry| entry.unwrap().path())
+            .any(|path| path.extension().is_some_and(|ext| ext == "parquet")));
+    }
+
+    #[test]
+    fn test_key_based_compaction_covers_cold_segments() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().join("data");
+        let cold = ColdStorageConfig {
+            object_store: ObjectStoreConfig::Local {
+                path: temp_dir.path().join("objects"),
+            },
+            tier_after_secs: 0,
+            retention: RetentionPolicy::default(),
+        };
+        let store = cold.object_store.open().unwrap();
+
+        let mut storage = ParquetStorage::new(&data_dir)
+            .unwrap()
+            .with_cold_storage(Arc::clone(&store));
+        let tiered = vec![
+            keyed("user-0", "user.updated", 300),
+            keyed("user-0", "user.updated", 200),
+            keyed("user-1", "user.updated", 300),
+        ];
+        for event in tiered.clone() {
+            storage.append_event(event).unwrap();
+        }
+        storage.flush().unwrap();
+
+        let config = CompactionConfig {
+            strategy: CompactionStrategy::KeyBased,
+            key_compaction: KeyCompactionConfig {
+                event_types: vec!["user.updated".to_string()],
+                tombstone_event_types: vec!["user.deleted".to_string()],
+                tombstone_grace_secs: 0,
+                ..Default::default()
+            },
+            cold_storage: Some(cold),
+            ..Default::default()
+        };
+        let manager = CompactionManager::new(&data_dir, config).with_cold_storage(Arc::clone(&store));
+        assert_eq!(manager.tier_cold_segments().unwrap().segments_tiered, 1);
+
+        // Newer local events supersede and delete the tiered ones
+        let latest = keyed("user-0", "user.updated", 100);
+        storage.append_event(latest.clone()).unwrap();
+        storage.append_event(keyed("user-1", "user.deleted", 100)).unwrap();
+        storage.flush().unwrap();
+
+        let (result, removed) = manager.compact_removing().unwrap();
+        assert_eq!((result.events_superseded, result.events_tombstoned), (2, 2));
+        assert!(tiered.iter().all(|event| removed.contains(&event.id)));
+
+        // The emptied segment is gone, so nothing reappears on reload
+        assert!(object_store::block_on(store.list(COLD_SEGMENT_PREFIX)).unwrap().is_empty());
+        let events = storage.load_all_events().unwrap();
+        assert_eq!(events.iter().map(|event| event.id).collect::<Vec<_>>(), vec![latest.id]);
+    }
 }
@@ -8,9 +8,24 @@
 /// - Secure credential handling
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use crate::backup::{BackupConfig, BackupEncryptionConfig};
 use crate::error::{AllSourceError, Result};
+use crate::infrastructure::cluster::{AckMode, ForwardingConfig, RaftConfig, ReplicationConfig};
+use crate::infrastructure::security::IpNetwork;
+use crate::infrastructure::persistence::{
+    AuthStorageConfig, ObjectStoreConfig, RetentionPolicy, StorageEngineConfig,
+};
+use crate::infrastructure::repositories::AuditChainConfig;
+use crate::rate_limit::{CostModel, RateLimitConfig};
+use crate::retention::{RetentionConfig, RetentionPolicies, RetentionRule};
+use crate::security::adaptive_rate_limit::AdaptiveRateLimitConfig;
+use crate::security::jwt_keys::{JwtAlgorithm, JwtKeyConfig};
+use crate::security::kms::KmsConfig;
+use crate::security::oidc::OidcConfig;
+use crate::security::payload_encryption::{PayloadEncryptionConfig, SensitiveFieldPolicy};
 
 /// Main application configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +37,14 @@ pub struct Config {
     pub backup: BackupConfigFile,
     pub metrics: MetricsConfig,
     pub logging: LoggingConfig,
+    #[serde(default)]
+    pub cluster: ClusterConfig,
+    /// Sensitive payload field encryption (`[payload_encryption]` table)
+    #[serde(default)]
+    pub payload_encryption: PayloadEncryptionConfig,
+    /// Tamper-evident audit log (`[audit]` table)
+    #[serde(default)]
+    pub audit: AuditConfigFile,
 }
 
 impl Default for Config {
@@ -34,6 +57,9 @@ impl Default for Config {
             backup: BackupConfigFile::default(),
             metrics: MetricsConfig::default(),
             logging: LoggingConfig::default(),
+            cluster: ClusterConfig::default(),
+            payload_encryption: PayloadEncryptionConfig::default(),
+            audit: AuditConfigFile::default(),
         }
     }
 }
@@ -48,6 +74,10 @@ pub struct ServerConfig {
     pub request_timeout_secs: u64,
     pub cors_enabled: bool,
     pub cors_origins: Vec<String>,
+    /// Reverse proxies whose `X-Forwarded-For` header is trusted for the
+    /// client address (empty = always use the socket address)
+    #[serde(default)]
+    pub trusted_proxies: Vec<IpNetwork>,
 }
 
 impl Default for ServerConfig {
@@ -60,6 +90,7 @@ impl Default for ServerConfig {
             request_timeout_secs: 30,
             cors_enabled: true,
             cors_origins: vec!["*".to_string()],
+            trusted_proxies: Vec::new(),
         }
     }
 }
@@ -71,8 +102,29 @@ pub struct StorageConfig {
     pub wal_dir: PathBuf,
     pub batch_size: usize,
     pub compression: CompressionType,
+    /// Expire events older than this unless a retention rule says otherwise
     pub retention_days: Option<u32>,
     pub max_storage_gb: Option<u32>,
+    /// Storage engine holding the events (`[storage.engine]` table)
+    #[serde(default)]
+    pub engine: StorageEngineConfig,
+    /// Per-tenant and per-namespace retention (`[[storage.retention.rules]]`)
+    #[serde(default)]
+    pub retention: RetentionConfig,
+}
+
+impl StorageConfig {
+    /// Retention rules, with `retention_days` as the catch-all rule
+    pub fn retention_config(&self) -> RetentionConfig {
+        let mut retention = self.retention.clone();
+        if let Some(days) = self.retention_days {
+            retention.rules.push(RetentionRule {
+                max_age_secs: Some(u64::from(days) * 86_400),
+                ..Default::default()
+            });
+        }
+        retention
+    }
 }
 
 impl Default for StorageConfig {
@@ -84,6 +136,8 @@ impl Default for StorageConfig {
             compression: CompressionType::Lz4,
             retention_days: None,
             max_storage_gb: None,
+            engine: StorageEngineConfig::default(),
+            retention: RetentionConfig::default(),
         }
     }
 }
@@ -106,6 +160,67 @@ pub struct AuthConfig {
     pub password_min_length: usize,
     pub require_email_verification: bool,
     pub session_timeout_minutes: u64,
+    /// OpenID Connect login (None = local accounts only)
+    #[serde(default)]
+    pub oidc: Option<OidcConfig>,
+    /// Where users and API keys are kept (`[auth.storage]` table)
+    #[serde(default)]
+    pub storage: AuthStorageConfig,
+    /// JWT signing keys when no shared secret is set (`[auth.signing_keys]` table)
+    #[serde(default)]
+    pub signing_keys: JwtSigningConfigFile,
+}
+
+/// JWT signing key configuration
+///
+/// By default the key ring is kept on disk wrapped by a local KMS key, so
+/// issued tokens survive restarts. Nodes that must accept each other's
+/// tokens point `key_ring` at shared storage and use a shared KMS.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JwtSigningConfigFile {
+    /// Keep the key ring in `key_ring` (false = in-memory keys that do not
+    /// survive a restart)
+    pub persistent: bool,
+    /// Algorithm of generated keys
+    pub algorithm: JwtAlgorithm,
+    /// Generate a new signing key after this long (seconds, 0 = never rotate)
+    pub rotation_interval_secs: u64,
+    /// How long a rotated-out key still verifies tokens (seconds)
+    pub grace_period_secs: u64,
+    /// KMS wrapping the key ring (`[auth.signing_keys.kms]` table); a local
+    /// KMS keeps its key file under the data directory
+    pub kms: KmsConfig,
+    /// KMS key (id or alias) wrapping the key ring; created if missing on a local KMS
+    pub wrapping_key: String,
+    /// Key ring file (None = `<data_dir>/kms/jwt_key_ring.json`)
+    pub key_ring: Option<PathBuf>,
+}
+
+impl Default for JwtSigningConfigFile {
+    fn default() -> Self {
+        let keys = JwtKeyConfig::default();
+        Self {
+            persistent: true,
+            algorithm: keys.algorithm,
+            rotation_interval_secs: keys.rotation_interval_secs,
+            grace_period_secs: keys.grace_period_secs,
+            kms: KmsConfig::default(),
+            wrapping_key: "jwt-signing".to_string(),
+            key_ring: None,
+        }
+    }
+}
+
+impl JwtSigningConfigFile {
+    /// Settings for the JWT key manager
+    pub fn key_config(&self) -> JwtKeyConfig {
+        JwtKeyConfig {
+            algorithm: self.algorithm,
+            rotation_interval_secs: self.rotation_interval_secs,
+            grace_period_secs: self.grace_period_secs,
+        }
+    }
 }
 
 impl Default for AuthConfig {
@@ -117,6 +232,9 @@ impl Default for AuthConfig {
             password_min_length: 8,
             require_email_verification: false,
             session_timeout_minutes: 60,
+            oidc: None,
+            storage: AuthStorageConfig::default(),
+            signing_keys: JwtSigningConfigFile::default(),
         }
     }
 }
@@ -128,6 +246,21 @@ pub struct RateLimitConfigFile {
     pub default_tier: RateLimitTier,
     pub requests_per_minute: Option<u32>,
     pub burst_size: Option<u32>,
+    /// Static token buckets, or per-tenant limits adapted to load
+    #[serde(default)]
+    pub mode: RateLimitMode,
+    /// How often the adaptive limiter samples system load
+    #[serde(default = "default_load_sample_interval_secs")]
+    pub load_sample_interval_secs: u64,
+    /// Request cost models by tier, replacing the built-in ones
+    #[serde(default)]
+    pub costs: HashMap<RateLimitTier, CostModel>,
+    #[serde(default)]
+    pub adaptive: AdaptiveRateLimitConfig,
+}
+
+fn default_load_sample_interval_secs() -> u64 {
+    15
 }
 
 impl Default for RateLimitConfigFile {
@@ -137,11 +270,53 @@ impl Default for RateLimitConfigFile {
             default_tier: RateLimitTier::Professional,
             requests_per_minute: None,
             burst_size: None,
+            mode: RateLimitMode::default(),
+            load_sample_interval_secs: default_load_sample_interval_secs(),
+            costs: HashMap::new(),
+            adaptive: AdaptiveRateLimitConfig::default(),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+impl RateLimitConfigFile {
+    /// Token bucket limits for the default tier, with explicit overrides
+    pub fn limiter_config(&self) -> RateLimitConfig {
+        let mut config = match self.default_tier {
+            RateLimitTier::Free => RateLimitConfig::free_tier(),
+            RateLimitTier::Professional | RateLimitTier::Custom => RateLimitConfig::professional(),
+            RateLimitTier::Unlimited => RateLimitConfig::unlimited(),
+        };
+        if let Some(requests_per_minute) = self.requests_per_minute {
+            config.requests_per_minute = requests_per_minute;
+        }
+        if let Some(burst_size) = self.burst_size {
+            config.burst_size = burst_size;
+        }
+        config
+    }
+
+    /// Request cost model for the default tier
+    pub fn cost_model(&self) -> CostModel {
+        self.costs
+            .get(&self.default_tier)
+            .cloned()
+            .unwrap_or_else(|| match self.default_tier {
+                RateLimitTier::Free => CostModel::free_tier(),
+                RateLimitTier::Professional | RateLimitTier::Custom => CostModel::professional(),
+                RateLimitTier::Unlimited => CostModel::flat(),
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RateLimitMode {
+    #[default]
+    Static,
+    Adaptive,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum RateLimitTier {
     Free,
@@ -150,6 +325,104 @@ pub enum RateLimitTier {
     Custom,
 }
 
+/// Cluster replication configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClusterConfig {
+    pub enabled: bool,
+    /// This node's ID; must appear in `peers`
+    pub node_id: u32,
+    /// Every node in the cluster, this one included
+    pub peers: Vec<ClusterPeer>,
+    pub partition_count: u32,
+    /// Copies of each partition, including the leader
+    pub replication_factor: usize,
+    pub ack_mode: AckMode,
+    pub ack_timeout_ms: u64,
+    /// How long a follower holds a read for a write it has not applied yet
+    pub read_wait_timeout_ms: u64,
+    /// Shared secret for node-to-node requests
+    pub cluster_secret: Option<String>,
+    /// Metadata leader heartbeat period
+    pub heartbeat_interval_ms: u64,
+    /// Minimum time without a heartbeat before a metadata election
+    pub election_timeout_ms: u64,
+    /// Time without a heartbeat before a node is marked unhealthy
+    pub failure_timeout_ms: u64,
+    /// Proxy entity requests to the partition owner and fan out queries
+    pub forward_requests: bool,
+    pub forward_timeout_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ClusterPeer {
+    pub id: u32,
+    /// host:port of the peer's API
+    pub address: String,
+}
+
+impl Default for ClusterConfig {
+    fn default() -> Self {
+        let replication = ReplicationConfig::default();
+        let raft = RaftConfig::default();
+        let forwarding = ForwardingConfig::default();
+        Self {
+            enabled: false,
+            node_id: 0,
+            peers: Vec::new(),
+            partition_count: 32,
+            replication_factor: replication.replication_factor,
+            ack_mode: replication.ack_mode,
+            ack_timeout_ms: replication.ack_timeout.as_millis() as u64,
+            read_wait_timeout_ms: replication.read_wait_timeout.as_millis() as u64,
+            cluster_secret: None,
+            heartbeat_interval_ms: raft.heartbeat_interval.as_millis() as u64,
+            election_timeout_ms: raft.election_timeout.as_millis() as u64,
+            failure_timeout_ms: raft.failure_timeout.as_millis() as u64,
+            forward_requests: true,
+            forward_timeout_ms: forwarding.timeout.as_millis() as u64,
+        }
+    }
+}
+
+impl ClusterConfig {
+    /// Replication settings for this node
+    pub fn replication_config(&self) -> ReplicationConfig {
+        ReplicationConfig {
+            node_id: self.node_id,
+            replication_factor: self.replication_factor,
+            ack_mode: self.ack_mode,
+            ack_timeout: std::time::Duration::from_millis(self.ack_timeout_ms),
+            read_wait_timeout: std::time::Duration::from_millis(self.read_wait_timeout_ms),
+            cluster_secret: self.cluster_secret.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Metadata consensus settings for this node
+    pub fn raft_config(&self, state_path: Option<PathBuf>) -> RaftConfig {
+        RaftConfig {
+            node_id: self.node_id,
+            heartbeat_interval: std::time::Duration::from_millis(self.heartbeat_interval_ms),
+            election_timeout: std::time::Duration::from_millis(self.election_timeout_ms),
+            failure_timeout: std::time::Duration::from_millis(self.failure_timeout_ms),
+            state_path,
+            cluster_secret: self.cluster_secret.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Request forwarding settings for this node
+    pub fn forwarding_config(&self) -> ForwardingConfig {
+        ForwardingConfig {
+            node_id: self.node_id,
+            timeout: std::time::Duration::from_millis(self.forward_timeout_ms),
+            cluster_secret: self.cluster_secret.clone(),
+            ..Default::default()
+        }
+    }
+}
+
 /// Backup configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BackupConfigFile {
@@ -159,6 +432,17 @@ pub struct BackupConfigFile {
     pub retention_count: usize,
     pub compression_level: u8,
     pub verify_after_backup: bool,
+    /// Delete backups older than this many days. The newest
+    /// `retention_count` backups, and any backup a kept incremental builds
+    /// on, are always kept; without an age limit everything else goes.
+    #[serde(default)]
+    pub retention_days: Option<u32>,
+    /// Copy backups to an object store (`[backup.object_store]` table)
+    #[serde(default)]
+    pub object_store: Option<ObjectStoreConfig>,
+    /// Encrypt backups through the KMS (`[backup.encryption]` table)
+    #[serde(default)]
+    pub encryption: Option<BackupEncryptionConfig>,
 }
 
 impl Default for BackupConfigFile {
@@ -170,6 +454,57 @@ impl Default for BackupConfigFile {
             retention_count: 7,
             compression_level: 6,
             verify_after_backup: true,
+            retention_days: None,
+            object_store: None,
+            encryption: None,
+        }
+    }
+}
+
+impl BackupConfigFile {
+    /// Settings for the backup manager behind the admin backup endpoints
+    pub fn manager_config(&self) -> BackupConfig {
+        BackupConfig {
+            backup_dir: self.backup_dir.clone(),
+            compression_level: flate2::Compression::new(u32::from(self.compression_level.min(9))),
+            verify_after_backup: self.verify_after_backup,
+            object_store: self.object_store.clone(),
+            retention: RetentionPolicy {
+                max_age_secs: self.retention_days.map(|days| u64::from(days) * 86_400),
+                keep_last: Some(self.retention_count),
+            },
+        }
+    }
+}
+
+/// Audit log configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuditConfigFile {
+    /// KMS holding the checkpoint signing key (`[audit.kms]` table); a local
+    /// KMS keeps its key file under the data directory
+    pub kms: KmsConfig,
+    /// Checkpoint signing key (id or alias); created if missing on a local KMS
+    pub signing_key: String,
+    /// Append a signed checkpoint after every N audit events (0 = never)
+    pub checkpoint_interval: u64,
+}
+
+impl Default for AuditConfigFile {
+    fn default() -> Self {
+        Self {
+            kms: KmsConfig::default(),
+            signing_key: "audit-chain".to_string(),
+            checkpoint_interval: AuditChainConfig::default().checkpoint_interval,
+        }
+    }
+}
+
+impl AuditConfigFile {
+    /// Settings for the hash-chained audit repository
+    pub fn chain_config(&self) -> AuditChainConfig {
+        AuditChainConfig {
+            checkpoint_interval: self.checkpoint_interval,
         }
     }
 }
@@ -266,16 +601,72 @@ impl Config {
             config.server.port = port.parse()
                 .map_err(|_| AllSourceError::ValidationError("Invalid port number".to_string()))?;
         }
+        if let Ok(proxies) = std::env::var("ALLSOURCE_TRUSTED_PROXIES") {
+            config.server.trusted_proxies = proxies
+                .split(',')
+                .filter(|proxy| !proxy.trim().is_empty())
+                .map(|proxy| {
+                    proxy.parse().map_err(|_| {
+                        AllSourceError::ValidationError(format!("Invalid trusted proxy: {}", proxy))
+                    })
+                })
+                .collect::<Result<_>>()?;
+        }
 
         // Storage
         if let Ok(data_dir) = std::env::var("ALLSOURCE_DATA_DIR") {
             config.storage.data_dir = PathBuf::from(data_dir);
         }
+        if let Some(engine) = storage_engine_from_env(&config.storage.data_dir)? {
+            config.storage.engine = engine;
+        }
 
         // Auth
         if let Ok(jwt_secret) = std::env::var("ALLSOURCE_JWT_SECRET") {
             config.auth.jwt_secret = jwt_secret;
         }
+        config.auth.oidc = oidc_from_env()?;
+        if let Some(storage) = auth_storage_from_env(&config.storage.data_dir)? {
+            config.auth.storage = storage;
+        }
+        if let Ok(algorithm) = std::env::var("ALLSOURCE_JWT_ALGORITHM") {
+            config.auth.signing_keys.algorithm = match algorithm.to_uppercase().as_str() {
+                "HS256" => JwtAlgorithm::HS256,
+                "RS256" => JwtAlgorithm::RS256,
+                "ES256" => JwtAlgorithm::ES256,
+                other => {
+                    return Err(AllSourceError::ValidationError(format!(
+                        "Invalid JWT algorithm: {}",
+                        other
+                    )))
+                }
+            };
+        }
+        if let Ok(key_ring) = std::env::var("ALLSOURCE_JWT_KEY_RING") {
+            config.auth.signing_keys.key_ring = Some(PathBuf::from(key_ring));
+        }
+        if let Some(cluster) = cluster_from_env()? {
+            config.cluster = cluster;
+        }
+
+        // Payload encryption
+        if let Some(payload_encryption) = payload_encryption_from_env()? {
+            config.payload_encryption = payload_encryption;
+        }
+
+        // Rate limiting
+        if let Ok(mode) = std::env::var("ALLSOURCE_RATE_LIMIT_MODE") {
+            config.rate_limit.mode = match mode.to_lowercase().as_str() {
+                "static" => RateLimitMode::Static,
+                "adaptive" => RateLimitMode::Adaptive,
+                other => {
+                    return Err(AllSourceError::ValidationError(format!(
+                        "Invalid rate limit mode: {}",
+                        other
+                    )))
+                }
+            };
+        }
 
         Ok(config)
     }
@@ -316,16 +707,52 @@ impl Config {
         if env_config.server.port != ServerConfig::default().port {
             self.server.port = env_config.server.port;
         }
+        if !env_config.server.trusted_proxies.is_empty() {
+            self.server.trusted_proxies = env_config.server.trusted_proxies;
+        }
 
         // Merge storage config
         if env_config.storage.data_dir != StorageConfig::default().data_dir {
             self.storage.data_dir = env_config.storage.data_dir;
         }
+        if env_config.storage.engine != StorageEngineConfig::default() {
+            self.storage.engine = env_config.storage.engine;
+        }
 
         // Merge auth config
         if env_config.auth.jwt_secret != AuthConfig::default().jwt_secret {
             self.auth.jwt_secret = env_config.auth.jwt_secret;
         }
+        if env_config.auth.oidc.is_some() {
+            self.auth.oidc = env_config.auth.oidc;
+        }
+        if env_config.auth.storage != AuthStorageConfig::default() {
+            self.auth.storage = env_config.auth.storage;
+        }
+        if env_config.auth.signing_keys.algorithm != JwtSigningConfigFile::default().algorithm {
+            self.auth.signing_keys.algorithm = env_config.auth.signing_keys.algorithm;
+        }
+        if env_config.auth.signing_keys.key_ring.is_some() {
+            self.auth.signing_keys.key_ring = env_config.auth.signing_keys.key_ring;
+        }
+
+        // Merge rate limit config
+        if env_config.rate_limit.mode != RateLimitMode::default() {
+            self.rate_limit.mode = env_config.rate_limit.mode;
+        }
+
+        // Merge cluster config
+        if env_config.cluster.enabled {
+            self.cluster = env_config.cluster;
+        }
+
+        // Merge payload encryption config
+        if env_config.payload_encryption.enabled {
+            self.payload_encryption.enabled = true;
+            if !env_config.payload_encryption.policies.is_empty() {
+                self.payload_encryption.policies = env_config.payload_encryption.policies;
+            }
+        }
     }
 
     /// Validate configuration
@@ -342,12 +769,99 @@ impl Config {
             tracing::warn!("⚠️  Using default JWT secret - INSECURE for production!");
         }
 
+        if self.rate_limit.mode == RateLimitMode::Adaptive && self.rate_limit.load_sample_interval_secs == 0 {
+            return Err(AllSourceError::ValidationError(
+                "Load sample interval cannot be 0".to_string(),
+            ));
+        }
+
+        if self.cluster.enabled {
+            if !self.cluster.peers.iter().any(|p| p.id == self.cluster.node_id) {
+                return Err(AllSourceError::ValidationError(format!(
+                    "Cluster node {} is not listed in peers",
+                    self.cluster.node_id
+                )));
+            }
+            if self.cluster.partition_count == 0 || self.cluster.replication_factor == 0 {
+                return Err(AllSourceError::ValidationError(
+                    "Cluster partition count and replication factor must be positive".to_string(),
+                ));
+            }
+            // Replication, Raft and forwarding routes are authenticated only by the secret
+            if self.cluster.cluster_secret.as_deref().is_none_or(str::is_empty) {
+                return Err(AllSourceError::ValidationError(
+                    "Cluster mode requires a cluster secret".to_string(),
+                ));
+            }
+            if self.cluster.heartbeat_interval_ms == 0
+                || self.cluster.heartbeat_interval_ms >= self.cluster.election_timeout_ms
+            {
+                return Err(AllSourceError::ValidationError(
+                    "Cluster heartbeat interval must be positive and shorter than the election timeout"
+                        .to_string(),
+                ));
+            }
+        }
+
         // Validate storage paths
         if self.storage.data_dir.as_os_str().is_empty() {
             return Err(AllSourceError::ValidationError(
                 "Data directory path cannot be empty".to_string(),
             ));
         }
+        match &self.storage.engine {
+            StorageEngineConfig::Rocksdb { path } if path.as_os_str().is_empty() => {
+                return Err(AllSourceError::ValidationError(
+                    "RocksDB storage engine path cannot be empty".to_string(),
+                ));
+            }
+            StorageEngineConfig::Postgres { url, max_connections }
+                if url.is_empty() || *max_connections == 0 =>
+            {
+                return Err(AllSourceError::ValidationError(
+                    "Postgres storage engine needs a URL and at least one connection".to_string(),
+                ));
+            }
+            _ => {}
+        }
+
+        match &self.auth.storage {
+            AuthStorageConfig::Rocksdb { path } if path.as_os_str().is_empty() => {
+                return Err(AllSourceError::ValidationError(
+                    "RocksDB auth storage path cannot be empty".to_string(),
+                ));
+            }
+            AuthStorageConfig::Rocksdb { path }
+                if matches!(&self.storage.engine, StorageEngineConfig::Rocksdb { path: engine } if engine == path) =>
+            {
+                return Err(AllSourceError::ValidationError(
+                    "RocksDB auth storage needs a path of its own, not the storage engine's".to_string(),
+                ));
+            }
+            AuthStorageConfig::Postgres { url, max_connections }
+                if url.is_empty() || *max_connections == 0 =>
+            {
+                return Err(AllSourceError::ValidationError(
+                    "Postgres auth storage needs a URL and at least one connection".to_string(),
+                ));
+            }
+            _ => {}
+        }
+
+        let signing_keys = &self.auth.signing_keys;
+        if signing_keys.persistent && signing_keys.wrapping_key.is_empty() {
+            return Err(AllSourceError::ValidationError(
+                "Persistent JWT signing keys need a KMS wrapping key".to_string(),
+            ));
+        }
+
+        let retention = self.storage.retention_config();
+        RetentionPolicies::new(retention.rules)?;
+        if retention.sweep_interval_secs == 0 {
+            return Err(AllSourceError::ValidationError(
+                "Retention sweep interval cannot be 0".to_string(),
+            ));
+        }
 
         Ok(())
     }
@@ -369,9 +883,213 @@ impl Config {
     }
 }
 
+/// Auth storage from `ALLSOURCE_AUTH_STORAGE` (`file`, `memory`, `rocksdb`
+/// or `postgres`). RocksDB lives at `ALLSOURCE_AUTH_ROCKSDB_PATH`,
+/// defaulting to `<data_dir>/auth-rocksdb`; Postgres connects to
+/// `ALLSOURCE_DATABASE_URL`.
+fn auth_storage_from_env(data_dir: &Path) -> Result<Option<AuthStorageConfig>> {
+    let Ok(storage) = std::env::var("ALLSOURCE_AUTH_STORAGE") else {
+        return Ok(None);
+    };
+
+    let storage = match storage.to_lowercase().as_str() {
+        "file" => AuthStorageConfig::File,
+        "memory" => AuthStorageConfig::Memory,
+        "rocksdb" => AuthStorageConfig::Rocksdb {
+            path: std::env::var("ALLSOURCE_AUTH_ROCKSDB_PATH")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| data_dir.join("auth-rocksdb")),
+        },
+        "postgres" => AuthStorageConfig::Postgres {
+            url: std::env::var("ALLSOURCE_DATABASE_URL").map_err(|_| {
+                AllSourceError::ValidationError(
+                    "ALLSOURCE_DATABASE_URL is required for the postgres auth storage".to_string(),
+                )
+            })?,
+            max_connections: 5,
+        },
+        other => {
+            return Err(AllSourceError::ValidationError(format!(
+                "Invalid auth storage: {}",
+                other
+            )))
+        }
+    };
+
+    Ok(Some(storage))
+}
+
+/// Storage engine from `ALLSOURCE_STORAGE_ENGINE` (`native`, `memory`,
+/// `rocksdb` or `postgres`). RocksDB lives at `ALLSOURCE_ROCKSDB_PATH`,
+/// defaulting to `<data_dir>/rocksdb`; Postgres connects to
+/// `ALLSOURCE_DATABASE_URL`.
+fn storage_engine_from_env(data_dir: &Path) -> Result<Option<StorageEngineConfig>> {
+    let Ok(engine) = std::env::var("ALLSOURCE_STORAGE_ENGINE") else {
+        return Ok(None);
+    };
+
+    let engine = match engine.to_lowercase().as_str() {
+        "native" => StorageEngineConfig::Native,
+        "memory" => StorageEngineConfig::Memory,
+        "rocksdb" => StorageEngineConfig::Rocksdb {
+            path: std::env::var("ALLSOURCE_ROCKSDB_PATH")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| data_dir.join("rocksdb")),
+        },
+        "postgres" => StorageEngineConfig::Postgres {
+            url: std::env::var("ALLSOURCE_DATABASE_URL").map_err(|_| {
+                AllSourceError::ValidationError(
+                    "ALLSOURCE_DATABASE_URL is required for the postgres storage engine".to_string(),
+                )
+            })?,
+            max_connections: 10,
+        },
+        other => {
+            return Err(AllSourceError::ValidationError(format!(
+                "Invalid storage engine: {}",
+                other
+            )))
+        }
+    };
+
+    Ok(Some(engine))
+}
+
+/// OIDC settings from `ALLSOURCE_OIDC_*` variables; enabled when both the
+/// issuer and client ID are set. Role mapping format: `group=role,group=role`.
+fn oidc_from_env() -> Result<Option<OidcConfig>> {
+    let (Ok(issuer_url), Ok(client_id)) = (
+        std::env::var("ALLSOURCE_OIDC_ISSUER"),
+        std::env::var("ALLSOURCE_OIDC_CLIENT_ID"),
+    ) else {
+        return Ok(None);
+    };
+
+    let mut oidc = OidcConfig {
+        issuer_url,
+        client_id,
+        client_secret: std::env::var("ALLSOURCE_OIDC_CLIENT_SECRET").ok(),
+        ..Default::default()
+    };
+    if let Ok(redirect_uri) = std::env::var("ALLSOURCE_OIDC_REDIRECT_URI") {
+        oidc.redirect_uri = redirect_uri;
+    }
+    if let Ok(role_claim) = std::env::var("ALLSOURCE_OIDC_ROLE_CLAIM") {
+        oidc.role_claim = role_claim;
+    }
+    if let Ok(tenant_claim) = std::env::var("ALLSOURCE_OIDC_TENANT_CLAIM") {
+        oidc.tenant_claim = Some(tenant_claim);
+    }
+    let parse_role = |role: &str| {
+        serde_json::from_value(serde_json::Value::String(role.trim().to_lowercase()))
+            .map_err(|_| AllSourceError::ValidationError(format!("Invalid OIDC role: {}", role)))
+    };
+    if let Ok(default_role) = std::env::var("ALLSOURCE_OIDC_DEFAULT_ROLE") {
+        oidc.default_role = Some(parse_role(&default_role)?);
+    }
+    if let Ok(mapping) = std::env::var("ALLSOURCE_OIDC_ROLE_MAPPING") {
+        for entry in mapping.split(',').filter(|e| !e.trim().is_empty()) {
+            let (value, role) = entry.split_once('=').ok_or_else(|| {
+                AllSourceError::ValidationError(format!("Invalid OIDC role mapping entry: {}", entry))
+            })?;
+            oidc.role_mapping.insert(value.trim().to_string(), parse_role(role)?);
+        }
+    }
+
+    Ok(Some(oidc))
+}
+
+/// Cluster settings from `ALLSOURCE_CLUSTER_*` variables; enabled when
+/// peers are set. Peers format: `id=host:port,id=host:port`. Ack format:
+/// `async`, `sync` or `sync:<min acks>`.
+fn cluster_from_env() -> Result<Option<ClusterConfig>> {
+    let Ok(peers) = std::env::var("ALLSOURCE_CLUSTER_PEERS") else {
+        return Ok(None);
+    };
+    let invalid = |what: &str, value: &str| {
+        AllSourceError::ValidationError(format!("Invalid cluster {}: {}", what, value))
+    };
+
+    let mut cluster = ClusterConfig {
+        enabled: true,
+        cluster_secret: std::env::var("ALLSOURCE_CLUSTER_SECRET").ok(),
+        ..Default::default()
+    };
+    for entry in peers.split(',').filter(|e| !e.trim().is_empty()) {
+        let (id, address) = entry.split_once('=').ok_or_else(|| invalid("peer", entry))?;
+        cluster.peers.push(ClusterPeer {
+            id: id.trim().parse().map_err(|_| invalid("peer", entry))?,
+            address: address.trim().to_string(),
+        });
+    }
+    if let Ok(node_id) = std::env::var("ALLSOURCE_CLUSTER_NODE_ID") {
+        cluster.node_id = node_id.parse().map_err(|_| invalid("node ID", &node_id))?;
+    }
+    if let Ok(factor) = std::env::var("ALLSOURCE_CLUSTER_REPLICATION_FACTOR") {
+        cluster.replication_factor = factor.parse().map_err(|_| invalid("replication factor", &factor))?;
+    }
+    if let Ok(ack) = std::env::var("ALLSOURCE_CLUSTER_ACK") {
+        cluster.ack_mode = match ack.to_lowercase().split_once(':') {
+            None if ack.eq_ignore_ascii_case("async") => AckMode::Async,
+            None if ack.eq_ignore_ascii_case("sync") => AckMode::Sync { min_acks: 1 },
+            Some(("sync", min_acks)) => AckMode::Sync {
+                min_acks: min_acks.parse().map_err(|_| invalid("ack mode", &ack))?,
+            },
+            _ => return Err(invalid("ack mode", &ack)),
+        };
+    }
+    if let Ok(forward) = std::env::var("ALLSOURCE_CLUSTER_FORWARD") {
+        cluster.forward_requests = forward.parse().map_err(|_| invalid("forwarding flag", &forward))?;
+    }
+
+    Ok(Some(cluster))
+}
+
+/// Payload encryption from `ALLSOURCE_PAYLOAD_ENCRYPTION` (`true`/`false`)
+/// and `ALLSOURCE_PAYLOAD_ENCRYPTION_POLICIES`, formatted as
+/// `event.type=path,path;other.type=path`.
+fn payload_encryption_from_env() -> Result<Option<PayloadEncryptionConfig>> {
+    let Ok(enabled) = std::env::var("ALLSOURCE_PAYLOAD_ENCRYPTION") else {
+        return Ok(None);
+    };
+    let mut config = PayloadEncryptionConfig {
+        enabled: enabled.parse().map_err(|_| {
+            AllSourceError::ValidationError(format!("Invalid payload encryption flag: {}", enabled))
+        })?,
+        ..Default::default()
+    };
+    if let Ok(policies) = std::env::var("ALLSOURCE_PAYLOAD_ENCRYPTION_POLICIES") {
+        config.policies = parse_sensitive_field_policies(&policies)?;
+    }
+
+    Ok(Some(config))
+}
+
+fn parse_sensitive_field_policies(value: &str) -> Result<Vec<SensitiveFieldPolicy>> {
+    value
+        .split(';')
+        .filter(|entry| !entry.trim().is_empty())
+        .map(|entry| {
+            let (event_type, fields) = entry.split_once('=').ok_or_else(|| {
+                AllSourceError::ValidationError(format!("Invalid payload encryption policy: {}", entry))
+            })?;
+            Ok(SensitiveFieldPolicy {
+                event_type: event_type.trim().to_string(),
+                fields: fields
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|f| !f.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::security::kms::KmsProvider;
 
     #[test]
     fn test_default_config() {
@@ -400,4 +1118,201 @@ mod tests {
         let deserialized: Config = toml::from_str(&toml).unwrap();
         assert_eq!(config.server.port, deserialized.server.port);
     }
+
+    #[test]
+    fn test_rate_limit_cost_models_by_tier() {
+        let mut config = RateLimitConfigFile::default();
+        assert_eq!(config.cost_model(), CostModel::professional());
+
+        config.default_tier = RateLimitTier::Unlimited;
+        assert_eq!(config.cost_model(), CostModel::flat());
+
+        let config: Config = toml::from_str(&format!(
+            "{}\n{}",
+            toml::to_string(&Config::default()).unwrap().replace("default_tier = \"professional\"", "default_tier = \"free\""),
+            "[rate_limit.costs.free]\ndefault_cost = 2.0\ningest_cost_per_kb = 1.0\n\n[[rate_limit.costs.free.routes]]\nmethod = \"GET\"\npath = \"/api/v1/events/query\"\ncost = 5.0\n",
+        ))
+        .unwrap();
+        let model = config.rate_limit.cost_model();
+        assert_eq!(model.request_cost("GET", "/api/v1/events/query", 0), 5.0);
+        assert_eq!(model.request_cost("POST", "/api/v1/events", 2048), 4.0);
+        assert_eq!(config.rate_limit.limiter_config().requests_per_minute, 60);
+    }
+
+    #[test]
+    fn test_cluster_config() {
+        let mut config = Config::default();
+        assert!(!config.cluster.enabled);
+
+        config.cluster.enabled = true;
+        config.cluster.node_id = 1;
+        config.cluster.ack_mode = AckMode::Sync { min_acks: 1 };
+        assert!(config.validate().is_err());
+
+        config.cluster.peers = vec![
+            ClusterPeer { id: 0, address: "10.0.0.1:3900".to_string() },
+            ClusterPeer { id: 1, address: "10.0.0.2:3900".to_string() },
+        ];
+        assert!(config.validate().is_err());
+
+        config.cluster.cluster_secret = Some("s3cret".to_string());
+        assert!(config.validate().is_ok());
+
+        let deserialized: Config = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(deserialized.cluster.peers, config.cluster.peers);
+        let replication = deserialized.cluster.replication_config();
+        assert_eq!(replication.node_id, 1);
+        assert_eq!(replication.ack_mode, AckMode::Sync { min_acks: 1 });
+
+        let forwarding = deserialized.cluster.forwarding_config();
+        assert!(deserialized.cluster.forward_requests);
+        assert_eq!(forwarding.node_id, 1);
+
+        config.cluster.heartbeat_interval_ms = config.cluster.election_timeout_ms;
+        assert!(config.validate().is_err());
+    }
+    #[test]
+    fn test_audit_config() {
+        let mut config = Config::default();
+        assert_eq!(config.audit.signing_key, "audit-chain");
+        assert_eq!(config.audit.kms.provider, KmsProvider::Local);
+
+        config.audit.kms.provider = KmsProvider::HashicorpVault;
+        config.audit.signing_key = "checkpoints".to_string();
+        config.audit.checkpoint_interval = 50;
+        let deserialized: Config = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(deserialized.audit.kms.provider, KmsProvider::HashicorpVault);
+        assert_eq!(deserialized.audit.signing_key, "checkpoints");
+        assert_eq!(deserialized.audit.chain_config().checkpoint_interval, 50);
+    }
+
+    #[test]
+    fn test_storage_engine_config() {
+        let mut config = Config::default();
+        assert_eq!(config.storage.engine, StorageEngineConfig::Native);
+
+        config.storage.engine = StorageEngineConfig::Rocksdb {
+            path: PathBuf::from("/var/lib/allsource/rocksdb"),
+        };
+        assert!(config.validate().is_ok());
+        let deserialized: Config = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(deserialized.storage.engine, config.storage.engine);
+
+        let config: Config = toml::from_str(&toml::to_string(&Config::default()).unwrap().replace(
+            "engine = \"native\"",
+            "engine = \"postgres\"\nurl = \"postgres://localhost/allsource\"",
+        ))
+        .unwrap();
+        assert_eq!(
+            config.storage.engine,
+            StorageEngineConfig::Postgres {
+                url: "postgres://localhost/allsource".to_string(),
+                max_connections: 10,
+            }
+        );
+
+        let mut config = config;
+        config.storage.engine = StorageEngineConfig::Postgres {
+            url: String::new(),
+            max_connections: 10,
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_auth_storage_config() {
+        let mut config = Config::default();
+        assert_eq!(config.auth.storage, AuthStorageConfig::File);
+
+        config.auth.storage = AuthStorageConfig::Postgres {
+            url: "postgres://localhost/allsource".to_string(),
+            max_connections: 5,
+        };
+        assert!(config.validate().is_ok());
+        let deserialized: Config = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(deserialized.auth.storage, config.auth.storage);
+
+        // Auth and events cannot share one RocksDB database
+        let path = PathBuf::from("/var/lib/allsource/rocksdb");
+        config.storage.engine = StorageEngineConfig::Rocksdb { path: path.clone() };
+        config.auth.storage = AuthStorageConfig::Rocksdb { path };
+        assert!(config.validate().is_err());
+        config.auth.storage = AuthStorageConfig::Rocksdb {
+            path: PathBuf::from("/var/lib/allsource/auth-rocksdb"),
+        };
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_jwt_signing_config() {
+        let mut config = Config::default();
+        assert!(config.auth.signing_keys.persistent);
+        assert_eq!(config.auth.signing_keys.key_config().algorithm, JwtAlgorithm::RS256);
+
+        // Unset fields keep their defaults
+        let mut value = toml::Value::try_from(&config).unwrap();
+        let signing_keys = value["auth"]["signing_keys"].as_table_mut().unwrap();
+        signing_keys.clear();
+        signing_keys.insert("persistent".to_string(), false.into());
+        signing_keys.insert("algorithm".to_string(), "ES256".into());
+        let deserialized: Config = value.try_into().unwrap();
+        assert!(!deserialized.auth.signing_keys.persistent);
+        assert_eq!(deserialized.auth.signing_keys.algorithm, JwtAlgorithm::ES256);
+        assert_eq!(deserialized.auth.signing_keys.wrapping_key, "jwt-signing");
+
+        config.auth.signing_keys.wrapping_key = String::new();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_payload_encryption_config() {
+        let policies = parse_sensitive_field_policies("user.created=ssn, address.street;payment.made=card.number").unwrap();
+        assert_eq!(policies.len(), 2);
+        assert_eq!(policies[0].fields, vec!["ssn", "address.street"]);
+        assert_eq!(policies[1].event_type, "payment.made");
+        assert!(parse_sensitive_field_policies("user.created").is_err());
+
+        let mut config = Config::default();
+        config.payload_encryption.enabled = true;
+        config.payload_encryption.policies = policies;
+        let deserialized: Config = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+        assert!(deserialized.payload_encryption.enabled);
+        assert_eq!(deserialized.payload_encryption.policies, config.payload_encryption.policies);
+
+        let store_config = deserialized.payload_encryption.with_data_dir(Path::new("/var/lib/allsource"));
+        assert_eq!(
+            store_config.kms.unwrap().config.get("key_file").map(String::as_str),
+            Some("/var/lib/allsource/kms/payload_keys.json")
+        );
+        assert_eq!(
+            store_config.policy_file,
+            Some(PathBuf::from("/var/lib/allsource/encryption_policies.json"))
+        );
+    }
+
+    #[test]
+    fn test_storage_retention_config() {
+        let mut config = Config::default();
+        assert!(config.storage.retention_config().rules.is_empty());
+
+        config.storage.retention_days = Some(30);
+        config.storage.retention.rules.push(RetentionRule {
+            tenant_id: Some("acme".to_string()),
+            legal_hold: true,
+            ..Default::default()
+        });
+        assert!(config.validate().is_ok());
+
+        let deserialized: Config = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+        let retention = deserialized.storage.retention_config();
+        assert_eq!(retention.rules.len(), 2);
+        assert!(retention.rules[0].legal_hold);
+        assert_eq!(retention.rules[1].max_age_secs, Some(30 * 86_400));
+
+        config.storage.retention.rules.push(RetentionRule {
+            namespace: Some("audit".to_string()),
+            ..Default::default()
+        });
+        assert!(config.validate().is_err());
+    }
 }
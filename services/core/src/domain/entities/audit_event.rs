@@ -64,6 +64,10 @@ pub enum AuditAction {
     EventIngested,
     EventQueried,
     EventStreamCreated,
+    SnapshotCreated,
+    ReplayStarted,
+    ReplayCancelled,
+    ReplayDeleted,
 
     // Tenant Management
     TenantCreated,
@@ -103,6 +107,8 @@ pub enum AuditAction {
 
     // System
     ConfigurationChanged,
+    CompactionTriggered,
+    ChainCheckpoint,
     BackupCreated,
     BackupRestored,
 }
@@ -117,7 +123,8 @@ impl AuditAction {
             Self::ApiKeyCreated | Self::ApiKeyRevoked | Self::ApiKeyUsed => {
                 AuditCategory::ApiKey
             }
-            Self::EventIngested | Self::EventQueried | Self::EventStreamCreated => {
+            Self::EventIngested | Self::EventQueried | Self::EventStreamCreated | Self::SnapshotCreated
+            | Self::ReplayStarted | Self::ReplayCancelled | Self::ReplayDeleted => {
                 AuditCategory::Event
             }
             Self::TenantCreated | Self::TenantUpdated | Self::TenantActivated | Self::TenantDeactivated | Self::TenantDeleted => {
@@ -138,7 +145,8 @@ impl AuditAction {
             Self::PermissionDenied | Self::RateLimitExceeded | Self::IpBlocked | Self::SuspiciousActivity => {
                 AuditCategory::Security
             }
-            Self::ConfigurationChanged | Self::BackupCreated | Self::BackupRestored => {
+            Self::ConfigurationChanged | Self::CompactionTriggered | Self::ChainCheckpoint | Self::BackupCreated
+            | Self::BackupRestored => {
                 AuditCategory::System
             }
         }
@@ -261,6 +269,18 @@ pub struct AuditEvent {
 
     /// Additional metadata (JSON)
     metadata: Option<JsonValue>,
+
+    /// Position in the tenant's hash chain (set when the event is chained)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sequence: Option<u64>,
+
+    /// Hash of the tenant's previous audit event (None for the first link)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    previous_hash: Option<String>,
+
+    /// SHA-256 over this event's content and `previous_hash`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hash: Option<String>,
 }
 
 impl AuditEvent {
@@ -286,9 +306,84 @@ impl AuditEvent {
             request_id: None,
             error_message: None,
             metadata: None,
+            sequence: None,
+            previous_hash: None,
+            hash: None,
+        }
+    }
+
+    /// Reconstruct an audit event from storage, keeping its original ID and timestamp
+    pub fn reconstruct(
+        id: AuditEventId,
+        tenant_id: TenantId,
+        timestamp: DateTime<Utc>,
+        action: AuditAction,
+        actor: Actor,
+        outcome: AuditOutcome,
+    ) -> Self {
+        Self {
+            id,
+            timestamp,
+            ..Self::new(tenant_id, action, actor, outcome)
         }
     }
 
+    /// Link this event into a tenant hash chain and seal it with its hash
+    pub fn chain(mut self, sequence: u64, previous_hash: Option<String>) -> Self {
+        self.sequence = Some(sequence);
+        self.previous_hash = previous_hash;
+        self.hash = Some(self.compute_hash());
+        self
+    }
+
+    /// Restore stored chain fields without recomputing the hash
+    pub fn with_chain_link(
+        mut self,
+        sequence: Option<u64>,
+        previous_hash: Option<String>,
+        hash: Option<String>,
+    ) -> Self {
+        self.sequence = sequence;
+        self.previous_hash = previous_hash;
+        self.hash = hash;
+        self
+    }
+
+    /// Compute the chain hash over all recorded fields and the previous hash
+    ///
+    /// The timestamp is hashed at microsecond precision so that events
+    /// round-tripped through storage still verify.
+    pub fn compute_hash(&self) -> String {
+        use sha2::{Digest, Sha256};
+
+        let canonical = serde_json::json!({
+            "id": self.id.as_str(),
+            "tenant_id": self.tenant_id.as_str(),
+            "timestamp": self.timestamp.to_rfc3339_opts(chrono::SecondsFormat::Micros, true),
+            "action": self.action,
+            "actor": self.actor,
+            "resource_type": self.resource_type,
+            "resource_id": self.resource_id,
+            "outcome": self.outcome,
+            "ip_address": self.ip_address,
+            "user_agent": self.user_agent,
+            "request_id": self.request_id,
+            "error_message": self.error_message,
+            "metadata": self.metadata,
+            "sequence": self.sequence,
+            "previous_hash": self.previous_hash,
+        });
+
+        let mut hasher = Sha256::new();
+        hasher.update(canonical.to_string().as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Check that the stored hash matches the event content
+    pub fn verify_hash(&self) -> bool {
+        self.hash.as_deref() == Some(self.compute_hash().as_str())
+    }
+
     /// Builder pattern methods
 
     pub fn with_resource(mut self, resource_type: String, resource_id: String) -> Self {
@@ -376,6 +471,18 @@ impl AuditEvent {
         self.metadata.as_ref()
     }
 
+    pub fn sequence(&self) -> Option<u64> {
+        self.sequence
+    }
+
+    pub fn previous_hash(&self) -> Option<&str> {
+        self.previous_hash.as_deref()
+    }
+
+    pub fn hash(&self) -> Option<&str> {
+        self.hash.as_deref()
+    }
+
     /// Check if this is a security event
     pub fn is_security_event(&self) -> bool {
         self.action.is_security_event()
@@ -588,4 +695,29 @@ mod tests {
         assert_eq!(event.action(), deserialized.action());
         assert_eq!(event.outcome(), deserialized.outcome());
     }
+
+    #[test]
+    fn test_audit_event_hash_chain() {
+        let tenant_id = TenantId::new("test-tenant".to_string()).unwrap();
+        let actor = Actor::user("user-1".to_string(), "alice".to_string());
+
+        let first = AuditEvent::new(tenant_id.clone(), AuditAction::Login, actor.clone(), AuditOutcome::Success)
+            .chain(1, None);
+        let second = AuditEvent::new(tenant_id, AuditAction::Logout, actor, AuditOutcome::Success)
+            .chain(2, first.hash().map(str::to_string));
+
+        assert!(first.verify_hash());
+        assert!(second.verify_hash());
+        assert_eq!(second.previous_hash(), first.hash());
+
+        // Any change to the content breaks the hash
+        let tampered = second.clone().with_error("edited".to_string());
+        assert!(!tampered.verify_hash());
+
+        // Serde round trip keeps the chain intact
+        let json = serde_json::to_string(&second).unwrap();
+        let restored: AuditEvent = serde_json::from_str(&json).unwrap();
+        assert!(restored.verify_hash());
+        assert_eq!(restored.sequence(), Some(2));
+    }
 }
@@ -201,6 +201,10 @@ impl EventStream {
         self.watermark
     }
 
+    pub fn expected_version(&self) -> Option<u64> {
+        self.expected_version
+    }
+
     pub fn event_count(&self) -> usize {
         self.events.len()
     }
@@ -0,0 +1,53 @@
+use crate::auth::ApiKey;
+use crate::error::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// Repository trait for API keys
+///
+/// Provides persistent storage for the API keys managed by `AuthManager`.
+/// Only the key hash is stored; the plaintext key is shown once at creation.
+/// Implementations must store the hash, which `ApiKey` omits when
+/// serialized for API responses.
+///
+/// # Thread Safety
+/// Implementations must be thread-safe (Send + Sync).
+#[async_trait]
+pub trait ApiKeyRepository: Send + Sync {
+    /// Save or update an API key
+    ///
+    /// # Errors
+    /// - `StorageError` - If the operation fails
+    async fn save(&self, api_key: &ApiKey) -> Result<()>;
+
+    /// Find an API key by ID
+    ///
+    /// # Returns
+    /// `Some(ApiKey)` if found, `None` otherwise
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<ApiKey>>;
+
+    /// Find an API key by the hash of its plaintext value
+    ///
+    /// # Returns
+    /// `Some(ApiKey)` if found, `None` otherwise
+    async fn find_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>>;
+
+    /// Get all API keys of a tenant, ordered by creation date (oldest first)
+    async fn find_by_tenant(&self, tenant_id: &str) -> Result<Vec<ApiKey>>;
+
+    /// Get all API keys, ordered by creation date (oldest first)
+    async fn find_all(&self) -> Result<Vec<ApiKey>>;
+
+    /// Record when an API key was last used
+    ///
+    /// # Returns
+    /// `true` if updated, `false` if the key was not found
+    async fn update_last_used(&self, id: &Uuid, last_used: DateTime<Utc>) -> Result<bool>;
+
+    /// Delete an API key
+    ///
+    /// # Returns
+    /// `true` if the key was deleted, `false` if it didn't exist
+    async fn delete(&self, id: &Uuid) -> Result<bool>;
+}
@@ -2,6 +2,20 @@ use async_trait::async_trait;
 use crate::domain::entities::{Event, EventStream};
 use crate::domain::value_objects::{EntityId, PartitionKey};
 use crate::error::Result;
+use chrono::{DateTime, Utc};
+
+/// Filter for [`EventStreamRepository::query_events`]; unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct EventStreamQuery {
+    pub entity_id: Option<String>,
+    pub event_type: Option<String>,
+    pub tenant_id: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    /// JSON the payload must contain, as with Postgres `@>`
+    pub payload_contains: Option<serde_json::Value>,
+    pub limit: Option<usize>,
+}
 
 /// Event Stream Repository Trait (SierraDB Pattern)
 ///
@@ -112,6 +126,44 @@ pub trait EventStreamRepository: Send + Sync {
     /// Returns the total number of streams belonging to the specified tenant.
     /// Used for quota enforcement and tenant monitoring.
     async fn count_streams_by_tenant(&self, tenant_id: &crate::domain::value_objects::TenantId) -> Result<usize>;
+
+    /// Append an event to the end of its entity's stream
+    ///
+    /// The event is stored with the stream version it is given, which is
+    /// returned. The default loads the stream and appends with an
+    /// optimistic lock; backends can do this in one round trip.
+    async fn append_event(&self, mut event: Event) -> Result<u64> {
+        let mut stream = self.get_or_create_stream(event.entity_id()).await?;
+        let current = stream.current_version();
+        stream.expect_version(current);
+        event.version = current as i64 + 1;
+        self.append_to_stream(&mut stream, event).await
+    }
+
+    /// Load every stored event, oldest first
+    ///
+    /// Used to rebuild in-memory state on startup. The default walks all
+    /// partitions and orders the events by timestamp.
+    async fn load_all_events(&self) -> Result<Vec<Event>> {
+        let mut events = Vec::new();
+        for partition in 0..PartitionKey::DEFAULT_PARTITION_COUNT {
+            let key = PartitionKey::from_partition_id(partition, PartitionKey::DEFAULT_PARTITION_COUNT)?;
+            for stream in self.get_streams_by_partition(&key).await? {
+                events.extend(stream.events_from(1).into_iter().cloned());
+            }
+        }
+        events.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then(a.id.cmp(&b.id)));
+        Ok(events)
+    }
+
+    /// Answer a query from the backend's own indexes
+    ///
+    /// Returns None when the backend cannot, and the store answers from
+    /// its in-memory index instead (the default). Backends shared by
+    /// several nodes answer here, so results include every node's writes.
+    async fn query_events(&self, _query: &EventStreamQuery) -> Result<Option<Vec<Event>>> {
+        Ok(None)
+    }
 }
 
 /// Read-only stream repository (query optimization)
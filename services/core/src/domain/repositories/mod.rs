@@ -2,8 +2,14 @@ pub mod event_repository;
 pub mod event_stream_repository;
 pub mod audit_event_repository;
 pub mod tenant_repository;
+pub mod user_repository;
+pub mod api_key_repository;
+pub mod session_repository;
 
 pub use event_repository::{EventRepository, EventReader, EventWriter};
-pub use event_stream_repository::{EventStreamRepository, EventStreamReader, EventStreamWriter};
+pub use event_stream_repository::{EventStreamQuery, EventStreamRepository, EventStreamReader, EventStreamWriter};
 pub use audit_event_repository::{AuditEventRepository, AuditEventQuery};
 pub use tenant_repository::{TenantRepository, TenantQuery};
+pub use user_repository::UserRepository;
+pub use api_key_repository::ApiKeyRepository;
+pub use session_repository::SessionRepository;
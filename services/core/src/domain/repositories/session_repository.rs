@@ -0,0 +1,56 @@
+use crate::auth::{RefreshToken, Revocation};
+use crate::error::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// Repository trait for sessions
+///
+/// Provides persistent storage for the refresh tokens and revocations of
+/// `AuthManager`, so logouts survive restarts and, with a shared backend,
+/// are seen by every node. Refresh tokens are stored by hash only.
+///
+/// # Thread Safety
+/// Implementations must be thread-safe (Send + Sync).
+#[async_trait]
+pub trait SessionRepository: Send + Sync {
+    /// Save or update a refresh token
+    ///
+    /// # Errors
+    /// - `StorageError` - If the operation fails
+    async fn save_refresh_token(&self, token: &RefreshToken) -> Result<()>;
+
+    /// Find a refresh token by its hash
+    ///
+    /// # Returns
+    /// `Some(RefreshToken)` if found, `None` otherwise
+    async fn find_refresh_token(&self, token_hash: &str) -> Result<Option<RefreshToken>>;
+
+    /// Get all refresh tokens issued to a user
+    async fn find_refresh_tokens_by_user(&self, user_id: &Uuid) -> Result<Vec<RefreshToken>>;
+
+    /// Mark a refresh token as exchanged
+    ///
+    /// Must be atomic: of concurrent calls for the same token, only one
+    /// succeeds, so a token is exchanged once even across nodes.
+    ///
+    /// # Returns
+    /// `true` if the token was unused and is now marked, `false` if it was
+    /// already used or is unknown
+    async fn mark_refresh_token_used(&self, token_hash: &str, used_at: DateTime<Utc>) -> Result<bool>;
+
+    /// Delete every refresh token of a session
+    async fn delete_session_refresh_tokens(&self, session_id: &str) -> Result<()>;
+
+    /// Save a revoked session or access token
+    async fn save_revocation(&self, revocation: &Revocation) -> Result<()>;
+
+    /// Get all revocations that have not expired at `now`
+    async fn find_revocations(&self, now: DateTime<Utc>) -> Result<Vec<Revocation>>;
+
+    /// Delete refresh tokens and revocations that expired before `now`
+    ///
+    /// # Returns
+    /// The number of entries deleted
+    async fn prune(&self, now: DateTime<Utc>) -> Result<usize>;
+}
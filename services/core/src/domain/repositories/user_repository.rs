@@ -0,0 +1,68 @@
+use crate::auth::User;
+use crate::error::Result;
+use async_trait::async_trait;
+use uuid::Uuid;
+
+/// Repository trait for user accounts
+///
+/// Provides persistent storage for the accounts managed by `AuthManager`.
+/// Implementations must store the password hash, which `User` omits when
+/// serialized for API responses.
+///
+/// # Thread Safety
+/// Implementations must be thread-safe (Send + Sync).
+///
+/// # Example
+/// ```rust
+/// use allsource_core::auth::{Role, User};
+/// use allsource_core::domain::repositories::UserRepository;
+/// use allsource_core::infrastructure::repositories::InMemoryAuthRepository;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let repo = InMemoryAuthRepository::new();
+///
+///     let user = User::new(
+///         "alice".to_string(),
+///         "alice@example.com".to_string(),
+///         "password123",
+///         Role::Developer,
+///         "acme".to_string(),
+///     )?;
+///     UserRepository::save(&repo, &user).await?;
+///
+///     let found = repo.find_by_username("alice").await?;
+///     assert!(found.is_some());
+///     Ok(())
+/// }
+/// ```
+#[async_trait]
+pub trait UserRepository: Send + Sync {
+    /// Save or update a user
+    ///
+    /// # Errors
+    /// - `ValidationError` - If the username is taken by another user
+    /// - `StorageError` - If the operation fails
+    async fn save(&self, user: &User) -> Result<()>;
+
+    /// Find a user by ID
+    ///
+    /// # Returns
+    /// `Some(User)` if found, `None` otherwise
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<User>>;
+
+    /// Find a user by username (exact match)
+    ///
+    /// # Returns
+    /// `Some(User)` if found, `None` otherwise
+    async fn find_by_username(&self, username: &str) -> Result<Option<User>>;
+
+    /// Get all users, ordered by creation date (oldest first)
+    async fn find_all(&self) -> Result<Vec<User>>;
+
+    /// Delete a user
+    ///
+    /// # Returns
+    /// `true` if the user was deleted, `false` if it didn't exist
+    async fn delete(&self, id: &Uuid) -> Result<bool>;
+}
@@ -40,12 +40,21 @@ pub enum AllSourceError {
     #[error("Validation error: {0}")]
     ValidationError(String),
 
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+
     #[error("Concurrency error: {0}")]
     ConcurrencyError(String),
 
     #[error("Queue full: {0}")]
     QueueFull(String),
 
+    #[error("Not leader: {0}")]
+    NotLeader(String),
+
+    #[error("Replication error: {0}")]
+    ReplicationError(String),
+
     #[error("Internal error: {0}")]
     InternalError(String),
 }
@@ -65,6 +74,13 @@ impl From<parquet::errors::ParquetError> for AllSourceError {
     }
 }
 
+#[cfg(feature = "postgres")]
+impl From<sqlx::Error> for AllSourceError {
+    fn from(err: sqlx::Error) -> Self {
+        AllSourceError::StorageError(err.to_string())
+    }
+}
+
 /// Custom Result type for AllSource operations
 pub type Result<T> = std::result::Result<T, AllSourceError>;
 
@@ -83,11 +99,18 @@ impl IntoResponse for AllSourceError {
             | AllSourceError::ValidationError(_) => {
                 (StatusCode::BAD_REQUEST, self.to_string())
             }
+            AllSourceError::PermissionDenied(_) => {
+                (StatusCode::FORBIDDEN, self.to_string())
+            }
             AllSourceError::TenantAlreadyExists(_)
             | AllSourceError::ConcurrencyError(_) => {
                 (StatusCode::CONFLICT, self.to_string())
             }
-            AllSourceError::QueueFull(_) => {
+            AllSourceError::NotLeader(_) => {
+                (StatusCode::MISDIRECTED_REQUEST, self.to_string())
+            }
+            AllSourceError::QueueFull(_)
+            | AllSourceError::ReplicationError(_) => {
                 (StatusCode::SERVICE_UNAVAILABLE, self.to_string())
             }
             AllSourceError::StorageError(_)
@@ -0,0 +1,1435 @@
+//! Consensus-Based Cluster Metadata
+//!
+//! Replicates membership, node health and partition handoffs with Raft, so
+//! every node applies the same changes to its `NodeRegistry` in the same
+//! order and derives the same partition-to-leader map.
+//!
+//! # Design
+//! - **Raft log of metadata commands**: leader election with randomized
+//!   timeouts, log replication and majority commit over HTTP
+//! - **Voters**: the nodes in the registry; membership changes one node at
+//!   a time through the log
+//! - **Failure detection**: the leader's heartbeats double as health checks;
+//!   a node silent for `failure_timeout` is marked unhealthy through the log,
+//!   and healthy again once it answers
+//! - **Durable state**: term, vote and log are written to `state_path`
+//!   before a node answers
+//!
+//! Every node must start from the same initial membership; later members
+//! join with `AddNode`. The log is not compacted, which suits the low rate
+//! of metadata changes.
+//!
+//! # Example
+//! ```ignore
+//! let raft = Arc::new(RaftNode::new(
+//!     RaftConfig { node_id: 0, ..Default::default() },
+//!     registry.clone(),
+//! )?);
+//! raft.clone().spawn();
+//! let app = app.merge(raft.router());
+//!
+//! // From any node; forwarded to the metadata leader
+//! raft.submit(MetadataCommand::AddNode { id: 3, address: "node-3:3900".into() }).await?;
+//! ```
+
+use super::node_registry::{Node, NodeRegistry};
+use super::replication::{check_cluster_secret, CLUSTER_SECRET_HEADER};
+use crate::error::{AllSourceError, Result};
+use crate::infrastructure::persistence::object_store::write_atomically;
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    routing::{delete, get, post},
+    Json, Router,
+};
+use parking_lot::Mutex;
+use rand::Rng;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+/// Most log entries sent in one append
+const MAX_ENTRIES_PER_APPEND: usize = 256;
+
+/// A change to cluster metadata
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MetadataCommand {
+    /// Appended by each new leader so entries of earlier terms can commit
+    Noop,
+    AddNode { id: u32, address: String },
+    RemoveNode { id: u32 },
+    SetHealth { id: u32, healthy: bool },
+    CompleteHandoff { partition: u32, from: u32, to: u32 },
+    /// Followers in sync with a partition's leader during `epoch`
+    SetInSync { partition: u32, epoch: u64, replicas: Vec<u32> },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub term: u64,
+    pub command: MetadataCommand,
+}
+
+/// Raft configuration
+#[derive(Debug, Clone)]
+pub struct RaftConfig {
+    /// This node's ID in the registry
+    pub node_id: u32,
+
+    /// Leader heartbeat period, and how often followers check for a timeout
+    pub heartbeat_interval: Duration,
+
+    /// Followers call an election after hearing nothing for a random time
+    /// between this and twice this
+    pub election_timeout: Duration,
+
+    /// Nodes the leader has not heard from for this long are marked unhealthy
+    pub failure_timeout: Duration,
+
+    /// How long a proposal waits to be committed and applied
+    pub commit_timeout: Duration,
+
+    /// Where term, vote and log are persisted; in memory only if unset
+    pub state_path: Option<PathBuf>,
+
+    /// Shared secret required on Raft and membership endpoints
+    pub cluster_secret: Option<String>,
+}
+
+impl Default for RaftConfig {
+    fn default() -> Self {
+        Self {
+            node_id: 0,
+            heartbeat_interval: Duration::from_millis(100),
+            election_timeout: Duration::from_millis(1000),
+            failure_timeout: Duration::from_secs(5),
+            commit_timeout: Duration::from_secs(5),
+            state_path: None,
+            cluster_secret: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RaftRole {
+    Follower,
+    Candidate,
+    Leader,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoteRequest {
+    pub term: u64,
+    pub candidate_id: u32,
+    pub last_log_index: u64,
+    pub last_log_term: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoteResponse {
+    pub term: u64,
+    pub vote_granted: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppendRequest {
+    pub term: u64,
+    pub leader_id: u32,
+    pub prev_log_index: u64,
+    pub prev_log_term: u64,
+    pub entries: Vec<LogEntry>,
+    pub leader_commit: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppendResponse {
+    pub term: u64,
+    pub success: bool,
+    /// Last index known to match the leader; a retry hint on failure
+    pub match_index: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProposeRequest {
+    pub command: MetadataCommand,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProposeResponse {
+    pub index: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddMemberRequest {
+    pub id: u32,
+    pub address: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MemberStatus {
+    pub id: u32,
+    pub address: String,
+    pub healthy: bool,
+    pub partitions: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClusterStatus {
+    pub node_id: u32,
+    pub role: RaftRole,
+    pub term: u64,
+    pub leader_id: Option<u32>,
+    pub commit_index: u64,
+    pub members: Vec<MemberStatus>,
+    /// Pending handoffs: partition -> target node
+    pub handoffs: HashMap<u32, u32>,
+}
+
+/// State that must survive restarts
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PersistentState {
+    current_term: u64,
+    voted_for: Option<u32>,
+    log: Vec<LogEntry>,
+}
+
+struct RaftState {
+    persistent: PersistentState,
+    role: RaftRole,
+    leader_id: Option<u32>,
+    commit_index: u64,
+    last_applied: u64,
+    election_deadline: Instant,
+
+    // Leader only
+    next_index: HashMap<u32, u64>,
+    match_index: HashMap<u32, u64>,
+    last_contact: HashMap<u32, Instant>,
+}
+
+impl RaftState {
+    fn last_index(&self) -> u64 {
+        self.persistent.log.len() as u64
+    }
+
+    /// Term of the entry at a 1-based index; 0 before the first entry
+    fn term_at(&self, index: u64) -> u64 {
+        match index {
+            0 => 0,
+            i => self.persistent.log.get(i as usize - 1).map(|e| e.term).unwrap_or(0),
+        }
+    }
+}
+
+/// One member of the metadata Raft group
+pub struct RaftNode {
+    config: RaftConfig,
+    registry: Arc<NodeRegistry>,
+    state: Mutex<RaftState>,
+    client: reqwest::Client,
+
+    /// Wakes proposers when entries are applied
+    applied: Notify,
+}
+
+impl RaftNode {
+    /// Create a node, restoring term, vote and log from `state_path`
+    pub fn new(config: RaftConfig, registry: Arc<NodeRegistry>) -> Result<Self> {
+        let persistent = match &config.state_path {
+            Some(path) if path.exists() => {
+                let content = std::fs::read_to_string(path).map_err(|e| {
+                    AllSourceError::StorageError(format!("Failed to read Raft state: {}", e))
+                })?;
+                serde_json::from_str(&content)?
+            }
+            _ => PersistentState::default(),
+        };
+
+        let client = reqwest::Client::builder()
+            .timeout(config.election_timeout)
+            .build()
+            .unwrap_or_default();
+        let election_deadline = Instant::now() + random_election_timeout(config.election_timeout);
+
+        Ok(Self {
+            config,
+            registry,
+            state: Mutex::new(RaftState {
+                persistent,
+                role: RaftRole::Follower,
+                leader_id: None,
+                commit_index: 0,
+                last_applied: 0,
+                election_deadline,
+                next_index: HashMap::new(),
+                match_index: HashMap::new(),
+                last_contact: HashMap::new(),
+            }),
+            client,
+            applied: Notify::new(),
+        })
+    }
+
+    pub fn registry(&self) -> &Arc<NodeRegistry> {
+        &self.registry
+    }
+
+    pub fn role(&self) -> RaftRole {
+        self.state.lock().role
+    }
+
+    pub fn leader_id(&self) -> Option<u32> {
+        self.state.lock().leader_id
+    }
+
+    pub fn status(&self) -> ClusterStatus {
+        let state = self.state.lock();
+        let mut members: Vec<MemberStatus> = self
+            .registry
+            .all_nodes()
+            .into_iter()
+            .map(|n| MemberStatus {
+                id: n.id,
+                address: n.address,
+                healthy: n.healthy,
+                partitions: n.assigned_partitions,
+            })
+            .collect();
+        members.sort_by_key(|m| m.id);
+
+        ClusterStatus {
+            node_id: self.config.node_id,
+            role: state.role,
+            term: state.persistent.current_term,
+            leader_id: state.leader_id,
+            commit_index: state.commit_index,
+            members,
+            handoffs: self.registry.pending_handoffs(),
+        }
+    }
+
+    /// Run elections or heartbeats every `heartbeat_interval`
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.config.heartbeat_interval);
+            loop {
+                interval.tick().await;
+                self.tick().await;
+            }
+        })
+    }
+
+    /// One round: heartbeat and failure detection as leader, otherwise an
+    /// election if the leader has gone quiet
+    pub async fn tick(&self) {
+        let (role, deadline) = {
+            let state = self.state.lock();
+            (state.role, state.election_deadline)
+        };
+
+        if role == RaftRole::Leader {
+            self.replicate().await;
+            self.detect_failures();
+        } else if Instant::now() >= deadline && self.is_voter() {
+            self.start_election().await;
+        }
+    }
+
+    /// Commit a command as leader and wait until it is applied here
+    pub async fn propose(&self, command: MetadataCommand) -> Result<u64> {
+        let (index, term) = {
+            let mut state = self.state.lock();
+            if state.role != RaftRole::Leader {
+                return Err(self.not_leader(&state));
+            }
+            let index = self.append_locked(&mut state, command)?;
+            (index, state.persistent.current_term)
+        };
+
+        self.replicate().await;
+        self.await_commit(index, term).await?;
+        Ok(index)
+    }
+
+    /// Commit a command from any node: proposed here if this node leads,
+    /// otherwise forwarded to the leader. Returns once applied locally.
+    pub async fn submit(&self, command: MetadataCommand) -> Result<u64> {
+        let leader = self.await_leader().await?;
+        if leader == self.config.node_id {
+            return self.propose(command).await;
+        }
+
+        let node = self.registry.get_node(leader).ok_or_else(|| {
+            AllSourceError::ReplicationError(format!("Metadata leader {} is not a member", leader))
+        })?;
+        let response: ProposeResponse = self
+            .send(&node, "propose", &ProposeRequest { command })
+            .await
+            .ok_or_else(|| {
+                AllSourceError::ReplicationError(format!(
+                    "Metadata leader {} did not accept the proposal",
+                    leader
+                ))
+            })?;
+
+        if !self.wait_applied(response.index, self.config.commit_timeout).await {
+            return Err(AllSourceError::ReplicationError(format!(
+                "Metadata change {} not applied locally within {:?}",
+                response.index, self.config.commit_timeout
+            )));
+        }
+        Ok(response.index)
+    }
+
+    /// Wait until the entry at `index` has been applied here. Returns false on timeout.
+    pub async fn wait_applied(&self, index: u64, timeout: Duration) -> bool {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            // Registered before checking, so a wakeup in between is not lost
+            let notified = self.applied.notified();
+            if self.state.lock().last_applied >= index {
+                return true;
+            }
+            if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                return self.state.lock().last_applied >= index;
+            }
+        }
+    }
+
+    /// Wait up to `commit_timeout` for a leader to be known, e.g. while an
+    /// election is in progress
+    async fn await_leader(&self) -> Result<u32> {
+        let deadline = Instant::now() + self.config.commit_timeout;
+        loop {
+            {
+                let state = self.state.lock();
+                if let Some(leader) = state.leader_id {
+                    return Ok(leader);
+                }
+                if Instant::now() >= deadline {
+                    return Err(self.not_leader(&state));
+                }
+            }
+            tokio::time::sleep(self.config.heartbeat_interval).await;
+        }
+    }
+
+    async fn await_commit(&self, index: u64, term: u64) -> Result<()> {
+        if !self.wait_applied(index, self.config.commit_timeout).await {
+            return Err(AllSourceError::ReplicationError(format!(
+                "Metadata change {} not committed within {:?}",
+                index, self.config.commit_timeout
+            )));
+        }
+        // A later leader may have replaced the entry before it committed
+        if self.state.lock().term_at(index) != term {
+            return Err(AllSourceError::ReplicationError(format!(
+                "Metadata change {} was lost to a leader change",
+                index
+            )));
+        }
+        Ok(())
+    }
+
+    /// Handle a RequestVote RPC
+    pub fn handle_vote(&self, request: VoteRequest) -> VoteResponse {
+        let mut state = self.state.lock();
+        if request.term > state.persistent.current_term {
+            if let Err(e) = self.step_down(&mut state, request.term) {
+                tracing::error!("❌ Failed to persist Raft term; refusing vote: {}", e);
+                return VoteResponse {
+                    term: state.persistent.current_term,
+                    vote_granted: false,
+                };
+            }
+        }
+
+        let last_index = state.last_index();
+        let up_to_date =
+            (request.last_log_term, request.last_log_index) >= (state.term_at(last_index), last_index);
+        let vote_granted = request.term == state.persistent.current_term
+            && up_to_date
+            && state
+                .persistent
+                .voted_for
+                .is_none_or(|candidate| candidate == request.candidate_id);
+
+        if vote_granted {
+            let previous = state.persistent.voted_for.replace(request.candidate_id);
+            if let Err(e) = self.persist(&state.persistent) {
+                // A vote that is not on disk could be cast again after a restart
+                tracing::error!("❌ Failed to persist Raft vote; refusing it: {}", e);
+                state.persistent.voted_for = previous;
+                let term = state.persistent.current_term;
+                let _ = self.step_down(&mut state, term);
+                return VoteResponse {
+                    term,
+                    vote_granted: false,
+                };
+            }
+            state.election_deadline = self.next_election_deadline();
+        }
+
+        VoteResponse {
+            term: state.persistent.current_term,
+            vote_granted,
+        }
+    }
+
+    /// Handle an AppendEntries RPC (heartbeats carry no entries)
+    pub fn handle_append(&self, request: AppendRequest) -> AppendResponse {
+        let mut state = self.state.lock();
+        let reject = |state: &RaftState, match_index: u64| AppendResponse {
+            term: state.persistent.current_term,
+            success: false,
+            match_index,
+        };
+
+        if request.term < state.persistent.current_term {
+            return reject(&state, 0);
+        }
+        if request.term > state.persistent.current_term || state.role != RaftRole::Follower {
+            if let Err(e) = self.step_down(&mut state, request.term) {
+                tracing::error!("❌ Failed to persist Raft term; refusing entries: {}", e);
+                let commit_index = state.commit_index;
+                return reject(&state, commit_index);
+            }
+        }
+        state.leader_id = Some(request.leader_id);
+        state.election_deadline = self.next_election_deadline();
+
+        // The entry before the new ones must match the leader's
+        let prev = request.prev_log_index;
+        if prev > state.last_index() {
+            let last_index = state.last_index();
+            return reject(&state, last_index);
+        }
+        if state.term_at(prev) != request.prev_log_term {
+            let removed = state.persistent.log.split_off(prev as usize - 1);
+            if let Err(e) = self.persist(&state.persistent) {
+                tracing::error!("❌ Failed to persist Raft log: {}", e);
+                state.persistent.log.extend(removed);
+                let commit_index = state.commit_index;
+                return reject(&state, commit_index);
+            }
+            return reject(&state, prev - 1);
+        }
+
+        let count = request.entries.len() as u64;
+        // Position of the first change and the entries it replaced, to undo
+        // the change if it cannot be persisted
+        let mut replaced: Option<(usize, Vec<LogEntry>)> = None;
+        for (offset, entry) in request.entries.into_iter().enumerate() {
+            let index = prev + 1 + offset as u64;
+            let position = index as usize - 1;
+            if index <= state.last_index() {
+                if state.term_at(index) == entry.term {
+                    continue;
+                }
+                // Conflicting suffix from an earlier leader
+                let removed = state.persistent.log.split_off(position);
+                replaced.get_or_insert((position, removed));
+            } else {
+                replaced.get_or_insert((position, Vec::new()));
+            }
+            state.persistent.log.push(entry);
+        }
+        if let Some((position, removed)) = replaced {
+            if let Err(e) = self.persist(&state.persistent) {
+                // Entries only held in memory must not count as matched
+                tracing::error!("❌ Failed to persist Raft log; refusing entries: {}", e);
+                state.persistent.log.truncate(position);
+                state.persistent.log.extend(removed);
+                let commit_index = state.commit_index;
+                return reject(&state, commit_index);
+            }
+        }
+
+        let matched = prev + count;
+        if request.leader_commit > state.commit_index {
+            state.commit_index = request.leader_commit.min(matched);
+            self.apply_committed(&mut state);
+        }
+
+        AppendResponse {
+            term: state.persistent.current_term,
+            success: true,
+            match_index: matched,
+        }
+    }
+
+    /// Raft and membership endpoints
+    pub fn router<S>(self: &Arc<Self>) -> Router<S> {
+        Router::new()
+            .route("/api/v1/cluster/raft/vote", post(vote_handler))
+            .route("/api/v1/cluster/raft/append", post(append_handler))
+            .route("/api/v1/cluster/raft/propose", post(propose_handler))
+            .route("/api/v1/cluster/members", get(members_handler))
+            .route("/api/v1/cluster/members", post(add_member_handler))
+            .route("/api/v1/cluster/members/:id", delete(remove_member_handler))
+            .with_state(Arc::clone(self))
+    }
+
+    fn is_voter(&self) -> bool {
+        self.registry.get_node(self.config.node_id).is_some()
+    }
+
+    fn majority(&self) -> usize {
+        self.registry.node_count() / 2 + 1
+    }
+
+    fn peers(&self) -> Vec<Node> {
+        self.registry
+            .all_nodes()
+            .into_iter()
+            .filter(|n| n.id != self.config.node_id)
+            .collect()
+    }
+
+    fn not_leader(&self, state: &RaftState) -> AllSourceError {
+        AllSourceError::NotLeader(match state.leader_id {
+            Some(leader) => format!("metadata leader is node {}", leader),
+            None => "no metadata leader elected".to_string(),
+        })
+    }
+
+    fn next_election_deadline(&self) -> Instant {
+        Instant::now() + random_election_timeout(self.config.election_timeout)
+    }
+
+    async fn start_election(&self) {
+        let request = {
+            let mut state = self.state.lock();
+            let (term, voted_for) = (state.persistent.current_term, state.persistent.voted_for);
+            state.persistent.current_term += 1;
+            state.persistent.voted_for = Some(self.config.node_id);
+            state.role = RaftRole::Candidate;
+            state.leader_id = None;
+            state.election_deadline = self.next_election_deadline();
+            if let Err(e) = self.persist(&state.persistent) {
+                // Stand down rather than campaign in a term that is not on disk
+                tracing::error!("❌ Failed to persist Raft term; not calling an election: {}", e);
+                state.persistent.current_term = term;
+                state.persistent.voted_for = voted_for;
+                state.role = RaftRole::Follower;
+                return;
+            }
+            let last_log_index = state.last_index();
+            VoteRequest {
+                term: state.persistent.current_term,
+                candidate_id: self.config.node_id,
+                last_log_index,
+                last_log_term: state.term_at(last_log_index),
+            }
+        };
+        tracing::debug!(
+            "Node {} calling a metadata election for term {}",
+            self.config.node_id,
+            request.term
+        );
+
+        let peers = self.peers();
+        let responses = futures::future::join_all(
+            peers
+                .iter()
+                .map(|peer| self.send::<_, VoteResponse>(peer, "vote", &request)),
+        )
+        .await;
+
+        let mut state = self.state.lock();
+        let mut votes = 1;
+        for response in responses.into_iter().flatten() {
+            if response.term > state.persistent.current_term {
+                if let Err(e) = self.step_down(&mut state, response.term) {
+                    tracing::error!("❌ Failed to persist Raft term: {}", e);
+                }
+                return;
+            }
+            if response.vote_granted {
+                votes += 1;
+            }
+        }
+
+        if state.role == RaftRole::Candidate
+            && state.persistent.current_term == request.term
+            && votes >= self.majority()
+        {
+            self.become_leader(&mut state);
+        }
+    }
+
+    fn become_leader(&self, state: &mut RaftState) {
+        state.role = RaftRole::Leader;
+        state.leader_id = Some(self.config.node_id);
+
+        let now = Instant::now();
+        let next_index = state.last_index() + 1;
+        state.next_index.clear();
+        state.match_index.clear();
+        state.last_contact.clear();
+        for peer in self.peers() {
+            state.next_index.insert(peer.id, next_index);
+            state.last_contact.insert(peer.id, now);
+        }
+
+        if let Err(e) = self.append_locked(state, MetadataCommand::Noop) {
+            // A leader that cannot persist its log cannot commit anything
+            tracing::error!("❌ Failed to persist Raft log; not taking leadership: {}", e);
+            state.role = RaftRole::Follower;
+            state.leader_id = None;
+            return;
+        }
+        tracing::info!(
+            "👑 Node {} is metadata leader for term {}",
+            self.config.node_id,
+            state.persistent.current_term
+        );
+    }
+
+    /// Adopt a newer term (if any) and become a follower. Fails if the new
+    /// term could not be persisted; the node is a follower either way.
+    fn step_down(&self, state: &mut RaftState, term: u64) -> Result<()> {
+        let mut result = Ok(());
+        if term > state.persistent.current_term {
+            state.persistent.current_term = term;
+            state.persistent.voted_for = None;
+            state.leader_id = None;
+            result = self.persist(&state.persistent);
+        }
+        if state.role == RaftRole::Leader {
+            tracing::info!(
+                "Node {} stepping down as metadata leader (term {})",
+                self.config.node_id,
+                state.persistent.current_term
+            );
+        }
+        state.role = RaftRole::Follower;
+        state.election_deadline = self.next_election_deadline();
+        result
+    }
+
+    fn append_locked(&self, state: &mut RaftState, command: MetadataCommand) -> Result<u64> {
+        state.persistent.log.push(LogEntry {
+            term: state.persistent.current_term,
+            command,
+        });
+        if let Err(e) = self.persist(&state.persistent) {
+            state.persistent.log.pop();
+            return Err(e);
+        }
+        Ok(state.last_index())
+    }
+
+    /// Send pending entries (or a heartbeat) to every peer, then advance
+    /// the commit index
+    async fn replicate(&self) {
+        let requests: Vec<(Node, AppendRequest)> = {
+            let state = self.state.lock();
+            if state.role != RaftRole::Leader {
+                return;
+            }
+            self.peers()
+                .into_iter()
+                .map(|peer| {
+                    let next = state
+                        .next_index
+                        .get(&peer.id)
+                        .copied()
+                        .unwrap_or(state.last_index() + 1)
+                        .max(1);
+                    let prev_log_index = next - 1;
+                    let request = AppendRequest {
+                        term: state.persistent.current_term,
+                        leader_id: self.config.node_id,
+                        prev_log_index,
+                        prev_log_term: state.term_at(prev_log_index),
+                        entries: state
+                            .persistent
+                            .log
+                            .iter()
+                            .skip(prev_log_index as usize)
+                            .take(MAX_ENTRIES_PER_APPEND)
+                            .cloned()
+                            .collect(),
+                        leader_commit: state.commit_index,
+                    };
+                    (peer, request)
+                })
+                .collect()
+        };
+
+        let responses = futures::future::join_all(requests.iter().map(|(peer, request)| async move {
+            self.send::<_, AppendResponse>(peer, "append", request).await
+        }))
+        .await;
+
+        let mut state = self.state.lock();
+        for ((peer, request), response) in requests.iter().zip(responses) {
+            let Some(response) = response else {
+                continue;
+            };
+            if response.term > state.persistent.current_term {
+                if let Err(e) = self.step_down(&mut state, response.term) {
+                    tracing::error!("❌ Failed to persist Raft term: {}", e);
+                }
+                return;
+            }
+            if state.role != RaftRole::Leader || request.term != state.persistent.current_term {
+                return;
+            }
+
+            state.last_contact.insert(peer.id, Instant::now());
+            if response.success {
+                let matched = request.prev_log_index + request.entries.len() as u64;
+                state.match_index.insert(peer.id, matched);
+                state.next_index.insert(peer.id, matched + 1);
+            } else {
+                // Back up to the follower's hint
+                let next = (response.match_index + 1).min(request.prev_log_index).max(1);
+                state.next_index.insert(peer.id, next);
+            }
+        }
+
+        // Commit the newest entry of this term stored on a majority
+        let voters = self.registry.all_nodes();
+        let current_term = state.persistent.current_term;
+        for index in (state.commit_index + 1..=state.last_index()).rev() {
+            if state.term_at(index) != current_term {
+                break;
+            }
+            let replicated = voters
+                .iter()
+                .filter(|v| {
+                    v.id == self.config.node_id
+                        || state.match_index.get(&v.id).copied().unwrap_or(0) >= index
+                })
+                .count();
+            if replicated > voters.len() / 2 {
+                state.commit_index = index;
+                break;
+            }
+        }
+        self.apply_committed(&mut state);
+    }
+
+    /// Propose health changes for peers that went silent or came back
+    fn detect_failures(&self) {
+        let mut state = self.state.lock();
+        if state.role != RaftRole::Leader {
+            return;
+        }
+
+        let now = Instant::now();
+        let pending: Vec<MetadataCommand> = state
+            .persistent
+            .log
+            .iter()
+            .skip(state.commit_index as usize)
+            .map(|e| e.command.clone())
+            .collect();
+
+        for peer in self.peers() {
+            let last_contact = *state.last_contact.entry(peer.id).or_insert(now);
+            let silent = now.duration_since(last_contact) > self.config.failure_timeout;
+            if silent != peer.healthy {
+                continue;
+            }
+
+            let command = MetadataCommand::SetHealth {
+                id: peer.id,
+                healthy: !silent,
+            };
+            if pending.contains(&command) {
+                continue;
+            }
+            if silent {
+                tracing::warn!(
+                    "⚠️  Node {} missed heartbeats for {:?}; marking it unhealthy",
+                    peer.id,
+                    self.config.failure_timeout
+                );
+            }
+            if let Err(e) = self.append_locked(&mut state, command) {
+                tracing::error!("❌ Failed to persist Raft log: {}", e);
+            }
+        }
+    }
+
+    fn apply_committed(&self, state: &mut RaftState) {
+        while state.last_applied < state.commit_index {
+            state.last_applied += 1;
+            let command = state.persistent.log[state.last_applied as usize - 1].command.clone();
+            self.apply(&command);
+        }
+        self.applied.notify_waiters();
+    }
+
+    /// Apply a committed command to the registry
+    fn apply(&self, command: &MetadataCommand) {
+        match command {
+            MetadataCommand::Noop => {}
+            MetadataCommand::AddNode { id, address } => {
+                if self.registry.get_node(*id).is_none() {
+                    self.registry.register_node(Node {
+                        id: *id,
+                        address: address.clone(),
+                        healthy: true,
+                        assigned_partitions: vec![],
+                    });
+                    tracing::info!("➕ Node {} ({}) joined the cluster", id, address);
+                }
+            }
+            MetadataCommand::RemoveNode { id } => {
+                self.registry.unregister_node(*id);
+                tracing::info!("➖ Node {} left the cluster", id);
+            }
+            MetadataCommand::SetHealth { id, healthy } => {
+                self.registry.set_node_health(*id, *healthy);
+                tracing::info!(
+                    "Node {} marked {}",
+                    id,
+                    if *healthy { "healthy" } else { "unhealthy" }
+                );
+            }
+            MetadataCommand::CompleteHandoff { partition, from, to } => {
+                if self.registry.complete_handoff(*partition, *from, *to) {
+                    tracing::info!("🔀 Partition {} moved from node {} to node {}", partition, from, to);
+                }
+            }
+            MetadataCommand::SetInSync { partition, epoch, replicas } => {
+                if self.registry.set_in_sync(*partition, *epoch, replicas.clone()) {
+                    tracing::debug!("Partition {} in-sync replicas: {:?}", partition, replicas);
+                }
+            }
+        }
+    }
+
+    /// Write persistent state atomically (temp file, then rename)
+    fn persist(&self, state: &PersistentState) -> Result<()> {
+        let Some(path) = &self.config.state_path else {
+            return Ok(());
+        };
+        // Votes and terms must be on disk before they are acted on
+        let data = serde_json::to_vec(state)?;
+        write_atomically(path, |file| file.write_all(&data))
+    }
+
+    async fn send<Req: Serialize, Resp: DeserializeOwned>(
+        &self,
+        node: &Node,
+        rpc: &str,
+        body: &Req,
+    ) -> Option<Resp> {
+        let mut builder = self
+            .client
+            .post(format!("http://{}/api/v1/cluster/raft/{}", node.address, rpc))
+            .json(body);
+        if let Some(secret) = &self.config.cluster_secret {
+            builder = builder.header(CLUSTER_SECRET_HEADER, secret);
+        }
+
+        match builder.send().await {
+            Ok(response) if response.status().is_success() => response.json().await.ok(),
+            Ok(response) => {
+                tracing::debug!("Raft {} to node {} failed: {}", rpc, node.id, response.status());
+                None
+            }
+            Err(e) => {
+                tracing::debug!("Raft {} to node {} failed: {}", rpc, node.id, e);
+                None
+            }
+        }
+    }
+
+    fn authorize(&self, headers: &HeaderMap) -> Result<()> {
+        check_cluster_secret(self.config.cluster_secret.as_deref(), headers)
+    }
+}
+
+fn random_election_timeout(base: Duration) -> Duration {
+    let base_ms = base.as_millis().max(1) as u64;
+    Duration::from_millis(base_ms + rand::thread_rng().gen_range(0..base_ms))
+}
+
+/// POST /api/v1/cluster/raft/vote
+async fn vote_handler(
+    State(raft): State<Arc<RaftNode>>,
+    headers: HeaderMap,
+    Json(request): Json<VoteRequest>,
+) -> Result<Json<VoteResponse>> {
+    raft.authorize(&headers)?;
+    Ok(Json(raft.handle_vote(request)))
+}
+
+/// POST /api/v1/cluster/raft/append
+async fn append_handler(
+    State(raft): State<Arc<RaftNode>>,
+    headers: HeaderMap,
+    Json(request): Json<AppendRequest>,
+) -> Result<Json<AppendResponse>> {
+    raft.authorize(&headers)?;
+    Ok(Json(raft.handle_append(request)))
+}
+
+/// POST /api/v1/cluster/raft/propose
+async fn propose_handler(
+    State(raft): State<Arc<RaftNode>>,
+    headers: HeaderMap,
+    Json(request): Json<ProposeRequest>,
+) -> Result<Json<ProposeResponse>> {
+    raft.authorize(&headers)?;
+    let index = raft.propose(request.command).await?;
+    Ok(Json(ProposeResponse { index }))
+}
+
+/// GET /api/v1/cluster/members
+async fn members_handler(
+    State(raft): State<Arc<RaftNode>>,
+    headers: HeaderMap,
+) -> Result<Json<ClusterStatus>> {
+    raft.authorize(&headers)?;
+    Ok(Json(raft.status()))
+}
+
+/// POST /api/v1/cluster/members
+async fn add_member_handler(
+    State(raft): State<Arc<RaftNode>>,
+    headers: HeaderMap,
+    Json(request): Json<AddMemberRequest>,
+) -> Result<Json<ClusterStatus>> {
+    raft.authorize(&headers)?;
+    raft.submit(MetadataCommand::AddNode {
+        id: request.id,
+        address: request.address,
+    })
+    .await?;
+    Ok(Json(raft.status()))
+}
+
+/// DELETE /api/v1/cluster/members/:id
+async fn remove_member_handler(
+    State(raft): State<Arc<RaftNode>>,
+    headers: HeaderMap,
+    Path(id): Path<u32>,
+) -> Result<Json<ClusterStatus>> {
+    raft.authorize(&headers)?;
+    raft.submit(MetadataCommand::RemoveNode { id }).await?;
+    Ok(Json(raft.status()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::response::IntoResponse;
+    use crate::test_support::eventually;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use tokio::net::TcpListener;
+    use tokio::task::JoinHandle;
+
+    const SECRET: &str = "raft-test-secret";
+
+    struct TestNode {
+        raft: Arc<RaftNode>,
+        ticker: JoinHandle<()>,
+        stopped: Arc<AtomicBool>,
+    }
+
+    impl TestNode {
+        /// Simulate a crash: stop ticking and answering RPCs. Pooled
+        /// connections outlive the server task, so requests are refused
+        /// rather than the listener closed.
+        fn stop(&self) {
+            self.ticker.abort();
+            self.stopped.store(true, Ordering::SeqCst);
+        }
+    }
+
+    fn config(node_id: u32) -> RaftConfig {
+        RaftConfig {
+            node_id,
+            heartbeat_interval: Duration::from_millis(50),
+            election_timeout: Duration::from_millis(250),
+            failure_timeout: Duration::from_millis(600),
+            commit_timeout: Duration::from_secs(2),
+            state_path: None,
+            cluster_secret: Some(SECRET.to_string()),
+        }
+    }
+
+    /// Start in-process Raft nodes on localhost with the same initial membership
+    async fn start_cluster(count: usize) -> Vec<TestNode> {
+        let mut listeners = Vec::new();
+        for _ in 0..count {
+            listeners.push(TcpListener::bind("127.0.0.1:0").await.unwrap());
+        }
+        let addresses: Vec<String> = listeners
+            .iter()
+            .map(|l| l.local_addr().unwrap().to_string())
+            .collect();
+
+        let mut nodes = Vec::new();
+        for (id, listener) in listeners.into_iter().enumerate() {
+            let registry = Arc::new(NodeRegistry::new(8));
+            for (peer, address) in addresses.iter().enumerate() {
+                registry.register_node(Node {
+                    id: peer as u32,
+                    address: address.clone(),
+                    healthy: true,
+                    assigned_partitions: vec![],
+                });
+            }
+
+            let raft = Arc::new(RaftNode::new(config(id as u32), registry).unwrap());
+            let stopped = Arc::new(AtomicBool::new(false));
+            let flag = stopped.clone();
+            let app: Router = raft.router().layer(axum::middleware::from_fn(
+                move |request: axum::extract::Request, next: axum::middleware::Next| {
+                    let stopped = flag.load(Ordering::SeqCst);
+                    async move {
+                        if stopped {
+                            axum::http::StatusCode::SERVICE_UNAVAILABLE.into_response()
+                        } else {
+                            next.run(request).await
+                        }
+                    }
+                },
+            ));
+            tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+            let ticker = raft.clone().spawn();
+            nodes.push(TestNode {
+                raft,
+                ticker,
+                stopped,
+            });
+        }
+        nodes
+    }
+
+    fn leaders(nodes: &[TestNode]) -> Vec<u32> {
+        nodes
+            .iter()
+            .filter(|n| n.raft.role() == RaftRole::Leader)
+            .map(|n| n.raft.config.node_id)
+            .collect()
+    }
+
+    #[test]
+    fn test_vote_requires_up_to_date_log() {
+        let registry = Arc::new(NodeRegistry::new(4));
+        let raft = RaftNode::new(config(0), registry).unwrap();
+        raft.state.lock().persistent.log.push(LogEntry {
+            term: 2,
+            command: MetadataCommand::Noop,
+        });
+
+        let stale = raft.handle_vote(VoteRequest {
+            term: 3,
+            candidate_id: 1,
+            last_log_index: 5,
+            last_log_term: 1,
+        });
+        assert!(!stale.vote_granted);
+        assert_eq!(stale.term, 3);
+
+        let current = raft.handle_vote(VoteRequest {
+            term: 3,
+            candidate_id: 2,
+            last_log_index: 1,
+            last_log_term: 2,
+        });
+        assert!(current.vote_granted);
+
+        // One vote per term
+        let second = raft.handle_vote(VoteRequest {
+            term: 3,
+            candidate_id: 1,
+            last_log_index: 1,
+            last_log_term: 2,
+        });
+        assert!(!second.vote_granted);
+    }
+
+    #[test]
+    fn test_append_replaces_conflicting_entries() {
+        let registry = Arc::new(NodeRegistry::new(4));
+        let raft = RaftNode::new(config(1), registry.clone()).unwrap();
+        let entry = |term, id| LogEntry {
+            term,
+            command: MetadataCommand::AddNode {
+                id,
+                address: format!("node-{}:3900", id),
+            },
+        };
+
+        let response = raft.handle_append(AppendRequest {
+            term: 1,
+            leader_id: 0,
+            prev_log_index: 0,
+            prev_log_term: 0,
+            entries: vec![entry(1, 5), entry(1, 6)],
+            leader_commit: 1,
+        });
+        assert!(response.success);
+        assert_eq!(response.match_index, 2);
+        assert!(registry.get_node(5).is_some());
+        assert!(registry.get_node(6).is_none());
+
+        // A new leader overwrote index 2
+        let response = raft.handle_append(AppendRequest {
+            term: 2,
+            leader_id: 2,
+            prev_log_index: 1,
+            prev_log_term: 1,
+            entries: vec![entry(2, 7)],
+            leader_commit: 2,
+        });
+        assert!(response.success);
+        assert!(registry.get_node(6).is_none());
+        assert!(registry.get_node(7).is_some());
+
+        // Gaps are refused with a hint
+        let response = raft.handle_append(AppendRequest {
+            term: 2,
+            leader_id: 2,
+            prev_log_index: 9,
+            prev_log_term: 2,
+            entries: vec![],
+            leader_commit: 2,
+        });
+        assert!(!response.success);
+        assert_eq!(response.match_index, 2);
+    }
+
+    #[tokio::test]
+    async fn test_unpersisted_votes_and_entries_are_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("raft.json");
+        let registry = Arc::new(NodeRegistry::new(4));
+        registry.register_node(Node {
+            id: 1,
+            address: "127.0.0.1:1".to_string(),
+            healthy: true,
+            assigned_partitions: vec![],
+        });
+        let raft = RaftNode::new(
+            RaftConfig {
+                state_path: Some(path.clone()),
+                ..config(1)
+            },
+            registry,
+        )
+        .unwrap();
+        let append = |term| AppendRequest {
+            term,
+            leader_id: 0,
+            prev_log_index: 0,
+            prev_log_term: 0,
+            entries: vec![LogEntry {
+                term,
+                command: MetadataCommand::Noop,
+            }],
+            leader_commit: 0,
+        };
+        let vote = |term| VoteRequest {
+            term,
+            candidate_id: 0,
+            last_log_index: 0,
+            last_log_term: 0,
+        };
+
+        // The state file can no longer be written
+        let blocker = path.with_extension("json.partial");
+        std::fs::create_dir(&blocker).unwrap();
+
+        assert!(!raft.handle_vote(vote(2)).vote_granted);
+        assert!(!raft.handle_append(append(2)).success);
+        assert_eq!(raft.state.lock().last_index(), 0);
+
+        raft.start_election().await;
+        assert_eq!(raft.role(), RaftRole::Follower);
+        assert_eq!(raft.state.lock().persistent.voted_for, None);
+
+        // Once it can persist again the node takes part as usual
+        std::fs::remove_dir(&blocker).unwrap();
+        assert!(raft.handle_vote(vote(3)).vote_granted);
+        let response = raft.handle_append(append(3));
+        assert!(response.success);
+        assert_eq!(response.match_index, 1);
+    }
+
+    #[test]
+    fn test_state_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("raft.json");
+        let registry = Arc::new(NodeRegistry::new(4));
+
+        let raft = RaftNode::new(
+            RaftConfig {
+                state_path: Some(path.clone()),
+                ..config(0)
+            },
+            registry.clone(),
+        )
+        .unwrap();
+        raft.handle_vote(VoteRequest {
+            term: 4,
+            candidate_id: 1,
+            last_log_index: 0,
+            last_log_term: 0,
+        });
+
+        let restarted = RaftNode::new(
+            RaftConfig {
+                state_path: Some(path),
+                ..config(0)
+            },
+            registry,
+        )
+        .unwrap();
+        let state = restarted.state.lock();
+        assert_eq!(state.persistent.current_term, 4);
+        assert_eq!(state.persistent.voted_for, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_commands_replicate_to_every_node() {
+        let nodes = start_cluster(3).await;
+        assert!(eventually(Duration::from_secs(5), || leaders(&nodes).len() == 1).await);
+
+        let leader = leaders(&nodes)[0];
+        assert!(
+            eventually(Duration::from_secs(2), || nodes
+                .iter()
+                .all(|n| n.raft.leader_id() == Some(leader)))
+            .await
+        );
+
+        // Submitted on a follower, forwarded to the leader
+        let follower = nodes.iter().find(|n| n.raft.config.node_id != leader).unwrap();
+        follower
+            .raft
+            .submit(MetadataCommand::AddNode {
+                id: 9,
+                address: "127.0.0.1:1".to_string(),
+            })
+            .await
+            .unwrap();
+
+        assert!(
+            eventually(Duration::from_secs(2), || nodes
+                .iter()
+                .all(|n| n.raft.registry().get_node(9).is_some()))
+            .await
+        );
+        for node in &nodes {
+            assert_eq!(node.raft.status().leader_id, Some(leader));
+        }
+        for node in &nodes {
+            node.stop();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_node_is_marked_unhealthy() {
+        let nodes = start_cluster(3).await;
+        assert!(eventually(Duration::from_secs(5), || leaders(&nodes).len() == 1).await);
+
+        // A follower crashes: the leader marks it unhealthy on every live node
+        let leader = leaders(&nodes)[0];
+        let crashed = nodes.iter().find(|n| n.raft.config.node_id != leader).unwrap();
+        let crashed_id = crashed.raft.config.node_id;
+        crashed.stop();
+
+        let survivors: Vec<&TestNode> = nodes
+            .iter()
+            .filter(|n| n.raft.config.node_id != crashed_id)
+            .collect();
+        assert!(
+            eventually(Duration::from_secs(5), || survivors
+                .iter()
+                .all(|n| !n.raft.registry().get_node(crashed_id).unwrap().healthy))
+            .await
+        );
+
+        // Its partitions moved to the remaining nodes
+        for node in &survivors {
+            for partition in 0..node.raft.registry().partition_count() {
+                assert_ne!(node.raft.registry().node_for_partition(partition), Some(crashed_id));
+            }
+        }
+        for node in &nodes {
+            node.stop();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_new_leader_elected_after_leader_failure() {
+        let nodes = start_cluster(3).await;
+        assert!(eventually(Duration::from_secs(5), || leaders(&nodes).len() == 1).await);
+
+        let old_leader = leaders(&nodes)[0];
+        let old_term = nodes[old_leader as usize].raft.status().term;
+        nodes[old_leader as usize].stop();
+
+        let survivors: Vec<&TestNode> = nodes
+            .iter()
+            .filter(|n| n.raft.config.node_id != old_leader)
+            .collect();
+        assert!(
+            eventually(Duration::from_secs(5), || survivors
+                .iter()
+                .any(|n| n.raft.role() == RaftRole::Leader))
+            .await
+        );
+        let new_leader = survivors
+            .iter()
+            .find(|n| n.raft.role() == RaftRole::Leader)
+            .unwrap();
+        assert!(new_leader.raft.status().term > old_term);
+
+        // The new leader keeps committing, and marks the old one unhealthy
+        assert!(
+            eventually(Duration::from_secs(5), || survivors
+                .iter()
+                .all(|n| !n.raft.registry().get_node(old_leader).unwrap().healthy))
+            .await
+        );
+        for node in &nodes {
+            node.stop();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_endpoints_require_cluster_secret() {
+        let nodes = start_cluster(1).await;
+        let address = nodes[0].raft.registry().get_node(0).unwrap().address;
+        let client = reqwest::Client::new();
+
+        let response = client
+            .get(format!("http://{}/api/v1/cluster/members", address))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 403);
+
+        // A single node elects itself and applies membership changes alone
+        assert!(eventually(Duration::from_secs(5), || leaders(&nodes).len() == 1).await);
+        let response = client
+            .post(format!("http://{}/api/v1/cluster/members", address))
+            .header(CLUSTER_SECRET_HEADER, SECRET)
+            .json(&serde_json::json!({"id": 1, "address": "127.0.0.1:1"}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        let status: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(status["members"].as_array().unwrap().len(), 2);
+        nodes[0].stop();
+    }
+}
@@ -0,0 +1,683 @@
+//! Request Forwarding to Partition Owners
+//!
+//! Lets clients talk to any node: entity-scoped requests are proxied to the
+//! node that owns the entity's partition, and cross-partition queries fan
+//! out to every healthy node with the results merged.
+//!
+//! # Design
+//! - **Entity requests**: ingest, entity state/snapshot and entity-filtered
+//!   queries go to the partition owner from `RequestRouter`
+//! - **Fan-out**: queries without an entity go to all healthy nodes; events
+//!   are de-duplicated (replicas hold copies), re-sorted by timestamp and
+//!   the limit re-applied
+//! - **Loop prevention**: forwarded requests carry `X-Forwarded-By-Node` and
+//!   are always served where they land
+//! - **Failures**: an unreachable owner, or any node failing during a
+//!   fan-out, yields 503 with `Retry-After`
+//!
+//! The forwarding node has already authenticated the caller, and credentials
+//! may only be valid there (per-node JWT keys and user store). Forwarded
+//! requests therefore carry the verified claims in `X-Forwarded-Principal`
+//! next to the cluster secret, and the owner trusts them instead of
+//! authenticating the caller again.
+//!
+//! # Example
+//! ```ignore
+//! let forwarder = Arc::new(RequestForwarder::new(
+//!     ForwardingConfig { node_id: 0, ..Default::default() },
+//!     registry.clone(),
+//! ));
+//! let app = app.layer(middleware::from_fn_with_state(
+//!     ForwardingState { forwarder: Some(forwarder) },
+//!     forwarding_middleware,
+//! ));
+//! ```
+
+use super::node_registry::{Node, NodeRegistry};
+use super::replication::CLUSTER_SECRET_HEADER;
+use super::request_router::RequestRouter;
+use crate::application::dto::EventDto;
+use crate::auth::Claims;
+use crate::domain::value_objects::EntityId;
+use crate::error::AllSourceError;
+use crate::middleware::AuthContext;
+use axum::{
+    body::{Body, Bytes},
+    http::{header, HeaderMap, HeaderName, HeaderValue, Request, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use base64::{engine::general_purpose, Engine as _};
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Header marking a request already forwarded by another node; its value
+/// is that node's ID
+pub const FORWARDED_BY_HEADER: &str = "x-forwarded-by-node";
+
+/// Header carrying the claims of the caller the forwarding node
+/// authenticated (base64-encoded JSON); only trusted with the cluster secret
+pub const FORWARDED_PRINCIPAL_HEADER: &str = "x-forwarded-principal";
+
+/// Headers that describe a single connection and are not passed along
+const HOP_BY_HOP_HEADERS: [HeaderName; 5] = [
+    header::HOST,
+    header::CONNECTION,
+    header::CONTENT_LENGTH,
+    header::TRANSFER_ENCODING,
+    header::UPGRADE,
+];
+
+/// Forwarding configuration
+#[derive(Debug, Clone)]
+pub struct ForwardingConfig {
+    /// This node's ID in the registry
+    pub node_id: u32,
+
+    /// Timeout for a forwarded request
+    pub timeout: Duration,
+
+    /// Suggested client back-off when a node cannot be reached
+    pub retry_after: Duration,
+
+    /// Shared secret sent on node-to-node requests
+    pub cluster_secret: Option<String>,
+}
+
+impl Default for ForwardingConfig {
+    fn default() -> Self {
+        Self {
+            node_id: 0,
+            timeout: Duration::from_secs(10),
+            retry_after: Duration::from_secs(1),
+            cluster_secret: None,
+        }
+    }
+}
+
+/// Forwarding failures, answered with 503 and a retry hint
+#[derive(Debug)]
+pub enum ForwardingError {
+    /// No healthy node owns the partition
+    NoOwner { partition: u32, retry_after: u64 },
+    /// A node did not answer
+    NodeUnavailable {
+        node_id: u32,
+        reason: String,
+        retry_after: u64,
+    },
+}
+
+impl IntoResponse for ForwardingError {
+    fn into_response(self) -> Response {
+        let (message, retry_after) = match self {
+            ForwardingError::NoOwner {
+                partition,
+                retry_after,
+            } => (
+                format!("No healthy node owns partition {}", partition),
+                retry_after,
+            ),
+            ForwardingError::NodeUnavailable {
+                node_id,
+                reason,
+                retry_after,
+            } => (
+                format!("Node {} is unavailable: {}", node_id, reason),
+                retry_after,
+            ),
+        };
+
+        let mut response = (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({
+                "error": message,
+                "retry_after": retry_after,
+            })),
+        )
+            .into_response();
+        response.headers_mut().insert(
+            header::RETRY_AFTER,
+            retry_after.to_string().parse().unwrap(),
+        );
+        response
+    }
+}
+
+/// Proxies requests to other nodes
+pub struct RequestForwarder {
+    config: ForwardingConfig,
+    router: RequestRouter,
+    client: reqwest::Client,
+}
+
+impl RequestForwarder {
+    pub fn new(config: ForwardingConfig, registry: Arc<NodeRegistry>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .build()
+            .unwrap_or_default();
+        Self {
+            config,
+            router: RequestRouter::new(registry),
+            client,
+        }
+    }
+
+    pub fn config(&self) -> &ForwardingConfig {
+        &self.config
+    }
+
+    /// Whether another node already forwarded this request
+    pub fn is_forwarded(headers: &HeaderMap) -> bool {
+        headers.contains_key(FORWARDED_BY_HEADER)
+    }
+
+    /// The node to forward an entity's requests to, or `None` to serve
+    /// them here. Invalid entity IDs are served here so the handler can
+    /// reject them.
+    pub fn owner_of(&self, entity_id: &str) -> Result<Option<Node>, ForwardingError> {
+        let Ok(entity_id) = EntityId::new(entity_id.to_string()) else {
+            return Ok(None);
+        };
+        match self.router.route_for_entity(&entity_id) {
+            Ok(node) if node.id == self.config.node_id => Ok(None),
+            Ok(node) => Ok(Some(node)),
+            Err(_) => Err(ForwardingError::NoOwner {
+                partition: self.router.partition_for_entity(&entity_id),
+                retry_after: self.retry_after(),
+            }),
+        }
+    }
+
+    /// Healthy nodes other than this one, for fan-out reads
+    pub fn other_nodes(&self) -> Vec<Node> {
+        self.router
+            .nodes_for_read()
+            .into_iter()
+            .filter(|node| node.id != self.config.node_id)
+            .collect()
+    }
+
+    /// Send a request to `node` and relay its response
+    pub async fn forward(
+        &self,
+        node: &Node,
+        request: &Request<Bytes>,
+    ) -> Result<Response, ForwardingError> {
+        let response = self.send(node, request).await?;
+
+        let mut builder = Response::builder().status(response.status().as_u16());
+        for (name, value) in response.headers() {
+            if !HOP_BY_HOP_HEADERS.contains(name) {
+                builder = builder.header(name.as_str(), value.as_bytes());
+            }
+        }
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| self.unavailable(node, e.to_string()))?;
+
+        Ok(builder
+            .body(Body::from(body))
+            .unwrap_or_else(|_| StatusCode::BAD_GATEWAY.into_response()))
+    }
+
+    /// Run an event query on every other healthy node and merge the
+    /// results into this node's `local` response
+    pub async fn fan_out_query(
+        &self,
+        request: &Request<Bytes>,
+        local: Response,
+        limit: Option<usize>,
+    ) -> Result<Response, ForwardingError> {
+        if !local.status().is_success() {
+            return Ok(local);
+        }
+        let (local_parts, local_body) = local.into_parts();
+
+        let nodes = self.other_nodes();
+        let remote = futures::future::join_all(nodes.iter().map(|node| async move {
+            let response = self.send(node, request).await?;
+            if !response.status().is_success() {
+                return Err(self.unavailable(node, format!("query failed: {}", response.status())));
+            }
+            response
+                .json::<QueryResults>()
+                .await
+                .map_err(|e| self.unavailable(node, format!("invalid query response: {}", e)))
+        }))
+        .await;
+
+        let local = axum::body::to_bytes(local_body, usize::MAX)
+            .await
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<QueryResults>(&bytes).ok())
+            .ok_or_else(|| ForwardingError::NodeUnavailable {
+                node_id: self.config.node_id,
+                reason: "invalid local query response".to_string(),
+                retry_after: self.retry_after(),
+            })?;
+
+        let mut results = vec![local];
+        for result in remote {
+            results.push(result?);
+        }
+        let merged = merge_query_results(results, limit);
+
+        // Keep the local extensions (e.g. query cost) for outer middleware
+        let mut response = Json(merged).into_response();
+        *response.extensions_mut() = local_parts.extensions;
+        Ok(response)
+    }
+
+    async fn send(
+        &self,
+        node: &Node,
+        request: &Request<Bytes>,
+    ) -> Result<reqwest::Response, ForwardingError> {
+        let path = request
+            .uri()
+            .path_and_query()
+            .map(|p| p.as_str())
+            .unwrap_or_else(|| request.uri().path());
+        let method = reqwest::Method::from_bytes(request.method().as_str().as_bytes())
+            .unwrap_or(reqwest::Method::GET);
+
+        let mut builder = self
+            .client
+            .request(method, format!("http://{}{}", node.address, path))
+            .header(FORWARDED_BY_HEADER, self.config.node_id.to_string())
+            .body(request.body().clone());
+        for (name, value) in request.headers() {
+            let node_header = [FORWARDED_BY_HEADER, FORWARDED_PRINCIPAL_HEADER, CLUSTER_SECRET_HEADER]
+                .contains(&name.as_str());
+            if !HOP_BY_HOP_HEADERS.contains(name) && !node_header {
+                builder = builder.header(name.as_str(), value.as_bytes());
+            }
+        }
+        if let Some(secret) = &self.config.cluster_secret {
+            builder = builder.header(CLUSTER_SECRET_HEADER, secret);
+            if let Some(auth) = request.extensions().get::<AuthContext>() {
+                builder = builder.header(FORWARDED_PRINCIPAL_HEADER, encode_principal(&auth.claims));
+            }
+        }
+
+        builder
+            .send()
+            .await
+            .map_err(|e| self.unavailable(node, e.to_string()))
+    }
+
+    fn unavailable(&self, node: &Node, reason: String) -> ForwardingError {
+        tracing::warn!("⚠️  Forwarding to node {} failed: {}", node.id, reason);
+        ForwardingError::NodeUnavailable {
+            node_id: node.id,
+            reason,
+            retry_after: self.retry_after(),
+        }
+    }
+
+    fn retry_after(&self) -> u64 {
+        self.config.retry_after.as_secs().max(1)
+    }
+}
+
+/// Encode verified claims for `X-Forwarded-Principal`
+fn encode_principal(claims: &Claims) -> String {
+    general_purpose::STANDARD.encode(serde_json::to_vec(claims).unwrap_or_default())
+}
+
+/// Decode the claims of a forwarded request
+///
+/// The caller must have checked the cluster secret first.
+pub(crate) fn decode_principal(value: &HeaderValue) -> crate::error::Result<Claims> {
+    let invalid = || AllSourceError::ValidationError("Invalid forwarded principal".to_string());
+    let bytes = general_purpose::STANDARD.decode(value.as_bytes()).map_err(|_| invalid())?;
+    let claims: Claims = serde_json::from_slice(&bytes).map_err(|_| invalid())?;
+    if claims.is_expired() {
+        return Err(AllSourceError::ValidationError("Forwarded principal has expired".to_string()));
+    }
+    Ok(claims)
+}
+
+/// Query response body as returned by every node
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct QueryResults {
+    events: Vec<EventDto>,
+    count: usize,
+}
+
+/// Merge per-node query results: drop replica duplicates, order by
+/// timestamp as a single node would, then re-apply the limit
+fn merge_query_results(results: Vec<QueryResults>, limit: Option<usize>) -> QueryResults {
+    let mut seen = HashSet::new();
+    let mut events: Vec<EventDto> = results
+        .into_iter()
+        .flat_map(|r| r.events)
+        .filter(|event| seen.insert(event.id))
+        .collect();
+    events.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then(a.id.cmp(&b.id)));
+    if let Some(limit) = limit {
+        events.truncate(limit);
+    }
+
+    QueryResults {
+        count: events.len(),
+        events,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{AuthManager, Role};
+    use crate::middleware::{auth_middleware, forwarding_middleware, AuthState, ForwardingState};
+    use crate::store::EventStore;
+    use axum::routing::{get, post};
+    use axum::Router;
+    use serde_json::json;
+    use tokio::net::TcpListener;
+
+    struct TestNode {
+        store: Arc<EventStore>,
+        registry: Arc<NodeRegistry>,
+        auth: Arc<AuthManager>,
+        address: String,
+    }
+
+    fn dto(id: u128, seconds: i64) -> EventDto {
+        EventDto {
+            id: uuid::Uuid::from_u128(id),
+            event_type: "user.created".to_string(),
+            entity_id: format!("user-{}", id),
+            tenant_id: "default".to_string(),
+            payload: json!({}),
+            timestamp: chrono::DateTime::from_timestamp(seconds, 0).unwrap(),
+            metadata: None,
+            version: 1,
+        }
+    }
+
+    /// Start nodes on localhost serving ingest, entity state and queries
+    /// behind the forwarding middleware
+    async fn start_cluster(count: usize) -> Vec<TestNode> {
+        start_nodes(count, None).await
+    }
+
+    /// With a cluster secret, each node also authenticates requests against
+    /// its own auth manager, as separate processes would
+    async fn start_nodes(count: usize, cluster_secret: Option<&str>) -> Vec<TestNode> {
+        let mut listeners = Vec::new();
+        for _ in 0..count {
+            listeners.push(TcpListener::bind("127.0.0.1:0").await.unwrap());
+        }
+        let addresses: Vec<String> = listeners
+            .iter()
+            .map(|l| l.local_addr().unwrap().to_string())
+            .collect();
+
+        let mut nodes = Vec::new();
+        for (id, listener) in listeners.into_iter().enumerate() {
+            let registry = Arc::new(NodeRegistry::new(8));
+            for (peer, address) in addresses.iter().enumerate() {
+                registry.register_node(Node {
+                    id: peer as u32,
+                    address: address.clone(),
+                    healthy: true,
+                    assigned_partitions: vec![],
+                });
+            }
+            let forwarder = Arc::new(RequestForwarder::new(
+                ForwardingConfig {
+                    node_id: id as u32,
+                    timeout: Duration::from_secs(2),
+                    cluster_secret: cluster_secret.map(str::to_string),
+                    ..Default::default()
+                },
+                registry.clone(),
+            ));
+
+            let store = Arc::new(EventStore::new());
+            let auth = Arc::new(AuthManager::new(&format!("node-{}-secret", id)));
+            let app = Router::new()
+                .route("/api/v1/events", post(crate::api::ingest_event))
+                .route("/api/v1/events/query", get(crate::api::query_events))
+                .route("/api/v1/entities/:entity_id/state", get(crate::api::get_entity_state))
+                .with_state(store.clone())
+                .layer(axum::middleware::from_fn_with_state(
+                    ForwardingState {
+                        forwarder: Some(forwarder),
+                    },
+                    forwarding_middleware,
+                ));
+            let app = match cluster_secret {
+                Some(secret) => app.layer(axum::middleware::from_fn_with_state(
+                    AuthState {
+                        auth_manager: auth.clone(),
+                        cluster_secret: Some(secret.to_string()),
+                    },
+                    auth_middleware,
+                )),
+                None => app,
+            };
+            tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+            nodes.push(TestNode {
+                store,
+                registry,
+                auth,
+                address: addresses[id].clone(),
+            });
+        }
+        nodes
+    }
+
+    fn owner(registry: &Arc<NodeRegistry>, entity: &str) -> u32 {
+        RequestRouter::new(registry.clone())
+            .route_for_entity(&EntityId::new(entity.to_string()).unwrap())
+            .unwrap()
+            .id
+    }
+
+    fn entity_owned_by(registry: &Arc<NodeRegistry>, node: u32) -> String {
+        (0..)
+            .map(|i| format!("user-{}", i))
+            .find(|entity| owner(registry, entity) == node)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_merge_query_results() {
+        let merged = merge_query_results(
+            vec![
+                QueryResults {
+                    events: vec![dto(1, 30), dto(2, 10)],
+                    count: 2,
+                },
+                // Node 2 holds a replica of event 2
+                QueryResults {
+                    events: vec![dto(2, 10), dto(3, 20)],
+                    count: 2,
+                },
+            ],
+            Some(2),
+        );
+
+        let ids: Vec<u128> = merged.events.iter().map(|e| e.id.as_u128()).collect();
+        assert_eq!(ids, vec![2, 3]);
+        assert_eq!(merged.count, 2);
+    }
+
+    #[tokio::test]
+    async fn test_entity_requests_are_served_by_the_owner() {
+        let nodes = start_cluster(2).await;
+        let client = reqwest::Client::new();
+        let entity = entity_owned_by(&nodes[0].registry, 1);
+
+        // Written through node 0, stored on node 1
+        let response = client
+            .post(format!("http://{}/api/v1/events", nodes[0].address))
+            .json(&json!({
+                "event_type": "user.created",
+                "entity_id": entity,
+                "payload": {"name": entity},
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        assert!(nodes[0].store.reconstruct_state(&entity, None).is_err());
+        assert!(nodes[1].store.reconstruct_state(&entity, None).is_ok());
+
+        // Read through node 0, by state and by entity query
+        let response = client
+            .get(format!("http://{}/api/v1/entities/{}/state", nodes[0].address, entity))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        let state: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(state["current_state"]["name"], entity.as_str());
+
+        let response = client
+            .get(format!("http://{}/api/v1/events/query?entity_id={}", nodes[0].address, entity))
+            .send()
+            .await
+            .unwrap();
+        let results: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(results["count"], 1);
+
+        // A forwarded request is never forwarded again
+        let response = client
+            .get(format!("http://{}/api/v1/entities/{}/state", nodes[0].address, entity))
+            .header(FORWARDED_BY_HEADER, "1")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_forwarded_requests_carry_the_verified_principal() {
+        let nodes = start_nodes(2, Some("s3cret")).await;
+        let client = reqwest::Client::new();
+        let entity = entity_owned_by(&nodes[0].registry, 1);
+
+        // The API key only exists on node 0
+        let (_, key) = nodes[0]
+            .auth
+            .create_api_key("writer".to_string(), "default".to_string(), Role::Developer, None)
+            .unwrap();
+        let response = client
+            .post(format!("http://{}/api/v1/events", nodes[0].address))
+            .bearer_auth(&key)
+            .json(&json!({
+                "event_type": "user.created",
+                "entity_id": entity,
+                "payload": {},
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        assert!(nodes[1].store.reconstruct_state(&entity, None).is_ok());
+
+        let response = client
+            .get(format!("http://{}/api/v1/events/query?event_type=user.created", nodes[0].address))
+            .bearer_auth(&key)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+
+        // Clients cannot assert a principal without the cluster secret
+        let claims = crate::auth::Claims::new(
+            "intruder".to_string(),
+            "default".to_string(),
+            Role::Admin,
+            chrono::Duration::hours(1),
+        );
+        let response = client
+            .get(format!("http://{}/api/v1/entities/{}/state", nodes[1].address, entity))
+            .header(FORWARDED_BY_HEADER, "0")
+            .header(FORWARDED_PRINCIPAL_HEADER, encode_principal(&claims))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 403);
+    }
+
+    #[tokio::test]
+    async fn test_queries_fan_out_to_every_node() {
+        let nodes = start_cluster(2).await;
+        let client = reqwest::Client::new();
+
+        for i in 0..6 {
+            let entity = format!("user-{}", i);
+            let event = crate::domain::entities::Event::from_strings(
+                "user.created".to_string(),
+                entity.clone(),
+                "default".to_string(),
+                json!({}),
+                None,
+            )
+            .unwrap();
+            nodes[owner(&nodes[0].registry, &entity) as usize].store.ingest(event).unwrap();
+        }
+        let local_counts: Vec<usize> = nodes.iter().map(|n| n.store.stats().total_events).collect();
+        assert!(local_counts.iter().all(|count| *count < 6));
+
+        let response = client
+            .get(format!("http://{}/api/v1/events/query?event_type=user.created", nodes[1].address))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        let results: QueryResults = response.json().await.unwrap();
+        assert_eq!(results.count, 6);
+        assert!(results.events.windows(2).all(|w| w[0].timestamp <= w[1].timestamp));
+
+        let response = client
+            .get(format!("http://{}/api/v1/events/query?event_type=user.created&limit=4", nodes[0].address))
+            .send()
+            .await
+            .unwrap();
+        let results: QueryResults = response.json().await.unwrap();
+        assert_eq!(results.count, 4);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_owner_returns_retry_hint() {
+        let nodes = start_cluster(1).await;
+        let client = reqwest::Client::new();
+
+        // A node that stopped listening
+        let dead = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let dead_address = dead.local_addr().unwrap().to_string();
+        drop(dead);
+        nodes[0].registry.register_node(Node {
+            id: 1,
+            address: dead_address,
+            healthy: true,
+            assigned_partitions: vec![],
+        });
+
+        let entity = entity_owned_by(&nodes[0].registry, 1);
+        let response = client
+            .get(format!("http://{}/api/v1/entities/{}/state", nodes[0].address, entity))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 503);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "1");
+
+        let response = client
+            .get(format!("http://{}/api/v1/events/query", nodes[0].address))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 503);
+    }
+}
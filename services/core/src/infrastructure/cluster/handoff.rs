@@ -0,0 +1,402 @@
+//! Partition Handoff
+//!
+//! Moves partition leadership to another node without losing writes. When
+//! membership changes, the registry plans handoffs instead of moving
+//! partitions outright; the current owner carries each one out:
+//!
+//! 1. Ship the partition to the target until it has caught up
+//! 2. Refuse new writes to the partition and wait for in-flight ones to finish
+//! 3. Ship the remaining entries and wait for the target to apply them
+//! 4. Commit the move through the metadata log, so every node switches
+//!    the partition's leader at the same point
+//!
+//! Writes refused while draining fail with a retryable error. If any step
+//! times out, the partition is reopened and the handoff retried later.
+//!
+//! The coordinator also reports, through the metadata log, which followers
+//! of its partitions are in sync, so that if this node fails its partitions
+//! go to nodes that already hold their logs.
+
+use super::consensus::{MetadataCommand, RaftNode};
+use super::replication::ReplicationManager;
+use crate::error::{AllSourceError, Result};
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Handoff configuration
+#[derive(Debug, Clone)]
+pub struct HandoffConfig {
+    /// How often to look for pending handoffs
+    pub interval: Duration,
+
+    /// How long the target may take to catch up before draining starts
+    pub catch_up_timeout: Duration,
+
+    /// How long in-flight writes, and the final catch-up, may take while
+    /// the partition refuses writes
+    pub drain_timeout: Duration,
+}
+
+impl Default for HandoffConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(1),
+            catch_up_timeout: Duration::from_secs(30),
+            drain_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Carries out the handoffs of partitions this node owns
+pub struct HandoffCoordinator {
+    raft: Arc<RaftNode>,
+    replication: Arc<ReplicationManager>,
+    config: HandoffConfig,
+
+    /// Leader epoch and sequence of each owned partition at the last report
+    reported: Mutex<HashMap<u32, (u64, u64)>>,
+}
+
+impl HandoffCoordinator {
+    pub fn new(
+        raft: Arc<RaftNode>,
+        replication: Arc<ReplicationManager>,
+        config: HandoffConfig,
+    ) -> Self {
+        Self {
+            raft,
+            replication,
+            config,
+            reported: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Check for pending handoffs, and report in-sync followers, every `interval`
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.config.interval);
+            loop {
+                interval.tick().await;
+                self.run_once().await;
+                self.report_in_sync().await;
+            }
+        })
+    }
+
+    /// Report the in-sync followers of each partition this node owns where
+    /// they changed. A follower is in sync if it has applied everything
+    /// committed as of the previous report, so it drops out only after
+    /// lagging for a whole interval. Returns the number of reports.
+    pub async fn report_in_sync(&self) -> usize {
+        let log = self.replication.log();
+        let registry = log.registry();
+
+        let mut changes = Vec::new();
+        {
+            let mut reported = self.reported.lock();
+            reported.retain(|partition, _| log.is_leader(*partition));
+            for partition in (0..registry.partition_count()).filter(|p| log.is_leader(*p)) {
+                let epoch = registry.leader_epoch(partition);
+                let sequence = log.sequence(partition);
+                let since = match reported.insert(partition, (epoch, sequence)) {
+                    Some((reported_epoch, reported_sequence)) if reported_epoch == epoch => {
+                        reported_sequence
+                    }
+                    _ => sequence,
+                };
+
+                let mut replicas: Vec<u32> = log
+                    .followers(partition)
+                    .into_iter()
+                    .map(|follower| follower.id)
+                    .filter(|&follower| log.acked(follower, partition) >= since)
+                    .collect();
+                replicas.sort_unstable();
+                if replicas != registry.in_sync_replicas(partition) {
+                    changes.push((partition, epoch, replicas));
+                }
+            }
+        }
+
+        let mut count = 0;
+        for (partition, epoch, replicas) in changes {
+            match self
+                .raft
+                .submit(MetadataCommand::SetInSync { partition, epoch, replicas })
+                .await
+            {
+                Ok(_) => count += 1,
+                Err(e) => tracing::warn!(
+                    "⚠️  Reporting in-sync replicas of partition {} failed: {}",
+                    partition,
+                    e
+                ),
+            }
+        }
+        count
+    }
+
+    /// Carry out every pending handoff of a partition this node owns.
+    /// Returns the number completed.
+    pub async fn run_once(&self) -> usize {
+        let log = self.replication.log();
+        let node_id = log.config().node_id;
+
+        let mut pending: Vec<(u32, u32)> = log
+            .registry()
+            .pending_handoffs()
+            .into_iter()
+            .filter(|(partition, _)| log.registry().node_for_partition(*partition) == Some(node_id))
+            .collect();
+        pending.sort_unstable();
+
+        let mut completed = 0;
+        for (partition, target) in pending {
+            match self.hand_off(partition, target).await {
+                Ok(()) => completed += 1,
+                Err(e) => tracing::warn!(
+                    "⚠️  Handoff of partition {} to node {} failed: {}",
+                    partition,
+                    target,
+                    e
+                ),
+            }
+        }
+        completed
+    }
+
+    /// Hand one partition this node owns to `target`
+    pub async fn hand_off(&self, partition: u32, target: u32) -> Result<()> {
+        let log = self.replication.log();
+        let from = log.config().node_id;
+
+        self.catch_up(partition, target, self.config.catch_up_timeout)
+            .await?;
+
+        log.begin_drain(partition);
+        let result = async {
+            if !log.wait_drained(partition, self.config.drain_timeout).await {
+                return Err(AllSourceError::ReplicationError(format!(
+                    "Writes to partition {} did not drain within {:?}",
+                    partition, self.config.drain_timeout
+                )));
+            }
+            self.catch_up(partition, target, self.config.drain_timeout)
+                .await?;
+            self.raft
+                .submit(MetadataCommand::CompleteHandoff {
+                    partition,
+                    from,
+                    to: target,
+                })
+                .await
+        }
+        .await;
+        log.end_drain(partition);
+
+        result?;
+        tracing::info!(
+            "🔀 Handed partition {} from node {} to node {}",
+            partition,
+            from,
+            target
+        );
+        Ok(())
+    }
+
+    /// Ship the partition to `target` and wait until it has applied
+    /// everything committed so far
+    async fn catch_up(&self, partition: u32, target: u32, timeout: Duration) -> Result<()> {
+        let log = self.replication.log();
+        let sequence = log.sequence(partition);
+
+        self.replication.sync_all();
+        if !log
+            .wait_for_follower(target, partition, sequence, timeout)
+            .await
+        {
+            return Err(AllSourceError::ReplicationError(format!(
+                "Node {} did not catch up on partition {} within {:?}",
+                target, partition, timeout
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::cluster::consensus::{RaftConfig, RaftRole};
+    use crate::infrastructure::cluster::node_registry::{Node, NodeRegistry};
+    use crate::infrastructure::cluster::replication::{ReplicationConfig, ReplicationLog};
+    use crate::store::EventStore;
+    use crate::test_support::{event, eventually};
+    use axum::Router;
+    use serde_json::json;
+    use tokio::net::TcpListener;
+
+    const SECRET: &str = "handoff-test-secret";
+
+    struct TestNode {
+        store: Arc<EventStore>,
+        log: Arc<ReplicationLog>,
+        raft: Arc<RaftNode>,
+    }
+
+    /// Start `count` nodes on localhost whose initial membership is only
+    /// the first `members` of them
+    async fn start_cluster(count: usize, members: usize) -> (Vec<TestNode>, Vec<String>) {
+        let mut listeners = Vec::new();
+        for _ in 0..count {
+            listeners.push(TcpListener::bind("127.0.0.1:0").await.unwrap());
+        }
+        let addresses: Vec<String> = listeners
+            .iter()
+            .map(|l| l.local_addr().unwrap().to_string())
+            .collect();
+
+        let mut nodes = Vec::new();
+        for (id, listener) in listeners.into_iter().enumerate() {
+            let registry = Arc::new(NodeRegistry::new(8));
+            for (peer, address) in addresses.iter().enumerate().take(members) {
+                registry.register_node(Node {
+                    id: peer as u32,
+                    address: address.clone(),
+                    healthy: true,
+                    assigned_partitions: vec![],
+                });
+            }
+            registry.enable_handoffs();
+
+            let raft = Arc::new(
+                RaftNode::new(
+                    RaftConfig {
+                        node_id: id as u32,
+                        heartbeat_interval: Duration::from_millis(50),
+                        election_timeout: Duration::from_millis(250),
+                        cluster_secret: Some(SECRET.to_string()),
+                        ..Default::default()
+                    },
+                    registry.clone(),
+                )
+                .unwrap(),
+            );
+            raft.clone().spawn();
+
+            let log = Arc::new(ReplicationLog::new(
+                ReplicationConfig {
+                    node_id: id as u32,
+                    sync_interval: Duration::from_millis(50),
+                    cluster_secret: Some(SECRET.to_string()),
+                    ..Default::default()
+                },
+                registry,
+            ));
+            let store = Arc::new(EventStore::new().with_replication(log.clone()));
+            let manager = Arc::new(ReplicationManager::new(log.clone(), store.clone()));
+            manager.clone().spawn();
+
+            Arc::new(HandoffCoordinator::new(
+                raft.clone(),
+                manager.clone(),
+                HandoffConfig {
+                    interval: Duration::from_millis(100),
+                    ..Default::default()
+                },
+            ))
+            .spawn();
+
+            let app: Router = raft.router().merge(manager.router());
+            tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+            nodes.push(TestNode { store, log, raft });
+        }
+        (nodes, addresses)
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_joining_node_takes_over_partitions_with_their_data() {
+        let (nodes, addresses) = start_cluster(3, 2).await;
+        assert!(
+            eventually(Duration::from_secs(5), || nodes
+                .iter()
+                .take(2)
+                .any(|n| n.raft.role() == RaftRole::Leader))
+            .await
+        );
+
+        // One entity per partition, written on its owner
+        let log = &nodes[0].log;
+        let partitions = log.registry().partition_count();
+        let entities: Vec<String> = (0..partitions)
+            .map(|partition| {
+                (0..)
+                    .map(|i| format!("user-{}", i))
+                    .find(|entity| log.partition_for(entity) == partition)
+                    .unwrap()
+            })
+            .collect();
+        for entity in &entities {
+            let owner = log.registry().node_for_partition(log.partition_for(entity)).unwrap();
+            nodes[owner as usize]
+                .store
+                .ingest(event(entity, "user.created", json!({"name": entity})))
+                .unwrap();
+        }
+
+        // A draining partition refuses writes with a retryable error
+        let owner = log.registry().node_for_partition(0).unwrap() as usize;
+        nodes[owner].log.begin_drain(0);
+        assert!(matches!(
+            nodes[owner]
+                .store
+                .ingest(event(&entities[0], "user.created", json!({"name": &entities[0]}))),
+            Err(AllSourceError::ReplicationError(_))
+        ));
+        nodes[owner].log.end_drain(0);
+
+        nodes[1]
+            .raft
+            .submit(MetadataCommand::AddNode {
+                id: 2,
+                address: addresses[2].clone(),
+            })
+            .await
+            .unwrap();
+
+        // Every node agrees once the handoffs complete
+        assert!(
+            eventually(Duration::from_secs(15), || nodes.iter().all(|n| {
+                let registry = n.log.registry();
+                registry.node_count() == 3 && registry.pending_handoffs().is_empty()
+            }))
+            .await
+        );
+        let moved: Vec<u32> = (0..partitions)
+            .filter(|p| log.registry().node_for_partition(*p) == Some(2))
+            .collect();
+        assert!(!moved.is_empty());
+        for node in &nodes {
+            for partition in 0..partitions {
+                assert_eq!(
+                    node.log.registry().node_for_partition(partition),
+                    log.registry().node_for_partition(partition)
+                );
+            }
+        }
+
+        // The new owner holds the data and accepts writes
+        for partition in moved {
+            let entity = &entities[partition as usize];
+            let state = nodes[2].store.reconstruct_state(entity, None).unwrap();
+            assert_eq!(state["current_state"]["name"], entity.as_str());
+            nodes[2]
+                .store
+                .ingest(event(entity, "user.created", json!({"name": entity})))
+                .unwrap();
+        }
+    }
+}
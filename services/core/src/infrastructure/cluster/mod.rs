@@ -14,6 +14,22 @@
 /// - Failover on node failures
 /// - Load balancing for read operations
 ///
+/// ## Replication
+/// - Streams each partition's WAL from its leader to follower replicas
+/// - Synchronous or asynchronous write acknowledgement
+/// - Follower reads with read-your-writes tokens
+///
+/// ## Consensus
+/// - Raft-replicated membership, node health and partition moves
+/// - Heartbeat-driven failure detection
+///
+/// ## Handoff
+/// - Drains in-flight writes before a partition changes leader
+///
+/// ## Forwarding
+/// - Proxies entity requests from any node to the partition owner
+/// - Fans cross-partition queries out to all healthy nodes
+///
 /// # Example
 ///
 /// ```ignore
@@ -43,8 +59,19 @@
 /// println!("Send request to: {}", target_node.address);
 /// ```
 
+pub mod consensus;
+pub mod forwarding;
+pub mod handoff;
 pub mod node_registry;
+pub mod replication;
 pub mod request_router;
 
+pub use consensus::{ClusterStatus, MetadataCommand, RaftConfig, RaftNode, RaftRole};
+pub use forwarding::{ForwardingConfig, ForwardingError, RequestForwarder};
+pub use handoff::{HandoffConfig, HandoffCoordinator};
 pub use node_registry::{Node, NodeRegistry};
+pub use replication::{
+    AckMode, ReplicationConfig, ReplicationLog, ReplicationManager, ReplicationStatus,
+    ReplicationToken,
+};
 pub use request_router::RequestRouter;
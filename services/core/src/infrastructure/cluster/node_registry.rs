@@ -7,7 +7,12 @@
 /// - **Fixed partitions**: 32 partitions (single-node) or 1024+ (cluster)
 /// - **Consistent assignment**: Partitions assigned to nodes deterministically
 /// - **Health monitoring**: Track node health status
-/// - **Automatic rebalancing**: Reassign partitions on node failures
+/// - **Minimal-movement rebalancing**: rendezvous hashing with bounded
+///   load, so a membership change only moves the partitions it must
+/// - **Planned handoffs**: optionally, partitions leaving a healthy node are
+///   recorded as handoffs and move only once the owner has drained them
+/// - **In-sync failover**: partitions of a failed node go to a replica its
+///   leader last reported in sync, when one is healthy and has room
 ///
 /// # Cluster Topology
 /// - Single-node: All 32 partitions on one node
@@ -15,6 +20,9 @@
 /// - 4-node: 8 partitions per node
 /// - 8-node: 4 partitions per node
 ///
+/// No node holds more than `ceil(partitions / healthy nodes)`; within that
+/// bound each partition prefers the nodes it scores highest on.
+///
 /// # Example
 /// ```ignore
 /// let registry = NodeRegistry::new(32);
@@ -33,6 +41,7 @@
 
 use parking_lot::RwLock;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use crate::error::Result;
 
@@ -59,6 +68,18 @@ pub struct NodeRegistry {
 
     /// Registered nodes
     nodes: Arc<RwLock<HashMap<u32, Node>>>,
+
+    /// Planned moves off healthy nodes: partition -> target node
+    handoffs: Arc<RwLock<HashMap<u32, u32>>>,
+
+    /// Whether moves off healthy nodes wait for a handoff
+    handoffs_enabled: AtomicBool,
+
+    /// Times each partition changed leader; see `leader_epoch`
+    leader_epochs: Arc<RwLock<HashMap<u32, u64>>>,
+
+    /// Followers each partition's leader last reported in sync
+    in_sync: Arc<RwLock<HashMap<u32, Vec<u32>>>>,
 }
 
 impl NodeRegistry {
@@ -70,9 +91,21 @@ impl NodeRegistry {
         Self {
             partition_count,
             nodes: Arc::new(RwLock::new(HashMap::new())),
+            handoffs: Arc::new(RwLock::new(HashMap::new())),
+            handoffs_enabled: AtomicBool::new(false),
+            leader_epochs: Arc::new(RwLock::new(HashMap::new())),
+            in_sync: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
+    /// From now on, record partitions leaving a healthy node as pending
+    /// handoffs instead of moving them at once; `complete_handoff` performs
+    /// the move. Partitions of unhealthy or removed nodes still move
+    /// immediately. Typically enabled once the initial members are seeded.
+    pub fn enable_handoffs(&self) {
+        self.handoffs_enabled.store(true, Ordering::SeqCst);
+    }
+
     /// Register a node in the cluster
     ///
     /// Automatically rebalances partitions across healthy nodes.
@@ -85,7 +118,7 @@ impl NodeRegistry {
         nodes.insert(node.id, node);
 
         // Rebalance partitions
-        self.rebalance_partitions_locked(&mut nodes);
+        self.rebalance_partitions_locked(&mut nodes, &mut self.handoffs.write());
     }
 
     /// Unregister a node from the cluster
@@ -94,7 +127,7 @@ impl NodeRegistry {
     pub fn unregister_node(&self, node_id: u32) {
         let mut nodes = self.nodes.write();
         nodes.remove(&node_id);
-        self.rebalance_partitions_locked(&mut nodes);
+        self.rebalance_partitions_locked(&mut nodes, &mut self.handoffs.write());
     }
 
     /// Mark node as healthy or unhealthy
@@ -105,16 +138,22 @@ impl NodeRegistry {
 
         if let Some(node) = nodes.get_mut(&node_id) {
             node.healthy = healthy;
-            self.rebalance_partitions_locked(&mut nodes);
+            self.rebalance_partitions_locked(&mut nodes, &mut self.handoffs.write());
         }
     }
 
     /// Rebalance partitions across healthy nodes
     ///
-    /// Uses round-robin distribution for even load balancing.
-    fn rebalance_partitions_locked(&self, nodes: &mut HashMap<u32, Node>) {
-        // Clear existing assignments
-        for node in nodes.values_mut() {
+    /// Only partitions that must move do: those of nodes that left or went
+    /// unhealthy, and the excess of nodes above the load bound (e.g. after
+    /// a node joins). Each goes to the highest-scoring node with room; a
+    /// partition that lost its leader prefers an in-sync follower, so it
+    /// fails over to a node that already holds its log.
+    fn rebalance_partitions_locked(&self, nodes: &mut HashMap<u32, Node>, handoffs: &mut HashMap<u32, u32>) {
+        let leaders_before = leaders(nodes);
+
+        // Unhealthy nodes give up their partitions
+        for node in nodes.values_mut().filter(|n| !n.healthy) {
             node.assigned_partitions.clear();
         }
 
@@ -126,19 +165,168 @@ impl NodeRegistry {
 
         healthy_nodes.sort();
 
+        let owners: HashMap<u32, u32> = nodes
+            .values()
+            .flat_map(|n| n.assigned_partitions.iter().map(move |p| (*p, n.id)))
+            .collect();
+        handoffs.retain(|partition, target| {
+            owners.contains_key(partition) && healthy_nodes.contains(target)
+        });
+
         if healthy_nodes.is_empty() {
             return; // No healthy nodes available
         }
 
-        // Distribute partitions evenly using round-robin
-        for partition_id in 0..self.partition_count {
-            let node_idx = (partition_id as usize) % healthy_nodes.len();
-            let node_id = healthy_nodes[node_idx];
+        // Load as it will be once pending handoffs complete
+        let capacity = (self.partition_count as usize).div_ceil(healthy_nodes.len());
+        let planned = |partition: &u32| handoffs.get(partition).or(owners.get(partition)).copied();
+        let mut planned_partitions: HashMap<u32, Vec<u32>> = HashMap::new();
+        for partition in 0..self.partition_count {
+            if let Some(node_id) = planned(&partition) {
+                planned_partitions.entry(node_id).or_default().push(partition);
+            }
+        }
 
-            if let Some(node) = nodes.get_mut(&node_id) {
-                node.assigned_partitions.push(partition_id);
+        // Nodes over the bound shed the partitions they score lowest on
+        let mut to_place: Vec<u32> = (0..self.partition_count)
+            .filter(|p| !owners.contains_key(p))
+            .collect();
+        let mut load: HashMap<u32, usize> = HashMap::new();
+        for &node_id in &healthy_nodes {
+            let mut partitions = planned_partitions.remove(&node_id).unwrap_or_default();
+            if partitions.len() > capacity {
+                partitions.sort_by_key(|p| rendezvous_score(*p, node_id));
+                let excess = partitions.len() - capacity;
+                for partition in partitions.drain(..excess) {
+                    handoffs.remove(&partition);
+                    to_place.push(partition);
+                }
+            }
+            load.insert(node_id, partitions.len());
+        }
+        to_place.sort();
+
+        let in_sync = self.in_sync.read().clone();
+        for partition in to_place {
+            let with_room = || healthy_nodes.iter().copied().filter(|id| load[id] < capacity);
+            let in_sync_target = if owners.contains_key(&partition) {
+                None
+            } else {
+                let replicas = in_sync.get(&partition).map(Vec::as_slice).unwrap_or_default();
+                with_room()
+                    .filter(|id| replicas.contains(id))
+                    .max_by_key(|id| rendezvous_score(partition, *id))
+            };
+            let Some(target) =
+                in_sync_target.or_else(|| with_room().max_by_key(|id| rendezvous_score(partition, *id)))
+            else {
+                continue;
+            };
+            *load.get_mut(&target).unwrap() += 1;
+
+            match owners.get(&partition).copied() {
+                Some(owner) if owner == target => {
+                    handoffs.remove(&partition);
+                }
+                Some(_) if self.handoffs_enabled.load(Ordering::SeqCst) => {
+                    handoffs.insert(partition, target);
+                }
+                owner => {
+                    if let Some(node) = owner.and_then(|id| nodes.get_mut(&id)) {
+                        node.assigned_partitions.retain(|p| *p != partition);
+                    }
+                    if let Some(node) = nodes.get_mut(&target) {
+                        node.assigned_partitions.push(partition);
+                    }
+                }
             }
         }
+
+        for node in nodes.values_mut() {
+            node.assigned_partitions.sort();
+        }
+
+        self.start_epochs(leaders(nodes).into_iter().filter(|(partition, leader)| {
+            leaders_before.get(partition) != Some(leader)
+        }));
+    }
+
+    /// Start a new epoch for partitions that changed leader. What the old
+    /// leader reported in sync says nothing about the new one's log.
+    fn start_epochs(&self, changed: impl Iterator<Item = (u32, u32)>) {
+        let mut epochs = self.leader_epochs.write();
+        let mut in_sync = self.in_sync.write();
+        for (partition, _) in changed {
+            *epochs.entry(partition).or_default() += 1;
+            in_sync.remove(&partition);
+        }
+    }
+
+    /// Record the followers a partition's leader reports in sync with it
+    ///
+    /// Ignored unless `epoch` is the partition's current leader epoch, so a
+    /// report from a deposed leader cannot apply to its successor.
+    pub fn set_in_sync(&self, partition_id: u32, epoch: u64, mut replicas: Vec<u32>) -> bool {
+        if self.leader_epoch(partition_id) != epoch {
+            return false;
+        }
+        replicas.sort_unstable();
+        replicas.dedup();
+        self.in_sync.write().insert(partition_id, replicas);
+        true
+    }
+
+    /// Followers last reported in sync with the partition's leader
+    pub fn in_sync_replicas(&self, partition_id: u32) -> Vec<u32> {
+        self.in_sync.read().get(&partition_id).cloned().unwrap_or_default()
+    }
+
+    /// Pending handoffs: partition -> target node
+    pub fn pending_handoffs(&self) -> HashMap<u32, u32> {
+        self.handoffs.read().clone()
+    }
+
+    /// Target of a pending handoff
+    pub fn handoff_target(&self, partition_id: u32) -> Option<u32> {
+        self.handoffs.read().get(&partition_id).copied()
+    }
+
+    /// Move a partition whose handoff from `from` to `to` has drained
+    ///
+    /// Returns false if the handoff is no longer pending as described.
+    pub fn complete_handoff(&self, partition_id: u32, from: u32, to: u32) -> bool {
+        let mut nodes = self.nodes.write();
+        let mut handoffs = self.handoffs.write();
+
+        let owned_by_source = nodes
+            .get(&from)
+            .map(|n| n.healthy && n.assigned_partitions.contains(&partition_id))
+            .unwrap_or(false);
+        if handoffs.get(&partition_id) != Some(&to) || !owned_by_source {
+            return false;
+        }
+        let Some(target) = nodes.get_mut(&to).filter(|n| n.healthy) else {
+            return false;
+        };
+
+        target.assigned_partitions.push(partition_id);
+        target.assigned_partitions.sort();
+        if let Some(source) = nodes.get_mut(&from) {
+            source.assigned_partitions.retain(|p| *p != partition_id);
+        }
+        handoffs.remove(&partition_id);
+        self.start_epochs(std::iter::once((partition_id, to)));
+        true
+    }
+
+    /// Number of times the partition has changed leader.
+    ///
+    /// Every node applies the same membership changes in the same order, so
+    /// they agree on it; a given epoch of a partition only ever has one
+    /// leader. The replication log tags entries with it to tell the logs of
+    /// successive leaders apart.
+    pub fn leader_epoch(&self, partition_id: u32) -> u64 {
+        self.leader_epochs.read().get(&partition_id).copied().unwrap_or(0)
     }
 
     /// Find node responsible for a partition
@@ -152,6 +340,32 @@ impl NodeRegistry {
             .map(|n| n.id)
     }
 
+    /// Replica set for a partition: the assigned node followed by the
+    /// `replication_factor - 1` other healthy nodes that score highest on
+    /// the partition, so replicas also change minimally with membership
+    pub fn replicas_for_partition(&self, partition_id: u32, replication_factor: usize) -> Vec<Node> {
+        let nodes = self.nodes.read();
+
+        let Some(leader) = nodes
+            .values()
+            .find(|n| n.healthy && n.assigned_partitions.contains(&partition_id))
+        else {
+            return Vec::new();
+        };
+
+        let mut followers: Vec<&Node> = nodes
+            .values()
+            .filter(|n| n.healthy && n.id != leader.id)
+            .collect();
+        followers.sort_by_key(|n| std::cmp::Reverse(rendezvous_score(partition_id, n.id)));
+
+        std::iter::once(leader)
+            .chain(followers)
+            .take(replication_factor)
+            .cloned()
+            .collect()
+    }
+
     /// Get node by ID
     pub fn get_node(&self, node_id: u32) -> Option<Node> {
         self.nodes.read().get(&node_id).cloned()
@@ -199,6 +413,11 @@ impl NodeRegistry {
         true
     }
 
+    /// Get the fixed partition count
+    pub fn partition_count(&self) -> u32 {
+        self.partition_count
+    }
+
     /// Get node count
     pub fn node_count(&self) -> usize {
         self.nodes.read().len()
@@ -210,6 +429,27 @@ impl NodeRegistry {
     }
 }
 
+/// Leader of each partition: its healthy assigned node
+fn leaders(nodes: &HashMap<u32, Node>) -> HashMap<u32, u32> {
+    nodes
+        .values()
+        .filter(|n| n.healthy)
+        .flat_map(|n| n.assigned_partitions.iter().map(move |p| (*p, n.id)))
+        .collect()
+}
+
+/// Rendezvous (highest random weight) score of a node for a partition
+///
+/// A stable mix rather than `DefaultHasher`, so every build of every node
+/// agrees on it.
+fn rendezvous_score(partition_id: u32, node_id: u32) -> u64 {
+    // splitmix64 finalizer
+    let mut x = ((partition_id as u64) << 32 | node_id as u64).wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -427,4 +667,156 @@ mod tests {
             assert_eq!(node1, node2);
         }
     }
+
+    #[test]
+    fn test_replicas_follow_assignment() {
+        let registry = NodeRegistry::new(8);
+
+        for i in 0..3 {
+            registry.register_node(Node {
+                id: i,
+                address: format!("node-{}:8080", i),
+                healthy: true,
+                assigned_partitions: vec![],
+            });
+        }
+
+        for partition_id in 0..8 {
+            let replicas: Vec<u32> = registry
+                .replicas_for_partition(partition_id, 2)
+                .iter()
+                .map(|n| n.id)
+                .collect();
+            assert_eq!(replicas.len(), 2);
+            assert_eq!(Some(replicas[0]), registry.node_for_partition(partition_id));
+            assert_ne!(replicas[0], replicas[1]);
+        }
+
+        // Replication factor is capped by the healthy node count
+        registry.set_node_health(2, false);
+        assert_eq!(registry.replicas_for_partition(0, 3).len(), 2);
+    }
+
+    fn node(id: u32) -> Node {
+        Node {
+            id,
+            address: format!("node-{}:8080", id),
+            healthy: true,
+            assigned_partitions: vec![],
+        }
+    }
+
+    fn assignment(registry: &NodeRegistry) -> HashMap<u32, u32> {
+        (0..registry.partition_count())
+            .filter_map(|p| registry.node_for_partition(p).map(|n| (p, n)))
+            .collect()
+    }
+
+    #[test]
+    fn test_membership_changes_move_minimal_partitions() {
+        let registry = NodeRegistry::new(32);
+        for i in 0..4 {
+            registry.register_node(node(i));
+        }
+        let before = assignment(&registry);
+
+        // A joining node takes only its share, all from existing nodes
+        registry.register_node(node(4));
+        let after = assignment(&registry);
+        let moved: Vec<u32> = (0..32).filter(|p| before[p] != after[p]).collect();
+        assert!(moved.len() <= 7);
+        assert!(moved.iter().all(|p| after[p] == 4));
+        assert!(registry.partition_distribution().values().all(|p| p.len() <= 7));
+
+        // A failing node's partitions move; nobody else's do
+        registry.set_node_health(2, false);
+        let failed = assignment(&registry);
+        for p in 0..32 {
+            if after[&p] != 2 {
+                assert_eq!(failed[&p], after[&p]);
+            }
+        }
+        assert_eq!(failed.len(), 32);
+    }
+
+    #[test]
+    fn test_leader_epoch_counts_leader_changes() {
+        let registry = NodeRegistry::new(8);
+        registry.register_node(node(0));
+        registry.register_node(node(1));
+        let leaders = assignment(&registry);
+        let epochs: Vec<u64> = (0..8).map(|p| registry.leader_epoch(p)).collect();
+        assert!(epochs.iter().all(|&epoch| epoch >= 1));
+
+        // Only the failed node's partitions start a new epoch
+        registry.set_node_health(1, false);
+        for p in 0..8 {
+            let expected = epochs[p as usize] + u64::from(leaders[&p] == 1);
+            assert_eq!(registry.leader_epoch(p), expected);
+        }
+
+        // A registry applying the same changes agrees
+        let replica = NodeRegistry::new(8);
+        replica.register_node(node(0));
+        replica.register_node(node(1));
+        replica.set_node_health(1, false);
+        assert!((0..8).all(|p| replica.leader_epoch(p) == registry.leader_epoch(p)));
+    }
+
+    #[test]
+    fn test_handoffs_wait_for_completion() {
+        let registry = NodeRegistry::new(8);
+        registry.register_node(node(0));
+        registry.enable_handoffs();
+        assert_eq!(registry.get_node(0).unwrap().assigned_partitions.len(), 8);
+
+        // Partitions for the new node are planned, not moved
+        registry.register_node(node(1));
+        let handoffs = registry.pending_handoffs();
+        assert_eq!(handoffs.len(), 4);
+        assert!(handoffs.values().all(|to| *to == 1));
+        assert_eq!(registry.get_node(0).unwrap().assigned_partitions.len(), 8);
+
+        let (&partition, _) = handoffs.iter().next().unwrap();
+        assert!(!registry.complete_handoff(partition, 1, 0));
+        assert!(registry.complete_handoff(partition, 0, 1));
+        assert_eq!(registry.node_for_partition(partition), Some(1));
+        assert_eq!(registry.handoff_target(partition), None);
+        assert_eq!(registry.pending_handoffs().len(), 3);
+
+        // Handoffs to a node that fails are abandoned; failed nodes' own
+        // partitions move at once
+        registry.set_node_health(1, false);
+        assert!(registry.pending_handoffs().is_empty());
+        assert_eq!(registry.node_for_partition(partition), Some(0));
+    }
+
+    #[test]
+    fn test_failover_prefers_in_sync_replica() {
+        let registry = NodeRegistry::new(8);
+        for i in 0..4 {
+            registry.register_node(node(i));
+        }
+        let partition = registry.get_node(0).unwrap().assigned_partitions[0];
+        let epoch = registry.leader_epoch(partition);
+
+        // Without a report, the partition would go to its top scorer
+        let fallback = NodeRegistry::new(8);
+        for i in 0..4 {
+            fallback.register_node(node(i));
+        }
+        fallback.set_node_health(0, false);
+        let unreported = fallback.node_for_partition(partition).unwrap();
+        let in_sync = (1..4).find(|id| *id != unreported).unwrap();
+
+        // Reports for another epoch are ignored
+        assert!(!registry.set_in_sync(partition, epoch + 1, vec![unreported]));
+        assert!(registry.set_in_sync(partition, epoch, vec![in_sync]));
+        assert_eq!(registry.in_sync_replicas(partition), vec![in_sync]);
+
+        registry.set_node_health(0, false);
+        assert_eq!(registry.node_for_partition(partition), Some(in_sync));
+        assert_eq!(registry.leader_epoch(partition), epoch + 1);
+        assert!(registry.in_sync_replicas(partition).is_empty());
+    }
 }
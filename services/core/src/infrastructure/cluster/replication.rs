@@ -0,0 +1,1474 @@
+//! WAL Replication Between Cluster Nodes
+//!
+//! Streams each partition's write-ahead log from the partition leader to
+//! follower replicas over HTTP, so followers hold a copy of the data and
+//! can serve reads.
+//!
+//! # Design
+//! - **Per-partition sequences**: every event the leader commits gets the
+//!   next sequence number in its partition's log
+//! - **Push-based shipping**: the leader POSTs batches of WAL entries to each
+//!   follower and resumes from whatever sequence the follower reports
+//! - **Ordered apply**: followers apply entries strictly in sequence, skip
+//!   entries they already hold and refuse gaps
+//! - **Leader epochs**: entries carry the partition's leader epoch (see
+//!   `NodeRegistry::leader_epoch`). Like Raft's log matching, a batch must
+//!   follow on from an entry the follower holds with the same epoch; a
+//!   follower whose log diverged (e.g. a former leader that rejoins) drops
+//!   the conflicting entries, and their events, before catching up
+//! - **Acknowledgement**: asynchronous, or synchronous where a write waits
+//!   until `min_acks` followers have applied it
+//! - **Read-your-writes**: writes return a `partition:sequence` token; a
+//!   follower holds reads carrying it until it has applied that sequence
+//! - **Handoff**: a partition being handed to another node ships to that
+//!   node as an extra follower, and can be drained of in-flight writes
+//!
+//! Partition logs keep the last `retained_entries` entries; a follower that
+//! falls further behind must be reseeded. Logs opened from a directory are
+//! durable: every entry is synced to the partition's file before it counts
+//! as committed or applied, and reopening restores the sequences and the
+//! retained entries, so a restarted leader continues its sequences and
+//! followers catch up from where they were.
+//!
+//! # Example
+//! ```ignore
+//! let log = Arc::new(ReplicationLog::open(
+//!     ReplicationConfig { node_id: 0, ..Default::default() },
+//!     registry.clone(),
+//!     data_dir.join("cluster").join("replication"),
+//! )?);
+//! let store = Arc::new(EventStore::new().with_replication(log.clone()));
+//! let manager = Arc::new(ReplicationManager::new(log, store.clone()));
+//! manager.clone().spawn();
+//!
+//! // Serve the node-to-node endpoints alongside the API
+//! let app = app.merge(manager.router());
+//! ```
+
+use super::node_registry::{Node, NodeRegistry};
+use crate::domain::entities::Event;
+use crate::domain::value_objects::PartitionKey;
+use crate::error::{AllSourceError, Result};
+use crate::infrastructure::persistence::object_store::write_atomically;
+use crate::store::EventStore;
+use crate::wal::WALEntry;
+use axum::{
+    extract::State,
+    http::HeaderMap,
+    routing::{get, post},
+    Json, Router,
+};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Header carrying a read-your-writes token
+pub const REPLICATION_TOKEN_HEADER: &str = "x-replication-token";
+
+/// Header authenticating node-to-node requests
+pub const CLUSTER_SECRET_HEADER: &str = "x-cluster-secret";
+
+/// When a write is acknowledged to the client
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "lowercase")]
+pub enum AckMode {
+    /// Once the leader has committed it; followers catch up in the background
+    Async,
+    /// Once `min_acks` followers (capped at the follower count) have applied it
+    Sync { min_acks: usize },
+}
+
+/// Replication configuration
+#[derive(Debug, Clone)]
+pub struct ReplicationConfig {
+    /// This node's ID in the registry
+    pub node_id: u32,
+
+    /// Copies of each partition, including the leader
+    pub replication_factor: usize,
+
+    pub ack_mode: AckMode,
+
+    /// How long a synchronous write waits for follower acks
+    pub ack_timeout: Duration,
+
+    /// How long a read carrying a token waits for this node to catch up
+    pub read_wait_timeout: Duration,
+
+    /// Entries kept per partition for follower catch-up
+    pub retained_entries: usize,
+
+    /// Maximum entries per append request
+    pub batch_size: usize,
+
+    /// Interval of the background catch-up pass
+    pub sync_interval: Duration,
+
+    /// Shared secret required on replication endpoints
+    pub cluster_secret: Option<String>,
+}
+
+impl Default for ReplicationConfig {
+    fn default() -> Self {
+        Self {
+            node_id: 0,
+            replication_factor: 2,
+            ack_mode: AckMode::Async,
+            ack_timeout: Duration::from_secs(5),
+            read_wait_timeout: Duration::from_secs(2),
+            retained_entries: 100_000,
+            batch_size: 500,
+            sync_interval: Duration::from_secs(1),
+            cluster_secret: None,
+        }
+    }
+}
+
+/// Position in a partition's log, handed to clients for read-your-writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplicationToken {
+    pub partition: u32,
+    pub sequence: u64,
+}
+
+impl fmt::Display for ReplicationToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.partition, self.sequence)
+    }
+}
+
+impl FromStr for ReplicationToken {
+    type Err = AllSourceError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || AllSourceError::InvalidInput(format!("Invalid replication token: {}", s));
+        let (partition, sequence) = s.split_once(':').ok_or_else(invalid)?;
+        Ok(Self {
+            partition: partition.parse().map_err(|_| invalid())?,
+            sequence: sequence.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// Batch of WAL entries from a partition leader
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppendEntriesRequest {
+    pub leader_id: u32,
+
+    /// The leader's epoch for the partition
+    pub term: u64,
+
+    pub partition: u32,
+
+    /// Sequence and epoch of the leader's entry preceding `entries`
+    pub prev_sequence: u64,
+    pub prev_term: u64,
+
+    pub entries: Vec<WALEntry>,
+
+    /// Last sequence in the leader's log
+    pub leader_sequence: u64,
+}
+
+/// Follower's position after a batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppendEntriesResponse {
+    pub node_id: u32,
+    pub partition: u32,
+
+    /// Whether the batch followed on from the follower's log
+    pub matched: bool,
+
+    /// If matched, the last sequence known to match the leader's log;
+    /// otherwise the follower's last sequence, after dropping entries that
+    /// conflict with the leader's
+    pub applied_sequence: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PartitionReplicationStatus {
+    pub partition: u32,
+    pub leader: Option<u32>,
+    /// Last sequence committed (leader) or applied (follower) on this node
+    pub sequence: u64,
+    /// Sequence each follower reported applied, when this node leads
+    pub follower_acks: HashMap<u32, u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplicationStatus {
+    pub node_id: u32,
+    pub ack_mode: AckMode,
+    pub partitions: Vec<PartitionReplicationStatus>,
+}
+
+#[derive(Default)]
+struct PartitionLog {
+    sequence: u64,
+    entries: VecDeque<WALEntry>,
+
+    /// Durable log file, open for appending. Rewritten with the retained
+    /// entries before it is reopened.
+    file: Option<File>,
+
+    /// Entries written to the file
+    file_entries: usize,
+
+    /// Epoch of the entry preceding the oldest retained one, if known
+    base_term: Option<u64>,
+}
+
+impl PartitionLog {
+    fn push(&mut self, entry: WALEntry, retained: usize) {
+        self.sequence = entry.sequence;
+        self.entries.push_back(entry);
+        while self.entries.len() > retained {
+            self.base_term = self.entries.pop_front().map(|e| e.term);
+        }
+    }
+
+    /// Sequence of the oldest retained entry
+    fn oldest(&self) -> u64 {
+        self.entries.front().map(|e| e.sequence).unwrap_or(self.sequence + 1)
+    }
+
+    /// Epoch of the entry at `sequence`; 0 before the first entry, None if
+    /// not held or no longer retained
+    fn term_at(&self, sequence: u64) -> Option<u64> {
+        let oldest = self.oldest();
+        match sequence {
+            0 => Some(0),
+            s if s > self.sequence => None,
+            s if s + 1 == oldest => self.base_term,
+            s => self.entries.get(s.checked_sub(oldest)? as usize).map(|e| e.term),
+        }
+    }
+}
+
+/// Writes between the leadership check and the log append
+#[derive(Default)]
+struct WriteState {
+    in_flight: HashMap<u32, usize>,
+    /// Partitions refusing new writes ahead of a handoff
+    draining: HashSet<u32>,
+}
+
+/// Per-partition replication log shared by the store and the shipper
+pub struct ReplicationLog {
+    config: ReplicationConfig,
+    registry: Arc<NodeRegistry>,
+    partitions: RwLock<HashMap<u32, PartitionLog>>,
+
+    /// Directory of the durable partition logs; in memory only when unset
+    log_dir: Option<PathBuf>,
+
+    /// (follower, partition) -> sequence the follower last reported applied
+    acks: RwLock<HashMap<(u32, u32), u64>>,
+
+    /// Serializes follower apply so entries land in sequence order
+    apply_lock: Mutex<()>,
+
+    writes: Mutex<WriteState>,
+
+    /// Wakes the shipper when the leader appends
+    appended: Notify,
+
+    /// Wakes ack and read waiters when a sequence advances
+    progress: Notify,
+}
+
+impl ReplicationLog {
+    /// A log kept in memory only; it starts over at sequence 0 on restart
+    pub fn new(config: ReplicationConfig, registry: Arc<NodeRegistry>) -> Self {
+        Self {
+            config,
+            registry,
+            partitions: RwLock::new(HashMap::new()),
+            log_dir: None,
+            acks: RwLock::new(HashMap::new()),
+            apply_lock: Mutex::new(()),
+            writes: Mutex::new(WriteState::default()),
+            appended: Notify::new(),
+            progress: Notify::new(),
+        }
+    }
+
+    /// A durable log kept in `dir`, restoring the sequences and retained
+    /// entries of every partition written before
+    pub fn open<P: AsRef<Path>>(config: ReplicationConfig, registry: Arc<NodeRegistry>, dir: P) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        let io_err = |e: std::io::Error| {
+            AllSourceError::StorageError(format!("Failed to open replication log {}: {}", dir.display(), e))
+        };
+        std::fs::create_dir_all(&dir).map_err(io_err)?;
+
+        let mut partitions = HashMap::new();
+        for file in std::fs::read_dir(&dir).map_err(io_err)? {
+            let path = file.map_err(io_err)?.path();
+            let Some(partition) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix("partition-"))
+                .and_then(|name| name.strip_suffix(".log"))
+                .and_then(|id| id.parse::<u32>().ok())
+            else {
+                continue;
+            };
+
+            let entries = read_entries(&path)?;
+            let mut log = PartitionLog {
+                file_entries: entries.len(),
+                ..Default::default()
+            };
+            for entry in entries {
+                if !entry.verify() {
+                    return Err(AllSourceError::StorageError(format!(
+                        "Checksum mismatch in {} at sequence {}",
+                        path.display(),
+                        entry.sequence
+                    )));
+                }
+                if entry.sequence > log.sequence {
+                    log.push(entry, config.retained_entries);
+                }
+            }
+            tracing::info!("📜 Partition {} replication log restored at sequence {}", partition, log.sequence);
+            partitions.insert(partition, log);
+        }
+
+        let mut log = Self::new(config, registry);
+        log.partitions = RwLock::new(partitions);
+        log.log_dir = Some(dir);
+        Ok(log)
+    }
+
+    pub fn config(&self) -> &ReplicationConfig {
+        &self.config
+    }
+
+    pub fn registry(&self) -> &Arc<NodeRegistry> {
+        &self.registry
+    }
+
+    /// Partition an entity belongs to
+    pub fn partition_for(&self, entity_id: &str) -> u32 {
+        PartitionKey::from_entity_id_with_count(entity_id, self.registry.partition_count()).partition_id()
+    }
+
+    pub fn is_leader(&self, partition: u32) -> bool {
+        self.registry.node_for_partition(partition) == Some(self.config.node_id)
+    }
+
+    /// Followers of the partition's current leader, plus the target of a
+    /// pending handoff
+    pub fn followers(&self, partition: u32) -> Vec<Node> {
+        let mut followers: Vec<Node> = self
+            .registry
+            .replicas_for_partition(partition, self.config.replication_factor)
+            .into_iter()
+            .skip(1)
+            .collect();
+        if let Some(target) = self.registry.handoff_target(partition) {
+            if !followers.iter().any(|f| f.id == target) {
+                followers.extend(self.registry.get_node(target));
+            }
+        }
+        followers
+    }
+
+    /// Partition of the entity, if this node leads it
+    pub fn check_leader(&self, entity_id: &str) -> Result<u32> {
+        let partition = self.partition_for(entity_id);
+        match self.registry.node_for_partition(partition) {
+            Some(leader) if leader == self.config.node_id => Ok(partition),
+            Some(leader) => Err(AllSourceError::NotLeader(format!(
+                "partition {} is led by node {}",
+                partition, leader
+            ))),
+            None => Err(AllSourceError::NotLeader(format!(
+                "partition {} has no healthy leader",
+                partition
+            ))),
+        }
+    }
+
+    /// Start a write to the entity's partition. Fails if another node leads
+    /// it or it is draining for a handoff; otherwise the write counts as in
+    /// flight until the returned guard appends or is dropped.
+    pub(crate) fn begin_write(&self, entity_id: &str) -> Result<PartitionWrite<'_>> {
+        let partition = self.check_leader(entity_id)?;
+
+        let mut writes = self.writes.lock();
+        if writes.draining.contains(&partition) {
+            return Err(AllSourceError::ReplicationError(format!(
+                "Partition {} is being handed off; retry shortly",
+                partition
+            )));
+        }
+        *writes.in_flight.entry(partition).or_default() += 1;
+        Ok(PartitionWrite { log: self, partition })
+    }
+
+    /// Refuse new writes to a partition ahead of a handoff
+    pub fn begin_drain(&self, partition: u32) {
+        self.writes.lock().draining.insert(partition);
+    }
+
+    pub fn end_drain(&self, partition: u32) {
+        self.writes.lock().draining.remove(&partition);
+    }
+
+    /// Wait until no writes to the partition are in flight. Returns false on timeout.
+    pub async fn wait_drained(&self, partition: u32, timeout: Duration) -> bool {
+        self.wait_until(timeout, || {
+            self.writes.lock().in_flight.get(&partition).copied().unwrap_or(0) == 0
+        })
+        .await
+    }
+
+    /// Wait until a follower has applied `sequence`. Returns false on timeout.
+    pub async fn wait_for_follower(
+        &self,
+        follower: u32,
+        partition: u32,
+        sequence: u64,
+        timeout: Duration,
+    ) -> bool {
+        self.wait_until(timeout, || self.acked(follower, partition) >= sequence)
+            .await
+    }
+
+    /// Last sequence committed or applied for a partition on this node
+    pub fn sequence(&self, partition: u32) -> u64 {
+        self.partitions.read().get(&partition).map(|log| log.sequence).unwrap_or(0)
+    }
+
+    /// Sequence a follower last reported applied
+    pub fn acked(&self, follower: u32, partition: u32) -> u64 {
+        self.acks.read().get(&(follower, partition)).copied().unwrap_or(0)
+    }
+
+    /// Epoch of the partition's entry at `sequence`, if held
+    fn term_at(&self, partition: u32, sequence: u64) -> Option<u64> {
+        match self.partitions.read().get(&partition) {
+            Some(log) => log.term_at(sequence),
+            None => (sequence == 0).then_some(0),
+        }
+    }
+
+    /// Append an event the leader has committed
+    fn append(&self, partition: u32, event: Event) -> Result<ReplicationToken> {
+        let term = self.registry.leader_epoch(partition);
+        let mut partitions = self.partitions.write();
+        let log = partitions.entry(partition).or_default();
+        let mut entry = WALEntry::new(log.sequence + 1, event);
+        entry.term = term;
+        let sequence = entry.sequence;
+        self.persist(partition, log, &entry)?;
+        log.push(entry, self.config.retained_entries);
+        drop(partitions);
+
+        self.appended.notify_one();
+        self.progress.notify_waiters();
+        Ok(ReplicationToken { partition, sequence })
+    }
+
+    /// Write an entry to the partition's log file, if the log is durable.
+    /// The file is first rewritten with just the retained entries when it
+    /// is not open yet (e.g. after a restart or a failed write) or has
+    /// grown to twice the retained size.
+    fn persist(&self, partition: u32, log: &mut PartitionLog, entry: &WALEntry) -> Result<()> {
+        let Some(path) = self.log_path(partition) else {
+            return Ok(());
+        };
+
+        if log.file.is_none() || log.file_entries >= 2 * self.config.retained_entries.max(1) {
+            rewrite_log(&path, log)?;
+        }
+
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        let file = log.file.as_mut().expect("log file opened above");
+        if let Err(e) = file.write_all(&line).and_then(|_| file.sync_data()) {
+            // The file may end in a partial line; rewrite it on the next entry
+            log.file = None;
+            return Err(write_err(&path, e));
+        }
+        log.file_entries += 1;
+        Ok(())
+    }
+
+    fn log_path(&self, partition: u32) -> Option<PathBuf> {
+        self.log_dir
+            .as_ref()
+            .map(|dir| dir.join(format!("partition-{}.log", partition)))
+    }
+
+    /// Apply a batch from the partition leader, committing new entries
+    /// through `commit`. Entries of this log that conflict with the
+    /// leader's are dropped, after `retract` removes their events.
+    ///
+    /// Returns whether the batch followed on from this log, and the last
+    /// sequence known to match the leader's (if it did) or this log's last
+    /// sequence (if not).
+    pub(crate) fn apply_batch(
+        &self,
+        request: AppendEntriesRequest,
+        commit: impl Fn(Event) -> Result<()>,
+        retract: impl Fn(&[WALEntry]) -> Result<()>,
+    ) -> Result<(bool, u64)> {
+        let partition = request.partition;
+        if let Some(entry) = request.entries.iter().find(|entry| !entry.verify()) {
+            return Err(AllSourceError::ReplicationError(format!(
+                "Checksum mismatch for partition {} sequence {}",
+                partition, entry.sequence
+            )));
+        }
+
+        let _guard = self.apply_lock.lock();
+
+        // The batch must follow on from an entry held with the same epoch
+        match self.term_at(partition, request.prev_sequence) {
+            Some(term) if term == request.prev_term => {}
+            Some(_) => {
+                self.truncate(partition, request.prev_sequence, &retract)?;
+                return Ok((false, self.sequence(partition)));
+            }
+            None if request.prev_sequence > self.sequence(partition) => {
+                return Ok((false, self.sequence(partition)));
+            }
+            None => {
+                return Err(AllSourceError::ReplicationError(format!(
+                    "Partition {} sequence {} is no longer retained; this node must be reseeded",
+                    partition, request.prev_sequence
+                )));
+            }
+        }
+
+        let mut matched = request.prev_sequence;
+        for entry in request.entries {
+            if entry.sequence != matched + 1 {
+                return Err(AllSourceError::ReplicationError(format!(
+                    "Batch for partition {} skips from sequence {} to {}",
+                    partition, matched, entry.sequence
+                )));
+            }
+            matched = entry.sequence;
+            match self.term_at(partition, entry.sequence) {
+                Some(term) if term == entry.term => continue,
+                Some(_) => self.truncate(partition, entry.sequence, &retract)?,
+                None => {}
+            }
+            self.apply(partition, entry, &commit)?;
+        }
+
+        // Entries past the end of the leader's log cannot be in it
+        if matched == request.leader_sequence && self.sequence(partition) > matched {
+            self.truncate(partition, matched + 1, &retract)?;
+        }
+
+        Ok((true, matched))
+    }
+
+    /// Drop the partition's entries from `from` on, after `retract` has
+    /// removed their events
+    fn truncate(&self, partition: u32, from: u64, retract: impl Fn(&[WALEntry]) -> Result<()>) -> Result<()> {
+        let removed: Vec<WALEntry> = {
+            let partitions = self.partitions.read();
+            let Some(log) = partitions.get(&partition).filter(|log| from <= log.sequence) else {
+                return Ok(());
+            };
+            if from < log.oldest() {
+                return Err(AllSourceError::ReplicationError(format!(
+                    "Partition {} diverged from its leader before sequence {}, the oldest retained; \
+                     this node must be reseeded",
+                    partition,
+                    log.oldest()
+                )));
+            }
+            log.entries.iter().filter(|e| e.sequence >= from).cloned().collect()
+        };
+
+        tracing::warn!(
+            "✂️  Dropping {} entries of partition {} from sequence {} that conflict with the leader's log",
+            removed.len(),
+            partition,
+            from
+        );
+        retract(&removed)?;
+
+        let mut partitions = self.partitions.write();
+        let log = partitions.entry(partition).or_default();
+        log.entries.retain(|e| e.sequence < from);
+        log.sequence = from - 1;
+        if let Some(path) = self.log_path(partition) {
+            rewrite_log(&path, log)?;
+        }
+        drop(partitions);
+        self.progress.notify_waiters();
+        Ok(())
+    }
+
+    /// Apply an entry from the partition leader, committing it through
+    /// `commit`. Returns false for entries already held or out of order.
+    /// The caller holds the apply lock.
+    fn apply(
+        &self,
+        partition: u32,
+        entry: WALEntry,
+        commit: impl FnOnce(Event) -> Result<()>,
+    ) -> Result<bool> {
+        if entry.sequence != self.sequence(partition) + 1 {
+            return Ok(false);
+        }
+
+        commit(entry.event.clone())?;
+        let mut partitions = self.partitions.write();
+        let log = partitions.entry(partition).or_default();
+        // Committed events are skipped on redelivery, so a failure here is
+        // retried from the same sequence
+        self.persist(partition, log, &entry)?;
+        log.push(entry, self.config.retained_entries);
+        drop(partitions);
+        self.progress.notify_waiters();
+        Ok(true)
+    }
+
+    /// The epoch of the entry at `after`, and up to `max` entries following it
+    fn entries_after(&self, partition: u32, after: u64, max: usize) -> Result<(u64, Vec<WALEntry>)> {
+        let partitions = self.partitions.read();
+        let Some(log) = partitions.get(&partition).filter(|log| after < log.sequence) else {
+            let term = partitions.get(&partition).and_then(|log| log.term_at(after));
+            return Ok((term.unwrap_or_default(), Vec::new()));
+        };
+
+        let oldest = log.oldest();
+        let Some(term) = log.term_at(after).filter(|_| after + 1 >= oldest) else {
+            return Err(AllSourceError::ReplicationError(format!(
+                "Partition {} sequence {} is no longer retained (oldest {})",
+                partition,
+                after + 1,
+                oldest
+            )));
+        };
+
+        let entries = log
+            .entries
+            .iter()
+            .skip((after + 1 - oldest) as usize)
+            .take(max)
+            .cloned()
+            .collect();
+        Ok((term, entries))
+    }
+
+    /// Record that a follower's log matches this node's through `sequence`
+    fn record_ack(&self, follower: u32, partition: u32, sequence: u64) {
+        let sequence = sequence.min(self.sequence(partition));
+        self.acks.write().insert((follower, partition), sequence);
+        self.progress.notify_waiters();
+    }
+
+    /// Forget a follower's acknowledgement once its log no longer matches
+    fn forget_ack(&self, follower: u32, partition: u32) {
+        self.acks.write().remove(&(follower, partition));
+    }
+
+    /// Whether a follower is known to hold the partition's whole log
+    fn caught_up(&self, follower: u32, partition: u32) -> bool {
+        self.acks
+            .read()
+            .get(&(follower, partition))
+            .is_some_and(|&acked| acked >= self.sequence(partition))
+    }
+
+    /// Wait until the write is acknowledged as `ack_mode` requires
+    pub async fn wait_for_acks(&self, token: ReplicationToken) -> Result<()> {
+        let AckMode::Sync { min_acks } = self.config.ack_mode else {
+            return Ok(());
+        };
+
+        let followers = self.followers(token.partition);
+        let required = min_acks.min(followers.len());
+        let acknowledged = || {
+            followers
+                .iter()
+                .filter(|f| self.acked(f.id, token.partition) >= token.sequence)
+                .count()
+                >= required
+        };
+
+        if self.wait_until(self.config.ack_timeout, acknowledged).await {
+            Ok(())
+        } else {
+            Err(AllSourceError::ReplicationError(format!(
+                "Write {} not acknowledged by {} follower(s) within {:?}",
+                token, required, self.config.ack_timeout
+            )))
+        }
+    }
+
+    /// Wait until this node holds the token's sequence. Returns false on timeout.
+    pub async fn wait_for_sequence(&self, token: ReplicationToken, timeout: Duration) -> bool {
+        self.wait_until(timeout, || self.sequence(token.partition) >= token.sequence)
+            .await
+    }
+
+    async fn wait_until(&self, timeout: Duration, condition: impl Fn() -> bool) -> bool {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            // Registered before checking, so a wakeup in between is not lost
+            let notified = self.progress.notified();
+            if condition() {
+                return true;
+            }
+            if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                return condition();
+            }
+        }
+    }
+
+    /// Sequences for every partition this node leads or holds data for
+    pub fn status(&self) -> ReplicationStatus {
+        let partitions = self.partitions.read();
+        let acks = self.acks.read();
+
+        let partitions = (0..self.registry.partition_count())
+            .filter(|p| partitions.contains_key(p) || self.is_leader(*p))
+            .map(|partition| {
+                let leader = self.registry.node_for_partition(partition);
+                let follower_acks = if leader == Some(self.config.node_id) {
+                    self.followers(partition)
+                        .into_iter()
+                        .map(|f| (f.id, acks.get(&(f.id, partition)).copied().unwrap_or(0)))
+                        .collect()
+                } else {
+                    HashMap::new()
+                };
+                PartitionReplicationStatus {
+                    partition,
+                    leader,
+                    sequence: partitions.get(&partition).map(|log| log.sequence).unwrap_or(0),
+                    follower_acks,
+                }
+            })
+            .collect();
+
+        ReplicationStatus {
+            node_id: self.config.node_id,
+            ack_mode: self.config.ack_mode,
+            partitions,
+        }
+    }
+}
+
+/// An in-flight write to a partition this node leads
+pub(crate) struct PartitionWrite<'a> {
+    log: &'a ReplicationLog,
+    partition: u32,
+}
+
+impl PartitionWrite<'_> {
+    /// Append the committed event and finish the write
+    pub(crate) fn append(self, event: Event) -> Result<ReplicationToken> {
+        self.log.append(self.partition, event)
+    }
+}
+
+impl Drop for PartitionWrite<'_> {
+    fn drop(&mut self) {
+        let mut writes = self.log.writes.lock();
+        if let Some(count) = writes.in_flight.get_mut(&self.partition) {
+            *count -= 1;
+        }
+        drop(writes);
+        self.log.progress.notify_waiters();
+    }
+}
+
+fn write_err(path: &Path, e: std::io::Error) -> AllSourceError {
+    AllSourceError::StorageError(format!("Failed to write replication log {}: {}", path.display(), e))
+}
+
+/// Rewrite a partition's log file with just the retained entries and
+/// reopen it for appending
+fn rewrite_log(path: &Path, log: &mut PartitionLog) -> Result<()> {
+    log.file = None;
+    write_atomically(path, |file| {
+        for retained in &log.entries {
+            serde_json::to_writer(&mut *file, retained)?;
+            file.write_all(b"\n")?;
+        }
+        Ok(())
+    })?;
+    log.file_entries = log.entries.len();
+    log.file = Some(OpenOptions::new().append(true).open(path).map_err(|e| write_err(path, e))?);
+    Ok(())
+}
+
+/// Read the entries of a partition log file. A partial last line, left by
+/// a crash during a write, is dropped.
+fn read_entries(path: &Path) -> Result<Vec<WALEntry>> {
+    let read_err = |e: std::io::Error| {
+        AllSourceError::StorageError(format!("Failed to read replication log {}: {}", path.display(), e))
+    };
+
+    let file = File::open(path).map_err(read_err)?;
+    let lines = BufReader::new(file)
+        .lines()
+        .collect::<std::io::Result<Vec<_>>>()
+        .map_err(read_err)?;
+    let last = lines.len().saturating_sub(1);
+
+    let mut entries = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(line) {
+            Ok(entry) => entries.push(entry),
+            Err(e) if i == last => {
+                tracing::warn!("⚠️  Dropping partial entry at the end of {}: {}", path.display(), e);
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(entries)
+}
+
+/// Ships partition logs to followers and applies entries from leaders
+pub struct ReplicationManager {
+    log: Arc<ReplicationLog>,
+    store: Arc<EventStore>,
+    client: reqwest::Client,
+
+    /// (follower, partition) pairs with a push in progress
+    in_flight: Mutex<HashSet<(u32, u32)>>,
+}
+
+impl ReplicationManager {
+    pub fn new(log: Arc<ReplicationLog>, store: Arc<EventStore>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(log.config().ack_timeout)
+            .build()
+            .unwrap_or_default();
+        Self {
+            log,
+            store,
+            client,
+            in_flight: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub fn log(&self) -> &Arc<ReplicationLog> {
+        &self.log
+    }
+
+    /// Apply a batch from a partition leader
+    pub fn handle_append(&self, request: AppendEntriesRequest) -> Result<AppendEntriesResponse> {
+        let registry = self.log.registry();
+        if registry.node_for_partition(request.partition) != Some(request.leader_id) {
+            return Err(AllSourceError::NotLeader(format!(
+                "node {} does not lead partition {}",
+                request.leader_id, request.partition
+            )));
+        }
+        if request.term < registry.leader_epoch(request.partition) {
+            return Err(AllSourceError::NotLeader(format!(
+                "epoch {} of partition {} is over",
+                request.term, request.partition
+            )));
+        }
+
+        let partition = request.partition;
+        let (matched, applied_sequence) = self.log.apply_batch(
+            request,
+            |event| self.store.commit_replicated(event),
+            |entries| {
+                self.store
+                    .retract_replicated(&entries.iter().map(|entry| entry.event.id).collect())
+            },
+        )?;
+
+        Ok(AppendEntriesResponse {
+            node_id: self.log.config().node_id,
+            partition,
+            matched,
+            applied_sequence,
+        })
+    }
+
+    /// Push one partition to one follower until it is caught up
+    async fn sync_follower(&self, follower: &Node, partition: u32) -> Result<()> {
+        let config = self.log.config();
+        let mut after = self.log.acked(follower.id, partition);
+        let mut probed = false;
+        loop {
+            let leader_sequence = self.log.sequence(partition);
+            let (prev_term, entries) = match self.log.entries_after(partition, after, config.batch_size) {
+                Ok(batch) => batch,
+                // Our record of the follower may be stale (e.g. this node
+                // just took the partition over); ask whether it matches the
+                // end of our log
+                Err(_) if !probed => {
+                    probed = true;
+                    after = leader_sequence;
+                    self.log.entries_after(partition, after, 0)?
+                }
+                Err(e) => return Err(e),
+            };
+            let sent = entries.len() as u64;
+
+            let request = AppendEntriesRequest {
+                leader_id: config.node_id,
+                term: self.log.registry().leader_epoch(partition),
+                partition,
+                prev_sequence: after,
+                prev_term,
+                entries,
+                leader_sequence,
+            };
+            let mut builder = self
+                .client
+                .post(format!("http://{}/api/v1/cluster/replication/append", follower.address))
+                .json(&request);
+            if let Some(secret) = &config.cluster_secret {
+                builder = builder.header(CLUSTER_SECRET_HEADER, secret);
+            }
+
+            let response = builder.send().await.map_err(|e| {
+                AllSourceError::ReplicationError(format!("Append to node {} failed: {}", follower.id, e))
+            })?;
+            if !response.status().is_success() {
+                return Err(AllSourceError::ReplicationError(format!(
+                    "Node {} rejected append for partition {}: {}",
+                    follower.id,
+                    partition,
+                    response.status()
+                )));
+            }
+            let response: AppendEntriesResponse = response.json().await.map_err(|e| {
+                AllSourceError::ReplicationError(format!("Invalid append response from node {}: {}", follower.id, e))
+            })?;
+
+            if !response.matched {
+                // The follower is behind or had to drop conflicting entries;
+                // back up to where it may match
+                self.log.forget_ack(follower.id, partition);
+                if after == 0 {
+                    return Err(AllSourceError::ReplicationError(format!(
+                        "Node {} refused partition {} from its start",
+                        follower.id, partition
+                    )));
+                }
+                after = response.applied_sequence.min(after - 1);
+                continue;
+            }
+
+            self.log.record_ack(follower.id, partition, response.applied_sequence);
+            if response.applied_sequence >= leader_sequence {
+                return Ok(());
+            }
+            if response.applied_sequence < after + sent {
+                return Err(AllSourceError::ReplicationError(format!(
+                    "Node {} made no progress on partition {} at sequence {}",
+                    follower.id, partition, response.applied_sequence
+                )));
+            }
+            after = response.applied_sequence;
+        }
+    }
+
+    /// Start a push to every follower that is behind on a partition this
+    /// node leads. Pushes run independently so a slow follower does not
+    /// hold up the others.
+    pub fn sync_all(self: &Arc<Self>) {
+        for partition in 0..self.log.registry().partition_count() {
+            if !self.log.is_leader(partition) {
+                continue;
+            }
+            for follower in self.log.followers(partition) {
+                if self.log.caught_up(follower.id, partition)
+                    || !self.in_flight.lock().insert((follower.id, partition))
+                {
+                    continue;
+                }
+
+                let manager = Arc::clone(self);
+                tokio::spawn(async move {
+                    let result = manager.sync_follower(&follower, partition).await;
+                    manager.in_flight.lock().remove(&(follower.id, partition));
+                    match result {
+                        // Entries appended after the last round went unshipped
+                        Ok(()) if !manager.log.caught_up(follower.id, partition) => manager.sync_all(),
+                        Ok(()) => {}
+                        Err(e) => tracing::warn!(
+                            "⚠️  Replication of partition {} to node {} failed: {}",
+                            partition,
+                            follower.id,
+                            e
+                        ),
+                    }
+                });
+            }
+        }
+    }
+
+    /// Ship on every append, and retry lagging followers every `sync_interval`
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.log.config().sync_interval);
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {}
+                    _ = self.log.appended.notified() => {}
+                }
+                self.sync_all();
+            }
+        })
+    }
+
+    /// Node-to-node replication endpoints
+    pub fn router<S>(self: &Arc<Self>) -> Router<S> {
+        Router::new()
+            .route("/api/v1/cluster/replication/append", post(append_entries_handler))
+            .route("/api/v1/cluster/replication/status", get(replication_status_handler))
+            .with_state(Arc::clone(self))
+    }
+
+    fn authorize(&self, headers: &HeaderMap) -> Result<()> {
+        check_cluster_secret(self.log.config().cluster_secret.as_deref(), headers)
+    }
+}
+
+/// Check the shared secret on a node-to-node request, if one is configured
+pub(crate) fn check_cluster_secret(secret: Option<&str>, headers: &HeaderMap) -> Result<()> {
+    let Some(secret) = secret else {
+        return Ok(());
+    };
+    let provided = headers
+        .get(CLUSTER_SECRET_HEADER)
+        .map(|v| v.as_bytes())
+        .unwrap_or_default();
+
+    // Constant-time comparison
+    let matches = provided.len() == secret.len()
+        && provided
+            .iter()
+            .zip(secret.as_bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0;
+    if matches {
+        Ok(())
+    } else {
+        Err(AllSourceError::PermissionDenied("Invalid cluster secret".to_string()))
+    }
+}
+
+/// POST /api/v1/cluster/replication/append
+async fn append_entries_handler(
+    State(manager): State<Arc<ReplicationManager>>,
+    headers: HeaderMap,
+    Json(request): Json<AppendEntriesRequest>,
+) -> Result<Json<AppendEntriesResponse>> {
+    manager.authorize(&headers)?;
+    Ok(Json(manager.handle_append(request)?))
+}
+
+/// GET /api/v1/cluster/replication/status
+async fn replication_status_handler(
+    State(manager): State<Arc<ReplicationManager>>,
+    headers: HeaderMap,
+) -> Result<Json<ReplicationStatus>> {
+    manager.authorize(&headers)?;
+    Ok(Json(manager.log.status()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::{read_your_writes_middleware, ReplicationState};
+    use crate::test_support::event;
+    use serde_json::json;
+    use tokio::net::TcpListener;
+
+    const SECRET: &str = "cluster-test-secret";
+
+    struct TestNode {
+        store: Arc<EventStore>,
+        log: Arc<ReplicationLog>,
+        address: String,
+    }
+
+    fn registry(addresses: &[String]) -> Arc<NodeRegistry> {
+        let registry = Arc::new(NodeRegistry::new(8));
+        for (id, address) in addresses.iter().enumerate() {
+            registry.register_node(Node {
+                id: id as u32,
+                address: address.clone(),
+                healthy: true,
+                assigned_partitions: vec![],
+            });
+        }
+        registry
+    }
+
+    /// Start in-process nodes on localhost, each serving the replication
+    /// endpoints plus event ingest and entity state reads
+    async fn start_cluster(count: usize, ack_mode: AckMode) -> Vec<TestNode> {
+        let mut listeners = Vec::new();
+        for _ in 0..count {
+            listeners.push(TcpListener::bind("127.0.0.1:0").await.unwrap());
+        }
+        let addresses: Vec<String> = listeners
+            .iter()
+            .map(|l| l.local_addr().unwrap().to_string())
+            .collect();
+
+        let mut nodes = Vec::new();
+        for (id, listener) in listeners.into_iter().enumerate() {
+            let log = Arc::new(ReplicationLog::new(
+                ReplicationConfig {
+                    node_id: id as u32,
+                    ack_mode,
+                    read_wait_timeout: Duration::from_millis(300),
+                    sync_interval: Duration::from_millis(50),
+                    cluster_secret: Some(SECRET.to_string()),
+                    ..Default::default()
+                },
+                registry(&addresses),
+            ));
+            let store = Arc::new(EventStore::new().with_replication(log.clone()));
+            let manager = Arc::new(ReplicationManager::new(log.clone(), store.clone()));
+            manager.clone().spawn();
+
+            let app = Router::new()
+                .route("/api/v1/events", post(crate::api::ingest_event))
+                .route("/api/v1/entities/:entity_id/state", get(crate::api::get_entity_state))
+                .with_state(store.clone())
+                .layer(axum::middleware::from_fn_with_state(
+                    ReplicationState { log: Some(log.clone()) },
+                    read_your_writes_middleware,
+                ))
+                .merge(manager.router());
+            tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+            nodes.push(TestNode {
+                store,
+                log,
+                address: addresses[id].clone(),
+            });
+        }
+        nodes
+    }
+
+    /// An entity whose partition is led by `node`
+    fn entity_led_by(log: &ReplicationLog, node: u32) -> String {
+        (0..)
+            .map(|i| format!("user-{}", i))
+            .find(|entity| log.registry().node_for_partition(log.partition_for(entity)) == Some(node))
+            .unwrap()
+    }
+
+    async fn ingest(client: &reqwest::Client, node: &TestNode, entity_id: &str) -> reqwest::Response {
+        client
+            .post(format!("http://{}/api/v1/events", node.address))
+            .json(&json!({
+                "event_type": "user.created",
+                "entity_id": entity_id,
+                "payload": {"name": entity_id},
+            }))
+            .send()
+            .await
+            .unwrap()
+    }
+
+    /// A batch from node 0, the partition's leader, following on from the
+    /// entry before `entries` (or the end of its log)
+    fn request(log: &ReplicationLog, partition: u32, entries: &[WALEntry], leader_sequence: u64) -> AppendEntriesRequest {
+        let prev_sequence = entries.first().map(|e| e.sequence - 1).unwrap_or(leader_sequence);
+        AppendEntriesRequest {
+            leader_id: 0,
+            term: log.registry().leader_epoch(partition),
+            partition,
+            prev_sequence,
+            prev_term: 0,
+            entries: entries.to_vec(),
+            leader_sequence,
+        }
+    }
+
+    fn entry(sequence: u64, term: u64, entity_id: &str) -> WALEntry {
+        let mut entry = WALEntry::new(
+            sequence,
+            event(entity_id, "user.created", json!({"name": entity_id})),
+        );
+        entry.term = term;
+        entry
+    }
+
+    #[test]
+    fn test_replication_token_round_trip() {
+        let token = ReplicationToken { partition: 3, sequence: 42 };
+        assert_eq!(token.to_string(), "3:42");
+        assert_eq!("3:42".parse::<ReplicationToken>().unwrap(), token);
+        assert!("3".parse::<ReplicationToken>().is_err());
+        assert!("a:1".parse::<ReplicationToken>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_follower_apply_is_ordered_and_idempotent() {
+        let addresses = vec!["127.0.0.1:1".to_string(), "127.0.0.1:2".to_string()];
+        let log = Arc::new(ReplicationLog::new(
+            ReplicationConfig {
+                node_id: 1,
+                ..Default::default()
+            },
+            registry(&addresses),
+        ));
+        let store = Arc::new(EventStore::new().with_replication(log.clone()));
+        let manager = ReplicationManager::new(log.clone(), store.clone());
+
+        let entity = entity_led_by(&log, 0);
+        let partition = log.partition_for(&entity);
+        let entries: Vec<WALEntry> = (1..=3)
+            .map(|seq| WALEntry::new(seq, event(&entity, "user.created", json!({"name": &entity}))))
+            .collect();
+        let append = |entries: &[WALEntry]| request(&log, partition, entries, 3);
+
+        // Writes for another node's partition are refused
+        assert!(matches!(
+            store.ingest(event(&entity, "user.created", json!({"name": &entity}))),
+            Err(AllSourceError::NotLeader(_))
+        ));
+
+        // A gap is not applied
+        let response = manager.handle_append(append(&entries[1..])).unwrap();
+        assert!(!response.matched);
+        assert_eq!(response.applied_sequence, 0);
+
+        assert_eq!(manager.handle_append(append(&entries[..2])).unwrap().applied_sequence, 2);
+        // Redelivery is skipped
+        assert_eq!(manager.handle_append(append(&entries)).unwrap().applied_sequence, 3);
+        assert_eq!(store.stats().total_events, 3);
+
+        // Only the partition leader may append
+        let mut request = append(&entries);
+        request.leader_id = 1;
+        assert!(matches!(manager.handle_append(request), Err(AllSourceError::NotLeader(_))));
+
+        let mut tampered =
+            WALEntry::new(4, event(&entity, "user.created", json!({"name": &entity})));
+        tampered.sequence = 5;
+        assert!(manager.handle_append(append(&[tampered])).is_err());
+        assert_eq!(log.sequence(partition), 3);
+    }
+
+    #[test]
+    fn test_diverged_follower_drops_conflicting_entries() {
+        let addresses = vec!["127.0.0.1:1".to_string(), "127.0.0.1:2".to_string()];
+        let log = Arc::new(ReplicationLog::new(
+            ReplicationConfig {
+                node_id: 1,
+                ..Default::default()
+            },
+            registry(&addresses),
+        ));
+        let store = Arc::new(EventStore::new().with_replication(log.clone()));
+        let manager = ReplicationManager::new(log.clone(), store.clone());
+        let entity = entity_led_by(&log, 0);
+        let partition = log.partition_for(&entity);
+
+        // Node 1 led the partition in epoch 1 and wrote three entries, of
+        // which only the first reached node 0, which leads it in epoch 2
+        let old: Vec<WALEntry> = (1..=3).map(|seq| entry(seq, 1, &entity)).collect();
+        for entry in &old {
+            assert!(log.apply(partition, entry.clone(), |event| store.commit_replicated(event)).unwrap());
+        }
+        let new = vec![old[0].clone(), entry(2, 2, &entity)];
+
+        // Probing the end of the leader's log finds the conflict at 2
+        let mut probe = request(&log, partition, &[], 2);
+        probe.prev_term = 2;
+        let response = manager.handle_append(probe).unwrap();
+        assert!(!response.matched);
+        assert_eq!(response.applied_sequence, 1);
+        assert_eq!(store.stats().total_events, 1);
+
+        // Catching up from there replaces the conflicting entries
+        let mut catch_up = request(&log, partition, &new[1..], 2);
+        catch_up.prev_term = 1;
+        let response = manager.handle_append(catch_up).unwrap();
+        assert!(response.matched);
+        assert_eq!(response.applied_sequence, 2);
+        assert_eq!(log.sequence(partition), 2);
+        assert_eq!(log.term_at(partition, 2), Some(2));
+        assert!(store.events_since(0).iter().any(|e| e.id == new[1].event.id));
+        assert_eq!(store.stats().total_events, 2);
+
+        // A longer diverged log is cut back to the leader's
+        assert!(log.apply(partition, entry(3, 1, &entity), |event| store.commit_replicated(event)).unwrap());
+        let mut probe = request(&log, partition, &[], 2);
+        probe.prev_term = 2;
+        let response = manager.handle_append(probe).unwrap();
+        assert!(response.matched);
+        assert_eq!(response.applied_sequence, 2);
+        assert_eq!(log.sequence(partition), 2);
+        assert_eq!(store.stats().total_events, 2);
+
+        // Acks never exceed the leader's own log, and stale epochs are refused
+        log.record_ack(0, partition, 10);
+        assert_eq!(log.acked(0, partition), 2);
+        let mut stale = request(&log, partition, &[], 2);
+        stale.term = log.registry().leader_epoch(partition) - 1;
+        assert!(matches!(manager.handle_append(stale), Err(AllSourceError::NotLeader(_))));
+    }
+
+    #[test]
+    fn test_leader_log_retention() {
+        let addresses = vec!["127.0.0.1:1".to_string()];
+        let log = ReplicationLog::new(
+            ReplicationConfig {
+                retained_entries: 2,
+                ..Default::default()
+            },
+            registry(&addresses),
+        );
+
+        for _ in 0..3 {
+            log.append(0, event("user-1", "user.created", json!({"name": "user-1"}))).unwrap();
+        }
+        assert_eq!(log.sequence(0), 3);
+        assert!(log.entries_after(0, 0, 10).is_err());
+        let (_, entries) = log.entries_after(0, 1, 10).unwrap();
+        assert_eq!(entries.iter().map(|e| e.sequence).collect::<Vec<_>>(), vec![2, 3]);
+        assert!(log.entries_after(0, 3, 10).unwrap().1.is_empty());
+    }
+
+    #[test]
+    fn test_partition_logs_survive_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let addresses = vec!["127.0.0.1:1".to_string(), "127.0.0.1:2".to_string()];
+        let config = |node_id| ReplicationConfig {
+            node_id,
+            retained_entries: 2,
+            ..Default::default()
+        };
+        let leader_dir = dir.path().join("leader");
+        let follower_dir = dir.path().join("follower");
+
+        let leader = ReplicationLog::open(config(0), registry(&addresses), &leader_dir).unwrap();
+        let follower = ReplicationLog::open(config(1), registry(&addresses), &follower_dir).unwrap();
+        // Enough appends to compact the file at least once
+        for _ in 0..5 {
+            let token = leader
+                .append(0, event("user-1", "user.created", json!({"name": "user-1"})))
+                .unwrap();
+            let entry = leader.entries_after(0, token.sequence - 1, 1).unwrap().1.remove(0);
+            assert!(follower.apply(0, entry, |_| Ok(())).unwrap());
+        }
+        drop(leader);
+        drop(follower);
+
+        // The restarted leader continues its sequence and still serves the
+        // retained entries for catch-up
+        let leader = ReplicationLog::open(config(0), registry(&addresses), &leader_dir).unwrap();
+        assert_eq!(leader.sequence(0), 5);
+        assert!(leader.entries_after(0, 2, 10).is_err());
+        let (_, entries) = leader.entries_after(0, 3, 10).unwrap();
+        assert_eq!(entries.iter().map(|e| e.sequence).collect::<Vec<_>>(), vec![4, 5]);
+        let token = leader
+            .append(0, event("user-1", "user.created", json!({"name": "user-1"})))
+            .unwrap();
+        assert_eq!(token.sequence, 6);
+
+        // The restarted follower resumes from what it applied
+        let follower = ReplicationLog::open(config(1), registry(&addresses), &follower_dir).unwrap();
+        assert_eq!(follower.sequence(0), 5);
+        let (_, next) = leader.entries_after(0, follower.sequence(0), 10).unwrap();
+        assert!(follower.apply(0, next[0].clone(), |_| Ok(())).unwrap());
+        assert_eq!(follower.sequence(0), 6);
+
+        // Both survive another restart after appending to a reopened file
+        drop(leader);
+        let leader = ReplicationLog::open(config(0), registry(&addresses), &leader_dir).unwrap();
+        assert_eq!(leader.sequence(0), 6);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_sync_replication_acknowledges_after_follower_applies() {
+        let nodes = start_cluster(3, AckMode::Sync { min_acks: 1 }).await;
+        let client = reqwest::Client::new();
+
+        for leader in 0..3u32 {
+            let node = &nodes[leader as usize];
+            let entity = entity_led_by(&node.log, leader);
+            let response = ingest(&client, node, &entity).await;
+            assert_eq!(response.status(), 200);
+            let token: ReplicationToken = response.headers()[REPLICATION_TOKEN_HEADER]
+                .to_str()
+                .unwrap()
+                .parse()
+                .unwrap();
+
+            // Already applied on the follower when the write returned
+            let followers = node.log.followers(token.partition);
+            assert_eq!(followers.len(), 1);
+            let follower = &nodes[followers[0].id as usize];
+            assert_eq!(follower.log.sequence(token.partition), token.sequence);
+            assert_eq!(follower.store.reconstruct_state(&entity, None).unwrap()["current_state"]["name"], entity);
+
+            // The follower refuses writes for the partition
+            assert_eq!(ingest(&client, follower, &entity).await.status(), 421);
+        }
+
+        let status = nodes[0].log.status();
+        assert!(status.partitions.iter().any(|p| p.sequence == 1 && p.follower_acks.values().all(|s| *s == 1)));
+
+        // Replication endpoints require the cluster secret
+        let response = client
+            .get(format!("http://{}/api/v1/cluster/replication/status", nodes[0].address))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 403);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_read_your_writes_on_follower() {
+        let nodes = start_cluster(2, AckMode::Async).await;
+        let client = reqwest::Client::new();
+
+        let leader = &nodes[0];
+        let entity = entity_led_by(&leader.log, 0);
+        let response = ingest(&client, leader, &entity).await;
+        assert_eq!(response.status(), 200);
+        let token = response.headers()[REPLICATION_TOKEN_HEADER].to_str().unwrap().to_string();
+
+        let read = |token: &str| {
+            client
+                .get(format!("http://{}/api/v1/entities/{}/state", nodes[1].address, entity))
+                .header(REPLICATION_TOKEN_HEADER, token)
+                .send()
+        };
+
+        // The follower holds the read until it has the write
+        let response = read(&token).await.unwrap();
+        assert_eq!(response.status(), 200);
+        let state: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(state["current_state"]["name"], entity);
+
+        // A write the follower never sees times out with 503
+        let partition = token.split(':').next().unwrap();
+        assert_eq!(read(&format!("{}:99", partition)).await.unwrap().status(), 503);
+        assert_eq!(read("garbage").await.unwrap().status(), 400);
+    }
+}
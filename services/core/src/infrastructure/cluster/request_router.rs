@@ -49,10 +49,15 @@ impl RequestRouter {
     /// - Returns error if no healthy node is available for the partition
     pub fn route_for_entity(&self, entity_id: &EntityId) -> Result<Node> {
         // Determine partition using consistent hashing
-        let partition_key = PartitionKey::from_entity_id(entity_id.as_str());
+        let partition_key = self.partition_key(entity_id);
         self.route_for_partition(&partition_key)
     }
 
+    /// Partition an entity belongs to
+    pub fn partition_for_entity(&self, entity_id: &EntityId) -> u32 {
+        self.partition_key(entity_id).partition_id()
+    }
+
     /// Route request for a specific partition
     ///
     /// # Returns
@@ -84,12 +89,18 @@ impl RequestRouter {
         self.registry.healthy_nodes()
     }
 
+    /// Replicas able to serve reads for an entity: the partition leader
+    /// first, then its followers
+    pub fn read_replicas_for_entity(&self, entity_id: &EntityId, replication_factor: usize) -> Vec<Node> {
+        self.registry
+            .replicas_for_partition(self.partition_for_entity(entity_id), replication_factor)
+    }
+
     /// Check if a specific node can handle the entity
     ///
     /// Useful for sticky sessions or connection pooling.
     pub fn can_node_handle_entity(&self, entity_id: &EntityId, node_id: u32) -> bool {
-        let partition_key = PartitionKey::from_entity_id(entity_id.as_str());
-        let partition_id = partition_key.partition_id();
+        let partition_id = self.partition_for_entity(entity_id);
 
         if let Some(assigned_node_id) = self.registry.node_for_partition(partition_id) {
             assigned_node_id == node_id
@@ -105,6 +116,11 @@ impl RequestRouter {
         self.registry.partition_distribution()
     }
 
+    /// Entities hash over the registry's partition count
+    fn partition_key(&self, entity_id: &EntityId) -> PartitionKey {
+        PartitionKey::from_entity_id_with_count(entity_id.as_str(), self.registry.partition_count())
+    }
+
     /// Check if routing is available
     ///
     /// Returns true if cluster is healthy and can handle requests.
@@ -0,0 +1,158 @@
+//! Auth Storage Selection
+//!
+//! Chooses where users, API keys and sessions are persisted:
+//!
+//! - `file`: `FileAuthRepository` JSON documents under `<data_dir>/auth` (default)
+//! - `memory`: `InMemoryAuthRepository`; nothing survives a restart
+//! - `rocksdb`: `RocksDBAuthRepository` (`rocksdb-storage` feature)
+//! - `postgres`: `PostgresAuthRepository` (`postgres` feature), so nodes
+//!   sharing a database share accounts and sessions
+//!
+//! Every backend implements `UserRepository`, `ApiKeyRepository` and
+//! `SessionRepository`.
+
+use crate::domain::repositories::{ApiKeyRepository, SessionRepository, UserRepository};
+use crate::error::{AllSourceError, Result};
+use crate::infrastructure::repositories::{FileAuthRepository, InMemoryAuthRepository};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Auth storage configuration
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "backend", rename_all = "lowercase")]
+pub enum AuthStorageConfig {
+    /// JSON documents in the data directory
+    #[default]
+    File,
+
+    /// In memory only
+    Memory,
+
+    /// RocksDB database at `path`; must not be the storage engine's
+    Rocksdb { path: PathBuf },
+
+    /// PostgreSQL database; migrations run when it is opened
+    Postgres {
+        url: String,
+        #[serde(default = "default_max_connections")]
+        max_connections: u32,
+    },
+}
+
+fn default_max_connections() -> u32 {
+    5
+}
+
+/// Repositories opened from an [`AuthStorageConfig`]
+pub struct AuthStores {
+    pub users: Arc<dyn UserRepository>,
+    pub api_keys: Arc<dyn ApiKeyRepository>,
+    pub sessions: Arc<dyn SessionRepository>,
+}
+
+impl AuthStores {
+    fn from_backend<R>(repository: R) -> Self
+    where
+        R: UserRepository + ApiKeyRepository + SessionRepository + 'static,
+    {
+        let repository = Arc::new(repository);
+        Self {
+            users: repository.clone(),
+            api_keys: repository.clone(),
+            sessions: repository,
+        }
+    }
+}
+
+impl AuthStorageConfig {
+    /// Short name used in logs and configuration
+    pub fn name(&self) -> &'static str {
+        match self {
+            AuthStorageConfig::File => "file",
+            AuthStorageConfig::Memory => "memory",
+            AuthStorageConfig::Rocksdb { .. } => "rocksdb",
+            AuthStorageConfig::Postgres { .. } => "postgres",
+        }
+    }
+
+    /// Open the configured repositories; `data_dir` holds the file backend
+    pub async fn open(&self, data_dir: &Path) -> Result<AuthStores> {
+        match self {
+            AuthStorageConfig::File => Ok(AuthStores::from_backend(FileAuthRepository::open(
+                data_dir.join("auth"),
+            )?)),
+            AuthStorageConfig::Memory => Ok(AuthStores::from_backend(InMemoryAuthRepository::new())),
+            #[cfg(feature = "rocksdb-storage")]
+            AuthStorageConfig::Rocksdb { path } => {
+                use crate::infrastructure::repositories::RocksDBAuthRepository;
+
+                Ok(AuthStores::from_backend(RocksDBAuthRepository::new(path)?))
+            }
+            #[cfg(not(feature = "rocksdb-storage"))]
+            AuthStorageConfig::Rocksdb { .. } => Err(AllSourceError::ValidationError(
+                "The rocksdb auth storage requires the `rocksdb-storage` feature".to_string(),
+            )),
+            #[cfg(feature = "postgres")]
+            AuthStorageConfig::Postgres {
+                url,
+                max_connections,
+            } => {
+                use crate::infrastructure::repositories::PostgresAuthRepository;
+                use sqlx::postgres::PgPoolOptions;
+
+                let pool = PgPoolOptions::new()
+                    .max_connections(*max_connections)
+                    .connect(url)
+                    .await
+                    .map_err(|e| {
+                        AllSourceError::StorageError(format!("Failed to connect to PostgreSQL: {}", e))
+                    })?;
+                let repository = PostgresAuthRepository::new(pool);
+                repository.migrate().await?;
+                Ok(AuthStores::from_backend(repository))
+            }
+            #[cfg(not(feature = "postgres"))]
+            AuthStorageConfig::Postgres { .. } => Err(AllSourceError::ValidationError(
+                "The postgres auth storage requires the `postgres` feature".to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{Role, User};
+
+    #[tokio::test]
+    async fn test_file_storage_keeps_users_across_opens() {
+        let dir = tempfile::tempdir().unwrap();
+        let user = User::new(
+            "alice".to_string(),
+            "alice@example.com".to_string(),
+            "password123",
+            Role::Developer,
+            "default".to_string(),
+        )
+        .unwrap();
+
+        let stores = AuthStorageConfig::File.open(dir.path()).await.unwrap();
+        stores.users.save(&user).await.unwrap();
+        assert!(dir.path().join("auth").exists());
+
+        let stores = AuthStorageConfig::File.open(dir.path()).await.unwrap();
+        let users = stores.users.find_all().await.unwrap();
+        assert_eq!(users.len(), 1);
+        assert_eq!(users[0].username, "alice");
+    }
+
+    #[cfg(not(feature = "rocksdb-storage"))]
+    #[tokio::test]
+    async fn test_rocksdb_storage_requires_feature() {
+        let config = AuthStorageConfig::Rocksdb {
+            path: PathBuf::from("/tmp/auth"),
+        };
+        assert!(config.open(Path::new(".")).await.is_err());
+    }
+}
@@ -1,7 +1,17 @@
 pub mod storage_integrity;
 pub mod performance;
 pub mod lock_free;
+pub mod storage_engine;
+pub mod auth_storage;
+pub mod object_store;
+pub mod sequence_journal;
+pub mod staged_restore;
+pub mod s3_object_store;
 
 pub use storage_integrity::{StorageIntegrity, IntegrityCheckResult};
 pub use performance::{BatchWriter, PerformanceMetrics, MemoryPool};
 pub use lock_free::{LockFreeEventQueue, LockFreeMetrics, MetricsSnapshot};
+pub use storage_engine::StorageEngineConfig;
+pub use auth_storage::{AuthStorageConfig, AuthStores};
+pub use object_store::{LocalObjectStore, ObjectMeta, ObjectStore, ObjectStoreConfig, RetentionPolicy};
+pub use s3_object_store::{S3ObjectStore, S3Settings};
@@ -0,0 +1,603 @@
+//! Object Storage
+//!
+//! Blob storage for data that lives outside the node's data directory,
+//! such as backups and cold Parquet segments:
+//!
+//! - `local`: a directory on the filesystem (also suits NFS mounts)
+//! - `s3`: any S3-compatible service (AWS S3, MinIO, Ceph RGW, ...), see
+//!   `S3ObjectStore`
+//!
+//! Keys are relative, `/`-separated paths. Objects carry the SHA-256
+//! checksum the backend keeps for them, base64 encoded the way S3 reports
+//! it, and `RetentionPolicy` expires objects by age while always keeping
+//! the newest ones.
+
+use crate::error::{AllSourceError, Result};
+use crate::infrastructure::persistence::s3_object_store::{S3ObjectStore, S3Settings};
+use async_trait::async_trait;
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::future::Future;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+/// An object as reported by the backend
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ObjectMeta {
+    pub key: String,
+    pub size: u64,
+    pub last_modified: DateTime<Utc>,
+    /// Base64 SHA-256 of the content; multipart S3 uploads report a
+    /// checksum of the part checksums suffixed with `-<parts>`
+    pub checksum: Option<String>,
+}
+
+/// Blob storage backend
+#[async_trait]
+pub trait ObjectStore: Send + Sync {
+    /// Short backend name used in logs ("local", "s3")
+    fn name(&self) -> &'static str;
+
+    /// Store `data` under `key`, replacing any existing object
+    async fn put(&self, key: &str, data: Vec<u8>) -> Result<ObjectMeta>;
+
+    /// Store the contents of a local file under `key`
+    async fn put_file(&self, key: &str, path: &Path) -> Result<ObjectMeta> {
+        let data = fs::read(path).map_err(|e| {
+            AllSourceError::StorageError(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+        self.put(key, data).await
+    }
+
+    /// Read an object; a missing object is an error
+    async fn get(&self, key: &str) -> Result<Vec<u8>>;
+
+    /// Download an object into a local file
+    async fn get_to_file(&self, key: &str, path: &Path) -> Result<()> {
+        let data = self.get(key).await?;
+        write_atomically(path, |file| {
+            use std::io::Write;
+            file.write_all(&data)
+        })
+    }
+
+    /// Object metadata, or `None` if there is no object under `key`
+    async fn head(&self, key: &str) -> Result<Option<ObjectMeta>>;
+
+    /// Objects whose key starts with `prefix`, sorted by key
+    async fn list(&self, prefix: &str) -> Result<Vec<ObjectMeta>>;
+
+    /// Delete an object; deleting a missing object succeeds
+    async fn delete(&self, key: &str) -> Result<()>;
+}
+
+/// Which object store to use
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "backend", rename_all = "lowercase")]
+pub enum ObjectStoreConfig {
+    /// Directory on the local filesystem
+    Local { path: PathBuf },
+
+    /// S3-compatible bucket
+    S3(S3Settings),
+}
+
+impl ObjectStoreConfig {
+    /// Open the configured backend
+    pub fn open(&self) -> Result<Arc<dyn ObjectStore>> {
+        match self {
+            ObjectStoreConfig::Local { path } => Ok(Arc::new(LocalObjectStore::new(path)?)),
+            ObjectStoreConfig::S3(settings) => Ok(Arc::new(S3ObjectStore::new(settings.clone())?)),
+        }
+    }
+
+    /// Where objects go, for logs
+    pub fn describe(&self) -> String {
+        match self {
+            ObjectStoreConfig::Local { path } => format!("local:{}", path.display()),
+            ObjectStoreConfig::S3(settings) => {
+                format!("s3://{}/{}", settings.bucket, settings.prefix.trim_matches('/'))
+            }
+        }
+    }
+}
+
+/// When objects expire
+///
+/// An object expires once it is older than `max_age_secs`, unless it is
+/// one of the `keep_last` newest objects. With only `keep_last` set,
+/// everything but the newest `keep_last` objects expires; with neither
+/// set, nothing does.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    #[serde(default)]
+    pub max_age_secs: Option<u64>,
+    #[serde(default)]
+    pub keep_last: Option<usize>,
+}
+
+impl RetentionPolicy {
+    pub fn is_enabled(&self) -> bool {
+        self.max_age_secs.is_some() || self.keep_last.is_some()
+    }
+
+    /// Whether the object at `rank` (0 = newest) created at `created_at`
+    /// has expired
+    pub fn expires(&self, rank: usize, created_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        if !self.is_enabled() || self.keep_last.is_some_and(|keep| rank < keep) {
+            return false;
+        }
+
+        match self.max_age_secs {
+            Some(max_age) => (now - created_at).num_seconds() > max_age as i64,
+            None => true,
+        }
+    }
+}
+
+/// The objects under `prefix` that `policy` expires, newest first
+pub async fn expired_objects(
+    store: &dyn ObjectStore,
+    prefix: &str,
+    policy: &RetentionPolicy,
+) -> Result<Vec<ObjectMeta>> {
+    if !policy.is_enabled() {
+        return Ok(Vec::new());
+    }
+
+    let mut objects = store.list(prefix).await?;
+    objects.sort_by_key(|o| std::cmp::Reverse(o.last_modified));
+
+    let now = Utc::now();
+    Ok(objects
+        .into_iter()
+        .enumerate()
+        .filter(|(rank, object)| policy.expires(*rank, object.last_modified, now))
+        .map(|(_, object)| object)
+        .collect())
+}
+
+/// Delete the objects under `prefix` that `policy` expires
+///
+/// Returns the deleted keys.
+pub async fn apply_retention(
+    store: &dyn ObjectStore,
+    prefix: &str,
+    policy: &RetentionPolicy,
+) -> Result<Vec<String>> {
+    let mut deleted = Vec::new();
+    for object in expired_objects(store, prefix, policy).await? {
+        store.delete(&object.key).await?;
+        deleted.push(object.key);
+    }
+
+    if !deleted.is_empty() {
+        tracing::info!(
+            "🧹 Retention removed {} object(s) under '{}' from {} storage",
+            deleted.len(),
+            prefix,
+            store.name()
+        );
+    }
+
+    Ok(deleted)
+}
+
+/// Wait for an object store, KMS or storage engine call from synchronous code
+///
+/// Runs on the current runtime when called inside a multi-threaded one,
+/// handing the worker over to blocking work first. Elsewhere the call gets
+/// a short-lived runtime of its own on a scoped thread, because HTTP
+/// backends and the Postgres engine need a Tokio reactor to make progress.
+pub(crate) fn block_on<F>(future: F) -> F::Output
+where
+    F: Future + Send,
+    F::Output: Send,
+{
+    use tokio::runtime::{Builder, Handle, RuntimeFlavor};
+
+    match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(|| handle.block_on(future))
+        }
+        _ => std::thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    Builder::new_current_thread()
+                        .enable_all()
+                        .build()
+                        .expect("Failed to start object store runtime")
+                        .block_on(future)
+                })
+                .join()
+                .expect("Object store call panicked")
+        }),
+    }
+}
+
+/// Base64 SHA-256 of `data`
+pub(crate) fn sha256_base64(data: &[u8]) -> String {
+    general_purpose::STANDARD.encode(Sha256::digest(data))
+}
+
+/// Write a file through a temporary sibling so readers never see it half
+/// written
+pub(crate) fn write_atomically(
+    path: &Path,
+    write: impl FnOnce(&mut File) -> std::io::Result<()>,
+) -> Result<()> {
+    let io_error = |e: std::io::Error| {
+        AllSourceError::StorageError(format!("Failed to write {}: {}", path.display(), e))
+    };
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(io_error)?;
+    }
+
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(PARTIAL_SUFFIX);
+    let temp_path = path.with_file_name(temp_name);
+
+    let result = File::create(&temp_path)
+        .and_then(|mut file| write(&mut file).and_then(|_| file.sync_all()))
+        .and_then(|_| fs::rename(&temp_path, path));
+    if let Err(e) = result {
+        let _ = fs::remove_file(&temp_path);
+        return Err(io_error(e));
+    }
+
+    Ok(())
+}
+
+/// Suffix of files still being written
+const PARTIAL_SUFFIX: &str = ".partial";
+
+/// Object store backed by a local directory
+pub struct LocalObjectStore {
+    root: PathBuf,
+}
+
+impl LocalObjectStore {
+    pub fn new(root: impl Into<PathBuf>) -> Result<Self> {
+        let root = root.into();
+        fs::create_dir_all(&root).map_err(|e| {
+            AllSourceError::StorageError(format!(
+                "Failed to create object store directory {}: {}",
+                root.display(),
+                e
+            ))
+        })?;
+
+        Ok(Self { root })
+    }
+
+    /// Filesystem path of `key`; keys may not leave the root directory
+    fn path(&self, key: &str) -> Result<PathBuf> {
+        let relative = Path::new(key);
+        let valid = !key.is_empty()
+            && !key.ends_with(PARTIAL_SUFFIX)
+            && relative
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)));
+
+        if !valid {
+            return Err(AllSourceError::ValidationError(format!(
+                "Invalid object key: {}",
+                key
+            )));
+        }
+
+        Ok(self.root.join(relative))
+    }
+
+    fn meta(&self, key: &str, path: &Path) -> Result<Option<ObjectMeta>> {
+        let metadata = match fs::metadata(path) {
+            Ok(metadata) if metadata.is_file() => metadata,
+            Ok(_) => return Ok(None),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(AllSourceError::StorageError(e.to_string())),
+        };
+
+        let last_modified = metadata
+            .modified()
+            .map(DateTime::<Utc>::from)
+            .unwrap_or_else(|_| Utc::now());
+
+        Ok(Some(ObjectMeta {
+            key: key.to_string(),
+            size: metadata.len(),
+            last_modified,
+            checksum: None,
+        }))
+    }
+
+    fn checksum(path: &Path) -> Result<String> {
+        let mut file = File::open(path).map_err(|e| AllSourceError::StorageError(e.to_string()))?;
+        let mut hasher = Sha256::new();
+        let mut buffer = [0; 64 * 1024];
+
+        loop {
+            let count = file
+                .read(&mut buffer)
+                .map_err(|e| AllSourceError::StorageError(e.to_string()))?;
+            if count == 0 {
+                break;
+            }
+            hasher.update(&buffer[..count]);
+        }
+
+        Ok(general_purpose::STANDARD.encode(hasher.finalize()))
+    }
+
+    fn collect(&self, dir: &Path, prefix: &str, objects: &mut Vec<ObjectMeta>) -> Result<()> {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(AllSourceError::StorageError(e.to_string())),
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                self.collect(&path, prefix, objects)?;
+                continue;
+            }
+
+            let Ok(relative) = path.strip_prefix(&self.root) else {
+                continue;
+            };
+            let key = relative
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+
+            if key.starts_with(prefix) && !key.ends_with(PARTIAL_SUFFIX) {
+                if let Some(meta) = self.meta(&key, &path)? {
+                    objects.push(meta);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ObjectStore for LocalObjectStore {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    async fn put(&self, key: &str, data: Vec<u8>) -> Result<ObjectMeta> {
+        let path = self.path(key)?;
+        write_atomically(&path, |file| {
+            use std::io::Write;
+            file.write_all(&data)
+        })?;
+
+        Ok(ObjectMeta {
+            key: key.to_string(),
+            size: data.len() as u64,
+            last_modified: Utc::now(),
+            checksum: Some(sha256_base64(&data)),
+        })
+    }
+
+    async fn put_file(&self, key: &str, source: &Path) -> Result<ObjectMeta> {
+        let path = self.path(key)?;
+        write_atomically(&path, |file| {
+            std::io::copy(&mut File::open(source)?, file).map(|_| ())
+        })?;
+
+        let mut meta = self.meta(key, &path)?.ok_or_else(|| {
+            AllSourceError::StorageError(format!("Object disappeared while writing: {}", key))
+        })?;
+        meta.checksum = Some(Self::checksum(&path)?);
+        Ok(meta)
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>> {
+        match fs::read(self.path(key)?) {
+            Ok(data) => Ok(data),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(
+                AllSourceError::StorageError(format!("Object not found: {}", key)),
+            ),
+            Err(e) => Err(AllSourceError::StorageError(e.to_string())),
+        }
+    }
+
+    async fn get_to_file(&self, key: &str, target: &Path) -> Result<()> {
+        let path = self.path(key)?;
+        if !path.is_file() {
+            return Err(AllSourceError::StorageError(format!("Object not found: {}", key)));
+        }
+
+        write_atomically(target, |file| {
+            std::io::copy(&mut File::open(&path)?, file).map(|_| ())
+        })
+    }
+
+    async fn head(&self, key: &str) -> Result<Option<ObjectMeta>> {
+        let path = self.path(key)?;
+        let Some(mut meta) = self.meta(key, &path)? else {
+            return Ok(None);
+        };
+        meta.checksum = Some(Self::checksum(&path)?);
+        Ok(Some(meta))
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<ObjectMeta>> {
+        // Only walk the directory the prefix points into
+        let dir = match prefix.rfind('/') {
+            Some(end) => self.path(&prefix[..end])?,
+            None => self.root.clone(),
+        };
+
+        let mut objects = Vec::new();
+        self.collect(&dir, prefix, &mut objects)?;
+        objects.sort_by(|a, b| a.key.cmp(&b.key));
+
+        Ok(objects)
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        match fs::remove_file(self.path(key)?) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(AllSourceError::StorageError(e.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_local_store_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let store = LocalObjectStore::new(dir.path()).unwrap();
+
+        let meta = store.put("backups/a.json", b"hello".to_vec()).await.unwrap();
+        assert_eq!(meta.size, 5);
+        assert_eq!(meta.checksum, Some(sha256_base64(b"hello")));
+        assert_eq!(store.get("backups/a.json").await.unwrap(), b"hello");
+
+        let source = dir.path().join("source.bin");
+        fs::write(&source, vec![7u8; 1000]).unwrap();
+        let meta = store.put_file("segments/x/b.parquet", &source).await.unwrap();
+        assert_eq!(meta.checksum, Some(sha256_base64(&[7u8; 1000])));
+
+        let head = store.head("segments/x/b.parquet").await.unwrap().unwrap();
+        assert_eq!(head.size, 1000);
+        assert_eq!(head.checksum, meta.checksum);
+        assert!(store.head("segments/missing").await.unwrap().is_none());
+
+        let target = dir.path().join("restored/b.parquet");
+        store.get_to_file("segments/x/b.parquet", &target).await.unwrap();
+        assert_eq!(fs::read(&target).unwrap(), vec![7u8; 1000]);
+
+        let keys: Vec<String> = store.list("segments/").await.unwrap().into_iter().map(|o| o.key).collect();
+        assert_eq!(keys, vec!["segments/x/b.parquet"]);
+        let keys: Vec<String> = store.list("back").await.unwrap().into_iter().map(|o| o.key).collect();
+        assert_eq!(keys, vec!["backups/a.json"]);
+
+        store.delete("backups/a.json").await.unwrap();
+        store.delete("backups/a.json").await.unwrap();
+        assert!(store.get("backups/a.json").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_local_store_rejects_keys_outside_root() {
+        let dir = TempDir::new().unwrap();
+        let store = LocalObjectStore::new(dir.path().join("objects")).unwrap();
+
+        for key in ["../escape", "/etc/passwd", "a/../../b", "", "x.partial"] {
+            assert!(store.put(key, b"nope".to_vec()).await.is_err(), "{}", key);
+        }
+        assert!(!dir.path().join("escape").exists());
+    }
+
+    #[test]
+    fn test_retention_policy() {
+        let now = Utc::now();
+        let old = now - Duration::days(10);
+        let recent = now - Duration::hours(1);
+
+        let by_age = RetentionPolicy {
+            max_age_secs: Some(7 * 86_400),
+            keep_last: None,
+        };
+        assert!(by_age.expires(0, old, now));
+        assert!(!by_age.expires(5, recent, now));
+
+        let by_age_keeping_two = RetentionPolicy {
+            max_age_secs: Some(7 * 86_400),
+            keep_last: Some(2),
+        };
+        assert!(!by_age_keeping_two.expires(1, old, now));
+        assert!(by_age_keeping_two.expires(2, old, now));
+
+        let by_count = RetentionPolicy {
+            max_age_secs: None,
+            keep_last: Some(2),
+        };
+        assert!(!by_count.expires(1, old, now));
+        assert!(by_count.expires(2, recent, now));
+
+        assert!(!RetentionPolicy::default().expires(100, old, now));
+    }
+
+    #[tokio::test]
+    async fn test_apply_retention_keeps_newest_objects() {
+        let dir = TempDir::new().unwrap();
+        let store = LocalObjectStore::new(dir.path()).unwrap();
+
+        for i in 0..4 {
+            store.put(&format!("segments/{}", i), vec![i]).await.unwrap();
+            let modified = std::time::SystemTime::now() - std::time::Duration::from_secs(3600 * (4 - i as u64));
+            File::options()
+                .write(true)
+                .open(dir.path().join(format!("segments/{}", i)))
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+        }
+        store.put("backups/keep", vec![1]).await.unwrap();
+
+        let policy = RetentionPolicy {
+            max_age_secs: Some(60),
+            keep_last: Some(1),
+        };
+        let mut deleted = apply_retention(&store, "segments/", &policy).await.unwrap();
+        deleted.sort();
+        assert_eq!(deleted, vec!["segments/0", "segments/1", "segments/2"]);
+
+        let remaining: Vec<String> = store.list("").await.unwrap().into_iter().map(|o| o.key).collect();
+        assert_eq!(remaining, vec!["backups/keep", "segments/3"]);
+    }
+
+    #[test]
+    fn test_object_store_config_format() {
+        let config: ObjectStoreConfig =
+            serde_json::from_str(r#"{"backend":"local","path":"/var/lib/allsource/objects"}"#).unwrap();
+        assert_eq!(
+            config,
+            ObjectStoreConfig::Local {
+                path: PathBuf::from("/var/lib/allsource/objects")
+            }
+        );
+
+        let config: ObjectStoreConfig = toml::from_str(
+            r#"
+            backend = "s3"
+            endpoint = "http://127.0.0.1:9000"
+            bucket = "allsource"
+            path_style = true
+            "#,
+        )
+        .unwrap();
+        let ObjectStoreConfig::S3(settings) = config else {
+            panic!("expected S3 settings");
+        };
+        assert_eq!(settings.bucket, "allsource");
+        assert_eq!(settings.region, "us-east-1");
+        assert_eq!(settings.max_retries, 3);
+    }
+
+    #[test]
+    fn test_block_on_outside_a_runtime() {
+        let dir = TempDir::new().unwrap();
+        let store = LocalObjectStore::new(dir.path()).unwrap();
+
+        block_on(store.put("a", b"1".to_vec())).unwrap();
+        assert_eq!(block_on(store.get("a")).unwrap(), b"1");
+    }
+}
@@ -0,0 +1,175 @@
+//! Event Sequence Journal
+//!
+//! Durable record of the sequence number the store gave each event, so the
+//! numbering backups rely on survives restarts, retention and compaction.
+//!
+//! The journal is a text file with one `<event id> <sequence>` line per
+//! committed event, appended and synced before the event is committed.
+//! Removing events rewrites it atomically with the remaining events,
+//! preceded by a `last <sequence>` line so the highest sequence handed out
+//! is kept even when that event is gone.
+
+use crate::domain::entities::Event;
+use crate::error::{AllSourceError, Result};
+use crate::infrastructure::persistence::object_store::write_atomically;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Sequences read back from the journal
+#[derive(Debug, Default)]
+pub struct RecordedSequences {
+    /// Sequence of every event recorded and not removed since
+    pub sequences: HashMap<Uuid, u64>,
+
+    /// Highest sequence ever handed out
+    pub last_sequence: u64,
+}
+
+impl RecordedSequences {
+    /// Sort loaded events by their recorded sequence. Events without one
+    /// (e.g. written before the journal existed) keep their order, last.
+    pub fn sort(&self, events: &mut [Event]) {
+        events.sort_by_key(|event| self.sequences.get(&event.id).copied().unwrap_or(u64::MAX));
+    }
+}
+
+/// Append-only journal of event sequences
+pub struct SequenceJournal {
+    path: PathBuf,
+    file: Mutex<JournalFile>,
+}
+
+struct JournalFile {
+    file: File,
+    /// A write failed part way, so the file may end in a partial line
+    torn: bool,
+}
+
+impl SequenceJournal {
+    /// Open (or create) the journal at `path`, returning the sequences
+    /// recorded so far
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<(Self, RecordedSequences)> {
+        let path = path.as_ref().to_path_buf();
+        let io_err = |e: std::io::Error| {
+            AllSourceError::StorageError(format!("Failed to open sequence journal {}: {}", path.display(), e))
+        };
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(io_err)?;
+        }
+
+        let recorded = read_sequences(&path)?;
+        let file = OpenOptions::new().create(true).append(true).open(&path).map_err(io_err)?;
+
+        Ok((
+            Self {
+                path,
+                file: Mutex::new(JournalFile { file, torn: false }),
+            },
+            recorded,
+        ))
+    }
+
+    fn write_err(&self, e: std::io::Error) -> AllSourceError {
+        AllSourceError::StorageError(format!(
+            "Failed to write sequence journal {}: {}",
+            self.path.display(),
+            e
+        ))
+    }
+
+    /// Record the sequences of newly numbered events
+    pub fn record(&self, entries: &[(Uuid, u64)]) -> Result<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        let mut journal = self.file.lock();
+        // Finish a partial line first, so it does not swallow the next one
+        let mut lines = if journal.torn { "\n".to_string() } else { String::new() };
+        for (id, sequence) in entries {
+            lines.push_str(&format!("{} {}\n", id, sequence));
+        }
+
+        let JournalFile { file, torn } = &mut *journal;
+        let result = file.write_all(lines.as_bytes()).and_then(|_| file.sync_data());
+        *torn = result.is_err();
+        result.map_err(|e| self.write_err(e))
+    }
+
+    /// Append to the file now at the journal's path, after it was replaced
+    /// on disk (see `StagedRestore`)
+    pub fn reopen(&self) -> Result<()> {
+        let mut journal = self.file.lock();
+        journal.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| self.write_err(e))?;
+        journal.torn = false;
+        Ok(())
+    }
+
+    /// Replace the journal with `entries`, e.g. after events were removed.
+    /// `last_sequence` is kept as the highest sequence handed out.
+    pub fn rewrite(&self, entries: impl IntoIterator<Item = (Uuid, u64)>, last_sequence: u64) -> Result<()> {
+        let mut lines = format!("last {}\n", last_sequence);
+        for (id, sequence) in entries {
+            lines.push_str(&format!("{} {}\n", id, sequence));
+        }
+
+        // Hold the append handle so no record lands in the old file
+        let mut journal = self.file.lock();
+        write_atomically(&self.path, |f| f.write_all(lines.as_bytes()))?;
+        journal.file = OpenOptions::new()
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| self.write_err(e))?;
+        journal.torn = false;
+        Ok(())
+    }
+}
+
+/// Read a journal (missing file = nothing recorded). Partial lines, left
+/// by a crash or a failed write, are skipped: the event they were written
+/// for was not committed.
+fn read_sequences(path: &Path) -> Result<RecordedSequences> {
+    let read_err = |e: std::io::Error| {
+        AllSourceError::StorageError(format!("Failed to read sequence journal {}: {}", path.display(), e))
+    };
+
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(RecordedSequences::default()),
+        Err(e) => return Err(read_err(e)),
+    };
+    let mut recorded = RecordedSequences::default();
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(read_err)?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let parsed = line.split_once(' ').and_then(|(key, sequence)| {
+            let sequence = sequence.trim().parse::<u64>().ok()?;
+            match key {
+                "last" => Some((None, sequence)),
+                id => Some((Some(Uuid::parse_str(id).ok()?), sequence)),
+            }
+        });
+
+        match parsed {
+            Some((id, sequence)) => {
+                if let Some(id) = id {
+                    recorded.sequences.insert(id, sequence);
+                }
+                recorded.last_sequence = recorded.last_sequence.max(sequence);
+            }
+            None => tracing::warn!("⚠️  Skipping partial line in {}", path.display()),
+        }
+    }
+    Ok(recorded)
+}
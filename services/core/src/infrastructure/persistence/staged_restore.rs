@@ -0,0 +1,307 @@
+//! Staged Store Replacement
+//!
+//! Replacing every event of the store (restoring a backup) must never lose
+//! both the old and the new events. The new WAL, Parquet files and sequence
+//! journal are first written to staging directories beside the live ones.
+//! Once they are on disk a commit marker is written, and only then are the
+//! live files swapped for the staged ones:
+//!
+//! 1. `prepare`, then write the new data under the staging paths
+//! 2. `commit`: sync the staged files and write the marker
+//! 3. `swap`: per part, remove the live files (and cold segments), then
+//!    move the staged files in; finally remove the marker
+//!
+//! Each swap step can be repeated, so `recover` at startup finishes a
+//! committed swap interrupted by a crash, and discards staging that was
+//! never committed. The store then holds either the old or the new events.
+
+use crate::error::{AllSourceError, Result};
+use crate::infrastructure::persistence::object_store::{self, write_atomically, ObjectStore};
+use crate::storage::{COLD_SEGMENT_PREFIX, SEGMENT_MANIFEST_FILE};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Suffix of the commit marker, next to the sequence journal
+const COMMIT_SUFFIX: &str = ".restore-commit";
+
+/// Left in a part's staging directory once its live files are removed
+const CLEARED_MARKER: &str = ".cleared";
+
+/// Kinds of files a restore replaces
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Part {
+    Parquet,
+    Wal,
+    Journal,
+}
+
+impl Part {
+    fn staging_name(self) -> &'static str {
+        match self {
+            Part::Parquet => "restore-staging-parquet",
+            Part::Wal => "restore-staging-wal",
+            Part::Journal => "restore-staging-journal",
+        }
+    }
+}
+
+/// Staging for one kind of file, beside its live directory
+#[derive(Debug, Clone)]
+struct StagedPart {
+    part: Part,
+    live_dir: PathBuf,
+    staging_dir: PathBuf,
+}
+
+impl StagedPart {
+    fn new(part: Part, live_dir: &Path) -> Self {
+        Self {
+            part,
+            live_dir: live_dir.to_path_buf(),
+            staging_dir: live_dir.join(part.staging_name()),
+        }
+    }
+}
+
+/// Replaces the store's files through staging; see the module docs
+pub struct StagedRestore {
+    parts: Vec<StagedPart>,
+    journal_name: String,
+    marker: PathBuf,
+    cold_storage: Option<Arc<dyn ObjectStore>>,
+}
+
+impl StagedRestore {
+    /// Staging for the Parquet files in `storage_dir`, the WAL in `wal_dir`
+    /// and the sequence journal at `journal_path`
+    pub fn new(storage_dir: Option<&Path>, wal_dir: Option<&Path>, journal_path: &Path) -> Self {
+        let journal_dir = journal_path.parent().unwrap_or(Path::new("."));
+        let mut parts = vec![StagedPart::new(Part::Journal, journal_dir)];
+        parts.extend(storage_dir.map(|dir| StagedPart::new(Part::Parquet, dir)));
+        parts.extend(wal_dir.map(|dir| StagedPart::new(Part::Wal, dir)));
+
+        let mut marker = journal_path.as_os_str().to_os_string();
+        marker.push(COMMIT_SUFFIX);
+
+        Self {
+            parts,
+            journal_name: journal_path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            marker: PathBuf::from(marker),
+            cold_storage: None,
+        }
+    }
+
+    /// Also remove the cold segments tiered from the Parquet files
+    pub fn with_cold_storage(mut self, store: Arc<dyn ObjectStore>) -> Self {
+        self.cold_storage = Some(store);
+        self
+    }
+
+    fn staging_dir(&self, part: Part) -> Option<&Path> {
+        self.parts
+            .iter()
+            .find(|p| p.part == part)
+            .map(|p| p.staging_dir.as_path())
+    }
+
+    /// Directory to write the new Parquet files to
+    pub fn parquet_dir(&self) -> Option<&Path> {
+        self.staging_dir(Part::Parquet)
+    }
+
+    /// Directory to write the new WAL to
+    pub fn wal_dir(&self) -> Option<&Path> {
+        self.staging_dir(Part::Wal)
+    }
+
+    /// Path to write the new sequence journal to
+    pub fn journal_path(&self) -> PathBuf {
+        self.staging_dir(Part::Journal)
+            .unwrap_or(Path::new("."))
+            .join(&self.journal_name)
+    }
+
+    /// Start with empty staging directories
+    pub fn prepare(&self) -> Result<()> {
+        if self.marker.exists() {
+            return Err(AllSourceError::StorageError(
+                "A committed restore has not been swapped in yet; restart to finish it".to_string(),
+            ));
+        }
+        self.discard();
+        for part in &self.parts {
+            fs::create_dir_all(&part.staging_dir).map_err(|e| io_error(&part.staging_dir, e))?;
+        }
+        Ok(())
+    }
+
+    /// Make the staged files durable, then mark them to replace the live ones
+    pub fn commit(&self) -> Result<()> {
+        for part in &self.parts {
+            for path in list_files(&part.staging_dir)? {
+                File::open(&path)
+                    .and_then(|file| file.sync_all())
+                    .map_err(|e| io_error(&path, e))?;
+            }
+        }
+        write_atomically(&self.marker, |_| Ok(()))
+    }
+
+    /// Replace the live files with the staged ones. Safe to repeat after a
+    /// failure or crash part way.
+    pub fn swap(&self) -> Result<()> {
+        for part in &self.parts {
+            if !part.staging_dir.exists() {
+                continue;
+            }
+
+            let cleared = part.staging_dir.join(CLEARED_MARKER);
+            if !cleared.exists() {
+                for path in list_files(&part.live_dir)? {
+                    if self.is_live(part.part, &path) {
+                        fs::remove_file(&path).map_err(|e| io_error(&path, e))?;
+                    }
+                }
+                if let (Part::Parquet, Some(store)) = (part.part, &self.cold_storage) {
+                    for object in object_store::block_on(store.list(COLD_SEGMENT_PREFIX))? {
+                        object_store::block_on(store.delete(&object.key))?;
+                    }
+                }
+                write_atomically(&cleared, |_| Ok(()))?;
+            }
+
+            for path in list_files(&part.staging_dir)? {
+                let Some(name) = path.file_name() else { continue };
+                if name == CLEARED_MARKER {
+                    continue;
+                }
+                let target = part.live_dir.join(name);
+                fs::rename(&path, &target).map_err(|e| io_error(&target, e))?;
+            }
+            fs::remove_dir_all(&part.staging_dir).map_err(|e| io_error(&part.staging_dir, e))?;
+        }
+
+        match fs::remove_file(&self.marker) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(io_error(&self.marker, e)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Remove the staging directories, e.g. after a failed restore
+    pub fn discard(&self) {
+        for part in &self.parts {
+            if let Err(e) = fs::remove_dir_all(&part.staging_dir) {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    tracing::warn!("Failed to remove {}: {}", part.staging_dir.display(), e);
+                }
+            }
+        }
+    }
+
+    /// Finish a committed restore interrupted by a crash, or discard an
+    /// uncommitted one. Returns whether a restore was swapped in.
+    pub fn recover(&self) -> Result<bool> {
+        if !self.marker.exists() {
+            self.discard();
+            return Ok(false);
+        }
+        tracing::warn!("♻️  Finishing an interrupted restore");
+        self.swap()?;
+        Ok(true)
+    }
+
+    /// Whether `path` in a live directory belongs to `part`
+    fn is_live(&self, part: Part, path: &Path) -> bool {
+        let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+        match part {
+            Part::Parquet => name.ends_with(".parquet") || name == SEGMENT_MANIFEST_FILE,
+            Part::Wal => name.starts_with("wal-") && name.ends_with(".log"),
+            Part::Journal => name == self.journal_name,
+        }
+    }
+}
+
+/// Files (not directories) directly in `dir`; none if it is missing
+fn list_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(io_error(dir, e)),
+    };
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry.map_err(|e| io_error(dir, e))?.path();
+        if path.is_file() {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> AllSourceError {
+    AllSourceError::StorageError(format!("Restore staging failed at {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(path: &Path, contents: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn test_uncommitted_staging_is_discarded() {
+        let dir = TempDir::new().unwrap();
+        let journal = dir.path().join("sequences.log");
+        write(&dir.path().join("events-1.parquet"), "old");
+        write(&journal, "old");
+
+        let staged = StagedRestore::new(Some(dir.path()), None, &journal);
+        staged.prepare().unwrap();
+        write(&staged.parquet_dir().unwrap().join("events-2.parquet"), "new");
+
+        // A restart before the commit keeps the old files
+        assert!(!staged.recover().unwrap());
+        assert!(dir.path().join("events-1.parquet").exists());
+        assert!(!dir.path().join("events-2.parquet").exists());
+        assert!(!dir.path().join(Part::Parquet.staging_name()).exists());
+    }
+
+    #[test]
+    fn test_committed_swap_finishes_after_interruption() {
+        let dir = TempDir::new().unwrap();
+        let wal_dir = dir.path().join("wal");
+        let journal = wal_dir.join("sequences.log");
+        write(&dir.path().join("events-1.parquet"), "old");
+        write(&wal_dir.join("wal-0000000000000000.log"), "old");
+        write(&journal, "old");
+
+        let staged = StagedRestore::new(Some(dir.path()), Some(&wal_dir), &journal);
+        staged.prepare().unwrap();
+        write(&staged.parquet_dir().unwrap().join("events-2.parquet"), "new");
+        write(&staged.wal_dir().unwrap().join("wal-0000000000000000.log"), "new");
+        write(&staged.journal_path(), "new");
+        staged.commit().unwrap();
+
+        // Interrupted after clearing the live Parquet files
+        let parquet_staging = staged.parquet_dir().unwrap();
+        fs::remove_file(dir.path().join("events-1.parquet")).unwrap();
+        write(&parquet_staging.join(CLEARED_MARKER), "");
+        assert!(staged.prepare().is_err());
+
+        assert!(staged.recover().unwrap());
+        assert!(!dir.path().join("events-1.parquet").exists());
+        assert_eq!(fs::read_to_string(dir.path().join("events-2.parquet")).unwrap(), "new");
+        assert_eq!(fs::read_to_string(wal_dir.join("wal-0000000000000000.log")).unwrap(), "new");
+        assert_eq!(fs::read_to_string(&journal).unwrap(), "new");
+        assert!(!staged.recover().unwrap());
+    }
+}
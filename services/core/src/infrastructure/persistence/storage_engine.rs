@@ -0,0 +1,332 @@
+//! Storage Engine Selection
+//!
+//! Chooses where the event store keeps its system of record:
+//!
+//! - `native`: in-memory events with optional WAL and Parquet files (default)
+//! - `memory`: an in-memory `EventStreamRepository`, mostly for tests
+//! - `rocksdb`: `RocksDBEventStreamRepository` (`rocksdb-storage` feature)
+//! - `postgres`: `PostgresEventStore` (`postgres` feature)
+//!
+//! Repository-backed engines append every event to its entity's stream
+//! with an optimistic version check, so stream versions stay gapless, and
+//! reload all streams on startup. Queries are served from the store's
+//! in-memory index, unless the engine answers them itself through
+//! `EventStreamRepository::query_events` (Postgres), so the HTTP API
+//! behaves the same on every engine.
+
+use crate::domain::repositories::EventStreamRepository;
+use crate::error::{AllSourceError, Result};
+use crate::infrastructure::repositories::InMemoryEventStreamRepository;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Storage engine configuration
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "engine", rename_all = "lowercase")]
+pub enum StorageEngineConfig {
+    /// In-memory events, persisted by the store's WAL and Parquet settings
+    #[default]
+    Native,
+
+    /// In-memory stream repository; nothing survives a restart
+    Memory,
+
+    /// RocksDB database at `path`
+    Rocksdb { path: PathBuf },
+
+    /// PostgreSQL database; migrations run when the engine is opened
+    Postgres {
+        url: String,
+        #[serde(default = "default_max_connections")]
+        max_connections: u32,
+    },
+}
+
+fn default_max_connections() -> u32 {
+    10
+}
+
+impl StorageEngineConfig {
+    /// Short name used in logs and configuration
+    pub fn name(&self) -> &'static str {
+        match self {
+            StorageEngineConfig::Native => "native",
+            StorageEngineConfig::Memory => "memory",
+            StorageEngineConfig::Rocksdb { .. } => "rocksdb",
+            StorageEngineConfig::Postgres { .. } => "postgres",
+        }
+    }
+
+    /// Open the configured repository. `Native` has none and returns `None`.
+    pub async fn open(&self) -> Result<Option<Arc<dyn EventStreamRepository>>> {
+        match self {
+            #[cfg(feature = "postgres")]
+            StorageEngineConfig::Postgres {
+                url,
+                max_connections,
+            } => {
+                use crate::infrastructure::repositories::PostgresEventStore;
+
+                let engine = PostgresEventStore::connect(url, *max_connections).await?;
+                Ok(Some(Arc::new(engine)))
+            }
+            _ => self.open_sync(),
+        }
+    }
+
+    /// Open engines that need no async setup. Postgres must be opened with
+    /// [`StorageEngineConfig::open`].
+    pub fn open_sync(&self) -> Result<Option<Arc<dyn EventStreamRepository>>> {
+        match self {
+            StorageEngineConfig::Native => Ok(None),
+            StorageEngineConfig::Memory => Ok(Some(Arc::new(InMemoryEventStreamRepository::new()))),
+            #[cfg(feature = "rocksdb-storage")]
+            StorageEngineConfig::Rocksdb { path } => {
+                use crate::infrastructure::repositories::RocksDBEventStreamRepository;
+
+                Ok(Some(Arc::new(RocksDBEventStreamRepository::new(path)?)))
+            }
+            #[cfg(not(feature = "rocksdb-storage"))]
+            StorageEngineConfig::Rocksdb { .. } => Err(AllSourceError::ValidationError(
+                "The rocksdb storage engine requires the `rocksdb-storage` feature".to_string(),
+            )),
+            #[cfg(feature = "postgres")]
+            StorageEngineConfig::Postgres { .. } => Err(AllSourceError::ValidationError(
+                "The postgres storage engine must be opened with EventStore::open".to_string(),
+            )),
+            #[cfg(not(feature = "postgres"))]
+            StorageEngineConfig::Postgres { .. } => Err(AllSourceError::ValidationError(
+                "The postgres storage engine requires the `postgres` feature".to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::dto::QueryEventsRequest;
+    use crate::domain::entities::Event;
+    use crate::domain::value_objects::EntityId;
+    use crate::retention::{RetentionConfig, RetentionRule};
+    use crate::store::{EventStore, EventStoreConfig};
+    use crate::wal::WALConfig;
+    use crate::test_support::event;
+    use serde_json::json;
+    use std::future::Future;
+    use std::time::Duration;
+
+    fn query_entity(store: &EventStore, entity_id: &str) -> Vec<Event> {
+        query_payload(store, entity_id, None)
+    }
+
+    fn query_payload(store: &EventStore, entity_id: &str, payload_contains: Option<&str>) -> Vec<Event> {
+        store
+            .query(QueryEventsRequest {
+                entity_id: Some(entity_id.to_string()),
+                event_type: None,
+                tenant_id: None,
+                as_of: None,
+                since: None,
+                until: None,
+                limit: None,
+                payload_contains: payload_contains.map(str::to_string),
+            })
+            .unwrap()
+    }
+
+    fn steps(events: &[Event]) -> Vec<u64> {
+        events.iter().map(|e| e.payload["step"].as_u64().unwrap()).collect()
+    }
+
+    /// Behaviour every engine must share. `open` returns a store over the
+    /// same underlying data each time it is called; entity IDs start with
+    /// `prefix` so shared databases can be reused between runs.
+    async fn check_conformance<F, Fut>(prefix: &str, open: F)
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = EventStore>,
+    {
+        let order = format!("{}-order-1", prefix);
+        let other = format!("{}-order-2", prefix);
+
+        let store = open().await;
+        let mut stream = store.websocket_manager().subscribe();
+        for step in 0..3 {
+            store.ingest(event(&order, "order.updated", json!({"step": step}))).unwrap();
+        }
+        store.ingest(event(&other, "order.updated", json!({"step": 0}))).unwrap();
+
+        // Ingested events reach stream subscribers in order
+        let mut streamed = Vec::new();
+        for _ in 0..4 {
+            let received = tokio::time::timeout(Duration::from_secs(1), stream.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            streamed.push(received.entity_id_str().to_string());
+        }
+        assert_eq!(streamed, vec![order.clone(), order.clone(), order.clone(), other.clone()]);
+
+        assert_eq!(steps(&query_entity(&store, &order)), vec![0, 1, 2]);
+        assert_eq!(steps(&query_entity(&store, &other)), vec![0]);
+        assert_eq!(steps(&query_payload(&store, &order, Some(r#"{"step": 1}"#))), vec![1]);
+        assert!(query_payload(&store, &order, Some(r#"{"step": 9}"#)).is_empty());
+        let state = store.reconstruct_state(&order, None).unwrap();
+        assert_eq!(state["current_state"]["step"], 2);
+        drop(stream);
+        drop(store);
+
+        // Reopening restores every event, and writes continue after them
+        let store = open().await;
+        assert_eq!(steps(&query_entity(&store, &order)), vec![0, 1, 2]);
+        assert_eq!(steps(&query_entity(&store, &other)), vec![0]);
+        store.ingest(event(&order, "order.updated", json!({"step": 3}))).unwrap();
+        assert_eq!(steps(&query_entity(&store, &order)), vec![0, 1, 2, 3]);
+        let state = store.reconstruct_state(&order, None).unwrap();
+        assert_eq!(state["current_state"]["step"], 3);
+    }
+
+    /// Repository engines number each entity's events without gaps
+    async fn check_stream_versions(engine: &dyn EventStreamRepository, prefix: &str) {
+        let order = EntityId::new(format!("{}-order-1", prefix)).unwrap();
+        let stream = engine.load_stream(&order).await.unwrap().unwrap();
+        assert_eq!(stream.current_version(), 4);
+        assert!(stream.is_gapless());
+        let versions: Vec<i64> = stream.events_from(1).iter().map(|e| e.version).collect();
+        assert_eq!(versions, vec![1, 2, 3, 4]);
+        assert!(engine.verify_gapless(&order).await.unwrap());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_native_engine_conformance() {
+        let dir = tempfile::tempdir().unwrap();
+        let wal_dir = dir.path().to_path_buf();
+        check_conformance("native", || {
+            let wal_dir = wal_dir.clone();
+            async move {
+                EventStore::open(EventStoreConfig::with_wal(wal_dir, WALConfig::default()))
+                    .await
+                    .unwrap()
+            }
+        })
+        .await;
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_memory_engine_conformance() {
+        let engine: Arc<dyn EventStreamRepository> = Arc::new(InMemoryEventStreamRepository::new());
+        check_conformance("memory", || {
+            let engine = engine.clone();
+            async move {
+                EventStore::with_engine(EventStoreConfig::default(), engine)
+                    .await
+                    .unwrap()
+            }
+        })
+        .await;
+        check_stream_versions(engine.as_ref(), "memory").await;
+    }
+
+    #[tokio::test]
+    async fn test_memory_engine_from_config_on_current_thread_runtime() {
+        let store = EventStore::open(EventStoreConfig {
+            storage_engine: StorageEngineConfig::Memory,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        store.ingest(event("order-1", "order.updated", json!({"step": 0}))).unwrap();
+        store.ingest(event("order-1", "order.updated", json!({"step": 1}))).unwrap();
+
+        let events = query_entity(&store, "order-1");
+        assert_eq!(events.iter().map(|e| e.version).collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_open_reports_invalid_configuration() {
+        let result = EventStore::open(EventStoreConfig {
+            retention: RetentionConfig {
+                rules: vec![RetentionRule::default()],
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .await;
+        assert!(matches!(result, Err(AllSourceError::ValidationError(_))));
+    }
+
+    #[test]
+    fn test_engine_directories_are_replaced_by_the_engine() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = EventStore::with_config(EventStoreConfig {
+            storage_engine: StorageEngineConfig::Memory,
+            ..EventStoreConfig::with_wal(dir.path(), WALConfig::default())
+        });
+        store.ingest(event("order-1", "order.updated", json!({"step": 0}))).unwrap();
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[cfg(not(feature = "rocksdb-storage"))]
+    #[test]
+    fn test_rocksdb_engine_requires_feature() {
+        let engine = StorageEngineConfig::Rocksdb {
+            path: PathBuf::from("./rocksdb"),
+        };
+        assert!(matches!(engine.open_sync(), Err(AllSourceError::ValidationError(_))));
+    }
+
+    #[cfg(feature = "rocksdb-storage")]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_rocksdb_engine_conformance() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = StorageEngineConfig::Rocksdb {
+            path: dir.path().join("rocksdb"),
+        };
+        check_conformance("rocksdb", || {
+            let config = config.clone();
+            async move {
+                EventStore::open(EventStoreConfig {
+                    storage_engine: config,
+                    ..Default::default()
+                })
+                .await
+                .unwrap()
+            }
+        })
+        .await;
+
+        let engine = config.open().await.unwrap().unwrap();
+        check_stream_versions(engine.as_ref(), "rocksdb").await;
+    }
+
+    /// Runs against the database at `ALLSOURCE_TEST_DATABASE_URL`, if set
+    #[cfg(feature = "postgres")]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_postgres_engine_conformance() {
+        let Ok(url) = std::env::var("ALLSOURCE_TEST_DATABASE_URL") else {
+            return;
+        };
+        let config = StorageEngineConfig::Postgres {
+            url,
+            max_connections: 5,
+        };
+        let prefix = format!("postgres-{}", uuid::Uuid::new_v4());
+        check_conformance(&prefix, || {
+            let config = config.clone();
+            async move {
+                EventStore::open(EventStoreConfig {
+                    storage_engine: config,
+                    ..Default::default()
+                })
+                .await
+                .unwrap()
+            }
+        })
+        .await;
+
+        let engine = config.open().await.unwrap().unwrap();
+        check_stream_versions(engine.as_ref(), &prefix).await;
+    }
+}
@@ -0,0 +1,242 @@
+//! File-backed Audit Event Repository
+//!
+//! Appends audit events as JSON lines to a single file and serves queries
+//! from an in-memory index rebuilt from that file on open.
+//!
+//! **Design**:
+//! - Each append is written and synced before it is acknowledged, so the
+//!   hash chain kept by `HashChainedAuditRepository` survives restarts
+//! - Queries are delegated to an `InMemoryAuditRepository`
+//! - Purges rewrite the file atomically (write to a temporary file, then rename)
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use tokio::sync::Mutex;
+
+use crate::domain::entities::{AuditEvent, AuditEventId};
+use crate::domain::repositories::{AuditEventQuery, AuditEventRepository};
+use crate::domain::value_objects::TenantId;
+use crate::error::{AllSourceError, Result};
+use crate::infrastructure::repositories::InMemoryAuditRepository;
+
+/// Audit repository persisted to an append-only JSON lines file
+pub struct FileAuditRepository {
+    path: PathBuf,
+    /// Open for appending; held across the write and the index update so
+    /// the file and the index see events in the same order
+    file: Mutex<File>,
+    index: InMemoryAuditRepository,
+}
+
+impl FileAuditRepository {
+    /// Open (or create) the audit log at `path`, loading existing events
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let io_err = |e: std::io::Error| {
+            AllSourceError::StorageError(format!("Failed to open audit log {}: {}", path.display(), e))
+        };
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(io_err)?;
+        }
+
+        let index = InMemoryAuditRepository::new();
+        for event in read_events(&path)? {
+            index.insert(event);
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(&path).map_err(io_err)?;
+
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+            index,
+        })
+    }
+
+    /// Number of stored events
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Whether the log is empty
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    fn write_err(&self, e: std::io::Error) -> AllSourceError {
+        AllSourceError::StorageError(format!("Failed to write audit log {}: {}", self.path.display(), e))
+    }
+
+    async fn write_events(&self, events: Vec<AuditEvent>) -> Result<()> {
+        let mut lines = Vec::new();
+        for event in &events {
+            serde_json::to_writer(&mut lines, event)?;
+            lines.push(b'\n');
+        }
+
+        let mut file = self.file.lock().await;
+        file.write_all(&lines).map_err(|e| self.write_err(e))?;
+        file.sync_data().map_err(|e| self.write_err(e))?;
+        for event in events {
+            self.index.insert(event);
+        }
+        Ok(())
+    }
+}
+
+/// Read every event of a JSON lines audit log (missing file = empty log)
+fn read_events(path: &Path) -> Result<Vec<AuditEvent>> {
+    let read_err = |e: std::io::Error| {
+        AllSourceError::StorageError(format!("Failed to read audit log {}: {}", path.display(), e))
+    };
+
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(read_err(e)),
+    };
+
+    let mut events = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(read_err)?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        events.push(serde_json::from_str(&line)?);
+    }
+    Ok(events)
+}
+
+#[async_trait]
+impl AuditEventRepository for FileAuditRepository {
+    async fn append(&self, event: AuditEvent) -> Result<()> {
+        self.write_events(vec![event]).await
+    }
+
+    async fn append_batch(&self, events: Vec<AuditEvent>) -> Result<()> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        self.write_events(events).await
+    }
+
+    async fn get_by_id(&self, id: &AuditEventId) -> Result<Option<AuditEvent>> {
+        self.index.get_by_id(id).await
+    }
+
+    async fn query(&self, query: AuditEventQuery) -> Result<Vec<AuditEvent>> {
+        self.index.query(query).await
+    }
+
+    async fn count(&self, query: AuditEventQuery) -> Result<usize> {
+        self.index.count(query).await
+    }
+
+    async fn get_by_tenant(&self, tenant_id: &TenantId, limit: usize, offset: usize) -> Result<Vec<AuditEvent>> {
+        self.index.get_by_tenant(tenant_id, limit, offset).await
+    }
+
+    async fn get_security_events(&self, tenant_id: &TenantId, limit: usize) -> Result<Vec<AuditEvent>> {
+        self.index.get_security_events(tenant_id, limit).await
+    }
+
+    async fn get_by_actor(
+        &self,
+        tenant_id: &TenantId,
+        actor_identifier: &str,
+        limit: usize,
+    ) -> Result<Vec<AuditEvent>> {
+        self.index.get_by_actor(tenant_id, actor_identifier, limit).await
+    }
+
+    async fn purge_old_events(&self, tenant_id: &TenantId, older_than: DateTime<Utc>) -> Result<usize> {
+        let mut file = self.file.lock().await;
+
+        let remaining: Vec<AuditEvent> = read_events(&self.path)?
+            .into_iter()
+            .filter(|event| event.tenant_id() != tenant_id || event.timestamp() >= &older_than)
+            .collect();
+
+        let tmp = self.path.with_extension("jsonl.tmp");
+        {
+            let mut out = File::create(&tmp).map_err(|e| self.write_err(e))?;
+            for event in &remaining {
+                serde_json::to_writer(&mut out, event)?;
+                out.write_all(b"\n").map_err(|e| self.write_err(e))?;
+            }
+            out.sync_all().map_err(|e| self.write_err(e))?;
+        }
+        std::fs::rename(&tmp, &self.path).map_err(|e| self.write_err(e))?;
+        *file = OpenOptions::new()
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| self.write_err(e))?;
+
+        self.index.purge_old_events(tenant_id, older_than).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::{Actor, AuditAction, AuditOutcome};
+    use tempfile::TempDir;
+
+    fn event(tenant: &TenantId, action: AuditAction) -> AuditEvent {
+        AuditEvent::new(
+            tenant.clone(),
+            action,
+            Actor::user("u1".to_string(), "alice".to_string()),
+            AuditOutcome::Success,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_events_survive_reopen() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("audit").join("audit.jsonl");
+        let tenant = TenantId::new("acme".to_string()).unwrap();
+
+        let repo = FileAuditRepository::open(&path).unwrap();
+        let first = event(&tenant, AuditAction::Login);
+        let first_id = first.id().clone();
+        repo.append(first).await.unwrap();
+        repo.append_batch(vec![event(&tenant, AuditAction::Logout), event(&tenant, AuditAction::UserCreated)])
+            .await
+            .unwrap();
+        drop(repo);
+
+        let reopened = FileAuditRepository::open(&path).unwrap();
+        assert_eq!(reopened.len(), 3);
+        assert!(reopened.get_by_id(&first_id).await.unwrap().is_some());
+        assert_eq!(reopened.count(AuditEventQuery::new(tenant)).await.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_purge_rewrites_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let acme = TenantId::new("acme".to_string()).unwrap();
+        let other = TenantId::new("other".to_string()).unwrap();
+
+        let repo = FileAuditRepository::open(&path).unwrap();
+        repo.append(event(&acme, AuditAction::Login)).await.unwrap();
+        repo.append(event(&other, AuditAction::Login)).await.unwrap();
+
+        let purged = repo
+            .purge_old_events(&acme, Utc::now() + chrono::Duration::seconds(1))
+            .await
+            .unwrap();
+        assert_eq!(purged, 1);
+        repo.append(event(&acme, AuditAction::Logout)).await.unwrap();
+        drop(repo);
+
+        let reopened = FileAuditRepository::open(&path).unwrap();
+        assert_eq!(reopened.len(), 2);
+        assert_eq!(reopened.count(AuditEventQuery::new(acme)).await.unwrap(), 1);
+    }
+}
@@ -0,0 +1,539 @@
+//! File-backed User and API Key Repository
+//!
+//! Persists accounts as JSON documents in a directory: `users.json`,
+//! `api_keys.json`, and for sessions `refresh_tokens.json` and
+//! `revocations.json`. The full set is held in memory and each mutation
+//! rewrites the affected file atomically (write to a temporary file, then
+//! rename).
+//!
+//! Account data is small and changes rarely, which makes whole-file rewrites
+//! a simple, dependency-free choice for single-node deployments. Every token
+//! refresh rewrites `refresh_tokens.json`; use the RocksDB or PostgreSQL
+//! backends when accounts or sessions change frequently.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use crate::auth::{ApiKey, ApiKeyScopes, RefreshToken, Revocation, RevocationKind, Role, User};
+use crate::domain::repositories::{ApiKeyRepository, SessionRepository, UserRepository};
+use crate::error::{AllSourceError, Result};
+
+const USERS_FILE: &str = "users.json";
+const API_KEYS_FILE: &str = "api_keys.json";
+const REFRESH_TOKENS_FILE: &str = "refresh_tokens.json";
+const REVOCATIONS_FILE: &str = "revocations.json";
+
+/// Storage form of `User`, including the password hash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct UserRecord {
+    pub id: Uuid,
+    pub username: String,
+    pub email: String,
+    pub password_hash: String,
+    pub role: Role,
+    pub tenant_id: String,
+    pub created_at: DateTime<Utc>,
+    pub active: bool,
+}
+
+impl From<&User> for UserRecord {
+    fn from(user: &User) -> Self {
+        Self {
+            id: user.id,
+            username: user.username.clone(),
+            email: user.email.clone(),
+            password_hash: user.password_hash.clone(),
+            role: user.role.clone(),
+            tenant_id: user.tenant_id.clone(),
+            created_at: user.created_at,
+            active: user.active,
+        }
+    }
+}
+
+impl From<UserRecord> for User {
+    fn from(record: UserRecord) -> Self {
+        Self {
+            id: record.id,
+            username: record.username,
+            email: record.email,
+            password_hash: record.password_hash,
+            role: record.role,
+            tenant_id: record.tenant_id,
+            created_at: record.created_at,
+            active: record.active,
+        }
+    }
+}
+
+/// Storage form of `ApiKey`, including the key hash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct ApiKeyRecord {
+    pub id: Uuid,
+    pub name: String,
+    pub tenant_id: String,
+    pub role: Role,
+    pub key_hash: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub active: bool,
+    pub last_used: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub scopes: ApiKeyScopes,
+}
+
+impl From<&ApiKey> for ApiKeyRecord {
+    fn from(key: &ApiKey) -> Self {
+        Self {
+            id: key.id,
+            name: key.name.clone(),
+            tenant_id: key.tenant_id.clone(),
+            role: key.role.clone(),
+            key_hash: key.key_hash.clone(),
+            created_at: key.created_at,
+            expires_at: key.expires_at,
+            active: key.active,
+            last_used: key.last_used,
+            scopes: key.scopes.clone(),
+        }
+    }
+}
+
+impl From<ApiKeyRecord> for ApiKey {
+    fn from(record: ApiKeyRecord) -> Self {
+        Self {
+            id: record.id,
+            name: record.name,
+            tenant_id: record.tenant_id,
+            role: record.role,
+            key_hash: record.key_hash,
+            created_at: record.created_at,
+            expires_at: record.expires_at,
+            active: record.active,
+            last_used: record.last_used,
+            scopes: record.scopes,
+        }
+    }
+}
+
+/// File-backed implementation of UserRepository, ApiKeyRepository and
+/// SessionRepository
+pub struct FileAuthRepository {
+    dir: PathBuf,
+    users: RwLock<HashMap<Uuid, UserRecord>>,
+    api_keys: RwLock<HashMap<Uuid, ApiKeyRecord>>,
+    refresh_tokens: RwLock<HashMap<String, RefreshToken>>,
+    revocations: RwLock<HashMap<(RevocationKind, String), Revocation>>,
+}
+
+impl FileAuthRepository {
+    /// Open (or create) the repository in `dir`
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir).map_err(|e| {
+            AllSourceError::StorageError(format!("Failed to create auth directory: {}", e))
+        })?;
+
+        let users: Vec<UserRecord> = read_records(&dir.join(USERS_FILE))?;
+        let api_keys: Vec<ApiKeyRecord> = read_records(&dir.join(API_KEYS_FILE))?;
+        let refresh_tokens: Vec<RefreshToken> = read_records(&dir.join(REFRESH_TOKENS_FILE))?;
+        let revocations: Vec<Revocation> = read_records(&dir.join(REVOCATIONS_FILE))?;
+
+        Ok(Self {
+            dir,
+            users: RwLock::new(users.into_iter().map(|u| (u.id, u)).collect()),
+            api_keys: RwLock::new(api_keys.into_iter().map(|k| (k.id, k)).collect()),
+            refresh_tokens: RwLock::new(
+                refresh_tokens.into_iter().map(|t| (t.token_hash.clone(), t)).collect(),
+            ),
+            revocations: RwLock::new(
+                revocations.into_iter().map(|r| ((r.kind, r.id.clone()), r)).collect(),
+            ),
+        })
+    }
+
+    /// Directory holding the account files
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn write_users(&self, users: &HashMap<Uuid, UserRecord>) -> Result<()> {
+        let mut records: Vec<&UserRecord> = users.values().collect();
+        records.sort_by_key(|r| r.created_at);
+        write_records(&self.dir.join(USERS_FILE), &records)
+    }
+
+    fn write_api_keys(&self, api_keys: &HashMap<Uuid, ApiKeyRecord>) -> Result<()> {
+        let mut records: Vec<&ApiKeyRecord> = api_keys.values().collect();
+        records.sort_by_key(|r| r.created_at);
+        write_records(&self.dir.join(API_KEYS_FILE), &records)
+    }
+
+    fn write_refresh_tokens(&self, refresh_tokens: &HashMap<String, RefreshToken>) -> Result<()> {
+        let mut records: Vec<&RefreshToken> = refresh_tokens.values().collect();
+        records.sort_by_key(|r| r.issued_at);
+        write_records(&self.dir.join(REFRESH_TOKENS_FILE), &records)
+    }
+
+    fn write_revocations(&self, revocations: &HashMap<(RevocationKind, String), Revocation>) -> Result<()> {
+        let mut records: Vec<&Revocation> = revocations.values().collect();
+        records.sort_by_key(|r| r.expires_at);
+        write_records(&self.dir.join(REVOCATIONS_FILE), &records)
+    }
+}
+
+fn read_records<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>> {
+    match std::fs::read(path) {
+        Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(AllSourceError::StorageError(format!(
+            "Failed to read {}: {}",
+            path.display(),
+            e
+        ))),
+    }
+}
+
+fn write_records<T: Serialize>(path: &Path, records: &[T]) -> Result<()> {
+    let json = serde_json::to_vec_pretty(records)?;
+    let tmp_path = path.with_extension("json.tmp");
+    std::fs::write(&tmp_path, json).map_err(|e| {
+        AllSourceError::StorageError(format!("Failed to write {}: {}", tmp_path.display(), e))
+    })?;
+    std::fs::rename(&tmp_path, path).map_err(|e| {
+        AllSourceError::StorageError(format!("Failed to replace {}: {}", path.display(), e))
+    })?;
+    Ok(())
+}
+
+fn sorted<T, K: Ord>(mut items: Vec<T>, key: impl Fn(&T) -> K) -> Vec<T> {
+    items.sort_by_key(key);
+    items
+}
+
+#[async_trait]
+impl UserRepository for FileAuthRepository {
+    async fn save(&self, user: &User) -> Result<()> {
+        let mut users = self.users.write();
+        if users.values().any(|u| u.username == user.username && u.id != user.id) {
+            return Err(AllSourceError::ValidationError(
+                "Username already exists".to_string(),
+            ));
+        }
+
+        let previous = users.insert(user.id, UserRecord::from(user));
+        if let Err(e) = self.write_users(&users) {
+            // Keep memory consistent with what is on disk
+            match previous {
+                Some(previous) => users.insert(user.id, previous),
+                None => users.remove(&user.id),
+            };
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<User>> {
+        Ok(self.users.read().get(id).cloned().map(User::from))
+    }
+
+    async fn find_by_username(&self, username: &str) -> Result<Option<User>> {
+        Ok(self
+            .users
+            .read()
+            .values()
+            .find(|u| u.username == username)
+            .cloned()
+            .map(User::from))
+    }
+
+    async fn find_all(&self) -> Result<Vec<User>> {
+        let users = self.users.read().values().cloned().map(User::from).collect();
+        Ok(sorted(users, |u: &User| u.created_at))
+    }
+
+    async fn delete(&self, id: &Uuid) -> Result<bool> {
+        let mut users = self.users.write();
+        let Some(previous) = users.remove(id) else {
+            return Ok(false);
+        };
+        if let Err(e) = self.write_users(&users) {
+            users.insert(*id, previous);
+            return Err(e);
+        }
+        Ok(true)
+    }
+}
+
+#[async_trait]
+impl ApiKeyRepository for FileAuthRepository {
+    async fn save(&self, api_key: &ApiKey) -> Result<()> {
+        let mut api_keys = self.api_keys.write();
+        let previous = api_keys.insert(api_key.id, ApiKeyRecord::from(api_key));
+        if let Err(e) = self.write_api_keys(&api_keys) {
+            match previous {
+                Some(previous) => api_keys.insert(api_key.id, previous),
+                None => api_keys.remove(&api_key.id),
+            };
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<ApiKey>> {
+        Ok(self.api_keys.read().get(id).cloned().map(ApiKey::from))
+    }
+
+    async fn find_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>> {
+        Ok(self
+            .api_keys
+            .read()
+            .values()
+            .find(|k| k.key_hash == key_hash)
+            .cloned()
+            .map(ApiKey::from))
+    }
+
+    async fn find_by_tenant(&self, tenant_id: &str) -> Result<Vec<ApiKey>> {
+        let keys = self
+            .api_keys
+            .read()
+            .values()
+            .filter(|k| k.tenant_id == tenant_id)
+            .cloned()
+            .map(ApiKey::from)
+            .collect();
+        Ok(sorted(keys, |k: &ApiKey| k.created_at))
+    }
+
+    async fn find_all(&self) -> Result<Vec<ApiKey>> {
+        let keys = self.api_keys.read().values().cloned().map(ApiKey::from).collect();
+        Ok(sorted(keys, |k: &ApiKey| k.created_at))
+    }
+
+    async fn update_last_used(&self, id: &Uuid, last_used: DateTime<Utc>) -> Result<bool> {
+        let mut api_keys = self.api_keys.write();
+        let Some(record) = api_keys.get_mut(id) else {
+            return Ok(false);
+        };
+        record.last_used = Some(last_used);
+        self.write_api_keys(&api_keys)?;
+        Ok(true)
+    }
+
+    async fn delete(&self, id: &Uuid) -> Result<bool> {
+        let mut api_keys = self.api_keys.write();
+        let Some(previous) = api_keys.remove(id) else {
+            return Ok(false);
+        };
+        if let Err(e) = self.write_api_keys(&api_keys) {
+            api_keys.insert(*id, previous);
+            return Err(e);
+        }
+        Ok(true)
+    }
+}
+
+#[async_trait]
+impl SessionRepository for FileAuthRepository {
+    async fn save_refresh_token(&self, token: &RefreshToken) -> Result<()> {
+        let mut refresh_tokens = self.refresh_tokens.write();
+        let previous = refresh_tokens.insert(token.token_hash.clone(), token.clone());
+        if let Err(e) = self.write_refresh_tokens(&refresh_tokens) {
+            match previous {
+                Some(previous) => refresh_tokens.insert(token.token_hash.clone(), previous),
+                None => refresh_tokens.remove(&token.token_hash),
+            };
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    async fn find_refresh_token(&self, token_hash: &str) -> Result<Option<RefreshToken>> {
+        Ok(self.refresh_tokens.read().get(token_hash).cloned())
+    }
+
+    async fn find_refresh_tokens_by_user(&self, user_id: &Uuid) -> Result<Vec<RefreshToken>> {
+        Ok(self
+            .refresh_tokens
+            .read()
+            .values()
+            .filter(|t| &t.user_id == user_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn mark_refresh_token_used(&self, token_hash: &str, used_at: DateTime<Utc>) -> Result<bool> {
+        let mut refresh_tokens = self.refresh_tokens.write();
+        match refresh_tokens.get_mut(token_hash) {
+            Some(token) if token.used_at.is_none() => token.used_at = Some(used_at),
+            _ => return Ok(false),
+        }
+        if let Err(e) = self.write_refresh_tokens(&refresh_tokens) {
+            if let Some(token) = refresh_tokens.get_mut(token_hash) {
+                token.used_at = None;
+            }
+            return Err(e);
+        }
+        Ok(true)
+    }
+
+    async fn delete_session_refresh_tokens(&self, session_id: &str) -> Result<()> {
+        let mut refresh_tokens = self.refresh_tokens.write();
+        let previous = refresh_tokens.clone();
+        refresh_tokens.retain(|_, t| t.session_id != session_id);
+        if refresh_tokens.len() == previous.len() {
+            return Ok(());
+        }
+        if let Err(e) = self.write_refresh_tokens(&refresh_tokens) {
+            *refresh_tokens = previous;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    async fn save_revocation(&self, revocation: &Revocation) -> Result<()> {
+        let mut revocations = self.revocations.write();
+        let key = (revocation.kind, revocation.id.clone());
+        let previous = revocations.insert(key.clone(), revocation.clone());
+        if let Err(e) = self.write_revocations(&revocations) {
+            match previous {
+                Some(previous) => revocations.insert(key, previous),
+                None => revocations.remove(&key),
+            };
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    async fn find_revocations(&self, now: DateTime<Utc>) -> Result<Vec<Revocation>> {
+        Ok(self
+            .revocations
+            .read()
+            .values()
+            .filter(|r| r.expires_at > now)
+            .cloned()
+            .collect())
+    }
+
+    async fn prune(&self, now: DateTime<Utc>) -> Result<usize> {
+        let mut pruned = 0;
+
+        let mut refresh_tokens = self.refresh_tokens.write();
+        let before = refresh_tokens.len();
+        refresh_tokens.retain(|_, t| t.expires_at > now);
+        if refresh_tokens.len() < before {
+            self.write_refresh_tokens(&refresh_tokens)?;
+            pruned += before - refresh_tokens.len();
+        }
+        drop(refresh_tokens);
+
+        let mut revocations = self.revocations.write();
+        let before = revocations.len();
+        revocations.retain(|_, r| r.expires_at > now);
+        if revocations.len() < before {
+            self.write_revocations(&revocations)?;
+            pruned += before - revocations.len();
+        }
+        Ok(pruned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_accounts_survive_reopen_with_hashes() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let user = User::new(
+            "alice".to_string(),
+            "alice@example.com".to_string(),
+            "password123",
+            Role::Admin,
+            "acme".to_string(),
+        )
+        .unwrap();
+        let (api_key, plaintext) =
+            ApiKey::new("ci".to_string(), "acme".to_string(), Role::ServiceAccount, None);
+
+        {
+            let repo = FileAuthRepository::open(dir.path()).unwrap();
+            UserRepository::save(&repo, &user).await.unwrap();
+            ApiKeyRepository::save(&repo, &api_key).await.unwrap();
+            let last_used = Utc::now();
+            assert!(repo.update_last_used(&api_key.id, last_used).await.unwrap());
+        }
+
+        let repo = FileAuthRepository::open(dir.path()).unwrap();
+        let restored_user = repo.find_by_username("alice").await.unwrap().unwrap();
+        assert!(restored_user.verify_password("password123").unwrap());
+
+        let restored_key = ApiKeyRepository::find_by_id(&repo, &api_key.id).await.unwrap().unwrap();
+        assert!(restored_key.verify(&plaintext));
+        assert!(restored_key.last_used.is_some());
+
+        assert!(UserRepository::delete(&repo, &user.id).await.unwrap());
+        let repo = FileAuthRepository::open(dir.path()).unwrap();
+        assert!(UserRepository::find_all(&repo).await.unwrap().is_empty());
+        assert_eq!(ApiKeyRepository::find_all(&repo).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_sessions_survive_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = Utc::now();
+        let token = RefreshToken {
+            session_id: "s1".to_string(),
+            user_id: Uuid::new_v4(),
+            token_hash: "hash".to_string(),
+            issued_at: now,
+            expires_at: now + chrono::Duration::days(1),
+            used_at: None,
+        };
+        let revocation = Revocation {
+            kind: RevocationKind::Session,
+            id: "s0".to_string(),
+            expires_at: now + chrono::Duration::minutes(15),
+        };
+
+        {
+            let repo = FileAuthRepository::open(dir.path()).unwrap();
+            repo.save_refresh_token(&token).await.unwrap();
+            repo.save_revocation(&revocation).await.unwrap();
+            assert!(repo.mark_refresh_token_used("hash", now).await.unwrap());
+            assert!(!repo.mark_refresh_token_used("hash", now).await.unwrap());
+        }
+
+        let repo = FileAuthRepository::open(dir.path()).unwrap();
+        let restored = repo.find_refresh_token("hash").await.unwrap().unwrap();
+        assert!(restored.used_at.is_some());
+        assert_eq!(repo.find_revocations(now).await.unwrap(), vec![revocation]);
+
+        // Both expire once their access tokens have
+        assert_eq!(repo.prune(now + chrono::Duration::days(2)).await.unwrap(), 2);
+        let repo = FileAuthRepository::open(dir.path()).unwrap();
+        assert!(repo.find_refresh_token("hash").await.unwrap().is_none());
+        assert!(repo.find_revocations(now).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_username_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = FileAuthRepository::open(dir.path()).unwrap();
+        let new_user = |name: &str| {
+            User::new(name.to_string(), "x@example.com".to_string(), "pw", Role::ReadOnly, "acme".to_string())
+                .unwrap()
+        };
+
+        UserRepository::save(&repo, &new_user("bob")).await.unwrap();
+        assert!(UserRepository::save(&repo, &new_user("bob")).await.is_err());
+        assert_eq!(UserRepository::find_all(&repo).await.unwrap().len(), 1);
+    }
+}
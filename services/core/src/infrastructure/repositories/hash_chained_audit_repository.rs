@@ -0,0 +1,766 @@
+//! Hash-Chained Audit Event Repository
+//!
+//! Decorates any AuditEventRepository to make the audit log tamper-evident.
+//!
+//! **Design**:
+//! - Every appended event is linked to the tenant's previous event: it gets
+//!   the next per-tenant sequence number, the predecessor's hash and its own
+//!   SHA-256 hash (see `AuditEvent::chain`)
+//! - Every `checkpoint_interval` events a `ChainCheckpoint` event is appended,
+//!   carrying a KMS signature over the chain head. Checkpoints are regular
+//!   chained events, so they are persisted by the inner repository
+//! - `verify_chain` walks a tenant's chain and reports the first broken link
+//! - Purging removes a prefix of the chain and first appends a signed
+//!   checkpoint recording the last purged event, so a chain that no longer
+//!   starts at sequence 1 only verifies if a signed record explains the gap
+//!
+//! Appends for all tenants are serialized so that sequence numbers and
+//! previous-hash links are assigned in the same order events are stored.
+
+use async_trait::async_trait;
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::domain::entities::{Actor, AuditAction, AuditEvent, AuditEventId, AuditOutcome};
+use crate::domain::repositories::{AuditEventQuery, AuditEventRepository};
+use crate::domain::value_objects::TenantId;
+use crate::error::Result;
+use crate::security::KmsClient;
+
+/// Hash chain configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditChainConfig {
+    /// Append a signed checkpoint after every N audit events (0 = never)
+    pub checkpoint_interval: u64,
+}
+
+impl Default for AuditChainConfig {
+    fn default() -> Self {
+        Self {
+            checkpoint_interval: 1000,
+        }
+    }
+}
+
+/// KMS key used to sign chain checkpoints
+#[derive(Clone)]
+pub struct AuditChainSigner {
+    pub kms: Arc<dyn KmsClient>,
+    /// Key created with `KeyPurpose::AuditSigning`
+    pub key_id: String,
+}
+
+/// A link that failed verification
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BrokenLink {
+    /// Sequence at which the chain breaks
+    pub sequence: u64,
+    /// Offending event (None if the event is missing)
+    pub event_id: Option<String>,
+    pub reason: String,
+}
+
+/// Result of walking a tenant's audit chain
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainVerificationReport {
+    pub tenant_id: String,
+    pub valid: bool,
+    pub events_checked: usize,
+    pub checkpoints_verified: usize,
+    pub first_sequence: Option<u64>,
+    pub last_sequence: Option<u64>,
+    pub head_hash: Option<String>,
+    /// True if the chain does not start at sequence 1 (older events purged);
+    /// the chain is only valid if a signed purge checkpoint covers the gap
+    pub truncated: bool,
+    pub broken_link: Option<BrokenLink>,
+    pub verified_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+struct ChainHead {
+    sequence: u64,
+    hash: Option<String>,
+    /// Events appended since the last checkpoint
+    since_checkpoint: u64,
+}
+
+/// Audit repository decorator that maintains a per-tenant hash chain
+pub struct HashChainedAuditRepository {
+    inner: Arc<dyn AuditEventRepository>,
+    config: AuditChainConfig,
+    signer: Option<AuditChainSigner>,
+    /// tenant_id -> current chain head (loaded lazily from the inner repository)
+    heads: Mutex<HashMap<String, ChainHead>>,
+}
+
+impl HashChainedAuditRepository {
+    /// Wrap an audit repository
+    pub fn new(inner: Arc<dyn AuditEventRepository>, config: AuditChainConfig) -> Self {
+        Self {
+            inner,
+            config,
+            signer: None,
+            heads: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sign periodic checkpoints with a KMS key
+    pub fn with_signer(mut self, signer: AuditChainSigner) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Get the wrapped repository
+    pub fn inner(&self) -> Arc<dyn AuditEventRepository> {
+        Arc::clone(&self.inner)
+    }
+
+    /// Load a tenant's chain events from the inner repository, ordered by sequence
+    async fn load_chain(&self, tenant_id: &TenantId) -> Result<Vec<AuditEvent>> {
+        let mut events: Vec<AuditEvent> = self
+            .inner
+            .query(AuditEventQuery::new(tenant_id.clone()))
+            .await?
+            .into_iter()
+            .filter(|e| e.sequence().is_some())
+            .collect();
+        events.sort_by_key(|e| e.sequence());
+        Ok(events)
+    }
+
+    async fn head_for(
+        &self,
+        heads: &mut HashMap<String, ChainHead>,
+        tenant_id: &TenantId,
+    ) -> Result<ChainHead> {
+        if let Some(head) = heads.get(tenant_id.as_str()) {
+            return Ok(head.clone());
+        }
+
+        let chain = self.load_chain(tenant_id).await?;
+        let since_checkpoint = chain
+            .iter()
+            .rev()
+            .take_while(|e| e.action() != &AuditAction::ChainCheckpoint)
+            .count() as u64;
+        let head = match chain.last() {
+            Some(last) => ChainHead {
+                sequence: last.sequence().unwrap_or(0),
+                hash: last.hash().map(str::to_string),
+                since_checkpoint,
+            },
+            None => ChainHead {
+                sequence: 0,
+                hash: None,
+                since_checkpoint: 0,
+            },
+        };
+        heads.insert(tenant_id.as_str().to_string(), head.clone());
+        Ok(head)
+    }
+
+    /// Chain an event (and a checkpoint, if one is due) onto the tenant's head
+    async fn link(
+        &self,
+        heads: &mut HashMap<String, ChainHead>,
+        event: AuditEvent,
+    ) -> Result<Vec<AuditEvent>> {
+        let tenant_id = event.tenant_id().clone();
+        let head = self.head_for(heads, &tenant_id).await?;
+
+        let sequence = head.sequence + 1;
+        let event = event.chain(sequence, head.hash);
+        let mut new_head = ChainHead {
+            sequence,
+            hash: event.hash().map(str::to_string),
+            since_checkpoint: head.since_checkpoint + 1,
+        };
+        let mut linked = vec![event];
+
+        let interval = self.config.checkpoint_interval;
+        if let Some(signer) = &self.signer {
+            if interval > 0 && new_head.since_checkpoint >= interval {
+                let head_hash = new_head.hash.clone().unwrap_or_default();
+                let checkpoint = self
+                    .checkpoint_event(signer, &tenant_id, sequence, &head_hash, None)
+                    .await?
+                    .chain(sequence + 1, Some(head_hash));
+                new_head = ChainHead {
+                    sequence: sequence + 1,
+                    hash: checkpoint.hash().map(str::to_string),
+                    since_checkpoint: 0,
+                };
+                linked.push(checkpoint);
+            }
+        }
+
+        heads.insert(tenant_id.as_str().to_string(), new_head);
+        Ok(linked)
+    }
+
+    /// Build a signed checkpoint over the chain head, optionally recording
+    /// the last event (sequence and hash) of a purged prefix
+    async fn checkpoint_event(
+        &self,
+        signer: &AuditChainSigner,
+        tenant_id: &TenantId,
+        sequence: u64,
+        head_hash: &str,
+        purged: Option<(u64, &str)>,
+    ) -> Result<AuditEvent> {
+        let message = checkpoint_message(tenant_id, sequence, head_hash, purged);
+        let (signature, key_version) = signer.kms.sign(&signer.key_id, message.as_bytes()).await?;
+
+        let mut metadata = serde_json::json!({
+            "checkpoint_sequence": sequence,
+            "checkpoint_hash": head_hash,
+            "key_id": signer.key_id,
+            "key_version": key_version,
+            "signature": general_purpose::STANDARD.encode(signature),
+        });
+        if let Some((through, hash)) = purged {
+            metadata["purged_through"] = serde_json::json!(through);
+            metadata["purged_hash"] = serde_json::json!(hash);
+        }
+
+        Ok(AuditEvent::new(
+            tenant_id.clone(),
+            AuditAction::ChainCheckpoint,
+            Actor::system("audit-chain".to_string()),
+            AuditOutcome::Success,
+        )
+        .with_metadata(metadata))
+    }
+
+    /// Walk a tenant's chain and report the first broken link
+    ///
+    /// Checks sequence continuity, previous-hash links, each event's own hash
+    /// and the KMS signature of every checkpoint. A chain that does not start
+    /// at sequence 1 must be explained by a signed purge checkpoint. Events
+    /// recorded before chaining was enabled (no sequence) are ignored.
+    pub async fn verify_chain(&self, tenant_id: &TenantId) -> Result<ChainVerificationReport> {
+        let events = self.load_chain(tenant_id).await?;
+        let known_head = self.heads.lock().await.get(tenant_id.as_str()).cloned();
+
+        let mut report = ChainVerificationReport {
+            tenant_id: tenant_id.as_str().to_string(),
+            valid: true,
+            events_checked: 0,
+            checkpoints_verified: 0,
+            first_sequence: events.first().and_then(|e| e.sequence()),
+            last_sequence: events.last().and_then(|e| e.sequence()),
+            head_hash: events.last().and_then(|e| e.hash().map(str::to_string)),
+            truncated: events.first().and_then(|e| e.sequence()).is_some_and(|s| s > 1),
+            broken_link: None,
+            verified_at: Utc::now(),
+        };
+
+        let mut previous: Option<&AuditEvent> = None;
+        for event in &events {
+            report.events_checked += 1;
+            if let Some(broken) = self.check_link(previous, event).await? {
+                report.valid = false;
+                report.broken_link = Some(broken);
+                return Ok(report);
+            }
+            if event.action() == &AuditAction::ChainCheckpoint {
+                report.checkpoints_verified += 1;
+            }
+            previous = Some(event);
+        }
+
+        // Events removed from the start of the chain
+        if let Some(first) = events.first().filter(|_| report.truncated) {
+            let first_sequence = first.sequence().unwrap_or(0);
+            let covered = events.iter().rev().find_map(purge_record).is_some_and(|(through, hash)| {
+                first_sequence <= through || (first_sequence == through + 1 && first.previous_hash() == Some(hash))
+            });
+            if !covered {
+                report.valid = false;
+                report.broken_link = Some(BrokenLink {
+                    sequence: first_sequence - 1,
+                    event_id: None,
+                    reason: format!(
+                        "chain starts at sequence {} but no signed purge checkpoint covers the removed events",
+                        first_sequence
+                    ),
+                });
+                return Ok(report);
+            }
+        }
+
+        // Events removed from the end of the chain
+        if let Some(head) = known_head {
+            let last = report.last_sequence.unwrap_or(0);
+            if head.sequence > last {
+                report.valid = false;
+                report.broken_link = Some(BrokenLink {
+                    sequence: last + 1,
+                    event_id: None,
+                    reason: format!("chain ends at sequence {} but head is {}", last, head.sequence),
+                });
+            }
+        }
+
+        Ok(report)
+    }
+
+    async fn check_link(
+        &self,
+        previous: Option<&AuditEvent>,
+        event: &AuditEvent,
+    ) -> Result<Option<BrokenLink>> {
+        let sequence = event.sequence().unwrap_or(0);
+        let broken = |reason: String| {
+            Some(BrokenLink {
+                sequence,
+                event_id: Some(event.id().as_str()),
+                reason,
+            })
+        };
+
+        if let Some(prev) = previous {
+            let expected = prev.sequence().unwrap_or(0) + 1;
+            if sequence != expected {
+                return Ok(Some(BrokenLink {
+                    sequence: expected,
+                    event_id: None,
+                    reason: format!("missing event: expected sequence {}, found {}", expected, sequence),
+                }));
+            }
+            if event.previous_hash() != prev.hash() {
+                return Ok(broken("previous_hash does not match the preceding event".to_string()));
+            }
+        } else if sequence == 1 && event.previous_hash().is_some() {
+            return Ok(broken("first event must not have a previous_hash".to_string()));
+        }
+
+        if !event.verify_hash() {
+            return Ok(broken("event content does not match its hash".to_string()));
+        }
+
+        if event.action() == &AuditAction::ChainCheckpoint {
+            if let Some(reason) = self.check_checkpoint(event, previous).await? {
+                return Ok(broken(reason));
+            }
+        }
+
+        Ok(None)
+    }
+
+    async fn check_checkpoint(
+        &self,
+        event: &AuditEvent,
+        previous: Option<&AuditEvent>,
+    ) -> Result<Option<String>> {
+        let metadata = event.metadata().cloned().unwrap_or_default();
+        let (Some(sequence), Some(head_hash), Some(key_id), Some(key_version), Some(signature)) = (
+            metadata["checkpoint_sequence"].as_u64(),
+            metadata["checkpoint_hash"].as_str(),
+            metadata["key_id"].as_str(),
+            metadata["key_version"].as_u64().and_then(|v| u32::try_from(v).ok()),
+            metadata["signature"].as_str(),
+        ) else {
+            return Ok(Some("checkpoint is missing signature fields".to_string()));
+        };
+
+        if let Some(prev) = previous {
+            if prev.sequence() != Some(sequence) || prev.hash() != Some(head_hash) {
+                return Ok(Some("checkpoint does not cover the preceding event".to_string()));
+            }
+        }
+
+        let Some(signer) = &self.signer else {
+            return Ok(Some("no KMS configured to verify checkpoint signature".to_string()));
+        };
+        let Ok(signature) = general_purpose::STANDARD.decode(signature) else {
+            return Ok(Some("checkpoint signature is not valid base64".to_string()));
+        };
+
+        let message = checkpoint_message(event.tenant_id(), sequence, head_hash, purge_record(event));
+        if !signer.kms.verify(key_id, key_version, message.as_bytes(), &signature).await? {
+            return Ok(Some("checkpoint signature is invalid".to_string()));
+        }
+
+        Ok(None)
+    }
+}
+
+/// Message signed by a checkpoint
+fn checkpoint_message(tenant_id: &TenantId, sequence: u64, head_hash: &str, purged: Option<(u64, &str)>) -> String {
+    let message = format!("allsource-audit-checkpoint:{}:{}:{}", tenant_id.as_str(), sequence, head_hash);
+    match purged {
+        Some((through, hash)) => format!("{}:purged:{}:{}", message, through, hash),
+        None => message,
+    }
+}
+
+/// Last purged sequence and hash recorded by a purge checkpoint
+fn purge_record(event: &AuditEvent) -> Option<(u64, &str)> {
+    if event.action() != &AuditAction::ChainCheckpoint {
+        return None;
+    }
+    let metadata = event.metadata()?;
+    Some((metadata["purged_through"].as_u64()?, metadata["purged_hash"].as_str()?))
+}
+
+#[async_trait]
+impl AuditEventRepository for HashChainedAuditRepository {
+    async fn append(&self, event: AuditEvent) -> Result<()> {
+        let mut heads = self.heads.lock().await;
+        let tenant_key = event.tenant_id().as_str().to_string();
+        let linked = self.link(&mut heads, event).await?;
+
+        let result = if linked.len() == 1 {
+            self.inner.append(linked.into_iter().next().unwrap()).await
+        } else {
+            self.inner.append_batch(linked).await
+        };
+
+        // Reload the head from storage next time if the write failed
+        if result.is_err() {
+            heads.remove(&tenant_key);
+        }
+        result
+    }
+
+    async fn append_batch(&self, events: Vec<AuditEvent>) -> Result<()> {
+        let mut heads = self.heads.lock().await;
+        let tenants: Vec<String> = events.iter().map(|e| e.tenant_id().as_str().to_string()).collect();
+
+        let mut linked = Vec::with_capacity(events.len());
+        for event in events {
+            linked.extend(self.link(&mut heads, event).await?);
+        }
+
+        let result = self.inner.append_batch(linked).await;
+        if result.is_err() {
+            for tenant in tenants {
+                heads.remove(&tenant);
+            }
+        }
+        result
+    }
+
+    async fn get_by_id(&self, id: &AuditEventId) -> Result<Option<AuditEvent>> {
+        self.inner.get_by_id(id).await
+    }
+
+    async fn query(&self, query: AuditEventQuery) -> Result<Vec<AuditEvent>> {
+        self.inner.query(query).await
+    }
+
+    async fn count(&self, query: AuditEventQuery) -> Result<usize> {
+        self.inner.count(query).await
+    }
+
+    async fn get_by_tenant(
+        &self,
+        tenant_id: &TenantId,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<AuditEvent>> {
+        self.inner.get_by_tenant(tenant_id, limit, offset).await
+    }
+
+    async fn get_security_events(
+        &self,
+        tenant_id: &TenantId,
+        limit: usize,
+    ) -> Result<Vec<AuditEvent>> {
+        self.inner.get_security_events(tenant_id, limit).await
+    }
+
+    async fn get_by_actor(
+        &self,
+        tenant_id: &TenantId,
+        actor_identifier: &str,
+        limit: usize,
+    ) -> Result<Vec<AuditEvent>> {
+        self.inner.get_by_actor(tenant_id, actor_identifier, limit).await
+    }
+
+    async fn purge_old_events(
+        &self,
+        tenant_id: &TenantId,
+        older_than: DateTime<Utc>,
+    ) -> Result<usize> {
+        let mut heads = self.heads.lock().await;
+        let chain = self.load_chain(tenant_id).await?;
+
+        // Only a prefix of the chain goes, so the retained events still link up
+        let purged = chain.iter().take_while(|e| e.timestamp() < &older_than).count();
+        let mut retained: Vec<DateTime<Utc>> = chain[purged..].iter().map(|e| *e.timestamp()).collect();
+
+        // Record the purge in a signed checkpoint before anything is removed.
+        // Without a signer the purge goes ahead but the chain no longer verifies
+        if let (Some(signer), Some(last_purged)) = (&self.signer, purged.checked_sub(1).map(|i| &chain[i])) {
+            let head = self.head_for(&mut heads, tenant_id).await?;
+            let head_hash = head.hash.clone().unwrap_or_default();
+            let through = (last_purged.sequence().unwrap_or(0), last_purged.hash().unwrap_or_default());
+            let record = self
+                .checkpoint_event(signer, tenant_id, head.sequence, &head_hash, Some(through))
+                .await?
+                .chain(head.sequence + 1, head.hash);
+            retained.push(*record.timestamp());
+            let new_head = ChainHead {
+                sequence: head.sequence + 1,
+                hash: record.hash().map(str::to_string),
+                since_checkpoint: 0,
+            };
+            if let Err(e) = self.inner.append(record).await {
+                heads.remove(tenant_id.as_str());
+                return Err(e);
+            }
+            heads.insert(tenant_id.as_str().to_string(), new_head);
+        }
+
+        let cutoff = retained.into_iter().min().map_or(older_than, |oldest| oldest.min(older_than));
+        self.inner.purge_old_events(tenant_id, cutoff).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::repositories::InMemoryAuditRepository;
+    use crate::security::{KeyAlgorithm, KeyPurpose, KmsConfig, LocalKms};
+
+    fn tenant() -> TenantId {
+        TenantId::new("test-tenant".to_string()).unwrap()
+    }
+
+    fn login_event() -> AuditEvent {
+        AuditEvent::new(
+            tenant(),
+            AuditAction::Login,
+            Actor::user("user-1".to_string(), "alice".to_string()),
+            AuditOutcome::Success,
+        )
+    }
+
+    async fn signed_repo(
+        inner: Arc<InMemoryAuditRepository>,
+        checkpoint_interval: u64,
+    ) -> HashChainedAuditRepository {
+        let kms = Arc::new(LocalKms::new(KmsConfig::default()));
+        let key = kms
+            .create_key("audit".to_string(), KeyPurpose::AuditSigning, KeyAlgorithm::HmacSha256)
+            .await
+            .unwrap();
+
+        HashChainedAuditRepository::new(inner, AuditChainConfig { checkpoint_interval })
+            .with_signer(AuditChainSigner { kms, key_id: key.key_id })
+    }
+
+    #[tokio::test]
+    async fn test_events_are_chained_per_tenant() {
+        let inner = Arc::new(InMemoryAuditRepository::new());
+        let repo = HashChainedAuditRepository::new(inner.clone(), AuditChainConfig::default());
+
+        for _ in 0..3 {
+            repo.append(login_event()).await.unwrap();
+        }
+        let other = TenantId::new("other-tenant".to_string()).unwrap();
+        repo.append(AuditEvent::new(other.clone(), AuditAction::Login, Actor::system("t".to_string()), AuditOutcome::Success))
+            .await
+            .unwrap();
+
+        let chain = repo.load_chain(&tenant()).await.unwrap();
+        assert_eq!(chain.iter().map(|e| e.sequence().unwrap()).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(chain[0].previous_hash(), None);
+        assert_eq!(chain[2].previous_hash(), chain[1].hash());
+
+        let report = repo.verify_chain(&tenant()).await.unwrap();
+        assert!(report.valid);
+        assert_eq!(report.events_checked, 3);
+        assert_eq!(repo.load_chain(&other).await.unwrap()[0].sequence(), Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_signed_checkpoints() {
+        let inner = Arc::new(InMemoryAuditRepository::new());
+        let repo = signed_repo(inner.clone(), 2).await;
+
+        repo.append_batch((0..4).map(|_| login_event()).collect()).await.unwrap();
+
+        let chain = repo.load_chain(&tenant()).await.unwrap();
+        let checkpoints: Vec<_> = chain
+            .iter()
+            .filter(|e| e.action() == &AuditAction::ChainCheckpoint)
+            .collect();
+        assert_eq!(checkpoints.len(), 2);
+        assert_eq!(checkpoints[0].sequence(), Some(3));
+
+        let report = repo.verify_chain(&tenant()).await.unwrap();
+        assert!(report.valid, "{:?}", report.broken_link);
+        assert_eq!(report.checkpoints_verified, 2);
+
+        // Checkpoints signed before a key rotation verify against their key version
+        let signer = repo.signer.clone().unwrap();
+        signer.kms.rotate_key(&signer.key_id).await.unwrap();
+        repo.append_batch((0..2).map(|_| login_event()).collect()).await.unwrap();
+        let report = repo.verify_chain(&tenant()).await.unwrap();
+        assert!(report.valid, "{:?}", report.broken_link);
+        assert_eq!(report.checkpoints_verified, 3);
+    }
+
+    #[tokio::test]
+    async fn test_chain_verifies_after_restart() {
+        use crate::infrastructure::repositories::FileAuditRepository;
+        use crate::security::KmsManager;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let log_path = dir.path().join("audit.jsonl");
+        let kms_config = KmsConfig::default().with_default_key_file(dir.path().join("audit_keys.json"));
+        let open = || async {
+            let kms = KmsManager::new(kms_config.clone()).unwrap();
+            let key = kms
+                .ensure_key("audit-chain", KeyPurpose::AuditSigning, KeyAlgorithm::HmacSha256)
+                .await
+                .unwrap();
+            HashChainedAuditRepository::new(
+                Arc::new(FileAuditRepository::open(&log_path).unwrap()),
+                AuditChainConfig { checkpoint_interval: 2 },
+            )
+            .with_signer(AuditChainSigner {
+                kms: kms.client().clone(),
+                key_id: key.key_id,
+            })
+        };
+
+        let repo = open().await;
+        repo.append_batch((0..4).map(|_| login_event()).collect()).await.unwrap();
+        drop(repo);
+
+        // Events, chain head and checkpoint key all come back
+        let repo = open().await;
+        repo.append(login_event()).await.unwrap();
+        let report = repo.verify_chain(&tenant()).await.unwrap();
+        assert!(report.valid, "{:?}", report.broken_link);
+        assert_eq!(report.events_checked, 7);
+        assert_eq!(report.checkpoints_verified, 2);
+    }
+
+    #[tokio::test]
+    async fn test_verification_reports_first_broken_link() {
+        let inner = Arc::new(InMemoryAuditRepository::new());
+        let repo = signed_repo(inner.clone(), 0).await;
+        for _ in 0..4 {
+            repo.append(login_event()).await.unwrap();
+        }
+
+        // Rewrite the third event in place (same ID, altered content)
+        let chain = repo.load_chain(&tenant()).await.unwrap();
+        let original = &chain[2];
+        let tampered = AuditEvent::reconstruct(
+            original.id().clone(),
+            original.tenant_id().clone(),
+            *original.timestamp(),
+            AuditAction::Logout,
+            original.actor().clone(),
+            AuditOutcome::Success,
+        )
+        .with_chain_link(
+            original.sequence(),
+            original.previous_hash().map(str::to_string),
+            original.hash().map(str::to_string),
+        );
+        inner.append(tampered).await.unwrap();
+
+        let report = repo.verify_chain(&tenant()).await.unwrap();
+        assert!(!report.valid);
+        let broken = report.broken_link.unwrap();
+        assert_eq!(broken.sequence, 3);
+        assert_eq!(broken.event_id, Some(original.id().as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_verification_detects_forged_checkpoint() {
+        let inner = Arc::new(InMemoryAuditRepository::new());
+        let repo = signed_repo(inner.clone(), 1).await;
+        repo.append(login_event()).await.unwrap();
+
+        // Replace the checkpoint's signature and re-seal it so only the signature is wrong
+        let chain = repo.load_chain(&tenant()).await.unwrap();
+        let checkpoint = &chain[1];
+        let mut metadata = checkpoint.metadata().unwrap().clone();
+        metadata["signature"] = serde_json::json!(general_purpose::STANDARD.encode([0u8; 32]));
+        let forged = AuditEvent::reconstruct(
+            checkpoint.id().clone(),
+            tenant(),
+            *checkpoint.timestamp(),
+            AuditAction::ChainCheckpoint,
+            checkpoint.actor().clone(),
+            AuditOutcome::Success,
+        )
+        .with_metadata(metadata)
+        .chain(2, chain[0].hash().map(str::to_string));
+        inner.append(forged).await.unwrap();
+
+        let report = repo.verify_chain(&tenant()).await.unwrap();
+        let broken = report.broken_link.unwrap();
+        assert_eq!(broken.sequence, 2);
+        assert_eq!(broken.reason, "checkpoint signature is invalid");
+    }
+
+    #[tokio::test]
+    async fn test_purged_prefix_is_covered_by_signed_checkpoint() {
+        let inner = Arc::new(InMemoryAuditRepository::new());
+        let repo = signed_repo(inner.clone(), 2).await;
+        repo.append_batch((0..4).map(|_| login_event()).collect()).await.unwrap();
+
+        let purged = repo
+            .purge_old_events(&tenant(), Utc::now() + chrono::Duration::seconds(1))
+            .await
+            .unwrap();
+        assert_eq!(purged, 6);
+        repo.append(login_event()).await.unwrap();
+
+        let report = repo.verify_chain(&tenant()).await.unwrap();
+        assert!(report.valid, "{:?}", report.broken_link);
+        assert!(report.truncated);
+        assert_eq!(report.first_sequence, Some(7));
+    }
+
+    #[tokio::test]
+    async fn test_verification_detects_unrecorded_purge() {
+        let inner = Arc::new(InMemoryAuditRepository::new());
+        let repo = signed_repo(inner.clone(), 0).await;
+        repo.append(login_event()).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        repo.append(login_event()).await.unwrap();
+
+        // Delete the first event behind the wrapper's back
+        let chain = repo.load_chain(&tenant()).await.unwrap();
+        inner.purge_old_events(&tenant(), *chain[1].timestamp()).await.unwrap();
+
+        let report = repo.verify_chain(&tenant()).await.unwrap();
+        assert!(!report.valid);
+        assert!(report.truncated);
+        assert_eq!(report.broken_link.unwrap().sequence, 1);
+    }
+
+    #[tokio::test]
+    async fn test_verification_detects_missing_tail() {
+        let inner = Arc::new(InMemoryAuditRepository::new());
+        let repo = HashChainedAuditRepository::new(inner.clone(), AuditChainConfig::default());
+        repo.append(login_event()).await.unwrap();
+        repo.append(login_event()).await.unwrap();
+
+        // Delete everything behind the wrapper's back
+        inner
+            .purge_old_events(&tenant(), Utc::now() + chrono::Duration::seconds(1))
+            .await
+            .unwrap();
+
+        let report = repo.verify_chain(&tenant()).await.unwrap();
+        assert!(!report.valid);
+        assert_eq!(report.broken_link.unwrap().sequence, 1);
+    }
+}
@@ -45,6 +45,11 @@ impl InMemoryAuditRepository {
         self.events.clear();
     }
 
+    /// Store an event synchronously (used when loading persisted events)
+    pub fn insert(&self, event: AuditEvent) {
+        self.events.insert(event.id().as_str(), event);
+    }
+
     /// Count all events
     pub fn len(&self) -> usize {
         self.events.len()
@@ -65,8 +70,7 @@ impl Default for InMemoryAuditRepository {
 #[async_trait]
 impl AuditEventRepository for InMemoryAuditRepository {
     async fn append(&self, event: AuditEvent) -> Result<()> {
-        let event_id = event.id().as_str();
-        self.events.insert(event_id, event);
+        self.insert(event);
         Ok(())
     }
 
@@ -0,0 +1,246 @@
+use crate::auth::{ApiKey, RefreshToken, Revocation, RevocationKind, User};
+use crate::domain::repositories::{ApiKeyRepository, SessionRepository, UserRepository};
+use crate::error::{AllSourceError, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// In-memory implementation of UserRepository, ApiKeyRepository and
+/// SessionRepository
+///
+/// Thread-safe account storage using DashMap for concurrent access.
+/// Suitable for testing and development; nothing survives a restart.
+pub struct InMemoryAuthRepository {
+    users: Arc<DashMap<Uuid, User>>,
+    api_keys: Arc<DashMap<Uuid, ApiKey>>,
+    refresh_tokens: Arc<DashMap<String, RefreshToken>>,
+    revocations: Arc<DashMap<(RevocationKind, String), Revocation>>,
+}
+
+impl InMemoryAuthRepository {
+    /// Create a new empty in-memory auth repository
+    pub fn new() -> Self {
+        Self {
+            users: Arc::new(DashMap::new()),
+            api_keys: Arc::new(DashMap::new()),
+            refresh_tokens: Arc::new(DashMap::new()),
+            revocations: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Clear all users, API keys and sessions (useful for testing)
+    pub fn clear(&self) {
+        self.users.clear();
+        self.api_keys.clear();
+        self.refresh_tokens.clear();
+        self.revocations.clear();
+    }
+}
+
+impl Default for InMemoryAuthRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl UserRepository for InMemoryAuthRepository {
+    async fn save(&self, user: &User) -> Result<()> {
+        let taken = self
+            .users
+            .iter()
+            .any(|entry| entry.value().username == user.username && entry.value().id != user.id);
+        if taken {
+            return Err(AllSourceError::ValidationError(
+                "Username already exists".to_string(),
+            ));
+        }
+        self.users.insert(user.id, user.clone());
+        Ok(())
+    }
+
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<User>> {
+        Ok(self.users.get(id).map(|entry| entry.value().clone()))
+    }
+
+    async fn find_by_username(&self, username: &str) -> Result<Option<User>> {
+        Ok(self
+            .users
+            .iter()
+            .find(|entry| entry.value().username == username)
+            .map(|entry| entry.value().clone()))
+    }
+
+    async fn find_all(&self) -> Result<Vec<User>> {
+        let mut users: Vec<User> = self.users.iter().map(|entry| entry.value().clone()).collect();
+        users.sort_by_key(|r| r.created_at);
+        Ok(users)
+    }
+
+    async fn delete(&self, id: &Uuid) -> Result<bool> {
+        Ok(self.users.remove(id).is_some())
+    }
+}
+
+#[async_trait]
+impl ApiKeyRepository for InMemoryAuthRepository {
+    async fn save(&self, api_key: &ApiKey) -> Result<()> {
+        self.api_keys.insert(api_key.id, api_key.clone());
+        Ok(())
+    }
+
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<ApiKey>> {
+        Ok(self.api_keys.get(id).map(|entry| entry.value().clone()))
+    }
+
+    async fn find_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>> {
+        Ok(self
+            .api_keys
+            .iter()
+            .find(|entry| entry.value().key_hash == key_hash)
+            .map(|entry| entry.value().clone()))
+    }
+
+    async fn find_by_tenant(&self, tenant_id: &str) -> Result<Vec<ApiKey>> {
+        let mut keys: Vec<ApiKey> = self
+            .api_keys
+            .iter()
+            .filter(|entry| entry.value().tenant_id == tenant_id)
+            .map(|entry| entry.value().clone())
+            .collect();
+        keys.sort_by_key(|r| r.created_at);
+        Ok(keys)
+    }
+
+    async fn find_all(&self) -> Result<Vec<ApiKey>> {
+        let mut keys: Vec<ApiKey> = self.api_keys.iter().map(|entry| entry.value().clone()).collect();
+        keys.sort_by_key(|r| r.created_at);
+        Ok(keys)
+    }
+
+    async fn update_last_used(&self, id: &Uuid, last_used: DateTime<Utc>) -> Result<bool> {
+        match self.api_keys.get_mut(id) {
+            Some(mut entry) => {
+                entry.last_used = Some(last_used);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn delete(&self, id: &Uuid) -> Result<bool> {
+        Ok(self.api_keys.remove(id).is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::Role;
+
+    fn user(username: &str) -> User {
+        User::new(
+            username.to_string(),
+            format!("{}@example.com", username),
+            "password123",
+            Role::Developer,
+            "acme".to_string(),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_user_crud_and_username_uniqueness() {
+        let repo = InMemoryAuthRepository::new();
+        let alice = user("alice");
+        UserRepository::save(&repo, &alice).await.unwrap();
+
+        let found = repo.find_by_username("alice").await.unwrap().unwrap();
+        assert_eq!(found.id, alice.id);
+        assert_eq!(found.password_hash, alice.password_hash);
+
+        // Updating the same user is fine, taking its username is not
+        UserRepository::save(&repo, &alice).await.unwrap();
+        assert!(UserRepository::save(&repo, &user("alice")).await.is_err());
+
+        assert!(UserRepository::delete(&repo, &alice.id).await.unwrap());
+        assert!(UserRepository::find_by_id(&repo, &alice.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_api_key_lookup_and_last_used() {
+        let repo = InMemoryAuthRepository::new();
+        let (key, _) = ApiKey::new("ci".to_string(), "acme".to_string(), Role::ServiceAccount, None);
+        ApiKeyRepository::save(&repo, &key).await.unwrap();
+
+        assert!(repo.find_by_hash(&key.key_hash).await.unwrap().is_some());
+        assert_eq!(repo.find_by_tenant("acme").await.unwrap().len(), 1);
+        assert!(repo.find_by_tenant("other").await.unwrap().is_empty());
+
+        let now = Utc::now();
+        assert!(repo.update_last_used(&key.id, now).await.unwrap());
+        let stored = ApiKeyRepository::find_by_id(&repo, &key.id).await.unwrap().unwrap();
+        assert_eq!(stored.last_used, Some(now));
+        assert!(!repo.update_last_used(&Uuid::new_v4(), now).await.unwrap());
+    }
+}
+
+#[async_trait]
+impl SessionRepository for InMemoryAuthRepository {
+    async fn save_refresh_token(&self, token: &RefreshToken) -> Result<()> {
+        self.refresh_tokens.insert(token.token_hash.clone(), token.clone());
+        Ok(())
+    }
+
+    async fn find_refresh_token(&self, token_hash: &str) -> Result<Option<RefreshToken>> {
+        Ok(self.refresh_tokens.get(token_hash).map(|entry| entry.value().clone()))
+    }
+
+    async fn find_refresh_tokens_by_user(&self, user_id: &Uuid) -> Result<Vec<RefreshToken>> {
+        Ok(self
+            .refresh_tokens
+            .iter()
+            .filter(|entry| &entry.value().user_id == user_id)
+            .map(|entry| entry.value().clone())
+            .collect())
+    }
+
+    async fn mark_refresh_token_used(&self, token_hash: &str, used_at: DateTime<Utc>) -> Result<bool> {
+        match self.refresh_tokens.get_mut(token_hash) {
+            Some(mut entry) if entry.used_at.is_none() => {
+                entry.used_at = Some(used_at);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn delete_session_refresh_tokens(&self, session_id: &str) -> Result<()> {
+        self.refresh_tokens.retain(|_, token| token.session_id != session_id);
+        Ok(())
+    }
+
+    async fn save_revocation(&self, revocation: &Revocation) -> Result<()> {
+        self.revocations
+            .insert((revocation.kind, revocation.id.clone()), revocation.clone());
+        Ok(())
+    }
+
+    async fn find_revocations(&self, now: DateTime<Utc>) -> Result<Vec<Revocation>> {
+        Ok(self
+            .revocations
+            .iter()
+            .filter(|entry| entry.value().expires_at > now)
+            .map(|entry| entry.value().clone())
+            .collect())
+    }
+
+    async fn prune(&self, now: DateTime<Utc>) -> Result<usize> {
+        let before = self.refresh_tokens.len() + self.revocations.len();
+        self.refresh_tokens.retain(|_, token| token.expires_at > now);
+        self.revocations.retain(|_, revocation| revocation.expires_at > now);
+        Ok(before.saturating_sub(self.refresh_tokens.len() + self.revocations.len()))
+    }
+}
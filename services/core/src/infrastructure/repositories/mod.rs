@@ -1,31 +1,63 @@
 pub mod in_memory_event_stream_repository;
 pub mod in_memory_audit_repository;
+pub mod hash_chained_audit_repository;
+pub mod monitored_audit_repository;
 pub mod in_memory_tenant_repository;
+pub mod in_memory_auth_repository;
+pub mod file_auth_repository;
+pub mod file_audit_repository;
 
 #[cfg(feature = "postgres")]
 pub mod postgres_event_stream_repository;
 
+#[cfg(feature = "postgres")]
+pub mod postgres_event_store;
+
 #[cfg(feature = "postgres")]
 pub mod postgres_audit_repository;
 
 #[cfg(feature = "postgres")]
 pub mod postgres_tenant_repository;
 
+#[cfg(feature = "postgres")]
+pub mod postgres_auth_repository;
+
 #[cfg(feature = "rocksdb-storage")]
 pub mod rocksdb_event_stream_repository;
 
+#[cfg(feature = "rocksdb-storage")]
+pub mod rocksdb_auth_repository;
+
 pub use in_memory_event_stream_repository::InMemoryEventStreamRepository;
 pub use in_memory_audit_repository::InMemoryAuditRepository;
+pub use hash_chained_audit_repository::{
+    AuditChainConfig, AuditChainSigner, BrokenLink, ChainVerificationReport, HashChainedAuditRepository,
+};
+pub use monitored_audit_repository::MonitoredAuditRepository;
 pub use in_memory_tenant_repository::InMemoryTenantRepository;
+pub use in_memory_auth_repository::InMemoryAuthRepository;
+pub use file_auth_repository::FileAuthRepository;
+pub use file_audit_repository::FileAuditRepository;
 
 #[cfg(feature = "postgres")]
 pub use postgres_event_stream_repository::PostgresEventStreamRepository;
 
+#[cfg(feature = "postgres")]
+pub use postgres_event_store::{
+    PostgresEventFeed, PostgresEventFeedConfig, PostgresEventFilter, PostgresEventStore, StoredEvent,
+};
+
 #[cfg(feature = "postgres")]
 pub use postgres_audit_repository::PostgresAuditRepository;
 
 #[cfg(feature = "postgres")]
 pub use postgres_tenant_repository::PostgresTenantRepository;
 
+#[cfg(feature = "postgres")]
+pub use postgres_auth_repository::PostgresAuthRepository;
+
 #[cfg(feature = "rocksdb-storage")]
 pub use rocksdb_event_stream_repository::RocksDBEventStreamRepository;
+
+#[cfg(feature = "rocksdb-storage")]
+pub use rocksdb_auth_repository::RocksDBAuthRepository;
@@ -0,0 +1,157 @@
+//! Anomaly-Monitored Audit Event Repository
+//!
+//! Decorates any AuditEventRepository so that every successfully stored
+//! audit event is handed to the `AnomalyResponder`. This subscribes anomaly
+//! detection to the audit flow without the callers of `AuditLogger` knowing
+//! about it.
+//!
+//! Monitoring never fails an append: detection errors are logged. The
+//! responder records its alerts to the inner repository, so alerts are not
+//! fed back through the detector.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+use crate::domain::entities::{AuditEvent, AuditEventId};
+use crate::domain::repositories::{AuditEventQuery, AuditEventRepository};
+use crate::domain::value_objects::TenantId;
+use crate::error::Result;
+use crate::security::AnomalyResponder;
+
+pub struct MonitoredAuditRepository {
+    inner: Arc<dyn AuditEventRepository>,
+    responder: Arc<AnomalyResponder>,
+}
+
+impl MonitoredAuditRepository {
+    pub fn new(inner: Arc<dyn AuditEventRepository>, responder: Arc<AnomalyResponder>) -> Self {
+        Self { inner, responder }
+    }
+
+    pub fn responder(&self) -> &Arc<AnomalyResponder> {
+        &self.responder
+    }
+
+    async fn observe(&self, event: &AuditEvent) {
+        if let Err(e) = self.responder.observe(event).await {
+            tracing::warn!("⚠️  Anomaly detection failed for audit event {}: {}", event.id().as_str(), e);
+        }
+    }
+}
+
+#[async_trait]
+impl AuditEventRepository for MonitoredAuditRepository {
+    async fn append(&self, event: AuditEvent) -> Result<()> {
+        self.inner.append(event.clone()).await?;
+        self.observe(&event).await;
+        Ok(())
+    }
+
+    async fn append_batch(&self, events: Vec<AuditEvent>) -> Result<()> {
+        self.inner.append_batch(events.clone()).await?;
+        for event in &events {
+            self.observe(event).await;
+        }
+        Ok(())
+    }
+
+    async fn get_by_id(&self, id: &AuditEventId) -> Result<Option<AuditEvent>> {
+        self.inner.get_by_id(id).await
+    }
+
+    async fn query(&self, query: AuditEventQuery) -> Result<Vec<AuditEvent>> {
+        self.inner.query(query).await
+    }
+
+    async fn count(&self, query: AuditEventQuery) -> Result<usize> {
+        self.inner.count(query).await
+    }
+
+    async fn get_by_tenant(
+        &self,
+        tenant_id: &TenantId,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<AuditEvent>> {
+        self.inner.get_by_tenant(tenant_id, limit, offset).await
+    }
+
+    async fn get_security_events(
+        &self,
+        tenant_id: &TenantId,
+        limit: usize,
+    ) -> Result<Vec<AuditEvent>> {
+        self.inner.get_security_events(tenant_id, limit).await
+    }
+
+    async fn get_by_actor(
+        &self,
+        tenant_id: &TenantId,
+        actor_identifier: &str,
+        limit: usize,
+    ) -> Result<Vec<AuditEvent>> {
+        self.inner.get_by_actor(tenant_id, actor_identifier, limit).await
+    }
+
+    async fn purge_old_events(
+        &self,
+        tenant_id: &TenantId,
+        older_than: DateTime<Utc>,
+    ) -> Result<usize> {
+        self.inner.purge_old_events(tenant_id, older_than).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::AuthManager;
+    use crate::domain::entities::{Actor, AuditAction, AuditOutcome};
+    use crate::infrastructure::repositories::InMemoryAuditRepository;
+    use crate::infrastructure::security::IpFilter;
+    use crate::rate_limit::{RateLimitConfig, RateLimiter};
+    use crate::security::{AnomalyDetectionConfig, AnomalyDetector, AnomalyResponseConfig};
+
+    #[tokio::test]
+    async fn test_appended_events_feed_detector_and_alerts_bypass_it() {
+        let inner = Arc::new(InMemoryAuditRepository::new());
+        let responder = Arc::new(
+            AnomalyResponder::new(
+                Arc::new(AnomalyDetector::new(AnomalyDetectionConfig::default())),
+                AnomalyResponseConfig::default(),
+                Arc::new(AuthManager::new("test-secret")),
+                Arc::new(IpFilter::new()),
+                Arc::new(RateLimiter::new(RateLimitConfig::professional())),
+                inner.clone(),
+            )
+            .unwrap(),
+        );
+        let repo = MonitoredAuditRepository::new(inner.clone(), responder.clone());
+        let tenant_id = TenantId::new("acme".to_string()).unwrap();
+
+        let events: Vec<AuditEvent> = (0..7)
+            .map(|_| {
+                AuditEvent::new(
+                    tenant_id.clone(),
+                    AuditAction::LoginFailed,
+                    Actor::user("u1".to_string(), "alice".to_string()),
+                    AuditOutcome::Failure,
+                )
+            })
+            .collect();
+        repo.append_batch(events).await.unwrap();
+
+        assert_eq!(responder.detector().get_stats().user_profiles_count, 1);
+
+        // 7 failed logins plus one alert from the detector
+        let stored = repo.get_by_tenant(&tenant_id, 100, 0).await.unwrap();
+        assert_eq!(stored.len(), 8);
+        let alerts: Vec<_> = stored
+            .iter()
+            .filter(|e| e.action() == &AuditAction::SuspiciousActivity)
+            .collect();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].actor().identifier(), "system:anomaly-detector");
+    }
+}
@@ -153,22 +153,27 @@ impl PostgresAuditRepository {
 
         let resource_type: Option<String> = row.try_get("resource_type").ok();
         let resource_id: Option<String> = row.try_get("resource_id").ok();
-        let ip_address: Option<String> = row.try_get::<Option<std::net::IpAddr>, _>("ip_address")
+        // INET is selected as text (`ip_text`); sqlx has no IpAddr mapping
+        let ip_address: Option<String> = row.try_get::<Option<String>, _>("ip_text")
             .ok()
-            .flatten()
-            .map(|ip| ip.to_string());
+            .flatten();
         let user_agent: Option<String> = row.try_get("user_agent").ok();
         let request_id: Option<String> = row.try_get("request_id").ok();
         let error_message: Option<String> = row.try_get("error_message").ok();
         let metadata: Option<JsonValue> = row.try_get("metadata").ok();
-
-        // Reconstruct event
-        let mut event = AuditEvent::new(tenant_id, action, actor, outcome);
-
-        // Manually set the ID and timestamp (need to use reconstruction pattern)
-        // Since AuditEvent doesn't expose setters, we'll need to reconstruct it properly
-        // For now, create a new event and it will get a new ID/timestamp
-        // In production, you'd add a `reconstruct` method like EventStream has
+        let chain_sequence: Option<i64> = row.try_get("chain_sequence").ok().flatten();
+        let previous_hash: Option<String> = row.try_get("previous_hash").ok().flatten();
+        let hash: Option<String> = row.try_get("hash").ok().flatten();
+
+        // Reconstruct event with its original ID and timestamp
+        let mut event = AuditEvent::reconstruct(
+            AuditEventId::from_uuid(id),
+            tenant_id,
+            timestamp,
+            action,
+            actor,
+            outcome,
+        );
 
         if let (Some(rt), Some(ri)) = (resource_type, resource_id) {
             event = event.with_resource(rt, ri);
@@ -188,6 +193,7 @@ impl PostgresAuditRepository {
         if let Some(meta) = metadata {
             event = event.with_metadata(meta);
         }
+        event = event.with_chain_link(chain_sequence.map(|s| s as u64), previous_hash, hash);
 
         Ok(event)
     }
@@ -202,8 +208,9 @@ impl AuditEventRepository for PostgresAuditRepository {
         let category_str = format!("{:?}", event.category()).to_lowercase();
         let outcome_str = Self::outcome_to_string(event.outcome());
 
-        let ip_addr: Option<std::net::IpAddr> = event.ip_address()
-            .and_then(|s| s.parse().ok());
+        let ip_addr: Option<String> = event.ip_address()
+            .and_then(|s| s.parse::<std::net::IpAddr>().ok())
+            .map(|ip| ip.to_string());
 
         sqlx::query(
             r#"
@@ -212,8 +219,9 @@ impl AuditEventRepository for PostgresAuditRepository {
                 actor_type, actor_id, actor_name,
                 resource_type, resource_id, outcome,
                 ip_address, user_agent, request_id,
-                error_message, metadata
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+                error_message, metadata,
+                chain_sequence, previous_hash, hash
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12::inet, $13, $14, $15, $16, $17, $18, $19)
             "#,
         )
         .bind(event.id().as_uuid())
@@ -232,6 +240,9 @@ impl AuditEventRepository for PostgresAuditRepository {
         .bind(event.request_id())
         .bind(event.error_message())
         .bind(event.metadata())
+        .bind(event.sequence().map(|s| s as i64))
+        .bind(event.previous_hash())
+        .bind(event.hash())
         .execute(&self.pool)
         .await
         .map_err(|e| AllSourceError::StorageError(format!("Failed to append audit event: {}", e)))?;
@@ -248,8 +259,9 @@ impl AuditEventRepository for PostgresAuditRepository {
             let action_str = Self::action_to_string(event.action());
             let category_str = format!("{:?}", event.category()).to_lowercase();
             let outcome_str = Self::outcome_to_string(event.outcome());
-            let ip_addr: Option<std::net::IpAddr> = event.ip_address()
-                .and_then(|s| s.parse().ok());
+            let ip_addr: Option<String> = event.ip_address()
+                .and_then(|s| s.parse::<std::net::IpAddr>().ok())
+                .map(|ip| ip.to_string());
 
             sqlx::query(
                 r#"
@@ -258,8 +270,9 @@ impl AuditEventRepository for PostgresAuditRepository {
                     actor_type, actor_id, actor_name,
                     resource_type, resource_id, outcome,
                     ip_address, user_agent, request_id,
-                    error_message, metadata
-                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+                    error_message, metadata,
+                    chain_sequence, previous_hash, hash
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12::inet, $13, $14, $15, $16, $17, $18, $19)
                 "#,
             )
             .bind(event.id().as_uuid())
@@ -278,6 +291,9 @@ impl AuditEventRepository for PostgresAuditRepository {
             .bind(event.request_id())
             .bind(event.error_message())
             .bind(event.metadata())
+            .bind(event.sequence().map(|s| s as i64))
+            .bind(event.previous_hash())
+            .bind(event.hash())
             .execute(&mut *tx)
             .await
             .map_err(|e| AllSourceError::StorageError(format!("Failed to append audit event: {}", e)))?;
@@ -292,7 +308,7 @@ impl AuditEventRepository for PostgresAuditRepository {
     async fn get_by_id(&self, id: &AuditEventId) -> Result<Option<AuditEvent>> {
         let row = sqlx::query(
             r#"
-            SELECT * FROM audit_events WHERE id = $1
+            SELECT *, host(ip_address) AS ip_text FROM audit_events WHERE id = $1
             "#,
         )
         .bind(id.as_uuid())
@@ -308,7 +324,7 @@ impl AuditEventRepository for PostgresAuditRepository {
 
     async fn query(&self, query: AuditEventQuery) -> Result<Vec<AuditEvent>> {
         let mut sql = String::from(
-            "SELECT * FROM audit_events WHERE tenant_id = $1"
+            "SELECT *, host(ip_address) AS ip_text FROM audit_events WHERE tenant_id = $1"
         );
         let mut param_count = 1;
 
@@ -374,7 +390,7 @@ impl AuditEventRepository for PostgresAuditRepository {
         }
         if let Some(actor_id) = query.actor_identifier {
             // Extract just the ID part after the colon
-            let actor_id_only = actor_id.split(':').nth(1).unwrap_or(&actor_id);
+            let actor_id_only = actor_id.split(':').nth(1).unwrap_or(&actor_id).to_string();
             db_query = db_query.bind(actor_id_only);
         }
         if let Some(resource_type) = query.resource_type {
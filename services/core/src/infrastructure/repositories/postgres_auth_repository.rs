@@ -0,0 +1,433 @@
+//! PostgreSQL User and API Key Repository
+//!
+//! Production-grade persistent account storage using PostgreSQL.
+//! Shares the migrations of the other PostgreSQL repositories
+//! (see `migrations/005_auth_accounts.sql`, `006_api_key_scopes.sql` and
+//! `009_auth_sessions.sql`). Nodes sharing the database share sessions: a
+//! refresh token is exchanged once across all of them, and revocations are
+//! visible to every node.
+
+#[cfg(feature = "postgres")]
+use async_trait::async_trait;
+#[cfg(feature = "postgres")]
+use chrono::{DateTime, Utc};
+#[cfg(feature = "postgres")]
+use sqlx::{PgPool, Row};
+#[cfg(feature = "postgres")]
+use uuid::Uuid;
+
+#[cfg(feature = "postgres")]
+use crate::auth::{ApiKey, ApiKeyScopes, RefreshToken, Revocation, RevocationKind, Role, User};
+#[cfg(feature = "postgres")]
+use crate::domain::repositories::{ApiKeyRepository, SessionRepository, UserRepository};
+#[cfg(feature = "postgres")]
+use crate::error::{AllSourceError, Result};
+
+#[cfg(feature = "postgres")]
+const USER_COLUMNS: &str = "id, username, email, password_hash, role, tenant_id, active, created_at";
+
+#[cfg(feature = "postgres")]
+const API_KEY_COLUMNS: &str =
+    "id, name, tenant_id, role, key_hash, active, created_at, expires_at, last_used, scopes";
+
+#[cfg(feature = "postgres")]
+const REFRESH_TOKEN_COLUMNS: &str = "token_hash, session_id, user_id, issued_at, expires_at, used_at";
+
+#[cfg(feature = "postgres")]
+/// PostgreSQL auth repository
+pub struct PostgresAuthRepository {
+    pool: PgPool,
+}
+
+#[cfg(feature = "postgres")]
+impl PostgresAuthRepository {
+    /// Create new PostgreSQL auth repository
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Run migrations (creates the users, api_keys, refresh_tokens and
+    /// revocations tables)
+    pub async fn migrate(&self) -> Result<()> {
+        sqlx::migrate!("./migrations")
+            .run(&self.pool)
+            .await
+            .map_err(|e| AllSourceError::StorageError(format!("Migration failed: {}", e)))?;
+        Ok(())
+    }
+
+    fn role_to_str(role: &Role) -> Result<String> {
+        match serde_json::to_value(role)? {
+            serde_json::Value::String(s) => Ok(s),
+            other => Err(AllSourceError::StorageError(format!("Unexpected role encoding: {}", other))),
+        }
+    }
+
+    fn str_to_role(role: &str) -> Result<Role> {
+        serde_json::from_value(serde_json::Value::String(role.to_string()))
+            .map_err(|e| AllSourceError::StorageError(format!("Invalid role '{}': {}", role, e)))
+    }
+
+    fn get<'r, T>(row: &'r sqlx::postgres::PgRow, column: &str) -> Result<T>
+    where
+        T: sqlx::Decode<'r, sqlx::Postgres> + sqlx::Type<sqlx::Postgres>,
+    {
+        row.try_get(column)
+            .map_err(|e| AllSourceError::StorageError(format!("Failed to get {}: {}", column, e)))
+    }
+
+    /// Helper: Convert database row to User
+    fn row_to_user(row: &sqlx::postgres::PgRow) -> Result<User> {
+        let role: String = Self::get(row, "role")?;
+        Ok(User {
+            id: Self::get(row, "id")?,
+            username: Self::get(row, "username")?,
+            email: Self::get(row, "email")?,
+            password_hash: Self::get(row, "password_hash")?,
+            role: Self::str_to_role(&role)?,
+            tenant_id: Self::get(row, "tenant_id")?,
+            created_at: Self::get(row, "created_at")?,
+            active: Self::get(row, "active")?,
+        })
+    }
+
+    /// Helper: Convert database row to ApiKey
+    fn row_to_api_key(row: &sqlx::postgres::PgRow) -> Result<ApiKey> {
+        let role: String = Self::get(row, "role")?;
+        let scopes: serde_json::Value = Self::get(row, "scopes")?;
+        Ok(ApiKey {
+            id: Self::get(row, "id")?,
+            name: Self::get(row, "name")?,
+            tenant_id: Self::get(row, "tenant_id")?,
+            role: Self::str_to_role(&role)?,
+            key_hash: Self::get(row, "key_hash")?,
+            created_at: Self::get(row, "created_at")?,
+            expires_at: Self::get(row, "expires_at")?,
+            active: Self::get(row, "active")?,
+            last_used: Self::get(row, "last_used")?,
+            scopes: serde_json::from_value::<ApiKeyScopes>(scopes)?,
+        })
+    }
+
+    /// Helper: Convert database row to RefreshToken
+    fn row_to_refresh_token(row: &sqlx::postgres::PgRow) -> Result<RefreshToken> {
+        Ok(RefreshToken {
+            session_id: Self::get(row, "session_id")?,
+            user_id: Self::get(row, "user_id")?,
+            token_hash: Self::get(row, "token_hash")?,
+            issued_at: Self::get(row, "issued_at")?,
+            expires_at: Self::get(row, "expires_at")?,
+            used_at: Self::get(row, "used_at")?,
+        })
+    }
+
+    fn kind_to_str(kind: RevocationKind) -> &'static str {
+        match kind {
+            RevocationKind::Session => "session",
+            RevocationKind::Token => "token",
+        }
+    }
+
+    /// Helper: Convert database row to Revocation
+    fn row_to_revocation(row: &sqlx::postgres::PgRow) -> Result<Revocation> {
+        let kind: String = Self::get(row, "kind")?;
+        Ok(Revocation {
+            kind: match kind.as_str() {
+                "session" => RevocationKind::Session,
+                "token" => RevocationKind::Token,
+                other => {
+                    return Err(AllSourceError::StorageError(format!(
+                        "Invalid revocation kind '{}'",
+                        other
+                    )))
+                }
+            },
+            id: Self::get(row, "id")?,
+            expires_at: Self::get(row, "expires_at")?,
+        })
+    }
+}
+
+#[cfg(feature = "postgres")]
+#[async_trait]
+impl UserRepository for PostgresAuthRepository {
+    async fn save(&self, user: &User) -> Result<()> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO users (id, username, email, password_hash, role, tenant_id, active, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (id) DO UPDATE SET
+                username = EXCLUDED.username,
+                email = EXCLUDED.email,
+                password_hash = EXCLUDED.password_hash,
+                role = EXCLUDED.role,
+                tenant_id = EXCLUDED.tenant_id,
+                active = EXCLUDED.active
+            "#,
+        )
+        .bind(user.id)
+        .bind(&user.username)
+        .bind(&user.email)
+        .bind(&user.password_hash)
+        .bind(Self::role_to_str(&user.role)?)
+        .bind(&user.tenant_id)
+        .bind(user.active)
+        .bind(user.created_at)
+        .execute(&self.pool)
+        .await;
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(sqlx::Error::Database(e)) if e.constraint() == Some("users_username_unique") => {
+                Err(AllSourceError::ValidationError("Username already exists".to_string()))
+            }
+            Err(e) => Err(AllSourceError::StorageError(format!("Failed to save user: {}", e))),
+        }
+    }
+
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<User>> {
+        let row = sqlx::query(&format!("SELECT {} FROM users WHERE id = $1", USER_COLUMNS))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| AllSourceError::StorageError(format!("Failed to find user: {}", e)))?;
+        row.as_ref().map(Self::row_to_user).transpose()
+    }
+
+    async fn find_by_username(&self, username: &str) -> Result<Option<User>> {
+        let row = sqlx::query(&format!("SELECT {} FROM users WHERE username = $1", USER_COLUMNS))
+            .bind(username)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| AllSourceError::StorageError(format!("Failed to find user: {}", e)))?;
+        row.as_ref().map(Self::row_to_user).transpose()
+    }
+
+    async fn find_all(&self) -> Result<Vec<User>> {
+        let rows = sqlx::query(&format!("SELECT {} FROM users ORDER BY created_at ASC", USER_COLUMNS))
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| AllSourceError::StorageError(format!("Failed to list users: {}", e)))?;
+        rows.iter().map(Self::row_to_user).collect()
+    }
+
+    async fn delete(&self, id: &Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| AllSourceError::StorageError(format!("Failed to delete user: {}", e)))?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[cfg(feature = "postgres")]
+#[async_trait]
+impl ApiKeyRepository for PostgresAuthRepository {
+    async fn save(&self, api_key: &ApiKey) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO api_keys (id, name, tenant_id, role, key_hash, active, created_at, expires_at, last_used, scopes)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT (id) DO UPDATE SET
+                name = EXCLUDED.name,
+                tenant_id = EXCLUDED.tenant_id,
+                role = EXCLUDED.role,
+                key_hash = EXCLUDED.key_hash,
+                active = EXCLUDED.active,
+                expires_at = EXCLUDED.expires_at,
+                last_used = EXCLUDED.last_used,
+                scopes = EXCLUDED.scopes
+            "#,
+        )
+        .bind(api_key.id)
+        .bind(&api_key.name)
+        .bind(&api_key.tenant_id)
+        .bind(Self::role_to_str(&api_key.role)?)
+        .bind(&api_key.key_hash)
+        .bind(api_key.active)
+        .bind(api_key.created_at)
+        .bind(api_key.expires_at)
+        .bind(api_key.last_used)
+        .bind(serde_json::to_value(&api_key.scopes)?)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AllSourceError::StorageError(format!("Failed to save API key: {}", e)))?;
+
+        Ok(())
+    }
+
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<ApiKey>> {
+        let row = sqlx::query(&format!("SELECT {} FROM api_keys WHERE id = $1", API_KEY_COLUMNS))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| AllSourceError::StorageError(format!("Failed to find API key: {}", e)))?;
+        row.as_ref().map(Self::row_to_api_key).transpose()
+    }
+
+    async fn find_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>> {
+        let row = sqlx::query(&format!("SELECT {} FROM api_keys WHERE key_hash = $1", API_KEY_COLUMNS))
+            .bind(key_hash)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| AllSourceError::StorageError(format!("Failed to find API key: {}", e)))?;
+        row.as_ref().map(Self::row_to_api_key).transpose()
+    }
+
+    async fn find_by_tenant(&self, tenant_id: &str) -> Result<Vec<ApiKey>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM api_keys WHERE tenant_id = $1 ORDER BY created_at ASC",
+            API_KEY_COLUMNS
+        ))
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AllSourceError::StorageError(format!("Failed to list API keys: {}", e)))?;
+        rows.iter().map(Self::row_to_api_key).collect()
+    }
+
+    async fn find_all(&self) -> Result<Vec<ApiKey>> {
+        let rows = sqlx::query(&format!("SELECT {} FROM api_keys ORDER BY created_at ASC", API_KEY_COLUMNS))
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| AllSourceError::StorageError(format!("Failed to list API keys: {}", e)))?;
+        rows.iter().map(Self::row_to_api_key).collect()
+    }
+
+    async fn update_last_used(&self, id: &Uuid, last_used: DateTime<Utc>) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE api_keys SET last_used = GREATEST(COALESCE(last_used, $2), $2) WHERE id = $1",
+        )
+        .bind(id)
+        .bind(last_used)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AllSourceError::StorageError(format!("Failed to update API key: {}", e)))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn delete(&self, id: &Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM api_keys WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| AllSourceError::StorageError(format!("Failed to delete API key: {}", e)))?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[cfg(feature = "postgres")]
+#[async_trait]
+impl SessionRepository for PostgresAuthRepository {
+    async fn save_refresh_token(&self, token: &RefreshToken) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO refresh_tokens (token_hash, session_id, user_id, issued_at, expires_at, used_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (token_hash) DO UPDATE SET
+                session_id = EXCLUDED.session_id,
+                user_id = EXCLUDED.user_id,
+                issued_at = EXCLUDED.issued_at,
+                expires_at = EXCLUDED.expires_at,
+                used_at = EXCLUDED.used_at
+            "#,
+        )
+        .bind(&token.token_hash)
+        .bind(&token.session_id)
+        .bind(token.user_id)
+        .bind(token.issued_at)
+        .bind(token.expires_at)
+        .bind(token.used_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AllSourceError::StorageError(format!("Failed to save refresh token: {}", e)))?;
+
+        Ok(())
+    }
+
+    async fn find_refresh_token(&self, token_hash: &str) -> Result<Option<RefreshToken>> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM refresh_tokens WHERE token_hash = $1",
+            REFRESH_TOKEN_COLUMNS
+        ))
+        .bind(token_hash)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| AllSourceError::StorageError(format!("Failed to find refresh token: {}", e)))?;
+        row.as_ref().map(Self::row_to_refresh_token).transpose()
+    }
+
+    async fn find_refresh_tokens_by_user(&self, user_id: &Uuid) -> Result<Vec<RefreshToken>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM refresh_tokens WHERE user_id = $1 ORDER BY issued_at ASC",
+            REFRESH_TOKEN_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AllSourceError::StorageError(format!("Failed to list refresh tokens: {}", e)))?;
+        rows.iter().map(Self::row_to_refresh_token).collect()
+    }
+
+    async fn mark_refresh_token_used(&self, token_hash: &str, used_at: DateTime<Utc>) -> Result<bool> {
+        let result =
+            sqlx::query("UPDATE refresh_tokens SET used_at = $2 WHERE token_hash = $1 AND used_at IS NULL")
+                .bind(token_hash)
+                .bind(used_at)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| AllSourceError::StorageError(format!("Failed to update refresh token: {}", e)))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn delete_session_refresh_tokens(&self, session_id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM refresh_tokens WHERE session_id = $1")
+            .bind(session_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| AllSourceError::StorageError(format!("Failed to delete refresh tokens: {}", e)))?;
+        Ok(())
+    }
+
+    async fn save_revocation(&self, revocation: &Revocation) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO revocations (kind, id, expires_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (kind, id) DO UPDATE SET
+                expires_at = GREATEST(revocations.expires_at, EXCLUDED.expires_at)
+            "#,
+        )
+        .bind(Self::kind_to_str(revocation.kind))
+        .bind(&revocation.id)
+        .bind(revocation.expires_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AllSourceError::StorageError(format!("Failed to save revocation: {}", e)))?;
+        Ok(())
+    }
+
+    async fn find_revocations(&self, now: DateTime<Utc>) -> Result<Vec<Revocation>> {
+        let rows = sqlx::query("SELECT kind, id, expires_at FROM revocations WHERE expires_at > $1")
+            .bind(now)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| AllSourceError::StorageError(format!("Failed to list revocations: {}", e)))?;
+        rows.iter().map(Self::row_to_revocation).collect()
+    }
+
+    async fn prune(&self, now: DateTime<Utc>) -> Result<usize> {
+        let tokens = sqlx::query("DELETE FROM refresh_tokens WHERE expires_at <= $1")
+            .bind(now)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| AllSourceError::StorageError(format!("Failed to prune refresh tokens: {}", e)))?;
+        let revocations = sqlx::query("DELETE FROM revocations WHERE expires_at <= $1")
+            .bind(now)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| AllSourceError::StorageError(format!("Failed to prune revocations: {}", e)))?;
+        Ok((tokens.rows_affected() + revocations.rows_affected()) as usize)
+    }
+}
@@ -0,0 +1,898 @@
+//! PostgreSQL Event Store
+//!
+//! Complete Postgres backend for events, used by the `postgres` storage
+//! engine:
+//! - **Global ordering**: every event gets a position from a sequence.
+//!   Writers hold an advisory lock from `nextval()` until commit, so
+//!   positions become visible in order and readers never skip one. The
+//!   lock is global: appends from all nodes commit one at a time, which
+//!   bounds write throughput by a single transaction's round trips
+//! - **Indexes**: entity, event type, ID and timestamp (as `EventIndex`),
+//!   tenant, plus GIN indexes for JSONB payload and metadata filters.
+//!   Store queries (`GET /api/v1/events/query`, including
+//!   `payload_contains`) are answered here through `query_events`, so
+//!   they see every node's writes as soon as they commit
+//! - **Time partitioning**: `event_log` is range-partitioned by month;
+//!   partitions are created on first write
+//! - **Live streaming**: each insert is announced with NOTIFY, and
+//!   [`PostgresEventFeed`] applies events written by other API nodes to
+//!   the local store and its WebSocket clients
+//!
+//! API nodes keep no state of their own: the database is the only copy.
+//! Each node still loads the event log at startup into the in-memory
+//! store that drives projections, snapshots and WebSocket streams, so its
+//! memory grows with the log as with the native engine.
+//!
+//! Schema: `migrations/007_event_log.sql`.
+
+#[cfg(feature = "postgres")]
+use crate::domain::entities::{Event, EventStream};
+#[cfg(feature = "postgres")]
+use crate::domain::repositories::{EventStreamQuery, EventStreamRepository};
+#[cfg(feature = "postgres")]
+use crate::domain::value_objects::{EntityId, PartitionKey, TenantId};
+#[cfg(feature = "postgres")]
+use crate::error::{AllSourceError, Result};
+#[cfg(feature = "postgres")]
+use crate::store::{EventStore, EventStoreConfig};
+#[cfg(feature = "postgres")]
+use async_trait::async_trait;
+#[cfg(feature = "postgres")]
+use chrono::{DateTime, Datelike, Utc};
+#[cfg(feature = "postgres")]
+use parking_lot::Mutex;
+#[cfg(feature = "postgres")]
+use sqlx::postgres::{PgListener, PgPool, PgPoolOptions, PgRow};
+#[cfg(feature = "postgres")]
+use sqlx::Row;
+#[cfg(feature = "postgres")]
+use std::collections::HashSet;
+#[cfg(feature = "postgres")]
+use std::sync::atomic::{AtomicI64, Ordering};
+#[cfg(feature = "postgres")]
+use std::sync::Arc;
+#[cfg(feature = "postgres")]
+use std::time::Duration;
+
+/// Channel on which committed event positions are announced
+#[cfg(feature = "postgres")]
+pub const EVENT_CHANNEL: &str = "allsource_events";
+
+/// Advisory lock serializing writers across every node sharing the
+/// database. Sequence values are handed out before commit, so without it
+/// a later position could become visible first and `read_after` (the
+/// feed) would skip the earlier one for good.
+#[cfg(feature = "postgres")]
+const WRITE_LOCK_KEY: i64 = 0x616c_6c73_6f75_7263;
+
+#[cfg(feature = "postgres")]
+const EVENT_COLUMNS: &str =
+    "position, id, tenant_id, event_type, entity_id, version, payload, metadata, timestamp";
+
+/// An event with its global position
+#[cfg(feature = "postgres")]
+#[derive(Debug, Clone)]
+pub struct StoredEvent {
+    pub position: i64,
+    pub event: Event,
+}
+
+/// Filter for [`PostgresEventStore::query`]; unset fields match everything
+#[cfg(feature = "postgres")]
+#[derive(Debug, Clone, Default)]
+pub struct PostgresEventFilter {
+    pub entity_id: Option<String>,
+    pub event_type: Option<String>,
+    pub tenant_id: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    /// JSON the payload must contain (`payload @> ...`)
+    pub payload_contains: Option<serde_json::Value>,
+    /// Only events after this global position
+    pub after_position: Option<i64>,
+    pub limit: Option<usize>,
+}
+
+#[cfg(feature = "postgres")]
+pub struct PostgresEventStore {
+    pool: PgPool,
+    /// Monthly partitions known to exist, as (year, month)
+    partitions: Mutex<HashSet<(i32, u32)>>,
+}
+
+#[cfg(feature = "postgres")]
+impl PostgresEventStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            partitions: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Connect to `url` and apply pending migrations
+    pub async fn connect(url: &str, max_connections: u32) -> Result<Self> {
+        let pool = PgPoolOptions::new()
+            .max_connections(max_connections)
+            .connect(url)
+            .await
+            .map_err(|e| AllSourceError::StorageError(format!("Failed to connect to PostgreSQL: {}", e)))?;
+        let store = Self::new(pool);
+        store.migrate().await?;
+        Ok(store)
+    }
+
+    /// Run database migrations
+    pub async fn migrate(&self) -> Result<()> {
+        sqlx::migrate!("./migrations")
+            .run(&self.pool)
+            .await
+            .map_err(|e| AllSourceError::StorageError(format!("Migration failed: {}", e)))?;
+        Ok(())
+    }
+
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+
+    /// Append an event to the end of its entity's stream, failing with
+    /// `ConcurrencyError` if `expected_version` is set and the stream is
+    /// at another version. The event is stored with its new version.
+    pub async fn append(&self, mut event: Event, expected_version: Option<u64>) -> Result<StoredEvent> {
+        self.ensure_partition(event.timestamp).await?;
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(WRITE_LOCK_KEY)
+            .execute(&mut *tx)
+            .await?;
+
+        let current: Option<i64> = sqlx::query_scalar(
+            "SELECT version FROM event_log_streams WHERE entity_id = $1 FOR UPDATE",
+        )
+        .bind(event.entity_id_str())
+        .fetch_optional(&mut *tx)
+        .await?;
+        let current_version = current.unwrap_or(0) as u64;
+        if let Some(expected) = expected_version {
+            if expected != current_version {
+                return Err(AllSourceError::ConcurrencyError(format!(
+                    "Version conflict: expected {}, got {}",
+                    expected, current_version
+                )));
+            }
+        }
+        event.version = current_version as i64 + 1;
+
+        sqlx::query(
+            "INSERT INTO event_log_streams (entity_id, tenant_id, partition_id, version)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (entity_id) DO UPDATE SET version = EXCLUDED.version, updated_at = NOW()",
+        )
+        .bind(event.entity_id_str())
+        .bind(event.tenant_id_str())
+        .bind(PartitionKey::from_entity_id(event.entity_id_str()).partition_id() as i32)
+        .bind(event.version)
+        .execute(&mut *tx)
+        .await?;
+
+        let position: i64 = sqlx::query_scalar(
+            "INSERT INTO event_log
+             (position, id, tenant_id, event_type, entity_id, version, payload, metadata, timestamp)
+             VALUES (nextval('event_log_position_seq'), $1, $2, $3, $4, $5, $6, $7, $8)
+             RETURNING position",
+        )
+        .bind(event.id)
+        .bind(event.tenant_id_str())
+        .bind(event.event_type_str())
+        .bind(event.entity_id_str())
+        .bind(event.version)
+        .bind(&event.payload)
+        .bind(&event.metadata)
+        .bind(event.timestamp)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(StoredEvent { position, event })
+    }
+
+    /// Events matching `filter`, in global order
+    pub async fn query(&self, filter: &PostgresEventFilter) -> Result<Vec<StoredEvent>> {
+        let mut sql = format!("SELECT {} FROM event_log WHERE TRUE", EVENT_COLUMNS);
+        let mut param_count = 0;
+        let mut condition = |sql: &mut String, clause: &str| {
+            param_count += 1;
+            sql.push_str(&format!(" AND {} ${}", clause, param_count));
+        };
+
+        if filter.entity_id.is_some() {
+            condition(&mut sql, "entity_id =");
+        }
+        if filter.event_type.is_some() {
+            condition(&mut sql, "event_type =");
+        }
+        if filter.tenant_id.is_some() {
+            condition(&mut sql, "tenant_id =");
+        }
+        if filter.since.is_some() {
+            condition(&mut sql, "timestamp >=");
+        }
+        if filter.until.is_some() {
+            condition(&mut sql, "timestamp <=");
+        }
+        if filter.payload_contains.is_some() {
+            condition(&mut sql, "payload @>");
+        }
+        if filter.after_position.is_some() {
+            condition(&mut sql, "position >");
+        }
+        sql.push_str(" ORDER BY position");
+        if filter.limit.is_some() {
+            param_count += 1;
+            sql.push_str(&format!(" LIMIT ${}", param_count));
+        }
+
+        let mut query = sqlx::query(&sql);
+        if let Some(ref entity_id) = filter.entity_id {
+            query = query.bind(entity_id);
+        }
+        if let Some(ref event_type) = filter.event_type {
+            query = query.bind(event_type);
+        }
+        if let Some(ref tenant_id) = filter.tenant_id {
+            query = query.bind(tenant_id);
+        }
+        if let Some(since) = filter.since {
+            query = query.bind(since);
+        }
+        if let Some(until) = filter.until {
+            query = query.bind(until);
+        }
+        if let Some(ref payload) = filter.payload_contains {
+            query = query.bind(payload);
+        }
+        if let Some(position) = filter.after_position {
+            query = query.bind(position);
+        }
+        if let Some(limit) = filter.limit {
+            query = query.bind(limit as i64);
+        }
+
+        let rows = query.fetch_all(&self.pool).await?;
+        rows.iter().map(Self::row_to_stored_event).collect()
+    }
+
+    /// Up to `limit` events after `position`, in global order
+    pub async fn read_after(&self, position: i64, limit: usize) -> Result<Vec<StoredEvent>> {
+        self.query(&PostgresEventFilter {
+            after_position: Some(position),
+            limit: Some(limit),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Position of the latest committed event, 0 if there are none
+    pub async fn head_position(&self) -> Result<i64> {
+        let position: i64 = sqlx::query_scalar("SELECT COALESCE(MAX(position), 0) FROM event_log")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(position)
+    }
+
+    /// Make sure the monthly partition for `timestamp` exists
+    async fn ensure_partition(&self, timestamp: DateTime<Utc>) -> Result<()> {
+        let month = (timestamp.year(), timestamp.month());
+        if self.partitions.lock().contains(&month) {
+            return Ok(());
+        }
+
+        let partition: String = sqlx::query_scalar("SELECT event_log_ensure_partition($1)")
+            .bind(timestamp)
+            .fetch_one(&self.pool)
+            .await?;
+        tracing::debug!("Event log partition {} ready", partition);
+        self.partitions.lock().insert(month);
+        Ok(())
+    }
+
+    fn row_to_stored_event(row: &PgRow) -> Result<StoredEvent> {
+        Ok(StoredEvent {
+            position: row.try_get("position")?,
+            event: Event::reconstruct_from_strings(
+                row.try_get("id")?,
+                row.try_get("event_type")?,
+                row.try_get("entity_id")?,
+                row.try_get("tenant_id")?,
+                row.try_get("payload")?,
+                row.try_get("timestamp")?,
+                row.try_get("metadata")?,
+                row.try_get("version")?,
+            ),
+        })
+    }
+
+    /// Rebuild a stream from its head row and events
+    async fn load_stream_row(&self, row: &PgRow) -> Result<EventStream> {
+        let entity_id: String = row.try_get("entity_id")?;
+        let partition_id: i32 = row.try_get("partition_id")?;
+        let version: i64 = row.try_get("version")?;
+
+        let events = self
+            .query(&PostgresEventFilter {
+                entity_id: Some(entity_id.clone()),
+                ..Default::default()
+            })
+            .await?
+            .into_iter()
+            .map(|stored| stored.event)
+            .collect();
+
+        EventStream::reconstruct(
+            EntityId::new(entity_id)?,
+            PartitionKey::from_partition_id(partition_id as u32, PartitionKey::DEFAULT_PARTITION_COUNT)?,
+            version as u64,
+            version as u64,
+            events,
+            None,
+            row.try_get("created_at")?,
+            row.try_get("updated_at")?,
+        )
+    }
+
+    async fn load_stream_rows(&self, rows: Vec<PgRow>) -> Result<Vec<EventStream>> {
+        let mut streams = Vec::with_capacity(rows.len());
+        for row in &rows {
+            streams.push(self.load_stream_row(row).await?);
+        }
+        Ok(streams)
+    }
+}
+
+/// Streams exist once they hold an event; empty streams are not stored.
+#[cfg(feature = "postgres")]
+#[async_trait]
+impl EventStreamRepository for PostgresEventStore {
+    async fn get_or_create_stream(&self, stream_id: &EntityId) -> Result<EventStream> {
+        Ok(self
+            .load_stream(stream_id)
+            .await?
+            .unwrap_or_else(|| EventStream::new(stream_id.clone())))
+    }
+
+    async fn append_to_stream(&self, stream: &mut EventStream, event: Event) -> Result<u64> {
+        // The stream must still be current, whether or not a version was expected
+        let previous = stream.current_version();
+        stream.append_event(event.clone())?;
+        let stored = self.append(event, Some(previous)).await?;
+        Ok(stored.event.version as u64)
+    }
+
+    async fn save_stream(&self, stream: &EventStream) -> Result<()> {
+        let stored = self.get_watermark(stream.stream_id()).await?;
+        for (version, event) in (stored + 1..).zip(stream.events_from(stored + 1)) {
+            self.append(event.clone(), Some(version - 1)).await?;
+        }
+        Ok(())
+    }
+
+    async fn load_stream(&self, stream_id: &EntityId) -> Result<Option<EventStream>> {
+        let row = sqlx::query(
+            "SELECT entity_id, partition_id, version, created_at, updated_at
+             FROM event_log_streams WHERE entity_id = $1",
+        )
+        .bind(stream_id.as_str())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => Ok(Some(self.load_stream_row(&row).await?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn get_streams_by_partition(&self, partition_key: &PartitionKey) -> Result<Vec<EventStream>> {
+        let rows = sqlx::query(
+            "SELECT entity_id, partition_id, version, created_at, updated_at
+             FROM event_log_streams WHERE partition_id = $1 ORDER BY entity_id",
+        )
+        .bind(partition_key.partition_id() as i32)
+        .fetch_all(&self.pool)
+        .await?;
+        self.load_stream_rows(rows).await
+    }
+
+    async fn get_watermark(&self, stream_id: &EntityId) -> Result<u64> {
+        let version: Option<i64> =
+            sqlx::query_scalar("SELECT version FROM event_log_streams WHERE entity_id = $1")
+                .bind(stream_id.as_str())
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(version.unwrap_or(0) as u64)
+    }
+
+    async fn verify_gapless(&self, stream_id: &EntityId) -> Result<bool> {
+        let (count, max_version): (i64, i64) = sqlx::query_as(
+            "SELECT COUNT(DISTINCT version), COALESCE(MAX(version), 0) FROM event_log WHERE entity_id = $1",
+        )
+        .bind(stream_id.as_str())
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(count == max_version && max_version as u64 == self.get_watermark(stream_id).await?)
+    }
+
+    async fn count_streams(&self) -> Result<usize> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM event_log_streams")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count as usize)
+    }
+
+    async fn partition_stats(&self) -> Result<Vec<(u32, usize)>> {
+        let rows: Vec<(i32, i64)> = sqlx::query_as(
+            "SELECT partition_id, COUNT(*) FROM event_log_streams GROUP BY partition_id ORDER BY partition_id",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(|(p, c)| (p as u32, c as usize)).collect())
+    }
+
+    async fn get_streams_by_tenant(&self, tenant_id: &TenantId) -> Result<Vec<EventStream>> {
+        let rows = sqlx::query(
+            "SELECT entity_id, partition_id, version, created_at, updated_at
+             FROM event_log_streams WHERE tenant_id = $1 ORDER BY entity_id",
+        )
+        .bind(tenant_id.as_str())
+        .fetch_all(&self.pool)
+        .await?;
+        self.load_stream_rows(rows).await
+    }
+
+    async fn count_streams_by_tenant(&self, tenant_id: &TenantId) -> Result<usize> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM event_log_streams WHERE tenant_id = $1")
+            .bind(tenant_id.as_str())
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count as usize)
+    }
+
+    async fn append_event(&self, event: Event) -> Result<u64> {
+        Ok(self.append(event, None).await?.event.version as u64)
+    }
+
+    async fn load_all_events(&self) -> Result<Vec<Event>> {
+        let rows = sqlx::query(&format!("SELECT {} FROM event_log ORDER BY position", EVENT_COLUMNS))
+            .fetch_all(&self.pool)
+            .await?;
+        rows.iter()
+            .map(|row| Self::row_to_stored_event(row).map(|stored| stored.event))
+            .collect()
+    }
+
+    async fn query_events(&self, query: &EventStreamQuery) -> Result<Option<Vec<Event>>> {
+        let filter = PostgresEventFilter {
+            entity_id: query.entity_id.clone(),
+            event_type: query.event_type.clone(),
+            tenant_id: query.tenant_id.clone(),
+            since: query.since,
+            until: query.until,
+            payload_contains: query.payload_contains.clone(),
+            after_position: None,
+            limit: query.limit,
+        };
+        let events = self.query(&filter).await?;
+        Ok(Some(events.into_iter().map(|stored| stored.event).collect()))
+    }
+}
+
+/// Live feed configuration
+#[cfg(feature = "postgres")]
+#[derive(Debug, Clone)]
+pub struct PostgresEventFeedConfig {
+    /// Catch up at least this often, in case a notification was missed
+    pub poll_interval: Duration,
+
+    /// Events read per query while catching up
+    pub batch_size: usize,
+}
+
+#[cfg(feature = "postgres")]
+impl Default for PostgresEventFeedConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(5),
+            batch_size: 1000,
+        }
+    }
+}
+
+/// Applies events committed to the shared database by any node to a
+/// local [`EventStore`], which indexes them and streams them to its
+/// WebSocket clients. Own writes come back too and are skipped.
+#[cfg(feature = "postgres")]
+pub struct PostgresEventFeed {
+    engine: Arc<PostgresEventStore>,
+    config: PostgresEventFeedConfig,
+    /// Last position applied
+    position: AtomicI64,
+}
+
+#[cfg(feature = "postgres")]
+impl PostgresEventFeed {
+    /// Follow events committed after the current head
+    pub async fn new(engine: Arc<PostgresEventStore>, config: PostgresEventFeedConfig) -> Result<Self> {
+        let position = engine.head_position().await?;
+        Ok(Self {
+            engine,
+            config,
+            position: AtomicI64::new(position),
+        })
+    }
+
+    /// Open an event store on `engine` together with the feed that keeps it
+    /// current. The feed starts before the store loads, so nothing written
+    /// in between is missed; spawn it once the store is shared.
+    pub async fn open_store(
+        engine: Arc<PostgresEventStore>,
+        store_config: EventStoreConfig,
+        config: PostgresEventFeedConfig,
+    ) -> Result<(EventStore, Self)> {
+        let feed = Self::new(engine.clone(), config).await?;
+        let store = EventStore::with_engine(store_config, engine).await?;
+        Ok((store, feed))
+    }
+
+    /// Last global position applied to the store
+    pub fn position(&self) -> i64 {
+        self.position.load(Ordering::SeqCst)
+    }
+
+    /// Listen for notifications and catch up whenever one arrives, or
+    /// every `poll_interval`
+    pub fn spawn(self: Arc<Self>, store: Arc<EventStore>) -> tokio::task::JoinHandle<()> {
+        let wake = Arc::new(tokio::sync::Notify::new());
+
+        let listener_wake = wake.clone();
+        let pool = self.engine.pool().clone();
+        let retry = self.config.poll_interval;
+        tokio::spawn(async move {
+            loop {
+                match Self::listen(&pool, &listener_wake).await {
+                    Ok(()) => {}
+                    Err(e) => tracing::warn!("⚠️  Event notification listener failed: {}", e),
+                }
+                tokio::time::sleep(retry).await;
+            }
+        });
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.config.poll_interval);
+            loop {
+                tokio::select! {
+                    _ = wake.notified() => {}
+                    _ = interval.tick() => {}
+                }
+                if let Err(e) = self.catch_up(&store).await {
+                    tracing::warn!("⚠️  Failed to apply shared events: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Wake the feed on every notification until the listener fails. A
+    /// lost connection also wakes it, since notifications may have been
+    /// missed while reconnecting.
+    async fn listen(pool: &PgPool, wake: &tokio::sync::Notify) -> Result<()> {
+        let mut listener = PgListener::connect_with(pool).await?;
+        listener.listen(EVENT_CHANNEL).await?;
+        tracing::info!("📡 Listening for shared events on '{}'", EVENT_CHANNEL);
+        wake.notify_one();
+
+        loop {
+            if listener.try_recv().await?.is_none() {
+                tracing::warn!("⚠️  Lost the event notification connection; reconnecting");
+            }
+            wake.notify_one();
+        }
+    }
+
+    /// Apply every event committed after the current position. Returns the
+    /// number of events read.
+    pub async fn catch_up(&self, store: &Arc<EventStore>) -> Result<usize> {
+        let mut applied = 0;
+        loop {
+            let batch = self
+                .engine
+                .read_after(self.position(), self.config.batch_size)
+                .await?;
+            let Some(last) = batch.last().map(|stored| stored.position) else {
+                return Ok(applied);
+            };
+            applied += batch.len();
+
+            // Committing takes the store's write lock; keep it off the runtime
+            let store = store.clone();
+            tokio::task::spawn_blocking(move || {
+                batch
+                    .into_iter()
+                    .try_for_each(|stored| store.commit_shared(stored.event))
+            })
+            .await
+            .map_err(|e| AllSourceError::InternalError(format!("Shared event task failed: {}", e)))??;
+
+            self.position.store(last, Ordering::SeqCst);
+        }
+    }
+}
+
+/// Run against the database at `ALLSOURCE_TEST_DATABASE_URL`, if set.
+/// Entity IDs are unique per run, so the database can be reused.
+#[cfg(all(test, feature = "postgres"))]
+mod tests {
+    use super::*;
+    use crate::application::dto::QueryEventsRequest;
+    use crate::test_support::{event, eventually};
+    use chrono::TimeZone;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    async fn engine() -> Option<Arc<PostgresEventStore>> {
+        let url = std::env::var("ALLSOURCE_TEST_DATABASE_URL").ok()?;
+        Some(Arc::new(PostgresEventStore::connect(&url, 5).await.unwrap()))
+    }
+
+    fn entity(name: &str) -> String {
+        format!("{}-{}", name, Uuid::new_v4())
+    }
+
+    #[tokio::test]
+    async fn test_global_positions_and_gapless_versions() {
+        let Some(engine) = engine().await else { return };
+        let order = entity("order");
+        let other = entity("order");
+
+        let mut positions = Vec::new();
+        for (i, entity_id) in [&order, &other, &order, &order].into_iter().enumerate() {
+            let stored = engine
+                .append(event(entity_id, "order.updated", json!({"step": i})), None)
+                .await
+                .unwrap();
+            positions.push(stored.position);
+        }
+        assert!(positions.windows(2).all(|w| w[0] < w[1]));
+
+        let events = engine
+            .query(&PostgresEventFilter {
+                entity_id: Some(order.clone()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let versions: Vec<i64> = events.iter().map(|e| e.event.version).collect();
+        assert_eq!(versions, vec![1, 2, 3]);
+
+        let read = engine.read_after(positions[0], 10).await.unwrap();
+        assert_eq!(read.first().map(|e| e.position), Some(positions[1]));
+
+        let order_id = EntityId::new(order.clone()).unwrap();
+        assert_eq!(engine.get_watermark(&order_id).await.unwrap(), 3);
+        assert!(engine.verify_gapless(&order_id).await.unwrap());
+        let stream = engine.load_stream(&order_id).await.unwrap().unwrap();
+        assert_eq!(stream.current_version(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_optimistic_lock() {
+        let Some(engine) = engine().await else { return };
+        let order = entity("order");
+
+        engine
+            .append(event(&order, "order.placed", json!({})), Some(0))
+            .await
+            .unwrap();
+        assert!(matches!(
+            engine
+                .append(event(&order, "order.paid", json!({})), Some(0))
+                .await,
+            Err(AllSourceError::ConcurrencyError(_))
+        ));
+
+        // A stream object that has fallen behind cannot append
+        let order_id = EntityId::new(order.clone()).unwrap();
+        let mut stale = engine.get_or_create_stream(&order_id).await.unwrap();
+        engine.append_event(event(&order, "order.paid", json!({}))).await.unwrap();
+        assert!(matches!(
+            engine
+                .append_to_stream(&mut stale, event(&order, "order.shipped", json!({})))
+                .await,
+            Err(AllSourceError::ConcurrencyError(_))
+        ));
+        assert_eq!(engine.get_watermark(&order_id).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_filters_and_payload_containment() {
+        let Some(engine) = engine().await else { return };
+        let order = entity("order");
+
+        engine
+            .append(event(&order, "order.placed", json!({"status": "new", "total": 10})), None)
+            .await
+            .unwrap();
+        engine
+            .append(event(&order, "order.paid", json!({"status": "paid", "total": 10})), None)
+            .await
+            .unwrap();
+
+        let paid = engine
+            .query(&PostgresEventFilter {
+                entity_id: Some(order.clone()),
+                payload_contains: Some(json!({"status": "paid"})),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(paid.len(), 1);
+        assert_eq!(paid[0].event.event_type_str(), "order.paid");
+
+        let placed = engine
+            .query(&PostgresEventFilter {
+                entity_id: Some(order.clone()),
+                event_type: Some("order.placed".to_string()),
+                tenant_id: Some("default".to_string()),
+                since: Some(Utc::now() - chrono::Duration::minutes(1)),
+                until: Some(Utc::now()),
+                limit: Some(5),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(placed.len(), 1);
+        assert_eq!(placed[0].event.payload["status"], "new");
+    }
+
+    #[tokio::test]
+    async fn test_events_are_partitioned_by_month() {
+        let Some(engine) = engine().await else { return };
+        let order = entity("order");
+
+        let mut partitions = Vec::new();
+        for month in [1, 2] {
+            let placed = event(&order, "order.placed", json!({}));
+            let event = Event::reconstruct(
+                placed.id,
+                placed.event_type.clone(),
+                placed.entity_id.clone(),
+                placed.tenant_id.clone(),
+                json!({"month": month}),
+                Utc.with_ymd_and_hms(2021, month, 15, 12, 0, 0).unwrap(),
+                None,
+                1,
+            );
+            engine.append(event.clone(), None).await.unwrap();
+
+            let partition: String =
+                sqlx::query_scalar("SELECT tableoid::regclass::text FROM event_log WHERE id = $1")
+                    .bind(event.id)
+                    .fetch_one(engine.pool())
+                    .await
+                    .unwrap();
+            partitions.push(partition);
+        }
+        assert_eq!(partitions, vec!["event_log_2021_01", "event_log_2021_02"]);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_writers_keep_versions_gapless() {
+        let Some(first) = engine().await else { return };
+        let second = engine().await.unwrap();
+        let order = entity("order");
+
+        let mut writers = Vec::new();
+        for engine in [first.clone(), second] {
+            let order = order.clone();
+            writers.push(tokio::spawn(async move {
+                for i in 0..10 {
+                    engine
+                        .append_event(event(&order, "order.updated", json!({"step": i})))
+                        .await
+                        .unwrap();
+                }
+            }));
+        }
+        for writer in writers {
+            writer.await.unwrap();
+        }
+
+        let order_id = EntityId::new(order).unwrap();
+        let stream = first.load_stream(&order_id).await.unwrap().unwrap();
+        let versions: Vec<i64> = stream.events_from(1).iter().map(|e| e.version).collect();
+        assert_eq!(versions, (1..=20).collect::<Vec<_>>());
+        assert!(first.verify_gapless(&order_id).await.unwrap());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_nodes_sharing_a_database_see_each_others_writes() {
+        let Some(first_engine) = engine().await else { return };
+        let second_engine = engine().await.unwrap();
+        let config = PostgresEventFeedConfig {
+            poll_interval: Duration::from_secs(30),
+            ..Default::default()
+        };
+
+        let mut nodes = Vec::new();
+        for engine in [first_engine, second_engine] {
+            let (store, feed) = PostgresEventFeed::open_store(engine, EventStoreConfig::default(), config.clone())
+                .await
+                .unwrap();
+            let store = Arc::new(store);
+            let feed = Arc::new(feed);
+            feed.clone().spawn(store.clone());
+            nodes.push((store, feed));
+        }
+        let (first, _) = &nodes[0];
+        let (second, second_feed) = &nodes[1];
+
+        // Wait until the second node is listening before writing
+        let start = second_feed.position();
+        assert!(eventually(Duration::from_secs(5), || second_feed.position() >= start).await);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let mut live = second.websocket_manager().subscribe();
+
+        let order = entity("order");
+        first
+            .ingest(event(&order, "order.placed", json!({"total": 10})))
+            .unwrap();
+        first
+            .ingest(event(&order, "order.paid", json!({"total": 10})))
+            .unwrap();
+
+        // Delivered by NOTIFY well before the 30s poll
+        let received = tokio::time::timeout(Duration::from_secs(5), live.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(received.entity_id_str(), order);
+
+        // Queries are answered by the database, payload filters included
+        let query = |store: &EventStore, payload_contains: Option<&str>| {
+            store
+                .query(QueryEventsRequest {
+                    entity_id: Some(order.clone()),
+                    event_type: None,
+                    tenant_id: None,
+                    as_of: None,
+                    since: None,
+                    until: None,
+                    limit: None,
+                    payload_contains: payload_contains.map(str::to_string),
+                })
+                .unwrap()
+        };
+        let versions: Vec<i64> = query(second, None).iter().map(|e| e.version).collect();
+        assert_eq!(versions, vec![1, 2]);
+        assert_eq!(query(second, Some(r#"{"total": 10}"#)).len(), 2);
+        assert!(query(second, Some(r#"{"total": 11}"#)).is_empty());
+
+        // Writes from the second node continue the same stream, and the
+        // first node does not apply its own writes twice
+        let mut first_live = first.websocket_manager().subscribe();
+        second
+            .ingest(event(&order, "order.shipped", json!({})))
+            .unwrap();
+        let mut shipped = Vec::new();
+        let _ = tokio::time::timeout(Duration::from_secs(2), async {
+            while let Ok(event) = first_live.recv().await {
+                if event.entity_id_str() == order {
+                    shipped.push(event);
+                }
+            }
+        })
+        .await;
+        assert_eq!(shipped.len(), 1);
+        assert_eq!(shipped[0].version, 3);
+        assert_eq!(query(first, None).len(), 3);
+        let state = first.reconstruct_state(&order, None).unwrap();
+        assert_eq!(state["current_state"]["total"], 10);
+    }
+}
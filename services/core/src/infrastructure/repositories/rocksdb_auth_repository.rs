@@ -0,0 +1,415 @@
+//! RocksDB-backed User and API Key Repository
+//!
+//! Embedded persistent account storage for single-node deployments.
+//!
+//! # Column Family Design
+//! - **users**: user_id -> UserRecord (JSON)
+//! - **usernames**: username -> user_id
+//! - **api_keys**: key_id -> ApiKeyRecord (JSON)
+//! - **api_key_hashes**: key_hash -> key_id
+//! - **refresh_tokens**: token_hash -> RefreshToken (JSON)
+//! - **revocations**: `session:<id>` or `token:<jti>` -> Revocation (JSON)
+//!
+//! Records and their index entries are written in one `WriteBatch`.
+
+#[cfg(feature = "rocksdb-storage")]
+use async_trait::async_trait;
+#[cfg(feature = "rocksdb-storage")]
+use chrono::{DateTime, Utc};
+#[cfg(feature = "rocksdb-storage")]
+use rocksdb::{ColumnFamilyDescriptor, IteratorMode, Options, WriteBatch, DB};
+#[cfg(feature = "rocksdb-storage")]
+use std::path::Path;
+#[cfg(feature = "rocksdb-storage")]
+use std::sync::Arc;
+#[cfg(feature = "rocksdb-storage")]
+use uuid::Uuid;
+
+#[cfg(feature = "rocksdb-storage")]
+use super::file_auth_repository::{ApiKeyRecord, UserRecord};
+#[cfg(feature = "rocksdb-storage")]
+use crate::auth::{ApiKey, RefreshToken, Revocation, RevocationKind, User};
+#[cfg(feature = "rocksdb-storage")]
+use crate::domain::repositories::{ApiKeyRepository, SessionRepository, UserRepository};
+#[cfg(feature = "rocksdb-storage")]
+use crate::error::{AllSourceError, Result};
+
+#[cfg(feature = "rocksdb-storage")]
+const CF_USERS: &str = "users";
+#[cfg(feature = "rocksdb-storage")]
+const CF_USERNAMES: &str = "usernames";
+#[cfg(feature = "rocksdb-storage")]
+const CF_API_KEYS: &str = "api_keys";
+#[cfg(feature = "rocksdb-storage")]
+const CF_API_KEY_HASHES: &str = "api_key_hashes";
+#[cfg(feature = "rocksdb-storage")]
+const CF_REFRESH_TOKENS: &str = "refresh_tokens";
+#[cfg(feature = "rocksdb-storage")]
+const CF_REVOCATIONS: &str = "revocations";
+
+#[cfg(feature = "rocksdb-storage")]
+pub struct RocksDBAuthRepository {
+    db: Arc<DB>,
+    /// Serializes read-modify-write sequences (index maintenance)
+    write_lock: parking_lot::Mutex<()>,
+}
+
+#[cfg(feature = "rocksdb-storage")]
+impl RocksDBAuthRepository {
+    /// Open (or create) the repository at `path`
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+
+        let cfs = [
+            CF_USERS,
+            CF_USERNAMES,
+            CF_API_KEYS,
+            CF_API_KEY_HASHES,
+            CF_REFRESH_TOKENS,
+            CF_REVOCATIONS,
+        ]
+        .into_iter()
+            .map(|name| ColumnFamilyDescriptor::new(name, Options::default()))
+            .collect::<Vec<_>>();
+
+        let db = DB::open_cf_descriptors(&opts, path, cfs)
+            .map_err(|e| AllSourceError::StorageError(format!("Failed to open RocksDB: {}", e)))?;
+
+        Ok(Self {
+            db: Arc::new(db),
+            write_lock: parking_lot::Mutex::new(()),
+        })
+    }
+
+    fn cf(&self, name: &str) -> Result<&rocksdb::ColumnFamily> {
+        self.db
+            .cf_handle(name)
+            .ok_or_else(|| AllSourceError::StorageError(format!("{} CF not found", name)))
+    }
+
+    fn get_json<T: serde::de::DeserializeOwned>(&self, cf: &str, key: &[u8]) -> Result<Option<T>> {
+        let data = self
+            .db
+            .get_cf(self.cf(cf)?, key)
+            .map_err(|e| AllSourceError::StorageError(format!("Failed to read {}: {}", cf, e)))?;
+        match data {
+            Some(data) => Ok(Some(serde_json::from_slice(&data)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn get_id(&self, cf: &str, key: &[u8]) -> Result<Option<Uuid>> {
+        let data = self
+            .db
+            .get_cf(self.cf(cf)?, key)
+            .map_err(|e| AllSourceError::StorageError(format!("Failed to read {}: {}", cf, e)))?;
+        match data {
+            Some(data) => Uuid::from_slice(&data)
+                .map(Some)
+                .map_err(|e| AllSourceError::StorageError(format!("Corrupt {} entry: {}", cf, e))),
+            None => Ok(None),
+        }
+    }
+
+    fn scan<T: serde::de::DeserializeOwned>(&self, cf: &str) -> Result<Vec<T>> {
+        let mut records = Vec::new();
+        for item in self.db.iterator_cf(self.cf(cf)?, IteratorMode::Start) {
+            let (_, value) = item
+                .map_err(|e| AllSourceError::StorageError(format!("Failed to scan {}: {}", cf, e)))?;
+            records.push(serde_json::from_slice(&value)?);
+        }
+        Ok(records)
+    }
+
+    fn write(&self, batch: WriteBatch) -> Result<()> {
+        self.db
+            .write(batch)
+            .map_err(|e| AllSourceError::StorageError(format!("Failed to write batch: {}", e)))
+    }
+
+    fn get_user_record(&self, id: &Uuid) -> Result<Option<UserRecord>> {
+        self.get_json(CF_USERS, id.as_bytes())
+    }
+
+    fn get_api_key_record(&self, id: &Uuid) -> Result<Option<ApiKeyRecord>> {
+        self.get_json(CF_API_KEYS, id.as_bytes())
+    }
+
+    fn revocation_key(revocation: &Revocation) -> String {
+        let kind = match revocation.kind {
+            RevocationKind::Session => "session",
+            RevocationKind::Token => "token",
+        };
+        format!("{}:{}", kind, revocation.id)
+    }
+}
+
+#[cfg(feature = "rocksdb-storage")]
+#[async_trait]
+impl UserRepository for RocksDBAuthRepository {
+    async fn save(&self, user: &User) -> Result<()> {
+        let _guard = self.write_lock.lock();
+
+        if let Some(owner) = self.get_id(CF_USERNAMES, user.username.as_bytes())? {
+            if owner != user.id {
+                return Err(AllSourceError::ValidationError(
+                    "Username already exists".to_string(),
+                ));
+            }
+        }
+
+        let mut batch = WriteBatch::default();
+        if let Some(previous) = self.get_user_record(&user.id)? {
+            if previous.username != user.username {
+                batch.delete_cf(self.cf(CF_USERNAMES)?, previous.username.as_bytes());
+            }
+        }
+        batch.put_cf(
+            self.cf(CF_USERS)?,
+            user.id.as_bytes(),
+            serde_json::to_vec(&UserRecord::from(user))?,
+        );
+        batch.put_cf(self.cf(CF_USERNAMES)?, user.username.as_bytes(), user.id.as_bytes());
+        self.write(batch)
+    }
+
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<User>> {
+        Ok(self.get_user_record(id)?.map(User::from))
+    }
+
+    async fn find_by_username(&self, username: &str) -> Result<Option<User>> {
+        match self.get_id(CF_USERNAMES, username.as_bytes())? {
+            Some(id) => Ok(self.get_user_record(&id)?.map(User::from)),
+            None => Ok(None),
+        }
+    }
+
+    async fn find_all(&self) -> Result<Vec<User>> {
+        let mut users: Vec<User> = self
+            .scan::<UserRecord>(CF_USERS)?
+            .into_iter()
+            .map(User::from)
+            .collect();
+        users.sort_by_key(|r| r.created_at);
+        Ok(users)
+    }
+
+    async fn delete(&self, id: &Uuid) -> Result<bool> {
+        let _guard = self.write_lock.lock();
+        let Some(previous) = self.get_user_record(id)? else {
+            return Ok(false);
+        };
+
+        let mut batch = WriteBatch::default();
+        batch.delete_cf(self.cf(CF_USERS)?, id.as_bytes());
+        batch.delete_cf(self.cf(CF_USERNAMES)?, previous.username.as_bytes());
+        self.write(batch)?;
+        Ok(true)
+    }
+}
+
+#[cfg(feature = "rocksdb-storage")]
+#[async_trait]
+impl ApiKeyRepository for RocksDBAuthRepository {
+    async fn save(&self, api_key: &ApiKey) -> Result<()> {
+        let _guard = self.write_lock.lock();
+
+        let mut batch = WriteBatch::default();
+        if let Some(previous) = self.get_api_key_record(&api_key.id)? {
+            if previous.key_hash != api_key.key_hash {
+                batch.delete_cf(self.cf(CF_API_KEY_HASHES)?, previous.key_hash.as_bytes());
+            }
+        }
+        batch.put_cf(
+            self.cf(CF_API_KEYS)?,
+            api_key.id.as_bytes(),
+            serde_json::to_vec(&ApiKeyRecord::from(api_key))?,
+        );
+        batch.put_cf(
+            self.cf(CF_API_KEY_HASHES)?,
+            api_key.key_hash.as_bytes(),
+            api_key.id.as_bytes(),
+        );
+        self.write(batch)
+    }
+
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<ApiKey>> {
+        Ok(self.get_api_key_record(id)?.map(ApiKey::from))
+    }
+
+    async fn find_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>> {
+        match self.get_id(CF_API_KEY_HASHES, key_hash.as_bytes())? {
+            Some(id) => Ok(self.get_api_key_record(&id)?.map(ApiKey::from)),
+            None => Ok(None),
+        }
+    }
+
+    async fn find_by_tenant(&self, tenant_id: &str) -> Result<Vec<ApiKey>> {
+        Ok(ApiKeyRepository::find_all(self)
+            .await?
+            .into_iter()
+            .filter(|k| k.tenant_id == tenant_id)
+            .collect())
+    }
+
+    async fn find_all(&self) -> Result<Vec<ApiKey>> {
+        let mut keys: Vec<ApiKey> = self
+            .scan::<ApiKeyRecord>(CF_API_KEYS)?
+            .into_iter()
+            .map(ApiKey::from)
+            .collect();
+        keys.sort_by_key(|r| r.created_at);
+        Ok(keys)
+    }
+
+    async fn update_last_used(&self, id: &Uuid, last_used: DateTime<Utc>) -> Result<bool> {
+        let _guard = self.write_lock.lock();
+        let Some(mut record) = self.get_api_key_record(id)? else {
+            return Ok(false);
+        };
+        record.last_used = Some(last_used);
+        self.db
+            .put_cf(self.cf(CF_API_KEYS)?, id.as_bytes(), serde_json::to_vec(&record)?)
+            .map_err(|e| AllSourceError::StorageError(format!("Failed to update API key: {}", e)))?;
+        Ok(true)
+    }
+
+    async fn delete(&self, id: &Uuid) -> Result<bool> {
+        let _guard = self.write_lock.lock();
+        let Some(previous) = self.get_api_key_record(id)? else {
+            return Ok(false);
+        };
+
+        let mut batch = WriteBatch::default();
+        batch.delete_cf(self.cf(CF_API_KEYS)?, id.as_bytes());
+        batch.delete_cf(self.cf(CF_API_KEY_HASHES)?, previous.key_hash.as_bytes());
+        self.write(batch)?;
+        Ok(true)
+    }
+}
+
+#[cfg(feature = "rocksdb-storage")]
+#[async_trait]
+impl SessionRepository for RocksDBAuthRepository {
+    async fn save_refresh_token(&self, token: &RefreshToken) -> Result<()> {
+        self.db
+            .put_cf(
+                self.cf(CF_REFRESH_TOKENS)?,
+                token.token_hash.as_bytes(),
+                serde_json::to_vec(token)?,
+            )
+            .map_err(|e| AllSourceError::StorageError(format!("Failed to save refresh token: {}", e)))
+    }
+
+    async fn find_refresh_token(&self, token_hash: &str) -> Result<Option<RefreshToken>> {
+        self.get_json(CF_REFRESH_TOKENS, token_hash.as_bytes())
+    }
+
+    async fn find_refresh_tokens_by_user(&self, user_id: &Uuid) -> Result<Vec<RefreshToken>> {
+        Ok(self
+            .scan::<RefreshToken>(CF_REFRESH_TOKENS)?
+            .into_iter()
+            .filter(|t| &t.user_id == user_id)
+            .collect())
+    }
+
+    async fn mark_refresh_token_used(&self, token_hash: &str, used_at: DateTime<Utc>) -> Result<bool> {
+        let _guard = self.write_lock.lock();
+        let Some(mut token) = self.get_json::<RefreshToken>(CF_REFRESH_TOKENS, token_hash.as_bytes())? else {
+            return Ok(false);
+        };
+        if token.used_at.is_some() {
+            return Ok(false);
+        }
+        token.used_at = Some(used_at);
+        self.db
+            .put_cf(self.cf(CF_REFRESH_TOKENS)?, token_hash.as_bytes(), serde_json::to_vec(&token)?)
+            .map_err(|e| AllSourceError::StorageError(format!("Failed to update refresh token: {}", e)))?;
+        Ok(true)
+    }
+
+    async fn delete_session_refresh_tokens(&self, session_id: &str) -> Result<()> {
+        let _guard = self.write_lock.lock();
+        let mut batch = WriteBatch::default();
+        for token in self.scan::<RefreshToken>(CF_REFRESH_TOKENS)? {
+            if token.session_id == session_id {
+                batch.delete_cf(self.cf(CF_REFRESH_TOKENS)?, token.token_hash.as_bytes());
+            }
+        }
+        self.write(batch)
+    }
+
+    async fn save_revocation(&self, revocation: &Revocation) -> Result<()> {
+        self.db
+            .put_cf(
+                self.cf(CF_REVOCATIONS)?,
+                Self::revocation_key(revocation).as_bytes(),
+                serde_json::to_vec(revocation)?,
+            )
+            .map_err(|e| AllSourceError::StorageError(format!("Failed to save revocation: {}", e)))
+    }
+
+    async fn find_revocations(&self, now: DateTime<Utc>) -> Result<Vec<Revocation>> {
+        Ok(self
+            .scan::<Revocation>(CF_REVOCATIONS)?
+            .into_iter()
+            .filter(|r| r.expires_at > now)
+            .collect())
+    }
+
+    async fn prune(&self, now: DateTime<Utc>) -> Result<usize> {
+        let _guard = self.write_lock.lock();
+        let mut batch = WriteBatch::default();
+        for token in self.scan::<RefreshToken>(CF_REFRESH_TOKENS)? {
+            if token.expires_at <= now {
+                batch.delete_cf(self.cf(CF_REFRESH_TOKENS)?, token.token_hash.as_bytes());
+            }
+        }
+        for revocation in self.scan::<Revocation>(CF_REVOCATIONS)? {
+            if revocation.expires_at <= now {
+                batch.delete_cf(self.cf(CF_REVOCATIONS)?, Self::revocation_key(&revocation).as_bytes());
+            }
+        }
+        let pruned = batch.len();
+        self.write(batch)?;
+        Ok(pruned)
+    }
+}
+
+#[cfg(all(test, feature = "rocksdb-storage"))]
+mod tests {
+    use super::*;
+    use crate::auth::Role;
+
+    #[tokio::test]
+    async fn test_rocksdb_auth_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let user = User::new(
+            "alice".to_string(),
+            "alice@example.com".to_string(),
+            "password123",
+            Role::Admin,
+            "acme".to_string(),
+        )
+        .unwrap();
+        let (api_key, plaintext) =
+            ApiKey::new("ci".to_string(), "acme".to_string(), Role::ServiceAccount, None);
+
+        {
+            let repo = RocksDBAuthRepository::new(dir.path()).unwrap();
+            UserRepository::save(&repo, &user).await.unwrap();
+            ApiKeyRepository::save(&repo, &api_key).await.unwrap();
+        }
+
+        let repo = RocksDBAuthRepository::new(dir.path()).unwrap();
+        let restored = repo.find_by_username("alice").await.unwrap().unwrap();
+        assert!(restored.verify_password("password123").unwrap());
+        let key = repo.find_by_hash(&api_key.key_hash).await.unwrap().unwrap();
+        assert!(key.verify(&plaintext));
+
+        assert!(UserRepository::delete(&repo, &user.id).await.unwrap());
+        assert!(repo.find_by_username("alice").await.unwrap().is_none());
+    }
+}
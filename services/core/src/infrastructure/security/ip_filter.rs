@@ -4,9 +4,12 @@
 /// Supports both global and per-tenant IP restrictions.
 
 use std::collections::HashSet;
+use std::fmt;
 use std::net::IpAddr;
+use std::str::FromStr;
 use std::sync::Arc;
 use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
 
 use crate::domain::value_objects::TenantId;
 use crate::error::{AllSourceError, Result};
@@ -264,10 +267,111 @@ impl Default for IpFilter {
     }
 }
 
+/// An IP network in CIDR notation (`10.0.0.0/8`, `2001:db8::/32`)
+///
+/// A bare address is a single-host network. IPv4 networks also match
+/// IPv4-mapped IPv6 clients (`::ffff:10.0.0.1`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct IpNetwork {
+    addr: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpNetwork {
+    /// Create a network, normalizing host bits away
+    pub fn new(addr: IpAddr, prefix_len: u8) -> Result<Self> {
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        if prefix_len > max {
+            return Err(AllSourceError::InvalidInput(format!(
+                "Invalid prefix length /{} for {}",
+                prefix_len, addr
+            )));
+        }
+        let addr = match addr {
+            IpAddr::V4(v4) => IpAddr::V4((u32::from(v4) & Self::mask_v4(prefix_len)).into()),
+            IpAddr::V6(v6) => IpAddr::V6((u128::from(v6) & Self::mask_v6(prefix_len)).into()),
+        };
+        Ok(Self { addr, prefix_len })
+    }
+
+    /// Network address
+    pub fn addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    /// Prefix length in bits
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    /// Check if the network contains an address
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        match (self.addr, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                u32::from(*ip) & Self::mask_v4(self.prefix_len) == u32::from(net)
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                u128::from(*ip) & Self::mask_v6(self.prefix_len) == u128::from(net)
+            }
+            (IpAddr::V4(_), IpAddr::V6(ip)) => ip
+                .to_ipv4_mapped()
+                .is_some_and(|ip| self.contains(&IpAddr::V4(ip))),
+            (IpAddr::V6(_), IpAddr::V4(_)) => false,
+        }
+    }
+
+    fn mask_v4(prefix_len: u8) -> u32 {
+        u32::MAX.checked_shl(32 - u32::from(prefix_len)).unwrap_or(0)
+    }
+
+    fn mask_v6(prefix_len: u8) -> u128 {
+        u128::MAX.checked_shl(128 - u32::from(prefix_len)).unwrap_or(0)
+    }
+}
+
+impl FromStr for IpNetwork {
+    type Err = AllSourceError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || AllSourceError::InvalidInput(format!("Invalid CIDR: {}", s));
+        let (addr, prefix_len) = match s.trim().split_once('/') {
+            Some((addr, len)) => {
+                let addr = IpAddr::from_str(addr).map_err(|_| invalid())?;
+                (addr, len.parse::<u8>().map_err(|_| invalid())?)
+            }
+            None => {
+                let addr = IpAddr::from_str(s.trim()).map_err(|_| invalid())?;
+                (addr, if addr.is_ipv4() { 32 } else { 128 })
+            }
+        };
+        Self::new(addr, prefix_len)
+    }
+}
+
+impl TryFrom<String> for IpNetwork {
+    type Error = AllSourceError;
+
+    fn try_from(value: String) -> Result<Self> {
+        value.parse()
+    }
+}
+
+impl From<IpNetwork> for String {
+    fn from(network: IpNetwork) -> Self {
+        network.to_string()
+    }
+}
+
+impl fmt::Display for IpNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::str::FromStr;
 
     fn test_ip() -> IpAddr {
         IpAddr::from_str("192.168.1.1").unwrap()
@@ -424,4 +528,27 @@ mod tests {
         assert_eq!(stats.tenant_allowlist_count, 0);
         assert_eq!(stats.tenant_blocklist_count, 0);
     }
+
+    #[test]
+    fn test_ip_network_contains() {
+        let net: IpNetwork = "10.1.2.3/16".parse().unwrap();
+        assert_eq!(net.to_string(), "10.1.0.0/16");
+        assert!(net.contains(&IpAddr::from_str("10.1.200.7").unwrap()));
+        assert!(net.contains(&IpAddr::from_str("::ffff:10.1.0.9").unwrap()));
+        assert!(!net.contains(&test_ip2()));
+
+        let host: IpNetwork = "192.168.1.1".parse().unwrap();
+        assert!(host.contains(&test_ip()));
+        assert!(!host.contains(&IpAddr::from_str("192.168.1.2").unwrap()));
+
+        let any: IpNetwork = "0.0.0.0/0".parse().unwrap();
+        assert!(any.contains(&test_ip()));
+
+        let v6: IpNetwork = "2001:db8::/32".parse().unwrap();
+        assert!(v6.contains(&IpAddr::from_str("2001:db8:ffff::1").unwrap()));
+        assert!(!v6.contains(&test_ip()));
+
+        assert!("10.0.0.0/33".parse::<IpNetwork>().is_err());
+        assert!("not-an-ip/8".parse::<IpNetwork>().is_err());
+    }
 }
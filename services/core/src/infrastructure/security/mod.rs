@@ -1,3 +1,3 @@
 pub mod ip_filter;
 
-pub use ip_filter::{IpFilter, FilterAction, FilterResult, IpFilterStats};
+pub use ip_filter::{IpFilter, FilterAction, FilterResult, IpFilterStats, IpNetwork};
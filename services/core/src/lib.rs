@@ -11,7 +11,9 @@ pub mod auth;
 pub mod auth_api;
 pub mod api;
 pub mod api_v1;
+pub mod audit_api;
 pub mod backup;
+pub mod backup_api;
 pub mod compaction;
 pub mod config;
 pub mod error;
@@ -19,9 +21,11 @@ pub mod index;
 pub mod metrics;
 pub mod middleware;
 pub mod pipeline;
+pub mod policy_api;
 pub mod projection;
 pub mod rate_limit;
 pub mod replay;
+pub mod retention;
 pub mod schema;
 pub mod snapshot;
 pub mod store;
@@ -45,5 +49,8 @@ pub use domain::repositories;
 #[cfg(test)]
 mod security_integration_tests;
 
+#[cfg(test)]
+mod test_support;
+
 // Advanced security module
 pub mod security;
@@ -1,11 +1,33 @@
 use allsource_core::{
+    application::services::AuditLogger,
     auth::AuthManager,
-    rate_limit::{RateLimiter, RateLimitConfig},
-    store::EventStore,
+    backup::{BackupJobManager, BackupManager},
+    domain::repositories::AuditEventRepository,
+    infrastructure::repositories::{
+        AuditChainSigner, HashChainedAuditRepository, FileAuditRepository,
+        MonitoredAuditRepository,
+    },
+    infrastructure::cluster::{
+        HandoffConfig, HandoffCoordinator, Node, NodeRegistry, RaftNode, ReplicationLog,
+        ReplicationManager, RequestForwarder,
+    },
+    infrastructure::persistence::StorageEngineConfig,
+    infrastructure::security::IpFilter,
+    middleware::TrustedProxies,
+    rate_limit::RateLimiter,
+    retention::RetentionTask,
+    security::{
+        AdaptiveRateLimiter, LoadSampler, LoadSamplerConfig, AnomalyDetectionConfig, AnomalyDetector, AnomalyResponder, AnomalyResponseConfig,
+        JwtKeyManager, KeyAlgorithm, KeyPurpose, KmsManager, KmsProvider,
+        OidcProvider, PolicyEngine,
+    },
+    store::{EventStore, EventStoreConfig},
     tenant::TenantManager,
     api_v1,
-    config::ServerConfig,
+    config::{Config, RateLimitMode, ServerConfig},
 };
+#[cfg(feature = "postgres")]
+use allsource_core::infrastructure::repositories::{PostgresEventFeed, PostgresEventStore};
 use anyhow::Result;
 use std::sync::Arc;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
@@ -25,24 +47,298 @@ async fn main() -> Result<()> {
     tracing::info!("   Production-ready event store with authentication & multi-tenancy");
 
     // Initialize components
-    let store = Arc::new(EventStore::new());
-    let auth_manager = Arc::new(AuthManager::default());
+    let app_config = Config::from_env()?;
+    app_config.validate()?;
+    let data_dir = app_config.storage.data_dir.clone();
+    let store_config = EventStoreConfig {
+        storage_engine: app_config.storage.engine.clone(),
+        retention: app_config.storage.retention_config(),
+        // Data keys are KMS-wrapped and policies persisted under the data directory
+        payload_encryption_config: app_config.payload_encryption.clone().with_data_dir(&data_dir),
+        // Backups track event sequences, so they must survive restarts
+        sequence_journal: Some(data_dir.join("sequences.log")),
+        ..Default::default()
+    };
+    tracing::info!("🗄️  Storage engine: {}", store_config.storage_engine.name());
+
+    // Nodes sharing a Postgres database follow each other's writes through
+    // LISTEN/NOTIFY once the store is running
+    #[cfg(feature = "postgres")]
+    let (event_store, shared_feed) = match &app_config.storage.engine {
+        StorageEngineConfig::Postgres { url, max_connections } => {
+            let engine = Arc::new(PostgresEventStore::connect(url, *max_connections).await?);
+            let (store, feed) = PostgresEventFeed::open_store(engine, store_config, Default::default()).await?;
+            (store, Some(Arc::new(feed)))
+        }
+        _ => (EventStore::open(store_config).await?, None),
+    };
+    #[cfg(not(feature = "postgres"))]
+    let event_store = EventStore::open(store_config).await?;
+
+    // Clustered: accept writes for led partitions, replicate their WAL and
+    // agree on membership and partition moves through Raft
+    let (store, replication, consensus, forwarder) = if app_config.cluster.enabled {
+        let cluster = &app_config.cluster;
+        let registry = Arc::new(NodeRegistry::new(cluster.partition_count));
+        for peer in &cluster.peers {
+            registry.register_node(Node {
+                id: peer.id,
+                address: peer.address.clone(),
+                healthy: true,
+                assigned_partitions: vec![],
+            });
+        }
+        // Later membership changes hand partitions over instead of moving them outright
+        registry.enable_handoffs();
+
+        let raft = Arc::new(RaftNode::new(
+            cluster.raft_config(Some(data_dir.join("cluster").join("raft.json"))),
+            registry.clone(),
+        )?);
+        raft.clone().spawn();
+
+        // Any node accepts entity requests and proxies them to the owner
+        let forwarder = cluster.forward_requests.then(|| {
+            Arc::new(RequestForwarder::new(cluster.forwarding_config(), registry.clone()))
+        });
+
+        let log = Arc::new(ReplicationLog::open(
+            cluster.replication_config(),
+            registry,
+            data_dir.join("cluster").join("replication"),
+        )?);
+        let store = Arc::new(event_store.with_replication(log.clone()));
+        let manager = Arc::new(ReplicationManager::new(log, store.clone()));
+        manager.clone().spawn();
+
+        Arc::new(HandoffCoordinator::new(raft.clone(), manager.clone(), HandoffConfig::default()))
+            .spawn();
+        (store, Some(manager), Some(raft), forwarder)
+    } else {
+        (Arc::new(event_store), None, None, None)
+    };
+
+    #[cfg(feature = "postgres")]
+    if let Some(feed) = shared_feed {
+        feed.spawn(store.clone());
+    }
+
+    // Expired events are swept from memory and the WAL in the background
+    let retention = app_config.storage.retention_config();
+    if !retention.rules.is_empty() {
+        if app_config.storage.engine == StorageEngineConfig::Native {
+            tokio::spawn(RetentionTask::new(store.clone(), retention.sweep_interval_secs).run());
+            tracing::info!(
+                "✅ Retention sweeps every {}s ({} rule(s))",
+                retention.sweep_interval_secs,
+                retention.rules.len()
+            );
+        } else {
+            tracing::warn!(
+                "⚠️  Retention rules are ignored by the {} storage engine",
+                app_config.storage.engine.name()
+            );
+        }
+    }
+
+    // Users and API keys persist across restarts
+    let auth_stores = app_config.auth.storage.open(&data_dir).await?;
+    tracing::info!("👤 Auth storage: {}", app_config.auth.storage.name());
+
+    // JWT signing keys: a shared HS256 secret if configured, otherwise
+    // rotating asymmetric keys published at /.well-known/jwks.json, kept in
+    // a KMS-wrapped key ring so tokens survive restarts
+    let jwt_keys = match std::env::var("ALLSOURCE_JWT_SECRET") {
+        Ok(secret) => Arc::new(JwtKeyManager::from_secret(&secret)),
+        Err(_) => {
+            let signing = &app_config.auth.signing_keys;
+            let jwt_keys = if signing.persistent {
+                let kms = KmsManager::new(
+                    signing.kms.clone().with_default_key_file(data_dir.join("kms").join("jwt_keys.json")),
+                )?;
+                let wrapping_key = if signing.kms.provider == KmsProvider::Local {
+                    kms.ensure_key(&signing.wrapping_key, KeyPurpose::JwtSigning, KeyAlgorithm::Aes256Gcm)
+                        .await?
+                } else {
+                    kms.client().get_key(&signing.wrapping_key).await?
+                };
+                let key_ring = signing
+                    .key_ring
+                    .clone()
+                    .unwrap_or_else(|| data_dir.join("kms").join("jwt_key_ring.json"));
+                tracing::info!("🔑 JWT signing keys kept in {}", key_ring.display());
+                Arc::new(
+                    JwtKeyManager::with_kms(signing.key_config(), kms.client().clone(), wrapping_key.key_id, key_ring)
+                        .await?,
+                )
+            } else {
+                tracing::warn!("⚠️  JWT signing keys are not persisted; issued tokens will not survive a restart");
+                Arc::new(JwtKeyManager::generate(signing.key_config())?)
+            };
+            jwt_keys.spawn_rotation();
+            jwt_keys
+        }
+    };
+    let mut auth_manager =
+        AuthManager::with_repositories(jwt_keys.clone(), auth_stores.users, auth_stores.api_keys, auth_stores.sessions)
+            .await?;
+    if let Some(oidc) = app_config.auth.oidc.clone() {
+        tracing::info!("🔑 OpenID Connect login enabled (issuer {})", oidc.issuer_url);
+        auth_manager = auth_manager.with_oidc(Arc::new(OidcProvider::new(oidc)?));
+    }
+    let auth_manager = Arc::new(auth_manager);
+    auth_manager.spawn_session_sync();
     let tenant_manager = Arc::new(TenantManager::new());
-    let rate_limiter = Arc::new(RateLimiter::new(RateLimitConfig::professional()));
+    let rate_limiter = Arc::new(RateLimiter::new(app_config.rate_limit.limiter_config()));
+    // Adaptive mode: per-tenant limits driven by sampled system load
+    let adaptive_limiter = match app_config.rate_limit.mode {
+        RateLimitMode::Adaptive => {
+            let limiter = Arc::new(AdaptiveRateLimiter::new(app_config.rate_limit.adaptive.clone()));
+            LoadSampler::new(
+                limiter.clone(),
+                store.clone(),
+                LoadSamplerConfig {
+                    interval: std::time::Duration::from_secs(app_config.rate_limit.load_sample_interval_secs),
+                    ..Default::default()
+                },
+            )
+            .spawn();
+            Some(limiter)
+        }
+        RateLimitMode::Static => None,
+    };
+    let ip_filter = Arc::new(IpFilter::new());
+    let policy_engine = Arc::new(PolicyEngine::open(data_dir.join("policies.json"))?);
+    let mut backup_manager = BackupManager::new(app_config.backup.manager_config())?;
+    if let Some(ref encryption) = app_config.backup.encryption {
+        // Local master keys are kept in a key file so backups stay restorable after a restart
+        let key_file = data_dir.join("kms").join("backup_keys.json");
+        let kms = Arc::new(KmsManager::new(encryption.kms.clone().with_default_key_file(&key_file))?);
+        let key = if encryption.kms.provider == KmsProvider::Local {
+            tracing::warn!(
+                "⚠️  Backups are encrypted with a local KMS key; keep {} safe and out of the backup location",
+                encryption.kms.config.get("key_file").map(std::path::PathBuf::from).unwrap_or(key_file).display()
+            );
+            kms.ensure_key(&encryption.key_id, KeyPurpose::DataEncryption, KeyAlgorithm::Aes256Gcm)
+                .await?
+        } else {
+            kms.client().get_key(&encryption.key_id).await?
+        };
+        tracing::info!("✅ Backups encrypted with KMS key {} (version {})", encryption.key_id, key.version);
+        backup_manager = backup_manager.with_encryption(kms, key.key_id);
+    }
+    let backup_jobs = Arc::new(BackupJobManager::new(Arc::new(backup_manager)));
+
+    // Tamper-evident audit log: hash-chained, with KMS-signed checkpoints.
+    // Both the log and the checkpoint key persist so the chain can be
+    // verified across restarts
+    let audit_config = &app_config.audit;
+    let kms = KmsManager::new(
+        audit_config.kms.clone().with_default_key_file(data_dir.join("kms").join("audit_keys.json")),
+    )?;
+    let audit_signing_key = if audit_config.kms.provider == KmsProvider::Local {
+        kms.ensure_key(&audit_config.signing_key, KeyPurpose::AuditSigning, KeyAlgorithm::HmacSha256)
+            .await?
+    } else {
+        kms.client().get_key(&audit_config.signing_key).await?
+    };
+    let audit_log = Arc::new(FileAuditRepository::open(data_dir.join("audit").join("audit.jsonl"))?);
+    let audit_chain = Arc::new(
+        HashChainedAuditRepository::new(audit_log, audit_config.chain_config())
+            .with_signer(AuditChainSigner {
+                kms: kms.client().clone(),
+                key_id: audit_signing_key.key_id,
+            }),
+    );
+
+    // Anomaly detection subscribed to the audit flow; alerts go straight to the chain
+    let anomaly_responder = Arc::new(AnomalyResponder::new(
+        Arc::new(AnomalyDetector::new(AnomalyDetectionConfig::default())),
+        AnomalyResponseConfig {
+            webhook_url: std::env::var("ALLSOURCE_ANOMALY_WEBHOOK_URL").ok(),
+            profile_path: Some(data_dir.join("anomaly_profiles.json")),
+            trusted_proxies: app_config.server.trusted_proxies.clone(),
+            ..Default::default()
+        },
+        auth_manager.clone(),
+        ip_filter.clone(),
+        rate_limiter.clone(),
+        audit_chain.clone(),
+    )?);
+    let audit_repository: Arc<dyn AuditEventRepository> = Arc::new(MonitoredAuditRepository::new(
+        audit_chain.clone(),
+        anomaly_responder,
+    ));
+    let audit_logger = Arc::new(AuditLogger::new(audit_repository));
 
     tracing::info!("✅ Event store initialized");
-    tracing::info!("✅ Authentication manager initialized");
+    tracing::info!(
+        "✅ Authentication manager initialized ({} users loaded from {})",
+        auth_manager.list_users().len(),
+        data_dir.join("auth").display()
+    );
+    tracing::info!(
+        "✅ JWT signing keys initialized ({:?}, current kid {})",
+        jwt_keys.algorithm(),
+        jwt_keys.current_kid().unwrap_or_default()
+    );
     tracing::info!("✅ Tenant manager initialized (default tenant created)");
-    tracing::info!("✅ Rate limiter initialized (professional tier defaults)");
+    match app_config.rate_limit.mode {
+        RateLimitMode::Adaptive => tracing::info!(
+            "✅ Rate limiter initialized (adaptive, sampling load every {}s)",
+            app_config.rate_limit.load_sample_interval_secs
+        ),
+        RateLimitMode::Static => tracing::info!(
+            "✅ Rate limiter initialized ({:?} tier, cost-weighted)",
+            app_config.rate_limit.default_tier
+        ),
+    }
+    tracing::info!(
+        "✅ Audit logger initialized (hash-chained, signed checkpoints, {})",
+        data_dir.join("audit").display()
+    );
+    tracing::info!("✅ Anomaly detection initialized (automatic responses enabled)");
+    tracing::info!("✅ Access policy engine initialized ({})", data_dir.join("policies.json").display());
+    tracing::info!("✅ Online backups enabled ({})", app_config.backup.backup_dir.display());
+    if let Some(ref object_store) = app_config.backup.object_store {
+        tracing::info!("✅ Backups copied to {}", object_store.describe());
+    }
+    if app_config.cluster.enabled {
+        tracing::info!(
+            "✅ Cluster replication enabled (node {} of {}, replication factor {}, {:?} acks)",
+            app_config.cluster.node_id,
+            app_config.cluster.peers.len(),
+            app_config.cluster.replication_factor,
+            app_config.cluster.ack_mode
+        );
+    }
 
     // Start API server (v1.0 with auth & rate limiting)
     let config = ServerConfig::default();
     let addr = format!("{}:{}", config.host, config.port);
     tracing::info!("🚀 AllSource Core listening on {}", addr);
     tracing::info!("📝 API Documentation: /health for health check");
-    tracing::info!("🔒 Features: Auth, Multi-tenancy, Rate Limiting");
+    tracing::info!("🔒 Features: Auth, Multi-tenancy, Rate Limiting, Access Policies, Audit Logging, Anomaly Detection");
 
-    api_v1::serve_v1(store, auth_manager, tenant_manager, rate_limiter, &addr).await?;
+    api_v1::serve_v1(
+        store,
+        auth_manager,
+        tenant_manager,
+        rate_limiter,
+        adaptive_limiter,
+        app_config.rate_limit.cost_model(),
+        ip_filter,
+        TrustedProxies::new(app_config.server.trusted_proxies.clone()),
+        audit_logger,
+        audit_chain,
+        policy_engine,
+        backup_jobs,
+        replication,
+        consensus,
+        forwarder,
+        &addr,
+    )
+    .await?;
 
     Ok(())
 }
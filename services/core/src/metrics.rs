@@ -75,6 +75,15 @@ pub struct MetricsRegistry {
     pub http_requests_total: IntCounterVec,
     pub http_request_duration_seconds: HistogramVec,
     pub http_requests_in_flight: IntGauge,
+
+    // Load and adaptive rate limit metrics
+    pub system_cpu_usage: Gauge,
+    pub system_memory_usage: Gauge,
+    pub process_cpu_usage: Gauge,
+    pub process_resident_memory_bytes: IntGauge,
+    pub ingest_backlog: IntGauge,
+    pub rate_limit_adjustments_total: IntCounterVec,
+    pub rate_limit_current: IntGaugeVec,
 }
 
 impl MetricsRegistry {
@@ -398,6 +407,55 @@ impl MetricsRegistry {
         ))
         .unwrap();
 
+        // Load and adaptive rate limit metrics
+        let system_cpu_usage = Gauge::with_opts(Opts::new(
+            "allsource_system_cpu_usage",
+            "System CPU usage (0.0-1.0)",
+        ))
+        .unwrap();
+
+        let system_memory_usage = Gauge::with_opts(Opts::new(
+            "allsource_system_memory_usage",
+            "System memory usage (0.0-1.0)",
+        ))
+        .unwrap();
+
+        let process_cpu_usage = Gauge::with_opts(Opts::new(
+            "allsource_process_cpu_usage",
+            "Share of total CPU time used by this process (0.0-1.0)",
+        ))
+        .unwrap();
+
+        let process_resident_memory_bytes = IntGauge::with_opts(Opts::new(
+            "allsource_process_resident_memory_bytes",
+            "Resident memory of this process",
+        ))
+        .unwrap();
+
+        let ingest_backlog = IntGauge::with_opts(Opts::new(
+            "allsource_ingest_backlog",
+            "Ingested events not yet flushed to storage",
+        ))
+        .unwrap();
+
+        let rate_limit_adjustments_total = IntCounterVec::new(
+            Opts::new(
+                "allsource_rate_limit_adjustments_total",
+                "Adaptive rate limit changes",
+            ),
+            &["reason"],
+        )
+        .unwrap();
+
+        let rate_limit_current = IntGaugeVec::new(
+            Opts::new(
+                "allsource_rate_limit_current",
+                "Current adaptive rate limit (requests/hour)",
+            ),
+            &["tenant_id"],
+        )
+        .unwrap();
+
         // Register all metrics
         registry
             .register(Box::new(events_ingested_total.clone()))
@@ -543,6 +601,28 @@ impl MetricsRegistry {
             .register(Box::new(http_requests_in_flight.clone()))
             .unwrap();
 
+        registry
+            .register(Box::new(system_cpu_usage.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(system_memory_usage.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(process_cpu_usage.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(process_resident_memory_bytes.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(ingest_backlog.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(rate_limit_adjustments_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(rate_limit_current.clone()))
+            .unwrap();
+
         Arc::new(Self {
             registry,
             events_ingested_total,
@@ -590,6 +670,13 @@ impl MetricsRegistry {
             http_requests_total,
             http_request_duration_seconds,
             http_requests_in_flight,
+            system_cpu_usage,
+            system_memory_usage,
+            process_cpu_usage,
+            process_resident_memory_bytes,
+            ingest_backlog,
+            rate_limit_adjustments_total,
+            rate_limit_current,
         })
     }
 
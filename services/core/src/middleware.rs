@@ -1,9 +1,13 @@
-use crate::auth::{AuthManager, Claims, Permission};
+use crate::auth::{ApiKeyScopes, ApiOperation, AuthManager, Claims, Permission};
 use crate::error::AllSourceError;
-use crate::rate_limit::RateLimiter;
+use crate::infrastructure::cluster::forwarding::{decode_principal, FORWARDED_PRINCIPAL_HEADER};
+use crate::infrastructure::cluster::replication::check_cluster_secret;
+use crate::rate_limit::{CostModel, QueryCost, RateLimiter};
+use crate::security::adaptive_rate_limit::AdaptiveRateLimiter;
 use axum::{
+    body::Body,
     extract::{Request, State},
-    http::{HeaderMap, StatusCode},
+    http::{header, HeaderMap, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
 };
@@ -13,14 +17,26 @@ use std::sync::Arc;
 #[derive(Clone)]
 pub struct AuthState {
     pub auth_manager: Arc<AuthManager>,
+    /// Cluster secret; requests carrying it may assert the principal the
+    /// forwarding node authenticated
+    pub cluster_secret: Option<String>,
 }
 
 /// Rate limiting state
 #[derive(Clone)]
 pub struct RateLimitState {
     pub rate_limiter: Arc<RateLimiter>,
+    /// When set, tenant limits come from the adaptive limiter instead of
+    /// the static token buckets
+    pub adaptive_limiter: Option<Arc<AdaptiveRateLimiter>>,
+    /// Request weights charged against the limits
+    pub cost_model: Arc<CostModel>,
 }
 
+/// Largest body without a Content-Length read to weigh its cost (axum's
+/// default limit)
+const MAX_COSTED_BODY_BYTES: usize = 2 * 1024 * 1024;
+
 /// Authenticated request context
 #[derive(Debug, Clone)]
 pub struct AuthContext {
@@ -84,6 +100,12 @@ pub async fn auth_middleware(
 ) -> Result<Response, AuthError> {
     let headers = request.headers();
 
+    // Already authenticated (and scope-checked) by the node that forwarded the request
+    if let Some(claims) = forwarded_principal(&auth_state, headers)? {
+        request.extensions_mut().insert(AuthContext { claims });
+        return Ok(next.run(request).await);
+    }
+
     // Extract and validate token (JWT or API key)
     let token = extract_token(headers)?;
 
@@ -91,16 +113,92 @@ pub async fn auth_middleware(
         // API Key authentication
         auth_state.auth_manager.validate_api_key(&token)?
     } else {
-        // JWT authentication
-        auth_state.auth_manager.validate_token(&token)?
+        // JWT (ours or the OIDC provider's ID token)
+        auth_state.auth_manager.validate_bearer(&token).await?
     };
 
+    // Scoped API keys: operation, client network and per-key rate limit
+    if let Some(scopes) = &claims.scopes {
+        if let Some(rejection) = api_key_scope_rejection(&auth_state, &claims.sub, scopes, &request) {
+            return Ok(rejection);
+        }
+    }
+
     // Insert auth context into request extensions
     request.extensions_mut().insert(AuthContext { claims });
 
     Ok(next.run(request).await)
 }
 
+/// Claims asserted by another node for a forwarded request
+///
+/// Only honoured alongside the cluster secret, so clients cannot assert a
+/// principal themselves.
+fn forwarded_principal(auth_state: &AuthState, headers: &HeaderMap) -> Result<Option<Claims>, AllSourceError> {
+    let Some(principal) = headers.get(FORWARDED_PRINCIPAL_HEADER) else {
+        return Ok(None);
+    };
+    let Some(secret) = auth_state.cluster_secret.as_deref() else {
+        return Err(AllSourceError::PermissionDenied(
+            "Forwarded principals are not accepted outside a cluster".to_string(),
+        ));
+    };
+    check_cluster_secret(Some(secret), headers)?;
+    decode_principal(principal).map(Some)
+}
+
+/// Check a request against the scopes of the API key that authenticated it,
+/// returning the rejection response if it falls outside them
+///
+/// The client network is the resolved client address: `X-Forwarded-For`
+/// only counts when the request came through a trusted proxy.
+fn api_key_scope_rejection(
+    auth_state: &AuthState,
+    key_id: &str,
+    scopes: &ApiKeyScopes,
+    request: &Request,
+) -> Option<Response> {
+    let operation = classify_operation(request.method(), request.uri().path());
+    if !scopes.allows_operation(operation) {
+        return Some(
+            AuthError(AllSourceError::PermissionDenied(format!(
+                "API key is not scoped for {} operations",
+                operation.as_str()
+            )))
+            .into_response(),
+        );
+    }
+
+    if !scopes.allowed_cidrs.is_empty() {
+        let client_ip = client_ip(request.extensions());
+        if !client_ip.is_some_and(|ip| scopes.allows_ip(&ip)) {
+            return Some(
+                AuthError(AllSourceError::PermissionDenied(
+                    "API key is not allowed from this address".to_string(),
+                ))
+                .into_response(),
+            );
+        }
+    }
+
+    if let Some(requests_per_minute) = scopes.rate_limit_per_minute {
+        let result = auth_state
+            .auth_manager
+            .check_api_key_rate_limit(key_id, requests_per_minute);
+        if !result.allowed {
+            return Some(
+                RateLimitError::RateLimitExceeded {
+                    retry_after: result.retry_after.unwrap_or_default().as_secs(),
+                    limit: result.limit,
+                }
+                .into_response(),
+            );
+        }
+    }
+
+    None
+}
+
 /// Optional authentication middleware (allows unauthenticated requests)
 pub async fn optional_auth_middleware(
     State(auth_state): State<AuthState>,
@@ -114,9 +212,16 @@ pub async fn optional_auth_middleware(
         let claims = if token.starts_with("ask_") {
             auth_state.auth_manager.validate_api_key(&token).ok()
         } else {
-            auth_state.auth_manager.validate_token(&token).ok()
+            auth_state.auth_manager.validate_bearer(&token).await.ok()
         };
 
+        // Scoped keys outside their scopes are treated as unauthenticated
+        let claims = claims.filter(|claims| {
+            claims.scopes.as_ref().is_none_or(|scopes| {
+                api_key_scope_rejection(&auth_state, &claims.sub, scopes, &request).is_none()
+            })
+        });
+
         if let Some(claims) = claims {
             request.extensions_mut().insert(AuthContext { claims });
         }
@@ -139,6 +244,7 @@ impl IntoResponse for AuthError {
     fn into_response(self) -> Response {
         let (status, message) = match self.0 {
             AllSourceError::ValidationError(msg) => (StatusCode::UNAUTHORIZED, msg),
+            AllSourceError::PermissionDenied(msg) => (StatusCode::FORBIDDEN, msg),
             _ => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "Internal server error".to_string(),
@@ -201,7 +307,15 @@ where
 }
 
 /// Rate limiting middleware
-/// Checks rate limits based on tenant_id from auth context
+/// Checks rate limits based on tenant_id from auth context, using the
+/// adaptive limiter when configured. Principals (users or API keys) with
+/// their own config, e.g. throttled by the anomaly responder, are
+/// additionally checked against that limit.
+///
+/// Requests are weighted by the cost model: the route's base cost and
+/// payload size are charged up front, and query work reported by the
+/// handler (`QueryCost`) is charged once the response is ready. The total
+/// is returned in `X-RateLimit-Cost`.
 pub async fn rate_limit_middleware(
     State(rate_limit_state): State<RateLimitState>,
     request: Request,
@@ -211,12 +325,53 @@ pub async fn rate_limit_middleware(
     let auth_ctx = request
         .extensions()
         .get::<AuthContext>()
-        .ok_or_else(|| RateLimitError::Unauthorized)?;
+        .cloned()
+        .ok_or(RateLimitError::Unauthorized)?;
+    let tenant_id = auth_ctx.tenant_id();
+    let principal = auth_ctx.user_id();
+
+    // Payload size from Content-Length, or by reading a body sent without one
+    let content_length = request
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    let (request, body_bytes) = match content_length {
+        Some(length) => (request, length),
+        None if matches!(*request.method(), Method::POST | Method::PUT | Method::PATCH) => {
+            let (parts, body) = request.into_parts();
+            let Ok(bytes) = axum::body::to_bytes(body, MAX_COSTED_BODY_BYTES).await else {
+                return Ok((StatusCode::PAYLOAD_TOO_LARGE, "Request body too large").into_response());
+            };
+            let length = bytes.len() as u64;
+            (Request::from_parts(parts, Body::from(bytes)), length)
+        }
+        None => (request, 0),
+    };
+    let cost = rate_limit_state
+        .cost_model
+        .request_cost(request.method().as_str(), request.uri().path(), body_bytes);
 
     // Check rate limit for this tenant
-    let result = rate_limit_state
-        .rate_limiter
-        .check_rate_limit(auth_ctx.tenant_id());
+    let adaptive = rate_limit_state.adaptive_limiter.as_ref().and_then(|limiter| {
+        limiter
+            .check_adaptive_limit_with_cost(tenant_id, cost)
+            .map_err(|e| tracing::warn!("Adaptive rate limit check failed, using static limit: {}", e))
+            .ok()
+            .map(|result| (limiter, result))
+    });
+    let result = match &adaptive {
+        Some((_, result)) if !result.allowed => {
+            return Err(RateLimitError::AdaptiveLimitExceeded {
+                retry_after: result.retry_after.unwrap_or_default().as_secs(),
+                limit: result.limit,
+            });
+        }
+        Some((_, result)) => result.clone(),
+        None => rate_limit_state
+            .rate_limiter
+            .check_rate_limit_with_cost(tenant_id, cost),
+    };
 
     if !result.allowed {
         return Err(RateLimitError::RateLimitExceeded {
@@ -225,29 +380,80 @@ pub async fn rate_limit_middleware(
         });
     }
 
-    // Add rate limit headers to response
+    // Check the principal's own limit, if one is set
+    let principal_limited = rate_limit_state.rate_limiter.has_config(principal);
+    let mut result = if principal_limited {
+        let principal_result = rate_limit_state
+            .rate_limiter
+            .check_rate_limit_with_cost(principal, cost);
+        if !principal_result.allowed {
+            return Err(RateLimitError::RateLimitExceeded {
+                retry_after: principal_result.retry_after.unwrap_or_default().as_secs(),
+                limit: principal_result.limit,
+            });
+        }
+        principal_result
+    } else {
+        result
+    };
+
     let mut response = next.run(request).await;
+
+    // Charge for the query work the handler reported
+    let query_cost = response
+        .extensions()
+        .get::<QueryCost>()
+        .map(|query| rate_limit_state.cost_model.query_cost(query))
+        .unwrap_or(0.0);
+    if query_cost > 0.0 {
+        match &adaptive {
+            Some((limiter, _)) => {
+                limiter.charge(tenant_id, query_cost);
+                result.remaining = (result.remaining as f64 - query_cost).max(0.0) as u32;
+            }
+            None => result = rate_limit_state.rate_limiter.charge(tenant_id, query_cost),
+        }
+        if principal_limited {
+            result = rate_limit_state.rate_limiter.charge(principal, query_cost);
+        }
+    }
+
+    // Add rate limit headers to response
     let headers = response.headers_mut();
     headers.insert("X-RateLimit-Limit", result.limit.to_string().parse().unwrap());
     headers.insert("X-RateLimit-Remaining", result.remaining.to_string().parse().unwrap());
+    headers.insert("X-RateLimit-Cost", format_cost(cost + query_cost).parse().unwrap());
 
     Ok(response)
 }
 
+/// Cost rounded to two decimals, without trailing zeros
+fn format_cost(cost: f64) -> String {
+    let rounded = (cost * 100.0).round() / 100.0;
+    rounded.to_string()
+}
+
 /// Error type for rate limiting failures
 #[derive(Debug)]
 pub enum RateLimitError {
     RateLimitExceeded { retry_after: u64, limit: u32 },
+    /// Adaptive limits are per hour
+    AdaptiveLimitExceeded { retry_after: u64, limit: u32 },
     Unauthorized,
 }
 
 impl IntoResponse for RateLimitError {
     fn into_response(self) -> Response {
         match self {
-            RateLimitError::RateLimitExceeded { retry_after, limit } => {
+            RateLimitError::RateLimitExceeded { retry_after, limit }
+            | RateLimitError::AdaptiveLimitExceeded { retry_after, limit } => {
+                let window = match self {
+                    RateLimitError::AdaptiveLimitExceeded { .. } => "hour",
+                    _ => "min",
+                };
                 let mut response = (
                     StatusCode::TOO_MANY_REQUESTS,
-                    format!("Rate limit exceeded. Limit: {} requests/min", limit),
+                    format!("Rate limit exceeded. Limit: {} requests/{}", limit, window),
                 )
                     .into_response();
 
@@ -607,12 +813,100 @@ pub async fn security_headers_middleware(
     response
 }
 
+// ============================================================================
+// Client Address Resolution
+// ============================================================================
+
+/// Reverse proxies allowed to report the client address in `X-Forwarded-For`
+///
+/// The header is client-controlled, so it is only read when the socket peer
+/// is one of these proxies. The hops are then walked right to left, skipping
+/// further trusted proxies, and the first untrusted address is the client.
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies {
+    networks: Arc<Vec<IpNetwork>>,
+}
+
+impl TrustedProxies {
+    pub fn new(networks: Vec<IpNetwork>) -> Self {
+        Self {
+            networks: Arc::new(networks),
+        }
+    }
+
+    /// Whether an address belongs to a trusted proxy
+    pub fn is_trusted(&self, ip: &IpAddr) -> bool {
+        self.networks.iter().any(|net| net.contains(ip))
+    }
+
+    /// Resolve the client address of a request received from `peer`
+    pub fn resolve(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        if !self.is_trusted(&peer) {
+            return peer;
+        }
+
+        let hops: Vec<&str> = headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .collect();
+
+        // A malformed hop ends the walk; anything left of it is unverifiable
+        let mut client = peer;
+        for hop in hops.into_iter().rev() {
+            let Ok(hop) = hop.trim().parse::<IpAddr>() else {
+                break;
+            };
+            client = hop;
+            if !self.is_trusted(&hop) {
+                break;
+            }
+        }
+        client
+    }
+}
+
+/// Client address of a request, resolved by `client_ip_middleware`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+/// Resolve the client address once per request
+///
+/// Inserts a `ClientIp` extension used by IP filtering, API key network
+/// scopes, access policies and audit logging.
+pub async fn client_ip_middleware(
+    State(trusted_proxies): State<TrustedProxies>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let peer = request
+        .extensions()
+        .get::<axum::extract::ConnectInfo<SocketAddr>>()
+        .map(|connect_info| connect_info.0.ip());
+    if let Some(peer) = peer {
+        let client_ip = trusted_proxies.resolve(peer, request.headers());
+        request.extensions_mut().insert(ClientIp(client_ip));
+    }
+    next.run(request).await
+}
+
+/// Client address of a request: the resolved `ClientIp` when
+/// `client_ip_middleware` ran, otherwise the socket address
+pub fn client_ip(extensions: &axum::http::Extensions) -> Option<IpAddr> {
+    extensions.get::<ClientIp>().map(|client_ip| client_ip.0).or_else(|| {
+        extensions
+            .get::<axum::extract::ConnectInfo<SocketAddr>>()
+            .map(|connect_info| connect_info.0.ip())
+    })
+}
+
 // ============================================================================
 // IP Filtering Middleware (Phase 5C)
 // ============================================================================
 
-use crate::infrastructure::security::IpFilter;
-use std::net::SocketAddr;
+use crate::infrastructure::security::{IpFilter, IpNetwork};
+use std::net::{IpAddr, SocketAddr};
 
 #[derive(Clone)]
 pub struct IpFilterState {
@@ -636,11 +930,7 @@ pub async fn ip_filter_middleware(
     next: Next,
 ) -> Result<Response, IpFilterError> {
     // Extract client IP address
-    let client_ip = request
-        .extensions()
-        .get::<axum::extract::ConnectInfo<SocketAddr>>()
-        .map(|connect_info| connect_info.0.ip())
-        .ok_or(IpFilterError::NoIpAddress)?;
+    let client_ip = client_ip(request.extensions()).ok_or(IpFilterError::NoIpAddress)?;
 
     // Check if this is a tenant-scoped request
     let result = if let Some(tenant_ctx) = request.extensions().get::<TenantContext>() {
@@ -684,6 +974,517 @@ impl IntoResponse for IpFilterError {
     }
 }
 
+// ============================================================================
+// Audit Logging Middleware
+// ============================================================================
+
+use crate::application::services::{AuditLogger, RequestContext};
+use crate::domain::entities::{Actor, AuditAction, AuditOutcome};
+use crate::domain::repositories::AuditEventRepository;
+use axum::http::Method;
+
+/// Audit logging state for middleware
+#[derive(Clone)]
+pub struct AuditState {
+    pub audit_logger: Arc<AuditLogger<dyn AuditEventRepository>>,
+    pub auth_manager: Arc<AuthManager>,
+}
+
+/// Audit logging middleware
+///
+/// Records an audit event for every mutating (POST/PUT/PATCH/DELETE) request
+/// that maps to a known `AuditAction`. The event carries the actor and tenant
+/// from `AuthContext`, the client IP, user agent and `RequestId`, and an
+/// outcome derived from the response status. Requests rejected with 403 are
+/// recorded as `PermissionDenied`.
+///
+/// Must be applied after auth_middleware (so `AuthContext` is available) and
+/// inside request_id_middleware. Audit failures never fail the request.
+pub async fn audit_middleware(
+    State(audit_state): State<AuditState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+
+    let Some((action, resource)) = classify_audit_action(&method, &path) else {
+        return next.run(request).await;
+    };
+    let Some(auth_ctx) = request.extensions().get::<AuthContext>().cloned() else {
+        return next.run(request).await;
+    };
+    let context = request_context(request.headers(), request.extensions());
+
+    let response = next.run(request).await;
+    let status = response.status();
+
+    let Ok(tenant_id) = TenantId::new(auth_ctx.tenant_id().to_string()) else {
+        tracing::warn!("⚠️  Skipping audit event for invalid tenant id '{}'", auth_ctx.tenant_id());
+        return response;
+    };
+    let actor = resolve_actor(&audit_state.auth_manager, &auth_ctx);
+
+    let (action, outcome) = if status == StatusCode::FORBIDDEN {
+        (AuditAction::PermissionDenied, AuditOutcome::Failure)
+    } else if status.is_success() {
+        (action, AuditOutcome::Success)
+    } else {
+        (action, AuditOutcome::Failure)
+    };
+
+    let mut entry = audit_state
+        .audit_logger
+        .log(tenant_id, action, actor)
+        .with_outcome(outcome.clone())
+        .with_context(context)
+        .with_metadata(serde_json::json!({
+            "method": method.as_str(),
+            "path": path,
+            "status": status.as_u16(),
+        }));
+    if let Some((resource_type, resource_id)) = resource {
+        entry = entry.with_resource(resource_type, resource_id);
+    }
+    if outcome == AuditOutcome::Failure {
+        entry = entry.with_error(format!("HTTP {}", status.as_u16()));
+    }
+    entry.record_silently().await;
+
+    response
+}
+
+/// Build a `RequestContext` (IP, user agent, request id) from request parts
+///
+/// The client IP is the address resolved through the trusted proxy list
+/// (see `TrustedProxies`), so it is the one IP filtering acts on.
+pub fn request_context(headers: &HeaderMap, extensions: &axum::http::Extensions) -> RequestContext {
+    let mut context = RequestContext::new();
+
+    if let Some(ip) = client_ip(extensions) {
+        context = context.with_ip(ip.to_string());
+    }
+
+    if let Some(user_agent) = headers.get("user-agent").and_then(|v| v.to_str().ok()) {
+        context = context.with_user_agent(user_agent.to_string());
+    }
+
+    if let Some(request_id) = extensions.get::<RequestId>() {
+        context = context.with_request_id(request_id.as_str().to_string());
+    }
+
+    context
+}
+
+/// Axum extractor for the audit `RequestContext` of a request
+///
+/// Used by handlers that record their own audit events.
+pub struct AuditRequestContext(pub RequestContext);
+
+#[axum::async_trait]
+impl<S> axum::extract::FromRequestParts<S> for AuditRequestContext
+where
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(AuditRequestContext(request_context(&parts.headers, &parts.extensions)))
+    }
+}
+
+/// Resolve the audit actor for an authenticated request
+///
+/// The claims subject is either a user ID or an API key ID.
+pub fn resolve_actor(auth_manager: &AuthManager, auth_ctx: &AuthContext) -> Actor {
+    let subject = auth_ctx.user_id().to_string();
+    if let Ok(id) = subject.parse::<Uuid>() {
+        if let Some(user) = auth_manager.get_user(&id) {
+            return Actor::user(subject, user.username);
+        }
+        if let Some(api_key) = auth_manager.get_api_key(&id) {
+            return Actor::api_key(subject, api_key.name);
+        }
+    }
+    Actor::user(subject.clone(), subject)
+}
+
+/// Map a mutating v1 request to its audit action and affected resource
+///
+/// Returns `None` for reads and for routes that record their own audit
+/// events (login, token refresh, logout).
+pub fn classify_audit_action(
+    method: &Method,
+    path: &str,
+) -> Option<(AuditAction, Option<(String, String)>)> {
+    let segments: Vec<&str> = path
+        .trim_start_matches("/api/v1/")
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect();
+    let resource = |kind: &str, id: &str| Some((kind.to_string(), id.to_string()));
+
+    let classified = match (method.as_str(), segments.as_slice()) {
+        // Auth
+        ("POST", ["auth", "register"]) => (AuditAction::UserCreated, None),
+        ("POST", ["auth", "api-keys"]) => (AuditAction::ApiKeyCreated, None),
+        ("DELETE", ["auth", "api-keys", id]) => (AuditAction::ApiKeyRevoked, resource("api_key", id)),
+        ("DELETE", ["auth", "users", id]) => (AuditAction::UserDeleted, resource("user", id)),
+
+        // Tenants
+        ("POST", ["tenants"]) => (AuditAction::TenantCreated, None),
+        ("PUT", ["tenants", id, "quotas"]) => (AuditAction::TenantUpdated, resource("tenant", id)),
+        ("POST", ["tenants", id, "activate"]) => (AuditAction::TenantActivated, resource("tenant", id)),
+        ("POST", ["tenants", id, "deactivate"]) => (AuditAction::TenantDeactivated, resource("tenant", id)),
+        ("DELETE", ["tenants", id]) => (AuditAction::TenantDeleted, resource("tenant", id)),
+
+        // Events, snapshots, compaction
+        ("POST", ["events"]) => (AuditAction::EventIngested, None),
+        ("POST", ["snapshots"]) => (AuditAction::SnapshotCreated, None),
+        ("POST", ["compaction", "trigger"]) => (AuditAction::CompactionTriggered, None),
+
+        // Schemas
+        ("POST", ["schemas"]) => (AuditAction::SchemaRegistered, None),
+        ("PUT", ["schemas", subject, "compatibility"]) => (AuditAction::SchemaUpdated, resource("schema", subject)),
+
+        // Replay
+        ("POST", ["replay"]) => (AuditAction::ReplayStarted, None),
+        ("POST", ["replay", id, "cancel"]) => (AuditAction::ReplayCancelled, resource("replay", id)),
+        ("DELETE", ["replay", id]) => (AuditAction::ReplayDeleted, resource("replay", id)),
+
+        // Pipelines
+        ("POST", ["pipelines"]) => (AuditAction::PipelineCreated, None),
+        ("PUT", ["pipelines", id, "reset"]) => (AuditAction::PipelineUpdated, resource("pipeline", id)),
+        ("DELETE", ["pipelines", id]) => (AuditAction::PipelineDeleted, resource("pipeline", id)),
+
+        // Payload encryption
+        ("PUT", ["encryption", "policies"]) => (AuditAction::ConfigurationChanged, resource("encryption_policy", "*")),
+        ("DELETE", ["encryption", "policies", event_type]) => {
+            (AuditAction::ConfigurationChanged, resource("encryption_policy", event_type))
+        }
+        ("POST", ["encryption", "rotate"]) => (AuditAction::ConfigurationChanged, resource("encryption_key", "payload")),
+
+        // Access policies
+        ("POST", ["policies"]) => (AuditAction::ConfigurationChanged, resource("access_policy", "*")),
+        ("DELETE", ["policies", id]) => (AuditAction::ConfigurationChanged, resource("access_policy", id)),
+
+        // Backup and restore
+        ("POST", ["admin", "backups"]) => (AuditAction::BackupCreated, None),
+        ("POST", ["admin", "backups", id, "restore"]) => (AuditAction::BackupRestored, resource("backup", id)),
+
+        _ => return None,
+    };
+
+    Some(classified)
+}
+
+/// Map a v1 request to the API key operation it needs
+///
+/// Key, user, tenant, audit, encryption, policy and backup management are
+/// admin operations; other routes are reads or writes by method.
+pub fn classify_operation(method: &Method, path: &str) -> ApiOperation {
+    let segments: Vec<&str> = path
+        .trim_start_matches("/api/v1/")
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    match (method.as_str(), segments.as_slice()) {
+        (_, ["events", "stream"]) => ApiOperation::Stream,
+        (_, ["auth", "api-keys" | "users", ..])
+        | (_, ["tenants", ..])
+        | (_, ["audit", ..])
+        | (_, ["encryption", ..])
+        | (_, ["policies", ..])
+        | (_, ["admin", ..]) => ApiOperation::Admin,
+        ("GET" | "HEAD" | "OPTIONS", _) => ApiOperation::Read,
+        _ => ApiOperation::Write,
+    }
+}
+
+// ============================================================================
+// Access Policy Middleware
+// ============================================================================
+
+use crate::security::policy::{PolicyEngine, PolicyRequest, PolicyResource};
+
+/// Largest request body inspected for policy attributes (axum's default limit)
+const MAX_POLICY_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// Access policy state for middleware
+#[derive(Clone)]
+pub struct PolicyState {
+    pub policy_engine: Arc<PolicyEngine>,
+}
+
+/// Access policy middleware
+///
+/// Evaluates the tenant's attribute-based access rules for authenticated
+/// requests and rejects denied ones with 403. Resource attributes come
+/// from the path, the query string and, for POST/PUT/PATCH, the JSON body
+/// (`event_type`, `entity_id`, `projection_name`, `subject`), which is
+/// buffered and handed on unchanged. Tenants without rules skip all of this.
+///
+/// Must be applied after auth_middleware (and inside audit_middleware, so
+/// denials are audited).
+pub async fn policy_middleware(
+    State(policy_state): State<PolicyState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(auth_ctx) = request.extensions().get::<AuthContext>().cloned() else {
+        return next.run(request).await;
+    };
+    if !policy_state.policy_engine.has_rules(auth_ctx.tenant_id()) {
+        return next.run(request).await;
+    }
+
+    let method = request.method().clone();
+    let mut policy_request =
+        PolicyRequest::new(&auth_ctx.claims, classify_operation(&method, request.uri().path()));
+    policy_request.resource = policy_resource(request.uri());
+    policy_request.ip = client_ip(request.extensions());
+
+    let request = if matches!(method, Method::POST | Method::PUT | Method::PATCH) {
+        let (parts, body) = request.into_parts();
+        let Ok(bytes) = axum::body::to_bytes(body, MAX_POLICY_BODY_BYTES).await else {
+            return (StatusCode::PAYLOAD_TOO_LARGE, "Request body too large").into_response();
+        };
+        if let Ok(body) = serde_json::from_slice::<serde_json::Value>(&bytes) {
+            merge_body_attributes(&mut policy_request.resource, &body);
+        }
+        Request::from_parts(parts, axum::body::Body::from(bytes))
+    } else {
+        request
+    };
+
+    let decision = policy_state.policy_engine.evaluate(&policy_request);
+    for rule_id in &decision.warnings {
+        tracing::warn!(
+            "⚠️  Access policy '{}' matched {} {} by {} (tenant {})",
+            rule_id,
+            method,
+            request.uri().path(),
+            auth_ctx.user_id(),
+            auth_ctx.tenant_id()
+        );
+    }
+    if !decision.allowed {
+        return AuthError(AllSourceError::PermissionDenied(decision.reason)).into_response();
+    }
+
+    next.run(request).await
+}
+
+/// Resource attributes carried in a v1 path and query string
+fn policy_resource(uri: &axum::http::Uri) -> PolicyResource {
+    let segments: Vec<&str> = uri
+        .path()
+        .trim_start_matches("/api/v1/")
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    let mut resource = PolicyResource::default();
+    match segments.as_slice() {
+        ["entities", entity_id, ..] | ["snapshots", entity_id, "latest"] => {
+            resource.entity_id = Some(entity_id.to_string());
+        }
+        ["schemas", "validate"] => {}
+        ["schemas", subject, ..] => resource.schema_subject = Some(subject.to_string()),
+        _ => {}
+    }
+
+    if let Ok(axum::extract::Query(params)) =
+        axum::extract::Query::<std::collections::HashMap<String, String>>::try_from_uri(uri)
+    {
+        let param = |name: &str| params.get(name).cloned();
+        resource.event_type = resource.event_type.or_else(|| param("event_type"));
+        resource.entity_id = resource.entity_id.or_else(|| param("entity_id"));
+        resource.projection = resource.projection.or_else(|| param("projection"));
+        resource.schema_subject = resource.schema_subject.or_else(|| param("subject"));
+    }
+
+    resource
+}
+
+/// Resource attributes carried in a JSON request body
+fn merge_body_attributes(resource: &mut PolicyResource, body: &serde_json::Value) {
+    let field = |name: &str| body.get(name).and_then(|v| v.as_str()).map(str::to_string);
+    resource.event_type = resource.event_type.take().or_else(|| field("event_type"));
+    resource.entity_id = resource.entity_id.take().or_else(|| field("entity_id"));
+    resource.projection = resource.projection.take().or_else(|| field("projection_name"));
+    resource.schema_subject = resource.schema_subject.take().or_else(|| field("subject"));
+}
+
+// ============================================================================
+// Read-Your-Writes Middleware
+// ============================================================================
+
+use crate::infrastructure::cluster::replication::{
+    ReplicationLog, ReplicationToken, REPLICATION_TOKEN_HEADER,
+};
+
+/// Replication state for middleware; `None` when not clustered
+#[derive(Clone)]
+pub struct ReplicationState {
+    pub log: Option<Arc<ReplicationLog>>,
+}
+
+/// Read-your-writes middleware
+///
+/// A GET carrying the `X-Replication-Token` returned by an earlier write
+/// waits until this node has applied that write (up to the configured
+/// read wait), then proceeds. If the node is still behind it answers 503
+/// so the client can retry elsewhere. Requests without a token, and
+/// nodes outside a cluster, pass straight through.
+pub async fn read_your_writes_middleware(
+    State(replication_state): State<ReplicationState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(log) = replication_state.log else {
+        return next.run(request).await;
+    };
+    if request.method() != Method::GET {
+        return next.run(request).await;
+    }
+    let Some(value) = request.headers().get(REPLICATION_TOKEN_HEADER) else {
+        return next.run(request).await;
+    };
+
+    let token = match value.to_str().map(str::parse::<ReplicationToken>) {
+        Ok(Ok(token)) => token,
+        _ => {
+            return AllSourceError::InvalidInput("Invalid replication token".to_string())
+                .into_response()
+        }
+    };
+
+    if !log.wait_for_sequence(token, log.config().read_wait_timeout).await {
+        return AllSourceError::ReplicationError(format!(
+            "This node has not yet applied write {}",
+            token
+        ))
+        .into_response();
+    }
+
+    next.run(request).await
+}
+
+// ============================================================================
+// Request Forwarding Middleware
+// ============================================================================
+
+use crate::application::dto::QueryEventsRequest;
+use crate::infrastructure::cluster::forwarding::RequestForwarder;
+use axum::extract::{MatchedPath, Path, Query};
+use std::collections::HashMap;
+
+/// Forwarding state for middleware; `None` when not clustered or
+/// forwarding is disabled
+#[derive(Clone)]
+pub struct ForwardingState {
+    pub forwarder: Option<Arc<RequestForwarder>>,
+}
+
+/// Largest ingest body read to find the entity it belongs to (axum's
+/// default limit)
+const MAX_FORWARDED_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+#[derive(serde::Deserialize)]
+struct IngestEntity {
+    entity_id: String,
+}
+
+/// Request forwarding middleware
+///
+/// Serves entity-scoped requests on the node owning the entity's partition:
+/// event ingest, entity state and snapshot, and queries filtered by entity
+/// are proxied there when this node is not the owner. Queries without an
+/// entity filter run on every healthy node and the results are merged.
+/// Requests already forwarded by another node are served locally.
+pub async fn forwarding_middleware(
+    State(forwarding_state): State<ForwardingState>,
+    matched_path: Option<MatchedPath>,
+    path_params: Option<Path<HashMap<String, String>>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(forwarder) = forwarding_state.forwarder else {
+        return next.run(request).await;
+    };
+    if RequestForwarder::is_forwarded(request.headers()) {
+        return next.run(request).await;
+    }
+    let Some(route) = matched_path else {
+        return next.run(request).await;
+    };
+
+    let (parts, body) = request.into_parts();
+    let entity_id = match (&parts.method, route.as_str()) {
+        (&Method::POST, "/api/v1/events") => None,
+        (&Method::GET, "/api/v1/entities/:entity_id/state" | "/api/v1/entities/:entity_id/snapshot") => {
+            path_params.and_then(|Path(params)| params.get("entity_id").cloned())
+        }
+        (&Method::GET, "/api/v1/events/query") => {
+            let Ok(Query(query)) = Query::<QueryEventsRequest>::try_from_uri(&parts.uri) else {
+                return next.run(Request::from_parts(parts, body)).await;
+            };
+            match query.entity_id {
+                Some(entity_id) => Some(entity_id),
+                None => {
+                    // Cross-partition: run here and on every other node
+                    let mut copy = axum::http::Request::new(axum::body::Bytes::new());
+                    *copy.method_mut() = parts.method.clone();
+                    *copy.uri_mut() = parts.uri.clone();
+                    *copy.headers_mut() = parts.headers.clone();
+                    if let Some(auth) = parts.extensions.get::<AuthContext>() {
+                        copy.extensions_mut().insert(auth.clone());
+                    }
+
+                    let local = next.run(Request::from_parts(parts, body)).await;
+                    return forwarder
+                        .fan_out_query(&copy, local, query.limit)
+                        .await
+                        .unwrap_or_else(IntoResponse::into_response);
+                }
+            }
+        }
+        _ => return next.run(Request::from_parts(parts, body)).await,
+    };
+
+    let Ok(bytes) = axum::body::to_bytes(body, MAX_FORWARDED_BODY_BYTES).await else {
+        return (StatusCode::PAYLOAD_TOO_LARGE, "Request body too large").into_response();
+    };
+    // Ingest names its entity in the body; unparseable bodies are left to the handler
+    let entity_id = entity_id.or_else(|| {
+        serde_json::from_slice::<IngestEntity>(&bytes)
+            .ok()
+            .map(|ingest| ingest.entity_id)
+    });
+
+    let owner = match entity_id.map(|entity_id| forwarder.owner_of(&entity_id)) {
+        Some(Ok(owner)) => owner,
+        Some(Err(e)) => return e.into_response(),
+        None => None,
+    };
+    match owner {
+        Some(node) => {
+            tracing::debug!("Forwarding {} {} to node {}", parts.method, parts.uri, node.id);
+            let request = axum::http::Request::from_parts(parts, bytes);
+            forwarder
+                .forward(&node, &request)
+                .await
+                .unwrap_or_else(IntoResponse::into_response)
+        }
+        None => next.run(Request::from_parts(parts, Body::from(bytes))).await,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -737,4 +1538,51 @@ mod tests {
         assert!(ctx.require_permission(Permission::Write).is_ok());
         assert!(ctx.require_permission(Permission::Admin).is_err());
     }
+
+    #[test]
+    fn test_classify_operation() {
+        assert_eq!(classify_operation(&Method::GET, "/api/v1/events/query"), ApiOperation::Read);
+        assert_eq!(classify_operation(&Method::POST, "/api/v1/events"), ApiOperation::Write);
+        assert_eq!(classify_operation(&Method::GET, "/api/v1/events/stream"), ApiOperation::Stream);
+        assert_eq!(classify_operation(&Method::GET, "/api/v1/auth/api-keys"), ApiOperation::Admin);
+        assert_eq!(classify_operation(&Method::DELETE, "/api/v1/tenants/acme"), ApiOperation::Admin);
+        assert_eq!(classify_operation(&Method::GET, "/api/v1/audit"), ApiOperation::Admin);
+        assert_eq!(classify_operation(&Method::GET, "/api/v1/auth/me"), ApiOperation::Read);
+        assert_eq!(classify_operation(&Method::POST, "/api/v1/admin/backups"), ApiOperation::Admin);
+    }
+
+    #[test]
+    fn test_classify_backup_audit_actions() {
+        let (action, resource) = classify_audit_action(&Method::POST, "/api/v1/admin/backups").unwrap();
+        assert_eq!(action, AuditAction::BackupCreated);
+        assert!(resource.is_none());
+
+        let (action, resource) =
+            classify_audit_action(&Method::POST, "/api/v1/admin/backups/full_20261018/restore").unwrap();
+        assert_eq!(action, AuditAction::BackupRestored);
+        assert_eq!(resource, Some(("backup".to_string(), "full_20261018".to_string())));
+
+        assert!(classify_audit_action(&Method::GET, "/api/v1/admin/backups").is_none());
+        assert!(classify_audit_action(&Method::GET, "/api/v1/admin/backups/jobs").is_none());
+    }
+
+    #[test]
+    fn test_trusted_proxy_resolution() {
+        let proxies = TrustedProxies::new(vec!["10.0.0.0/8".parse().unwrap()]);
+        let ip = |s: &str| s.parse::<IpAddr>().unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", "1.2.3.4, 203.0.113.7, 10.0.0.2".parse().unwrap());
+
+        // Untrusted peers cannot spoof their address
+        assert_eq!(proxies.resolve(ip("198.51.100.9"), &headers), ip("198.51.100.9"));
+        assert_eq!(TrustedProxies::default().resolve(ip("10.0.0.1"), &headers), ip("10.0.0.1"));
+
+        // Trusted hops are skipped; the first untrusted hop is the client
+        assert_eq!(proxies.resolve(ip("10.0.0.1"), &headers), ip("203.0.113.7"));
+
+        // A malformed hop stops the walk at the last verified address
+        headers.insert("x-forwarded-for", "203.0.113.7, garbage, 10.0.0.2".parse().unwrap());
+        assert_eq!(proxies.resolve(ip("10.0.0.1"), &headers), ip("10.0.0.2"));
+        assert_eq!(proxies.resolve(ip("10.0.0.1"), &HeaderMap::new()), ip("10.0.0.1"));
+    }
 }
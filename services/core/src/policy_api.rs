@@ -0,0 +1,153 @@
+use crate::auth::{ApiOperation, Role};
+use crate::error::AllSourceError;
+use crate::middleware::Admin;
+use crate::security::policy::{
+    PolicyDecision, PolicyEngine, PolicyRequest, PolicyResource, PolicyRule,
+};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+
+// AppState is defined in api_v1.rs
+use crate::api_v1::AppState;
+
+// ============================================================================
+// Request/Response Types
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+pub struct PolicyListResponse {
+    pub tenant_id: String,
+    pub rules: Vec<PolicyRule>,
+    pub total: usize,
+}
+
+/// Attributes to explain a decision for; the subject defaults to the caller
+#[derive(Debug, Deserialize)]
+pub struct EvaluatePolicyRequest {
+    pub user_id: Option<String>,
+    pub role: Option<Role>,
+    pub action: ApiOperation,
+    #[serde(default)]
+    pub resource: PolicyResource,
+    pub ip: Option<IpAddr>,
+    pub time: Option<DateTime<Utc>>,
+    /// Candidate rules to dry-run instead of the stored ones
+    pub rules: Option<Vec<PolicyRule>>,
+}
+
+fn policy_error(err: AllSourceError) -> (StatusCode, String) {
+    match err {
+        AllSourceError::ValidationError(msg) => (StatusCode::BAD_REQUEST, msg),
+        other => (StatusCode::INTERNAL_SERVER_ERROR, other.to_string()),
+    }
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// List the caller's tenant access policies in evaluation order (admin only)
+/// GET /api/v1/policies
+pub async fn list_policies_handler(
+    State(state): State<AppState>,
+    Admin(auth_ctx): Admin,
+) -> Json<PolicyListResponse> {
+    let rules = state.policy_engine.list_rules(auth_ctx.tenant_id());
+
+    Json(PolicyListResponse {
+        tenant_id: auth_ctx.tenant_id().to_string(),
+        total: rules.len(),
+        rules,
+    })
+}
+
+/// Create or replace (by id) an access policy (admin only)
+/// POST /api/v1/policies
+pub async fn put_policy_handler(
+    State(state): State<AppState>,
+    Admin(auth_ctx): Admin,
+    Json(rule): Json<PolicyRule>,
+) -> Result<Json<PolicyRule>, (StatusCode, String)> {
+    state
+        .policy_engine
+        .put_rule(auth_ctx.tenant_id(), rule.clone())
+        .map_err(policy_error)?;
+
+    tracing::info!(
+        "🛡️  Access policy '{}' ({:?}, priority {}) set for tenant {}",
+        rule.id,
+        rule.effect,
+        rule.priority,
+        auth_ctx.tenant_id()
+    );
+
+    Ok(Json(rule))
+}
+
+/// Get an access policy (admin only)
+/// GET /api/v1/policies/:id
+pub async fn get_policy_handler(
+    State(state): State<AppState>,
+    Admin(auth_ctx): Admin,
+    Path(rule_id): Path<String>,
+) -> Result<Json<PolicyRule>, (StatusCode, String)> {
+    state
+        .policy_engine
+        .get_rule(auth_ctx.tenant_id(), &rule_id)
+        .map(Json)
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("Policy not found: {}", rule_id)))
+}
+
+/// Delete an access policy (admin only)
+/// DELETE /api/v1/policies/:id
+pub async fn delete_policy_handler(
+    State(state): State<AppState>,
+    Admin(auth_ctx): Admin,
+    Path(rule_id): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let removed = state
+        .policy_engine
+        .remove_rule(auth_ctx.tenant_id(), &rule_id)
+        .map_err(policy_error)?;
+
+    if removed {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err((StatusCode::NOT_FOUND, format!("Policy not found: {}", rule_id)))
+    }
+}
+
+/// Explain the decision for a request, optionally against candidate rules
+/// (dry run); nothing is stored or enforced (admin only)
+/// POST /api/v1/policies/evaluate
+pub async fn evaluate_policy_handler(
+    State(state): State<AppState>,
+    Admin(auth_ctx): Admin,
+    Json(req): Json<EvaluatePolicyRequest>,
+) -> Result<Json<PolicyDecision>, (StatusCode, String)> {
+    let mut request = PolicyRequest::new(&auth_ctx.claims, req.action);
+    if let Some(user_id) = req.user_id {
+        request.user_id = user_id;
+    }
+    if let Some(role) = req.role {
+        request.role = role;
+    }
+    request.resource = req.resource;
+    request.ip = req.ip;
+    if let Some(time) = req.time {
+        request.time = time;
+    }
+
+    let decision = match req.rules {
+        Some(rules) => PolicyEngine::explain_with(&rules, &request).map_err(policy_error)?,
+        None => state.policy_engine.explain(&request),
+    };
+
+    Ok(Json(decision))
+}
@@ -5,11 +5,14 @@
 /// - Per-user rate limiting
 /// - Per-API key rate limiting
 /// - Configurable limits
+/// - Weighted request costs (per route, payload size and query work)
 /// - Efficient in-memory storage with DashMap
 /// - Automatic token replenishment
 
+use crate::security::policy::wildcard_match;
 use chrono::{DateTime, Utc};
 use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -110,14 +113,14 @@ impl TokenBucket {
         self.tokens.floor() as u32
     }
 
-    /// Get time until next token is available
-    fn retry_after(&mut self) -> Duration {
+    /// Get time until `tokens` tokens are available
+    fn retry_after(&mut self, tokens: f64) -> Duration {
         self.refill();
 
-        if self.tokens >= 1.0 {
+        if self.tokens >= tokens {
             Duration::from_secs(0)
         } else {
-            let tokens_needed = 1.0 - self.tokens;
+            let tokens_needed = tokens - self.tokens;
             let seconds = tokens_needed / self.refill_rate;
             Duration::from_secs_f64(seconds)
         }
@@ -151,17 +154,32 @@ impl RateLimiter {
         self.buckets.remove(identifier);
     }
 
+    /// Whether a custom config is set for this identifier
+    pub fn has_config(&self, identifier: &str) -> bool {
+        self.custom_configs.contains_key(identifier)
+    }
+
+    /// Remove a custom config, reverting the identifier to the default limits
+    pub fn remove_config(&self, identifier: &str) -> bool {
+        let removed = self.custom_configs.remove(identifier).is_some();
+        if removed {
+            self.buckets.remove(identifier);
+        }
+        removed
+    }
+
     /// Check if request is allowed
     pub fn check_rate_limit(&self, identifier: &str) -> RateLimitResult {
         self.check_rate_limit_with_cost(identifier, 1.0)
     }
 
     /// Check rate limit with custom cost (for expensive operations)
+    ///
+    /// Costs above the burst size are capped at it, so a single expensive
+    /// request drains the bucket rather than being rejected forever.
     pub fn check_rate_limit_with_cost(&self, identifier: &str, cost: f64) -> RateLimitResult {
-        let config = self.custom_configs
-            .get(identifier)
-            .map(|c| c.clone())
-            .unwrap_or_else(|| self.default_config.clone());
+        let config = self.config_for(identifier);
+        let cost = cost.min(config.burst_size as f64);
 
         let mut entry = self.buckets
             .entry(identifier.to_string())
@@ -170,7 +188,7 @@ impl RateLimiter {
         let allowed = entry.try_consume(cost);
         let remaining = entry.remaining();
         let retry_after = if !allowed {
-            Some(entry.retry_after())
+            Some(entry.retry_after(cost))
         } else {
             None
         };
@@ -183,12 +201,39 @@ impl RateLimiter {
         }
     }
 
+    /// Charge a cost known only after the request ran (e.g. rows scanned).
+    /// Always succeeds; the balance may go negative, delaying later requests.
+    pub fn charge(&self, identifier: &str, cost: f64) -> RateLimitResult {
+        let config = self.config_for(identifier);
+
+        let mut entry = self.buckets
+            .entry(identifier.to_string())
+            .or_insert_with(|| TokenBucket::new(&config));
+
+        entry.refill();
+        entry.tokens -= cost.max(0.0);
+
+        RateLimitResult {
+            allowed: true,
+            remaining: entry.remaining(),
+            retry_after: None,
+            limit: config.requests_per_minute,
+        }
+    }
+
+    fn config_for(&self, identifier: &str) -> RateLimitConfig {
+        self.custom_configs
+            .get(identifier)
+            .map(|c| c.clone())
+            .unwrap_or_else(|| self.default_config.clone())
+    }
+
     /// Get current stats for an identifier
     pub fn get_stats(&self, identifier: &str) -> Option<RateLimitStats> {
         self.buckets.get_mut(identifier).map(|mut bucket| {
             RateLimitStats {
                 remaining: bucket.remaining(),
-                retry_after: bucket.retry_after(),
+                retry_after: bucket.retry_after(1.0),
             }
         })
     }
@@ -231,6 +276,122 @@ pub struct RateLimitStats {
     pub retry_after: Duration,
 }
 
+/// Base cost for requests matching a route
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RouteCost {
+    /// HTTP method (None = any)
+    #[serde(default)]
+    pub method: Option<String>,
+    /// Path pattern; `*` matches any run of characters
+    pub path: String,
+    pub cost: f64,
+}
+
+impl RouteCost {
+    pub fn new(method: Option<&str>, path: &str, cost: f64) -> Self {
+        Self {
+            method: method.map(str::to_string),
+            path: path.to_string(),
+            cost,
+        }
+    }
+
+    fn matches(&self, method: &str, path: &str) -> bool {
+        self.method.as_deref().is_none_or(|m| m.eq_ignore_ascii_case(method))
+            && wildcard_match(&self.path, path)
+    }
+}
+
+/// Work done by a query, reported by handlers in the response extensions
+/// so the rate limiter can charge for it after execution
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct QueryCost {
+    pub rows_scanned: usize,
+    pub rows_returned: usize,
+}
+
+/// Request cost model, in tokens
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct CostModel {
+    /// Cost of a request matching no route
+    pub default_cost: f64,
+    /// Base costs by route; the first match wins
+    pub routes: Vec<RouteCost>,
+    /// Extra cost per KiB of request body (ingest payloads)
+    pub ingest_cost_per_kb: f64,
+    /// Extra cost per 1,000 events a query scanned
+    pub query_cost_per_1k_scanned: f64,
+    /// Extra cost per 100 events a query returned
+    pub query_cost_per_100_returned: f64,
+}
+
+impl CostModel {
+    /// Free tier: payloads and query work weigh double
+    pub fn free_tier() -> Self {
+        Self {
+            ingest_cost_per_kb: 0.25,
+            query_cost_per_1k_scanned: 2.0,
+            query_cost_per_100_returned: 1.0,
+            ..Self::professional()
+        }
+    }
+
+    /// Professional tier: expensive routes and large payloads cost more
+    pub fn professional() -> Self {
+        Self {
+            default_cost: 1.0,
+            routes: vec![
+                RouteCost::new(None, "/health", 0.0),
+                RouteCost::new(None, "/metrics", 0.0),
+                RouteCost::new(Some("GET"), "/api/v1/events/query", 2.0),
+                RouteCost::new(Some("GET"), "/api/v1/events/stream", 5.0),
+                RouteCost::new(Some("GET"), "/api/v1/entities/*/state", 2.0),
+                RouteCost::new(None, "/api/v1/analytics/*", 10.0),
+                RouteCost::new(Some("POST"), "/api/v1/replay", 20.0),
+                RouteCost::new(Some("POST"), "/api/v1/compaction/trigger", 20.0),
+            ],
+            ingest_cost_per_kb: 0.1,
+            query_cost_per_1k_scanned: 1.0,
+            query_cost_per_100_returned: 0.5,
+        }
+    }
+
+    /// Every request costs one token
+    pub fn flat() -> Self {
+        Self {
+            default_cost: 1.0,
+            routes: Vec::new(),
+            ingest_cost_per_kb: 0.0,
+            query_cost_per_1k_scanned: 0.0,
+            query_cost_per_100_returned: 0.0,
+        }
+    }
+
+    /// Up-front cost of a request: route base cost plus payload size
+    pub fn request_cost(&self, method: &str, path: &str, body_bytes: u64) -> f64 {
+        let base = self
+            .routes
+            .iter()
+            .find(|route| route.matches(method, path))
+            .map(|route| route.cost)
+            .unwrap_or(self.default_cost);
+        base + self.ingest_cost_per_kb * body_bytes as f64 / 1024.0
+    }
+
+    /// Cost of a query's work, charged after it ran
+    pub fn query_cost(&self, query: &QueryCost) -> f64 {
+        self.query_cost_per_1k_scanned * query.rows_scanned as f64 / 1000.0
+            + self.query_cost_per_100_returned * query.rows_returned as f64 / 100.0
+    }
+}
+
+impl Default for CostModel {
+    fn default() -> Self {
+        Self::professional()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -339,4 +500,45 @@ mod tests {
         assert!(result.allowed);
         assert_eq!(result.remaining, 5);
     }
+
+    #[test]
+    fn test_post_execution_charge() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            requests_per_minute: 60,
+            burst_size: 10,
+        });
+
+        assert!(limiter.check_rate_limit_with_cost("user1", 4.0).allowed);
+        // Charged after the fact, even beyond the remaining balance
+        let result = limiter.charge("user1", 20.0);
+        assert!(result.allowed);
+        assert_eq!(result.remaining, 0);
+
+        let result = limiter.check_rate_limit("user1");
+        assert!(!result.allowed);
+        assert!(result.retry_after.unwrap() > Duration::from_secs(10));
+
+        // Costs above the burst size drain the bucket instead of never passing
+        assert!(limiter.check_rate_limit_with_cost("user2", 50.0).allowed);
+        assert_eq!(limiter.check_rate_limit("user2").remaining, 0);
+    }
+
+    #[test]
+    fn test_cost_model() {
+        let model = CostModel::professional();
+        assert_eq!(model.request_cost("GET", "/health", 0), 0.0);
+        assert_eq!(model.request_cost("GET", "/api/v1/stats", 0), 1.0);
+        assert_eq!(model.request_cost("get", "/api/v1/analytics/correlation", 0), 10.0);
+        assert_eq!(model.request_cost("GET", "/api/v1/entities/user-1/state", 0), 2.0);
+        assert_eq!(model.request_cost("POST", "/api/v1/events", 20 * 1024), 3.0);
+
+        let query = QueryCost {
+            rows_scanned: 5_000,
+            rows_returned: 200,
+        };
+        assert_eq!(model.query_cost(&query), 6.0);
+        assert_eq!(CostModel::free_tier().query_cost(&query), 12.0);
+        assert_eq!(CostModel::flat().query_cost(&query), 0.0);
+        assert_eq!(CostModel::flat().request_cost("GET", "/api/v1/analytics/summary", 1 << 20), 1.0);
+    }
 }
@@ -150,6 +150,7 @@ impl ReplayManager {
             since: request.from_timestamp,
             until: request.to_timestamp,
             limit: None,
+            payload_contains: None,
         };
 
         let events = store.query(query)?;
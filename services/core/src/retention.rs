@@ -0,0 +1,509 @@
+//! Event retention and time-to-live
+//!
+//! Rules are scoped to a tenant, an event-type namespace (the part of the
+//! event type before the first dot), both, or neither. Each event is
+//! governed by the most specific rule with limits that matches it:
+//!
+//! - `max_age_secs` expires events older than the given age
+//! - `max_events_per_entity` keeps only the newest events of each entity
+//! - `keep_latest` protects the newest events of each entity from both
+//!
+//! Any matching rule with `legal_hold` set keeps the event regardless.
+//! Compaction applies the rules to Parquet files, and `RetentionTask`
+//! sweeps them from memory, the index, the WAL and snapshots.
+
+use crate::domain::entities::Event;
+use crate::error::{AllSourceError, Result};
+use crate::store::EventStore;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Retention rules and how often the store is swept
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetentionConfig {
+    #[serde(default)]
+    pub rules: Vec<RetentionRule>,
+
+    #[serde(default = "default_sweep_interval_secs")]
+    pub sweep_interval_secs: u64,
+}
+
+fn default_sweep_interval_secs() -> u64 {
+    3600 // 1 hour
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            sweep_interval_secs: default_sweep_interval_secs(),
+        }
+    }
+}
+
+/// Retention limits for the events of a tenant and/or namespace
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RetentionRule {
+    /// Tenant the rule applies to; all tenants if unset
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+
+    /// Event-type namespace the rule applies to; all types if unset
+    #[serde(default)]
+    pub namespace: Option<String>,
+
+    #[serde(default)]
+    pub max_age_secs: Option<u64>,
+
+    #[serde(default)]
+    pub max_events_per_entity: Option<usize>,
+
+    #[serde(default)]
+    pub keep_latest: Option<usize>,
+
+    /// Keep every matching event, whatever other rules say
+    #[serde(default)]
+    pub legal_hold: bool,
+}
+
+impl RetentionRule {
+    fn has_limits(&self) -> bool {
+        self.max_age_secs.is_some() || self.max_events_per_entity.is_some()
+    }
+
+    fn matches(&self, event: &Event) -> bool {
+        self.tenant_id
+            .as_deref()
+            .is_none_or(|tenant_id| event.tenant_id_str() == tenant_id)
+            && self
+                .namespace
+                .as_deref()
+                .is_none_or(|namespace| event.is_in_namespace(namespace))
+    }
+
+    /// Tenant rules beat namespace rules, which beat catch-alls
+    fn specificity(&self) -> u8 {
+        (self.tenant_id.is_some() as u8) * 2 + self.namespace.is_some() as u8
+    }
+
+    /// Whether the event at `rank` (0 = its entity's newest) has expired
+    fn expires(&self, rank: usize, timestamp: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        if self.keep_latest.is_some_and(|keep| rank < keep) {
+            return false;
+        }
+
+        self.max_events_per_entity.is_some_and(|max| rank >= max)
+            || self
+                .max_age_secs
+                .is_some_and(|max_age| (now - timestamp).num_seconds() > max_age as i64)
+    }
+}
+
+/// Outcome of evaluating the rules over a set of events
+#[derive(Debug, Clone, Default)]
+pub struct RetentionVerdict {
+    /// Whether the event at each position has expired
+    pub expired: Vec<bool>,
+    pub events_expired: usize,
+    /// Events that would have expired but are under legal hold
+    pub events_held: usize,
+}
+
+/// Validated retention rules
+#[derive(Debug, Clone, Default)]
+pub struct RetentionPolicies {
+    rules: Vec<RetentionRule>,
+}
+
+impl RetentionPolicies {
+    pub fn new(rules: Vec<RetentionRule>) -> Result<Self> {
+        for rule in &rules {
+            let scope = format!(
+                "tenant {}, namespace {}",
+                rule.tenant_id.as_deref().unwrap_or("*"),
+                rule.namespace.as_deref().unwrap_or("*")
+            );
+            if !rule.has_limits() && !rule.legal_hold {
+                return Err(AllSourceError::ValidationError(format!(
+                    "Retention rule for {} sets neither a limit nor a legal hold",
+                    scope
+                )));
+            }
+            if rule.max_events_per_entity == Some(0) || rule.max_age_secs == Some(0) {
+                return Err(AllSourceError::ValidationError(format!(
+                    "Retention limits for {} must be positive",
+                    scope
+                )));
+            }
+        }
+
+        Ok(Self { rules })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.rules.iter().any(RetentionRule::has_limits)
+    }
+
+    /// Decide which of `events` have expired as of `now`.
+    ///
+    /// Per-entity counts are taken over `events`, so they should hold
+    /// every stored event of the entities concerned. Ties in timestamp go
+    /// to the later position.
+    pub fn evaluate(&self, events: &[Event], now: DateTime<Utc>) -> RetentionVerdict {
+        let mut verdict = RetentionVerdict {
+            expired: vec![false; events.len()],
+            ..Default::default()
+        };
+        if !self.is_enabled() {
+            return verdict;
+        }
+
+        let mut newest_first: Vec<usize> = (0..events.len()).collect();
+        newest_first.sort_by(|&a, &b| (events[b].timestamp, b).cmp(&(events[a].timestamp, a)));
+
+        let mut ranks: HashMap<(usize, &str, &str), usize> = HashMap::new();
+        for position in newest_first {
+            let event = &events[position];
+            let Some(rule) = self.governing_rule(event) else {
+                continue;
+            };
+
+            let rank = ranks
+                .entry((rule, event.tenant_id_str(), event.entity_id_str()))
+                .or_insert(0);
+            let expires = self.rules[rule].expires(*rank, event.timestamp, now);
+            *rank += 1;
+
+            if !expires {
+                continue;
+            }
+            if self.is_held(event) {
+                verdict.events_held += 1;
+            } else {
+                verdict.expired[position] = true;
+                verdict.events_expired += 1;
+            }
+        }
+
+        verdict
+    }
+
+    /// Index of the most specific rule with limits matching `event`
+    fn governing_rule(&self, event: &Event) -> Option<usize> {
+        self.rules
+            .iter()
+            .enumerate()
+            .filter(|(_, rule)| rule.has_limits() && rule.matches(event))
+            .max_by_key(|(index, rule)| (rule.specificity(), std::cmp::Reverse(*index)))
+            .map(|(index, _)| index)
+    }
+
+    /// Whether a legal hold keeps `event`
+    pub fn is_held(&self, event: &Event) -> bool {
+        self.rules
+            .iter()
+            .any(|rule| rule.legal_hold && rule.matches(event))
+    }
+}
+
+/// Result of sweeping expired events from the store
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RetentionSweepResult {
+    pub events_expired: usize,
+    pub events_held: usize,
+    pub wal_entries_removed: usize,
+    pub parquet_events_removed: usize,
+    pub snapshots_removed: usize,
+}
+
+/// Background retention sweeper
+pub struct RetentionTask {
+    store: Arc<EventStore>,
+    interval: Duration,
+}
+
+impl RetentionTask {
+    /// Create a new background retention task
+    pub fn new(store: Arc<EventStore>, interval_seconds: u64) -> Self {
+        Self {
+            store,
+            interval: Duration::from_secs(interval_seconds),
+        }
+    }
+
+    /// Run the retention sweep in a loop
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(self.interval);
+
+        loop {
+            interval.tick().await;
+
+            let store = Arc::clone(&self.store);
+            match tokio::task::spawn_blocking(move || store.apply_retention()).await {
+                Ok(Ok(result)) if result.events_expired > 0 => {
+                    tracing::info!(
+                        "🧹 Retention sweep removed {} events ({} held)",
+                        result.events_expired,
+                        result.events_held
+                    );
+                }
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => tracing::error!("Retention sweep failed: {}", e),
+                Err(e) => tracing::error!("Retention sweep panicked: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::dto::QueryEventsRequest;
+    use crate::backup::{BackupConfig, BackupManager};
+    use crate::store::EventStoreConfig;
+    use crate::wal::WALConfig;
+    use serde_json::json;
+    use std::sync::atomic::AtomicU64;
+    use tempfile::TempDir;
+
+    fn event(tenant_id: &str, entity_id: &str, event_type: &str, age_secs: i64) -> Event {
+        Event::reconstruct_from_strings(
+            uuid::Uuid::new_v4(),
+            event_type.to_string(),
+            entity_id.to_string(),
+            tenant_id.to_string(),
+            json!({}),
+            Utc::now() - chrono::Duration::seconds(age_secs),
+            None,
+            1,
+        )
+    }
+
+    fn expired(policies: &RetentionPolicies, events: &[Event]) -> Vec<usize> {
+        let verdict = policies.evaluate(events, Utc::now());
+        (0..events.len()).filter(|&i| verdict.expired[i]).collect()
+    }
+
+    #[test]
+    fn test_max_age_and_keep_latest() {
+        let policies = RetentionPolicies::new(vec![RetentionRule {
+            max_age_secs: Some(100),
+            keep_latest: Some(1),
+            ..Default::default()
+        }])
+        .unwrap();
+
+        let events = vec![
+            event("acme", "a", "order.placed", 500),
+            event("acme", "a", "order.paid", 300),
+            event("acme", "b", "order.placed", 400),
+            event("acme", "a", "order.shipped", 50),
+        ];
+        // The newest event of "b" is kept despite its age
+        assert_eq!(expired(&policies, &events), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_max_events_per_entity() {
+        let policies = RetentionPolicies::new(vec![RetentionRule {
+            max_events_per_entity: Some(2),
+            ..Default::default()
+        }])
+        .unwrap();
+
+        let events: Vec<Event> = (0..5).map(|i| event("acme", "a", "order.updated", 50 - i)).collect();
+        assert_eq!(expired(&policies, &events), vec![0, 1, 2]);
+
+        // Entities are counted per tenant
+        let events = vec![
+            event("acme", "a", "order.placed", 30),
+            event("globex", "a", "order.placed", 20),
+            event("acme", "a", "order.paid", 10),
+        ];
+        assert!(expired(&policies, &events).is_empty());
+    }
+
+    #[test]
+    fn test_most_specific_rule_governs() {
+        let policies = RetentionPolicies::new(vec![
+            RetentionRule {
+                max_age_secs: Some(1000),
+                ..Default::default()
+            },
+            RetentionRule {
+                namespace: Some("audit".to_string()),
+                max_age_secs: Some(10_000),
+                ..Default::default()
+            },
+            RetentionRule {
+                tenant_id: Some("acme".to_string()),
+                max_age_secs: Some(100),
+                ..Default::default()
+            },
+        ])
+        .unwrap();
+
+        let events = vec![
+            event("globex", "a", "order.placed", 500),
+            event("globex", "a", "audit.logged", 5000),
+            event("acme", "a", "audit.logged", 500),
+            event("globex", "b", "order.placed", 5000),
+        ];
+        assert_eq!(expired(&policies, &events), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_legal_hold_overrides_retention() {
+        let policies = RetentionPolicies::new(vec![
+            RetentionRule {
+                max_age_secs: Some(100),
+                ..Default::default()
+            },
+            RetentionRule {
+                tenant_id: Some("acme".to_string()),
+                legal_hold: true,
+                ..Default::default()
+            },
+        ])
+        .unwrap();
+
+        let events = vec![
+            event("acme", "a", "order.placed", 500),
+            event("globex", "a", "order.placed", 500),
+            event("acme", "b", "order.placed", 50),
+        ];
+        let verdict = policies.evaluate(&events, Utc::now());
+        assert_eq!(verdict.expired, vec![false, true, false]);
+        assert_eq!((verdict.events_expired, verdict.events_held), (1, 1));
+    }
+
+    #[test]
+    fn test_rule_validation() {
+        assert!(!RetentionPolicies::new(Vec::new()).unwrap().is_enabled());
+        assert!(RetentionPolicies::new(vec![RetentionRule::default()]).is_err());
+        assert!(RetentionPolicies::new(vec![RetentionRule {
+            max_events_per_entity: Some(0),
+            ..Default::default()
+        }])
+        .is_err());
+
+        let hold_only = RetentionPolicies::new(vec![RetentionRule {
+            legal_hold: true,
+            ..Default::default()
+        }])
+        .unwrap();
+        assert!(!hold_only.is_enabled());
+    }
+
+    #[test]
+    fn test_store_sweep() {
+        let dir = TempDir::new().unwrap();
+        let config = EventStoreConfig {
+            storage_dir: Some(dir.path().join("data")),
+            wal_dir: Some(dir.path().join("wal")),
+            wal_config: WALConfig::default(),
+            retention: RetentionConfig {
+                rules: vec![
+                    RetentionRule {
+                        tenant_id: Some("acme".to_string()),
+                        max_events_per_entity: Some(2),
+                        ..Default::default()
+                    },
+                    RetentionRule {
+                        namespace: Some("audit".to_string()),
+                        legal_hold: true,
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let store = EventStore::with_config(config);
+
+        let log = vec![
+            event("acme", "order-1", "order.placed", 50),
+            event("acme", "order-1", "audit.logged", 40),
+            event("acme", "order-1", "order.paid", 30),
+            event("acme", "order-1", "order.shipped", 20),
+            event("globex", "order-1", "order.placed", 10),
+        ];
+        for event in log.clone() {
+            store.ingest(event).unwrap();
+        }
+        store.create_snapshot("order-1").unwrap();
+
+        let result = store.apply_retention().unwrap();
+        assert_eq!((result.events_expired, result.events_held), (1, 1));
+        assert_eq!(result.wal_entries_removed, 1);
+        assert_eq!(result.parquet_events_removed, 1);
+        assert_eq!(result.snapshots_removed, 1);
+        assert!(store.snapshot_manager().get_latest_snapshot("order-1").is_none());
+
+        let remaining = store
+            .query(QueryEventsRequest {
+                entity_id: Some("order-1".to_string()),
+                event_type: None,
+                tenant_id: None,
+                as_of: None,
+                since: None,
+                until: None,
+                limit: None,
+                payload_contains: None,
+            })
+            .unwrap();
+        let expected: Vec<_> = log[1..].iter().map(|event| event.id).collect();
+        assert_eq!(remaining.iter().map(|event| event.id).collect::<Vec<_>>(), expected);
+        assert_eq!(store.compaction_manager().unwrap().stats().total_events_expired, 1);
+
+        // Sequences keep their numbers, and backups span the gap
+        assert_eq!(store.current_sequence(), 5);
+        assert_eq!(store.events_since(1).len(), 4);
+        assert_eq!(store.events_between(2, 4).len(), 2);
+        let backups = BackupManager::new(BackupConfig {
+            backup_dir: dir.path().join("backups"),
+            ..Default::default()
+        })
+        .unwrap();
+        let backup = backups
+            .create_backup_with_progress(None, &store.events_since(0), Some(5), &AtomicU64::new(0))
+            .unwrap();
+        assert_eq!((backup.event_count, backup.to_sequence), (4, 5));
+        backups.validate_chain(&backup.backup_id).unwrap();
+
+        // The expired event does not come back from the WAL
+        drop(store);
+        let store = EventStore::with_config(EventStoreConfig::with_wal(
+            dir.path().join("wal"),
+            WALConfig::default(),
+        ));
+        assert_eq!(store.stats().total_events, 4);
+    }
+
+    #[test]
+    fn test_config_format() {
+        let config: RetentionConfig = toml::from_str(
+            r#"
+            sweep_interval_secs = 60
+
+            [[rules]]
+            max_age_secs = 2592000
+
+            [[rules]]
+            tenant_id = "acme"
+            namespace = "audit"
+            legal_hold = true
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.sweep_interval_secs, 60);
+        assert_eq!(config.rules.len(), 2);
+        assert_eq!(config.rules[1].namespace.as_deref(), Some("audit"));
+        assert!(config.rules[1].legal_hold);
+        assert_eq!(toml::from_str::<RetentionConfig>("").unwrap(), RetentionConfig::default());
+    }
+}
@@ -7,16 +7,19 @@
 /// - Tenant behavior
 /// - Attack detection
 
-use crate::error::{AllSourceError, Result};
-use crate::rate_limit::{RateLimitConfig, RateLimitResult};
-use chrono::{DateTime, Duration, Timelike, Utc};
+use crate::error::Result;
+use crate::rate_limit::RateLimitResult;
+use chrono::{DateTime, Datelike, Duration, Timelike, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use parking_lot::RwLock;
 
 /// Adaptive rate limiting configuration
+///
+/// Limits are requests per tenant per hour.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AdaptiveRateLimitConfig {
     /// Enable adaptive rate limiting
     pub enabled: bool,
@@ -72,6 +75,7 @@ struct TenantUsageProfile {
     avg_requests_per_hour: f64,
     stddev_requests_per_hour: f64,
     max_requests_per_hour: f64,
+    observed_hours: usize,
 
     // Adaptive parameters
     current_limit: u32,
@@ -91,17 +95,44 @@ struct LimitAdjustment {
     reason: AdjustmentReason,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-enum AdjustmentReason {
+/// Why a tenant's limit changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AdjustmentReason {
     NormalLearning,
     AnomalyDetected,
     HighLoad,
+    /// Back towards the base limit after a load or anomaly cut
+    Recovered,
     AttackMitigation,
     PatternPrediction,
 }
 
+impl AdjustmentReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AdjustmentReason::NormalLearning => "normal_learning",
+            AdjustmentReason::AnomalyDetected => "anomaly_detected",
+            AdjustmentReason::HighLoad => "high_load",
+            AdjustmentReason::Recovered => "recovered",
+            AdjustmentReason::AttackMitigation => "attack_mitigation",
+            AdjustmentReason::PatternPrediction => "pattern_prediction",
+        }
+    }
+}
+
+/// A limit change made by `update_adaptive_limits`
+#[derive(Debug, Clone, Serialize)]
+pub struct LimitChange {
+    pub tenant_id: String,
+    pub old_limit: u32,
+    pub new_limit: u32,
+    pub reason: AdjustmentReason,
+}
+
 /// System load metrics
-#[derive(Debug, Clone)]
+///
+/// CPU and memory usage are fractions (0.0-1.0).
+#[derive(Debug, Clone, Default)]
 pub struct SystemLoad {
     pub cpu_usage: f64,
     pub memory_usage: f64,
@@ -144,6 +175,12 @@ impl AdaptiveRateLimiter {
 
     /// Check rate limit with adaptive adjustment
     pub fn check_adaptive_limit(&self, tenant_id: &str) -> Result<RateLimitResult> {
+        self.check_adaptive_limit_with_cost(tenant_id, 1.0)
+    }
+
+    /// Check rate limit for a request weighing `cost` requests. Costs above
+    /// the limit are capped at it.
+    pub fn check_adaptive_limit_with_cost(&self, tenant_id: &str, cost: f64) -> Result<RateLimitResult> {
         let config = self.config.read();
 
         if !config.enabled {
@@ -160,28 +197,28 @@ impl AdaptiveRateLimiter {
         let profile = profiles.entry(tenant_id.to_string())
             .or_insert_with(|| TenantUsageProfile::new(tenant_id.to_string(), config.max_rate_limit));
 
-        // Record request
-        self.record_request(tenant_id, true, 1.0);
-
-        // Get current hour's request count
-        let recent = self.recent_requests.read();
+        // Cost admitted in the last hour; rejected requests don't use up the limit
         let cutoff = Utc::now() - Duration::hours(1);
-        let recent_count = recent.iter()
-            .filter(|r| r.tenant_id.as_str() == tenant_id && r.timestamp > cutoff)
-            .count();
+        let used: f64 = self.recent_requests.read().iter()
+            .filter(|r| r.allowed && r.tenant_id.as_str() == tenant_id && r.timestamp > cutoff)
+            .map(|r| r.cost)
+            .sum();
 
-        // Check against current adaptive limit
-        let allowed = (recent_count as u32) < profile.current_limit;
+        // Check against current adaptive limit, raised ahead of known peaks
+        let limit = profile.current_limit.max(predicted_limit(&config, profile));
+        let cost = cost.min(limit as f64);
+        let allowed = used + cost <= limit as f64;
+        self.record_request(tenant_id, allowed, cost);
 
         let result = RateLimitResult {
             allowed,
             remaining: if allowed {
-                profile.current_limit.saturating_sub(recent_count as u32)
+                (limit as f64 - used - cost) as u32
             } else {
                 0
             },
             retry_after: if allowed { None } else { Some(std::time::Duration::from_secs(60)) },
-            limit: profile.current_limit,
+            limit,
         };
 
         // Update profile statistics
@@ -191,15 +228,27 @@ impl AdaptiveRateLimiter {
         Ok(result)
     }
 
-    /// Update adaptive limits based on learned patterns
-    pub fn update_adaptive_limits(&self) -> Result<()> {
+    /// Charge a cost known only after the request ran (e.g. rows scanned)
+    pub fn charge(&self, tenant_id: &str, cost: f64) {
+        if cost > 0.0 && self.config.read().enabled {
+            self.record_request(tenant_id, true, cost);
+        }
+    }
+
+    /// Update adaptive limits based on learned patterns, returning the
+    /// limits that changed
+    pub fn update_adaptive_limits(&self) -> Result<Vec<LimitChange>> {
         let config = self.config.read();
 
         if !config.enabled {
-            return Ok(());
+            return Ok(Vec::new());
         }
 
+        self.refresh_profile_statistics(config.learning_window_hours);
+
         let mut profiles = self.profiles.write();
+        let mut changes = Vec::new();
+        let current_load = self.get_current_load();
 
         for (tenant_id, profile) in profiles.iter_mut() {
             if profile.data_points < 100 {
@@ -224,8 +273,8 @@ impl AdaptiveRateLimiter {
                 }
             }
 
-            // Anomaly-based throttling
-            if config.enable_anomaly_throttling {
+            // Anomaly-based throttling; the first hour has no baseline yet
+            if config.enable_anomaly_throttling && profile.observed_hours > 1 {
                 let recent = self.recent_requests.read();
                 let cutoff = Utc::now() - Duration::minutes(5);
                 let very_recent_count = recent.iter()
@@ -241,21 +290,46 @@ impl AdaptiveRateLimiter {
             }
 
             // Load-based adjustment
+            let mut high_load = false;
             if config.enable_load_based_adjustment {
-                if let Some(load) = self.get_current_load() {
+                if let Some(load) = &current_load {
                     if load.cpu_usage > 0.8 || load.memory_usage > 0.8 {
                         // High system load - reduce limits
                         new_limit = ((profile.current_limit as f64) * 0.7) as u32;
                         reason = AdjustmentReason::HighLoad;
+                        high_load = true;
                     }
                 }
             }
 
+            // Recovery: once load and traffic are back to normal, climb back
+            // towards the base limit instead of staying throttled
+            let throttled = matches!(
+                profile.adjustment_history.last().map(|a| a.reason),
+                Some(AdjustmentReason::HighLoad | AdjustmentReason::AnomalyDetected | AdjustmentReason::Recovered)
+            );
+            if throttled
+                && !high_load
+                && reason == AdjustmentReason::NormalLearning
+                && new_limit == profile.current_limit
+                && profile.current_limit < profile.base_limit
+            {
+                let recovered = ((profile.current_limit as f64) * (1.0 + config.adjustment_factor)).ceil() as u32;
+                new_limit = recovered.min(profile.base_limit);
+                reason = AdjustmentReason::Recovered;
+            }
+
             // Apply safety limits
             new_limit = new_limit.clamp(config.min_rate_limit, config.max_rate_limit);
 
             // Record adjustment if changed
             if new_limit != profile.current_limit {
+                changes.push(LimitChange {
+                    tenant_id: tenant_id.clone(),
+                    old_limit: profile.current_limit,
+                    new_limit,
+                    reason,
+                });
                 profile.adjustment_history.push(LimitAdjustment {
                     timestamp: Utc::now(),
                     old_limit: profile.current_limit,
@@ -272,7 +346,68 @@ impl AdaptiveRateLimiter {
             }
         }
 
-        Ok(())
+        Ok(changes)
+    }
+
+    /// Recompute each profile's hourly statistics from the request history
+    fn refresh_profile_statistics(&self, learning_window_hours: i64) {
+        let now = Utc::now();
+        let window_start = now - Duration::hours(learning_window_hours);
+
+        // Requests per (tenant, hour since epoch), and the first hour seen
+        let mut buckets: HashMap<String, (i64, HashMap<i64, f64>)> = HashMap::new();
+        for record in self.recent_requests.read().iter().filter(|r| r.timestamp > window_start) {
+            let hour = record.timestamp.timestamp() / 3600;
+            let (first_hour, hours) = buckets
+                .entry(record.tenant_id.clone())
+                .or_insert_with(|| (hour, HashMap::new()));
+            *first_hour = (*first_hour).min(hour);
+            *hours.entry(hour).or_default() += record.cost;
+        }
+
+        let current_hour = now.timestamp() / 3600;
+        let mut profiles = self.profiles.write();
+        for (tenant_id, profile) in profiles.iter_mut() {
+            let Some((first_hour, hours)) = buckets.get(tenant_id) else {
+                continue;
+            };
+
+            // Hours without requests count as zero
+            let counts: Vec<(i64, f64)> = (*first_hour..=current_hour)
+                .map(|hour| (hour, hours.get(&hour).copied().unwrap_or(0.0)))
+                .collect();
+            let n = counts.len() as f64;
+            let avg = counts.iter().map(|(_, c)| c).sum::<f64>() / n;
+            let variance = counts.iter().map(|(_, c)| (c - avg).powi(2)).sum::<f64>() / n;
+
+            let mut hourly = [(0.0, 0usize); 24];
+            let mut daily = [(0.0, 0usize); 7];
+            for (hour, count) in &counts {
+                let Some(ts) = DateTime::<Utc>::from_timestamp(hour * 3600, 0) else {
+                    continue;
+                };
+                let slot = &mut hourly[ts.hour() as usize];
+                slot.0 += count;
+                slot.1 += 1;
+                let day = &mut daily[ts.weekday().num_days_from_monday() as usize];
+                day.0 += count;
+                day.1 += 1;
+            }
+            let mean = |(sum, n): (f64, usize)| if n == 0 { 0.0 } else { sum / n as f64 };
+
+            profile.observed_hours = counts.len();
+            profile.avg_requests_per_hour = avg;
+            profile.stddev_requests_per_hour = variance.sqrt();
+            profile.max_requests_per_hour = counts.iter().map(|(_, c)| *c).fold(0.0, f64::max);
+            profile.hourly_averages = hourly.iter().copied().map(mean).collect();
+            // Daily averages are per hour of that weekday
+            profile.daily_averages = daily.iter().copied().map(mean).collect();
+            profile.peak_times = profile.hourly_averages.iter()
+                .enumerate()
+                .filter(|(_, a)| **a > 0.0 && **a > avg + profile.stddev_requests_per_hour)
+                .map(|(hour, _)| hour as u32)
+                .collect();
+        }
     }
 
     /// Predict future load and adjust proactively
@@ -288,20 +423,22 @@ impl AdaptiveRateLimiter {
             if profile.data_points < 1000 {
                 return Ok(profile.current_limit);
             }
-
-            // Simple pattern: check if we're approaching a known peak time
-            let current_hour = Utc::now().hour();
-
-            if profile.peak_times.contains(&current_hour) {
-                // Increase limit proactively
-                let predicted_limit = ((profile.current_limit as f64) * 1.2) as u32;
-                return Ok(predicted_limit.min(config.max_rate_limit));
-            }
+            return Ok(predicted_limit(&config, profile));
         }
 
         Ok(0)
     }
 
+    /// Current limit for a tenant, if it has a profile
+    pub fn current_limit(&self, tenant_id: &str) -> Option<u32> {
+        self.profiles.read().get(tenant_id).map(|p| p.current_limit)
+    }
+
+    /// Most recently recorded system load
+    pub fn current_load(&self) -> Option<SystemLoad> {
+        self.get_current_load()
+    }
+
     /// Record system load for load-based adjustments
     pub fn record_system_load(&self, load: SystemLoad) {
         let mut history = self.load_history.write();
@@ -366,6 +503,22 @@ impl AdaptiveRateLimiter {
     }
 }
 
+/// Limit raised ahead of a known peak hour, or 0 if no prediction applies
+fn predicted_limit(config: &AdaptiveRateLimitConfig, profile: &TenantUsageProfile) -> u32 {
+    if !config.enable_pattern_prediction || profile.data_points < 1000 {
+        return 0;
+    }
+
+    // Simple pattern: check if we're approaching a known peak time
+    if profile.peak_times.contains(&Utc::now().hour()) {
+        // Increase limit proactively
+        let predicted_limit = ((profile.current_limit as f64) * 1.2) as u32;
+        return predicted_limit.min(config.max_rate_limit);
+    }
+
+    0
+}
+
 impl TenantUsageProfile {
     fn new(tenant_id: String, base_limit: u32) -> Self {
         Self {
@@ -376,6 +529,7 @@ impl TenantUsageProfile {
             avg_requests_per_hour: 0.0,
             stddev_requests_per_hour: 0.0,
             max_requests_per_hour: 0.0,
+            observed_hours: 0,
             current_limit: base_limit,
             base_limit,
             adjustment_history: Vec::new(),
@@ -521,4 +675,85 @@ mod tests {
         assert!(stats.current_limit <= 200); // Should not exceed max
         assert!(stats.current_limit >= 50);  // Should not go below min
     }
+
+    #[test]
+    fn test_rejected_requests_do_not_consume_limit() {
+        let limiter = AdaptiveRateLimiter::new(AdaptiveRateLimitConfig {
+            min_rate_limit: 1,
+            max_rate_limit: 2,
+            ..Default::default()
+        });
+
+        assert!(limiter.check_adaptive_limit("tenant1").unwrap().allowed);
+        let second = limiter.check_adaptive_limit("tenant1").unwrap();
+        assert!(second.allowed);
+        assert_eq!(second.remaining, 0);
+
+        // Retrying while limited doesn't push the window further out
+        for _ in 0..5 {
+            assert!(!limiter.check_adaptive_limit("tenant1").unwrap().allowed);
+        }
+        let recent = limiter.recent_requests.read();
+        assert_eq!(recent.iter().filter(|r| r.allowed).count(), 2);
+    }
+
+    #[test]
+    fn test_limits_recover_after_high_load() {
+        let limiter = AdaptiveRateLimiter::new(AdaptiveRateLimitConfig::default());
+
+        {
+            let mut profiles = limiter.profiles.write();
+            let mut profile = TenantUsageProfile::new("tenant1".to_string(), 100);
+            profile.data_points = 500;
+            profiles.insert("tenant1".to_string(), profile);
+        }
+
+        limiter.record_system_load(SystemLoad {
+            cpu_usage: 0.95,
+            ..Default::default()
+        });
+        let changes = limiter.update_adaptive_limits().unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].reason, AdjustmentReason::HighLoad);
+        assert_eq!(changes[0].new_limit, 70);
+
+        // Load subsides: limits climb back to, but not past, the base limit
+        limiter.record_system_load(SystemLoad::default());
+        for _ in 0..10 {
+            limiter.update_adaptive_limits().unwrap();
+        }
+        let stats = limiter.get_tenant_stats("tenant1").unwrap();
+        assert_eq!(stats.current_limit, 100);
+        assert!(limiter.update_adaptive_limits().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_profile_statistics_from_history() {
+        let limiter = AdaptiveRateLimiter::new(AdaptiveRateLimitConfig::default());
+        for _ in 0..30 {
+            limiter.check_adaptive_limit("tenant1").unwrap();
+        }
+
+        // Ten more in the same hour two hours ago
+        {
+            let two_hours_ago = Utc::now() - Duration::hours(2);
+            let mut recent = limiter.recent_requests.write();
+            for _ in 0..10 {
+                recent.push(RequestRecord {
+                    tenant_id: "tenant1".to_string(),
+                    timestamp: two_hours_ago,
+                    allowed: true,
+                    cost: 1.0,
+                });
+            }
+        }
+
+        limiter.refresh_profile_statistics(24);
+        let profiles = limiter.profiles.read();
+        let profile = &profiles["tenant1"];
+        // 10 + 0 + 30 over three hours
+        assert_eq!(profile.observed_hours, 3);
+        assert!((profile.avg_requests_per_hour - 40.0 / 3.0).abs() < 1e-9);
+        assert_eq!(profile.max_requests_per_hour, 30.0);
+    }
 }
@@ -14,6 +14,7 @@ use crate::error::{AllSourceError, Result};
 use chrono::{DateTime, Duration, Timelike, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
 use parking_lot::RwLock;
 
@@ -161,6 +162,14 @@ struct TenantProfile {
     event_count: usize,
 }
 
+/// Persisted form of the learned behavior profiles
+#[derive(Debug, Serialize, Deserialize)]
+struct ProfileSnapshot {
+    saved_at: DateTime<Utc>,
+    user_profiles: HashMap<String, UserProfile>,
+    tenant_profiles: HashMap<String, TenantProfile>,
+}
+
 /// ML-based anomaly detector
 pub struct AnomalyDetector {
     config: Arc<RwLock<AnomalyDetectionConfig>>,
@@ -331,7 +340,7 @@ impl AnomalyDetector {
 
     fn detect_brute_force(&self, user_id: &str, event: &AuditEvent) -> Result<Option<(f64, Vec<String>)>> {
         // Detect multiple failed login attempts
-        if event.action() != &AuditAction::Login {
+        if !matches!(event.action(), AuditAction::Login | AuditAction::LoginFailed) {
             return Ok(None);
         }
 
@@ -339,8 +348,7 @@ impl AnomalyDetector {
         let mut recent_failures = recent.iter()
             .filter(|e| {
                 if let crate::domain::entities::Actor::User { user_id: uid, .. } = e.actor() {
-                    uid == user_id && e.action() == &AuditAction::Login
-                        && e.outcome() == &AuditOutcome::Failure
+                    uid == user_id && is_failed_login(e)
                         && (Utc::now() - e.timestamp()) < Duration::minutes(15)
                 } else {
                     false
@@ -349,7 +357,7 @@ impl AnomalyDetector {
             .count();
 
         // Include current event if it's also a failure
-        if event.outcome() == &AuditOutcome::Failure {
+        if is_failed_login(event) {
             recent_failures += 1;
         }
 
@@ -511,6 +519,58 @@ impl AnomalyDetector {
         }
     }
 
+    /// Persist learned user and tenant profiles to a JSON file
+    ///
+    /// The file is written to a temporary sibling first and then renamed,
+    /// so a crash never leaves a truncated snapshot behind.
+    pub fn save_profiles(&self, path: &Path) -> Result<()> {
+        let snapshot = ProfileSnapshot {
+            saved_at: Utc::now(),
+            user_profiles: self.user_profiles.read().clone(),
+            tenant_profiles: self.tenant_profiles.read().clone(),
+        };
+        let json = serde_json::to_vec(&snapshot)?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                AllSourceError::StorageError(format!("Failed to create profile directory: {}", e))
+            })?;
+        }
+        let tmp_path = path.with_extension("tmp");
+        std::fs::write(&tmp_path, json).map_err(|e| {
+            AllSourceError::StorageError(format!("Failed to write anomaly profiles: {}", e))
+        })?;
+        std::fs::rename(&tmp_path, path).map_err(|e| {
+            AllSourceError::StorageError(format!("Failed to persist anomaly profiles: {}", e))
+        })?;
+
+        Ok(())
+    }
+
+    /// Load profiles previously written by `save_profiles`
+    ///
+    /// Replaces the in-memory profiles and returns the number of user
+    /// profiles loaded. A missing file is not an error and loads nothing.
+    pub fn load_profiles(&self, path: &Path) -> Result<usize> {
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => {
+                return Err(AllSourceError::StorageError(format!(
+                    "Failed to read anomaly profiles: {}",
+                    e
+                )))
+            }
+        };
+        let snapshot: ProfileSnapshot = serde_json::from_slice(&bytes)?;
+        let count = snapshot.user_profiles.len();
+
+        *self.user_profiles.write() = snapshot.user_profiles;
+        *self.tenant_profiles.write() = snapshot.tenant_profiles;
+
+        Ok(count)
+    }
+
     /// Get statistics about detection
     pub fn get_stats(&self) -> DetectionStats {
         let profiles = self.user_profiles.read();
@@ -524,6 +584,15 @@ impl AnomalyDetector {
     }
 }
 
+/// A failed login is either `LoginFailed` or a `Login` with a failure outcome
+fn is_failed_login(event: &AuditEvent) -> bool {
+    match event.action() {
+        AuditAction::LoginFailed => true,
+        AuditAction::Login => event.outcome() == &AuditOutcome::Failure,
+        _ => false,
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DetectionStats {
     pub user_profiles_count: usize,
@@ -610,6 +679,43 @@ mod tests {
         assert_eq!(result.anomaly_type, Some(AnomalyType::VelocityAnomaly));
     }
 
+    #[test]
+    fn test_brute_force_detection_counts_login_failed() {
+        let detector = AnomalyDetector::new(AnomalyDetectionConfig::default());
+
+        for _ in 0..8 {
+            let event = create_test_event(AuditAction::LoginFailed, AuditOutcome::Success, "user1");
+            detector.add_recent_event(event);
+        }
+
+        let event = create_test_event(AuditAction::LoginFailed, AuditOutcome::Success, "user1");
+        let result = detector.analyze_event(&event).unwrap();
+
+        assert!(result.is_anomalous);
+        assert_eq!(result.anomaly_type, Some(AnomalyType::BruteForceAttack));
+    }
+
+    #[test]
+    fn test_profiles_persist_across_detectors() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("profiles").join("anomaly.json");
+
+        let detector = AnomalyDetector::new(AnomalyDetectionConfig::default());
+        for user in ["user1", "user2"] {
+            let event = create_test_event(AuditAction::EventQueried, AuditOutcome::Success, user);
+            detector.update_profile(&event).unwrap();
+        }
+        detector.save_profiles(&path).unwrap();
+
+        let restored = AnomalyDetector::new(AnomalyDetectionConfig::default());
+        assert_eq!(restored.load_profiles(&path).unwrap(), 2);
+        assert_eq!(restored.get_stats().user_profiles_count, 2);
+
+        // Missing snapshot loads nothing
+        let fresh = AnomalyDetector::new(AnomalyDetectionConfig::default());
+        assert_eq!(fresh.load_profiles(&dir.path().join("missing.json")).unwrap(), 0);
+    }
+
     #[test]
     fn test_disabled_detection() {
         let mut config = AnomalyDetectionConfig::default();
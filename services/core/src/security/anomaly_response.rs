@@ -0,0 +1,527 @@
+//! Automatic Anomaly Response
+//!
+//! Runs audit events through the `AnomalyDetector` as they are recorded and
+//! enforces its recommendations:
+//! - `RequireMFA`: throttle the principal via the rate limiter (there is no
+//!   step-up authentication yet, so throttling is the closest enforcement)
+//! - `Block`: throttle the principal and block the client IP in `IpFilter`.
+//!   The IP is the one recorded in the audit event, i.e. the socket address
+//!   or the address resolved through the trusted proxy list; the proxies
+//!   themselves are never blocked
+//! - `RevokeAccess`: additionally revoke the API key that was used
+//!
+//! Every anomaly is recorded as a `SuspiciousActivity` audit event by the
+//! `anomaly-detector` system actor and, if configured, POSTed to a webhook.
+//! Learned profiles are persisted periodically so baselines survive restarts.
+
+use crate::auth::AuthManager;
+use crate::domain::entities::{Actor, AuditAction, AuditEvent, AuditOutcome};
+use crate::domain::repositories::AuditEventRepository;
+use crate::error::Result;
+use crate::infrastructure::security::{IpFilter, IpNetwork};
+use crate::rate_limit::{RateLimitConfig, RateLimiter};
+use crate::security::anomaly_detection::{AnomalyDetector, AnomalyResult, RecommendedAction};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// System actor component used for anomaly alerts
+pub const ANOMALY_DETECTOR_COMPONENT: &str = "anomaly-detector";
+
+/// Anomaly response configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalyResponseConfig {
+    /// Enforce recommended actions (when false, anomalies are only alerted)
+    pub enforce: bool,
+
+    /// Webhook that receives every anomaly alert as JSON
+    pub webhook_url: Option<String>,
+
+    /// Webhook request timeout (seconds)
+    pub webhook_timeout_secs: u64,
+
+    /// Limits applied to throttled principals
+    pub throttle_requests_per_minute: u32,
+    pub throttle_burst_size: u32,
+
+    /// File for persisting learned profiles (None = in-memory only)
+    pub profile_path: Option<PathBuf>,
+
+    /// Persist profiles after every N observed events
+    pub profile_flush_interval: u64,
+
+    /// Reverse proxies that must never be blocked, since every client
+    /// behind them would be locked out
+    #[serde(default)]
+    pub trusted_proxies: Vec<IpNetwork>,
+}
+
+impl Default for AnomalyResponseConfig {
+    fn default() -> Self {
+        Self {
+            enforce: true,
+            webhook_url: None,
+            webhook_timeout_secs: 5,
+            throttle_requests_per_minute: 10,
+            throttle_burst_size: 10,
+            profile_path: None,
+            profile_flush_interval: 100,
+            trusted_proxies: Vec::new(),
+        }
+    }
+}
+
+/// Enforcement step taken in response to an anomaly
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseAction {
+    Throttled {
+        principal: String,
+        requests_per_minute: u32,
+    },
+    IpBlocked {
+        ip: String,
+    },
+    ApiKeyRevoked {
+        key_id: String,
+    },
+}
+
+/// Alert emitted for an anomalous audit event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalyAlert {
+    pub tenant_id: String,
+    /// Actor identifier, e.g. "user:<id>" or "api_key:<id>"
+    pub actor: String,
+    pub source_event_id: String,
+    pub source_action: AuditAction,
+    pub ip_address: Option<String>,
+    pub anomaly: AnomalyResult,
+    /// Actions newly taken for this alert
+    pub actions: Vec<ResponseAction>,
+    pub detected_at: DateTime<Utc>,
+}
+
+/// Feeds audit events to the anomaly detector and acts on its results
+pub struct AnomalyResponder {
+    detector: Arc<AnomalyDetector>,
+    config: AnomalyResponseConfig,
+    auth_manager: Arc<AuthManager>,
+    ip_filter: Arc<IpFilter>,
+    rate_limiter: Arc<RateLimiter>,
+
+    /// Where alerts are recorded; must not feed back into this responder
+    alert_repository: Arc<dyn AuditEventRepository>,
+
+    http_client: reqwest::Client,
+    observed: AtomicU64,
+}
+
+impl AnomalyResponder {
+    /// Create a responder, loading persisted profiles if configured
+    pub fn new(
+        detector: Arc<AnomalyDetector>,
+        config: AnomalyResponseConfig,
+        auth_manager: Arc<AuthManager>,
+        ip_filter: Arc<IpFilter>,
+        rate_limiter: Arc<RateLimiter>,
+        alert_repository: Arc<dyn AuditEventRepository>,
+    ) -> Result<Self> {
+        if let Some(path) = &config.profile_path {
+            let loaded = detector.load_profiles(path)?;
+            if loaded > 0 {
+                tracing::info!("📥 Loaded {} anomaly detection profiles from {}", loaded, path.display());
+            }
+        }
+
+        let http_client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(config.webhook_timeout_secs))
+            .build()
+            .map_err(|e| {
+                crate::error::AllSourceError::InternalError(format!("Failed to build webhook client: {}", e))
+            })?;
+
+        Ok(Self {
+            detector,
+            config,
+            auth_manager,
+            ip_filter,
+            rate_limiter,
+            alert_repository,
+            http_client,
+            observed: AtomicU64::new(0),
+        })
+    }
+
+    pub fn detector(&self) -> &Arc<AnomalyDetector> {
+        &self.detector
+    }
+
+    /// Analyze a recorded audit event and respond if it is anomalous
+    ///
+    /// Returns the alert that was raised, if any.
+    pub async fn observe(&self, event: &AuditEvent) -> Result<Option<AnomalyAlert>> {
+        if matches!(event.actor(), Actor::System { .. }) {
+            return Ok(None);
+        }
+
+        // Analyze against the baseline before the event becomes part of it
+        let result = self.detector.analyze_event(event)?;
+        self.detector.update_profile(event)?;
+        self.flush_profiles_if_due();
+
+        if !result.is_anomalous {
+            return Ok(None);
+        }
+
+        let actions = if self.config.enforce {
+            self.enforce(event, &result.recommended_action)
+        } else {
+            Vec::new()
+        };
+
+        let alert = AnomalyAlert {
+            tenant_id: event.tenant_id().as_str().to_string(),
+            actor: event.actor().identifier(),
+            source_event_id: event.id().as_str(),
+            source_action: event.action().clone(),
+            ip_address: event.ip_address().map(str::to_string),
+            anomaly: result,
+            actions,
+            detected_at: Utc::now(),
+        };
+
+        tracing::warn!(
+            "🚨 Anomaly detected for {} in tenant '{}': {} (actions: {:?})",
+            alert.actor,
+            alert.tenant_id,
+            alert.anomaly.reason,
+            alert.actions
+        );
+
+        self.record_alert(event, &alert).await;
+        self.send_webhook(&alert);
+
+        Ok(Some(alert))
+    }
+
+    /// Persist the detector's profiles now, if a profile path is configured
+    pub fn persist_profiles(&self) -> Result<()> {
+        match &self.config.profile_path {
+            Some(path) => self.detector.save_profiles(path),
+            None => Ok(()),
+        }
+    }
+
+    fn flush_profiles_if_due(&self) {
+        let interval = self.config.profile_flush_interval;
+        let observed = self.observed.fetch_add(1, Ordering::Relaxed) + 1;
+        if interval > 0 && observed.is_multiple_of(interval) {
+            if let Err(e) = self.persist_profiles() {
+                tracing::warn!("⚠️  Failed to persist anomaly profiles: {}", e);
+            }
+        }
+    }
+
+    /// Apply the recommended action, returning only steps not already in effect
+    fn enforce(&self, event: &AuditEvent, recommendation: &RecommendedAction) -> Vec<ResponseAction> {
+        let mut actions = Vec::new();
+
+        let (throttle, block_ip, revoke_key) = match recommendation {
+            RecommendedAction::Monitor | RecommendedAction::Alert => (false, false, false),
+            RecommendedAction::RequireMFA => (true, false, false),
+            RecommendedAction::Block => (true, true, false),
+            RecommendedAction::RevokeAccess => (true, true, true),
+        };
+
+        let principal = match event.actor() {
+            Actor::User { user_id, .. } => Some(user_id.clone()),
+            Actor::ApiKey { key_id, .. } => Some(key_id.clone()),
+            Actor::System { .. } => None,
+        };
+
+        if throttle {
+            if let Some(principal) = &principal {
+                if !self.rate_limiter.has_config(principal) {
+                    self.rate_limiter.set_config(
+                        principal,
+                        RateLimitConfig {
+                            requests_per_minute: self.config.throttle_requests_per_minute,
+                            burst_size: self.config.throttle_burst_size,
+                        },
+                    );
+                    actions.push(ResponseAction::Throttled {
+                        principal: principal.clone(),
+                        requests_per_minute: self.config.throttle_requests_per_minute,
+                    });
+                }
+            }
+        }
+
+        if block_ip {
+            if let Some(ip) = event.ip_address().and_then(|ip| ip.parse::<IpAddr>().ok()) {
+                let is_proxy = self.config.trusted_proxies.iter().any(|net| net.contains(&ip));
+                if !is_proxy && !self.ip_filter.is_in_global_blocklist(&ip) {
+                    self.ip_filter.add_to_global_blocklist(ip);
+                    actions.push(ResponseAction::IpBlocked { ip: ip.to_string() });
+                }
+            }
+        }
+
+        if revoke_key {
+            if let Actor::ApiKey { key_id, .. } = event.actor() {
+                if let Ok(id) = key_id.parse::<Uuid>() {
+                    let active = self.auth_manager.get_api_key(&id).map(|k| k.active).unwrap_or(false);
+                    if active && self.auth_manager.revoke_api_key(&id).is_ok() {
+                        actions.push(ResponseAction::ApiKeyRevoked { key_id: key_id.clone() });
+                    }
+                }
+            }
+        }
+
+        actions
+    }
+
+    async fn record_alert(&self, event: &AuditEvent, alert: &AnomalyAlert) {
+        let metadata = match serde_json::to_value(alert) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                tracing::error!("❌ Failed to serialize anomaly alert: {}", e);
+                return;
+            }
+        };
+
+        let mut alert_event = AuditEvent::new(
+            event.tenant_id().clone(),
+            AuditAction::SuspiciousActivity,
+            Actor::system(ANOMALY_DETECTOR_COMPONENT.to_string()),
+            AuditOutcome::Success,
+        )
+        .with_resource("principal".to_string(), alert.actor.clone())
+        .with_metadata(metadata);
+        if let Some(ip) = &alert.ip_address {
+            alert_event = alert_event.with_ip_address(ip.clone());
+        }
+
+        if let Err(e) = self.alert_repository.append(alert_event).await {
+            tracing::error!("❌ Failed to record anomaly alert: {}", e);
+        }
+    }
+
+    fn send_webhook(&self, alert: &AnomalyAlert) {
+        let Some(url) = self.config.webhook_url.clone() else {
+            return;
+        };
+        let client = self.http_client.clone();
+        let alert = alert.clone();
+
+        tokio::spawn(async move {
+            let result = client
+                .post(&url)
+                .json(&alert)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+            if let Err(e) = result {
+                tracing::warn!("⚠️  Anomaly webhook delivery to {} failed: {}", url, e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::Role;
+    use crate::domain::value_objects::TenantId;
+    use crate::infrastructure::repositories::InMemoryAuditRepository;
+    use crate::security::AnomalyDetectionConfig;
+
+    struct Fixture {
+        responder: AnomalyResponder,
+        auth_manager: Arc<AuthManager>,
+        ip_filter: Arc<IpFilter>,
+        rate_limiter: Arc<RateLimiter>,
+        alerts: Arc<InMemoryAuditRepository>,
+    }
+
+    fn fixture(config: AnomalyResponseConfig) -> Fixture {
+        let auth_manager = Arc::new(AuthManager::new("test-secret"));
+        let ip_filter = Arc::new(IpFilter::new());
+        let rate_limiter = Arc::new(RateLimiter::new(RateLimitConfig::professional()));
+        let alerts = Arc::new(InMemoryAuditRepository::new());
+        let responder = AnomalyResponder::new(
+            Arc::new(AnomalyDetector::new(AnomalyDetectionConfig::default())),
+            config,
+            auth_manager.clone(),
+            ip_filter.clone(),
+            rate_limiter.clone(),
+            alerts.clone(),
+        )
+        .unwrap();
+
+        Fixture {
+            responder,
+            auth_manager,
+            ip_filter,
+            rate_limiter,
+            alerts,
+        }
+    }
+
+    fn tenant() -> TenantId {
+        TenantId::new("acme".to_string()).unwrap()
+    }
+
+    fn failed_login(user_id: &str) -> AuditEvent {
+        AuditEvent::new(
+            tenant(),
+            AuditAction::LoginFailed,
+            Actor::user(user_id.to_string(), "alice".to_string()),
+            AuditOutcome::Failure,
+        )
+        .with_ip_address("203.0.113.7".to_string())
+    }
+
+    #[tokio::test]
+    async fn test_normal_events_raise_no_alert() {
+        let f = fixture(AnomalyResponseConfig::default());
+        let event = AuditEvent::new(
+            tenant(),
+            AuditAction::EventQueried,
+            Actor::user("u1".to_string(), "alice".to_string()),
+            AuditOutcome::Success,
+        );
+
+        assert!(f.responder.observe(&event).await.unwrap().is_none());
+        assert_eq!(f.responder.detector().get_stats().user_profiles_count, 1);
+        assert_eq!(f.alerts.count(crate::domain::repositories::AuditEventQuery::new(tenant())).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_brute_force_escalates_to_throttle_and_ip_block() {
+        let f = fixture(AnomalyResponseConfig::default());
+        let ip: IpAddr = "203.0.113.7".parse().unwrap();
+
+        let mut alerts = Vec::new();
+        for _ in 0..8 {
+            if let Some(alert) = f.responder.observe(&failed_login("u1")).await.unwrap() {
+                alerts.push(alert);
+            }
+        }
+
+        // 7 failures recommend RequireMFA (throttle), 8 recommend Block
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(alerts[0].anomaly.recommended_action, RecommendedAction::RequireMFA);
+        assert_eq!(
+            alerts[0].actions,
+            vec![ResponseAction::Throttled {
+                principal: "u1".to_string(),
+                requests_per_minute: 10,
+            }]
+        );
+        assert_eq!(alerts[1].anomaly.recommended_action, RecommendedAction::Block);
+        assert_eq!(alerts[1].actions, vec![ResponseAction::IpBlocked { ip: ip.to_string() }]);
+
+        assert!(f.rate_limiter.has_config("u1"));
+        assert!(f.ip_filter.is_in_global_blocklist(&ip));
+
+        let recorded = f.alerts.get_security_events(&tenant(), 10).await.unwrap();
+        assert_eq!(recorded.len(), 2);
+        assert!(recorded.iter().all(|e| e.action() == &AuditAction::SuspiciousActivity
+            && e.actor().identifier() == "system:anomaly-detector"));
+    }
+
+    #[tokio::test]
+    async fn test_trusted_proxies_are_never_blocked() {
+        let f = fixture(AnomalyResponseConfig {
+            trusted_proxies: vec!["203.0.113.0/24".parse().unwrap()],
+            ..Default::default()
+        });
+
+        for _ in 0..8 {
+            f.responder.observe(&failed_login("u1")).await.unwrap();
+        }
+
+        // The principal is still throttled, but the proxy stays reachable
+        assert!(f.rate_limiter.has_config("u1"));
+        assert!(!f.ip_filter.is_in_global_blocklist(&"203.0.113.7".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_revoke_access_revokes_api_key() {
+        let f = fixture(AnomalyResponseConfig::default());
+        let (api_key, _) = f.auth_manager.create_api_key(
+            "ci".to_string(),
+            "acme".to_string(),
+            Role::Developer,
+            None,
+        )
+        .unwrap();
+
+        let event = AuditEvent::new(
+            tenant(),
+            AuditAction::SuspiciousActivity,
+            Actor::api_key(api_key.id.to_string(), "ci".to_string()),
+            AuditOutcome::Failure,
+        );
+        let actions = f.responder.enforce(&event, &RecommendedAction::RevokeAccess);
+
+        assert!(actions.contains(&ResponseAction::ApiKeyRevoked {
+            key_id: api_key.id.to_string(),
+        }));
+        assert!(!f.auth_manager.get_api_key(&api_key.id).unwrap().active);
+
+        // Already-revoked keys and existing throttles are not reported again
+        assert!(f.responder.enforce(&event, &RecommendedAction::RevokeAccess).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_alert_only_mode_does_not_enforce() {
+        let f = fixture(AnomalyResponseConfig {
+            enforce: false,
+            ..Default::default()
+        });
+
+        let mut alerts = Vec::new();
+        for _ in 0..9 {
+            if let Some(alert) = f.responder.observe(&failed_login("u1")).await.unwrap() {
+                alerts.push(alert);
+            }
+        }
+
+        assert!(!alerts.is_empty());
+        assert!(alerts.iter().all(|a| a.actions.is_empty()));
+        assert!(!f.rate_limiter.has_config("u1"));
+        assert!(!f.ip_filter.is_in_global_blocklist(&"203.0.113.7".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_profiles_flushed_periodically() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("anomaly_profiles.json");
+        let f = fixture(AnomalyResponseConfig {
+            profile_path: Some(path.clone()),
+            profile_flush_interval: 2,
+            ..Default::default()
+        });
+
+        let event = AuditEvent::new(
+            tenant(),
+            AuditAction::EventQueried,
+            Actor::user("u1".to_string(), "alice".to_string()),
+            AuditOutcome::Success,
+        );
+        f.responder.observe(&event).await.unwrap();
+        assert!(!path.exists());
+        f.responder.observe(&event).await.unwrap();
+        assert!(path.exists());
+
+        let restored = AnomalyDetector::new(AnomalyDetectionConfig::default());
+        assert_eq!(restored.load_profiles(&path).unwrap(), 1);
+    }
+}
@@ -84,15 +84,35 @@ pub struct FieldEncryption {
 
     // Active key for encryption
     active_key_id: Arc<RwLock<Option<String>>>,
+
+    // Where new keys come from (None = generated locally)
+    key_source: Option<DataKeySource>,
 }
 
+/// Produces new data keys, e.g. by having a KMS generate and wrap them;
+/// returns the key id and plaintext key
+pub type DataKeySource = Arc<dyn Fn() -> Result<(String, Vec<u8>)> + Send + Sync>;
+
 impl FieldEncryption {
     /// Create new field encryption manager
     pub fn new(config: EncryptionConfig) -> Result<Self> {
+        Self::build(config, None)
+    }
+
+    /// Create a field encryption manager whose keys come from `key_source`
+    ///
+    /// Used when data keys come from a KMS instead of being generated locally;
+    /// the initial key and every rotated key are taken from the source.
+    pub fn with_key_source(config: EncryptionConfig, key_source: DataKeySource) -> Result<Self> {
+        Self::build(config, Some(key_source))
+    }
+
+    fn build(config: EncryptionConfig, key_source: Option<DataKeySource>) -> Result<Self> {
         let manager = Self {
             config: Arc::new(RwLock::new(config)),
             deks: Arc::new(RwLock::new(HashMap::new())),
             active_key_id: Arc::new(RwLock::new(None)),
+            key_source,
         };
 
         // Generate initial key
@@ -101,6 +121,46 @@ impl FieldEncryption {
         Ok(manager)
     }
 
+    /// Add a data key, optionally making it the key for new encryptions
+    pub fn add_key(&self, key_id: String, key_bytes: Vec<u8>, activate: bool) -> Result<()> {
+        if key_bytes.len() != 32 {
+            return Err(AllSourceError::ValidationError(format!(
+                "Data key {} must be 256 bits, got {} bytes",
+                key_id,
+                key_bytes.len()
+            )));
+        }
+
+        let mut deks = self.deks.write();
+        let mut active_key_id = self.active_key_id.write();
+
+        if activate {
+            for key in deks.values_mut() {
+                key.active = false;
+            }
+            *active_key_id = Some(key_id.clone());
+        }
+
+        let version = deks.len() as u32 + 1;
+        deks.insert(
+            key_id.clone(),
+            DataEncryptionKey {
+                key_id,
+                key_bytes,
+                version,
+                created_at: chrono::Utc::now(),
+                active: activate,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Check whether a data key is loaded
+    pub fn has_key(&self, key_id: &str) -> bool {
+        self.deks.read().contains_key(key_id)
+    }
+
     /// Encrypt a string value
     pub fn encrypt_string(&self, plaintext: &str, field_name: &str) -> Result<EncryptedData> {
         if !self.config.read().enabled {
@@ -176,35 +236,20 @@ impl FieldEncryption {
     }
 
     /// Rotate encryption keys
+    ///
+    /// The new key comes from the key source when one is set.
     pub fn rotate_keys(&self) -> Result<()> {
-        let mut deks = self.deks.write();
-        let mut active_key_id = self.active_key_id.write();
-
-        // Generate new key
-        let key_id = uuid::Uuid::new_v4().to_string();
-        let mut key_bytes = vec![0u8; 32]; // 256 bits for AES-256
-        aes_gcm::aead::rand_core::RngCore::fill_bytes(&mut OsRng, &mut key_bytes);
-
-        let version = deks.len() as u32 + 1;
-
-        let new_key = DataEncryptionKey {
-            key_id: key_id.clone(),
-            key_bytes,
-            version,
-            created_at: chrono::Utc::now(),
-            active: true,
+        let (key_id, key_bytes) = match &self.key_source {
+            Some(key_source) => key_source()?,
+            None => {
+                let mut key_bytes = vec![0u8; 32]; // 256 bits for AES-256
+                aes_gcm::aead::rand_core::RngCore::fill_bytes(&mut OsRng, &mut key_bytes);
+                (uuid::Uuid::new_v4().to_string(), key_bytes)
+            }
         };
 
-        // Deactivate old keys
-        for key in deks.values_mut() {
-            key.active = false;
-        }
-
-        // Add new key
-        deks.insert(key_id.clone(), new_key);
-        *active_key_id = Some(key_id);
-
-        Ok(())
+        // Deactivates the old keys
+        self.add_key(key_id, key_bytes, true)
     }
 
     /// Get encryption statistics
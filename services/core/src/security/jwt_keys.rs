@@ -0,0 +1,727 @@
+//! JWT Signing Keys
+//!
+//! Manages the keys used to sign and verify access tokens:
+//! - HS256 (shared secret), RS256 (RSA 2048) and ES256 (ECDSA P-256)
+//! - Several keys can be active at once, identified by the `kid` header
+//! - Scheduled rotation: a rotated-out key keeps verifying tokens for a grace
+//!   period, so sessions expire naturally instead of all at once
+//! - Public keys are published as a JWK Set (`/.well-known/jwks.json`)
+//!
+//! Keys are generated in-process. When backed by a `KmsClient`, the key ring
+//! is written to disk wrapped by a KMS key (HS256 secrets are KMS data keys),
+//! so signing keys survive restarts and can be shared between nodes.
+
+use crate::error::{AllSourceError, Result};
+use crate::security::kms::KmsClient;
+use base64::{
+    engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+    Engine as _,
+};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::jwk::{
+    AlgorithmParameters, CommonParameters, EllipticCurve, EllipticCurveKeyParameters,
+    EllipticCurveKeyType, Jwk, JwkSet, KeyAlgorithm as JwkKeyAlgorithm, PublicKeyUse,
+    RSAKeyParameters, RSAKeyType,
+};
+use jsonwebtoken::{decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use parking_lot::RwLock;
+use ring::rand::SystemRandom;
+use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+use rsa::pkcs1::{DecodeRsaPrivateKey, EncodeRsaPrivateKey};
+use rsa::traits::PublicKeyParts;
+use rsa::RsaPrivateKey;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Issuer set on, and required of, every token
+pub const JWT_ISSUER: &str = "allsource";
+
+/// How often the rotation task checks whether a rotation is due (seconds)
+const ROTATION_CHECK_INTERVAL_SECS: u64 = 60;
+
+/// JWT signing algorithm
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JwtAlgorithm {
+    HS256,
+    RS256,
+    ES256,
+}
+
+impl JwtAlgorithm {
+    fn algorithm(&self) -> Algorithm {
+        match self {
+            JwtAlgorithm::HS256 => Algorithm::HS256,
+            JwtAlgorithm::RS256 => Algorithm::RS256,
+            JwtAlgorithm::ES256 => Algorithm::ES256,
+        }
+    }
+}
+
+/// JWT key management configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JwtKeyConfig {
+    /// Algorithm of newly generated keys
+    pub algorithm: JwtAlgorithm,
+
+    /// Generate a new signing key after this long (seconds, 0 = never rotate)
+    pub rotation_interval_secs: u64,
+
+    /// How long a rotated-out key still verifies tokens (seconds).
+    /// Should be at least the token lifetime.
+    pub grace_period_secs: u64,
+}
+
+impl Default for JwtKeyConfig {
+    fn default() -> Self {
+        Self {
+            algorithm: JwtAlgorithm::RS256,
+            rotation_interval_secs: 7 * 24 * 3600,
+            grace_period_secs: 48 * 3600,
+        }
+    }
+}
+
+/// A signing key and its derived verification material
+struct SigningKey {
+    kid: String,
+    algorithm: JwtAlgorithm,
+    created_at: DateTime<Utc>,
+    /// Set when the key is rotated out; it stops verifying tokens afterwards
+    retires_at: Option<DateTime<Utc>>,
+    /// Key material encrypted by the KMS key ring, once wrapped
+    wrapped_material: Option<Vec<u8>>,
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    /// Public key (None for symmetric keys, which are never published)
+    jwk: Option<Jwk>,
+}
+
+impl SigningKey {
+    /// Generate new key material for `algorithm`
+    fn generate_material(algorithm: JwtAlgorithm) -> Result<Vec<u8>> {
+        match algorithm {
+            JwtAlgorithm::HS256 => Ok(rand::random::<[u8; 32]>().to_vec()),
+            JwtAlgorithm::RS256 => {
+                let key = RsaPrivateKey::new(&mut rand::rngs::OsRng, 2048)
+                    .map_err(|e| AllSourceError::InternalError(format!("RSA key generation failed: {}", e)))?;
+                let der = key
+                    .to_pkcs1_der()
+                    .map_err(|e| AllSourceError::InternalError(format!("RSA key encoding failed: {}", e)))?;
+                Ok(der.as_bytes().to_vec())
+            }
+            JwtAlgorithm::ES256 => {
+                let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &SystemRandom::new())
+                    .map_err(|_| AllSourceError::InternalError("ECDSA key generation failed".to_string()))?;
+                Ok(pkcs8.as_ref().to_vec())
+            }
+        }
+    }
+
+    /// Load key material: a secret (HS256), PKCS#1 DER (RS256) or PKCS#8 DER (ES256)
+    fn from_material(
+        kid: String,
+        algorithm: JwtAlgorithm,
+        created_at: DateTime<Utc>,
+        material: Vec<u8>,
+    ) -> Result<Self> {
+        let invalid = |e: String| AllSourceError::ValidationError(format!("Invalid {:?} key {}: {}", algorithm, kid, e));
+
+        let (encoding_key, decoding_key, public) = match algorithm {
+            JwtAlgorithm::HS256 => (
+                EncodingKey::from_secret(&material),
+                DecodingKey::from_secret(&material),
+                None,
+            ),
+            JwtAlgorithm::RS256 => {
+                let key = RsaPrivateKey::from_pkcs1_der(&material).map_err(|e| invalid(e.to_string()))?;
+                let n = URL_SAFE_NO_PAD.encode(key.n().to_bytes_be());
+                let e = URL_SAFE_NO_PAD.encode(key.e().to_bytes_be());
+                let decoding_key = DecodingKey::from_rsa_components(&n, &e).map_err(|e| invalid(e.to_string()))?;
+                (
+                    EncodingKey::from_rsa_der(&material),
+                    decoding_key,
+                    Some((
+                        JwkKeyAlgorithm::RS256,
+                        AlgorithmParameters::RSA(RSAKeyParameters {
+                            key_type: RSAKeyType::RSA,
+                            n,
+                            e,
+                        }),
+                    )),
+                )
+            }
+            JwtAlgorithm::ES256 => {
+                let pair = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &material, &SystemRandom::new())
+                    .map_err(|e| invalid(e.to_string()))?;
+                // Uncompressed point: 0x04 || x || y
+                let point = pair.public_key().as_ref();
+                let x = URL_SAFE_NO_PAD.encode(&point[1..33]);
+                let y = URL_SAFE_NO_PAD.encode(&point[33..65]);
+                let decoding_key = DecodingKey::from_ec_components(&x, &y).map_err(|e| invalid(e.to_string()))?;
+                (
+                    EncodingKey::from_ec_der(&material),
+                    decoding_key,
+                    Some((
+                        JwkKeyAlgorithm::ES256,
+                        AlgorithmParameters::EllipticCurve(EllipticCurveKeyParameters {
+                            key_type: EllipticCurveKeyType::EC,
+                            curve: EllipticCurve::P256,
+                            x,
+                            y,
+                        }),
+                    )),
+                )
+            }
+        };
+
+        let jwk = public.map(|(key_algorithm, parameters)| Jwk {
+            common: CommonParameters {
+                public_key_use: Some(PublicKeyUse::Signature),
+                key_algorithm: Some(key_algorithm),
+                key_id: Some(kid.clone()),
+                ..Default::default()
+            },
+            algorithm: parameters,
+        });
+
+        Ok(Self {
+            kid,
+            algorithm,
+            created_at,
+            retires_at: None,
+            wrapped_material: None,
+            encoding_key,
+            decoding_key,
+            jwk,
+        })
+    }
+
+    fn is_retired(&self, now: DateTime<Utc>) -> bool {
+        self.retires_at.is_some_and(|retires_at| now >= retires_at)
+    }
+}
+
+/// On-disk form of a KMS-wrapped signing key
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredSigningKey {
+    kid: String,
+    algorithm: JwtAlgorithm,
+    created_at: DateTime<Utc>,
+    retires_at: Option<DateTime<Utc>>,
+    /// Key material encrypted by the KMS key (base64)
+    wrapped_material: String,
+}
+
+/// Key ring persisted on disk, wrapped by a KMS key
+struct KmsKeyRing {
+    kms: Arc<dyn KmsClient>,
+    key_id: String,
+    path: PathBuf,
+}
+
+impl KmsKeyRing {
+    async fn load(&self) -> Result<Vec<SigningKey>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let data = std::fs::read(&self.path).map_err(|e| {
+            AllSourceError::StorageError(format!("Failed to read JWT key ring {}: {}", self.path.display(), e))
+        })?;
+        let stored: Vec<StoredSigningKey> = serde_json::from_slice(&data)?;
+
+        let mut keys = Vec::with_capacity(stored.len());
+        for record in stored {
+            let wrapped = STANDARD
+                .decode(&record.wrapped_material)
+                .map_err(|e| AllSourceError::StorageError(format!("Corrupt JWT key {}: {}", record.kid, e)))?;
+            let material = self.kms.decrypt(&self.key_id, &wrapped).await?;
+            let mut key = SigningKey::from_material(record.kid, record.algorithm, record.created_at, material)?;
+            key.retires_at = record.retires_at;
+            key.wrapped_material = Some(wrapped);
+            keys.push(key);
+        }
+        Ok(keys)
+    }
+
+    /// Generate key material through the KMS: HS256 secrets are KMS data keys
+    async fn generate(&self, algorithm: JwtAlgorithm) -> Result<(Vec<u8>, Vec<u8>)> {
+        if algorithm == JwtAlgorithm::HS256 {
+            return self.kms.generate_data_key(&self.key_id).await;
+        }
+        let material = tokio::task::spawn_blocking(move || SigningKey::generate_material(algorithm))
+            .await
+            .map_err(|e| AllSourceError::InternalError(format!("Key generation task failed: {}", e)))??;
+        let wrapped = self.kms.encrypt(&self.key_id, &material).await?;
+        Ok((material, wrapped))
+    }
+
+    fn save(&self, keys: &[StoredSigningKey]) -> Result<()> {
+        write_atomic(&self.path, &serde_json::to_vec_pretty(keys)?)
+    }
+}
+
+/// JWT key manager
+///
+/// Signs tokens with the newest key and verifies them with the key named by
+/// their `kid` header. Keys are kept oldest first; the last one is current.
+pub struct JwtKeyManager {
+    config: JwtKeyConfig,
+    keys: RwLock<Vec<SigningKey>>,
+    key_ring: Option<KmsKeyRing>,
+    /// Serializes rotations (which await the KMS)
+    rotation_lock: tokio::sync::Mutex<()>,
+}
+
+impl JwtKeyManager {
+    fn with_keys(config: JwtKeyConfig, keys: Vec<SigningKey>, key_ring: Option<KmsKeyRing>) -> Self {
+        Self {
+            config,
+            keys: RwLock::new(keys),
+            key_ring,
+            rotation_lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Key manager with a single HS256 shared secret and no rotation
+    ///
+    /// The `kid` is derived from the secret, so every node configured with
+    /// the same secret issues interchangeable tokens.
+    pub fn from_secret(secret: &str) -> Self {
+        let digest = format!("{:x}", Sha256::digest(secret.as_bytes()));
+        let kid = format!("hs256-{}", &digest[..16]);
+        let key = SigningKey::from_material(kid, JwtAlgorithm::HS256, Utc::now(), secret.as_bytes().to_vec())
+            .expect("HS256 keys accept any secret");
+
+        let config = JwtKeyConfig {
+            algorithm: JwtAlgorithm::HS256,
+            rotation_interval_secs: 0,
+            ..Default::default()
+        };
+        Self::with_keys(config, vec![key], None)
+    }
+
+    /// Key manager with a freshly generated signing key
+    ///
+    /// Keys live in memory only, so issued tokens do not survive a restart.
+    pub fn generate(config: JwtKeyConfig) -> Result<Self> {
+        let material = SigningKey::generate_material(config.algorithm)?;
+        let key = SigningKey::from_material(new_kid(), config.algorithm, Utc::now(), material)?;
+        Ok(Self::with_keys(config, vec![key], None))
+    }
+
+    /// Key manager whose key ring is wrapped by a KMS key and stored at `path`
+    ///
+    /// Loads the existing key ring (dropping keys past their grace period) or
+    /// generates the first key if there is none.
+    pub async fn with_kms(
+        config: JwtKeyConfig,
+        kms: Arc<dyn KmsClient>,
+        kms_key_id: String,
+        path: PathBuf,
+    ) -> Result<Self> {
+        let key_ring = KmsKeyRing {
+            kms,
+            key_id: kms_key_id,
+            path,
+        };
+        let now = Utc::now();
+        let keys: Vec<SigningKey> = key_ring
+            .load()
+            .await?
+            .into_iter()
+            .filter(|key| !key.is_retired(now))
+            .collect();
+        let loaded = keys.len();
+
+        let manager = Self::with_keys(config, keys, Some(key_ring));
+        if manager.current_kid().is_none() {
+            manager.rotate().await?;
+        } else {
+            tracing::info!("🔑 Loaded {} JWT signing key(s)", loaded);
+        }
+        Ok(manager)
+    }
+
+    /// Algorithm of newly generated keys
+    pub fn algorithm(&self) -> JwtAlgorithm {
+        self.config.algorithm
+    }
+
+    /// Key ID of the current signing key
+    pub fn current_kid(&self) -> Option<String> {
+        self.keys
+            .read()
+            .iter()
+            .rev()
+            .find(|key| key.retires_at.is_none())
+            .map(|key| key.kid.clone())
+    }
+
+    /// Key IDs that currently verify tokens, oldest first
+    pub fn active_kids(&self) -> Vec<String> {
+        let now = Utc::now();
+        self.keys
+            .read()
+            .iter()
+            .filter(|key| !key.is_retired(now))
+            .map(|key| key.kid.clone())
+            .collect()
+    }
+
+    /// Sign claims with the current key
+    pub fn sign<T: Serialize>(&self, claims: &T) -> Result<String> {
+        let keys = self.keys.read();
+        let key = keys
+            .iter()
+            .rev()
+            .find(|key| key.retires_at.is_none())
+            .ok_or_else(|| AllSourceError::InternalError("No JWT signing key available".to_string()))?;
+
+        let mut header = Header::new(key.algorithm.algorithm());
+        header.kid = Some(key.kid.clone());
+        encode(&header, claims, &key.encoding_key)
+            .map_err(|e| AllSourceError::ValidationError(format!("Failed to create token: {}", e)))
+    }
+
+    /// Verify a token's signature, issuer and expiry and return its claims
+    ///
+    /// Tokens carrying a `kid` are only checked against that key; older
+    /// tokens without one are checked against every key of their algorithm.
+    pub fn verify<T: DeserializeOwned>(&self, token: &str) -> Result<T> {
+        let invalid = |msg: String| AllSourceError::ValidationError(format!("Invalid token: {}", msg));
+        let header = decode_header(token).map_err(|e| invalid(e.to_string()))?;
+
+        let now = Utc::now();
+        let keys = self.keys.read();
+        let candidates: Vec<&SigningKey> = keys
+            .iter()
+            .filter(|key| !key.is_retired(now))
+            .filter(|key| match &header.kid {
+                Some(kid) => &key.kid == kid,
+                None => key.algorithm.algorithm() == header.alg,
+            })
+            .collect();
+        if candidates.is_empty() {
+            return Err(invalid("unknown signing key".to_string()));
+        }
+
+        let mut last_error = None;
+        for key in candidates.into_iter().rev() {
+            let mut validation = Validation::new(key.algorithm.algorithm());
+            validation.set_issuer(&[JWT_ISSUER]);
+            match decode::<T>(token, &key.decoding_key, &validation) {
+                Ok(data) => return Ok(data.claims),
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(invalid(last_error.map(|e| e.to_string()).unwrap_or_default()))
+    }
+
+    /// Public keys of all keys that still verify tokens
+    pub fn jwks(&self) -> JwkSet {
+        let now = Utc::now();
+        JwkSet {
+            keys: self
+                .keys
+                .read()
+                .iter()
+                .filter(|key| !key.is_retired(now))
+                .filter_map(|key| key.jwk.clone())
+                .collect(),
+        }
+    }
+
+    /// Generate a new signing key and retire the current one after the grace period
+    ///
+    /// Returns the new key ID.
+    pub async fn rotate(&self) -> Result<String> {
+        let _rotation = self.rotation_lock.lock().await;
+        let algorithm = self.config.algorithm;
+
+        let (material, wrapped) = match &self.key_ring {
+            Some(key_ring) => {
+                let (material, wrapped) = key_ring.generate(algorithm).await?;
+                (material, Some(wrapped))
+            }
+            None => {
+                let material = tokio::task::spawn_blocking(move || SigningKey::generate_material(algorithm))
+                    .await
+                    .map_err(|e| AllSourceError::InternalError(format!("Key generation task failed: {}", e)))??;
+                (material, None)
+            }
+        };
+        let mut key = SigningKey::from_material(new_kid(), algorithm, Utc::now(), material)?;
+        key.wrapped_material = wrapped;
+        let kid = key.kid.clone();
+
+        let retires_at = Utc::now() + Duration::seconds(self.config.grace_period_secs as i64);
+        let mut keys = self.keys.write();
+        let mut stored = Vec::with_capacity(keys.len() + 1);
+        if self.key_ring.is_some() {
+            for existing in keys.iter() {
+                stored.push(stored_key(existing, Some(existing.retires_at.unwrap_or(retires_at)))?);
+            }
+            stored.push(stored_key(&key, None)?);
+        }
+        if let Some(key_ring) = &self.key_ring {
+            key_ring.save(&stored)?;
+        }
+
+        for existing in keys.iter_mut() {
+            existing.retires_at.get_or_insert(retires_at);
+        }
+        keys.push(key);
+        drop(keys);
+
+        tracing::info!("🔑 Rotated JWT signing key: {} ({:?})", kid, algorithm);
+        Ok(kid)
+    }
+
+    /// Drop keys whose grace period has ended; returns how many were removed
+    pub fn prune(&self) -> Result<usize> {
+        let now = Utc::now();
+        let mut keys = self.keys.write();
+        let before = keys.len();
+        keys.retain(|key| !key.is_retired(now));
+        let removed = before - keys.len();
+
+        if removed > 0 {
+            if let Some(key_ring) = &self.key_ring {
+                let stored = keys
+                    .iter()
+                    .map(|key| stored_key(key, key.retires_at))
+                    .collect::<Result<Vec<_>>>()?;
+                key_ring.save(&stored)?;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Rotate if the current key is older than the rotation interval, then prune
+    ///
+    /// Returns the new key ID if a rotation happened.
+    pub async fn rotate_if_due(&self) -> Result<Option<String>> {
+        let due = self.config.rotation_interval_secs > 0 && {
+            let keys = self.keys.read();
+            match keys.iter().rev().find(|key| key.retires_at.is_none()) {
+                Some(current) => {
+                    Utc::now() - current.created_at
+                        >= Duration::seconds(self.config.rotation_interval_secs as i64)
+                }
+                None => true,
+            }
+        };
+
+        let rotated = if due { Some(self.rotate().await?) } else { None };
+        let pruned = self.prune()?;
+        if pruned > 0 {
+            tracing::info!("🔑 Removed {} expired JWT signing key(s)", pruned);
+        }
+        Ok(rotated)
+    }
+
+    /// Spawn the background task that performs scheduled rotations
+    pub fn spawn_rotation(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let manager = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(std::time::Duration::from_secs(ROTATION_CHECK_INTERVAL_SECS));
+            loop {
+                interval.tick().await;
+                if let Err(e) = manager.rotate_if_due().await {
+                    tracing::error!("❌ JWT key rotation failed: {}", e);
+                }
+            }
+        })
+    }
+}
+
+fn new_kid() -> String {
+    Uuid::new_v4().simple().to_string()
+}
+
+fn stored_key(key: &SigningKey, retires_at: Option<DateTime<Utc>>) -> Result<StoredSigningKey> {
+    let wrapped = key.wrapped_material.as_ref().ok_or_else(|| {
+        AllSourceError::InternalError(format!("JWT key {} has not been wrapped by the KMS", key.kid))
+    })?;
+    Ok(StoredSigningKey {
+        kid: key.kid.clone(),
+        algorithm: key.algorithm,
+        created_at: key.created_at,
+        retires_at,
+        wrapped_material: STANDARD.encode(wrapped),
+    })
+}
+
+fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| {
+            AllSourceError::StorageError(format!("Failed to create key ring directory: {}", e))
+        })?;
+    }
+    let tmp_path = path.with_extension("tmp");
+    std::fs::write(&tmp_path, data).map_err(|e| {
+        AllSourceError::StorageError(format!("Failed to write JWT key ring: {}", e))
+    })?;
+    std::fs::rename(&tmp_path, path).map_err(|e| {
+        AllSourceError::StorageError(format!("Failed to persist JWT key ring: {}", e))
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::kms::{KeyAlgorithm, KeyPurpose, KmsConfig, LocalKms};
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct TestClaims {
+        sub: String,
+        iss: String,
+        exp: i64,
+    }
+
+    fn claims() -> TestClaims {
+        TestClaims {
+            sub: "alice".to_string(),
+            iss: JWT_ISSUER.to_string(),
+            exp: (Utc::now() + Duration::hours(1)).timestamp(),
+        }
+    }
+
+    fn config(algorithm: JwtAlgorithm) -> JwtKeyConfig {
+        JwtKeyConfig {
+            algorithm,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_sign_verify_each_algorithm() {
+        for algorithm in [JwtAlgorithm::HS256, JwtAlgorithm::RS256, JwtAlgorithm::ES256] {
+            let manager = JwtKeyManager::generate(config(algorithm)).unwrap();
+            let token = manager.sign(&claims()).unwrap();
+
+            let header = decode_header(&token).unwrap();
+            assert_eq!(header.alg, algorithm.algorithm());
+            assert_eq!(header.kid, manager.current_kid());
+
+            let verified: TestClaims = manager.verify(&token).unwrap();
+            assert_eq!(verified.sub, "alice");
+
+            // Symmetric keys are never published
+            let published = manager.jwks().keys.len();
+            assert_eq!(published, usize::from(algorithm != JwtAlgorithm::HS256));
+        }
+    }
+
+    #[test]
+    fn test_jwks_verifies_tokens() {
+        let manager = JwtKeyManager::generate(config(JwtAlgorithm::ES256)).unwrap();
+        let token = manager.sign(&claims()).unwrap();
+
+        // A third party only needs the published JWK Set
+        let jwks: JwkSet = serde_json::from_value(serde_json::to_value(manager.jwks()).unwrap()).unwrap();
+        let kid = decode_header(&token).unwrap().kid.unwrap();
+        let jwk = jwks.find(&kid).unwrap();
+        let mut validation = Validation::new(Algorithm::ES256);
+        validation.set_issuer(&[JWT_ISSUER]);
+        let data = decode::<TestClaims>(&token, &DecodingKey::from_jwk(jwk).unwrap(), &validation).unwrap();
+        assert_eq!(data.claims.sub, "alice");
+    }
+
+    #[tokio::test]
+    async fn test_rotation_keeps_old_key_during_grace_period() {
+        let manager = JwtKeyManager::generate(config(JwtAlgorithm::ES256)).unwrap();
+        let old_kid = manager.current_kid().unwrap();
+        let old_token = manager.sign(&claims()).unwrap();
+
+        let new_kid = manager.rotate().await.unwrap();
+        assert_ne!(old_kid, new_kid);
+        assert_eq!(manager.current_kid(), Some(new_kid.clone()));
+        assert_eq!(decode_header(&manager.sign(&claims()).unwrap()).unwrap().kid, Some(new_kid));
+
+        // Old tokens still verify and the old key is still published
+        assert!(manager.verify::<TestClaims>(&old_token).is_ok());
+        assert_eq!(manager.jwks().keys.len(), 2);
+
+        // Once the grace period has passed the old key is gone
+        manager.keys.write()[0].retires_at = Some(Utc::now() - Duration::seconds(1));
+        assert!(manager.verify::<TestClaims>(&old_token).is_err());
+        assert_eq!(manager.prune().unwrap(), 1);
+        assert_eq!(manager.active_kids().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rotate_if_due() {
+        let manager = JwtKeyManager::generate(JwtKeyConfig {
+            algorithm: JwtAlgorithm::HS256,
+            rotation_interval_secs: 3600,
+            grace_period_secs: 60,
+        })
+        .unwrap();
+        assert!(manager.rotate_if_due().await.unwrap().is_none());
+
+        manager.keys.write()[0].created_at = Utc::now() - Duration::hours(2);
+        assert!(manager.rotate_if_due().await.unwrap().is_some());
+        assert_eq!(manager.active_kids().len(), 2);
+    }
+
+    #[test]
+    fn test_rejects_unknown_kid_and_algorithm_confusion() {
+        let manager = JwtKeyManager::generate(config(JwtAlgorithm::ES256)).unwrap();
+        let other = JwtKeyManager::generate(config(JwtAlgorithm::ES256)).unwrap();
+        assert!(manager.verify::<TestClaims>(&other.sign(&claims()).unwrap()).is_err());
+
+        // An HS256 token naming our kid must not be accepted
+        let mut header = Header::new(Algorithm::HS256);
+        header.kid = manager.current_kid();
+        let forged = encode(&header, &claims(), &EncodingKey::from_secret(b"guess")).unwrap();
+        assert!(manager.verify::<TestClaims>(&forged).is_err());
+    }
+
+    #[test]
+    fn test_secret_tokens_without_kid_still_verify() {
+        let manager = JwtKeyManager::from_secret("shared-secret");
+        let legacy = encode(&Header::default(), &claims(), &EncodingKey::from_secret(b"shared-secret")).unwrap();
+        assert!(manager.verify::<TestClaims>(&legacy).is_ok());
+
+        // The kid only depends on the secret
+        assert_eq!(manager.current_kid(), JwtKeyManager::from_secret("shared-secret").current_kid());
+    }
+
+    #[tokio::test]
+    async fn test_kms_key_ring_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("jwt_keys.json");
+        let kms: Arc<dyn KmsClient> = Arc::new(LocalKms::new(KmsConfig::default()));
+        let wrapping_key = kms
+            .create_key("jwt".to_string(), KeyPurpose::JwtSigning, KeyAlgorithm::Aes256Gcm)
+            .await
+            .unwrap();
+
+        for algorithm in [JwtAlgorithm::HS256, JwtAlgorithm::ES256] {
+            let _ = std::fs::remove_file(&path);
+            let manager = JwtKeyManager::with_kms(config(algorithm), kms.clone(), wrapping_key.key_id.clone(), path.clone())
+                .await
+                .unwrap();
+            let token = manager.sign(&claims()).unwrap();
+            manager.rotate().await.unwrap();
+
+            // Key material is only stored wrapped
+            let stored: Vec<StoredSigningKey> = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+            assert_eq!(stored.len(), 2);
+            assert!(stored[0].retires_at.is_some());
+
+            let reopened =
+                JwtKeyManager::with_kms(config(algorithm), kms.clone(), wrapping_key.key_id.clone(), path.clone())
+                    .await
+                    .unwrap();
+            assert_eq!(reopened.current_kid(), manager.current_kid());
+            assert!(reopened.verify::<TestClaims>(&token).is_ok());
+        }
+    }
+}
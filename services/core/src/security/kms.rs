@@ -8,8 +8,10 @@
 /// - Local HSM via PKCS#11
 
 use crate::error::{AllSourceError, Result};
+use base64::engine::general_purpose;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use parking_lot::RwLock;
 
@@ -51,6 +53,19 @@ impl Default for KmsConfig {
     }
 }
 
+impl KmsConfig {
+    /// Persist local KMS keys to `path` unless a `key_file` is already configured
+    ///
+    /// Has no effect for other providers, whose keys live in the KMS itself.
+    pub fn with_default_key_file(mut self, path: impl Into<PathBuf>) -> Self {
+        if self.provider == KmsProvider::Local && !self.config.contains_key("key_file") {
+            self.config
+                .insert("key_file".to_string(), path.into().to_string_lossy().into_owned());
+        }
+        self
+    }
+}
+
 /// Key metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KeyMetadata {
@@ -85,6 +100,7 @@ pub enum KeyPurpose {
     JwtSigning,
     ApiKeySigning,
     DatabaseEncryption,
+    AuditSigning,
     Custom(String),
 }
 
@@ -96,6 +112,7 @@ pub enum KeyAlgorithm {
     RsaOaep,
     EcdsaP256,
     Ed25519,
+    HmacSha256,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -135,26 +152,152 @@ pub trait KmsClient: Send + Sync {
 
     /// Generate data encryption key (for envelope encryption)
     async fn generate_data_key(&self, key_id: &str) -> Result<(Vec<u8>, Vec<u8>)>;
+
+    /// Sign a message (HMAC-SHA256 with the current key version); returns
+    /// the signature and the key version that produced it
+    async fn sign(&self, key_id: &str, message: &[u8]) -> Result<(Vec<u8>, u32)>;
+
+    /// Verify a signature produced by `sign` with the given key version
+    async fn verify(&self, key_id: &str, version: u32, message: &[u8], signature: &[u8]) -> Result<bool>;
 }
 
 /// Local KMS implementation (for testing/development)
+///
+/// Keys are held in memory. When `KmsConfig::config` has a `key_file`
+/// entry, `LocalKms::open` loads keys from that JSON file and every key
+/// change is written back to it, so data wrapped under a key stays
+/// decryptable across restarts. The file holds raw key material and is
+/// created readable by its owner only.
 pub struct LocalKms {
     keys: Arc<RwLock<HashMap<String, StoredKey>>>,
     config: KmsConfig,
+    key_file: Option<PathBuf>,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 struct StoredKey {
     metadata: KeyMetadata,
+    #[serde(with = "base64_bytes")]
     key_material: Vec<u8>,
+    /// Material of earlier versions, kept so older ciphertexts still decrypt
+    #[serde(default, with = "base64_versions")]
+    previous_versions: HashMap<u32, Vec<u8>>,
 }
 
 impl LocalKms {
+    /// Create an in-memory local KMS
     pub fn new(config: KmsConfig) -> Self {
         Self {
             keys: Arc::new(RwLock::new(HashMap::new())),
             config,
+            key_file: None,
         }
     }
+
+    /// Create a local KMS, loading keys from the configured `key_file`
+    ///
+    /// Without a `key_file` this is the same as `new`.
+    pub fn open(config: KmsConfig) -> Result<Self> {
+        let Some(key_file) = config.config.get("key_file").map(PathBuf::from) else {
+            return Ok(Self::new(config));
+        };
+
+        let keys: HashMap<String, StoredKey> = match std::fs::read(&key_file) {
+            Ok(data) => serde_json::from_slice(&data)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => {
+                return Err(AllSourceError::StorageError(format!(
+                    "Failed to read KMS keys from {}: {}",
+                    key_file.display(),
+                    e
+                )))
+            }
+        };
+
+        Ok(Self {
+            keys: Arc::new(RwLock::new(keys)),
+            config,
+            key_file: Some(key_file),
+        })
+    }
+
+    /// Apply a change to the key set, persisting it before it takes effect
+    fn update<T>(&self, change: impl FnOnce(&mut HashMap<String, StoredKey>) -> Result<T>) -> Result<T> {
+        let mut keys = self.keys.write();
+        let Some(key_file) = &self.key_file else {
+            return change(&mut keys);
+        };
+
+        let mut updated = keys.clone();
+        let result = change(&mut updated)?;
+        save_keys(key_file, &updated)?;
+        *keys = updated;
+        Ok(result)
+    }
+}
+
+fn save_keys(path: &Path, keys: &HashMap<String, StoredKey>) -> Result<()> {
+    let write_err = |e: std::io::Error| {
+        AllSourceError::StorageError(format!("Failed to write KMS keys to {}: {}", path.display(), e))
+    };
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(write_err)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    {
+        use std::io::Write;
+        let mut file = options.open(&tmp).map_err(write_err)?;
+        file.write_all(&serde_json::to_vec_pretty(keys)?).map_err(write_err)?;
+        file.sync_all().map_err(write_err)?;
+    }
+    std::fs::rename(&tmp, path).map_err(write_err)
+}
+
+mod base64_bytes {
+    use super::general_purpose;
+    use base64::Engine as _;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&general_purpose::STANDARD.encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        general_purpose::STANDARD.decode(encoded).map_err(serde::de::Error::custom)
+    }
+}
+
+mod base64_versions {
+    use super::general_purpose;
+    use base64::Engine as _;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::HashMap;
+
+    pub fn serialize<S: Serializer>(versions: &HashMap<u32, Vec<u8>>, serializer: S) -> Result<S::Ok, S::Error> {
+        versions
+            .iter()
+            .map(|(version, bytes)| (*version, general_purpose::STANDARD.encode(bytes)))
+            .collect::<HashMap<u32, String>>()
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<HashMap<u32, Vec<u8>>, D::Error> {
+        HashMap::<u32, String>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(version, encoded)| {
+                general_purpose::STANDARD
+                    .decode(encoded)
+                    .map(|bytes| (version, bytes))
+                    .map_err(serde::de::Error::custom)
+            })
+            .collect()
+    }
 }
 
 #[async_trait::async_trait]
@@ -164,7 +307,7 @@ impl KmsClient for LocalKms {
 
         // Generate key material based on algorithm
         let key_material = match algorithm {
-            KeyAlgorithm::Aes256Gcm => {
+            KeyAlgorithm::Aes256Gcm | KeyAlgorithm::HmacSha256 => {
                 let mut key = vec![0u8; 32];
                 use aes_gcm::aead::OsRng;
                 use aes_gcm::aead::rand_core::RngCore;
@@ -199,9 +342,13 @@ impl KmsClient for LocalKms {
         let stored_key = StoredKey {
             metadata: metadata.clone(),
             key_material,
+            previous_versions: HashMap::new(),
         };
 
-        self.keys.write().insert(key_id, stored_key);
+        self.update(|keys| {
+            keys.insert(key_id, stored_key);
+            Ok(())
+        })?;
 
         Ok(metadata)
     }
@@ -246,8 +393,9 @@ impl KmsClient for LocalKms {
         let ciphertext = cipher.encrypt(nonce, plaintext)
             .map_err(|e| AllSourceError::ValidationError(format!("Encryption failed: {}", e)))?;
 
-        // Prepend nonce to ciphertext
-        let mut result = nonce.to_vec();
+        // Prepend key version and nonce to ciphertext
+        let mut result = stored_key.metadata.version.to_be_bytes().to_vec();
+        result.extend_from_slice(nonce);
         result.extend_from_slice(&ciphertext);
 
         Ok(result)
@@ -257,7 +405,7 @@ impl KmsClient for LocalKms {
         use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
         use aes_gcm::aead::Aead;
 
-        if ciphertext_with_nonce.len() < 12 {
+        if ciphertext_with_nonce.len() < 16 {
             return Err(AllSourceError::ValidationError("Invalid ciphertext".to_string()));
         }
 
@@ -265,54 +413,73 @@ impl KmsClient for LocalKms {
         let stored_key = keys.get(key_id)
             .ok_or_else(|| AllSourceError::ValidationError(format!("Key {} not found", key_id)))?;
 
-        let cipher = Aes256Gcm::new_from_slice(&stored_key.key_material)
+        // Decrypt with the key version the data was encrypted under
+        let mut version = [0u8; 4];
+        version.copy_from_slice(&ciphertext_with_nonce[..4]);
+        let version = u32::from_be_bytes(version);
+        let key_material = if version == stored_key.metadata.version {
+            &stored_key.key_material
+        } else {
+            stored_key.previous_versions.get(&version).ok_or_else(|| {
+                AllSourceError::ValidationError(format!(
+                    "Version {} of key {} no longer exists",
+                    version, key_id
+                ))
+            })?
+        };
+
+        let cipher = Aes256Gcm::new_from_slice(key_material)
             .map_err(|e| AllSourceError::ValidationError(format!("Invalid key: {}", e)))?;
 
         // Extract nonce and ciphertext
-        let nonce = Nonce::from_slice(&ciphertext_with_nonce[..12]);
-        let ciphertext = &ciphertext_with_nonce[12..];
+        let nonce = Nonce::from_slice(&ciphertext_with_nonce[4..16]);
+        let ciphertext = &ciphertext_with_nonce[16..];
 
         cipher.decrypt(nonce, ciphertext)
             .map_err(|e| AllSourceError::ValidationError(format!("Decryption failed: {}", e)))
     }
 
     async fn rotate_key(&self, key_id: &str) -> Result<KeyMetadata> {
-        let mut keys = self.keys.write();
-        let stored_key = keys.get_mut(key_id)
-            .ok_or_else(|| AllSourceError::ValidationError(format!("Key {} not found", key_id)))?;
+        self.update(|keys| {
+            let stored_key = keys.get_mut(key_id)
+                .ok_or_else(|| AllSourceError::ValidationError(format!("Key {} not found", key_id)))?;
 
-        // Generate new key material
-        let new_key_material = {
-            let mut key = vec![0u8; 32];
-            use aes_gcm::aead::OsRng;
-            use aes_gcm::aead::rand_core::RngCore;
-            RngCore::fill_bytes(&mut OsRng, &mut key);
-            key
-        };
+            // Generate new key material
+            let new_key_material = {
+                let mut key = vec![0u8; 32];
+                use aes_gcm::aead::OsRng;
+                use aes_gcm::aead::rand_core::RngCore;
+                RngCore::fill_bytes(&mut OsRng, &mut key);
+                key
+            };
 
-        stored_key.key_material = new_key_material;
-        stored_key.metadata.version += 1;
-        stored_key.metadata.last_rotated = Some(chrono::Utc::now());
+            let old_key_material = std::mem::replace(&mut stored_key.key_material, new_key_material);
+            stored_key.previous_versions.insert(stored_key.metadata.version, old_key_material);
+            stored_key.metadata.version += 1;
+            stored_key.metadata.last_rotated = Some(chrono::Utc::now());
 
-        Ok(stored_key.metadata.clone())
+            Ok(stored_key.metadata.clone())
+        })
     }
 
     async fn disable_key(&self, key_id: &str) -> Result<()> {
-        let mut keys = self.keys.write();
-        let stored_key = keys.get_mut(key_id)
-            .ok_or_else(|| AllSourceError::ValidationError(format!("Key {} not found", key_id)))?;
+        self.update(|keys| {
+            let stored_key = keys.get_mut(key_id)
+                .ok_or_else(|| AllSourceError::ValidationError(format!("Key {} not found", key_id)))?;
 
-        stored_key.metadata.status = KeyStatus::Deprecated;
-        Ok(())
+            stored_key.metadata.status = KeyStatus::Deprecated;
+            Ok(())
+        })
     }
 
     async fn enable_key(&self, key_id: &str) -> Result<()> {
-        let mut keys = self.keys.write();
-        let stored_key = keys.get_mut(key_id)
-            .ok_or_else(|| AllSourceError::ValidationError(format!("Key {} not found", key_id)))?;
+        self.update(|keys| {
+            let stored_key = keys.get_mut(key_id)
+                .ok_or_else(|| AllSourceError::ValidationError(format!("Key {} not found", key_id)))?;
 
-        stored_key.metadata.status = KeyStatus::Active;
-        Ok(())
+            stored_key.metadata.status = KeyStatus::Active;
+            Ok(())
+        })
     }
 
     async fn generate_data_key(&self, key_id: &str) -> Result<(Vec<u8>, Vec<u8>)> {
@@ -327,6 +494,51 @@ impl KmsClient for LocalKms {
 
         Ok((dek, encrypted_dek))
     }
+
+    async fn sign(&self, key_id: &str, message: &[u8]) -> Result<(Vec<u8>, u32)> {
+        use hmac::Mac;
+
+        let keys = self.keys.read();
+        let stored_key = keys.get(key_id)
+            .ok_or_else(|| AllSourceError::ValidationError(format!("Key {} not found", key_id)))?;
+
+        if stored_key.metadata.status != KeyStatus::Active {
+            return Err(AllSourceError::ValidationError("Key is not active".to_string()));
+        }
+
+        let signature = hmac_sha256(&stored_key.key_material, message)?.finalize().into_bytes().to_vec();
+        Ok((signature, stored_key.metadata.version))
+    }
+
+    async fn verify(&self, key_id: &str, version: u32, message: &[u8], signature: &[u8]) -> Result<bool> {
+        use hmac::Mac;
+
+        let keys = self.keys.read();
+        let stored_key = keys.get(key_id)
+            .ok_or_else(|| AllSourceError::ValidationError(format!("Key {} not found", key_id)))?;
+
+        // Verify with the key version the message was signed under
+        let key_material = if version == stored_key.metadata.version {
+            &stored_key.key_material
+        } else {
+            match stored_key.previous_versions.get(&version) {
+                Some(key_material) => key_material,
+                None => return Ok(false),
+            }
+        };
+
+        // Constant-time comparison
+        Ok(hmac_sha256(key_material, message)?.verify_slice(signature).is_ok())
+    }
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> Result<hmac::Hmac<sha2::Sha256>> {
+    use hmac::Mac;
+
+    let mut mac = <hmac::Hmac<sha2::Sha256> as Mac>::new_from_slice(key)
+        .map_err(|e| AllSourceError::ValidationError(format!("Invalid key: {}", e)))?;
+    mac.update(message);
+    Ok(mac)
 }
 
 /// KMS manager for handling multiple providers
@@ -340,7 +552,10 @@ impl KmsManager {
     pub fn new(config: KmsConfig) -> Result<Self> {
         let client: Arc<dyn KmsClient> = match config.provider {
             KmsProvider::Local => {
-                Arc::new(LocalKms::new(config.clone()))
+                Arc::new(LocalKms::open(config.clone())?)
+            }
+            KmsProvider::HashicorpVault => {
+                Arc::new(crate::security::vault_kms::VaultKms::new(config.clone())?)
             }
             _ => {
                 return Err(AllSourceError::ValidationError(
@@ -357,6 +572,17 @@ impl KmsManager {
         &self.client
     }
 
+    /// Find a key by id or alias, creating it under `alias` if it does not exist
+    pub async fn ensure_key(&self, alias: &str, purpose: KeyPurpose, algorithm: KeyAlgorithm) -> Result<KeyMetadata> {
+        let existing = self.client.list_keys().await?
+            .into_iter()
+            .find(|key| key.key_id == alias || key.alias == alias);
+        match existing {
+            Some(key) => Ok(key),
+            None => self.client.create_key(alias.to_string(), purpose, algorithm).await,
+        }
+    }
+
     /// Encrypt data using envelope encryption
     pub async fn envelope_encrypt(&self, master_key_id: &str, plaintext: &[u8]) -> Result<EnvelopeEncryptedData> {
         // Generate data encryption key
@@ -471,9 +697,16 @@ mod tests {
             KeyAlgorithm::Aes256Gcm,
         ).await.unwrap();
 
+        let before = kms.encrypt(&key.key_id, b"v1 data").await.unwrap();
+
         let rotated = kms.rotate_key(&key.key_id).await.unwrap();
         assert_eq!(rotated.version, 2);
         assert!(rotated.last_rotated.is_some());
+
+        // Data encrypted under the previous version still decrypts
+        let after = kms.encrypt(&key.key_id, b"v2 data").await.unwrap();
+        assert_eq!(kms.decrypt(&key.key_id, &before).await.unwrap(), b"v1 data");
+        assert_eq!(kms.decrypt(&key.key_id, &after).await.unwrap(), b"v2 data");
     }
 
     #[tokio::test]
@@ -527,4 +760,62 @@ mod tests {
         let result = kms.encrypt(&key.key_id, b"test").await;
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_local_kms_key_file_survives_restart() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = KmsConfig::default().with_default_key_file(dir.path().join("keys.json"));
+
+        let kms = KmsManager::new(config.clone()).unwrap();
+        let key = kms.ensure_key("backups", KeyPurpose::DataEncryption, KeyAlgorithm::Aes256Gcm).await.unwrap();
+        let v1 = kms.client().encrypt(&key.key_id, b"v1 data").await.unwrap();
+        kms.client().rotate_key(&key.key_id).await.unwrap();
+        let v2 = kms.client().encrypt(&key.key_id, b"v2 data").await.unwrap();
+        drop(kms);
+
+        // Same key (found by alias), every version still decrypts
+        let reopened = KmsManager::new(config.clone()).unwrap();
+        let found = reopened.ensure_key("backups", KeyPurpose::DataEncryption, KeyAlgorithm::Aes256Gcm).await.unwrap();
+        assert_eq!(found.key_id, key.key_id);
+        assert_eq!(found.version, 2);
+        assert_eq!(reopened.client().decrypt(&key.key_id, &v1).await.unwrap(), b"v1 data");
+        assert_eq!(reopened.client().decrypt(&key.key_id, &v2).await.unwrap(), b"v2 data");
+
+        // Status changes persist too
+        reopened.client().disable_key(&key.key_id).await.unwrap();
+        let reopened = KmsManager::new(config).unwrap();
+        assert!(reopened.client().encrypt(&key.key_id, b"data").await.is_err());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(dir.path().join("keys.json")).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sign_verify() {
+        let kms = LocalKms::new(KmsConfig::default());
+        let key = kms.create_key(
+            "audit-signing".to_string(),
+            KeyPurpose::AuditSigning,
+            KeyAlgorithm::HmacSha256,
+        ).await.unwrap();
+
+        let (signature, version) = kms.sign(&key.key_id, b"checkpoint").await.unwrap();
+        assert_eq!(signature.len(), 32);
+        assert_eq!(version, 1);
+        assert!(kms.verify(&key.key_id, version, b"checkpoint", &signature).await.unwrap());
+        assert!(!kms.verify(&key.key_id, version, b"tampered", &signature).await.unwrap());
+
+        // Signatures from before a rotation verify against their key version
+        kms.rotate_key(&key.key_id).await.unwrap();
+        assert!(kms.verify(&key.key_id, version, b"checkpoint", &signature).await.unwrap());
+        assert!(!kms.verify(&key.key_id, 2, b"checkpoint", &signature).await.unwrap());
+
+        // Disabled keys cannot sign
+        kms.disable_key(&key.key_id).await.unwrap();
+        assert!(kms.sign(&key.key_id, b"checkpoint").await.is_err());
+    }
 }
@@ -0,0 +1,301 @@
+//! System Load Sampler
+//!
+//! Feeds the adaptive rate limiter with real load signals:
+//! - System CPU and memory usage from /proc/stat and /proc/meminfo
+//! - Process CPU share and resident memory from /proc/self
+//! - Active WebSocket connections
+//! - Ingest backlog (events not yet flushed to storage)
+//!
+//! Each tick records the load, re-evaluates adaptive limits and exports
+//! both as Prometheus metrics. Where /proc is unavailable CPU and memory
+//! read as zero, so only connection and backlog signals apply.
+
+use crate::security::adaptive_rate_limit::{AdaptiveRateLimiter, LimitChange, SystemLoad};
+use crate::store::EventStore;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Load sampler configuration
+#[derive(Debug, Clone)]
+pub struct LoadSamplerConfig {
+    /// Time between samples
+    pub interval: Duration,
+
+    /// procfs mount point
+    pub proc_root: PathBuf,
+}
+
+impl Default for LoadSamplerConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(15),
+            proc_root: PathBuf::from("/proc"),
+        }
+    }
+}
+
+/// One load reading
+#[derive(Debug, Clone, Default)]
+pub struct LoadSample {
+    pub system: SystemLoad,
+    /// Share of all CPU time used by this process (0.0-1.0)
+    pub process_cpu_usage: f64,
+    pub process_resident_memory_bytes: u64,
+}
+
+/// Cumulative CPU counters, in clock ticks
+#[derive(Debug, Clone, Copy)]
+struct CpuTimes {
+    total: u64,
+    idle: u64,
+    process: u64,
+}
+
+/// Periodically samples load into an `AdaptiveRateLimiter`
+pub struct LoadSampler {
+    limiter: Arc<AdaptiveRateLimiter>,
+    store: Arc<EventStore>,
+    config: LoadSamplerConfig,
+    previous: Option<CpuTimes>,
+}
+
+impl LoadSampler {
+    pub fn new(limiter: Arc<AdaptiveRateLimiter>, store: Arc<EventStore>, config: LoadSamplerConfig) -> Self {
+        Self {
+            limiter,
+            store,
+            config,
+            previous: None,
+        }
+    }
+
+    /// Read the current load. CPU usage is measured since the previous
+    /// sample, so the first one reports zero.
+    pub fn sample(&mut self) -> LoadSample {
+        let read = |path: &str| std::fs::read_to_string(self.config.proc_root.join(path)).ok();
+
+        let times = read("stat").and_then(|s| parse_cpu_times(&s)).map(|(total, idle)| CpuTimes {
+            total,
+            idle,
+            process: read("self/stat").and_then(|s| parse_process_cpu_time(&s)).unwrap_or(0),
+        });
+
+        let (cpu_usage, process_cpu_usage) = match (self.previous, times) {
+            (Some(prev), Some(now)) if now.total > prev.total => {
+                let elapsed = (now.total - prev.total) as f64;
+                let busy = elapsed - now.idle.saturating_sub(prev.idle) as f64;
+                let process = now.process.saturating_sub(prev.process) as f64;
+                ((busy / elapsed).clamp(0.0, 1.0), (process / elapsed).clamp(0.0, 1.0))
+            }
+            _ => (0.0, 0.0),
+        };
+        if times.is_some() {
+            self.previous = times;
+        }
+
+        LoadSample {
+            system: SystemLoad {
+                cpu_usage,
+                memory_usage: read("meminfo").and_then(|s| parse_memory_usage(&s)).unwrap_or(0.0),
+                active_connections: self.store.websocket_manager().stats().connected_clients,
+                queue_depth: self.store.ingest_backlog(),
+            },
+            process_cpu_usage,
+            process_resident_memory_bytes: read("self/status")
+                .and_then(|s| parse_vm_rss_bytes(&s))
+                .unwrap_or(0),
+        }
+    }
+
+    /// Sample once, record the load, re-evaluate limits and export metrics
+    pub fn tick(&mut self) -> Vec<LimitChange> {
+        let sample = self.sample();
+        let metrics = self.store.metrics();
+        metrics.system_cpu_usage.set(sample.system.cpu_usage);
+        metrics.system_memory_usage.set(sample.system.memory_usage);
+        metrics.process_cpu_usage.set(sample.process_cpu_usage);
+        metrics.process_resident_memory_bytes.set(sample.process_resident_memory_bytes as i64);
+        metrics.websocket_connections_active.set(sample.system.active_connections as i64);
+        metrics.ingest_backlog.set(sample.system.queue_depth as i64);
+
+        self.limiter.record_system_load(sample.system);
+
+        let changes = match self.limiter.update_adaptive_limits() {
+            Ok(changes) => changes,
+            Err(e) => {
+                tracing::error!("❌ Adaptive rate limit update failed: {}", e);
+                return Vec::new();
+            }
+        };
+        for change in &changes {
+            metrics
+                .rate_limit_adjustments_total
+                .with_label_values(&[change.reason.as_str()])
+                .inc();
+            metrics
+                .rate_limit_current
+                .with_label_values(&[&change.tenant_id])
+                .set(change.new_limit as i64);
+            tracing::info!(
+                "🚦 Rate limit for tenant {} changed {} -> {} requests/hour ({})",
+                change.tenant_id,
+                change.old_limit,
+                change.new_limit,
+                change.reason.as_str()
+            );
+        }
+        changes
+    }
+
+    /// Sample in the background every `interval`
+    pub fn spawn(mut self) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.config.interval);
+            loop {
+                interval.tick().await;
+                self.tick();
+            }
+        })
+    }
+}
+
+/// Total and idle (idle + iowait) ticks from the aggregate `cpu` line of /proc/stat
+fn parse_cpu_times(stat: &str) -> Option<(u64, u64)> {
+    let line = stat.lines().find(|l| l.starts_with("cpu "))?;
+    let fields: Vec<u64> = line
+        .split_whitespace()
+        .skip(1)
+        .map(|f| f.parse().ok())
+        .collect::<Option<_>>()?;
+    if fields.len() < 4 {
+        return None;
+    }
+    // guest and guest_nice are already counted in user and nice
+    let total = fields.iter().take(8).sum();
+    let idle = fields[3] + fields.get(4).copied().unwrap_or(0);
+    Some((total, idle))
+}
+
+/// utime + stime ticks from /proc/self/stat
+fn parse_process_cpu_time(stat: &str) -> Option<u64> {
+    // The command name may contain spaces; fields resume after its ')'
+    let rest = &stat[stat.rfind(')')? + 1..];
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    Some(utime + stime)
+}
+
+/// Used fraction of memory from /proc/meminfo
+fn parse_memory_usage(meminfo: &str) -> Option<f64> {
+    let field = |name: &str| -> Option<u64> {
+        meminfo
+            .lines()
+            .find(|l| l.starts_with(name))?
+            .split_whitespace()
+            .nth(1)?
+            .parse()
+            .ok()
+    };
+    let total = field("MemTotal:")?;
+    let available = field("MemAvailable:").or_else(|| field("MemFree:"))?;
+    if total == 0 {
+        return None;
+    }
+    Some((1.0 - available as f64 / total as f64).clamp(0.0, 1.0))
+}
+
+/// Resident set size from /proc/self/status
+fn parse_vm_rss_bytes(status: &str) -> Option<u64> {
+    let kb: u64 = status
+        .lines()
+        .find(|l| l.starts_with("VmRSS:"))?
+        .split_whitespace()
+        .nth(1)?
+        .parse()
+        .ok()?;
+    Some(kb * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::adaptive_rate_limit::AdaptiveRateLimitConfig;
+
+    fn write_proc(root: &std::path::Path, cpu: (u64, u64), process: u64, available_kb: u64) {
+        std::fs::create_dir_all(root.join("self")).unwrap();
+        std::fs::write(
+            root.join("stat"),
+            format!(
+                "cpu  {} 0 0 {} 0 0 0 0 0 0\ncpu0 1 2 3 4 5 6 7 8 0 0\nintr 1\n",
+                cpu.0 - cpu.1,
+                cpu.1
+            ),
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("self/stat"),
+            format!("42 (all source) S 1 42 42 0 -1 4194560 100 0 0 0 {} 0 0 0 20 0 8 0 100 1000 250", process),
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("meminfo"),
+            format!("MemTotal:       1000 kB\nMemFree:         100 kB\nMemAvailable:   {} kB\n", available_kb),
+        )
+        .unwrap();
+        std::fs::write(root.join("self/status"), "Name:\tallsource\nVmRSS:\t    2048 kB\n").unwrap();
+    }
+
+    #[test]
+    fn test_proc_parsing() {
+        assert_eq!(parse_cpu_times("cpu  10 5 5 70 10 0 0 0 3 0\n"), Some((100, 80)));
+        assert_eq!(parse_cpu_times("intr 1\n"), None);
+        assert_eq!(
+            parse_process_cpu_time("7 (a) b) R 1 1 1 0 -1 0 0 0 0 0 30 12 0 0"),
+            Some(42)
+        );
+        assert_eq!(
+            parse_memory_usage("MemTotal: 400 kB\nMemAvailable: 100 kB\n"),
+            Some(0.75)
+        );
+        assert_eq!(parse_vm_rss_bytes("VmRSS:\t 4 kB\n"), Some(4096));
+    }
+
+    #[test]
+    fn test_sampler_feeds_limiter_and_metrics() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(EventStore::new());
+        let limiter = Arc::new(AdaptiveRateLimiter::new(AdaptiveRateLimitConfig::default()));
+        let mut sampler = LoadSampler::new(
+            limiter.clone(),
+            store.clone(),
+            LoadSamplerConfig {
+                proc_root: dir.path().to_path_buf(),
+                ..Default::default()
+            },
+        );
+
+        // No /proc: load reads as zero
+        let sample = sampler.sample();
+        assert_eq!(sample.system.cpu_usage, 0.0);
+        assert_eq!(sample.system.memory_usage, 0.0);
+
+        write_proc(dir.path(), (1000, 800), 10, 900);
+        assert_eq!(sampler.sample().system.cpu_usage, 0.0);
+
+        // 100 of the next 200 ticks busy, 20 of them ours
+        write_proc(dir.path(), (1200, 900), 30, 100);
+        sampler.tick();
+
+        let load = limiter.current_load().unwrap();
+        assert!((load.cpu_usage - 0.5).abs() < 1e-9);
+        assert!((load.memory_usage - 0.9).abs() < 1e-9);
+        assert_eq!(load.queue_depth, 0);
+
+        let metrics = store.metrics();
+        assert!((metrics.process_cpu_usage.get() - 0.1).abs() < 1e-9);
+        assert_eq!(metrics.process_resident_memory_bytes.get(), 2048 * 1024);
+        assert!((metrics.system_memory_usage.get() - 0.9).abs() < 1e-9);
+    }
+}
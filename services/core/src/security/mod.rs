@@ -1,16 +1,27 @@
 /// Advanced Security Module
 ///
 /// Comprehensive security features including:
-/// - ML-based anomaly detection
+/// - ML-based anomaly detection with automatic responses
 /// - Field-level encryption
-/// - HSM/KMS integration
-/// - Adaptive rate limiting
+/// - Payload encryption policies for the event path
+/// - HSM/KMS integration (local and HashiCorp Vault Transit)
+/// - JWT signing key rotation and JWKS publication
+/// - OpenID Connect login
+/// - Attribute-based access policies
+/// - Adaptive rate limiting driven by sampled system load
 /// - Security automation and CI/CD scanning
 
 pub mod anomaly_detection;
+pub mod anomaly_response;
 pub mod encryption;
+pub mod payload_encryption;
 pub mod kms;
+pub mod vault_kms;
+pub mod jwt_keys;
+pub mod oidc;
+pub mod policy;
 pub mod adaptive_rate_limit;
+pub mod load_sampler;
 pub mod automation;
 
 // Re-export main types
@@ -19,22 +30,45 @@ pub use anomaly_detection::{
     RecommendedAction, DetectionStats,
 };
 
+pub use anomaly_response::{
+    AnomalyResponder, AnomalyResponseConfig, AnomalyAlert, ResponseAction,
+    ANOMALY_DETECTOR_COMPONENT,
+};
+
 pub use encryption::{
     FieldEncryption, EncryptionConfig, EncryptedData, EncryptionAlgorithm,
     Encryptable, encrypt_json_value, decrypt_json_value, EncryptionStats,
 };
 
+pub use payload_encryption::{
+    PayloadEncryption, PayloadEncryptionConfig, SensitiveFieldPolicy,
+    REDACTED_PLACEHOLDER,
+};
+
 pub use kms::{
     KmsManager, KmsConfig, KmsProvider, KmsClient, LocalKms,
     KeyMetadata, KeyPurpose, KeyAlgorithm, KeyStatus,
     EnvelopeEncryptedData,
 };
 
+pub use vault_kms::{VaultKms, VaultAuth, VaultSettings};
+
+pub use jwt_keys::{JwtKeyManager, JwtKeyConfig, JwtAlgorithm, JWT_ISSUER};
+
+pub use oidc::{OidcProvider, OidcConfig, AuthorizationRequest, ExternalIdentity, ProviderMetadata};
+
+pub use policy::{
+    PolicyEngine, PolicyRule, PolicyEffect, PolicyRequest, PolicyResource, PolicyDecision,
+    SubjectMatch, ResourceMatch, ContextMatch, TimeWindow, RuleEvaluation,
+};
+
 pub use adaptive_rate_limit::{
     AdaptiveRateLimiter, AdaptiveRateLimitConfig, SystemLoad,
-    AdaptiveLimitStats, AdaptiveRateLimiterStats,
+    AdaptiveLimitStats, AdaptiveRateLimiterStats, AdjustmentReason, LimitChange,
 };
 
+pub use load_sampler::{LoadSampler, LoadSamplerConfig, LoadSample};
+
 pub use automation::{
     SecurityScanner, SecurityScanConfig, SecurityScanResult,
     ScanStatus, SecurityFinding, Severity, FindingCategory,
@@ -0,0 +1,692 @@
+//! OpenID Connect Login
+//!
+//! Lets users sign in with the company identity provider instead of local
+//! passwords:
+//! - Authorization-code flow with PKCE (S256), `state` and `nonce`
+//! - Provider discovery via `/.well-known/openid-configuration`
+//! - ID token validation against the provider's JWKS (cached, refetched
+//!   when an unknown `kid` shows up after a provider key rotation)
+//! - Configurable claim-to-`Role` and claim-to-tenant mapping producing
+//!   standard `Claims`, so existing `Permission` checks apply unchanged
+//!
+//! ID tokens from the provider are also accepted directly as bearer tokens.
+
+use crate::auth::{Claims, Role};
+use crate::error::{AllSourceError, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use jsonwebtoken::jwk::{Jwk, JwkSet};
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use tokio::sync::OnceCell;
+
+/// How long a started login may take before its `state` expires (seconds)
+const PENDING_LOGIN_TTL_SECS: i64 = 600;
+
+/// Minimum time between JWKS refetches triggered by unknown key IDs (seconds)
+const JWKS_MIN_REFRESH_SECS: i64 = 30;
+
+/// OIDC provider configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OidcConfig {
+    /// Issuer URL; discovery is fetched from `<issuer>/.well-known/openid-configuration`
+    pub issuer_url: String,
+
+    pub client_id: String,
+
+    /// Client secret (None for public clients relying on PKCE alone)
+    pub client_secret: Option<String>,
+
+    /// Our callback URL registered with the provider
+    pub redirect_uri: String,
+
+    pub scopes: Vec<String>,
+
+    /// ID token claim listing the user's roles or groups (dots address
+    /// nested claims, e.g. `realm_access.roles`)
+    pub role_claim: String,
+
+    /// Role claim value -> Role; the most privileged match wins
+    pub role_mapping: HashMap<String, Role>,
+
+    /// Role for users without a mapped value (None = reject the login)
+    pub default_role: Option<Role>,
+
+    /// ID token claim holding the tenant ID (None = always `default_tenant`)
+    pub tenant_claim: Option<String>,
+
+    pub default_tenant: String,
+
+    /// How long the provider's JWKS is cached (seconds)
+    pub jwks_cache_secs: u64,
+}
+
+impl Default for OidcConfig {
+    fn default() -> Self {
+        Self {
+            issuer_url: String::new(),
+            client_id: String::new(),
+            client_secret: None,
+            redirect_uri: String::new(),
+            scopes: vec!["openid".to_string(), "profile".to_string(), "email".to_string()],
+            role_claim: "groups".to_string(),
+            role_mapping: HashMap::new(),
+            default_role: None,
+            tenant_claim: None,
+            default_tenant: "default".to_string(),
+            jwks_cache_secs: 300,
+        }
+    }
+}
+
+/// Subset of the provider's discovery document we rely on
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProviderMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+}
+
+/// Where to send the user to start a login
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthorizationRequest {
+    pub authorization_url: String,
+    pub state: String,
+}
+
+/// A user authenticated by the provider, mapped to AllSource claims
+#[derive(Debug, Clone)]
+pub struct ExternalIdentity {
+    pub claims: Claims,
+    /// Display name for audit records (`preferred_username`, `email` or `sub`)
+    pub username: String,
+    pub email: Option<String>,
+}
+
+/// A login started by `begin_login`, waiting for the callback
+struct PendingLogin {
+    code_verifier: String,
+    nonce: String,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+/// OpenID Connect relying party for one provider
+pub struct OidcProvider {
+    config: OidcConfig,
+    http: reqwest::Client,
+    metadata: OnceCell<ProviderMetadata>,
+    /// Cached JWKS and when it was fetched
+    jwks: RwLock<Option<(JwkSet, DateTime<Utc>)>>,
+    /// Started logins by `state`
+    pending: DashMap<String, PendingLogin>,
+}
+
+impl OidcProvider {
+    /// Create a provider; discovery happens on first use
+    pub fn new(config: OidcConfig) -> Result<Self> {
+        if config.issuer_url.is_empty() || config.client_id.is_empty() {
+            return Err(AllSourceError::ValidationError(
+                "OIDC issuer_url and client_id are required".to_string(),
+            ));
+        }
+
+        let http = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(10))
+            .build()
+            .map_err(|e| AllSourceError::InternalError(format!("Failed to build HTTP client: {}", e)))?;
+
+        Ok(Self {
+            config,
+            http,
+            metadata: OnceCell::new(),
+            jwks: RwLock::new(None),
+            pending: DashMap::new(),
+        })
+    }
+
+    pub fn config(&self) -> &OidcConfig {
+        &self.config
+    }
+
+    /// Whether `token` claims to be issued by this provider (signature unchecked)
+    pub fn is_issuer_of(&self, token: &str) -> bool {
+        unverified_issuer(token).is_some_and(|iss| same_issuer(&iss, &self.config.issuer_url))
+    }
+
+    /// Provider metadata from the discovery document
+    pub async fn metadata(&self) -> Result<&ProviderMetadata> {
+        self.metadata
+            .get_or_try_init(|| async {
+                let url = format!(
+                    "{}/.well-known/openid-configuration",
+                    self.config.issuer_url.trim_end_matches('/')
+                );
+                let metadata: ProviderMetadata = self.get_json(&url).await?;
+                if !same_issuer(&metadata.issuer, &self.config.issuer_url) {
+                    return Err(AllSourceError::ValidationError(format!(
+                        "OIDC discovery issuer mismatch: {}",
+                        metadata.issuer
+                    )));
+                }
+                Ok(metadata)
+            })
+            .await
+    }
+
+    /// Start a login: returns the provider URL to redirect the user to
+    pub async fn begin_login(&self) -> Result<AuthorizationRequest> {
+        let metadata = self.metadata().await?;
+
+        let now = Utc::now();
+        self.pending
+            .retain(|_, login| now - login.created_at < Duration::seconds(PENDING_LOGIN_TTL_SECS));
+
+        let state = random_token();
+        let nonce = random_token();
+        let code_verifier = random_token();
+        let code_challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()));
+
+        let authorization_url = reqwest::Url::parse_with_params(
+            &metadata.authorization_endpoint,
+            &[
+                ("response_type", "code"),
+                ("client_id", self.config.client_id.as_str()),
+                ("redirect_uri", self.config.redirect_uri.as_str()),
+                ("scope", self.config.scopes.join(" ").as_str()),
+                ("state", state.as_str()),
+                ("nonce", nonce.as_str()),
+                ("code_challenge", code_challenge.as_str()),
+                ("code_challenge_method", "S256"),
+            ],
+        )
+        .map_err(|e| AllSourceError::ValidationError(format!("Invalid authorization endpoint: {}", e)))?;
+
+        self.pending.insert(
+            state.clone(),
+            PendingLogin {
+                code_verifier,
+                nonce,
+                created_at: now,
+            },
+        );
+
+        Ok(AuthorizationRequest {
+            authorization_url: authorization_url.to_string(),
+            state,
+        })
+    }
+
+    /// Finish a login: exchange the authorization code and validate the ID token
+    ///
+    /// Returns the mapped identity and the raw ID token.
+    pub async fn complete_login(&self, code: &str, state: &str) -> Result<(ExternalIdentity, String)> {
+        let (_, pending) = self
+            .pending
+            .remove(state)
+            .ok_or_else(|| AllSourceError::ValidationError("Unknown or expired login state".to_string()))?;
+        if Utc::now() - pending.created_at >= Duration::seconds(PENDING_LOGIN_TTL_SECS) {
+            return Err(AllSourceError::ValidationError("Unknown or expired login state".to_string()));
+        }
+
+        let metadata = self.metadata().await?;
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.config.redirect_uri.as_str()),
+            ("client_id", self.config.client_id.as_str()),
+            ("code_verifier", pending.code_verifier.as_str()),
+        ];
+        if let Some(secret) = &self.config.client_secret {
+            form.push(("client_secret", secret.as_str()));
+        }
+
+        let response = self
+            .http
+            .post(&metadata.token_endpoint)
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| AllSourceError::InternalError(format!("OIDC token request failed: {}", e)))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(AllSourceError::ValidationError(format!(
+                "OIDC token exchange rejected ({}): {}",
+                status, body
+            )));
+        }
+        let tokens: TokenResponse = response
+            .json()
+            .await
+            .map_err(|e| AllSourceError::InternalError(format!("Invalid OIDC token response: {}", e)))?;
+
+        let id_claims = self.validate_id_token(&tokens.id_token, Some(&pending.nonce)).await?;
+        Ok((self.map_claims(&id_claims)?, tokens.id_token))
+    }
+
+    /// Validate a provider ID token used as a bearer token
+    pub async fn authenticate_bearer(&self, token: &str) -> Result<ExternalIdentity> {
+        let id_claims = self.validate_id_token(token, None).await?;
+        self.map_claims(&id_claims)
+    }
+
+    /// Verify an ID token's signature, issuer, audience, expiry and nonce
+    pub async fn validate_id_token(&self, token: &str, nonce: Option<&str>) -> Result<JsonValue> {
+        let invalid = |msg: String| AllSourceError::ValidationError(format!("Invalid ID token: {}", msg));
+
+        let header = decode_header(token).map_err(|e| invalid(e.to_string()))?;
+        if matches!(header.alg, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512) {
+            return Err(invalid("symmetric algorithms are not accepted".to_string()));
+        }
+
+        let jwk = self.find_jwk(header.kid.as_deref()).await?;
+        let decoding_key = DecodingKey::from_jwk(&jwk).map_err(|e| invalid(e.to_string()))?;
+
+        let metadata = self.metadata().await?;
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&metadata.issuer]);
+        validation.set_audience(&[&self.config.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+        let claims = decode::<JsonValue>(token, &decoding_key, &validation)
+            .map_err(|e| invalid(e.to_string()))?
+            .claims;
+
+        if let Some(expected) = nonce {
+            if claims["nonce"].as_str() != Some(expected) {
+                return Err(invalid("nonce mismatch".to_string()));
+            }
+        }
+
+        Ok(claims)
+    }
+
+    /// Map validated ID token claims to AllSource claims
+    pub fn map_claims(&self, id_claims: &JsonValue) -> Result<ExternalIdentity> {
+        let sub = id_claims["sub"]
+            .as_str()
+            .ok_or_else(|| AllSourceError::ValidationError("ID token has no subject".to_string()))?;
+
+        let role = claim_values(id_claims, &self.config.role_claim)
+            .iter()
+            .filter_map(|value| self.config.role_mapping.get(value))
+            .max_by_key(|role| role_rank(role))
+            .cloned()
+            .or_else(|| self.config.default_role.clone())
+            .ok_or_else(|| {
+                AllSourceError::ValidationError(format!("No AllSource role mapped for {}", sub))
+            })?;
+
+        let tenant_id = self
+            .config
+            .tenant_claim
+            .as_ref()
+            .and_then(|claim| claim_values(id_claims, claim).into_iter().next())
+            .unwrap_or_else(|| self.config.default_tenant.clone());
+
+        let now = Utc::now().timestamp();
+        let claims = Claims {
+            sub: sub.to_string(),
+            tenant_id,
+            role,
+            exp: id_claims["exp"].as_i64().unwrap_or(now),
+            iat: id_claims["iat"].as_i64().unwrap_or(now),
+            iss: id_claims["iss"].as_str().unwrap_or_default().to_string(),
+            jti: id_claims["jti"].as_str().unwrap_or_default().to_string(),
+            sid: None,
+            scopes: None,
+        };
+
+        let email = id_claims["email"].as_str().map(str::to_string);
+        let username = id_claims["preferred_username"]
+            .as_str()
+            .map(str::to_string)
+            .or_else(|| email.clone())
+            .unwrap_or_else(|| sub.to_string());
+
+        Ok(ExternalIdentity {
+            claims,
+            username,
+            email,
+        })
+    }
+
+    /// Find the provider key for `kid`, refetching the JWKS when it is stale
+    /// or the key is unknown (the provider may have rotated keys)
+    async fn find_jwk(&self, kid: Option<&str>) -> Result<Jwk> {
+        let lookup = |jwks: &JwkSet| match kid {
+            Some(kid) => jwks.find(kid).cloned(),
+            None if jwks.keys.len() == 1 => jwks.keys.first().cloned(),
+            None => None,
+        };
+
+        let now = Utc::now();
+        let cached = self.jwks.read().clone();
+        if let Some((jwks, fetched_at)) = &cached {
+            let fresh = now - *fetched_at < Duration::seconds(self.config.jwks_cache_secs as i64);
+            if let Some(jwk) = lookup(jwks).filter(|_| fresh) {
+                return Ok(jwk);
+            }
+            if now - *fetched_at < Duration::seconds(JWKS_MIN_REFRESH_SECS) {
+                return lookup(jwks).ok_or_else(|| unknown_key(kid));
+            }
+        }
+
+        let jwks: JwkSet = self.get_json(&self.metadata().await?.jwks_uri).await?;
+        let jwk = lookup(&jwks);
+        *self.jwks.write() = Some((jwks, now));
+        jwk.ok_or_else(|| unknown_key(kid))
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T> {
+        let response = self
+            .http
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| AllSourceError::InternalError(format!("OIDC request to {} failed: {}", url, e)))?;
+        if !response.status().is_success() {
+            return Err(AllSourceError::InternalError(format!(
+                "OIDC request to {} returned {}",
+                url,
+                response.status()
+            )));
+        }
+        response
+            .json()
+            .await
+            .map_err(|e| AllSourceError::InternalError(format!("Invalid OIDC response from {}: {}", url, e)))
+    }
+}
+
+fn unknown_key(kid: Option<&str>) -> AllSourceError {
+    AllSourceError::ValidationError(format!(
+        "Invalid ID token: unknown signing key {}",
+        kid.unwrap_or("(no kid)")
+    ))
+}
+
+/// Values of a (possibly nested, dot-separated) claim that is a string or array of strings
+fn claim_values(claims: &JsonValue, path: &str) -> Vec<String> {
+    let value = path.split('.').fold(claims, |value, segment| &value[segment]);
+    match value {
+        JsonValue::String(s) => vec![s.clone()],
+        JsonValue::Array(items) => items.iter().filter_map(|v| v.as_str().map(str::to_string)).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn role_rank(role: &Role) -> u8 {
+    match role {
+        Role::Admin => 3,
+        Role::Developer => 2,
+        Role::ServiceAccount => 1,
+        Role::ReadOnly => 0,
+    }
+}
+
+fn same_issuer(a: &str, b: &str) -> bool {
+    a.trim_end_matches('/') == b.trim_end_matches('/')
+}
+
+/// Read the `iss` claim without verifying the token
+fn unverified_issuer(token: &str) -> Option<String> {
+    let payload = token.split('.').nth(1)?;
+    let bytes = URL_SAFE_NO_PAD.decode(payload).ok()?;
+    let claims: JsonValue = serde_json::from_slice(&bytes).ok()?;
+    claims["iss"].as_str().map(str::to_string)
+}
+
+fn random_token() -> String {
+    URL_SAFE_NO_PAD.encode(rand::random::<[u8; 32]>())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::security::jwt_keys::{JwtAlgorithm, JwtKeyConfig, JwtKeyManager};
+    use axum::{
+        extract::State,
+        http::StatusCode,
+        routing::{get, post},
+        Form, Json, Router,
+    };
+    use std::sync::Arc;
+
+    pub(crate) const CLIENT_ID: &str = "allsource";
+
+    /// Minimal in-process OpenID provider
+    #[derive(Clone)]
+    pub(crate) struct MockIdp {
+        pub(crate) issuer: String,
+        pub(crate) keys: Arc<JwtKeyManager>,
+        /// code -> (code_challenge, id token claims)
+        codes: Arc<parking_lot::Mutex<HashMap<String, (String, JsonValue)>>>,
+    }
+
+    impl MockIdp {
+        pub(crate) async fn start() -> Self {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let idp = Self {
+                issuer: format!("http://{}", listener.local_addr().unwrap()),
+                keys: Arc::new(
+                    JwtKeyManager::generate(JwtKeyConfig {
+                        algorithm: JwtAlgorithm::ES256,
+                        ..Default::default()
+                    })
+                    .unwrap(),
+                ),
+                codes: Arc::new(parking_lot::Mutex::new(HashMap::new())),
+            };
+
+            let app = Router::new()
+                .route("/.well-known/openid-configuration", get(discovery))
+                .route("/jwks", get(jwks))
+                .route("/token", post(token))
+                .with_state(idp.clone());
+            tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+            idp
+        }
+
+        pub(crate) fn config(&self) -> OidcConfig {
+            OidcConfig {
+                issuer_url: self.issuer.clone(),
+                client_id: CLIENT_ID.to_string(),
+                redirect_uri: "http://localhost:3900/api/v1/auth/oidc/callback".to_string(),
+                role_mapping: HashMap::from([
+                    ("allsource-admins".to_string(), Role::Admin),
+                    ("engineering".to_string(), Role::Developer),
+                ]),
+                tenant_claim: Some("org".to_string()),
+                ..Default::default()
+            }
+        }
+
+        /// Claims of an ID token for `sub`, valid for an hour
+        pub(crate) fn id_claims(&self, sub: &str, groups: &[&str]) -> JsonValue {
+            let now = Utc::now().timestamp();
+            serde_json::json!({
+                "iss": self.issuer,
+                "aud": CLIENT_ID,
+                "sub": sub,
+                "iat": now,
+                "exp": now + 3600,
+                "preferred_username": sub,
+                "groups": groups,
+                "org": "acme",
+            })
+        }
+
+        pub(crate) fn sign(&self, claims: &JsonValue) -> String {
+            self.keys.sign(claims).unwrap()
+        }
+
+        /// Play the user's browser: approve the authorization request and
+        /// return the code that would be sent to the callback
+        pub(crate) fn authorize(&self, authorization_url: &str, mut claims: JsonValue) -> String {
+            let url = reqwest::Url::parse(authorization_url).unwrap();
+            let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+            assert_eq!(params["code_challenge_method"], "S256");
+            assert_eq!(params["client_id"], CLIENT_ID);
+
+            claims["nonce"] = JsonValue::String(params["nonce"].clone());
+            let code = random_token();
+            self.codes
+                .lock()
+                .insert(code.clone(), (params["code_challenge"].clone(), claims));
+            code
+        }
+    }
+
+    async fn discovery(State(idp): State<MockIdp>) -> Json<JsonValue> {
+        Json(serde_json::json!({
+            "issuer": idp.issuer,
+            "authorization_endpoint": format!("{}/authorize", idp.issuer),
+            "token_endpoint": format!("{}/token", idp.issuer),
+            "jwks_uri": format!("{}/jwks", idp.issuer),
+        }))
+    }
+
+    async fn jwks(State(idp): State<MockIdp>) -> Json<JwkSet> {
+        Json(idp.keys.jwks())
+    }
+
+    async fn token(
+        State(idp): State<MockIdp>,
+        Form(form): Form<HashMap<String, String>>,
+    ) -> std::result::Result<Json<JsonValue>, StatusCode> {
+        let (challenge, claims) = idp
+            .codes
+            .lock()
+            .remove(&form["code"])
+            .ok_or(StatusCode::BAD_REQUEST)?;
+        let verifier_hash = URL_SAFE_NO_PAD.encode(Sha256::digest(form["code_verifier"].as_bytes()));
+        if form["grant_type"] != "authorization_code" || verifier_hash != challenge {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+        Ok(Json(serde_json::json!({
+            "access_token": "opaque",
+            "token_type": "Bearer",
+            "id_token": idp.sign(&claims),
+        })))
+    }
+
+    #[tokio::test]
+    async fn test_authorization_code_flow_with_pkce() {
+        let idp = MockIdp::start().await;
+        let provider = OidcProvider::new(idp.config()).unwrap();
+
+        let request = provider.begin_login().await.unwrap();
+        assert!(request.authorization_url.starts_with(&format!("{}/authorize?", idp.issuer)));
+
+        let code = idp.authorize(&request.authorization_url, idp.id_claims("alice", &["engineering"]));
+        let (identity, id_token) = provider.complete_login(&code, &request.state).await.unwrap();
+        assert_eq!(identity.claims.sub, "alice");
+        assert_eq!(identity.claims.role, Role::Developer);
+        assert_eq!(identity.claims.tenant_id, "acme");
+        assert_eq!(identity.username, "alice");
+        assert!(!id_token.is_empty());
+
+        // The state is single-use
+        assert!(provider.complete_login(&code, &request.state).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_code_bound_to_original_verifier() {
+        let idp = MockIdp::start().await;
+        let provider = OidcProvider::new(idp.config()).unwrap();
+
+        let first = provider.begin_login().await.unwrap();
+        let second = provider.begin_login().await.unwrap();
+
+        // A code issued for one login cannot be redeemed with another login's verifier
+        let code = idp.authorize(&first.authorization_url, idp.id_claims("alice", &["engineering"]));
+        assert!(provider.complete_login(&code, &second.state).await.is_err());
+        assert!(provider.complete_login("bogus", "unknown-state").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_bearer_id_token_validation() {
+        let idp = MockIdp::start().await;
+        let provider = OidcProvider::new(idp.config()).unwrap();
+
+        let token = idp.sign(&idp.id_claims("root", &["engineering", "allsource-admins"]));
+        assert!(provider.is_issuer_of(&token));
+        let identity = provider.authenticate_bearer(&token).await.unwrap();
+        assert_eq!(identity.claims.role, Role::Admin);
+        assert_eq!(identity.claims.iss, idp.issuer);
+
+        // Wrong audience, expired and foreign-signed tokens are rejected
+        let mut claims = idp.id_claims("root", &["allsource-admins"]);
+        claims["aud"] = JsonValue::String("someone-else".to_string());
+        assert!(provider.authenticate_bearer(&idp.sign(&claims)).await.is_err());
+
+        let mut claims = idp.id_claims("root", &["allsource-admins"]);
+        claims["exp"] = JsonValue::from(Utc::now().timestamp() - 3600);
+        assert!(provider.authenticate_bearer(&idp.sign(&claims)).await.is_err());
+
+        let forger = JwtKeyManager::generate(JwtKeyConfig {
+            algorithm: JwtAlgorithm::ES256,
+            ..Default::default()
+        })
+        .unwrap();
+        let forged = forger.sign(&idp.id_claims("root", &["allsource-admins"])).unwrap();
+        assert!(provider.authenticate_bearer(&forged).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_provider_key_rotation_refetches_jwks() {
+        let idp = MockIdp::start().await;
+        let provider = OidcProvider::new(idp.config()).unwrap();
+
+        let before = idp.sign(&idp.id_claims("alice", &["engineering"]));
+        provider.authenticate_bearer(&before).await.unwrap();
+
+        // Pretend the cache is old enough to be refreshed, then rotate
+        if let Some((_, fetched_at)) = provider.jwks.write().as_mut() {
+            *fetched_at -= Duration::seconds(JWKS_MIN_REFRESH_SECS);
+        }
+        idp.keys.rotate().await.unwrap();
+        let after = idp.sign(&idp.id_claims("alice", &["engineering"]));
+        assert!(provider.authenticate_bearer(&after).await.is_ok());
+        assert!(provider.authenticate_bearer(&before).await.is_ok());
+    }
+
+    #[test]
+    fn test_claim_mapping() {
+        let provider = OidcProvider::new(OidcConfig {
+            issuer_url: "https://idp.example.com".to_string(),
+            client_id: CLIENT_ID.to_string(),
+            role_claim: "realm_access.roles".to_string(),
+            role_mapping: HashMap::from([("viewer".to_string(), Role::ReadOnly)]),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let claims = serde_json::json!({
+            "sub": "bob",
+            "email": "bob@example.com",
+            "realm_access": { "roles": ["viewer", "unmapped"] },
+        });
+        let identity = provider.map_claims(&claims).unwrap();
+        assert_eq!(identity.claims.role, Role::ReadOnly);
+        assert_eq!(identity.claims.tenant_id, "default");
+        assert_eq!(identity.username, "bob@example.com");
+
+        // Users without a mapped role are rejected unless there is a default
+        let unmapped = serde_json::json!({ "sub": "eve", "realm_access": { "roles": ["unmapped"] } });
+        assert!(provider.map_claims(&unmapped).is_err());
+    }
+}
@@ -0,0 +1,671 @@
+//! Payload Encryption for the Event Path
+//!
+//! Applies field-level encryption to event payloads based on per-event-type
+//! policies:
+//! - Sensitive JSON paths configured statically or via schema annotations
+//! - Encryption before WAL/Parquet persistence
+//! - Permission-gated decryption on read, redaction otherwise
+//! - Data keys wrapped by a KMS master key; each encrypted field carries its
+//!   wrapped data key, so fields stay readable after a restart
+//! - Key rotation by generating a new data key
+//! - Policies set at runtime persisted to a JSON file
+
+use crate::error::{AllSourceError, Result};
+use crate::infrastructure::persistence::object_store::block_on;
+use crate::schema::SchemaRegistry;
+use crate::security::encryption::{
+    decrypt_json_value, encrypt_json_value, DataKeySource, EncryptedData, EncryptionConfig,
+    FieldEncryption,
+};
+use crate::security::kms::{KeyAlgorithm, KeyPurpose, KmsConfig, KmsManager};
+use base64::{engine::general_purpose, Engine as _};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Key marking an encrypted field envelope inside a payload
+pub const ENCRYPTED_FIELD_MARKER: &str = "$encrypted";
+
+/// Placeholder returned to callers not allowed to see sensitive fields
+pub const REDACTED_PLACEHOLDER: &str = "[REDACTED]";
+
+/// JSON Schema keyword used to mark a property as sensitive
+pub const SENSITIVE_SCHEMA_KEYWORD: &str = "x-sensitive";
+
+/// Sensitive field policy for a single event type
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SensitiveFieldPolicy {
+    /// Event type the policy applies to (e.g., "user.created")
+    pub event_type: String,
+
+    /// Dot-separated payload paths to encrypt (e.g., "customer.ssn")
+    pub fields: Vec<String>,
+}
+
+/// Payload encryption configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayloadEncryptionConfig {
+    /// Enable payload encryption on ingest
+    pub enabled: bool,
+
+    /// Statically configured policies
+    pub policies: Vec<SensitiveFieldPolicy>,
+
+    /// Also derive sensitive paths from `x-sensitive` schema annotations
+    pub use_schema_annotations: bool,
+
+    /// Underlying field encryption configuration
+    pub encryption: EncryptionConfig,
+
+    /// KMS wrapping the data keys (None = unwrapped keys held in memory only,
+    /// so encrypted fields cannot be read after a restart)
+    #[serde(default)]
+    pub kms: Option<KmsConfig>,
+
+    /// KMS key (id or alias) wrapping the data keys; created if missing
+    #[serde(default = "default_master_key")]
+    pub master_key: String,
+
+    /// JSON file persisting policies set at runtime. Once it exists it holds
+    /// the complete policy set and `policies` only seeds a fresh file
+    #[serde(default)]
+    pub policy_file: Option<PathBuf>,
+}
+
+fn default_master_key() -> String {
+    "payload-encryption".to_string()
+}
+
+impl Default for PayloadEncryptionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            policies: Vec::new(),
+            use_schema_annotations: true,
+            encryption: EncryptionConfig::default(),
+            kms: None,
+            master_key: default_master_key(),
+            policy_file: None,
+        }
+    }
+}
+
+impl PayloadEncryptionConfig {
+    /// Keep keys and policies under `data_dir` unless configured otherwise
+    ///
+    /// Data keys are wrapped by a local KMS whose key file lives in
+    /// `data_dir/kms` when no KMS is configured.
+    pub fn with_data_dir(mut self, data_dir: &Path) -> Self {
+        let kms = self.kms.take().unwrap_or_default();
+        self.kms = Some(kms.with_default_key_file(data_dir.join("kms").join("payload_keys.json")));
+        if self.policy_file.is_none() {
+            self.policy_file = Some(data_dir.join("encryption_policies.json"));
+        }
+        self
+    }
+}
+
+/// Encrypts and reveals sensitive payload fields
+pub struct PayloadEncryption {
+    config: PayloadEncryptionConfig,
+
+    encryption: Arc<FieldEncryption>,
+
+    // Wraps data keys when a KMS is configured
+    kms: Option<Arc<PayloadKms>>,
+
+    // Event type -> sensitive paths
+    policies: Arc<DashMap<String, Vec<String>>>,
+
+    schema_registry: Option<Arc<SchemaRegistry>>,
+}
+
+/// KMS master key wrapping payload data keys
+struct PayloadKms {
+    manager: KmsManager,
+    master_key_id: String,
+    // Data key id -> wrapped data key (base64)
+    wrapped_keys: DashMap<String, String>,
+}
+
+impl PayloadKms {
+    /// Generate a data key under the master key; returns its id and plaintext
+    fn new_data_key(&self) -> Result<(String, Vec<u8>)> {
+        let (data_key, wrapped) = block_on(self.manager.client().generate_data_key(&self.master_key_id))?;
+        let key_id = uuid::Uuid::new_v4().to_string();
+        self.wrapped_keys
+            .insert(key_id.clone(), general_purpose::STANDARD.encode(wrapped));
+        Ok((key_id, data_key))
+    }
+
+    /// Unwrap a data key recorded in an encrypted field envelope
+    fn unwrap_data_key(&self, key_id: &str, envelope: &JsonValue) -> Result<Vec<u8>> {
+        let missing = || {
+            AllSourceError::ValidationError(format!("Encryption key {} not found and not wrapped", key_id))
+        };
+        let wrapped = envelope["wrapped_key"].as_str().ok_or_else(missing)?;
+        let kms_key_id = envelope["kms_key_id"].as_str().ok_or_else(missing)?;
+
+        let ciphertext = general_purpose::STANDARD
+            .decode(wrapped)
+            .map_err(|e| AllSourceError::ValidationError(format!("Invalid wrapped data key: {}", e)))?;
+        let data_key = block_on(self.manager.client().decrypt(kms_key_id, &ciphertext))?;
+        self.wrapped_keys.insert(key_id.to_string(), wrapped.to_string());
+        Ok(data_key)
+    }
+}
+
+impl PayloadEncryption {
+    /// Create new payload encryption manager
+    ///
+    /// When enabled with a KMS configured, the master key is looked up (or
+    /// created) and the first data key is generated through it.
+    pub fn new(config: PayloadEncryptionConfig) -> Result<Self> {
+        let (encryption, kms) = match config.kms.as_ref().filter(|_| config.enabled) {
+            Some(kms_config) => {
+                let manager = KmsManager::new(kms_config.clone())?;
+                let master_key = block_on(manager.ensure_key(
+                    &config.master_key,
+                    KeyPurpose::DataEncryption,
+                    KeyAlgorithm::Aes256Gcm,
+                ))?;
+                let kms = Arc::new(PayloadKms {
+                    manager,
+                    master_key_id: master_key.key_id,
+                    wrapped_keys: DashMap::new(),
+                });
+                let key_source: DataKeySource = {
+                    let kms = Arc::clone(&kms);
+                    Arc::new(move || kms.new_data_key())
+                };
+                let encryption = FieldEncryption::with_key_source(config.encryption.clone(), key_source)?;
+                (encryption, Some(kms))
+            }
+            None => (FieldEncryption::new(config.encryption.clone())?, None),
+        };
+
+        let policies = match config.policy_file.as_deref().map(load_policies).transpose()? {
+            Some(Some(saved)) => saved,
+            _ => config
+                .policies
+                .iter()
+                .map(|policy| (policy.event_type.clone(), policy.fields.clone()))
+                .collect(),
+        };
+
+        Ok(Self {
+            config,
+            encryption: Arc::new(encryption),
+            kms,
+            policies: Arc::new(policies.into_iter().collect()),
+            schema_registry: None,
+        })
+    }
+
+    /// Resolve annotated sensitive fields from the given schema registry
+    pub fn with_schema_registry(mut self, schema_registry: Arc<SchemaRegistry>) -> Self {
+        self.schema_registry = Some(schema_registry);
+        self
+    }
+
+    /// Check if payload encryption is enabled
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// Set (or replace) the policy for an event type
+    pub fn set_policy(&self, policy: SensitiveFieldPolicy) -> Result<()> {
+        let previous = self.policies.insert(policy.event_type.clone(), policy.fields);
+        self.save_policies().inspect_err(|_| match previous {
+            Some(fields) => {
+                self.policies.insert(policy.event_type, fields);
+            }
+            None => {
+                self.policies.remove(&policy.event_type);
+            }
+        })
+    }
+
+    /// Remove the policy for an event type
+    pub fn remove_policy(&self, event_type: &str) -> Result<bool> {
+        let Some((event_type, fields)) = self.policies.remove(event_type) else {
+            return Ok(false);
+        };
+        self.save_policies().inspect_err(|_| {
+            self.policies.insert(event_type, fields);
+        })?;
+        Ok(true)
+    }
+
+    fn save_policies(&self) -> Result<()> {
+        let Some(path) = &self.config.policy_file else {
+            return Ok(());
+        };
+        let write_err = |e: std::io::Error| {
+            AllSourceError::StorageError(format!(
+                "Failed to write encryption policies to {}: {}",
+                path.display(),
+                e
+            ))
+        };
+
+        let policies: HashMap<String, Vec<String>> = self
+            .policies
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(write_err)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(&policies)?).map_err(write_err)?;
+        std::fs::rename(&tmp, path).map_err(write_err)
+    }
+
+    /// List all statically registered policies
+    pub fn list_policies(&self) -> Vec<SensitiveFieldPolicy> {
+        self.policies
+            .iter()
+            .map(|entry| SensitiveFieldPolicy {
+                event_type: entry.key().clone(),
+                fields: entry.value().clone(),
+            })
+            .collect()
+    }
+
+    /// Get all sensitive paths for an event type (policies + schema annotations)
+    pub fn sensitive_fields(&self, event_type: &str) -> Vec<String> {
+        let mut fields = self
+            .policies
+            .get(event_type)
+            .map(|f| f.clone())
+            .unwrap_or_default();
+
+        if self.config.use_schema_annotations {
+            if let Some(ref registry) = self.schema_registry {
+                if let Ok(schema) = registry.get_schema(event_type, None) {
+                    for path in sensitive_paths_from_schema(&schema.schema) {
+                        if !fields.contains(&path) {
+                            fields.push(path);
+                        }
+                    }
+                }
+            }
+        }
+
+        fields
+    }
+
+    /// Encrypt the sensitive fields of a payload in place
+    ///
+    /// Returns the number of fields encrypted. Fields already encrypted are
+    /// left untouched, so re-applying the policy is a no-op.
+    pub fn encrypt_payload(&self, event_type: &str, payload: &mut JsonValue) -> Result<usize> {
+        if !self.config.enabled {
+            return Ok(0);
+        }
+
+        let mut encrypted = 0;
+        for path in self.sensitive_fields(event_type) {
+            let segments: Vec<&str> = path.split('.').filter(|s| !s.is_empty()).collect();
+            encrypted += self.encrypt_path(payload, &segments, &path)?;
+        }
+
+        Ok(encrypted)
+    }
+
+    fn encrypt_path(&self, value: &mut JsonValue, segments: &[&str], path: &str) -> Result<usize> {
+        match value {
+            // Apply the remaining path to every element of an array
+            JsonValue::Array(items) => {
+                let mut count = 0;
+                for item in items {
+                    count += self.encrypt_path(item, segments, path)?;
+                }
+                Ok(count)
+            }
+            JsonValue::Object(map) => {
+                let Some((head, rest)) = segments.split_first() else {
+                    return Ok(0);
+                };
+
+                let Some(field) = map.get_mut(*head) else {
+                    return Ok(0);
+                };
+
+                if !rest.is_empty() {
+                    return self.encrypt_path(field, rest, path);
+                }
+
+                if field.is_null() || is_encrypted_envelope(field) {
+                    return Ok(0);
+                }
+
+                let encrypted = encrypt_json_value(field, &self.encryption, path)?;
+                let mut envelope = serde_json::to_value(&encrypted)?;
+                if let Some(kms) = &self.kms {
+                    if let Some(wrapped) = kms.wrapped_keys.get(&encrypted.key_id) {
+                        envelope["wrapped_key"] = JsonValue::String(wrapped.clone());
+                        envelope["kms_key_id"] = JsonValue::String(kms.master_key_id.clone());
+                    }
+                }
+                *field = serde_json::json!({ ENCRYPTED_FIELD_MARKER: envelope });
+                Ok(1)
+            }
+            _ => Ok(0),
+        }
+    }
+
+    /// Reveal encrypted fields in place
+    ///
+    /// Authorized callers get the decrypted value back; everyone else sees
+    /// `REDACTED_PLACEHOLDER`. Works on any JSON shape (payloads, entity
+    /// state, snapshots) since envelopes are self-describing.
+    pub fn reveal(&self, value: &mut JsonValue, authorized: bool) -> Result<()> {
+        if is_encrypted_envelope(value) {
+            *value = if authorized {
+                let envelope = &value[ENCRYPTED_FIELD_MARKER];
+                let encrypted: EncryptedData = serde_json::from_value(envelope.clone())?;
+                self.load_data_key(&encrypted.key_id, envelope)?;
+                decrypt_json_value(&encrypted, &self.encryption)?
+            } else {
+                JsonValue::String(REDACTED_PLACEHOLDER.to_string())
+            };
+            return Ok(());
+        }
+
+        match value {
+            JsonValue::Array(items) => {
+                for item in items {
+                    self.reveal(item, authorized)?;
+                }
+            }
+            JsonValue::Object(map) => {
+                for (_, field) in map.iter_mut() {
+                    self.reveal(field, authorized)?;
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Make sure the data key of an envelope is loaded, unwrapping it if needed
+    fn load_data_key(&self, key_id: &str, envelope: &JsonValue) -> Result<()> {
+        if self.encryption.has_key(key_id) {
+            return Ok(());
+        }
+        let Some(kms) = &self.kms else {
+            // decrypt_json_value reports the missing key
+            return Ok(());
+        };
+        let data_key = kms.unwrap_data_key(key_id, envelope)?;
+        self.encryption.add_key(key_id.to_string(), data_key, false)
+    }
+
+    /// Rotate the data encryption key
+    ///
+    /// Previously encrypted fields stay readable since `EncryptedData` keeps
+    /// the key id it was encrypted with (and, with a KMS, its wrapped key).
+    /// With a KMS the new key is generated and wrapped by it.
+    pub fn rotate_keys(&self) -> Result<()> {
+        self.encryption.rotate_keys()
+    }
+
+    /// Get the underlying field encryption manager
+    pub fn field_encryption(&self) -> Arc<FieldEncryption> {
+        Arc::clone(&self.encryption)
+    }
+}
+
+/// Read persisted policies (None if the file does not exist yet)
+fn load_policies(path: &Path) -> Result<Option<HashMap<String, Vec<String>>>> {
+    match std::fs::read(path) {
+        Ok(data) => Ok(Some(serde_json::from_slice(&data)?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(AllSourceError::StorageError(format!(
+            "Failed to read encryption policies from {}: {}",
+            path.display(),
+            e
+        ))),
+    }
+}
+
+/// Check if a JSON value is an encrypted field envelope
+pub fn is_encrypted_envelope(value: &JsonValue) -> bool {
+    match value {
+        JsonValue::Object(map) => map.len() == 1 && map.contains_key(ENCRYPTED_FIELD_MARKER),
+        _ => false,
+    }
+}
+
+/// Extract dot-separated paths of properties annotated with `x-sensitive: true`
+pub fn sensitive_paths_from_schema(schema: &JsonValue) -> Vec<String> {
+    let mut paths = Vec::new();
+    collect_sensitive_paths(schema, "", &mut paths);
+    paths
+}
+
+fn collect_sensitive_paths(schema: &JsonValue, prefix: &str, paths: &mut Vec<String>) {
+    // Array items share the path of the array itself
+    if let Some(items) = schema.get("items") {
+        collect_sensitive_paths(items, prefix, paths);
+    }
+
+    let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) else {
+        return;
+    };
+
+    for (name, property) in properties {
+        let path = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{}.{}", prefix, name)
+        };
+
+        let sensitive = property
+            .get(SENSITIVE_SCHEMA_KEYWORD)
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        if sensitive {
+            paths.push(path);
+        } else {
+            collect_sensitive_paths(property, &path, paths);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::SchemaRegistryConfig;
+    use serde_json::json;
+
+    fn enabled_config(policies: Vec<SensitiveFieldPolicy>) -> PayloadEncryptionConfig {
+        PayloadEncryptionConfig {
+            enabled: true,
+            policies,
+            ..Default::default()
+        }
+    }
+
+    fn user_policy() -> SensitiveFieldPolicy {
+        SensitiveFieldPolicy {
+            event_type: "user.created".to_string(),
+            fields: vec!["ssn".to_string(), "address.street".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_encrypt_and_reveal_payload() {
+        let encryption = PayloadEncryption::new(enabled_config(vec![user_policy()])).unwrap();
+        let original = json!({
+            "name": "Alice",
+            "ssn": "123-45-6789",
+            "address": { "street": "1 Main St", "city": "Springfield" }
+        });
+
+        let mut payload = original.clone();
+        let count = encryption.encrypt_payload("user.created", &mut payload).unwrap();
+        assert_eq!(count, 2);
+        assert!(is_encrypted_envelope(&payload["ssn"]));
+        assert!(is_encrypted_envelope(&payload["address"]["street"]));
+        assert_eq!(payload["name"], "Alice");
+        assert_eq!(payload["address"]["city"], "Springfield");
+
+        let mut revealed = payload.clone();
+        encryption.reveal(&mut revealed, true).unwrap();
+        assert_eq!(revealed, original);
+
+        let mut redacted = payload.clone();
+        encryption.reveal(&mut redacted, false).unwrap();
+        assert_eq!(redacted["ssn"], REDACTED_PLACEHOLDER);
+        assert_eq!(redacted["address"]["street"], REDACTED_PLACEHOLDER);
+        assert_eq!(redacted["name"], "Alice");
+    }
+
+    #[test]
+    fn test_disabled_is_noop() {
+        let mut config = enabled_config(vec![user_policy()]);
+        config.enabled = false;
+        let encryption = PayloadEncryption::new(config).unwrap();
+
+        let mut payload = json!({ "ssn": "123-45-6789" });
+        assert_eq!(encryption.encrypt_payload("user.created", &mut payload).unwrap(), 0);
+        assert_eq!(payload["ssn"], "123-45-6789");
+    }
+
+    #[test]
+    fn test_other_event_types_untouched() {
+        let encryption = PayloadEncryption::new(enabled_config(vec![user_policy()])).unwrap();
+
+        let mut payload = json!({ "ssn": "123-45-6789" });
+        assert_eq!(encryption.encrypt_payload("order.placed", &mut payload).unwrap(), 0);
+        assert_eq!(payload["ssn"], "123-45-6789");
+    }
+
+    #[test]
+    fn test_array_paths() {
+        let encryption = PayloadEncryption::new(enabled_config(vec![SensitiveFieldPolicy {
+            event_type: "order.placed".to_string(),
+            fields: vec!["cards.number".to_string()],
+        }]))
+        .unwrap();
+
+        let mut payload = json!({ "cards": [{ "number": "4111" }, { "number": "5500" }] });
+        assert_eq!(encryption.encrypt_payload("order.placed", &mut payload).unwrap(), 2);
+
+        // Encrypting twice must not double-wrap
+        assert_eq!(encryption.encrypt_payload("order.placed", &mut payload).unwrap(), 0);
+
+        encryption.reveal(&mut payload, true).unwrap();
+        assert_eq!(payload["cards"][1]["number"], "5500");
+    }
+
+    #[test]
+    fn test_reveal_after_key_rotation() {
+        let encryption = PayloadEncryption::new(enabled_config(vec![user_policy()])).unwrap();
+
+        let mut before = json!({ "ssn": "111-11-1111" });
+        encryption.encrypt_payload("user.created", &mut before).unwrap();
+
+        encryption.rotate_keys().unwrap();
+
+        let mut after = json!({ "ssn": "222-22-2222" });
+        encryption.encrypt_payload("user.created", &mut after).unwrap();
+        assert_ne!(
+            before["ssn"][ENCRYPTED_FIELD_MARKER]["key_id"],
+            after["ssn"][ENCRYPTED_FIELD_MARKER]["key_id"]
+        );
+
+        encryption.reveal(&mut before, true).unwrap();
+        encryption.reveal(&mut after, true).unwrap();
+        assert_eq!(before["ssn"], "111-11-1111");
+        assert_eq!(after["ssn"], "222-22-2222");
+    }
+
+    #[test]
+    fn test_wrapped_keys_survive_restart() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = enabled_config(vec![user_policy()]).with_data_dir(dir.path());
+
+        let mut before = json!({ "ssn": "111-11-1111" });
+        let mut after = json!({ "ssn": "222-22-2222" });
+        {
+            let encryption = PayloadEncryption::new(config.clone()).unwrap();
+            encryption.encrypt_payload("user.created", &mut before).unwrap();
+            // Rotating through the field encryption manager wraps the new key too
+            encryption.field_encryption().rotate_keys().unwrap();
+            encryption.encrypt_payload("user.created", &mut after).unwrap();
+        }
+        assert!(before["ssn"][ENCRYPTED_FIELD_MARKER]["wrapped_key"].is_string());
+        assert!(after["ssn"][ENCRYPTED_FIELD_MARKER]["wrapped_key"].is_string());
+
+        // A new instance unwraps the data keys through the persisted master key
+        let restarted = PayloadEncryption::new(config).unwrap();
+        restarted.reveal(&mut before, true).unwrap();
+        restarted.reveal(&mut after, true).unwrap();
+        assert_eq!(before["ssn"], "111-11-1111");
+        assert_eq!(after["ssn"], "222-22-2222");
+    }
+
+    #[test]
+    fn test_runtime_policies_are_persisted() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = enabled_config(vec![user_policy()]).with_data_dir(dir.path());
+
+        let encryption = PayloadEncryption::new(config.clone()).unwrap();
+        encryption
+            .set_policy(SensitiveFieldPolicy {
+                event_type: "order.placed".to_string(),
+                fields: vec!["card".to_string()],
+            })
+            .unwrap();
+        assert!(encryption.remove_policy("user.created").unwrap());
+        drop(encryption);
+
+        let restarted = PayloadEncryption::new(config).unwrap();
+        assert_eq!(restarted.sensitive_fields("order.placed"), vec!["card"]);
+        assert!(restarted.sensitive_fields("user.created").is_empty());
+    }
+
+    #[test]
+    fn test_schema_annotations() {
+        let registry = Arc::new(SchemaRegistry::new(SchemaRegistryConfig::default()));
+        registry
+            .register_schema(
+                "payment.made".to_string(),
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "amount": { "type": "number" },
+                        "card": {
+                            "type": "object",
+                            "properties": {
+                                "number": { "type": "string", "x-sensitive": true },
+                                "brand": { "type": "string" }
+                            }
+                        }
+                    }
+                }),
+                None,
+                None,
+            )
+            .unwrap();
+
+        let encryption = PayloadEncryption::new(enabled_config(Vec::new()))
+            .unwrap()
+            .with_schema_registry(registry);
+
+        assert_eq!(encryption.sensitive_fields("payment.made"), vec!["card.number"]);
+
+        let mut payload = json!({ "amount": 10, "card": { "number": "4111", "brand": "visa" } });
+        assert_eq!(encryption.encrypt_payload("payment.made", &mut payload).unwrap(), 1);
+        assert!(is_encrypted_envelope(&payload["card"]["number"]));
+    }
+}
@@ -0,0 +1,663 @@
+//! Attribute-Based Access Policies
+//!
+//! Declarative allow/deny rules evaluated on every authenticated request, on
+//! top of role permissions. Rules are stored per tenant. A rule matches when
+//! every one of its non-empty conditions matches:
+//! - **subject**: role, user id
+//! - **resource**: event type, entity id, projection, schema subject
+//!   (`*` wildcards, e.g. `pii.*`, `user-*`)
+//! - **action**: read, write, stream, admin (`ApiOperation`)
+//! - **context**: client network, UTC time window and weekdays
+//!
+//! Enabled rules are evaluated by descending priority. The first matching
+//! `allow` or `deny` rule decides; matching `warn` rules are reported and
+//! evaluation continues. Without a decisive match the request is allowed and
+//! role permissions alone apply, as in the control-plane's policy engine.
+//!
+//! A condition on an attribute the request does not carry fails closed: it
+//! matches `deny` rules, since an unfiltered query may return exactly what
+//! the rule protects, and never matches `allow` or `warn` rules. Admin
+//! operations are the exception, so a deny rule cannot lock out the
+//! administrators who manage it.
+
+use chrono::{DateTime, Datelike, NaiveTime, Utc, Weekday};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+
+use crate::auth::{ApiOperation, Claims, Role};
+use crate::error::{AllSourceError, Result};
+use crate::infrastructure::security::IpNetwork;
+
+/// What a matching rule does
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyEffect {
+    Allow,
+    Deny,
+    /// Reported, but does not decide
+    Warn,
+}
+
+/// Subject conditions
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SubjectMatch {
+    pub roles: Vec<Role>,
+    /// User or API key ids (wildcards allowed)
+    pub user_ids: Vec<String>,
+}
+
+/// Resource conditions (wildcards allowed)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ResourceMatch {
+    pub event_types: Vec<String>,
+    pub entity_ids: Vec<String>,
+    pub projections: Vec<String>,
+    pub schema_subjects: Vec<String>,
+}
+
+/// Request context conditions
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ContextMatch {
+    /// Client networks
+    pub ip_cidrs: Vec<IpNetwork>,
+    /// UTC time of day
+    pub time_window: Option<TimeWindow>,
+    /// UTC days of the week (`Mon`, `Tue`, ...)
+    pub weekdays: Vec<Weekday>,
+}
+
+/// Time of day range `[start, end)` in UTC; wraps midnight when `end < start`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct TimeWindow {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl TimeWindow {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// A single access rule
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PolicyRule {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub effect: PolicyEffect,
+    /// Higher priority rules are evaluated first
+    #[serde(default)]
+    pub priority: i32,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub subject: SubjectMatch,
+    #[serde(default)]
+    pub resource: ResourceMatch,
+    /// Operations the rule applies to (empty = all)
+    #[serde(default)]
+    pub actions: Vec<ApiOperation>,
+    #[serde(default)]
+    pub context: ContextMatch,
+}
+
+impl PolicyRule {
+    /// Validate the rule before storing it
+    pub fn validate(&self) -> Result<()> {
+        if self.id.trim().is_empty() {
+            return Err(AllSourceError::ValidationError(
+                "Policy id cannot be empty".to_string(),
+            ));
+        }
+        if self.name.trim().is_empty() {
+            return Err(AllSourceError::ValidationError(
+                "Policy name cannot be empty".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// The first condition the request fails, or `None` if the rule matches
+    fn mismatch(&self, request: &PolicyRequest) -> Option<String> {
+        // Deny rules also cover requests missing the attribute they test
+        let missing_matches = self.effect == PolicyEffect::Deny && request.action != ApiOperation::Admin;
+
+        if !self.subject.roles.is_empty() && !self.subject.roles.contains(&request.role) {
+            return Some(format!("role {:?} not matched", request.role));
+        }
+        if let Some(mismatch) =
+            pattern_mismatch("user id", &self.subject.user_ids, Some(&request.user_id), false)
+        {
+            return Some(mismatch);
+        }
+        if !self.actions.is_empty() && !self.actions.contains(&request.action) {
+            return Some(format!("action {} not matched", request.action.as_str()));
+        }
+
+        let resource = &request.resource;
+        let resource_checks = [
+            ("event type", &self.resource.event_types, resource.event_type.as_deref()),
+            ("entity id", &self.resource.entity_ids, resource.entity_id.as_deref()),
+            ("projection", &self.resource.projections, resource.projection.as_deref()),
+            ("schema subject", &self.resource.schema_subjects, resource.schema_subject.as_deref()),
+        ];
+        for (attribute, patterns, value) in resource_checks {
+            if let Some(mismatch) = pattern_mismatch(attribute, patterns, value, missing_matches) {
+                return Some(mismatch);
+            }
+        }
+
+        if !self.context.ip_cidrs.is_empty() {
+            match request.ip {
+                None if missing_matches => {}
+                None => return Some("client address unknown".to_string()),
+                Some(ip) if !self.context.ip_cidrs.iter().any(|net| net.contains(&ip)) => {
+                    return Some(format!("client address {} not matched", ip));
+                }
+                Some(_) => {}
+            }
+        }
+        if let Some(window) = &self.context.time_window {
+            if !window.contains(request.time.time()) {
+                return Some("outside time window".to_string());
+            }
+        }
+        if !self.context.weekdays.is_empty() && !self.context.weekdays.contains(&request.time.weekday()) {
+            return Some(format!("weekday {} not matched", request.time.weekday()));
+        }
+
+        None
+    }
+}
+
+fn pattern_mismatch(
+    attribute: &str,
+    patterns: &[String],
+    value: Option<&str>,
+    missing_matches: bool,
+) -> Option<String> {
+    if patterns.is_empty() {
+        return None;
+    }
+    match value {
+        None if missing_matches => None,
+        None => Some(format!("request has no {}", attribute)),
+        Some(value) if patterns.iter().any(|p| wildcard_match(p, value)) => None,
+        Some(value) => Some(format!("{} '{}' not matched", attribute, value)),
+    }
+}
+
+/// Match `value` against a pattern where `*` matches any run of characters
+pub fn wildcard_match(pattern: &str, value: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == value;
+    }
+
+    let Some(mut rest) = value.strip_prefix(parts[0]) else {
+        return false;
+    };
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(parts[parts.len() - 1])
+}
+
+/// Resource attributes of a request
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct PolicyResource {
+    pub event_type: Option<String>,
+    pub entity_id: Option<String>,
+    pub projection: Option<String>,
+    pub schema_subject: Option<String>,
+}
+
+/// Attributes a decision is made on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyRequest {
+    pub tenant_id: String,
+    pub user_id: String,
+    pub role: Role,
+    pub action: ApiOperation,
+    #[serde(default)]
+    pub resource: PolicyResource,
+    #[serde(default)]
+    pub ip: Option<IpAddr>,
+    #[serde(default = "Utc::now")]
+    pub time: DateTime<Utc>,
+}
+
+impl PolicyRequest {
+    /// Request attributes for an authenticated subject, at the current time
+    pub fn new(claims: &Claims, action: ApiOperation) -> Self {
+        Self {
+            tenant_id: claims.tenant_id.clone(),
+            user_id: claims.sub.clone(),
+            role: claims.role.clone(),
+            action,
+            resource: PolicyResource::default(),
+            ip: None,
+            time: Utc::now(),
+        }
+    }
+}
+
+/// How one rule fared in an explained evaluation
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleEvaluation {
+    pub rule_id: String,
+    pub effect: PolicyEffect,
+    pub priority: i32,
+    pub matched: bool,
+    pub detail: String,
+}
+
+/// Outcome of evaluating a tenant's rules
+#[derive(Debug, Clone, Serialize)]
+pub struct PolicyDecision {
+    pub allowed: bool,
+    /// Rule that decided (None = no decisive match)
+    pub matched_rule: Option<String>,
+    /// Matching `warn` rules evaluated before the decision
+    pub warnings: Vec<String>,
+    pub reason: String,
+    /// Every rule in evaluation order (explain only)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub trace: Vec<RuleEvaluation>,
+}
+
+/// Per-tenant policy store and evaluator
+pub struct PolicyEngine {
+    /// Rules by tenant, highest priority first
+    rules: RwLock<HashMap<String, Vec<PolicyRule>>>,
+    /// JSON file the rules are persisted to (None = in-memory only)
+    path: Option<PathBuf>,
+}
+
+impl PolicyEngine {
+    /// Create an in-memory policy engine
+    pub fn new() -> Self {
+        Self {
+            rules: RwLock::new(HashMap::new()),
+            path: None,
+        }
+    }
+
+    /// Open a policy engine persisted to `path`, loading existing rules
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut rules: HashMap<String, Vec<PolicyRule>> = match std::fs::read(&path) {
+            Ok(data) => serde_json::from_slice(&data)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => {
+                return Err(AllSourceError::StorageError(format!(
+                    "Failed to read policies from {}: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+        for tenant_rules in rules.values_mut() {
+            sort_rules(tenant_rules);
+        }
+
+        Ok(Self {
+            rules: RwLock::new(rules),
+            path: Some(path),
+        })
+    }
+
+    /// Whether a tenant has any rules
+    pub fn has_rules(&self, tenant_id: &str) -> bool {
+        self.rules.read().get(tenant_id).is_some_and(|rules| !rules.is_empty())
+    }
+
+    /// Rules of a tenant, in evaluation order
+    pub fn list_rules(&self, tenant_id: &str) -> Vec<PolicyRule> {
+        self.rules.read().get(tenant_id).cloned().unwrap_or_default()
+    }
+
+    /// Get a rule by id
+    pub fn get_rule(&self, tenant_id: &str, rule_id: &str) -> Option<PolicyRule> {
+        self.rules
+            .read()
+            .get(tenant_id)?
+            .iter()
+            .find(|rule| rule.id == rule_id)
+            .cloned()
+    }
+
+    /// Add a rule, replacing any rule with the same id
+    pub fn put_rule(&self, tenant_id: &str, rule: PolicyRule) -> Result<()> {
+        rule.validate()?;
+
+        let mut rules = self.rules.write();
+        let tenant_rules = rules.entry(tenant_id.to_string()).or_default();
+        tenant_rules.retain(|existing| existing.id != rule.id);
+        tenant_rules.push(rule);
+        sort_rules(tenant_rules);
+        self.save(&rules)
+    }
+
+    /// Remove a rule; returns whether it existed
+    pub fn remove_rule(&self, tenant_id: &str, rule_id: &str) -> Result<bool> {
+        let mut rules = self.rules.write();
+        let Some(tenant_rules) = rules.get_mut(tenant_id) else {
+            return Ok(false);
+        };
+        let before = tenant_rules.len();
+        tenant_rules.retain(|rule| rule.id != rule_id);
+        if tenant_rules.len() == before {
+            return Ok(false);
+        }
+        if tenant_rules.is_empty() {
+            rules.remove(tenant_id);
+        }
+        self.save(&rules)?;
+        Ok(true)
+    }
+
+    /// Decide a request against its tenant's rules
+    pub fn evaluate(&self, request: &PolicyRequest) -> PolicyDecision {
+        let rules = self.rules.read();
+        let tenant_rules = rules.get(&request.tenant_id).map(Vec::as_slice).unwrap_or_default();
+        decide(tenant_rules, request, false)
+    }
+
+    /// Decide a request and trace every rule of its tenant
+    pub fn explain(&self, request: &PolicyRequest) -> PolicyDecision {
+        let rules = self.rules.read();
+        let tenant_rules = rules.get(&request.tenant_id).map(Vec::as_slice).unwrap_or_default();
+        decide(tenant_rules, request, true)
+    }
+
+    /// Dry run: decide and trace a request against candidate rules
+    pub fn explain_with(rules: &[PolicyRule], request: &PolicyRequest) -> Result<PolicyDecision> {
+        for rule in rules {
+            rule.validate()?;
+        }
+        let mut rules = rules.to_vec();
+        sort_rules(&mut rules);
+        Ok(decide(&rules, request, true))
+    }
+
+    fn save(&self, rules: &HashMap<String, Vec<PolicyRule>>) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let write_err = |e: std::io::Error| {
+            AllSourceError::StorageError(format!("Failed to write policies to {}: {}", path.display(), e))
+        };
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(write_err)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(rules)?).map_err(write_err)?;
+        std::fs::rename(&tmp, path).map_err(write_err)
+    }
+}
+
+impl Default for PolicyEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Highest priority first; equal priorities keep insertion order
+fn sort_rules(rules: &mut [PolicyRule]) {
+    rules.sort_by_key(|rule| std::cmp::Reverse(rule.priority));
+}
+
+fn decide(rules: &[PolicyRule], request: &PolicyRequest, explain: bool) -> PolicyDecision {
+    let mut decision = PolicyDecision {
+        allowed: true,
+        matched_rule: None,
+        warnings: Vec::new(),
+        reason: "No matching policy; role permissions apply".to_string(),
+        trace: Vec::new(),
+    };
+
+    for rule in rules {
+        let mismatch = if rule.enabled {
+            rule.mismatch(request)
+        } else {
+            Some("disabled".to_string())
+        };
+
+        if explain {
+            let detail = match (&mismatch, &decision.matched_rule) {
+                (Some(mismatch), _) => mismatch.clone(),
+                (None, Some(decided_by)) => format!("matched, but already decided by '{}'", decided_by),
+                (None, None) => "matched".to_string(),
+            };
+            decision.trace.push(RuleEvaluation {
+                rule_id: rule.id.clone(),
+                effect: rule.effect,
+                priority: rule.priority,
+                matched: mismatch.is_none(),
+                detail,
+            });
+        }
+
+        if mismatch.is_some() || decision.matched_rule.is_some() {
+            continue;
+        }
+        match rule.effect {
+            PolicyEffect::Warn => decision.warnings.push(rule.id.clone()),
+            effect => {
+                decision.allowed = effect == PolicyEffect::Allow;
+                decision.matched_rule = Some(rule.id.clone());
+                decision.reason = format!(
+                    "{} by access policy '{}'",
+                    if decision.allowed { "Allowed" } else { "Denied" },
+                    rule.name
+                );
+                if !explain {
+                    break;
+                }
+            }
+        }
+    }
+
+    decision
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn rule(id: &str, effect: PolicyEffect, priority: i32) -> PolicyRule {
+        PolicyRule {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: String::new(),
+            effect,
+            priority,
+            enabled: true,
+            subject: SubjectMatch::default(),
+            resource: ResourceMatch::default(),
+            actions: Vec::new(),
+            context: ContextMatch::default(),
+        }
+    }
+
+    fn request(event_type: Option<&str>) -> PolicyRequest {
+        PolicyRequest {
+            tenant_id: "acme".to_string(),
+            user_id: "svc-1".to_string(),
+            role: Role::ServiceAccount,
+            action: ApiOperation::Write,
+            resource: PolicyResource {
+                event_type: event_type.map(str::to_string),
+                ..Default::default()
+            },
+            ip: Some("10.0.0.5".parse().unwrap()),
+            // A Wednesday
+            time: Utc.with_ymd_and_hms(2026, 10, 14, 12, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("pii.*", "pii.ssn_updated"));
+        assert!(!wildcard_match("pii.*", "order.placed"));
+        assert!(wildcard_match("*", "anything"));
+        assert!(wildcard_match("user-*-eu", "user-42-eu"));
+        assert!(!wildcard_match("user-*-eu", "user-42-us"));
+        assert!(wildcard_match("exact", "exact"));
+        assert!(!wildcard_match("a*a", "a"));
+    }
+
+    #[test]
+    fn test_priority_and_first_decisive_match() {
+        let engine = PolicyEngine::new();
+        let mut deny_pii = rule("deny-pii", PolicyEffect::Deny, 10);
+        deny_pii.resource.event_types = vec!["pii.*".to_string()];
+        deny_pii.actions = vec![ApiOperation::Write];
+        let mut allow_admins = rule("allow-admins", PolicyEffect::Allow, 100);
+        allow_admins.subject.roles = vec![Role::Admin];
+        engine.put_rule("acme", deny_pii).unwrap();
+        engine.put_rule("acme", allow_admins).unwrap();
+
+        let decision = engine.evaluate(&request(Some("pii.ssn_updated")));
+        assert!(!decision.allowed);
+        assert_eq!(decision.matched_rule.as_deref(), Some("deny-pii"));
+
+        let mut admin = request(Some("pii.ssn_updated"));
+        admin.role = Role::Admin;
+        assert!(engine.evaluate(&admin).allowed);
+
+        // A write without an event type may be a PII write, so it is denied;
+        // other tenants are unaffected
+        let decision = engine.evaluate(&request(None));
+        assert!(!decision.allowed);
+        assert_eq!(decision.matched_rule.as_deref(), Some("deny-pii"));
+        let mut other = request(Some("pii.ssn_updated"));
+        other.tenant_id = "globex".to_string();
+        assert!(engine.evaluate(&other).allowed);
+    }
+
+    #[test]
+    fn test_missing_attributes_fail_closed() {
+        let mut deny_pii = rule("deny-pii", PolicyEffect::Deny, 10);
+        deny_pii.resource.event_types = vec!["pii.*".to_string()];
+        deny_pii.resource.entity_ids = vec!["user-*".to_string()];
+        let mut allow_orders = rule("allow-orders", PolicyEffect::Allow, 20);
+        allow_orders.resource.event_types = vec!["order.*".to_string()];
+        let rules = [deny_pii, allow_orders];
+
+        // An unfiltered read matches the deny rule but not the allow rule
+        let mut unfiltered = request(None);
+        unfiltered.action = ApiOperation::Read;
+        let decision = PolicyEngine::explain_with(&rules, &unfiltered).unwrap();
+        assert!(!decision.allowed);
+        assert_eq!(decision.matched_rule.as_deref(), Some("deny-pii"));
+
+        // A known attribute outside the rule still escapes it
+        let decision = PolicyEngine::explain_with(&rules, &request(Some("audit.viewed"))).unwrap();
+        assert!(decision.allowed);
+        assert!(decision.matched_rule.is_none());
+
+        // Admin operations are never denied for a missing attribute
+        let mut admin = request(None);
+        admin.action = ApiOperation::Admin;
+        assert!(PolicyEngine::explain_with(&rules, &admin).unwrap().allowed);
+    }
+
+    #[test]
+    fn test_warn_rules_and_context_conditions() {
+        let engine = PolicyEngine::new();
+        let mut warn = rule("warn-writes", PolicyEffect::Warn, 50);
+        warn.actions = vec![ApiOperation::Write];
+        let mut office_hours = rule("office-hours", PolicyEffect::Deny, 10);
+        office_hours.context.time_window = Some(TimeWindow {
+            start: NaiveTime::from_hms_opt(18, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
+        });
+        let mut outside_network = rule("outside-network", PolicyEffect::Deny, 5);
+        outside_network.context.ip_cidrs = vec!["192.168.0.0/16".parse().unwrap()];
+        outside_network.context.weekdays = vec![Weekday::Wed];
+        for r in [warn, office_hours, outside_network] {
+            engine.put_rule("acme", r).unwrap();
+        }
+
+        // Noon from 10.0.0.5: only the warning matches
+        let decision = engine.evaluate(&request(Some("order.placed")));
+        assert!(decision.allowed);
+        assert_eq!(decision.warnings, vec!["warn-writes".to_string()]);
+
+        let mut night = request(Some("order.placed"));
+        night.time = Utc.with_ymd_and_hms(2026, 10, 14, 23, 30, 0).unwrap();
+        assert_eq!(engine.evaluate(&night).matched_rule.as_deref(), Some("office-hours"));
+
+        let mut lan = request(Some("order.placed"));
+        lan.ip = Some("192.168.1.1".parse().unwrap());
+        assert_eq!(engine.evaluate(&lan).matched_rule.as_deref(), Some("outside-network"));
+        lan.time = Utc.with_ymd_and_hms(2026, 10, 15, 12, 0, 0).unwrap();
+        assert!(engine.evaluate(&lan).allowed);
+    }
+
+    #[test]
+    fn test_explain_traces_every_rule() {
+        let mut disabled = rule("disabled", PolicyEffect::Deny, 100);
+        disabled.enabled = false;
+        let deny_all = rule("deny-all", PolicyEffect::Deny, 10);
+        let mut allow_orders = rule("allow-orders", PolicyEffect::Allow, 1);
+        allow_orders.resource.event_types = vec!["order.*".to_string()];
+
+        let decision =
+            PolicyEngine::explain_with(&[allow_orders, deny_all, disabled], &request(Some("order.placed")))
+                .unwrap();
+        assert!(!decision.allowed);
+        let trace: Vec<(&str, bool)> = decision
+            .trace
+            .iter()
+            .map(|t| (t.rule_id.as_str(), t.matched))
+            .collect();
+        assert_eq!(trace, vec![("disabled", false), ("deny-all", true), ("allow-orders", true)]);
+        assert!(decision.trace[2].detail.contains("already decided"));
+
+        assert!(PolicyEngine::explain_with(&[rule("", PolicyEffect::Deny, 0)], &request(None)).is_err());
+    }
+
+    #[test]
+    fn test_rules_persist_across_restarts() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("policies.json");
+
+        let engine = PolicyEngine::open(&path).unwrap();
+        engine.put_rule("acme", rule("low", PolicyEffect::Allow, 1)).unwrap();
+        engine.put_rule("acme", rule("high", PolicyEffect::Deny, 9)).unwrap();
+        engine.put_rule("globex", rule("other", PolicyEffect::Deny, 0)).unwrap();
+        assert!(engine.remove_rule("globex", "other").unwrap());
+        assert!(!engine.remove_rule("globex", "other").unwrap());
+
+        let reopened = PolicyEngine::open(&path).unwrap();
+        let ids: Vec<String> = reopened.list_rules("acme").into_iter().map(|r| r.id).collect();
+        assert_eq!(ids, vec!["high".to_string(), "low".to_string()]);
+        assert!(!reopened.has_rules("globex"));
+        assert_eq!(reopened.get_rule("acme", "low").unwrap().effect, PolicyEffect::Allow);
+    }
+}
@@ -0,0 +1,1076 @@
+//! HashiCorp Vault Transit KMS Client
+//!
+//! Implements `KmsClient` on top of Vault's Transit secrets engine:
+//! - Token or AppRole authentication (with automatic re-login)
+//! - Retries with exponential backoff on transient failures
+//! - Key metadata caching
+//!
+//! Transit has no notion of key purpose or a disabled state, so both are
+//! kept as custom metadata on a KV v2 entry per key
+//! (`<metadata_mount>/metadata/<metadata_prefix>/<key>`) and survive
+//! restarts of the client.
+//!
+//! Provider-specific settings are read from `KmsConfig::config`:
+//! `address`, `token`, `role_id`, `secret_id`, `mount`, `namespace`,
+//! `metadata_mount`, `metadata_prefix`, `max_retries`, `timeout_secs` and
+//! `cache_ttl_secs`. `address` and `token`
+//! fall back to the `VAULT_ADDR` and `VAULT_TOKEN` environment variables.
+
+use crate::error::{AllSourceError, Result};
+use crate::security::kms::{
+    KeyAlgorithm, KeyMetadata, KeyPurpose, KeyStatus, KmsClient, KmsConfig,
+};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::RwLock;
+use reqwest::{Method, StatusCode};
+use serde::Deserialize;
+use serde_json::{json, Value as JsonValue};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Vault authentication method
+#[derive(Debug, Clone)]
+pub enum VaultAuth {
+    /// Static Vault token
+    Token(String),
+
+    /// AppRole login (role_id + secret_id)
+    AppRole { role_id: String, secret_id: String },
+}
+
+/// Vault Transit client settings
+#[derive(Debug, Clone)]
+pub struct VaultSettings {
+    /// Vault server address (e.g., "http://127.0.0.1:8200")
+    pub address: String,
+
+    /// Authentication method
+    pub auth: VaultAuth,
+
+    /// Transit engine mount path
+    pub mount: String,
+
+    /// Vault Enterprise namespace
+    pub namespace: Option<String>,
+
+    /// KV v2 mount holding key purpose and status
+    pub metadata_mount: String,
+
+    /// Path prefix under `metadata_mount` for key metadata entries
+    pub metadata_prefix: String,
+
+    /// Maximum number of retries for transient failures
+    pub max_retries: u32,
+
+    /// Request timeout in seconds
+    pub timeout_secs: u64,
+
+    /// Key metadata cache TTL in seconds
+    pub cache_ttl_secs: i64,
+}
+
+impl VaultSettings {
+    /// Build settings from a `KmsConfig`
+    pub fn from_kms_config(config: &KmsConfig) -> Result<Self> {
+        let get = |key: &str| config.config.get(key).cloned();
+
+        let address = get("address")
+            .or_else(|| std::env::var("VAULT_ADDR").ok())
+            .ok_or_else(|| {
+                AllSourceError::ValidationError("Vault address not configured".to_string())
+            })?;
+
+        let auth = match (get("role_id"), get("secret_id")) {
+            (Some(role_id), Some(secret_id)) => VaultAuth::AppRole { role_id, secret_id },
+            _ => {
+                let token = get("token")
+                    .or_else(|| std::env::var("VAULT_TOKEN").ok())
+                    .ok_or_else(|| {
+                        AllSourceError::ValidationError(
+                            "Vault token or AppRole credentials not configured".to_string(),
+                        )
+                    })?;
+                VaultAuth::Token(token)
+            }
+        };
+
+        let parse_num = |key: &str, default: u64| -> Result<u64> {
+            match get(key) {
+                Some(value) => value.parse().map_err(|_| {
+                    AllSourceError::ValidationError(format!("Invalid Vault setting {}: {}", key, value))
+                }),
+                None => Ok(default),
+            }
+        };
+
+        Ok(Self {
+            address: address.trim_end_matches('/').to_string(),
+            auth,
+            mount: get("mount").unwrap_or_else(|| "transit".to_string()),
+            namespace: get("namespace"),
+            metadata_mount: get("metadata_mount").unwrap_or_else(|| "secret".to_string()),
+            metadata_prefix: get("metadata_prefix").unwrap_or_else(|| "allsource/kms".to_string()),
+            max_retries: parse_num("max_retries", 3)? as u32,
+            timeout_secs: parse_num("timeout_secs", 10)?,
+            cache_ttl_secs: parse_num("cache_ttl_secs", 300)? as i64,
+        })
+    }
+}
+
+/// Cached key metadata entry
+struct CachedKey {
+    metadata: KeyMetadata,
+    fetched_at: DateTime<Utc>,
+}
+
+/// Current Vault client token
+struct VaultToken {
+    token: String,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+/// Vault Transit implementation of `KmsClient`
+pub struct VaultKms {
+    settings: VaultSettings,
+    http: reqwest::Client,
+    token: Arc<RwLock<Option<VaultToken>>>,
+    cache: Arc<RwLock<HashMap<String, CachedKey>>>,
+}
+
+impl VaultKms {
+    /// Create a Vault Transit client from a `KmsConfig`
+    pub fn new(config: KmsConfig) -> Result<Self> {
+        Self::with_settings(VaultSettings::from_kms_config(&config)?)
+    }
+
+    /// Create a Vault Transit client from explicit settings
+    pub fn with_settings(settings: VaultSettings) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(settings.timeout_secs))
+            .build()
+            .map_err(|e| AllSourceError::InternalError(format!("Failed to build HTTP client: {}", e)))?;
+
+        let token = match &settings.auth {
+            VaultAuth::Token(token) => Some(VaultToken {
+                token: token.clone(),
+                expires_at: None,
+            }),
+            VaultAuth::AppRole { .. } => None,
+        };
+
+        Ok(Self {
+            settings,
+            http,
+            token: Arc::new(RwLock::new(token)),
+            cache: Arc::new(RwLock::new(HashMap::new())),
+        })
+    }
+
+    /// Get the client settings
+    pub fn settings(&self) -> &VaultSettings {
+        &self.settings
+    }
+
+    /// Drop all cached key metadata
+    pub fn clear_cache(&self) {
+        self.cache.write().clear();
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/v1/{}", self.settings.address, path)
+    }
+
+    fn transit_path(&self, path: &str) -> String {
+        format!("{}/{}", self.settings.mount, path)
+    }
+
+    fn attributes_path(&self, key_id: &str) -> String {
+        format!(
+            "{}/metadata/{}/{}",
+            self.settings.metadata_mount,
+            self.settings.metadata_prefix.trim_matches('/'),
+            key_id
+        )
+    }
+
+    /// Get a valid client token, logging in via AppRole if needed
+    async fn client_token(&self) -> Result<String> {
+        if let Some(ref token) = *self.token.read() {
+            let valid = token
+                .expires_at
+                .map(|expires_at| Utc::now() < expires_at)
+                .unwrap_or(true);
+            if valid {
+                return Ok(token.token.clone());
+            }
+        }
+
+        self.login().await
+    }
+
+    /// Authenticate via AppRole and cache the resulting token
+    async fn login(&self) -> Result<String> {
+        let (role_id, secret_id) = match &self.settings.auth {
+            VaultAuth::AppRole { role_id, secret_id } => (role_id, secret_id),
+            VaultAuth::Token(_) => {
+                return Err(AllSourceError::ValidationError(
+                    "Vault token rejected".to_string(),
+                ));
+            }
+        };
+
+        let body = self
+            .send(
+                Method::POST,
+                "auth/approle/login",
+                Some(json!({ "role_id": role_id, "secret_id": secret_id })),
+                None,
+            )
+            .await?;
+
+        let auth: VaultAuthResponse = serde_json::from_value(body["auth"].clone())
+            .map_err(|e| AllSourceError::InternalError(format!("Invalid Vault login response: {}", e)))?;
+
+        // Renew a little before the lease actually runs out
+        let expires_at = (auth.lease_duration > 0)
+            .then(|| Utc::now() + Duration::seconds((auth.lease_duration * 9 / 10) as i64));
+
+        *self.token.write() = Some(VaultToken {
+            token: auth.client_token.clone(),
+            expires_at,
+        });
+
+        tracing::debug!("Authenticated with Vault via AppRole");
+
+        Ok(auth.client_token)
+    }
+
+    /// Send an authenticated request, re-logging in once on 403
+    async fn request(&self, method: Method, path: &str, body: Option<JsonValue>) -> Result<JsonValue> {
+        let token = self.client_token().await?;
+
+        match self.send(method.clone(), path, body.clone(), Some(&token)).await {
+            Err(VaultRequestError::Forbidden) if matches!(self.settings.auth, VaultAuth::AppRole { .. }) => {
+                *self.token.write() = None;
+                let token = self.login().await?;
+                Ok(self.send(method, path, body, Some(&token)).await?)
+            }
+            result => Ok(result?),
+        }
+    }
+
+    /// Send a request with retries on transient failures
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<JsonValue>,
+        token: Option<&str>,
+    ) -> std::result::Result<JsonValue, VaultRequestError> {
+        let mut attempt = 0;
+
+        loop {
+            let mut request = self.http.request(method.clone(), self.url(path));
+            if let Some(token) = token {
+                request = request.header("X-Vault-Token", token);
+            }
+            if let Some(ref namespace) = self.settings.namespace {
+                request = request.header("X-Vault-Namespace", namespace);
+            }
+            if let Some(ref body) = body {
+                request = request.json(body);
+            }
+
+            let error = match request.send().await {
+                Ok(response) => {
+                    let status = response.status();
+                    if status.is_success() {
+                        if status == StatusCode::NO_CONTENT {
+                            return Ok(JsonValue::Null);
+                        }
+                        return response.json().await.map_err(|e| {
+                            VaultRequestError::Other(format!("Invalid Vault response: {}", e))
+                        });
+                    }
+
+                    let text = response.text().await.unwrap_or_default();
+                    match status {
+                        StatusCode::FORBIDDEN => return Err(VaultRequestError::Forbidden),
+                        StatusCode::NOT_FOUND => return Err(VaultRequestError::NotFound),
+                        s if s.is_server_error() || s == StatusCode::TOO_MANY_REQUESTS => {
+                            format!("Vault returned {}: {}", status, text)
+                        }
+                        _ => {
+                            return Err(VaultRequestError::Other(format!(
+                                "Vault returned {}: {}",
+                                status, text
+                            )))
+                        }
+                    }
+                }
+                Err(e) => format!("Vault request failed: {}", e),
+            };
+
+            if attempt >= self.settings.max_retries {
+                return Err(VaultRequestError::Other(error));
+            }
+
+            let backoff = std::time::Duration::from_millis(100 * 2u64.pow(attempt));
+            tracing::warn!("{} (retrying in {:?})", error, backoff);
+            tokio::time::sleep(backoff).await;
+            attempt += 1;
+        }
+    }
+
+    /// Fetch key metadata from Vault, bypassing the cache
+    async fn fetch_key(&self, key_id: &str) -> Result<KeyMetadata> {
+        let body = self
+            .request(Method::GET, &self.transit_path(&format!("keys/{}", key_id)), None)
+            .await
+            .map_err(|e| not_found_as_key_error(e, key_id))?;
+
+        let key: TransitKey = serde_json::from_value(body["data"].clone())
+            .map_err(|e| AllSourceError::InternalError(format!("Invalid Vault key response: {}", e)))?;
+
+        let attributes = self.read_attributes(key_id).await?;
+        let metadata = to_metadata(key_id, key, attributes);
+
+        self.cache.write().insert(
+            key_id.to_string(),
+            CachedKey {
+                metadata: metadata.clone(),
+                fetched_at: Utc::now(),
+            },
+        );
+
+        Ok(metadata)
+    }
+
+    /// Read the purpose and status stored for a key (defaults if none)
+    async fn read_attributes(&self, key_id: &str) -> Result<KeyAttributes> {
+        match self.request(Method::GET, &self.attributes_path(key_id), None).await {
+            Ok(body) => Ok(KeyAttributes::from_custom_metadata(&body["data"]["custom_metadata"])),
+            Err(AllSourceError::EntityNotFound(_)) => Ok(KeyAttributes::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Store the purpose and status of a key in Vault
+    async fn write_attributes(&self, key_id: &str, attributes: &KeyAttributes) -> Result<()> {
+        self.request(
+            Method::POST,
+            &self.attributes_path(key_id),
+            Some(json!({ "custom_metadata": attributes.to_custom_metadata() })),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Persist a new status for an existing key and update the cache
+    async fn set_status(&self, key_id: &str, status: KeyStatus) -> Result<()> {
+        // Make sure the key exists before marking it
+        let metadata = self.fetch_key(key_id).await?;
+
+        let attributes = KeyAttributes {
+            purpose: metadata.purpose,
+            disabled: status != KeyStatus::Active,
+        };
+        self.write_attributes(key_id, &attributes).await?;
+
+        if let Some(cached) = self.cache.write().get_mut(key_id) {
+            cached.metadata.status = status;
+        }
+        Ok(())
+    }
+
+    async fn ensure_enabled(&self, key_id: &str) -> Result<()> {
+        if self.get_key(key_id).await?.status != KeyStatus::Active {
+            return Err(AllSourceError::ValidationError("Key is not active".to_string()));
+        }
+        Ok(())
+    }
+}
+
+fn to_metadata(key_id: &str, key: TransitKey, attributes: KeyAttributes) -> KeyMetadata {
+    // Vault reports version creation times as unix seconds (or RFC 3339 on newer releases)
+    let version_time = |version: u32| -> Option<DateTime<Utc>> {
+        key.keys.get(&version.to_string()).and_then(|v| match v {
+            JsonValue::Number(n) => n.as_i64().and_then(|s| DateTime::from_timestamp(s, 0)),
+            JsonValue::String(s) => DateTime::parse_from_rfc3339(s).ok().map(|t| t.with_timezone(&Utc)),
+            _ => None,
+        })
+    };
+
+    KeyMetadata {
+        key_id: key_id.to_string(),
+        alias: key.name,
+        purpose: attributes.purpose,
+        algorithm: algorithm_from_transit(&key.key_type),
+        created_at: version_time(1).unwrap_or_else(Utc::now),
+        last_rotated: (key.latest_version > 1)
+            .then(|| version_time(key.latest_version))
+            .flatten(),
+        status: if attributes.disabled {
+            KeyStatus::Deprecated
+        } else {
+            KeyStatus::Active
+        },
+        version: key.latest_version,
+    }
+}
+
+/// Key attributes Transit cannot hold, stored as KV v2 custom metadata
+struct KeyAttributes {
+    purpose: KeyPurpose,
+    disabled: bool,
+}
+
+impl Default for KeyAttributes {
+    fn default() -> Self {
+        Self {
+            purpose: KeyPurpose::DataEncryption,
+            disabled: false,
+        }
+    }
+}
+
+impl KeyAttributes {
+    fn from_custom_metadata(value: &JsonValue) -> Self {
+        let defaults = Self::default();
+        Self {
+            purpose: value["purpose"]
+                .as_str()
+                .and_then(|p| serde_json::from_str(p).ok())
+                .unwrap_or(defaults.purpose),
+            disabled: value["status"].as_str() == Some("disabled"),
+        }
+    }
+
+    fn to_custom_metadata(&self) -> JsonValue {
+        json!({
+            "purpose": serde_json::to_string(&self.purpose).unwrap_or_default(),
+            "status": if self.disabled { "disabled" } else { "active" },
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl KmsClient for VaultKms {
+    async fn create_key(&self, alias: String, purpose: KeyPurpose, algorithm: KeyAlgorithm) -> Result<KeyMetadata> {
+        let key_type = transit_key_type(&algorithm);
+
+        self.request(
+            Method::POST,
+            &self.transit_path(&format!("keys/{}", alias)),
+            Some(json!({ "type": key_type })),
+        )
+        .await?;
+
+        self.write_attributes(&alias, &KeyAttributes { purpose, disabled: false }).await?;
+
+        tracing::info!("🔑 Created Vault transit key '{}' ({})", alias, key_type);
+
+        self.fetch_key(&alias).await
+    }
+
+    async fn get_key(&self, key_id: &str) -> Result<KeyMetadata> {
+        let ttl = Duration::seconds(self.settings.cache_ttl_secs);
+        if let Some(cached) = self.cache.read().get(key_id) {
+            if Utc::now() - cached.fetched_at < ttl {
+                return Ok(cached.metadata.clone());
+            }
+        }
+
+        self.fetch_key(key_id).await
+    }
+
+    async fn list_keys(&self) -> Result<Vec<KeyMetadata>> {
+        let body = match self
+            .request(Method::GET, &format!("{}?list=true", self.transit_path("keys")), None)
+            .await
+        {
+            Ok(body) => body,
+            // Vault answers 404 when there are no keys at all
+            Err(AllSourceError::EntityNotFound(_)) => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let names: Vec<String> = serde_json::from_value(body["data"]["keys"].clone())
+            .map_err(|e| AllSourceError::InternalError(format!("Invalid Vault list response: {}", e)))?;
+
+        let mut keys = Vec::with_capacity(names.len());
+        for name in names {
+            keys.push(self.get_key(&name).await?);
+        }
+
+        Ok(keys)
+    }
+
+    async fn encrypt(&self, key_id: &str, plaintext: &[u8]) -> Result<Vec<u8>> {
+        self.ensure_enabled(key_id).await?;
+
+        let body = self
+            .request(
+                Method::POST,
+                &self.transit_path(&format!("encrypt/{}", key_id)),
+                Some(json!({ "plaintext": general_purpose::STANDARD.encode(plaintext) })),
+            )
+            .await
+            .map_err(|e| not_found_as_key_error(e, key_id))?;
+
+        body["data"]["ciphertext"]
+            .as_str()
+            .map(|c| c.as_bytes().to_vec())
+            .ok_or_else(|| AllSourceError::InternalError("Vault response missing ciphertext".to_string()))
+    }
+
+    async fn decrypt(&self, key_id: &str, ciphertext: &[u8]) -> Result<Vec<u8>> {
+        // Vault ciphertexts are "vault:v<version>:<base64>" strings
+        let ciphertext = std::str::from_utf8(ciphertext)
+            .map_err(|_| AllSourceError::ValidationError("Invalid ciphertext".to_string()))?;
+
+        let body = self
+            .request(
+                Method::POST,
+                &self.transit_path(&format!("decrypt/{}", key_id)),
+                Some(json!({ "ciphertext": ciphertext })),
+            )
+            .await
+            .map_err(|e| not_found_as_key_error(e, key_id))?;
+
+        decode_plaintext(&body)
+    }
+
+    async fn rotate_key(&self, key_id: &str) -> Result<KeyMetadata> {
+        self.request(
+            Method::POST,
+            &self.transit_path(&format!("keys/{}/rotate", key_id)),
+            None,
+        )
+        .await
+        .map_err(|e| not_found_as_key_error(e, key_id))?;
+
+        tracing::info!("🔄 Rotated Vault transit key '{}'", key_id);
+
+        self.fetch_key(key_id).await
+    }
+
+    async fn disable_key(&self, key_id: &str) -> Result<()> {
+        self.set_status(key_id, KeyStatus::Deprecated).await
+    }
+
+    async fn enable_key(&self, key_id: &str) -> Result<()> {
+        self.set_status(key_id, KeyStatus::Active).await
+    }
+
+    async fn generate_data_key(&self, key_id: &str) -> Result<(Vec<u8>, Vec<u8>)> {
+        self.ensure_enabled(key_id).await?;
+
+        let body = self
+            .request(
+                Method::POST,
+                &self.transit_path(&format!("datakey/plaintext/{}", key_id)),
+                Some(json!({ "bits": 256 })),
+            )
+            .await
+            .map_err(|e| not_found_as_key_error(e, key_id))?;
+
+        let dek = decode_plaintext(&body)?;
+        let encrypted_dek = body["data"]["ciphertext"]
+            .as_str()
+            .map(|c| c.as_bytes().to_vec())
+            .ok_or_else(|| AllSourceError::InternalError("Vault response missing ciphertext".to_string()))?;
+
+        Ok((dek, encrypted_dek))
+    }
+
+    async fn sign(&self, key_id: &str, message: &[u8]) -> Result<(Vec<u8>, u32)> {
+        self.ensure_enabled(key_id).await?;
+
+        let body = self
+            .request(
+                Method::POST,
+                &self.transit_path(&format!("hmac/{}/sha2-256", key_id)),
+                Some(json!({ "input": general_purpose::STANDARD.encode(message) })),
+            )
+            .await
+            .map_err(|e| not_found_as_key_error(e, key_id))?;
+
+        // Vault HMACs are "vault:v<version>:<base64>" strings
+        let hmac = body["data"]["hmac"]
+            .as_str()
+            .ok_or_else(|| AllSourceError::InternalError("Vault response missing hmac".to_string()))?;
+        let version = hmac_version(hmac)
+            .ok_or_else(|| AllSourceError::InternalError(format!("Unexpected Vault hmac format: {}", hmac)))?;
+        Ok((hmac.as_bytes().to_vec(), version))
+    }
+
+    async fn verify(&self, key_id: &str, version: u32, message: &[u8], signature: &[u8]) -> Result<bool> {
+        // Vault picks the key version from the hmac itself
+        let Some(hmac) = std::str::from_utf8(signature).ok().filter(|h| hmac_version(h) == Some(version)) else {
+            return Ok(false);
+        };
+
+        let body = self
+            .request(
+                Method::POST,
+                &self.transit_path(&format!("verify/{}/sha2-256", key_id)),
+                Some(json!({
+                    "input": general_purpose::STANDARD.encode(message),
+                    "hmac": hmac,
+                })),
+            )
+            .await
+            .map_err(|e| not_found_as_key_error(e, key_id))?;
+
+        Ok(body["data"]["valid"].as_bool().unwrap_or(false))
+    }
+}
+
+/// Key version of a "vault:v<version>:<base64>" hmac
+fn hmac_version(hmac: &str) -> Option<u32> {
+    hmac.strip_prefix("vault:v")?.split(':').next()?.parse().ok()
+}
+
+/// Error from a single Vault HTTP exchange
+#[derive(Debug)]
+enum VaultRequestError {
+    Forbidden,
+    NotFound,
+    Other(String),
+}
+
+impl From<VaultRequestError> for AllSourceError {
+    fn from(err: VaultRequestError) -> Self {
+        match err {
+            VaultRequestError::Forbidden => {
+                AllSourceError::ValidationError("Vault permission denied".to_string())
+            }
+            VaultRequestError::NotFound => {
+                AllSourceError::EntityNotFound("Vault path not found".to_string())
+            }
+            VaultRequestError::Other(msg) => AllSourceError::InternalError(msg),
+        }
+    }
+}
+
+/// Map Vault 404s to the "Key not found" error used by other KMS clients
+fn not_found_as_key_error(err: AllSourceError, key_id: &str) -> AllSourceError {
+    match err {
+        AllSourceError::EntityNotFound(_) => {
+            AllSourceError::ValidationError(format!("Key {} not found", key_id))
+        }
+        other => other,
+    }
+}
+
+fn decode_plaintext(body: &JsonValue) -> Result<Vec<u8>> {
+    let plaintext = body["data"]["plaintext"]
+        .as_str()
+        .ok_or_else(|| AllSourceError::InternalError("Vault response missing plaintext".to_string()))?;
+
+    general_purpose::STANDARD
+        .decode(plaintext)
+        .map_err(|e| AllSourceError::InternalError(format!("Invalid plaintext encoding: {}", e)))
+}
+
+fn transit_key_type(algorithm: &KeyAlgorithm) -> &'static str {
+    match algorithm {
+        KeyAlgorithm::Aes256Gcm => "aes256-gcm96",
+        KeyAlgorithm::Aes128Gcm => "aes128-gcm96",
+        KeyAlgorithm::ChaCha20Poly1305 => "chacha20-poly1305",
+        KeyAlgorithm::RsaOaep => "rsa-2048",
+        KeyAlgorithm::EcdsaP256 => "ecdsa-p256",
+        KeyAlgorithm::Ed25519 => "ed25519",
+        KeyAlgorithm::HmacSha256 => "hmac",
+    }
+}
+
+fn algorithm_from_transit(key_type: &str) -> KeyAlgorithm {
+    match key_type {
+        "aes128-gcm96" => KeyAlgorithm::Aes128Gcm,
+        "chacha20-poly1305" => KeyAlgorithm::ChaCha20Poly1305,
+        "rsa-2048" | "rsa-3072" | "rsa-4096" => KeyAlgorithm::RsaOaep,
+        "ecdsa-p256" => KeyAlgorithm::EcdsaP256,
+        "ed25519" => KeyAlgorithm::Ed25519,
+        "hmac" => KeyAlgorithm::HmacSha256,
+        _ => KeyAlgorithm::Aes256Gcm,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct VaultAuthResponse {
+    client_token: String,
+    #[serde(default)]
+    lease_duration: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct TransitKey {
+    name: String,
+    #[serde(rename = "type")]
+    key_type: String,
+    latest_version: u32,
+    #[serde(default)]
+    keys: HashMap<String, JsonValue>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::kms::{KmsManager, KmsProvider};
+    use axum::{
+        extract::{Path, Query, State},
+        http::{HeaderMap, StatusCode as HttpStatus},
+        routing::{get, post},
+        Json, Router,
+    };
+
+    const TEST_TOKEN: &str = "root-token";
+
+    /// Minimal in-process stand-in for the Vault Transit API
+    #[derive(Clone, Default)]
+    struct FakeVault {
+        // name -> (type, latest_version)
+        keys: Arc<RwLock<HashMap<String, (String, u32)>>>,
+        // KV v2 path -> custom_metadata
+        metadata: Arc<RwLock<HashMap<String, JsonValue>>>,
+        failures_left: Arc<RwLock<u32>>,
+        logins: Arc<RwLock<u32>>,
+    }
+
+    type FakeResult = std::result::Result<Json<JsonValue>, HttpStatus>;
+
+    fn check_token(headers: &HeaderMap) -> std::result::Result<(), HttpStatus> {
+        match headers.get("x-vault-token").and_then(|v| v.to_str().ok()) {
+            Some(TEST_TOKEN) | Some("approle-token") => Ok(()),
+            _ => Err(HttpStatus::FORBIDDEN),
+        }
+    }
+
+    async fn login(State(vault): State<FakeVault>, Json(body): Json<JsonValue>) -> FakeResult {
+        if body["role_id"] != "role" || body["secret_id"] != "secret" {
+            return Err(HttpStatus::BAD_REQUEST);
+        }
+        *vault.logins.write() += 1;
+        Ok(Json(json!({ "auth": { "client_token": "approle-token", "lease_duration": 3600 } })))
+    }
+
+    async fn create_or_read_key(
+        State(vault): State<FakeVault>,
+        headers: HeaderMap,
+        Path(name): Path<String>,
+        body: Option<Json<JsonValue>>,
+    ) -> FakeResult {
+        check_token(&headers)?;
+        let mut keys = vault.keys.write();
+        if let Some(Json(body)) = body {
+            let key_type = body["type"].as_str().unwrap_or("aes256-gcm96").to_string();
+            keys.entry(name).or_insert((key_type, 1));
+            return Ok(Json(JsonValue::Null));
+        }
+        let (key_type, version) = keys.get(&name).cloned().ok_or(HttpStatus::NOT_FOUND)?;
+        let versions: HashMap<String, i64> =
+            (1..=version).map(|v| (v.to_string(), 1_700_000_000 + v as i64)).collect();
+        Ok(Json(json!({
+            "data": { "name": name, "type": key_type, "latest_version": version, "keys": versions }
+        })))
+    }
+
+    async fn list_keys(
+        State(vault): State<FakeVault>,
+        headers: HeaderMap,
+        Query(_): Query<HashMap<String, String>>,
+    ) -> FakeResult {
+        check_token(&headers)?;
+        let keys: Vec<String> = vault.keys.read().keys().cloned().collect();
+        if keys.is_empty() {
+            return Err(HttpStatus::NOT_FOUND);
+        }
+        Ok(Json(json!({ "data": { "keys": keys } })))
+    }
+
+    async fn rotate(State(vault): State<FakeVault>, headers: HeaderMap, Path(name): Path<String>) -> FakeResult {
+        check_token(&headers)?;
+        let mut keys = vault.keys.write();
+        let key = keys.get_mut(&name).ok_or(HttpStatus::NOT_FOUND)?;
+        key.1 += 1;
+        Ok(Json(JsonValue::Null))
+    }
+
+    async fn encrypt(
+        State(vault): State<FakeVault>,
+        headers: HeaderMap,
+        Path(name): Path<String>,
+        Json(body): Json<JsonValue>,
+    ) -> FakeResult {
+        check_token(&headers)?;
+        {
+            let mut failures = vault.failures_left.write();
+            if *failures > 0 {
+                *failures -= 1;
+                return Err(HttpStatus::SERVICE_UNAVAILABLE);
+            }
+        }
+        let version = vault.keys.read().get(&name).map(|k| k.1).ok_or(HttpStatus::NOT_FOUND)?;
+        let plaintext = body["plaintext"].as_str().unwrap_or_default();
+        Ok(Json(json!({ "data": { "ciphertext": format!("vault:v{}:{}", version, plaintext) } })))
+    }
+
+    async fn decrypt(
+        State(vault): State<FakeVault>,
+        headers: HeaderMap,
+        Path(name): Path<String>,
+        Json(body): Json<JsonValue>,
+    ) -> FakeResult {
+        check_token(&headers)?;
+        if !vault.keys.read().contains_key(&name) {
+            return Err(HttpStatus::NOT_FOUND);
+        }
+        let ciphertext = body["ciphertext"].as_str().unwrap_or_default();
+        let plaintext = ciphertext.splitn(3, ':').nth(2).ok_or(HttpStatus::BAD_REQUEST)?;
+        Ok(Json(json!({ "data": { "plaintext": plaintext } })))
+    }
+
+    async fn datakey(State(vault): State<FakeVault>, headers: HeaderMap, Path(name): Path<String>) -> FakeResult {
+        check_token(&headers)?;
+        let version = vault.keys.read().get(&name).map(|k| k.1).ok_or(HttpStatus::NOT_FOUND)?;
+        let dek = general_purpose::STANDARD.encode([7u8; 32]);
+        Ok(Json(json!({
+            "data": { "plaintext": dek, "ciphertext": format!("vault:v{}:{}", version, dek) }
+        })))
+    }
+
+    async fn hmac(
+        State(vault): State<FakeVault>,
+        headers: HeaderMap,
+        Path(name): Path<String>,
+        Json(body): Json<JsonValue>,
+    ) -> FakeResult {
+        check_token(&headers)?;
+        let version = vault.keys.read().get(&name).map(|k| k.1).ok_or(HttpStatus::NOT_FOUND)?;
+        let input = body["input"].as_str().unwrap_or_default();
+        Ok(Json(json!({ "data": { "hmac": format!("vault:v{}:{}-{}", version, name, input) } })))
+    }
+
+    async fn verify(
+        State(vault): State<FakeVault>,
+        headers: HeaderMap,
+        Path(name): Path<String>,
+        Json(body): Json<JsonValue>,
+    ) -> FakeResult {
+        check_token(&headers)?;
+        if !vault.keys.read().contains_key(&name) {
+            return Err(HttpStatus::NOT_FOUND);
+        }
+        let input = body["input"].as_str().unwrap_or_default();
+        let hmac = body["hmac"].as_str().unwrap_or_default();
+        let expected = format!("{}-{}", name, input);
+        let valid = hmac.splitn(3, ':').nth(2) == Some(expected.as_str());
+        Ok(Json(json!({ "data": { "valid": valid } })))
+    }
+
+    async fn read_metadata(State(vault): State<FakeVault>, headers: HeaderMap, Path(path): Path<String>) -> FakeResult {
+        check_token(&headers)?;
+        let custom_metadata = vault.metadata.read().get(&path).cloned().ok_or(HttpStatus::NOT_FOUND)?;
+        Ok(Json(json!({ "data": { "custom_metadata": custom_metadata } })))
+    }
+
+    async fn write_metadata(
+        State(vault): State<FakeVault>,
+        headers: HeaderMap,
+        Path(path): Path<String>,
+        Json(body): Json<JsonValue>,
+    ) -> FakeResult {
+        check_token(&headers)?;
+        vault.metadata.write().insert(path, body["custom_metadata"].clone());
+        Ok(Json(JsonValue::Null))
+    }
+
+    async fn start_fake_vault(vault: FakeVault) -> String {
+        let app = Router::new()
+            .route("/v1/auth/approle/login", post(login))
+            .route("/v1/transit/keys", get(list_keys))
+            .route("/v1/transit/keys/:name", get(create_or_read_key).post(create_or_read_key))
+            .route("/v1/transit/keys/:name/rotate", post(rotate))
+            .route("/v1/transit/encrypt/:name", post(encrypt))
+            .route("/v1/transit/decrypt/:name", post(decrypt))
+            .route("/v1/transit/datakey/plaintext/:name", post(datakey))
+            .route("/v1/transit/hmac/:name/sha2-256", post(hmac))
+            .route("/v1/transit/verify/:name/sha2-256", post(verify))
+            .route("/v1/secret/metadata/*path", get(read_metadata).post(write_metadata))
+            .with_state(vault);
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        format!("http://{}", addr)
+    }
+
+    fn kms_config(address: &str, extra: &[(&str, &str)]) -> KmsConfig {
+        let mut config = KmsConfig {
+            provider: KmsProvider::HashicorpVault,
+            ..Default::default()
+        };
+        config.config.insert("address".to_string(), address.to_string());
+        for (key, value) in extra {
+            config.config.insert(key.to_string(), value.to_string());
+        }
+        config
+    }
+
+    #[tokio::test]
+    async fn test_vault_key_lifecycle() {
+        let address = start_fake_vault(FakeVault::default()).await;
+        let kms = VaultKms::new(kms_config(&address, &[("token", TEST_TOKEN)])).unwrap();
+
+        assert!(kms.list_keys().await.unwrap().is_empty());
+
+        let key = kms
+            .create_key("events".to_string(), KeyPurpose::DataEncryption, KeyAlgorithm::Aes256Gcm)
+            .await
+            .unwrap();
+        assert_eq!(key.key_id, "events");
+        assert_eq!(key.version, 1);
+        assert_eq!(key.status, KeyStatus::Active);
+
+        let ciphertext = kms.encrypt("events", b"secret").await.unwrap();
+        assert!(ciphertext.starts_with(b"vault:v1:"));
+        assert_eq!(kms.decrypt("events", &ciphertext).await.unwrap(), b"secret");
+
+        let rotated = kms.rotate_key("events").await.unwrap();
+        assert_eq!(rotated.version, 2);
+        assert!(rotated.last_rotated.is_some());
+
+        let (dek, encrypted_dek) = kms.generate_data_key("events").await.unwrap();
+        assert_eq!(dek.len(), 32);
+        assert_eq!(kms.decrypt("events", &encrypted_dek).await.unwrap(), dek);
+
+        assert_eq!(kms.list_keys().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_vault_disable_enable_key() {
+        let address = start_fake_vault(FakeVault::default()).await;
+        let kms = VaultKms::new(kms_config(&address, &[("token", TEST_TOKEN)])).unwrap();
+
+        kms.create_key("k".to_string(), KeyPurpose::JwtSigning, KeyAlgorithm::Aes256Gcm)
+            .await
+            .unwrap();
+
+        kms.disable_key("k").await.unwrap();
+        assert_eq!(kms.get_key("k").await.unwrap().status, KeyStatus::Deprecated);
+        assert!(kms.encrypt("k", b"data").await.is_err());
+
+        kms.enable_key("k").await.unwrap();
+        let key = kms.get_key("k").await.unwrap();
+        assert_eq!(key.status, KeyStatus::Active);
+        assert_eq!(key.purpose, KeyPurpose::JwtSigning);
+        assert!(kms.encrypt("k", b"data").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_vault_key_state_survives_restart() {
+        let vault = FakeVault::default();
+        let address = start_fake_vault(vault.clone()).await;
+        let kms = VaultKms::new(kms_config(&address, &[("token", TEST_TOKEN)])).unwrap();
+
+        kms.create_key("k".to_string(), KeyPurpose::AuditSigning, KeyAlgorithm::HmacSha256)
+            .await
+            .unwrap();
+        kms.disable_key("k").await.unwrap();
+        assert!(vault.metadata.read().contains_key("allsource/kms/k"));
+
+        // A fresh client reads purpose and status back from Vault
+        let restarted = VaultKms::new(kms_config(&address, &[("token", TEST_TOKEN)])).unwrap();
+        let key = restarted.get_key("k").await.unwrap();
+        assert_eq!(key.status, KeyStatus::Deprecated);
+        assert_eq!(key.purpose, KeyPurpose::AuditSigning);
+        assert!(restarted.sign("k", b"checkpoint").await.is_err());
+
+        restarted.enable_key("k").await.unwrap();
+        let again = VaultKms::new(kms_config(&address, &[("token", TEST_TOKEN)])).unwrap();
+        assert!(again.sign("k", b"checkpoint").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_vault_sign_verify() {
+        let address = start_fake_vault(FakeVault::default()).await;
+        let kms = VaultKms::new(kms_config(&address, &[("token", TEST_TOKEN)])).unwrap();
+
+        let key = kms
+            .create_key("audit".to_string(), KeyPurpose::AuditSigning, KeyAlgorithm::HmacSha256)
+            .await
+            .unwrap();
+        assert_eq!(key.algorithm, KeyAlgorithm::HmacSha256);
+
+        let (signature, version) = kms.sign("audit", b"checkpoint").await.unwrap();
+        assert!(signature.starts_with(b"vault:v1:"));
+        assert_eq!(version, 1);
+        assert!(kms.verify("audit", version, b"checkpoint", &signature).await.unwrap());
+        assert!(!kms.verify("audit", version, b"tampered", &signature).await.unwrap());
+        assert!(!kms.verify("audit", 2, b"checkpoint", &signature).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_vault_retries_transient_failures() {
+        let vault = FakeVault::default();
+        *vault.failures_left.write() = 2;
+        let address = start_fake_vault(vault.clone()).await;
+        let kms = VaultKms::new(kms_config(&address, &[("token", TEST_TOKEN)])).unwrap();
+
+        kms.create_key("k".to_string(), KeyPurpose::DataEncryption, KeyAlgorithm::Aes256Gcm)
+            .await
+            .unwrap();
+        assert!(kms.encrypt("k", b"data").await.is_ok());
+
+        *vault.failures_left.write() = 10;
+        assert!(kms.encrypt("k", b"data").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_vault_approle_auth() {
+        let vault = FakeVault::default();
+        let address = start_fake_vault(vault.clone()).await;
+        let kms = VaultKms::new(kms_config(&address, &[("role_id", "role"), ("secret_id", "secret")])).unwrap();
+
+        kms.create_key("k".to_string(), KeyPurpose::DataEncryption, KeyAlgorithm::Aes256Gcm)
+            .await
+            .unwrap();
+        kms.encrypt("k", b"data").await.unwrap();
+
+        // Token is reused across requests
+        assert_eq!(*vault.logins.read(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_vault_bad_token_and_missing_key() {
+        let address = start_fake_vault(FakeVault::default()).await;
+
+        let kms = VaultKms::new(kms_config(&address, &[("token", "wrong")])).unwrap();
+        assert!(kms.list_keys().await.is_err());
+
+        let kms = VaultKms::new(kms_config(&address, &[("token", TEST_TOKEN)])).unwrap();
+        let err = kms.get_key("missing").await.unwrap_err();
+        assert!(err.to_string().contains("Key missing not found"));
+    }
+
+    #[tokio::test]
+    async fn test_kms_manager_selects_vault() {
+        let address = start_fake_vault(FakeVault::default()).await;
+        let manager = KmsManager::new(kms_config(&address, &[("token", TEST_TOKEN)])).unwrap();
+
+        manager
+            .client()
+            .create_key("master".to_string(), KeyPurpose::DataEncryption, KeyAlgorithm::Aes256Gcm)
+            .await
+            .unwrap();
+
+        let encrypted = manager.envelope_encrypt("master", b"payload").await.unwrap();
+        assert_eq!(manager.envelope_decrypt(&encrypted).await.unwrap(), b"payload");
+    }
+}
@@ -65,6 +65,7 @@ fn create_test_api_key(auth: &AuthManager, tenant_id: &str) -> (ApiKey, String)
         Role::Developer,
         Some(Utc::now() + Duration::days(30)),
     )
+    .unwrap()
 }
 
 // ============================================================================
@@ -379,6 +380,200 @@ fn test_rate_limiting_multiple_tenants() {
     assert!(rate_limiter.check_rate_limit("tenant-2").allowed);
 }
 
+/// The production v1 router (`serve_v1`'s middleware stack) around `store`
+fn v1_router(
+    store: Arc<crate::store::EventStore>,
+    auth: Arc<AuthManager>,
+    rate_limiter: Arc<RateLimiter>,
+    adaptive_limiter: Option<Arc<crate::security::AdaptiveRateLimiter>>,
+    cost_model: crate::rate_limit::CostModel,
+    backup_dir: &std::path::Path,
+) -> axum::Router {
+    use crate::application::services::AuditLogger;
+    use crate::backup::{BackupConfig, BackupJobManager, BackupManager};
+    use crate::infrastructure::repositories::{AuditChainConfig, HashChainedAuditRepository};
+    use crate::middleware::TrustedProxies;
+    use crate::security::PolicyEngine;
+    use crate::tenant::TenantManager;
+
+    let audit_chain = Arc::new(HashChainedAuditRepository::new(
+        Arc::new(InMemoryAuditRepository::new()),
+        AuditChainConfig::default(),
+    ));
+    let backup_manager = BackupManager::new(BackupConfig {
+        backup_dir: backup_dir.to_path_buf(),
+        ..Default::default()
+    })
+    .unwrap();
+
+    crate::api_v1::router_v1(
+        store,
+        auth,
+        Arc::new(TenantManager::new()),
+        rate_limiter,
+        adaptive_limiter,
+        cost_model,
+        Arc::new(IpFilter::new()),
+        TrustedProxies::default(),
+        Arc::new(AuditLogger::new(audit_chain.clone() as Arc<dyn AuditEventRepository>)),
+        audit_chain,
+        Arc::new(PolicyEngine::new()),
+        Arc::new(BackupJobManager::new(Arc::new(backup_manager))),
+        None,
+        None,
+        None,
+    )
+}
+
+/// Attach a client socket address, as `serve_v1` does with connect info
+fn from_client(mut request: axum::http::Request<axum::body::Body>) -> axum::http::Request<axum::body::Body> {
+    request.extensions_mut().insert(axum::extract::ConnectInfo(std::net::SocketAddr::new(
+        IpAddr::from_str("192.0.2.10").unwrap(),
+        4000,
+    )));
+    request
+}
+
+#[tokio::test]
+async fn test_adaptive_rate_limiting_enforced_by_middleware() {
+    use crate::security::{AdaptiveRateLimitConfig, AdaptiveRateLimiter};
+    use crate::store::EventStore;
+    use axum::{body::Body, http::Request, http::StatusCode};
+    use tower::Service;
+
+    let auth = Arc::new(setup_auth_manager());
+    let (_, key) = auth.create_api_key("svc".to_string(), "default".to_string(), Role::ServiceAccount, None).unwrap();
+    let adaptive = Arc::new(AdaptiveRateLimiter::new(AdaptiveRateLimitConfig {
+        min_rate_limit: 1,
+        max_rate_limit: 3,
+        ..Default::default()
+    }));
+
+    // The static limiter would allow far more; the adaptive one decides
+    let backup_dir = tempfile::TempDir::new().unwrap();
+    let mut app = v1_router(
+        Arc::new(EventStore::new()),
+        auth.clone(),
+        Arc::new(RateLimiter::new(RateLimitConfig::dev_mode())),
+        Some(adaptive.clone()),
+        crate::rate_limit::CostModel::flat(),
+        backup_dir.path(),
+    );
+
+    let request = || {
+        from_client(
+            Request::builder()
+                .uri("/api/v1/events/query")
+                .header("authorization", format!("Bearer {}", key))
+                .body(Body::empty())
+                .unwrap(),
+        )
+    };
+
+    // Unauthenticated requests are rejected by auth and not counted
+    let response = app
+        .clone()
+        .call(from_client(Request::builder().uri("/api/v1/events/query").body(Body::empty()).unwrap()))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert!(!response.headers().contains_key("X-RateLimit-Limit"));
+
+    for remaining in ["2", "1", "0"] {
+        let response = app.clone().call(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["X-RateLimit-Limit"], "3");
+        assert_eq!(response.headers()["X-RateLimit-Remaining"], remaining);
+    }
+
+    let response = app.call(request()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(response.headers().contains_key("Retry-After"));
+    assert_eq!(adaptive.get_tenant_stats("default").unwrap().requests_last_hour, 4);
+}
+
+#[tokio::test]
+async fn test_cost_based_rate_limiting() {
+    use crate::rate_limit::CostModel;
+    use crate::store::EventStore;
+    use axum::{body::Body, http::Request, http::StatusCode};
+    use tower::Service;
+
+    let store = Arc::new(EventStore::new());
+    for i in 0..3000 {
+        let event = Event::from_strings(
+            "order.placed".to_string(),
+            format!("order-{}", i),
+            "default".to_string(),
+            json!({}),
+            None,
+        )
+        .unwrap();
+        store.ingest(event).unwrap();
+    }
+
+    let auth = Arc::new(setup_auth_manager());
+    let (_, key) = auth.create_api_key("svc".to_string(), "default".to_string(), Role::Developer, None).unwrap();
+    let rate_limiter = Arc::new(RateLimiter::new(RateLimitConfig {
+        requests_per_minute: 60,
+        burst_size: 10,
+    }));
+
+    let backup_dir = tempfile::TempDir::new().unwrap();
+    let mut app = v1_router(
+        store.clone(),
+        auth.clone(),
+        rate_limiter,
+        None,
+        CostModel::professional(),
+        backup_dir.path(),
+    );
+
+    // Route cost 2, plus 3,000 rows scanned and 10 returned
+    let response = app
+        .clone()
+        .call(from_client(
+            Request::builder()
+                .uri("/api/v1/events/query?event_type=order.placed&limit=10")
+                .header("authorization", format!("Bearer {}", key))
+                .body(Body::empty())
+                .unwrap(),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["X-RateLimit-Cost"], "5.05");
+    assert_eq!(response.headers()["X-RateLimit-Remaining"], "4");
+
+    // Ingest cost grows with the payload: 1 + 20 KiB at 0.1/KiB. Sent without
+    // Content-Length, so the body is measured after authentication
+    let ingest = || {
+        let envelope = |note: &str| {
+            json!({"event_type": "order.placed", "entity_id": "order-big", "payload": {"note": note}}).to_string()
+        };
+        let padding = 20 * 1024 - envelope("").len();
+        let body = envelope(&" ".repeat(padding));
+        assert_eq!(body.len(), 20 * 1024);
+        from_client(
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/events")
+                .header("authorization", format!("Bearer {}", key))
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .unwrap(),
+        )
+    };
+    let response = app.clone().call(ingest()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["X-RateLimit-Cost"], "3");
+    assert_eq!(store.stats().total_events, 3001);
+
+    // Less than 3 tokens left
+    let response = app.call(ingest()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+}
+
 // ============================================================================
 // Audit Logging Tests
 // ============================================================================
@@ -455,6 +650,111 @@ async fn test_audit_logging_tenant_isolation() {
     assert_eq!(events2.len(), 1);
 }
 
+#[tokio::test]
+async fn test_audit_middleware_records_mutating_requests() {
+    use crate::application::services::AuditLogger;
+    use crate::domain::entities::{AuditAction, AuditOutcome};
+    use crate::domain::repositories::AuditEventQuery;
+    use crate::middleware::{
+        audit_middleware, client_ip_middleware, request_id_middleware, AuditState, TrustedProxies,
+    };
+    use axum::{
+        body::Body,
+        extract::ConnectInfo,
+        http::{Request, StatusCode},
+        routing::{delete, get, post},
+        Router,
+    };
+    use std::net::SocketAddr;
+    use tower::Service;
+
+    let auth = Arc::new(setup_auth_manager());
+    let (user_id, token) = create_test_user(&auth, "test-tenant");
+    let claims = auth.validate_token(&token).unwrap();
+
+    let audit_repo = Arc::new(InMemoryAuditRepository::new());
+    let audit_logger = Arc::new(AuditLogger::new(
+        audit_repo.clone() as Arc<dyn AuditEventRepository>
+    ));
+    let audit_state = AuditState {
+        audit_logger,
+        auth_manager: auth.clone(),
+    };
+
+    let app = Router::new()
+        .route("/api/v1/tenants", post(|| async { StatusCode::CREATED }))
+        .route("/api/v1/tenants/:id", delete(|| async { StatusCode::FORBIDDEN }))
+        .route("/api/v1/stats", get(|| async { StatusCode::OK }))
+        .layer(axum::middleware::from_fn_with_state(audit_state, audit_middleware))
+        .layer(axum::middleware::from_fn(
+            move |mut req: axum::extract::Request, next: axum::middleware::Next| {
+                let claims = claims.clone();
+                async move {
+                    req.extensions_mut().insert(AuthContext { claims });
+                    next.run(req).await
+                }
+            },
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            TrustedProxies::new(vec!["10.0.0.0/8".parse().unwrap()]),
+            client_ip_middleware,
+        ))
+        .layer(axum::middleware::from_fn(request_id_middleware));
+
+    // Requests arrive through the trusted proxy at 10.0.0.1
+    let send_from = |peer: &str, method: &str, uri: &str| {
+        let mut request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("x-request-id", "req-42")
+            .header("x-forwarded-for", "198.51.100.1, 203.0.113.7, 10.0.0.2")
+            .header("user-agent", "audit-test")
+            .body(Body::empty())
+            .unwrap();
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(SocketAddr::new(peer.parse().unwrap(), 4000)));
+        request
+    };
+    let send = |method: &str, uri: &str| send_from("10.0.0.1", method, uri);
+    app.clone().call(send("POST", "/api/v1/tenants")).await.unwrap();
+    app.clone().call(send("DELETE", "/api/v1/tenants/acme")).await.unwrap();
+    app.clone().call(send("GET", "/api/v1/stats")).await.unwrap();
+
+    // Reads are not audited
+    let query = AuditEventQuery::new(create_test_tenant_id());
+    assert_eq!(audit_repo.count(query.clone()).await.unwrap(), 2);
+
+    let created = audit_repo
+        .query(query.clone().with_action(AuditAction::TenantCreated))
+        .await
+        .unwrap();
+    assert_eq!(created.len(), 1);
+    assert_eq!(created[0].outcome(), &AuditOutcome::Success);
+    assert_eq!(created[0].actor().identifier(), format!("user:{}", user_id));
+    assert_eq!(created[0].ip_address(), Some("203.0.113.7"));
+    assert_eq!(created[0].user_agent(), Some("audit-test"));
+    assert_eq!(created[0].request_id(), Some("req-42"));
+
+    // Forbidden requests are recorded as permission denials
+    let denied = audit_repo.query(query.security_only()).await.unwrap();
+    assert_eq!(denied.len(), 1);
+    assert_eq!(denied[0].action(), &AuditAction::PermissionDenied);
+    assert_eq!(denied[0].resource_id(), Some("acme"));
+
+    // X-Forwarded-For from an untrusted peer is ignored
+    app.clone()
+        .call(send_from("192.0.2.50", "POST", "/api/v1/tenants"))
+        .await
+        .unwrap();
+    let created = audit_repo
+        .query(AuditEventQuery::new(create_test_tenant_id()).with_action(AuditAction::TenantCreated))
+        .await
+        .unwrap();
+    assert_eq!(created.len(), 2);
+    assert!(created.iter().any(|event| event.ip_address() == Some("192.0.2.50")));
+}
+
 // ============================================================================
 // IP Filtering Tests
 // ============================================================================
@@ -529,6 +829,359 @@ fn test_security_config_defaults() {
     assert!(config.csp.is_some());
 }
 
+// ============================================================================
+// Payload Encryption Tests
+// ============================================================================
+
+#[test]
+fn test_payload_encryption_on_ingest_and_read() {
+    use crate::application::dto::QueryEventsRequest;
+    use crate::security::{PayloadEncryptionConfig, SensitiveFieldPolicy, REDACTED_PLACEHOLDER};
+    use crate::store::{EventStore, EventStoreConfig};
+
+    let store = EventStore::with_config(EventStoreConfig {
+        payload_encryption_config: PayloadEncryptionConfig {
+            enabled: true,
+            policies: vec![SensitiveFieldPolicy {
+                event_type: "user.created".to_string(),
+                fields: vec!["ssn".to_string()],
+            }],
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+
+    let event = Event::from_strings(
+        "user.created".to_string(),
+        "user-1".to_string(),
+        "default".to_string(),
+        json!({"name": "Alice", "ssn": "123-45-6789"}),
+        None,
+    )
+    .unwrap();
+    store.ingest(event).unwrap();
+
+    let stored = store
+        .query(QueryEventsRequest {
+            entity_id: Some("user-1".to_string()),
+            event_type: None,
+            tenant_id: None,
+            as_of: None,
+            since: None,
+            until: None,
+            limit: None,
+            payload_contains: None,
+        })
+        .unwrap();
+
+    // Stored payload never holds the plaintext
+    assert_ne!(stored[0].payload["ssn"], "123-45-6789");
+    assert_eq!(stored[0].payload["name"], "Alice");
+
+    let admin = Claims::new("a".to_string(), "default".to_string(), Role::Admin, Duration::hours(1));
+    let dev = Claims::new("d".to_string(), "default".to_string(), Role::Developer, Duration::hours(1));
+
+    let mut for_admin = stored[0].payload.clone();
+    store
+        .payload_encryption()
+        .reveal(&mut for_admin, admin.has_permission(Permission::ReadSensitive))
+        .unwrap();
+    assert_eq!(for_admin["ssn"], "123-45-6789");
+
+    let mut for_dev = stored[0].payload.clone();
+    store
+        .payload_encryption()
+        .reveal(&mut for_dev, dev.has_permission(Permission::ReadSensitive))
+        .unwrap();
+    assert_eq!(for_dev["ssn"], REDACTED_PLACEHOLDER);
+}
+
+// ============================================================================
+// Scoped API Key Tests
+// ============================================================================
+
+#[test]
+fn test_scoped_api_key_query_filtering() {
+    use crate::application::dto::QueryEventsRequest;
+    use crate::auth::ApiKeyScopes;
+    use crate::store::EventStore;
+
+    let store = EventStore::new();
+    for (event_type, entity_id) in [
+        ("order.placed", "order-1"),
+        ("order.shipped", "order-1"),
+        ("user.created", "user-1"),
+        ("order.placed", "legacy-7"),
+    ] {
+        let event = Event::from_strings(
+            event_type.to_string(),
+            entity_id.to_string(),
+            "default".to_string(),
+            json!({}),
+            None,
+        )
+        .unwrap();
+        store.ingest(event).unwrap();
+    }
+
+    let auth = setup_auth_manager();
+    let (_, key) = auth.create_scoped_api_key(
+        "orders".to_string(),
+        "default".to_string(),
+        Role::ServiceAccount,
+        None,
+        ApiKeyScopes {
+            event_type_namespaces: vec!["order".to_string()],
+            entity_id_prefixes: vec!["order".to_string()],
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let claims = auth.validate_api_key(&key).unwrap();
+
+    let all = || QueryEventsRequest {
+        entity_id: None,
+        event_type: None,
+        tenant_id: None,
+        as_of: None,
+        since: None,
+        until: None,
+        limit: None,
+        payload_contains: None,
+    };
+    assert_eq!(store.query(all()).unwrap().len(), 4);
+
+    let visible = store.query_with_scopes(all(), claims.scopes.as_ref()).unwrap();
+    assert_eq!(visible.len(), 2);
+    assert!(visible.iter().all(|e| e.entity_id_str() == "order-1"));
+}
+
+#[tokio::test]
+async fn test_scoped_api_key_enforced_by_auth_middleware() {
+    use crate::auth::{ApiKeyScopes, ApiOperation};
+    use crate::middleware::{auth_middleware, AuthState};
+    use axum::{body::Body, extract::ConnectInfo, http::Request, http::StatusCode, routing::get, Router};
+    use std::net::SocketAddr;
+    use tower::Service;
+
+    let auth = Arc::new(setup_auth_manager());
+    let (_, key) = auth.create_scoped_api_key(
+        "reader".to_string(),
+        "default".to_string(),
+        Role::ServiceAccount,
+        None,
+        ApiKeyScopes {
+            operations: vec![ApiOperation::Read],
+            allowed_cidrs: vec!["10.0.0.0/8".parse().unwrap()],
+            rate_limit_per_minute: Some(2),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    let mut app = Router::new()
+        .route("/api/v1/events/query", get(|| async { "ok" }))
+        .route("/api/v1/events", axum::routing::post(|| async { "ok" }))
+        .layer(axum::middleware::from_fn_with_state(
+            AuthState { auth_manager: auth.clone(), cluster_secret: None },
+            auth_middleware,
+        ));
+
+    let request = |method: &str, path: &str, ip: &str| {
+        let mut request = Request::builder()
+            .method(method)
+            .uri(path)
+            .header("authorization", format!("Bearer {}", key))
+            .body(Body::empty())
+            .unwrap();
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(SocketAddr::new(ip.parse().unwrap(), 4000)));
+        request
+    };
+
+    let response = app.clone().call(request("GET", "/api/v1/events/query", "10.0.0.5")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Write operation outside the key's scopes
+    let response = app.clone().call(request("POST", "/api/v1/events", "10.0.0.5")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // Client outside the allowed networks
+    let response = app.clone().call(request("GET", "/api/v1/events/query", "192.168.1.1")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // Per-key limit of 2 requests/min; the POST above consumed no budget
+    let response = app.clone().call(request("GET", "/api/v1/events/query", "10.0.0.5")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = app.call(request("GET", "/api/v1/events/query", "10.0.0.5")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn test_scoped_api_keys_on_entity_and_aggregate_endpoints() {
+    use crate::auth::ApiKeyScopes;
+    use crate::store::EventStore;
+    use axum::{body::Body, http::Request, http::StatusCode};
+    use tower::Service;
+
+    let store = Arc::new(EventStore::new());
+    for entity_id in ["order-1", "user-1"] {
+        let event = Event::from_strings(
+            "order.placed".to_string(),
+            entity_id.to_string(),
+            "default".to_string(),
+            json!({"total": 10}),
+            None,
+        )
+        .unwrap();
+        store.ingest(event).unwrap();
+        store.create_snapshot(entity_id).unwrap();
+    }
+
+    let auth = Arc::new(setup_auth_manager());
+    let scoped_key = |event_type_namespaces: Vec<String>, entity_id_prefixes: Vec<String>| {
+        auth.create_scoped_api_key(
+            "scoped".to_string(),
+            "default".to_string(),
+            Role::ServiceAccount,
+            None,
+            ApiKeyScopes {
+                event_type_namespaces,
+                entity_id_prefixes,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .1
+    };
+    let entity_key = scoped_key(Vec::new(), vec!["order".to_string()]);
+    let type_key = scoped_key(vec!["order".to_string()], Vec::new());
+
+    let backup_dir = tempfile::TempDir::new().unwrap();
+    let app = v1_router(
+        store,
+        auth.clone(),
+        Arc::new(RateLimiter::new(RateLimitConfig::dev_mode())),
+        None,
+        crate::rate_limit::CostModel::flat(),
+        backup_dir.path(),
+    );
+    let call = |key: &str, method: &str, uri: &str, body: Option<serde_json::Value>| {
+        let mut app = app.clone();
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("authorization", format!("Bearer {}", key))
+            .header("content-type", "application/json");
+        let request = from_client(request.body(body.map_or(Body::empty(), |b| Body::from(b.to_string()))).unwrap());
+        async move { app.call(request).await.unwrap() }
+    };
+    let status = |key: &str, method: &str, uri: &str| {
+        let response = call(key, method, uri, None);
+        async move { response.await.status() }
+    };
+
+    // Entity-prefix keys read their own entities only
+    assert_eq!(status(&entity_key, "GET", "/api/v1/entities/order-1/state").await, StatusCode::OK);
+    assert_eq!(status(&entity_key, "GET", "/api/v1/entities/user-1/state").await, StatusCode::FORBIDDEN);
+    assert_eq!(status(&entity_key, "GET", "/api/v1/snapshots/user-1/latest").await, StatusCode::FORBIDDEN);
+    let response = call(&entity_key, "GET", "/api/v1/snapshots", None).await;
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let listed: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(listed["total"], 1);
+    assert_eq!(listed["snapshots"][0]["entity_id"], "order-1");
+
+    // Entity state spans all event types, so event-type keys cannot read it
+    assert_eq!(status(&type_key, "GET", "/api/v1/entities/order-1/state").await, StatusCode::FORBIDDEN);
+    assert_eq!(status(&type_key, "GET", "/api/v1/entities/order-1/snapshot").await, StatusCode::FORBIDDEN);
+    assert_eq!(status(&type_key, "GET", "/api/v1/snapshots?entity_id=order-1").await, StatusCode::FORBIDDEN);
+
+    // Store-wide analytics and replays are closed to any event-scoped key
+    for key in [&entity_key, &type_key] {
+        assert_eq!(status(key, "GET", "/api/v1/analytics/summary").await, StatusCode::FORBIDDEN);
+        let response = call(key, "POST", "/api/v1/replay", Some(json!({}))).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+}
+
+// ============================================================================
+// Access Policy Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_access_policies_enforced_by_middleware() {
+    use crate::auth::ApiOperation;
+    use crate::middleware::{auth_middleware, policy_middleware, AuthState, PolicyState};
+    use crate::security::policy::{PolicyEngine, PolicyRule};
+    use axum::{body::Body, http::Request, http::StatusCode, routing::post, Router};
+    use tower::Service;
+
+    let auth = Arc::new(setup_auth_manager());
+    let (_, service_key) = auth.create_api_key(
+        "ingest".to_string(),
+        "acme".to_string(),
+        Role::ServiceAccount,
+        None,
+    )
+    .unwrap();
+    let (_, other_tenant_key) = auth.create_api_key(
+        "ingest".to_string(),
+        "globex".to_string(),
+        Role::ServiceAccount,
+        None,
+    )
+    .unwrap();
+
+    let engine = Arc::new(PolicyEngine::new());
+    let mut rule: PolicyRule = serde_json::from_value(json!({
+        "id": "no-pii-from-services",
+        "name": "Services may not write PII events",
+        "effect": "deny",
+        "subject": { "roles": ["serviceaccount"] },
+        "resource": { "event_types": ["pii.*"] },
+    }))
+    .unwrap();
+    rule.actions = vec![ApiOperation::Write];
+    engine.put_rule("acme", rule).unwrap();
+
+    // The handler echoes the body, proving it survives inspection
+    let mut app = Router::new()
+        .route("/api/v1/events", post(|body: String| async move { body }))
+        .layer(axum::middleware::from_fn_with_state(
+            PolicyState { policy_engine: engine },
+            policy_middleware,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            AuthState { auth_manager: auth.clone(), cluster_secret: None },
+            auth_middleware,
+        ));
+
+    let ingest = |key: &str, event_type: &str| {
+        Request::builder()
+            .method("POST")
+            .uri("/api/v1/events")
+            .header("authorization", format!("Bearer {}", key))
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({"event_type": event_type, "entity_id": "user-1", "payload": {}}).to_string(),
+            ))
+            .unwrap()
+    };
+
+    let response = app.call(ingest(&service_key, "pii.ssn_updated")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = app.call(ingest(&service_key, "order.placed")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(String::from_utf8_lossy(&body).contains("order.placed"));
+
+    // Rules are per tenant
+    let response = app.call(ingest(&other_tenant_key, "pii.ssn_updated")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
 // ============================================================================
 // Integration Test Summary
 // ============================================================================
@@ -1,17 +1,194 @@
 use crate::error::{AllSourceError, Result};
 use crate::domain::entities::Event;
+use crate::infrastructure::persistence::object_store::{self, ObjectStore};
 use arrow::array::{
     Array, ArrayRef, StringBuilder, TimestampMicrosecondArray, TimestampMicrosecondBuilder,
     UInt64Builder,
 };
 use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
 use arrow::record_batch::RecordBatch;
+use chrono::{DateTime, Utc};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
 use parquet::arrow::ArrowWriter;
-use parquet::file::properties::WriterProperties;
+use parquet::basic::Compression;
+use parquet::file::properties::{EnabledStatistics, WriterProperties};
+use parquet::format::SortingColumn;
+use parquet::schema::types::ColumnPath;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs::{self, File};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+/// Key prefix of Parquet files moved to cold storage
+pub const COLD_SEGMENT_PREFIX: &str = "segments/";
+
+/// File in the storage directory holding the `SegmentManifest`
+pub const SEGMENT_MANIFEST_FILE: &str = "manifest.json";
+
+// Positions of the columns segments are sorted by
+const ENTITY_ID_COLUMN: usize = 2;
+const TIMESTAMP_COLUMN: usize = 4;
+const TENANT_ID_COLUMN: usize = 7;
+
+/// Order of the events in the segments compaction writes
+pub fn segment_sort_key(event: &Event) -> (&str, &str, DateTime<Utc>) {
+    (event.tenant_id_str(), event.entity_id_str(), event.timestamp)
+}
+
+/// Layout of the Parquet segments compaction writes
+///
+/// Rows are sorted by `segment_sort_key`, statistics are kept per page
+/// (so the page index is written), and `entity_id` and `event_type` get
+/// bloom filters. The store answers queries from memory and never reads
+/// them; they let Parquet readers outside the store (DataFusion, DuckDB,
+/// Spark) skip row groups when querying the storage directory.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SegmentLayout {
+    /// Maximum rows per row group
+    #[serde(default = "default_row_group_size")]
+    pub row_group_size: usize,
+
+    /// False positive probability of the bloom filters, in (0, 1)
+    #[serde(default = "default_bloom_filter_fpp")]
+    pub bloom_filter_fpp: f64,
+}
+
+fn default_row_group_size() -> usize {
+    10_000
+}
+
+fn default_bloom_filter_fpp() -> f64 {
+    0.01
+}
+
+impl Default for SegmentLayout {
+    fn default() -> Self {
+        Self {
+            row_group_size: default_row_group_size(),
+            bloom_filter_fpp: default_bloom_filter_fpp(),
+        }
+    }
+}
+
+impl SegmentLayout {
+    fn writer_properties(&self, events: usize) -> Result<WriterProperties> {
+        if !(self.bloom_filter_fpp > 0.0 && self.bloom_filter_fpp < 1.0) {
+            return Err(AllSourceError::ValidationError(format!(
+                "Bloom filter false positive probability must be between 0 and 1, got {}",
+                self.bloom_filter_fpp
+            )));
+        }
+        let row_group_size = self.row_group_size.max(1);
+
+        let sorting_columns = [TENANT_ID_COLUMN, ENTITY_ID_COLUMN, TIMESTAMP_COLUMN]
+            .into_iter()
+            .map(|column| SortingColumn::new(column as i32, false, true))
+            .collect();
+        let mut props = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .set_max_row_group_size(row_group_size)
+            .set_statistics_enabled(EnabledStatistics::Page)
+            .set_sorting_columns(Some(sorting_columns));
+        for column in ["entity_id", "event_type"] {
+            props = props
+                .set_column_bloom_filter_fpp(ColumnPath::from(column), self.bloom_filter_fpp)
+                .set_column_bloom_filter_ndv(
+                    ColumnPath::from(column),
+                    events.clamp(1, row_group_size) as u64,
+                );
+        }
+
+        Ok(props.build())
+    }
+}
+
+/// Min/max statistics of one Parquet file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SegmentStats {
+    pub events: usize,
+    pub size_bytes: u64,
+    /// Whether the rows are ordered by `segment_sort_key`
+    pub sorted: bool,
+    pub min_timestamp: DateTime<Utc>,
+    pub max_timestamp: DateTime<Utc>,
+    pub min_tenant_id: String,
+    pub max_tenant_id: String,
+    pub min_entity_id: String,
+    pub max_entity_id: String,
+}
+
+impl SegmentStats {
+    fn from_events(events: &[Event], size_bytes: u64, sorted: bool) -> Option<Self> {
+        let first = events.first()?;
+        let mut stats = Self {
+            events: events.len(),
+            size_bytes,
+            sorted,
+            min_timestamp: first.timestamp,
+            max_timestamp: first.timestamp,
+            min_tenant_id: first.tenant_id_str().to_string(),
+            max_tenant_id: first.tenant_id_str().to_string(),
+            min_entity_id: first.entity_id_str().to_string(),
+            max_entity_id: first.entity_id_str().to_string(),
+        };
+        for event in &events[1..] {
+            stats.min_timestamp = stats.min_timestamp.min(event.timestamp);
+            stats.max_timestamp = stats.max_timestamp.max(event.timestamp);
+            widen(&mut stats.min_tenant_id, &mut stats.max_tenant_id, event.tenant_id_str());
+            widen(&mut stats.min_entity_id, &mut stats.max_entity_id, event.entity_id_str());
+        }
+        Some(stats)
+    }
+
+}
+
+fn widen(min: &mut String, max: &mut String, value: &str) {
+    if value < min.as_str() {
+        *min = value.to_string();
+    }
+    if value > max.as_str() {
+        *max = value.to_string();
+    }
+}
+
+/// Statistics of the local Parquet files, by file name
+///
+/// Every file written through `ParquetStorage` is recorded, so readers
+/// outside the store can pick files without opening each of them. Files
+/// missing from it must still be read, and entries of files that are gone
+/// are dropped on load.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SegmentManifest {
+    pub files: BTreeMap<String, SegmentStats>,
+}
+
+impl SegmentManifest {
+    /// Load the manifest of `storage_dir`; an unreadable one is rebuilt as
+    /// files are written
+    pub fn load(storage_dir: &Path) -> Self {
+        let path = storage_dir.join(SEGMENT_MANIFEST_FILE);
+        let mut manifest: Self = match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+                tracing::warn!("Ignoring unreadable segment manifest {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        };
+        manifest
+            .files
+            .retain(|file_name, _| storage_dir.join(file_name).exists());
+        manifest
+    }
+
+    fn save(&self, storage_dir: &Path) -> Result<()> {
+        let json = serde_json::to_vec_pretty(self)?;
+        object_store::write_atomically(&storage_dir.join(SEGMENT_MANIFEST_FILE), |file| {
+            std::io::Write::write_all(file, &json)
+        })
+    }
+}
+
 /// Parquet-based persistent storage for events
 pub struct ParquetStorage {
     /// Base directory for storing parquet files
@@ -25,6 +202,9 @@ pub struct ParquetStorage {
 
     /// Schema for Arrow/Parquet
     schema: Arc<Schema>,
+
+    /// Object store holding older files moved out by compaction
+    cold_storage: Option<Arc<dyn ObjectStore>>,
 }
 
 impl ParquetStorage {
@@ -49,6 +229,7 @@ impl ParquetStorage {
             ),
             Field::new("metadata", DataType::Utf8, true),
             Field::new("version", DataType::UInt64, false),
+            Field::new("tenant_id", DataType::Utf8, true),
         ]));
 
         Ok(Self {
@@ -56,9 +237,16 @@ impl ParquetStorage {
             current_batch: Vec::new(),
             batch_size: 1000, // Flush every 1000 events
             schema,
+            cold_storage: None,
         })
     }
 
+    /// Also read (and clear) files that compaction moved to `store`
+    pub fn with_cold_storage(mut self, store: Arc<dyn ObjectStore>) -> Self {
+        self.cold_storage = Some(store);
+        self
+    }
+
     /// Add an event to the current batch
     pub fn append_event(&mut self, event: Event) -> Result<()> {
         self.current_batch.push(event);
@@ -71,6 +259,11 @@ impl ParquetStorage {
         Ok(())
     }
 
+    /// Events appended but not yet flushed to disk
+    pub fn pending_events(&self) -> usize {
+        self.current_batch.len()
+    }
+
     /// Flush current batch to a Parquet file
     pub fn flush(&mut self) -> Result<()> {
         if self.current_batch.is_empty() {
@@ -80,29 +273,14 @@ impl ParquetStorage {
         let batch_count = self.current_batch.len();
         tracing::info!("Flushing {} events to Parquet storage", batch_count);
 
-        // Create record batch from events
-        let record_batch = self.events_to_record_batch(&self.current_batch)?;
-
         // Generate filename with timestamp
         let filename = format!(
             "events-{}.parquet",
-            chrono::Utc::now().format("%Y%m%d-%H%M%S")
+            chrono::Utc::now().format("%Y%m%d-%H%M%S-%f")
         );
         let file_path = self.storage_dir.join(filename);
 
-        // Write to Parquet file
-        let file = File::create(&file_path).map_err(|e| {
-            AllSourceError::StorageError(format!("Failed to create parquet file: {}", e))
-        })?;
-
-        let props = WriterProperties::builder()
-            .set_compression(parquet::basic::Compression::SNAPPY)
-            .build();
-
-        let mut writer = ArrowWriter::try_new(file, self.schema.clone(), Some(props))?;
-
-        writer.write(&record_batch)?;
-        writer.close()?;
+        self.write_file(&file_path, &self.current_batch)?;
 
         tracing::info!(
             "Successfully wrote {} events to {}",
@@ -116,6 +294,61 @@ impl ParquetStorage {
         Ok(())
     }
 
+    /// Write `events` to a Parquet file at `file_path`, replacing it
+    /// atomically if it exists
+    pub(crate) fn write_file(&self, file_path: &Path, events: &[Event]) -> Result<()> {
+        let props = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+
+        self.write_with_properties(file_path, events, props, false)
+    }
+
+    /// Write `events`, ordered by `segment_sort_key`, as a segment laid out
+    /// per `layout`
+    pub(crate) fn write_segment(
+        &self,
+        file_path: &Path,
+        events: &[Event],
+        layout: &SegmentLayout,
+    ) -> Result<()> {
+        let props = layout.writer_properties(events.len())?;
+        self.write_with_properties(file_path, events, props, true)
+    }
+
+    fn write_with_properties(
+        &self,
+        file_path: &Path,
+        events: &[Event],
+        props: WriterProperties,
+        sorted: bool,
+    ) -> Result<()> {
+        let record_batch = self.events_to_record_batch(events)?;
+
+        object_store::write_atomically(file_path, |file| {
+            let mut writer = ArrowWriter::try_new(file, self.schema.clone(), Some(props))
+                .map_err(std::io::Error::other)?;
+            writer.write(&record_batch).map_err(std::io::Error::other)?;
+            writer.close().map_err(std::io::Error::other).map(|_| ())
+        })?;
+
+        // Record the file's statistics for scans
+        let (Some(file_name), Some(dir)) = (
+            file_path.file_name().and_then(|name| name.to_str()),
+            file_path.parent(),
+        ) else {
+            return Ok(());
+        };
+        let size_bytes = fs::metadata(file_path).map(|m| m.len()).unwrap_or(0);
+        if let Some(stats) = SegmentStats::from_events(events, size_bytes, sorted) {
+            let mut manifest = SegmentManifest::load(dir);
+            manifest.files.insert(file_name.to_string(), stats);
+            manifest.save(dir)?;
+        }
+
+        Ok(())
+    }
+
     /// Convert events to Arrow RecordBatch
     fn events_to_record_batch(&self, events: &[Event]) -> Result<RecordBatch> {
         let mut event_id_builder = StringBuilder::new();
@@ -125,6 +358,7 @@ impl ParquetStorage {
         let mut timestamp_builder = TimestampMicrosecondBuilder::new();
         let mut metadata_builder = StringBuilder::new();
         let mut version_builder = UInt64Builder::new();
+        let mut tenant_id_builder = StringBuilder::new();
 
         for event in events {
             event_id_builder.append_value(event.id.to_string());
@@ -143,6 +377,7 @@ impl ParquetStorage {
             }
 
             version_builder.append_value(event.version as u64);
+            tenant_id_builder.append_value(event.tenant_id_str());
         }
 
         let arrays: Vec<ArrayRef> = vec![
@@ -153,6 +388,7 @@ impl ParquetStorage {
             Arc::new(timestamp_builder.finish()),
             Arc::new(metadata_builder.finish()),
             Arc::new(version_builder.finish()),
+            Arc::new(tenant_id_builder.finish()),
         ];
 
         let record_batch = RecordBatch::try_new(self.schema.clone(), arrays)?;
@@ -160,11 +396,57 @@ impl ParquetStorage {
         Ok(record_batch)
     }
 
-    /// Load events from all Parquet files
+    /// Delete every Parquet file and drop the unflushed batch
+    pub fn clear(&mut self) -> Result<()> {
+        self.current_batch.clear();
+
+        let entries = fs::read_dir(&self.storage_dir).map_err(|e| {
+            AllSourceError::StorageError(format!("Failed to read storage directory: {}", e))
+        })?;
+
+        for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+            if path.extension().and_then(|ext| ext.to_str()) == Some("parquet") {
+                fs::remove_file(&path).map_err(|e| {
+                    AllSourceError::StorageError(format!("Failed to remove parquet file: {}", e))
+                })?;
+            }
+        }
+
+        if let Some(ref store) = self.cold_storage {
+            for object in object_store::block_on(store.list(COLD_SEGMENT_PREFIX))? {
+                object_store::block_on(store.delete(&object.key))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Load events from all Parquet files, cold ones first, in timestamp
+    /// order
     pub fn load_all_events(&self) -> Result<Vec<Event>> {
-        let mut all_events = Vec::new();
+        let mut all_events = self.load_cold_events()?;
 
-        // Read all parquet files in storage directory
+        for file_path in self.local_files()? {
+            tracing::info!("Loading events from {}", file_path.display());
+            let file_events = self.load_events_from_file(&file_path)?;
+            all_events.extend(file_events);
+        }
+
+        // Compacted segments are sorted by entity rather than by time
+        all_events.sort_by_key(|event| event.timestamp);
+
+        tracing::info!("Loaded {} total events from storage", all_events.len());
+
+        Ok(all_events)
+    }
+
+    /// Statistics of the local Parquet files
+    pub fn manifest(&self) -> SegmentManifest {
+        SegmentManifest::load(&self.storage_dir)
+    }
+
+    /// Local Parquet files, sorted by name (which includes the timestamp)
+    fn local_files(&self) -> Result<Vec<PathBuf>> {
         let entries = fs::read_dir(&self.storage_dir).map_err(|e| {
             AllSourceError::StorageError(format!("Failed to read storage directory: {}", e))
         })?;
@@ -180,29 +462,47 @@ impl ParquetStorage {
             })
             .collect();
 
-        // Sort files by name (which includes timestamp)
         parquet_files.sort();
+        Ok(parquet_files)
+    }
 
-        for file_path in parquet_files {
-            tracing::info!("Loading events from {}", file_path.display());
-            let file_events = self.load_events_from_file(&file_path)?;
-            all_events.extend(file_events);
-        }
+    /// Load events from the files in cold storage, oldest first
+    fn load_cold_events(&self) -> Result<Vec<Event>> {
+        let Some(ref store) = self.cold_storage else {
+            return Ok(Vec::new());
+        };
 
-        tracing::info!("Loaded {} total events from storage", all_events.len());
+        // Downloads go to a subdirectory so they are never mistaken for
+        // local files
+        let cache_dir = self.storage_dir.join("cold-cache");
+        let mut events = Vec::new();
 
-        Ok(all_events)
+        for object in object_store::block_on(store.list(COLD_SEGMENT_PREFIX))? {
+            let Some(name) = object.key.strip_prefix(COLD_SEGMENT_PREFIX) else {
+                continue;
+            };
+            let file_path = cache_dir.join(name);
+
+            tracing::info!("Loading events from {} storage: {}", store.name(), object.key);
+            object_store::block_on(store.get_to_file(&object.key, &file_path))?;
+            let file_events = self.load_events_from_file(&file_path);
+            let _ = fs::remove_file(&file_path);
+            events.extend(file_events?);
+        }
+
+        Ok(events)
     }
 
     /// Load events from a single Parquet file
-    fn load_events_from_file(&self, file_path: &Path) -> Result<Vec<Event>> {
-        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
-
+    pub(crate) fn load_events_from_file(&self, file_path: &Path) -> Result<Vec<Event>> {
         let file = File::open(file_path).map_err(|e| {
             AllSourceError::StorageError(format!("Failed to open parquet file: {}", e))
         })?;
 
-        let builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
+        self.read_batches(ParquetRecordBatchReaderBuilder::try_new(file)?)
+    }
+
+    fn read_batches(&self, builder: ParquetRecordBatchReaderBuilder<File>) -> Result<Vec<Event>> {
         let mut reader = builder.build()?;
 
         let mut events = Vec::new();
@@ -261,6 +561,20 @@ impl ParquetStorage {
             .downcast_ref::<arrow::array::UInt64Array>()
             .ok_or_else(|| AllSourceError::StorageError("Invalid version column".to_string()))?;
 
+        // Files written before tenants were stored hold default-tenant events
+        let tenant_ids = match batch.schema().index_of("tenant_id") {
+            Ok(column) => Some(
+                batch
+                    .column(column)
+                    .as_any()
+                    .downcast_ref::<arrow::array::StringArray>()
+                    .ok_or_else(|| {
+                        AllSourceError::StorageError("Invalid tenant_id column".to_string())
+                    })?,
+            ),
+            Err(_) => None,
+        };
+
         let mut events = Vec::new();
 
         for i in 0..batch.num_rows() {
@@ -283,7 +597,10 @@ impl ParquetStorage {
                 id,
                 event_types.value(i).to_string(),
                 entity_ids.value(i).to_string(),
-                "default".to_string(), // Default tenant for backward compatibility
+                tenant_ids
+                    .filter(|tenant_ids| !tenant_ids.is_null(i))
+                    .map_or("default", |tenant_ids| tenant_ids.value(i))
+                    .to_string(),
                 serde_json::from_str(payloads.value(i))?,
                 timestamp,
                 metadata,
@@ -346,6 +663,9 @@ pub struct StorageStats {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use parquet::file::properties::ReaderProperties;
+    use parquet::file::reader::FileReader;
+    use parquet::file::serialized_reader::{ReadOptionsBuilder, SerializedFileReader};
     use serde_json::json;
     use tempfile::TempDir;
 
@@ -399,4 +719,90 @@ mod tests {
         assert_eq!(stats.total_files, 1);
         assert!(stats.total_size_bytes > 0);
     }
+
+    #[test]
+    fn test_tenant_survives_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut storage = ParquetStorage::new(temp_dir.path()).unwrap();
+
+        let event = Event::reconstruct_from_strings(
+            uuid::Uuid::new_v4(),
+            "test.event".to_string(),
+            "entity-1".to_string(),
+            "acme".to_string(),
+            json!({}),
+            chrono::Utc::now(),
+            None,
+            1,
+        );
+        storage.append_event(event).unwrap();
+        storage.append_event(create_test_event("entity-2")).unwrap();
+        storage.flush().unwrap();
+
+        let tenants: Vec<String> = storage
+            .load_all_events()
+            .unwrap()
+            .iter()
+            .map(|event| event.tenant_id_str().to_string())
+            .collect();
+        assert_eq!(tenants, vec!["acme", "default"]);
+    }
+
+    #[test]
+    fn test_sorted_segment_layout() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = ParquetStorage::new(temp_dir.path()).unwrap();
+
+        // Parquet keeps microseconds
+        let start = DateTime::from_timestamp_micros(Utc::now().timestamp_micros()).unwrap();
+        let mut events: Vec<Event> = (0..100)
+            .map(|i| {
+                Event::reconstruct_from_strings(
+                    uuid::Uuid::new_v4(),
+                    format!("type-{}", i % 3),
+                    format!("entity-{:02}", i % 10),
+                    "default".to_string(),
+                    json!({ "i": i }),
+                    start + chrono::Duration::seconds(i),
+                    None,
+                    1,
+                )
+            })
+            .collect();
+        events.sort_by(|a, b| segment_sort_key(a).cmp(&segment_sort_key(b)));
+
+        let layout = SegmentLayout {
+            row_group_size: 10,
+            ..Default::default()
+        };
+        let path = temp_dir.path().join("events-compacted-1.parquet");
+        storage.write_segment(&path, &events, &layout).unwrap();
+
+        let manifest = storage.manifest();
+        let stats = &manifest.files["events-compacted-1.parquet"];
+        assert!(stats.sorted);
+        assert_eq!(stats.events, 100);
+        assert_eq!(stats.min_entity_id, "entity-00");
+        assert_eq!(stats.max_entity_id, "entity-09");
+
+        // Each entity fills exactly one row group
+        let options = ReadOptionsBuilder::new()
+            .with_reader_properties(ReaderProperties::builder().set_read_bloom_filter(true).build())
+            .build();
+        let reader =
+            SerializedFileReader::new_with_options(File::open(&path).unwrap(), options).unwrap();
+        assert_eq!(reader.num_row_groups(), 10);
+        assert_eq!(reader.metadata().row_group(0).sorting_columns().unwrap().len(), 3);
+        let row_group = reader.get_row_group(3).unwrap();
+        let bloom = row_group.get_column_bloom_filter(ENTITY_ID_COLUMN).unwrap();
+        assert!(bloom.check(&"entity-03"));
+        assert!(!bloom.check(&"entity-04"));
+
+        // Loading puts events back in timestamp order
+        let loaded = storage.load_all_events().unwrap();
+        assert!(loaded.windows(2).all(|pair| pair[0].timestamp <= pair[1].timestamp));
+
+        fs::remove_file(&path).unwrap();
+        assert!(storage.manifest().files.is_empty());
+    }
 }
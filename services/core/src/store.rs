@@ -1,29 +1,58 @@
-use crate::compaction::{CompactionConfig, CompactionManager};
+use crate::auth::ApiKeyScopes;
+use crate::compaction::{CompactionConfig, CompactionManager, CompactionResult};
 use crate::domain::entities::Event;
+use crate::domain::repositories::{EventStreamQuery, EventStreamRepository};
+use crate::domain::value_objects::PartitionKey;
 use crate::error::{AllSourceError, Result};
 use crate::application::dto::QueryEventsRequest;
 use crate::index::{EventIndex, IndexEntry};
+use crate::infrastructure::cluster::replication::{ReplicationLog, ReplicationToken};
+use crate::infrastructure::persistence::sequence_journal::{RecordedSequences, SequenceJournal};
+use crate::infrastructure::persistence::staged_restore::StagedRestore;
+use crate::infrastructure::persistence::object_store::block_on;
+use crate::infrastructure::persistence::storage_engine::StorageEngineConfig;
 use crate::metrics::MetricsRegistry;
 use crate::pipeline::PipelineManager;
 use crate::projection::{
     EntitySnapshotProjection, EventCounterProjection, ProjectionManager,
 };
 use crate::replay::ReplayManager;
+use crate::retention::{RetentionConfig, RetentionPolicies, RetentionSweepResult};
 use crate::schema::{SchemaRegistry, SchemaRegistryConfig};
+use crate::security::payload_encryption::{PayloadEncryption, PayloadEncryptionConfig};
 use crate::snapshot::{SnapshotConfig, SnapshotManager, SnapshotType};
 use crate::storage::ParquetStorage;
 use crate::wal::{WALConfig, WriteAheadLog};
 use crate::websocket::WebSocketManager;
 use chrono::{DateTime, Utc};
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
+use std::collections::HashSet;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use uuid::Uuid;
+
+/// Sequence journal file name in the WAL or Parquet directory
+const SEQUENCE_JOURNAL_FILE: &str = "sequences.log";
 
 /// High-performance event store with columnar storage
 pub struct EventStore {
     /// In-memory event storage
     events: Arc<RwLock<Vec<Event>>>,
 
+    /// Sequence of each in-memory event; retention leaves gaps
+    sequences: Arc<RwLock<Vec<u64>>>,
+
+    /// Sequence of the most recently committed event
+    last_sequence: Arc<AtomicU64>,
+
+    /// Durable record of each event's sequence; without it the numbering
+    /// starts over on every load
+    journal: Option<Arc<SequenceJournal>>,
+
+    /// Staging for replacing the persisted events; see `replace_events`
+    restore_staging: Option<StagedRestore>,
+
     /// High-performance concurrent index
     index: Arc<EventIndex>,
 
@@ -57,8 +86,24 @@ pub struct EventStore {
     /// Prometheus metrics registry (v0.6 feature)
     metrics: Arc<MetricsRegistry>,
 
+    /// Field-level payload encryption for sensitive fields
+    payload_encryption: Arc<PayloadEncryption>,
+
     /// Total events ingested (for metrics)
     total_ingested: Arc<RwLock<u64>>,
+
+    /// Partition replication log when running as part of a cluster
+    replication: Option<Arc<ReplicationLog>>,
+
+    /// Stream repository holding the events, unless the native engine is used
+    engine: Option<Arc<dyn EventStreamRepository>>,
+
+    /// Held across an engine append and the commit that follows, one per
+    /// partition, so an entity's events reach memory in version order
+    append_locks: Vec<Mutex<()>>,
+
+    /// Rules for expiring old events
+    retention: Arc<RetentionPolicies>,
 }
 
 impl EventStore {
@@ -68,18 +113,113 @@ impl EventStore {
     }
 
     /// Create event store with custom configuration
+    ///
+    /// Panics if the storage engine cannot be opened or the store cannot be
+    /// initialized; the Postgres engine needs [`EventStore::open`].
     pub fn with_config(config: EventStoreConfig) -> Self {
+        let engine = config
+            .storage_engine
+            .open_sync()
+            .expect("Failed to open storage engine");
+        let loaded = match engine {
+            Some(ref engine) => {
+                block_on(engine.load_all_events()).expect("Failed to load events from storage engine")
+            }
+            None => Vec::new(),
+        };
+        Self::build(config, engine, loaded).expect("Failed to initialize event store")
+    }
+
+    /// Open the configured storage engine and load its events
+    pub async fn open(config: EventStoreConfig) -> Result<Self> {
+        let engine = config.storage_engine.open().await?;
+        let loaded = match engine {
+            Some(ref engine) => engine.load_all_events().await?,
+            None => Vec::new(),
+        };
+        Self::build(config, engine, loaded)
+    }
+
+    /// Create an event store on an already opened stream repository,
+    /// loading the events it holds
+    pub async fn with_engine(
+        config: EventStoreConfig,
+        engine: Arc<dyn EventStreamRepository>,
+    ) -> Result<Self> {
+        let loaded = engine.load_all_events().await?;
+        Self::build(config, Some(engine), loaded)
+    }
+
+    fn build(
+        mut config: EventStoreConfig,
+        engine: Option<Arc<dyn EventStreamRepository>>,
+        loaded: Vec<Event>,
+    ) -> Result<Self> {
+        // A stream repository is the system of record; the WAL and Parquet
+        // files would only duplicate it
+        if engine.is_some() && (config.storage_dir.is_some() || config.wal_dir.is_some()) {
+            tracing::warn!(
+                "⚠️  Storage engine '{}' holds all events; ignoring WAL and Parquet directories",
+                config.storage_engine.name()
+            );
+            config.storage_dir = None;
+            config.wal_dir = None;
+        }
+
         let mut projections = ProjectionManager::new();
 
         // Register built-in projections
         projections.register(Arc::new(EntitySnapshotProjection::new("entity_snapshots")));
         projections.register(Arc::new(EventCounterProjection::new("event_counters")));
 
+        // Open the object store that old Parquet files are moved to
+        let cold_storage = config
+            .compaction_config
+            .cold_storage
+            .as_ref()
+            .filter(|_| config.storage_dir.is_some())
+            .and_then(|cold| match cold.object_store.open() {
+                Ok(store) => {
+                    tracing::info!("✅ Cold storage tiering enabled: {}", cold.object_store.describe());
+                    Some(store)
+                }
+                Err(e) => {
+                    tracing::error!("❌ Failed to open cold storage: {}", e);
+                    None
+                }
+            });
+
+        // Sequences are journaled next to the events they number
+        let journal_path = config.sequence_journal.clone().or_else(|| {
+            config
+                .wal_dir
+                .as_ref()
+                .or(config.storage_dir.as_ref())
+                .map(|dir| dir.join(SEQUENCE_JOURNAL_FILE))
+        });
+
+        // Finish or discard a restore interrupted by a crash before
+        // anything reads the files
+        let restore_staging = journal_path.as_ref().map(|path| {
+            let staging = StagedRestore::new(config.storage_dir.as_deref(), config.wal_dir.as_deref(), path);
+            match cold_storage {
+                Some(ref store) => staging.with_cold_storage(Arc::clone(store)),
+                None => staging,
+            }
+        });
+        if let Some(ref staging) = restore_staging {
+            staging.recover()?;
+        }
+
         // Initialize persistent storage if configured
         let storage = config.storage_dir.as_ref().and_then(|dir| {
             match ParquetStorage::new(dir) {
                 Ok(storage) => {
                     tracing::info!("✅ Parquet persistence enabled at: {}", dir.display());
+                    let storage = match cold_storage {
+                        Some(ref store) => storage.with_cold_storage(Arc::clone(store)),
+                        None => storage,
+                    };
                     Some(Arc::new(RwLock::new(storage)))
                 }
                 Err(e) => {
@@ -103,10 +243,27 @@ impl EventStore {
             }
         });
 
+        let (journal, recorded) = match journal_path {
+            Some(path) => {
+                let (journal, recorded) = SequenceJournal::open(&path)?;
+                (Some(Arc::new(journal)), recorded)
+            }
+            None => (None, RecordedSequences::default()),
+        };
+
+        let retention = Arc::new(RetentionPolicies::new(config.retention.rules.clone())?);
+        if retention.is_enabled() {
+            tracing::info!("✅ Retention enabled with {} rule(s)", config.retention.rules.len());
+        }
+
         // Initialize compaction manager if Parquet storage is enabled (v0.2 feature)
         let compaction_manager = config.storage_dir.as_ref().map(|dir| {
-            let manager = CompactionManager::new(dir, config.compaction_config.clone());
-            Arc::new(manager)
+            let manager = CompactionManager::new(dir, config.compaction_config.clone())
+                .with_retention(Arc::clone(&retention));
+            Arc::new(match cold_storage {
+                Some(store) => manager.with_cold_storage(store),
+                None => manager,
+            })
         });
 
         // Initialize schema registry (v0.5 feature)
@@ -125,8 +282,21 @@ impl EventStore {
         let metrics = MetricsRegistry::new();
         tracing::info!("✅ Prometheus metrics registry initialized");
 
+        // Initialize payload encryption (sensitive fields are encrypted before WAL/Parquet)
+        let payload_encryption = Arc::new(
+            PayloadEncryption::new(config.payload_encryption_config.clone())?
+                .with_schema_registry(Arc::clone(&schema_registry)),
+        );
+        if payload_encryption.is_enabled() {
+            tracing::info!("✅ Payload field encryption enabled");
+        }
+
         let store = Self {
             events: Arc::new(RwLock::new(Vec::new())),
+            sequences: Arc::new(RwLock::new(Vec::new())),
+            last_sequence: Arc::new(AtomicU64::new(recorded.last_sequence)),
+            journal,
+            restore_staging,
             index: Arc::new(EventIndex::new()),
             projections: Arc::new(RwLock::new(projections)),
             storage,
@@ -138,14 +308,55 @@ impl EventStore {
             replay_manager,
             pipeline_manager,
             metrics,
+            payload_encryption,
             total_ingested: Arc::new(RwLock::new(0)),
+            replication: None,
+            engine,
+            append_locks: (0..PartitionKey::DEFAULT_PARTITION_COUNT).map(|_| Mutex::new(())).collect(),
+            retention,
         };
 
+        // Loaded events the journal has no sequence for yet
+        let mut unrecorded = Vec::new();
+
+        if store.engine.is_some() {
+            let mut loaded = loaded;
+            recorded.sort(&mut loaded);
+            tracing::info!(
+                "📂 Loading {} events from the {} storage engine...",
+                loaded.len(),
+                config.storage_engine.name()
+            );
+            for event in loaded {
+                let offset = store.events.read().len();
+                if let Err(e) = store.index.index_event(
+                    event.id,
+                    event.entity_id_str(),
+                    event.event_type_str(),
+                    event.timestamp,
+                    offset,
+                ) {
+                    tracing::error!("Failed to re-index event {}: {}", event.id, e);
+                }
+
+                if let Err(e) = store.projections.read().process_event(&event) {
+                    tracing::error!("Failed to re-process event {}: {}", event.id, e);
+                }
+
+                store.push_loaded(&mut store.events.write(), event, &recorded, &mut unrecorded);
+            }
+
+            let total = store.events.read().len();
+            *store.total_ingested.write() = total as u64;
+            tracing::info!("✅ Successfully loaded {} events from storage engine", total);
+        }
+
         // Recover from WAL first (most recent data)
         let mut wal_recovered = false;
         if let Some(ref wal) = store.wal {
             match wal.recover() {
-                Ok(recovered_events) if !recovered_events.is_empty() => {
+                Ok(mut recovered_events) if !recovered_events.is_empty() => {
+                    recorded.sort(&mut recovered_events);
                     tracing::info!("🔄 Recovering {} events from WAL...", recovered_events.len());
 
                     for event in recovered_events {
@@ -165,7 +376,7 @@ impl EventStore {
                             tracing::error!("Failed to re-process WAL event {}: {}", event.id, e);
                         }
 
-                        store.events.write().push(event);
+                        store.push_loaded(&mut store.events.write(), event, &recorded, &mut unrecorded);
                     }
 
                     let total = store.events.read().len();
@@ -199,7 +410,8 @@ impl EventStore {
         // (to avoid loading the same events twice after WAL checkpoint)
         if !wal_recovered {
             if let Some(ref storage) = store.storage {
-                if let Ok(persisted_events) = storage.read().load_all_events() {
+                if let Ok(mut persisted_events) = storage.read().load_all_events() {
+                    recorded.sort(&mut persisted_events);
                     tracing::info!("📂 Loading {} persisted events...", persisted_events.len());
 
                     for event in persisted_events {
@@ -220,7 +432,7 @@ impl EventStore {
                             tracing::error!("Failed to re-process event {}: {}", event.id, e);
                         }
 
-                        store.events.write().push(event);
+                        store.push_loaded(&mut store.events.write(), event, &recorded, &mut unrecorded);
                     }
 
                     let total = store.events.read().len();
@@ -230,11 +442,24 @@ impl EventStore {
             }
         }
 
-        store
+        if let Some(ref journal) = store.journal {
+            journal.record(&unrecorded)?;
+        }
+
+        Ok(store)
     }
 
     /// Ingest a new event into the store
     pub fn ingest(&self, event: Event) -> Result<()> {
+        self.ingest_tracked(event).map(|_| ())
+    }
+
+    /// Ingest an event, returning its position in the partition
+    /// replication log when this store is part of a cluster.
+    ///
+    /// Fails with `NotLeader` if another node leads the event's partition,
+    /// or `ReplicationError` while the partition is being handed off.
+    pub fn ingest_tracked(&self, mut event: Event) -> Result<Option<ReplicationToken>> {
         // Start metrics timer (v0.6 feature)
         let timer = self.metrics.ingestion_duration_seconds.start_timer();
 
@@ -247,19 +472,136 @@ impl EventStore {
             return Err(e);
         }
 
+        // Encrypt sensitive payload fields before anything is persisted
+        let event_type = event.event_type_str().to_string();
+        if let Err(e) = self
+            .payload_encryption
+            .encrypt_payload(&event_type, &mut event.payload)
+        {
+            self.metrics.ingestion_errors_total.inc();
+            timer.observe_duration();
+            return Err(e);
+        }
+
+        let token = self.write_prepared(event)?;
+
+        timer.observe_duration();
+
+        Ok(token)
+    }
+
+    /// Ingest an event restored from a backup. It was validated and
+    /// encrypted when first ingested, so it is committed as is.
+    pub fn ingest_restored(&self, event: Event) -> Result<()> {
+        self.write_prepared(event).map(|_| ())
+    }
+
+    /// Commit a validated, encrypted event, shipping it to followers when
+    /// clustered
+    fn write_prepared(&self, event: Event) -> Result<Option<ReplicationToken>> {
+        // In a cluster only the partition leader accepts writes
+        let write = match self
+            .replication
+            .as_ref()
+            .map(|log| log.begin_write(event.entity_id_str()))
+            .transpose()
+        {
+            Ok(write) => write,
+            Err(e) => {
+                self.metrics.ingestion_errors_total.inc();
+                return Err(e);
+            }
+        };
+
+        let event = self.commit_event(event, true)?;
+
+        // Ship the committed (already encrypted) event to followers
+        write.map(|write| write.append(event)).transpose()
+    }
+
+    /// Store an event received from its partition leader. The leader has
+    /// already validated and encrypted it; events held already are skipped.
+    pub(crate) fn commit_replicated(&self, event: Event) -> Result<()> {
+        if self.index.get_by_id(&event.id).is_some() {
+            return Ok(());
+        }
+        self.commit_event(event, true).map(|_| ())
+    }
+
+    /// Remove events received from a partition leader that turned out to
+    /// conflict with its log (see `ReplicationLog::apply_batch`). They
+    /// leave memory, the index, the WAL and the Parquet files; projections
+    /// keep their aggregated state, as with retention.
+    pub(crate) fn retract_replicated(&self, ids: &HashSet<Uuid>) -> Result<()> {
+        let mut log = self.events.write();
+        let removed: Vec<bool> = log.iter().map(|event| ids.contains(&event.id)).collect();
+        if removed.contains(&true) {
+            self.remove_events(&mut log, &removed)?;
+        }
+        if let (Some(storage), Some(compaction)) = (&self.storage, &self.compaction_manager) {
+            storage.write().flush()?;
+            compaction.remove_events(ids)?;
+        }
+        Ok(())
+    }
+
+    /// Publish an event another node already wrote to the shared storage
+    /// engine. Events held already are skipped.
+    #[cfg(feature = "postgres")]
+    pub(crate) fn commit_shared(&self, event: Event) -> Result<()> {
+        self.commit_event(event, false).map(|_| ())
+    }
+
+    /// Persist (unless `persist` is false), index and publish a prepared
+    /// event, returning it as stored
+    fn commit_event(&self, mut event: Event, persist: bool) -> Result<Event> {
         // Write to WAL FIRST for durability (v0.2 feature)
         // This ensures event is persisted before processing
-        if let Some(ref wal) = self.wal {
+        if let Some(wal) = self.wal.as_ref().filter(|_| persist) {
             if let Err(e) = wal.append(event.clone()) {
                 self.metrics.ingestion_errors_total.inc();
-                timer.observe_duration();
                 return Err(e);
             }
         }
 
+        // Append to the entity's stream before taking the write lock, so
+        // queries and other writes do not wait on the engine round trip
+        let _append_lock = match self.engine.as_ref().filter(|_| persist) {
+            Some(engine) => {
+                let partition = PartitionKey::from_entity_id(event.entity_id_str()).partition_id();
+                let lock = self.append_locks[partition as usize].lock();
+                match block_on(engine.append_event(event.clone())) {
+                    Ok(version) => event.version = version as i64,
+                    Err(e) => {
+                        self.metrics.ingestion_errors_total.inc();
+                        return Err(e);
+                    }
+                }
+                Some(lock)
+            }
+            None => None,
+        };
+
         let mut events = self.events.write();
         let offset = events.len();
 
+        // A shared event may be this node's own write arriving back from
+        // the engine, before or after this node commits it; check under the
+        // write lock so it is not indexed twice
+        if (!persist || self.engine.is_some()) && self.index.get_by_id(&event.id).is_some() {
+            return Ok(event);
+        }
+
+        // Journal the event's sequence before anything else sees it, so it
+        // keeps the number across restarts
+        let sequence = self.last_sequence.load(Ordering::SeqCst) + 1;
+        if let Some(ref journal) = self.journal {
+            if let Err(e) = journal.record(&[(event.id, sequence)]) {
+                self.metrics.ingestion_errors_total.inc();
+                return Err(e);
+            }
+        }
+
         // Index the event
         self.index.index_event(
             event.id,
@@ -291,13 +633,13 @@ impl EventStore {
         }
 
         // Persist to Parquet storage if enabled (v0.2)
-        if let Some(ref storage) = self.storage {
+        if let Some(storage) = self.storage.as_ref().filter(|_| persist) {
             let mut storage = storage.write();
             storage.append_event(event.clone())?;
         }
 
         // Store the event in memory
-        events.push(event.clone());
+        self.push_to_log(&mut events, event.clone(), sequence);
         let total_events = events.len();
         drop(events); // Release lock early
 
@@ -318,15 +660,53 @@ impl EventStore {
         let mut total = self.total_ingested.write();
         *total += 1;
 
-        timer.observe_duration();
-
         tracing::debug!(
             "Event ingested: {} (offset: {})",
             event.id,
             offset
         );
 
-        Ok(())
+        Ok(event)
+    }
+
+    /// Append `event` to the in-memory log under `sequence`. `log` is the
+    /// locked event log.
+    fn push_to_log(&self, log: &mut Vec<Event>, event: Event, sequence: u64) {
+        self.last_sequence.fetch_max(sequence, Ordering::SeqCst);
+        self.sequences.write().push(sequence);
+        log.push(event);
+    }
+
+    /// Append a loaded event under its recorded sequence. Events without
+    /// one are numbered after the last sequence and added to `unrecorded`.
+    fn push_loaded(
+        &self,
+        log: &mut Vec<Event>,
+        event: Event,
+        recorded: &RecordedSequences,
+        unrecorded: &mut Vec<(Uuid, u64)>,
+    ) {
+        let sequence = match recorded.sequences.get(&event.id) {
+            Some(&sequence) => sequence,
+            None => {
+                let sequence = self.last_sequence.load(Ordering::SeqCst) + 1;
+                unrecorded.push((event.id, sequence));
+                sequence
+            }
+        };
+        self.push_to_log(log, event, sequence);
+    }
+
+    /// Join a cluster: writes are accepted only for partitions this node
+    /// leads and are appended to the replication log
+    pub fn with_replication(mut self, log: Arc<ReplicationLog>) -> Self {
+        self.replication = Some(log);
+        self
+    }
+
+    /// Get the replication log, if clustered
+    pub fn replication_log(&self) -> Option<&Arc<ReplicationLog>> {
+        self.replication.as_ref()
     }
 
     /// Get the WebSocket manager for this store
@@ -364,6 +744,11 @@ impl EventStore {
         Arc::clone(&self.metrics)
     }
 
+    /// Get the payload encryption manager for this store
+    pub fn payload_encryption(&self) -> Arc<PayloadEncryption> {
+        Arc::clone(&self.payload_encryption)
+    }
+
     /// Manually flush any pending events to persistent storage
     pub fn flush_storage(&self) -> Result<()> {
         if let Some(ref storage) = self.storage {
@@ -385,6 +770,7 @@ impl EventStore {
             since: None,
             until: None,
             limit: None,
+            payload_contains: None,
         })?;
 
         if events.is_empty() {
@@ -461,6 +847,27 @@ impl EventStore {
 
     /// Query events based on filters (optimized with indices)
     pub fn query(&self, request: QueryEventsRequest) -> Result<Vec<Event>> {
+        self.query_with_scopes(request, None)
+    }
+
+    /// Query events, keeping only those visible to the given API key scopes
+    pub fn query_with_scopes(
+        &self,
+        request: QueryEventsRequest,
+        scopes: Option<&ApiKeyScopes>,
+    ) -> Result<Vec<Event>> {
+        self.query_scanned(request, scopes).map(|(events, _)| events)
+    }
+
+    /// Query events like `query_with_scopes`, also returning how many
+    /// events were scanned to answer it. Engines that index events
+    /// themselves (Postgres) answer it; otherwise it is answered from the
+    /// in-memory log and index, never from the Parquet files.
+    pub fn query_scanned(
+        &self,
+        request: QueryEventsRequest,
+        scopes: Option<&ApiKeyScopes>,
+    ) -> Result<(Vec<Event>, usize)> {
         // Determine query type for metrics (v0.6 feature)
         let query_type = if request.entity_id.is_some() {
             "entity"
@@ -480,6 +887,77 @@ impl EventStore {
             .with_label_values(&[query_type])
             .inc();
 
+        let payload_contains = request
+            .payload_contains
+            .as_deref()
+            .map(serde_json::from_str::<serde_json::Value>)
+            .transpose()
+            .map_err(|e| AllSourceError::ValidationError(format!("payload_contains is not valid JSON: {}", e)))?;
+
+        let (mut results, scanned) = match self.query_engine(&request, &payload_contains, scopes.is_none())? {
+            Some(events) => {
+                let scanned = events.len();
+                let results: Vec<Event> = events
+                    .into_iter()
+                    .filter(|event| scopes.is_none_or(|scopes| scopes.allows_event(event)))
+                    .collect();
+                (results, scanned)
+            }
+            None => self.query_memory(&request, &payload_contains, scopes),
+        };
+
+        // Sort by timestamp (ascending)
+        results.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+
+        // Apply limit
+        if let Some(limit) = request.limit {
+            results.truncate(limit);
+        }
+
+        // Record query results count (v0.6 feature)
+        self.metrics.query_results_total
+            .with_label_values(&[query_type])
+            .inc_by(results.len() as u64);
+
+        timer.observe_duration();
+
+        Ok((results, scanned))
+    }
+
+    /// Answer a query in the storage engine, if it indexes events itself.
+    /// The limit is left to the caller when results are filtered further.
+    fn query_engine(
+        &self,
+        request: &QueryEventsRequest,
+        payload_contains: &Option<serde_json::Value>,
+        apply_limit: bool,
+    ) -> Result<Option<Vec<Event>>> {
+        let Some(engine) = self.engine.as_ref() else {
+            return Ok(None);
+        };
+        let until = match (request.until, request.as_of) {
+            (Some(until), Some(as_of)) => Some(until.min(as_of)),
+            (until, as_of) => until.or(as_of),
+        };
+        block_on(engine.query_events(&EventStreamQuery {
+            entity_id: request.entity_id.clone(),
+            event_type: request.event_type.clone(),
+            tenant_id: request.tenant_id.clone(),
+            since: request.since,
+            until,
+            payload_contains: payload_contains.clone(),
+            limit: request.limit.filter(|_| apply_limit),
+        }))
+    }
+
+    /// Answer a query from the in-memory log and index, returning the
+    /// matching events and how many were scanned
+    fn query_memory(
+        &self,
+        request: &QueryEventsRequest,
+        payload_contains: &Option<serde_json::Value>,
+        scopes: Option<&ApiKeyScopes>,
+    ) -> (Vec<Event>, usize) {
         let events = self.events.read();
 
         // Use index for fast lookups
@@ -487,13 +965,13 @@ impl EventStore {
             // Use entity index
             self.index
                 .get_by_entity(entity_id)
-                .map(|entries| self.filter_entries(entries, &request))
+                .map(|entries| self.filter_entries(entries, request))
                 .unwrap_or_default()
         } else if let Some(event_type) = &request.event_type {
             // Use type index
             self.index
                 .get_by_type(event_type)
-                .map(|entries| self.filter_entries(entries, &request))
+                .map(|entries| self.filter_entries(entries, request))
                 .unwrap_or_default()
         } else {
             // Full scan (less efficient but necessary for complex queries)
@@ -501,28 +979,20 @@ impl EventStore {
         };
 
         // Fetch events and apply remaining filters
-        let mut results: Vec<Event> = offsets
+        let results: Vec<Event> = offsets
             .iter()
-            .filter_map(|&offset| events.get(offset).cloned())
-            .filter(|event| self.apply_filters(event, &request))
+            .filter_map(|&offset| events.get(offset))
+            .filter(|event| self.apply_filters(event, request))
+            .filter(|event| {
+                payload_contains
+                    .as_ref()
+                    .is_none_or(|expected| json_contains(&event.payload, expected))
+            })
+            .filter(|event| scopes.is_none_or(|scopes| scopes.allows_event(event)))
+            .cloned()
             .collect();
 
-        // Sort by timestamp (ascending)
-        results.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
-
-        // Apply limit
-        if let Some(limit) = request.limit {
-            results.truncate(limit);
-        }
-
-        // Record query results count (v0.6 feature)
-        self.metrics.query_results_total
-            .with_label_values(&[query_type])
-            .inc_by(results.len() as u64);
-
-        timer.observe_duration();
-
-        Ok(results)
+        (results, offsets.len())
     }
 
     /// Filter index entries based on query parameters
@@ -610,6 +1080,7 @@ impl EventStore {
             since: since_timestamp,
             until: None,
             limit: None,
+            payload_contains: None,
         })?;
 
         // If no events and no snapshot, entity not found
@@ -666,6 +1137,291 @@ impl EventStore {
         Err(AllSourceError::EntityNotFound(entity_id.to_string()))
     }
 
+    /// Ingested events still waiting to be flushed to Parquet storage
+    pub fn ingest_backlog(&self) -> usize {
+        self.storage
+            .as_ref()
+            .map(|storage| storage.read().pending_events())
+            .unwrap_or(0)
+    }
+
+    /// Sequence of the most recently committed event.
+    ///
+    /// Events are numbered in commit order, starting at 1. Unlike the WAL's
+    /// own numbering this survives WAL checkpoints, so backups use it to
+    /// know where they left off. Expired events leave gaps. With a sequence
+    /// journal the numbers also survive restarts; without one, events are
+    /// renumbered as they are loaded.
+    pub fn current_sequence(&self) -> u64 {
+        self.last_sequence.load(Ordering::SeqCst)
+    }
+
+    /// Events committed after `sequence`, oldest first
+    pub fn events_since(&self, sequence: u64) -> Vec<Event> {
+        self.events_between(sequence, u64::MAX)
+    }
+
+    /// Events with sequences in `after + 1..=through`, oldest first
+    pub fn events_between(&self, after: u64, through: u64) -> Vec<Event> {
+        let events = self.events.read();
+        let sequences = self.sequences.read();
+        let start = sequences.partition_point(|&sequence| sequence <= after);
+        let end = sequences.partition_point(|&sequence| sequence <= through);
+        events[start..end.max(start)].to_vec()
+    }
+
+    /// Whether any event belongs to `tenant_id`
+    pub fn holds_tenant_events(&self, tenant_id: &str) -> bool {
+        self.events
+            .read()
+            .iter()
+            .any(|event| event.tenant_id_str() == tenant_id)
+    }
+
+    /// Replace every event with `events`, e.g. when restoring a backup.
+    ///
+    /// Indexes, projections and snapshots are rebuilt, and the WAL and
+    /// Parquet files are rewritten to hold only the new events. The new
+    /// files are staged and swapped in only once complete (see
+    /// `StagedRestore`), so a failure or crash part way leaves either the
+    /// old or the new events in place. Ingestion waits until the
+    /// replacement is done. `progress` counts the events applied so far.
+    ///
+    /// Only the native engine can be replaced, and not while replicating:
+    /// stream repositories and followers cannot drop events.
+    pub fn replace_events(&self, events: Vec<Event>, progress: &AtomicU64) -> Result<()> {
+        if self.engine.is_some() {
+            return Err(AllSourceError::ValidationError(
+                "Only the native storage engine can be replaced; restore into a new tenant instead"
+                    .to_string(),
+            ));
+        }
+        if self.replication.is_some() {
+            return Err(AllSourceError::ValidationError(
+                "A replicated store cannot be replaced; restore into a new tenant instead".to_string(),
+            ));
+        }
+
+        let mut log = self.events.write();
+        tracing::info!("♻️  Replacing {} events with {} restored events", log.len(), events.len());
+
+        if let Some(ref staging) = self.restore_staging {
+            if let Err(e) = self.stage_events(staging, &events, progress) {
+                staging.discard();
+                return Err(e);
+            }
+            staging.commit()?;
+            staging.swap()?;
+            if let Some(ref wal) = self.wal {
+                wal.reopen()?;
+            }
+            if let Some(ref journal) = self.journal {
+                journal.reopen()?;
+            }
+        }
+
+        // Restored events are numbered afresh, as in the staged journal
+        log.clear();
+        self.sequences.write().clear();
+        self.last_sequence.store(0, Ordering::SeqCst);
+        self.index.clear();
+        self.projections.read().clear_all();
+        self.snapshot_manager.clear_all();
+
+        for event in events {
+            self.index.index_event(
+                event.id,
+                event.entity_id_str(),
+                event.event_type_str(),
+                event.timestamp,
+                log.len(),
+            )?;
+
+            if let Err(e) = self.projections.read().process_event(&event) {
+                tracing::error!("Failed to process restored event {}: {}", event.id, e);
+            }
+
+            let sequence = self.last_sequence.load(Ordering::SeqCst) + 1;
+            self.push_to_log(&mut log, event, sequence);
+            if self.restore_staging.is_none() {
+                progress.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let total = log.len();
+        *self.total_ingested.write() = total as u64;
+        self.metrics.storage_events_total.set(total as i64);
+        tracing::info!("✅ Store replaced with {} restored events", total);
+
+        Ok(())
+    }
+
+    /// Write `events` to the staged WAL, Parquet files and sequence journal
+    fn stage_events(&self, staging: &StagedRestore, events: &[Event], progress: &AtomicU64) -> Result<()> {
+        // Unflushed events belong to the files being replaced
+        if let Some(ref storage) = self.storage {
+            storage.write().flush()?;
+        }
+        staging.prepare()?;
+
+        let mut parquet = staging.parquet_dir().map(ParquetStorage::new).transpose()?;
+        let wal = match (staging.wal_dir(), &self.wal) {
+            (Some(dir), Some(live)) => Some(WriteAheadLog::new(dir, live.config().clone())?),
+            _ => None,
+        };
+        for event in events {
+            if let Some(ref wal) = wal {
+                wal.append(event.clone())?;
+            }
+            if let Some(ref mut parquet) = parquet {
+                parquet.append_event(event.clone())?;
+            }
+            progress.fetch_add(1, Ordering::Relaxed);
+        }
+        if let Some(ref wal) = wal {
+            wal.flush()?;
+        }
+        if let Some(ref mut parquet) = parquet {
+            parquet.flush()?;
+        }
+
+        let (journal, _) = SequenceJournal::open(staging.journal_path())?;
+        journal.rewrite(
+            events.iter().map(|event| event.id).zip(1..),
+            events.len() as u64,
+        )
+    }
+
+    /// Remove the events the retention rules expire.
+    ///
+    /// Expired events leave memory, the index and the WAL, and the Parquet
+    /// files are pruned through the compaction manager. Snapshots of
+    /// entities that lost events are dropped, since they include them;
+    /// projections keep their aggregated state. Ingestion waits until the
+    /// sweep is done.
+    ///
+    /// Stream repositories cannot drop events, so only the native engine
+    /// can be swept.
+    pub fn apply_retention(&self) -> Result<RetentionSweepResult> {
+        if !self.retention.is_enabled() {
+            return Ok(RetentionSweepResult::default());
+        }
+        if self.engine.is_some() {
+            return Err(AllSourceError::ValidationError(
+                "Retention is only supported by the native storage engine".to_string(),
+            ));
+        }
+
+        let mut log = self.events.write();
+        let verdict = self.retention.evaluate(&log, Utc::now());
+        let mut result = RetentionSweepResult {
+            events_expired: verdict.events_expired,
+            events_held: verdict.events_held,
+            ..Default::default()
+        };
+
+        if verdict.events_expired > 0 {
+            (result.wal_entries_removed, result.snapshots_removed) =
+                self.remove_events(&mut log, &verdict.expired)?;
+        }
+
+        // Prune Parquet with everything flushed, so entity counts match
+        if let (Some(storage), Some(compaction)) = (&self.storage, &self.compaction_manager) {
+            storage.write().flush()?;
+            result.parquet_events_removed = compaction.apply_retention()?;
+        }
+        drop(log);
+
+        if result.events_expired > 0 {
+            tracing::info!(
+                "🧹 Expired {} events ({} on legal hold, {} snapshots dropped)",
+                result.events_expired,
+                result.events_held,
+                result.snapshots_removed
+            );
+        }
+
+        Ok(result)
+    }
+
+    /// Compact the Parquet files, then drop the events key-based compaction
+    /// removed from memory, the WAL and the index as well
+    pub fn compact(&self) -> Result<CompactionResult> {
+        let compaction = self.compaction_manager.as_ref().ok_or_else(|| {
+            AllSourceError::InternalError("Compaction not enabled (no Parquet storage)".to_string())
+        })?;
+
+        let mut log = self.events.write();
+        if let Some(ref storage) = self.storage {
+            storage.write().flush()?;
+        }
+        let (result, removed_ids) = compaction.compact_removing()?;
+
+        if !removed_ids.is_empty() {
+            let removed: Vec<bool> = log.iter().map(|event| removed_ids.contains(&event.id)).collect();
+            self.remove_events(&mut log, &removed)?;
+        }
+
+        Ok(result)
+    }
+
+    /// Remove the events at the `removed` positions of `log` from the WAL,
+    /// the index and the entities' snapshots. Returns the number of WAL
+    /// entries and snapshots removed.
+    fn remove_events(&self, log: &mut Vec<Event>, removed: &[bool]) -> Result<(usize, usize)> {
+        let mut removed_ids = HashSet::new();
+        let mut entities = HashSet::new();
+        for (event, _) in log.iter().zip(removed).filter(|(_, &removed)| removed) {
+            removed_ids.insert(event.id);
+            entities.insert(event.entity_id_str().to_string());
+        }
+
+        let mut wal_entries_removed = 0;
+        if let Some(ref wal) = self.wal {
+            wal_entries_removed = wal.retain(|event| !removed_ids.contains(&event.id))?;
+        }
+
+        let mut drop = removed.iter();
+        self.sequences.write().retain(|_| !drop.next().copied().unwrap_or(false));
+        let mut drop = removed.iter();
+        log.retain(|_| !drop.next().copied().unwrap_or(false));
+        self.rewrite_journal(log)?;
+
+        // Offsets have moved, so the index is rebuilt
+        self.index.clear();
+        for (offset, event) in log.iter().enumerate() {
+            self.index.index_event(
+                event.id,
+                event.entity_id_str(),
+                event.event_type_str(),
+                event.timestamp,
+                offset,
+            )?;
+        }
+
+        let mut snapshots_removed = 0;
+        for entity_id in &entities {
+            snapshots_removed += self.snapshot_manager.delete_snapshots(entity_id)?;
+        }
+
+        self.metrics.storage_events_total.set(log.len() as i64);
+
+        Ok((wal_entries_removed, snapshots_removed))
+    }
+
+    /// Rewrite the sequence journal to hold just the events in `log`, the
+    /// locked event log
+    fn rewrite_journal(&self, log: &[Event]) -> Result<()> {
+        let Some(ref journal) = self.journal else {
+            return Ok(());
+        };
+        let sequences = self.sequences.read();
+        journal.rewrite(
+            log.iter().map(|event| event.id).zip(sequences.iter().copied()),
+            self.current_sequence(),
+        )
+    }
+
     /// Get statistics about the event store
     pub fn stats(&self) -> StoreStats {
         let events = self.events.read();
@@ -700,6 +1456,20 @@ pub struct EventStoreConfig {
 
     /// Schema registry configuration (v0.5 feature)
     pub schema_registry_config: SchemaRegistryConfig,
+
+    /// Sensitive field encryption configuration
+    pub payload_encryption_config: PayloadEncryptionConfig,
+
+    /// Where events are kept; repository engines replace the WAL and Parquet
+    pub storage_engine: StorageEngineConfig,
+
+    /// Retention rules for expiring old events
+    pub retention: RetentionConfig,
+
+    /// File recording each event's sequence; defaults to `sequences.log`
+    /// in the WAL or Parquet directory. Storage engines need it set for
+    /// sequences to survive a restart.
+    pub sequence_journal: Option<PathBuf>,
 }
 
 impl Default for EventStoreConfig {
@@ -711,6 +1481,10 @@ impl Default for EventStoreConfig {
             wal_config: WALConfig::default(),
             compaction_config: CompactionConfig::default(),
             schema_registry_config: SchemaRegistryConfig::default(),
+            payload_encryption_config: PayloadEncryptionConfig::default(),
+            storage_engine: StorageEngineConfig::default(),
+            retention: RetentionConfig::default(),
+            sequence_journal: None,
         }
     }
 }
@@ -725,6 +1499,10 @@ impl EventStoreConfig {
             wal_config: WALConfig::default(),
             compaction_config: CompactionConfig::default(),
             schema_registry_config: SchemaRegistryConfig::default(),
+            payload_encryption_config: PayloadEncryptionConfig::default(),
+            storage_engine: StorageEngineConfig::default(),
+            retention: RetentionConfig::default(),
+            sequence_journal: None,
         }
     }
 
@@ -737,6 +1515,10 @@ impl EventStoreConfig {
             wal_config: WALConfig::default(),
             compaction_config: CompactionConfig::default(),
             schema_registry_config: SchemaRegistryConfig::default(),
+            payload_encryption_config: PayloadEncryptionConfig::default(),
+            storage_engine: StorageEngineConfig::default(),
+            retention: RetentionConfig::default(),
+            sequence_journal: None,
         }
     }
 
@@ -749,6 +1531,10 @@ impl EventStoreConfig {
             wal_config,
             compaction_config: CompactionConfig::default(),
             schema_registry_config: SchemaRegistryConfig::default(),
+            payload_encryption_config: PayloadEncryptionConfig::default(),
+            storage_engine: StorageEngineConfig::default(),
+            retention: RetentionConfig::default(),
+            sequence_journal: None,
         }
     }
 
@@ -761,6 +1547,10 @@ impl EventStoreConfig {
             wal_config: WALConfig::default(),
             compaction_config: CompactionConfig::default(),
             schema_registry_config: SchemaRegistryConfig::default(),
+            payload_encryption_config: PayloadEncryptionConfig::default(),
+            storage_engine: StorageEngineConfig::default(),
+            retention: RetentionConfig::default(),
+            sequence_journal: None,
         }
     }
 
@@ -779,6 +1569,10 @@ impl EventStoreConfig {
             wal_config,
             compaction_config,
             schema_registry_config: SchemaRegistryConfig::default(),
+            payload_encryption_config: PayloadEncryptionConfig::default(),
+            storage_engine: StorageEngineConfig::default(),
+            retention: RetentionConfig::default(),
+            sequence_journal: None,
         }
     }
 }
@@ -797,4 +1591,21 @@ impl Default for EventStore {
     }
 }
 
+/// Whether `value` contains `expected`, like Postgres JSONB `@>`: objects
+/// match if every expected key matches, arrays if every expected element
+/// is contained in some element, scalars if equal
+fn json_contains(value: &serde_json::Value, expected: &serde_json::Value) -> bool {
+    use serde_json::Value;
+
+    match (value, expected) {
+        (Value::Object(value), Value::Object(expected)) => expected
+            .iter()
+            .all(|(key, expected)| value.get(key).is_some_and(|value| json_contains(value, expected))),
+        (Value::Array(value), Value::Array(expected)) => expected
+            .iter()
+            .all(|expected| value.iter().any(|value| json_contains(value, expected))),
+        (value, expected) => value == expected,
+    }
+}
+
 // Tests for store are covered in integration tests
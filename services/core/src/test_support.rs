@@ -0,0 +1,28 @@
+//! Helpers shared by unit tests
+
+use crate::domain::entities::Event;
+use std::time::Duration;
+
+/// An event in the default tenant
+pub fn event(entity_id: &str, event_type: &str, payload: serde_json::Value) -> Event {
+    Event::from_strings(
+        event_type.to_string(),
+        entity_id.to_string(),
+        "default".to_string(),
+        payload,
+        None,
+    )
+    .unwrap()
+}
+
+/// Poll `condition` until it holds or `timeout` passes; returns whether it held
+pub async fn eventually(timeout: Duration, condition: impl Fn() -> bool) -> bool {
+    let deadline = tokio::time::Instant::now() + timeout;
+    while tokio::time::Instant::now() < deadline {
+        if condition() {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    condition()
+}
@@ -1,5 +1,6 @@
 use crate::error::{AllSourceError, Result};
 use crate::domain::entities::Event;
+use crate::infrastructure::persistence::object_store::write_atomically;
 use chrono::{DateTime, Utc};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
@@ -68,6 +69,10 @@ pub struct WALEntry {
     /// Sequence number for ordering
     pub sequence: u64,
 
+    /// Term of the partition leader that wrote the entry, when replicated
+    #[serde(default)]
+    pub term: u64,
+
     /// Timestamp when written to WAL
     pub wal_timestamp: DateTime<Utc>,
 
@@ -82,6 +87,7 @@ impl WALEntry {
     pub fn new(sequence: u64, event: Event) -> Self {
         let mut entry = Self {
             sequence,
+            term: 0,
             wal_timestamp: Utc::now(),
             event,
             checksum: 0,
@@ -297,64 +303,79 @@ impl WriteAheadLog {
         Ok(wal_files)
     }
 
-    /// Recover events from WAL files
-    pub fn recover(&self) -> Result<Vec<Event>> {
-        tracing::info!("🔄 Starting WAL recovery...");
+    /// Read the valid entries of a WAL file, returning them with the
+    /// number of corrupted entries skipped
+    fn read_entries(wal_file_path: &Path) -> Result<(Vec<WALEntry>, usize)> {
+        tracing::debug!("Reading WAL file: {:?}", wal_file_path);
 
-        let mut wal_files = self.list_wal_files()?;
-        wal_files.sort();
+        let file = File::open(wal_file_path).map_err(|e| {
+            AllSourceError::StorageError(format!("Failed to open WAL file for recovery: {}", e))
+        })?;
 
-        let mut recovered_events = Vec::new();
-        let mut max_sequence = 0u64;
+        let reader = BufReader::new(file);
+        let mut entries = Vec::new();
         let mut corrupted_entries = 0;
 
-        for wal_file_path in &wal_files {
-            tracing::debug!("Reading WAL file: {:?}", wal_file_path);
-
-            let file = File::open(wal_file_path).map_err(|e| {
-                AllSourceError::StorageError(format!("Failed to open WAL file for recovery: {}", e))
+        for (line_num, line) in reader.lines().enumerate() {
+            let line = line.map_err(|e| {
+                AllSourceError::StorageError(format!("Failed to read WAL line: {}", e))
             })?;
 
-            let reader = BufReader::new(file);
-
-            for (line_num, line) in reader.lines().enumerate() {
-                let line = line.map_err(|e| {
-                    AllSourceError::StorageError(format!("Failed to read WAL line: {}", e))
-                })?;
-
-                if line.trim().is_empty() {
-                    continue;
-                }
+            if line.trim().is_empty() {
+                continue;
+            }
 
-                match serde_json::from_str::<WALEntry>(&line) {
-                    Ok(entry) => {
-                        // Verify checksum
-                        if !entry.verify() {
-                            tracing::warn!(
-                                "Corrupted WAL entry at {:?}:{} (checksum mismatch)",
-                                wal_file_path,
-                                line_num + 1
-                            );
-                            corrupted_entries += 1;
-                            continue;
-                        }
-
-                        max_sequence = max_sequence.max(entry.sequence);
-                        recovered_events.push(entry.event);
-                    }
-                    Err(e) => {
+            match serde_json::from_str::<WALEntry>(&line) {
+                Ok(entry) => {
+                    // Verify checksum
+                    if !entry.verify() {
                         tracing::warn!(
-                            "Failed to parse WAL entry at {:?}:{}: {}",
+                            "Corrupted WAL entry at {:?}:{} (checksum mismatch)",
                             wal_file_path,
-                            line_num + 1,
-                            e
+                            line_num + 1
                         );
                         corrupted_entries += 1;
+                        continue;
                     }
+
+                    entries.push(entry);
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to parse WAL entry at {:?}:{}: {}",
+                        wal_file_path,
+                        line_num + 1,
+                        e
+                    );
+                    corrupted_entries += 1;
                 }
             }
         }
 
+        Ok((entries, corrupted_entries))
+    }
+
+    /// Recover events from WAL files
+    pub fn recover(&self) -> Result<Vec<Event>> {
+        tracing::info!("🔄 Starting WAL recovery...");
+
+        let mut wal_files = self.list_wal_files()?;
+        wal_files.sort();
+
+        let mut recovered_events = Vec::new();
+        let mut max_sequence = 0u64;
+        let mut corrupted_entries = 0;
+
+        for wal_file_path in &wal_files {
+            let (entries, corrupted) = Self::read_entries(wal_file_path)?;
+            corrupted_entries += corrupted;
+
+            for entry in entries {
+                max_sequence = max_sequence.max(entry.sequence);
+                recovered_events.push(entry.event);
+            }
+        }
+
         // Update sequence counter
         let mut seq = self.sequence.write();
         *seq = max_sequence;
@@ -381,6 +402,47 @@ impl WriteAheadLog {
         Ok(())
     }
 
+    /// Drop the logged events `keep` rejects, e.g. when they expire.
+    ///
+    /// Each WAL file holding such events is rewritten atomically, keeping
+    /// the sequence numbers of the rest. Returns the number of entries
+    /// removed.
+    pub fn retain(&self, mut keep: impl FnMut(&Event) -> bool) -> Result<usize> {
+        // Hold the current file so nothing is appended while rewriting
+        let mut current = self.current_file.write();
+        current.flush()?;
+
+        let mut removed = 0;
+        for wal_file_path in self.list_wal_files()? {
+            let (entries, _) = Self::read_entries(&wal_file_path)?;
+            let total = entries.len();
+            let kept: Vec<WALEntry> = entries.into_iter().filter(|entry| keep(&entry.event)).collect();
+            if kept.len() == total {
+                continue;
+            }
+            removed += total - kept.len();
+
+            let mut lines = String::new();
+            for entry in &kept {
+                lines.push_str(&serde_json::to_string(entry)?);
+                lines.push('\n');
+            }
+            write_atomically(&wal_file_path, |file| file.write_all(lines.as_bytes()))?;
+
+            // The active file was replaced under its writer
+            if wal_file_path == current.path {
+                *current = WALFile::new(wal_file_path)?;
+            }
+        }
+
+        if removed > 0 {
+            self.stats.write().current_file_size = current.size;
+            tracing::info!("🧹 Removed {} entries from the WAL", removed);
+        }
+
+        Ok(removed)
+    }
+
     /// Truncate WAL after successful checkpoint
     pub fn truncate(&self) -> Result<()> {
         tracing::info!("🧹 Truncating WAL after checkpoint");
@@ -411,6 +473,32 @@ impl WriteAheadLog {
         Ok(())
     }
 
+    /// Append to the newest WAL file on disk, after the files were
+    /// replaced (see `StagedRestore`), continuing its sequence
+    pub fn reopen(&self) -> Result<()> {
+        let mut current = self.current_file.write();
+
+        let mut wal_files = self.list_wal_files()?;
+        wal_files.sort();
+        let mut max_sequence = 0;
+        for wal_file_path in &wal_files {
+            let (entries, _) = Self::read_entries(wal_file_path)?;
+            max_sequence = entries.iter().map(|entry| entry.sequence).fold(max_sequence, u64::max);
+        }
+
+        let path = wal_files
+            .pop()
+            .unwrap_or_else(|| Self::generate_wal_filename(&self.wal_dir, 0));
+        *current = WALFile::new(path)?;
+        self.stats.write().current_file_size = current.size;
+        *self.sequence.write() = max_sequence;
+        Ok(())
+    }
+
+    pub fn config(&self) -> &WALConfig {
+        &self.config
+    }
+
     /// Get WAL statistics
     pub fn stats(&self) -> WALStats {
         (*self.stats.read()).clone()
@@ -534,4 +622,33 @@ mod tests {
         let recovered = wal.recover().unwrap();
         assert_eq!(recovered.len(), 0);
     }
+
+    #[test]
+    fn test_wal_retain() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = WALConfig {
+            max_file_size: 1024, // Spread entries over several files
+            ..Default::default()
+        };
+        let wal = WriteAheadLog::new(temp_dir.path(), config.clone()).unwrap();
+
+        let events: Vec<Event> = (0..20).map(|_| create_test_event()).collect();
+        for event in &events {
+            wal.append(event.clone()).unwrap();
+        }
+
+        let expired: Vec<Uuid> = events.iter().step_by(2).map(|event| event.id).collect();
+        assert_eq!(wal.retain(|event| !expired.contains(&event.id)).unwrap(), 10);
+        assert_eq!(wal.retain(|_| true).unwrap(), 0);
+
+        // Appends go on after the kept entries
+        let event = create_test_event();
+        assert_eq!(wal.append(event.clone()).unwrap(), 21);
+        wal.flush().unwrap();
+
+        let recovered = WriteAheadLog::new(temp_dir.path(), config).unwrap().recover().unwrap();
+        let mut expected: Vec<Uuid> = events.iter().skip(1).step_by(2).map(|event| event.id).collect();
+        expected.push(event.id);
+        assert_eq!(recovered.iter().map(|event| event.id).collect::<Vec<_>>(), expected);
+    }
 }
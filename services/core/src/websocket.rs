@@ -1,4 +1,6 @@
+use crate::auth::ApiKeyScopes;
 use crate::domain::entities::Event;
+use crate::security::payload_encryption::PayloadEncryption;
 use axum::extract::ws::{Message, WebSocket};
 use futures::{sink::SinkExt, stream::StreamExt};
 use parking_lot::RwLock;
@@ -44,8 +46,23 @@ impl WebSocketManager {
         let _ = self.event_tx.send(event);
     }
 
+    /// Receive every event broadcast from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<Event>> {
+        self.event_tx.subscribe()
+    }
+
     /// Handle a new WebSocket connection
-    pub async fn handle_socket(&self, socket: WebSocket) {
+    ///
+    /// Events outside `scopes` (the API key's scopes, if any) are never
+    /// sent, whatever filters the client sets. Sensitive payload fields are
+    /// decrypted only for `read_sensitive` clients and redacted otherwise.
+    pub async fn handle_socket(
+        &self,
+        socket: WebSocket,
+        scopes: Option<ApiKeyScopes>,
+        payload_encryption: Arc<PayloadEncryption>,
+        read_sensitive: bool,
+    ) {
         let client_id = Uuid::new_v4();
         tracing::info!("🔌 WebSocket client connected: {}", client_id);
 
@@ -77,6 +94,10 @@ impl WebSocketManager {
                         .unwrap_or_default()
                 };
 
+                if !scopes.as_ref().is_none_or(|scopes| scopes.allows_event(&event)) {
+                    continue;
+                }
+
                 // Apply filters
                 if let Some(ref entity_id) = filters.entity_id {
                     if event.entity_id_str() != entity_id {
@@ -90,8 +111,14 @@ impl WebSocketManager {
                     }
                 }
 
+                let mut event = (*event).clone();
+                if let Err(e) = payload_encryption.reveal(&mut event.payload, read_sensitive) {
+                    tracing::error!("Failed to reveal event {} payload: {}", event.id, e);
+                    continue;
+                }
+
                 // Serialize event to JSON
-                match serde_json::to_string(&*event) {
+                match serde_json::to_string(&event) {
                     Ok(json) => {
                         if sender.send(Message::Text(json)).await.is_err() {
                             tracing::warn!("Failed to send event to client {}", client_id);
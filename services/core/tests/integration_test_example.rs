@@ -55,7 +55,8 @@ fn test_complete_auth_flow() {
         "default".to_string(),
         Role::ServiceAccount,
         None,
-    );
+    )
+    .unwrap();
 
     assert!(key_string.starts_with("ask_"));
 
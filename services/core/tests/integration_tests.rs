@@ -47,6 +47,7 @@ fn test_full_lifecycle_in_memory() {
         since: None,
         until: None,
         limit: None,
+        payload_contains: None,
     };
 
     let events = store.query(query).unwrap();
@@ -157,6 +158,7 @@ fn test_wal_durability_and_recovery() {
             since: None,
             until: None,
             limit: None,
+            payload_contains: None,
         };
         let events = store.query(query).unwrap();
         assert_eq!(events.len(), 30, "Should have exactly 30 events for user-2");
@@ -273,6 +275,7 @@ fn test_multi_entity_queries() {
         since: None,
         until: None,
         limit: None,
+        payload_contains: None,
     };
     let events = store.query(query).unwrap();
     assert_eq!(events.len(), 10);
@@ -285,6 +288,7 @@ fn test_multi_entity_queries() {
         since: None,
         until: None,
         limit: None,
+        payload_contains: None,
     };
     let events = store.query(query).unwrap();
     assert_eq!(events.len(), 50); // 5 users * 10 events
@@ -423,6 +427,7 @@ fn test_event_stream_ordering() {
         since: None,
         until: None,
         limit: None,
+        payload_contains: None,
     };
 
     let events = store.query(query).unwrap();
@@ -510,6 +515,7 @@ fn test_entity_not_found_error() {
         since: None,
         until: None,
         limit: None,
+        payload_contains: None,
     };
     let events = store.query(query).unwrap();
     assert_eq!(events.len(), 0);
@@ -623,6 +629,7 @@ fn test_metadata_preservation() {
         since: None,
         until: None,
         limit: None,
+        payload_contains: None,
     };
 
     let events = store.query(query).unwrap();